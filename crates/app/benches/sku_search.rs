@@ -0,0 +1,167 @@
+//! Compares the admin SKU search before and after the trigram indexes and UNION split on 10k SKUs.
+//!
+//! Runs against the Postgres at `DATABASE_URL` (e.g. `docker compose up -d db`) inside a throwaway
+//! database that is created, migrated and dropped by the bench:
+//!
+//! ```sh
+//! DATABASE_URL=postgres://... cargo bench -p rs-ecommerce --bench sku_search
+//! ```
+
+use std::str::FromStr;
+
+use criterion::Criterion;
+use sqlx::{
+    PgPool, Row,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+
+const PRODUCTS: i32 = 1_000;
+const SKUS_PER_PRODUCT: i32 = 10;
+const PATTERN: &str = "%0042%";
+
+// The single `OR` search that `list_skus_admin` ran before the trigram migration.
+const OR_ILIKE_SQL: &str = r#"
+    SELECT v.id::text as id, v.sku, v.jan_code, v.product_id::text as product_id, p.title as product_title,
+           v.fulfillment_type, v.price_amount, v.price_currency, v.status
+    FROM product_skus v
+    JOIN products p ON p.id = v.product_id
+    WHERE p.store_id = $1
+      AND ($2 = '' OR v.sku ILIKE $2 OR p.title ILIKE $2)
+    ORDER BY v.created_at DESC
+    LIMIT 50
+"#;
+
+// The search branch of `list_skus_admin` today, on the first page.
+const UNION_SQL: &str = r#"
+    WITH matched AS (
+        SELECT v.id
+        FROM product_skus v
+        JOIN products p ON p.id = v.product_id
+        WHERE p.store_id = $1 AND v.sku ILIKE $2
+        UNION
+        SELECT v.id
+        FROM product_skus v
+        JOIN products p ON p.id = v.product_id
+        WHERE p.store_id = $1 AND p.title ILIKE $2
+    )
+    SELECT v.id::text as id, v.id as cursor_id, v.sku, v.jan_code, v.product_id::text as product_id,
+           p.title as product_title, v.fulfillment_type, v.price_amount, v.price_currency,
+           v.status, v.created_at
+    FROM matched m
+    JOIN product_skus v ON v.id = m.id
+    JOIN products p ON p.id = v.product_id
+    ORDER BY v.created_at DESC, v.id DESC
+    LIMIT 51
+"#;
+
+fn main() {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required to run the SKU search bench");
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let database = format!("sku_search_bench_{}", uuid::Uuid::new_v4().simple());
+    let admin = runtime
+        .block_on(PgPool::connect(&database_url))
+        .expect("connect to DATABASE_URL");
+    runtime
+        .block_on(sqlx::query(&format!("CREATE DATABASE {database}")).execute(&admin))
+        .expect("create bench database");
+
+    let options = PgConnectOptions::from_str(&database_url)
+        .expect("parse DATABASE_URL")
+        .database(&database);
+    let db = runtime
+        .block_on(PgPoolOptions::new().max_connections(1).connect_with(options))
+        .expect("connect to bench database");
+    let store_id = runtime.block_on(seed(&db));
+
+    let mut criterion = Criterion::default().configure_from_args();
+    for (name, sql) in [("or_ilike", OR_ILIKE_SQL), ("union_trigram", UNION_SQL)] {
+        println!("{name}:\n{}", runtime.block_on(explain(&db, sql, store_id)));
+    }
+    let mut group = criterion.benchmark_group("list_skus_admin_search");
+    let pool = &db;
+    for (name, sql) in [("or_ilike", OR_ILIKE_SQL), ("union_trigram", UNION_SQL)] {
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async move {
+                sqlx::query(sql)
+                    .bind(store_id)
+                    .bind(PATTERN)
+                    .fetch_all(pool)
+                    .await
+                    .expect("search skus")
+            })
+        });
+    }
+    group.finish();
+    criterion.final_summary();
+
+    runtime.block_on(db.close());
+    runtime
+        .block_on(sqlx::query(&format!("DROP DATABASE {database}")).execute(&admin))
+        .expect("drop bench database");
+}
+
+/// Migrates the bench database and loads one store with `PRODUCTS * SKUS_PER_PRODUCT` SKUs.
+async fn seed(db: &PgPool) -> uuid::Uuid {
+    sqlx::migrate!("../../migrations")
+        .run(db)
+        .await
+        .expect("run migrations");
+    let tenant_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO tenants (name, type, default_currency, status)
+        VALUES ('Bench tenant', 'single_brand', 'JPY', 'active')
+        RETURNING id
+        "#,
+    )
+    .fetch_one(db)
+    .await
+    .expect("insert tenant");
+    let store_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO stores (tenant_id, name) VALUES ($1, 'Bench store') RETURNING id")
+            .bind(tenant_id)
+            .fetch_one(db)
+            .await
+            .expect("insert store");
+    sqlx::query(
+        r#"
+        INSERT INTO products (tenant_id, store_id, title, description, status)
+        SELECT $1, $2, 'Product ' || lpad(n::text, 4, '0'), '', 'active'
+        FROM generate_series(1, $3) n
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(store_id)
+    .bind(PRODUCTS)
+    .execute(db)
+    .await
+    .expect("insert products");
+    sqlx::query(
+        r#"
+        INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+        SELECT p.id, 'SKU-' || upper(substr(md5(p.id::text || n::text), 1, 10)), 1000, 'JPY', 'active'
+        FROM products p, generate_series(1, $1) n
+        "#,
+    )
+    .bind(SKUS_PER_PRODUCT)
+    .execute(db)
+    .await
+    .expect("insert skus");
+    sqlx::query("ANALYZE products, product_skus")
+        .execute(db)
+        .await
+        .expect("analyze");
+    store_id
+}
+
+async fn explain(db: &PgPool, sql: &str, store_id: uuid::Uuid) -> String {
+    let rows = sqlx::query(&format!("EXPLAIN (ANALYZE, COSTS OFF) {sql}"))
+        .bind(store_id)
+        .bind(PATTERN)
+        .fetch_all(db)
+        .await
+        .expect("explain search");
+    rows.iter()
+        .map(|row| row.get::<String, _>(0))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -5,17 +5,20 @@ use sqlx::{Postgres, Row, Transaction};
 use crate::{
     AppState,
     auction::status::{AuctionStatus, AuctionType, AutoBidStatus},
-    infrastructure::audit,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::{
+        json::{ConnectError, ErrorCode},
+        request_context,
+    },
     shared::{
         audit_action::AuctionAuditAction,
         audit_helpers::{audit_input, to_json_opt},
         ids::{StoreId, parse_uuid},
         money::{money_from_parts, money_to_parts, money_to_parts_opt},
+        pagination::{MAX_PAGE_SIZE, encode_page_token, page_params},
+        store_context::resolve_store_context,
         time::chrono_to_timestamp,
     },
-    store_settings::service::resolve_store_context,
 };
 
 
@@ -38,10 +41,7 @@ pub async fn create_auction(
     if req.sku_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id is required")),
         ));
     }
 
@@ -50,10 +50,8 @@ pub async fn create_auction(
         return Err(invalid_arg("title is required"));
     }
 
-    let start_at =
-        crate::shared::time::timestamp_to_chrono(req.start_at).ok_or_else(|| invalid_arg("start_at is required"))?;
-    let end_at =
-        crate::shared::time::timestamp_to_chrono(req.end_at).ok_or_else(|| invalid_arg("end_at is required"))?;
+    let start_at = crate::shared::time::timestamp_to_chrono_required(req.start_at, "start_at")?;
+    let end_at = crate::shared::time::timestamp_to_chrono_required(req.end_at, "end_at")?;
     if end_at <= start_at {
         return Err(invalid_arg("end_at must be after start_at"));
     }
@@ -186,19 +184,21 @@ pub async fn create_auction(
         description: req.description,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::Create.into(),
-            Some("auction"),
-            Some(auction.id.clone()),
-            None,
-            to_json_opt(Some(auction.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::Create,
+                Some("auction"),
+                Some(auction.id.clone()),
+                None,
+                to_json_opt(Some(auction.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(db_error)?;
 
@@ -222,10 +222,8 @@ pub async fn update_auction(
         return Err(invalid_arg("title is required"));
     }
 
-    let start_at =
-        crate::shared::time::timestamp_to_chrono(req.start_at).ok_or_else(|| invalid_arg("start_at is required"))?;
-    let end_at =
-        crate::shared::time::timestamp_to_chrono(req.end_at).ok_or_else(|| invalid_arg("end_at is required"))?;
+    let start_at = crate::shared::time::timestamp_to_chrono_required(req.start_at, "start_at")?;
+    let end_at = crate::shared::time::timestamp_to_chrono_required(req.end_at, "end_at")?;
     if end_at <= start_at {
         return Err(invalid_arg("end_at must be after start_at"));
     }
@@ -357,6 +355,10 @@ pub async fn update_auction(
     .execute(tx.as_mut())
     .await
     .map_err(db_error)?;
+    // Only drafts reach this point; an auction saved back as draft must not carry live auto-bids.
+    if status == AuctionStatus::Draft {
+        disable_auto_bids_tx(&mut tx, auction_uuid).await?;
+    }
 
     let updated_row = sqlx::query("SELECT * FROM auctions WHERE id = $1")
         .bind(auction_uuid)
@@ -365,19 +367,21 @@ pub async fn update_auction(
         .map_err(db_error)?;
     let auction = auction_from_row(&updated_row);
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::Update.into(),
-            Some("auction"),
-            Some(req.auction_id),
-            None,
-            to_json_opt(Some(auction.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::Update,
+                Some("auction"),
+                Some(req.auction_id),
+                None,
+                to_json_opt(Some(auction.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(db_error)?;
 
@@ -391,61 +395,43 @@ pub async fn list_auctions(
     page: Option<pb::PageInfo>,
 ) -> Result<(Vec<pb::Auction>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
     let store_uuid = StoreId::parse(&store_id)?;
-    let (limit, offset) = page_params(page);
-    let rows = if status.is_empty() {
-        sqlx::query(
-            r#"
-            SELECT *
-            FROM auctions
-            WHERE store_id = $1
-            ORDER BY created_at DESC
-            LIMIT $2 OFFSET $3
-            "#,
-        )
-        .bind(store_uuid.as_uuid())
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await
-        .map_err(db_error)?
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
+    let status = if status.is_empty() {
+        None
     } else {
-        let status = AuctionStatus::try_from(status.as_str()).map_err(invalid_arg)?;
-        sqlx::query(
-            r#"
-            SELECT *
-            FROM auctions
-            WHERE store_id = $1 AND status = $2
-            ORDER BY created_at DESC
-            LIMIT $3 OFFSET $4
-            "#,
-        )
-        .bind(store_uuid.as_uuid())
-        .bind(status.as_str())
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await
-        .map_err(db_error)?
+        Some(AuctionStatus::try_from(status.as_str()).map_err(invalid_arg)?)
     };
+    let mut rows = sqlx::query(
+        r#"
+        SELECT *
+        FROM auctions
+        WHERE store_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(store_uuid.as_uuid())
+    .bind(status.map(|status| status.as_str()))
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
 
-    let auctions: Vec<pb::Auction> = rows.into_iter().map(|row| auction_from_row(&row)).collect();
     let mut next_page_token = String::new();
-    if (auctions.len() as i64) == limit {
-        next_page_token = (offset + limit).to_string();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("id"));
+        }
     }
+    let auctions = rows.into_iter().map(|row| auction_from_row(&row)).collect();
     Ok((auctions, pb::PageResult { next_page_token }))
 }
 
-fn page_params(page: Option<pb::PageInfo>) -> (i64, i64) {
-    let page = page.unwrap_or(pb::PageInfo {
-        page_size: 50,
-        page_token: String::new(),
-    });
-    let limit = (page.page_size.max(1).min(200)) as i64;
-    let offset = page.page_token.parse::<i64>().unwrap_or(0).max(0);
-    (limit, offset)
-}
-
 pub async fn get_auction(
     state: &AppState,
     store_id: String,
@@ -473,26 +459,40 @@ pub async fn list_bids(
     state: &AppState,
     store_id: String,
     auction_id: String,
-) -> Result<Vec<pb::AuctionBid>, (StatusCode, Json<ConnectError>)> {
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::AuctionBid>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
     let store_uuid = StoreId::parse(&store_id)?;
     let auction_uuid = parse_uuid(&auction_id, "auction_id")?;
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
 
-    let rows = sqlx::query(
+    let mut rows = sqlx::query(
         r#"
-        SELECT id::text as id, auction_id::text as auction_id, customer_id::text as customer_id,
-               amount, currency, created_at
+        SELECT id::text as id, id as cursor_id, auction_id::text as auction_id,
+               customer_id::text as customer_id, amount, currency, created_at
         FROM auction_bids
         WHERE auction_id = $1 AND store_id = $2
-        ORDER BY created_at DESC
+          AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
         "#,
     )
     .bind(auction_uuid)
     .bind(store_uuid.as_uuid())
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
     .fetch_all(&state.db)
     .await
     .map_err(db_error)?;
 
-    Ok(rows
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("cursor_id"));
+        }
+    }
+    let bids = rows
         .into_iter()
         .map(|row| pb::AuctionBid {
             id: row.get("id"),
@@ -504,7 +504,8 @@ pub async fn list_bids(
             )),
             created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
         })
-        .collect())
+        .collect();
+    Ok((bids, pb::PageResult { next_page_token }))
 }
 
 pub async fn place_bid(
@@ -572,6 +573,10 @@ pub async fn place_bid(
     if now >= end_at {
         return Err(invalid_arg("auction has already ended"));
     }
+    // The row lock above is held until commit, so a concurrent buyout bid is already visible here.
+    if status == AuctionStatus::AwaitingApproval {
+        return Err(invalid_arg("auction is awaiting approval"));
+    }
     if status != AuctionStatus::Running && status != AuctionStatus::Scheduled {
         return Err(invalid_arg("auction is not running"));
     }
@@ -612,7 +617,10 @@ pub async fn place_bid(
     .bind(bid_currency.as_str())
     .execute(tx.as_mut())
     .await
-    .map_err(db_error)?;
+    .map_err(|err| match err.as_database_error() {
+        Some(db_err) if db_err.is_unique_violation() => already_exists("bid with this amount already exists"),
+        _ => db_error(err),
+    })?;
 
     let buyout_amount: Option<i64> = row.get("buyout_price_amount");
     let buyout_currency: Option<String> = row.get("buyout_price_currency");
@@ -633,6 +641,11 @@ pub async fn place_bid(
         winning_bid_id = Some(bid_id);
     }
 
+    if status == AuctionStatus::Scheduled && now >= start_at {
+        next_status = AuctionStatus::Running;
+    }
+
+    // Checked after the scheduled -> running transition so a buyout on the first bid is not overwritten.
     if let (Some(buyout), Some(cur)) = (buyout_amount, buyout_currency.clone())
         && cur == bid_currency
         && bid_amount >= buyout
@@ -640,10 +653,6 @@ pub async fn place_bid(
         next_status = AuctionStatus::AwaitingApproval;
     }
 
-    if status == AuctionStatus::Scheduled && now >= start_at {
-        next_status = AuctionStatus::Running;
-    }
-
     sqlx::query(
         r#"
         UPDATE auctions
@@ -688,21 +697,23 @@ pub async fn place_bid(
         created_at: chrono_to_timestamp(Some(now)),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::Bid.into(),
-            Some("auction_bid"),
-            Some(bid.id.clone()),
-            None,
-            to_json_opt(Some(bid.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::Bid,
+                Some("auction_bid"),
+                Some(bid.id.clone()),
+                None,
+                to_json_opt(Some(bid.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
-    let auto_updated = apply_auto_bids_tx(&mut tx, &store_uuid.as_uuid(), auction_uuid).await?;
+    let auto_updated = apply_auto_bids_tx(state, &mut tx, &store_uuid.as_uuid(), auction_uuid).await?;
     let auction = auto_updated.unwrap_or(auction);
 
     tx.commit().await.map_err(db_error)?;
@@ -817,21 +828,23 @@ pub async fn set_auto_bid(
         updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::Bid.into(),
-            Some("auction_auto_bid"),
-            Some(auto_bid.id.clone()),
-            None,
-            to_json_opt(Some(auto_bid.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::Bid,
+                Some("auction_auto_bid"),
+                Some(auto_bid.id.clone()),
+                None,
+                to_json_opt(Some(auto_bid.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
-    let auto_updated = apply_auto_bids_tx(&mut tx, &store_uuid.as_uuid(), auction_uuid).await?;
+    let auto_updated = apply_auto_bids_tx(state, &mut tx, &store_uuid.as_uuid(), auction_uuid).await?;
     let auction = auto_updated.unwrap_or(auction);
 
     tx.commit().await.map_err(db_error)?;
@@ -843,24 +856,38 @@ pub async fn list_auto_bids(
     state: &AppState,
     store_id: String,
     auction_id: String,
-) -> Result<Vec<pb::AuctionAutoBid>, (StatusCode, Json<ConnectError>)> {
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::AuctionAutoBid>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
     let store_uuid = StoreId::parse(&store_id)?;
     let auction_uuid = parse_uuid(&auction_id, "auction_id")?;
-    let rows = sqlx::query(
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
+    let mut rows = sqlx::query(
         r#"
         SELECT id, auction_id, customer_id, max_amount, currency, status, created_at, updated_at
         FROM auction_auto_bids
         WHERE auction_id = $1 AND store_id = $2
-        ORDER BY created_at DESC
+          AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
         "#,
     )
     .bind(auction_uuid)
     .bind(store_uuid.as_uuid())
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
     .fetch_all(&state.db)
     .await
     .map_err(db_error)?;
 
-    Ok(rows
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("id"));
+        }
+    }
+    let auto_bids = rows
         .into_iter()
         .map(|row| pb::AuctionAutoBid {
             id: row.get::<uuid::Uuid, _>("id").to_string(),
@@ -874,7 +901,8 @@ pub async fn list_auto_bids(
             created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
             updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
         })
-        .collect())
+        .collect();
+    Ok((auto_bids, pb::PageResult { next_page_token }))
 }
 
 pub async fn run_scheduled_auctions(
@@ -912,14 +940,36 @@ pub async fn run_scheduled_auctions(
         let auction_id: uuid::Uuid = row.get("id");
         let store_id: uuid::Uuid = row.get("store_id");
         let mut tx = state.db.begin().await.map_err(db_error)?;
-        let _ = apply_auto_bids_tx(&mut tx, &store_id, auction_id).await?;
+        let _ = apply_auto_bids_tx(state, &mut tx, &store_id, auction_id).await?;
         tx.commit().await.map_err(db_error)?;
     }
 
     Ok(rows.len())
 }
 
+/// Disables the auction's remaining active auto-bids once it can no longer receive bids.
+async fn disable_auto_bids_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    auction_uuid: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    sqlx::query(
+        r#"
+        UPDATE auction_auto_bids
+        SET status = $1, updated_at = now()
+        WHERE auction_id = $2 AND status = $3
+        "#,
+    )
+    .bind(AutoBidStatus::Disabled.as_str())
+    .bind(auction_uuid)
+    .bind(AutoBidStatus::Active.as_str())
+    .execute(tx.as_mut())
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
 async fn apply_auto_bids_tx(
+    state: &AppState,
     tx: &mut Transaction<'_, Postgres>,
     store_uuid: &uuid::Uuid,
     auction_uuid: uuid::Uuid,
@@ -965,6 +1015,23 @@ async fn apply_auto_bids_tx(
         None
     };
 
+    // Lock every auto-bid of this auction (in id order to avoid deadlocks) so concurrent callers
+    // cannot compute the same second-price bid from the same candidates.
+    sqlx::query(
+        r#"
+        SELECT id
+        FROM auction_auto_bids
+        WHERE auction_id = $1 AND store_id = $2
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind(auction_uuid)
+    .bind(store_uuid)
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(db_error)?;
+
     let candidates = sqlx::query(
         r#"
         SELECT id, customer_id, max_amount, currency, created_at
@@ -1026,11 +1093,12 @@ async fn apply_auto_bids_tx(
 
     let bid_id = uuid::Uuid::new_v4();
     let now = Utc::now();
-    sqlx::query(
+    let inserted = sqlx::query(
         r#"
         INSERT INTO auction_bids
             (id, auction_id, store_id, customer_id, amount, currency)
         VALUES ($1,$2,$3,$4,$5,$6)
+        ON CONFLICT (auction_id, customer_id, amount) DO NOTHING
         "#,
     )
     .bind(bid_id)
@@ -1042,6 +1110,9 @@ async fn apply_auto_bids_tx(
     .execute(tx.as_mut())
     .await
     .map_err(db_error)?;
+    if inserted.rows_affected() == 0 {
+        return Ok(None);
+    }
 
     let mut next_status = status;
     if let (Some(buyout), Some(cur)) = (buyout_amount, buyout_currency.clone())
@@ -1091,19 +1162,21 @@ async fn apply_auto_bids_tx(
         created_at: chrono_to_timestamp(Some(now)),
     };
 
-    audit::record_tx(
-        tx,
-        audit_input(
-            Some(auction.store_id.clone()),
-            AuctionAuditAction::Bid.into(),
-            Some("auction_auto_bid"),
-            Some(bid.id.clone()),
-            None,
-            to_json_opt(Some(bid)),
-            None,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            tx,
+            audit_input(
+                Some(auction.store_id.clone()),
+                AuctionAuditAction::Bid,
+                Some("auction_auto_bid"),
+                Some(bid.id.clone()),
+                None,
+                to_json_opt(Some(bid)),
+                None,
+            ),
+        )
+        .await?;
 
     Ok(Some(auction))
 }
@@ -1113,11 +1186,74 @@ pub async fn close_auction(
     store_id: String,
     auction_id: String,
     actor: Option<pb::ActorContext>,
+) -> Result<pb::Auction, (StatusCode, Json<ConnectError>)> {
+    let mut tx = state.db.begin().await.map_err(db_error)?;
+    let auction = close_auction_tx(state, &mut tx, store_id, auction_id, actor).await?;
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(auction)
+}
+
+/// Closes running auctions whose `end_at` has passed. Each auction is closed in its own transaction;
+/// rows already locked by another scheduler instance are skipped.
+pub async fn run_expired_auctions(
+    state: &AppState,
+    batch_size: i64,
+) -> Result<usize, (StatusCode, Json<ConnectError>)> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id, store_id
+        FROM auctions
+        WHERE status = $1 AND end_at <= now()
+        ORDER BY end_at ASC
+        LIMIT $2
+        "#,
+    )
+    .bind(AuctionStatus::Running.as_str())
+    .bind(batch_size)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    let mut closed = 0;
+    for row in rows.iter() {
+        let auction_id: uuid::Uuid = row.get("id");
+        let store_id: uuid::Uuid = row.get("store_id");
+        let mut tx = state.db.begin().await.map_err(db_error)?;
+        let still_expired = sqlx::query(
+            r#"
+            SELECT id
+            FROM auctions
+            WHERE id = $1 AND status = $2 AND end_at <= now()
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(auction_id)
+        .bind(AuctionStatus::Running.as_str())
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(db_error)?;
+        if still_expired.is_none() {
+            continue;
+        }
+        close_auction_tx(state, &mut tx, store_id.to_string(), auction_id.to_string(), None).await?;
+        tx.commit().await.map_err(db_error)?;
+        closed += 1;
+    }
+
+    Ok(closed)
+}
+
+async fn close_auction_tx(
+    state: &AppState,
+    tx: &mut Transaction<'_, Postgres>,
+    store_id: String,
+    auction_id: String,
+    actor: Option<pb::ActorContext>,
 ) -> Result<pb::Auction, (StatusCode, Json<ConnectError>)> {
     let store_uuid = StoreId::parse(&store_id)?;
     let auction_uuid = parse_uuid(&auction_id, "auction_id")?;
 
-    let mut tx = state.db.begin().await.map_err(db_error)?;
     let row = sqlx::query(
         r#"
         SELECT *
@@ -1189,6 +1325,7 @@ pub async fn close_auction(
     .execute(tx.as_mut())
     .await
     .map_err(db_error)?;
+    disable_auto_bids_tx(tx, auction_uuid).await?;
 
     let updated_row = sqlx::query("SELECT * FROM auctions WHERE id = $1")
         .bind(auction_uuid)
@@ -1197,21 +1334,21 @@ pub async fn close_auction(
         .map_err(db_error)?;
     let auction = auction_from_row(&updated_row);
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::End.into(),
-            Some("auction"),
-            Some(auction_id),
-            None,
-            to_json_opt(Some(auction.clone())),
-            actor,
-        ),
-    )
-    .await?;
-
-    tx.commit().await.map_err(db_error)?;
+    state
+        .audit_writer
+        .record_tx(
+            tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::End,
+                Some("auction"),
+                Some(auction_id),
+                None,
+                to_json_opt(Some(auction.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     Ok(auction)
 }
@@ -1274,6 +1411,7 @@ pub async fn approve_auction(
     .execute(tx.as_mut())
     .await
     .map_err(db_error)?;
+    disable_auto_bids_tx(&mut tx, auction_uuid).await?;
 
     let updated_row = sqlx::query("SELECT * FROM auctions WHERE id = $1")
         .bind(auction_uuid)
@@ -1282,19 +1420,21 @@ pub async fn approve_auction(
         .map_err(db_error)?;
     let auction = auction_from_row(&updated_row);
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            AuctionAuditAction::Approve.into(),
-            Some("auction"),
-            Some(auction_id),
-            None,
-            to_json_opt(Some(auction.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                AuctionAuditAction::Approve,
+                Some("auction"),
+                Some(auction_id),
+                None,
+                to_json_opt(Some(auction.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(db_error)?;
 
@@ -1361,30 +1501,28 @@ fn auction_from_row(row: &sqlx::postgres::PgRow) -> pb::Auction {
 fn invalid_arg(message: &str) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::BAD_REQUEST,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::InvalidArgument,
-            message: message.to_string(),
-        }),
+        Json(ConnectError::new(ErrorCode::InvalidArgument, message.to_string())),
     )
 }
 
 fn permission_denied(message: &str) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::FORBIDDEN,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::PermissionDenied,
-            message: message.to_string(),
-        }),
+        Json(ConnectError::new(ErrorCode::PermissionDenied, message.to_string())),
+    )
+}
+
+fn already_exists(message: &str) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::CONFLICT,
+        Json(ConnectError::new(ErrorCode::AlreadyExists, message.to_string())),
     )
 }
 
 fn db_error(err: impl std::fmt::Display) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::INTERNAL_SERVER_ERROR,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::Internal,
-            message: format!("db error: {}", err),
-        }),
+        Json(ConnectError::new(ErrorCode::Internal, format!("db error: {}", err))),
     )
 }
 
@@ -1393,5 +1531,276 @@ pub async fn resolve_context(
     store: Option<pb::StoreContext>,
 ) -> Result<String, (StatusCode, Json<ConnectError>)> {
     let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
+    // Every auction RPC goes through here, so the resolved store is checked against the token
+    // regardless of whether it came from store_id, store_code or the token itself.
+    if let Some(ctx) = request_context::current()
+        && let Some(auth_store) = ctx.store_id.as_deref()
+        && auth_store != store_id
+    {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ConnectError::new(
+                ErrorCode::PermissionDenied,
+                "store_id does not match token",
+            )),
+        ));
+    }
     Ok(store_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        rpc::json::ErrorCode,
+        shared::test_support::{self, TestStore},
+    };
+
+    async fn create_customer(db: &PgPool, store: &TestStore) -> uuid::Uuid {
+        let customer_id: uuid::Uuid = sqlx::query_scalar("INSERT INTO customers (tenant_id) VALUES ($1) RETURNING id")
+            .bind(store.tenant_id)
+            .fetch_one(db)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO customer_profiles (customer_id, store_id) VALUES ($1, $2)")
+            .bind(customer_id)
+            .bind(store.store_id)
+            .execute(db)
+            .await
+            .unwrap();
+        customer_id
+    }
+
+    async fn create_running_auction(db: &PgPool, store: &TestStore) -> uuid::Uuid {
+        let product_id = test_support::create_product(db, store, "Auction item", "active").await;
+        let sku_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+            VALUES ($1, 'AUCTION-1', 1000, 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(product_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO auctions
+                (store_id, product_id, sku_id, auction_type, status, start_at, end_at,
+                 bid_increment_amount, bid_increment_currency, start_price_amount, start_price_currency)
+            VALUES ($1, $2, $3, 'open', 'running', now() - interval '1 hour', now() + interval '1 hour',
+                    100, 'JPY', 1000, 'JPY')
+            RETURNING id
+            "#,
+        )
+        .bind(store.store_id)
+        .bind(product_id)
+        .bind(sku_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    async fn create_auto_bid(db: &PgPool, store: &TestStore, auction_id: uuid::Uuid, max_amount: i64) -> uuid::Uuid {
+        let customer_id = create_customer(db, store).await;
+        sqlx::query(
+            r#"
+            INSERT INTO auction_auto_bids (auction_id, store_id, customer_id, max_amount, currency)
+            VALUES ($1, $2, $3, $4, 'JPY')
+            "#,
+        )
+        .bind(auction_id)
+        .bind(store.store_id)
+        .bind(customer_id)
+        .bind(max_amount)
+        .execute(db)
+        .await
+        .unwrap();
+        customer_id
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn concurrent_bids_leave_one_bid_per_customer_and_amount(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let auction_id = create_running_auction(&db, &store).await;
+        let top_bidder = create_auto_bid(&db, &store, auction_id, 5000).await;
+        create_auto_bid(&db, &store, auction_id, 3000).await;
+        let mut bidders = Vec::new();
+        for _ in 0..3 {
+            bidders.push(create_customer(&db, &store).await);
+        }
+
+        let bid = |customer_id: uuid::Uuid, amount: i64| {
+            place_bid(
+                &state,
+                store.store_id.to_string(),
+                auction_id.to_string(),
+                customer_id.to_string(),
+                money_from_parts(amount, "JPY".to_string()),
+                None,
+            )
+        };
+        let (first, second, third) = tokio::join!(bid(bidders[0], 1000), bid(bidders[1], 1000), bid(bidders[2], 1200));
+
+        for result in [first, second, third] {
+            if let Err((_, Json(err))) = result {
+                assert!(
+                    matches!(err.code, ErrorCode::InvalidArgument | ErrorCode::AlreadyExists),
+                    "unexpected error: {}",
+                    err.message
+                );
+            }
+        }
+        let duplicates: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM (
+                SELECT 1
+                FROM auction_bids
+                WHERE auction_id = $1
+                GROUP BY customer_id, amount
+                HAVING COUNT(*) > 1
+            ) duplicated
+            "#,
+        )
+        .bind(auction_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(duplicates, 0);
+
+        // The auto-bid with the highest limit ends up leading at the highest recorded bid.
+        let row = sqlx::query(
+            r#"
+            SELECT b.customer_id,
+                   a.current_price_amount,
+                   (SELECT MAX(amount) FROM auction_bids WHERE auction_id = a.id) AS top_amount
+            FROM auctions a
+            JOIN auction_bids b ON b.id = a.current_bid_id
+            WHERE a.id = $1
+            "#,
+        )
+        .bind(auction_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(row.get::<Option<uuid::Uuid>, _>("customer_id"), Some(top_bidder));
+        assert_eq!(
+            row.get::<Option<i64>, _>("current_price_amount"),
+            row.get::<Option<i64>, _>("top_amount")
+        );
+    }
+
+    async fn auto_bid_statuses(db: &PgPool, auction_id: uuid::Uuid) -> Vec<String> {
+        sqlx::query_scalar("SELECT status FROM auction_auto_bids WHERE auction_id = $1 ORDER BY max_amount")
+            .bind(auction_id)
+            .fetch_all(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn closing_an_auction_disables_its_auto_bids(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let auction_id = create_running_auction(&db, &store).await;
+        create_auto_bid(&db, &store, auction_id, 3000).await;
+        create_auto_bid(&db, &store, auction_id, 5000).await;
+        let other_auction = create_running_auction(&db, &store).await;
+        create_auto_bid(&db, &store, other_auction, 4000).await;
+
+        let auction = close_auction(&state, store.store_id.to_string(), auction_id.to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(auction.status, AuctionStatus::Ended.as_str());
+
+        assert_eq!(auto_bid_statuses(&db, auction_id).await, ["disabled", "disabled"]);
+        assert_eq!(auto_bid_statuses(&db, other_auction).await, ["active"]);
+    }
+
+    async fn auction_status(db: &PgPool, auction_id: uuid::Uuid) -> String {
+        sqlx::query_scalar("SELECT status FROM auctions WHERE id = $1")
+            .bind(auction_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn expired_auctions_are_closed_once(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let expired = create_running_auction(&db, &store).await;
+        let running = create_running_auction(&db, &store).await;
+        sqlx::query("UPDATE auctions SET end_at = now() - interval '1 minute' WHERE id = $1")
+            .bind(expired)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(run_expired_auctions(&state, 10).await.unwrap(), 1);
+        assert_eq!(auction_status(&db, expired).await, AuctionStatus::Ended.as_str());
+        assert_eq!(auction_status(&db, running).await, AuctionStatus::Running.as_str());
+        assert_eq!(run_expired_auctions(&state, 10).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn buyout_bid_moves_the_auction_to_awaiting_approval_once(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let scheduled = create_running_auction(&db, &store).await;
+        let running = create_running_auction(&db, &store).await;
+        sqlx::query(
+            r#"
+            UPDATE auctions
+            SET buyout_price_amount = 2000, buyout_price_currency = 'JPY',
+                status = CASE WHEN id = $1 THEN 'scheduled' ELSE status END
+            WHERE id = ANY($2)
+            "#,
+        )
+        .bind(scheduled)
+        .bind(vec![scheduled, running])
+        .execute(&db)
+        .await
+        .unwrap();
+        let first = create_customer(&db, &store).await;
+        let second = create_customer(&db, &store).await;
+        let bid = |auction_id: uuid::Uuid, customer_id: uuid::Uuid| {
+            place_bid(
+                &state,
+                store.store_id.to_string(),
+                auction_id.to_string(),
+                customer_id.to_string(),
+                money_from_parts(2000, "JPY".to_string()),
+                None,
+            )
+        };
+
+        // A buyout on the first bid of a started scheduled auction is not overwritten by the start.
+        bid(scheduled, first).await.unwrap();
+        assert_eq!(
+            auction_status(&db, scheduled).await,
+            AuctionStatus::AwaitingApproval.as_str()
+        );
+        let (_, Json(err)) = bid(scheduled, second).await.expect_err("auction is awaiting approval");
+        assert_eq!(err.message, "auction is awaiting approval");
+
+        let (first_result, second_result) = tokio::join!(bid(running, first), bid(running, second));
+        let rejected: Vec<_> = [first_result, second_result]
+            .into_iter()
+            .filter_map(Result::err)
+            .map(|(_, Json(err))| err.message)
+            .collect();
+        assert_eq!(rejected, ["auction is awaiting approval"]);
+        let bids: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM auction_bids WHERE auction_id = $1")
+            .bind(running)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(bids, 1);
+    }
+}
@@ -1,30 +1,39 @@
 use axum::{Json, http::StatusCode};
+use chrono::{DateTime, Utc};
 use sqlx::{QueryBuilder, Row};
+use std::time::{Duration, Instant};
 
 use crate::{
     AppState,
     infrastructure::db,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
+        audit_action::{ALL_AUDIT_ACTIONS, AuditActionTrait},
         ids::parse_uuid,
-        time::{chrono_to_timestamp, timestamp_to_chrono},
+        pagination::{encode_page_token, page_params_with_default},
+        time::{chrono_to_timestamp, chrono_to_timestamp_required, timestamp_to_chrono},
     },
 };
 
-const DEFAULT_PAGE_SIZE: i64 = 50;
-const MAX_PAGE_SIZE: i64 = 200;
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+const ACTIVITY_DEFAULT_LIMIT: i64 = 20;
+const ACTIVITY_MAX_LIMIT: i64 = 100;
+const SLOW_METADATA_QUERY: Duration = Duration::from_secs(2);
 
 pub async fn list_audit_logs(
     state: &AppState,
     store_id: String,
     req: pb::ListAuditLogsRequest,
-) -> Result<(Vec<pb::AuditLog>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
-    let (limit, offset) = page_params(req.page.clone());
+) -> Result<(Vec<pb::AuditLog>, pb::PageResult, Vec<String>), (StatusCode, Json<ConnectError>)> {
+    let (limit, cursor) = page_params_with_default(req.page.clone(), DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE)?;
+    let metadata_query = parse_metadata_query(&req.metadata_query)?;
 
     let mut qb = QueryBuilder::new(
         r#"
         SELECT id::text as id,
+            id as cursor_id,
             store_id,
             actor_id,
             actor_type,
@@ -74,15 +83,49 @@ pub async fn list_audit_logs(
     if !req.user_agent.is_empty() {
         qb.push(" AND user_agent = ").push_bind(req.user_agent);
     }
+    if let Some(fragment) = &metadata_query {
+        qb.push(" AND (metadata_json @> ")
+            .push_bind(fragment.clone())
+            .push("::jsonb OR after_json @> ")
+            .push_bind(fragment.clone())
+            .push("::jsonb)");
+    }
+
+    if let Some((cursor_created_at, cursor_id)) = cursor {
+        qb.push(" AND (created_at, id) < (")
+            .push_bind(cursor_created_at)
+            .push(", ")
+            .push_bind(cursor_id)
+            .push(")");
+    }
 
     qb.push(" ORDER BY created_at DESC, id DESC");
     qb.push(" LIMIT ").push_bind(limit + 1);
-    qb.push(" OFFSET ").push_bind(offset);
 
+    let started_at = Instant::now();
     let rows = qb.build().fetch_all(&state.db).await.map_err(db::error)?;
+    let elapsed = started_at.elapsed();
+
+    let mut warnings = Vec::new();
+    if metadata_query.is_some() && elapsed > SLOW_METADATA_QUERY {
+        tracing::warn!(
+            store_id = %store_id,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "slow audit log metadata_query"
+        );
+        warnings.push(format!(
+            "metadata_query took {}ms; narrow from_time/to_time or add filters",
+            elapsed.as_millis()
+        ));
+    }
 
     let mut logs = Vec::new();
+    let mut cursors = Vec::new();
     for row in rows.into_iter() {
+        cursors.push((
+            row.get::<DateTime<Utc>, _>("created_at"),
+            row.get::<uuid::Uuid, _>("cursor_id"),
+        ));
         logs.push(pb::AuditLog {
             id: row.get("id"),
             store_id: row.get::<uuid::Uuid, _>("store_id").to_string(),
@@ -106,30 +149,231 @@ pub async fn list_audit_logs(
                 .get::<Option<serde_json::Value>, _>("metadata_json")
                 .map(|v| v.to_string())
                 .unwrap_or_default(),
-            created_at: chrono_to_timestamp(row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")),
+            created_at: Some(chrono_to_timestamp_required(
+                row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at"),
+                "created_at",
+            )?),
         });
     }
 
     let mut next_page_token = String::new();
     if logs.len() > limit as usize {
         logs.truncate(limit as usize);
-        next_page_token = (offset + limit).to_string();
+        let (created_at, id) = cursors[logs.len() - 1];
+        next_page_token = encode_page_token(created_at, id);
+    }
+
+    Ok((logs, pb::PageResult { next_page_token }, warnings))
+}
+
+fn parse_metadata_query(value: &str) -> Result<Option<serde_json::Value>, (StatusCode, Json<ConnectError>)> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
     }
+    match serde_json::from_str::<serde_json::Value>(value) {
+        Ok(fragment @ serde_json::Value::Object(_)) => Ok(Some(fragment)),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "metadata_query must be a JSON object",
+            )),
+        )),
+    }
+}
+
+/// Distinct action keys recorded for the store, for narrowing the action filter in the UI.
+pub async fn list_recorded_actions(
+    state: &AppState,
+    store_id: String,
+) -> Result<Vec<String>, (StatusCode, Json<ConnectError>)> {
+    let rows = sqlx::query("SELECT DISTINCT action FROM audit_logs WHERE store_id = $1")
+        .bind(parse_uuid(&store_id, "store_id")?)
+        .fetch_all(&state.db)
+        .await
+        .map_err(db::error)?;
+    Ok(rows.into_iter().map(|row| row.get("action")).collect())
+}
+
+pub async fn get_store_activity(
+    state: &AppState,
+    store_id: String,
+    limit: i32,
+) -> Result<Vec<pb::ActivityEvent>, (StatusCode, Json<ConnectError>)> {
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let limit = if limit <= 0 {
+        ACTIVITY_DEFAULT_LIMIT
+    } else {
+        (limit as i64).min(ACTIVITY_MAX_LIMIT)
+    };
 
-    Ok((logs, pb::PageResult { next_page_token }))
+    // Each branch is limited first so every source can use its (store_id, created_at) ordering.
+    let rows = sqlx::query(
+        r#"
+        SELECT event_type, entity_id, created_at
+        FROM (
+            (
+                SELECT 'order' as event_type, o.id::text as entity_id, o.created_at
+                FROM orders o
+                WHERE EXISTS (
+                    SELECT 1
+                    FROM order_items oi
+                    JOIN product_skus v ON v.id = oi.variant_id
+                    JOIN products p ON p.id = v.product_id
+                    WHERE oi.order_id = o.id AND p.store_id = $1
+                )
+                ORDER BY o.created_at DESC
+                LIMIT $2
+            )
+            UNION ALL
+            (
+                SELECT action as event_type, COALESCE(target_id, '') as entity_id, created_at
+                FROM audit_logs
+                WHERE store_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            )
+            UNION ALL
+            (
+                SELECT 'auction_bid' as event_type, id::text as entity_id, created_at
+                FROM auction_bids
+                WHERE store_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            )
+        ) activity
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(store_uuid)
+    .bind(limit)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let event_type: String = row.get("event_type");
+            let description = match event_type.as_str() {
+                "order" => "Order placed".to_string(),
+                "auction_bid" => "Auction bid placed".to_string(),
+                action => ALL_AUDIT_ACTIONS
+                    .iter()
+                    .find(|known| known.as_str() == action)
+                    .map(|known| known.label().to_string())
+                    .unwrap_or_else(|| action.to_string()),
+            };
+            pb::ActivityEvent {
+                event_type,
+                entity_id: row.get("entity_id"),
+                created_at: chrono_to_timestamp(Some(row.get::<DateTime<Utc>, _>("created_at"))),
+                description,
+            }
+        })
+        .collect())
 }
 
-fn page_params(page: Option<pb::PageInfo>) -> (i64, i64) {
-    let page = page.unwrap_or(pb::PageInfo {
-        page_size: DEFAULT_PAGE_SIZE as i32,
-        page_token: String::new(),
-    });
-    let mut limit = page.page_size as i64;
-    if limit <= 0 {
-        limit = DEFAULT_PAGE_SIZE;
-    } else if limit > MAX_PAGE_SIZE {
-        limit = MAX_PAGE_SIZE;
-    }
-    let offset = page.page_token.parse::<i64>().unwrap_or(0).max(0);
-    (limit, offset)
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    /// `count` logs one minute apart, newest first; the three newest are deletes.
+    async fn insert_logs(db: &PgPool, store_id: uuid::Uuid, count: i32) {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_logs (store_id, actor_type, action, created_at)
+            SELECT $1, 'staff',
+                   CASE WHEN i <= 3 THEN 'product.delete' ELSE 'product.update' END,
+                   now() - make_interval(mins => i)
+            FROM generate_series(1, $2) AS i
+            "#,
+        )
+        .bind(store_id)
+        .bind(count)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    fn page(page_token: &str) -> Option<pb::PageInfo> {
+        Some(pb::PageInfo {
+            page_size: 0,
+            page_token: page_token.to_string(),
+        })
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn logs_are_paged_by_one_hundred_and_filtered(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        insert_logs(&db, store.store_id, 103).await;
+        let other = test_support::create_store(&db).await;
+        insert_logs(&db, other.store_id, 1).await;
+
+        let (first, first_page, _) = list_audit_logs(&state, store.store_id.to_string(), Default::default())
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 100);
+        assert_eq!(first[0].action, "product.delete");
+        let (rest, rest_page, _) = list_audit_logs(
+            &state,
+            store.store_id.to_string(),
+            pb::ListAuditLogsRequest {
+                page: page(&first_page.next_page_token),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(rest.len(), 3);
+        assert!(rest_page.next_page_token.is_empty());
+
+        let (recent, _, _) = list_audit_logs(
+            &state,
+            store.store_id.to_string(),
+            pb::ListAuditLogsRequest {
+                from_time: chrono_to_timestamp(Some(Utc::now() - chrono::Duration::seconds(10 * 60 + 30))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(recent.len(), 10);
+
+        let (recent_deletes, _, _) = list_audit_logs(
+            &state,
+            store.store_id.to_string(),
+            pb::ListAuditLogsRequest {
+                action: "product.delete".to_string(),
+                from_time: chrono_to_timestamp(Some(Utc::now() - chrono::Duration::seconds(2 * 60 + 30))),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(recent_deletes.len(), 2);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn recorded_actions_are_scoped_to_the_store(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        insert_logs(&db, store.store_id, 5).await;
+        let other = test_support::create_store(&db).await;
+        sqlx::query("INSERT INTO audit_logs (store_id, actor_type, action) VALUES ($1, 'staff', 'order.cancel')")
+            .bind(other.store_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let mut actions = list_recorded_actions(&state, store.store_id.to_string()).await.unwrap();
+        actions.sort();
+        assert_eq!(actions, ["product.delete", "product.update"]);
+    }
 }
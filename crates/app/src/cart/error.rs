@@ -48,52 +48,31 @@ impl CartError {
         match self {
             CartError::InvalidArgument(message) => (
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: ErrorCode::InvalidArgument,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
             ),
             CartError::NotFound(message) => (
                 StatusCode::NOT_FOUND,
-                Json(ConnectError {
-                    code: ErrorCode::NotFound,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::NotFound, message)),
             ),
             CartError::AlreadyExists(message) => (
                 StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::AlreadyExists,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::AlreadyExists, message)),
             ),
             CartError::FailedPrecondition(message) => (
                 StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::FailedPrecondition,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::FailedPrecondition, message)),
             ),
             CartError::PermissionDenied(message) => (
                 StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: ErrorCode::PermissionDenied,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::PermissionDenied, message)),
             ),
             CartError::Unauthenticated(message) => (
                 StatusCode::UNAUTHORIZED,
-                Json(ConnectError {
-                    code: ErrorCode::Unauthenticated,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Unauthenticated, message)),
             ),
             CartError::Internal(message) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: ErrorCode::Internal,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, message)),
             ),
         }
     }
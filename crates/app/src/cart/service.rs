@@ -4,12 +4,19 @@ use sqlx::Row;
 use crate::{
     AppState,
     cart::error::{CartError, CartResult},
+    infrastructure::outbox,
     pb::pb,
+    shared::audit_action::CartAuditAction,
+    shared::audit_helpers::audit_input,
     shared::ids::{CartId, CartItemId, CustomerId, LocationId, SkuId, StoreId, parse_uuid},
     shared::status::{CartItemStatus, CartStatus, PaymentMethod},
     shared::time::chrono_to_timestamp,
+    shared::validation::Email,
 };
 
+const CART_ABANDONED_EVENT_TYPE: &str = "cart.abandoned";
+const GUEST_CUSTOMER_STATUS: &str = "guest";
+
 fn cart_ttl_days() -> i64 {
     std::env::var("CART_TTL_DAYS")
         .ok()
@@ -20,30 +27,68 @@ fn cart_ttl_days() -> i64 {
 
 async fn resolve_store_id(state: &AppState, store: Option<pb::StoreContext>) -> CartResult<String> {
     let (store_id, _tenant_id) =
-        crate::identity::context::resolve_store_context_without_token_guard(state, store, None).await?;
+        crate::shared::store_context::resolve_store_context_without_token_guard(state, store, None).await?;
     Ok(store_id)
 }
 
-pub async fn create_cart(state: &AppState, req: pb::CreateCartRequest) -> CartResult<pb::Cart> {
+#[derive(Debug, Clone, Default)]
+pub struct CartAccess {
+    pub customer_id: Option<String>,
+    pub cart_token: Option<String>,
+}
+
+// Customer carts are only open to that customer, anonymous carts only to the holder of X-Cart-Token.
+fn authorize_cart(
+    access: &CartAccess,
+    customer_id: Option<&str>,
+    anonymous_token: Option<uuid::Uuid>,
+) -> CartResult<()> {
+    if let Some(owner) = customer_id {
+        if access.customer_id.as_deref() != Some(owner) {
+            return Err(CartError::permission_denied("cart belongs to another customer"));
+        }
+        return Ok(());
+    }
+    let cart_token = access
+        .cart_token
+        .as_deref()
+        .and_then(|value| uuid::Uuid::parse_str(value).ok());
+    match anonymous_token {
+        Some(anonymous_token) if cart_token == Some(anonymous_token) => Ok(()),
+        _ => Err(CartError::permission_denied("cart token is invalid")),
+    }
+}
+
+pub async fn create_cart(state: &AppState, req: pb::CreateCartRequest, access: &CartAccess) -> CartResult<pb::Cart> {
     let cart_id = uuid::Uuid::new_v4();
     let store_id = resolve_store_id(state, req.store.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let expires_at = Utc::now() + Duration::days(cart_ttl_days());
+    // The owner always comes from the customer JWT; customer_id may only restate it.
+    let customer_id = access.customer_id.clone();
+    if !req.customer_id.is_empty() && customer_id.as_deref() != Some(req.customer_id.as_str()) {
+        return Err(CartError::permission_denied(
+            "customer_id does not match the authenticated customer",
+        ));
+    }
+    let customer_uuid = customer_id
+        .as_deref()
+        .map(CustomerId::parse)
+        .transpose()?
+        .map(|id| id.as_uuid());
+    let anonymous_token = customer_uuid.is_none().then(uuid::Uuid::new_v4);
     sqlx::query(
         r#"
-        INSERT INTO carts (id, store_id, customer_id, status, expires_at)
-        VALUES ($1, $2, $3, $4, $5)
+        INSERT INTO carts (id, store_id, customer_id, status, expires_at, anonymous_token)
+        VALUES ($1, $2, $3, $4, $5, $6)
         "#,
     )
     .bind(cart_id)
     .bind(store_uuid.as_uuid())
-    .bind(if req.customer_id.is_empty() {
-        None
-    } else {
-        Some(CustomerId::parse(&req.customer_id)?.as_uuid())
-    })
+    .bind(customer_uuid)
     .bind(CartStatus::Active.as_str())
     .bind(expires_at)
+    .bind(anonymous_token)
     .execute(&state.db)
     .await
     .map_err(CartError::from)?;
@@ -51,15 +96,16 @@ pub async fn create_cart(state: &AppState, req: pb::CreateCartRequest) -> CartRe
     Ok(pb::Cart {
         id: cart_id.to_string(),
         store_id,
-        customer_id: req.customer_id,
+        customer_id: customer_id.unwrap_or_default(),
         items: Vec::new(),
         total: None,
         status: CartStatus::Active.as_str().to_string(),
         expires_at: chrono_to_timestamp(Some(expires_at)),
+        cart_token: anonymous_token.map(|token| token.to_string()).unwrap_or_default(),
     })
 }
 
-pub async fn add_cart_item(state: &AppState, req: pb::AddCartItemRequest) -> CartResult<pb::Cart> {
+pub async fn add_cart_item(state: &AppState, req: pb::AddCartItemRequest, access: &CartAccess) -> CartResult<pb::Cart> {
     if req.quantity <= 0 {
         return Err(CartError::invalid_argument("quantity must be greater than 0"));
     }
@@ -75,15 +121,27 @@ pub async fn add_cart_item(state: &AppState, req: pb::AddCartItemRequest) -> Car
     };
 
     // Validate cart ownership.
-    let cart_exists = sqlx::query("SELECT id, expires_at FROM carts WHERE id = $1 AND store_id = $2 LIMIT 1")
-        .bind(cart_uuid.as_uuid())
-        .bind(store_uuid.as_uuid())
-        .fetch_optional(&state.db)
-        .await
-        .map_err(CartError::from)?;
+    let cart_exists = sqlx::query(
+        r#"
+        SELECT id, customer_id::text as customer_id, anonymous_token, expires_at
+        FROM carts
+        WHERE id = $1 AND store_id = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(cart_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(&state.db)
+    .await
+    .map_err(CartError::from)?;
     let Some(cart_row) = cart_exists else {
         return Err(CartError::not_found("cart not found"));
     };
+    authorize_cart(
+        access,
+        cart_row.get::<Option<String>, _>("customer_id").as_deref(),
+        cart_row.get("anonymous_token"),
+    )?;
     let expires_at: chrono::DateTime<Utc> = cart_row.get("expires_at");
 
     // Fetch SKU price + fulfillment type via product.
@@ -136,6 +194,12 @@ pub async fn add_cart_item(state: &AppState, req: pb::AddCartItemRequest) -> Car
     .await
     .map_err(CartError::from)?;
 
+    sqlx::query("UPDATE carts SET last_activity_at = now(), abandoned_at = NULL, updated_at = now() WHERE id = $1")
+        .bind(cart_uuid.as_uuid())
+        .execute(&state.db)
+        .await
+        .map_err(CartError::from)?;
+
     // Enqueue reservation request (async worker) for physical items only.
     if is_physical {
         let request_id = uuid::Uuid::new_v4();
@@ -181,10 +245,15 @@ pub async fn add_cart_item(state: &AppState, req: pb::AddCartItemRequest) -> Car
         }),
         status: CartStatus::Active.as_str().to_string(),
         expires_at: chrono_to_timestamp(Some(expires_at)),
+        cart_token: String::new(),
     })
 }
 
-pub async fn remove_cart_item(state: &AppState, req: pb::RemoveCartItemRequest) -> CartResult<pb::Cart> {
+pub async fn remove_cart_item(
+    state: &AppState,
+    req: pb::RemoveCartItemRequest,
+    access: &CartAccess,
+) -> CartResult<pb::Cart> {
     let store_id = resolve_store_id(state, req.store.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let cart_item_uuid = CartItemId::parse(&req.cart_item_id)?;
@@ -193,8 +262,8 @@ pub async fn remove_cart_item(state: &AppState, req: pb::RemoveCartItemRequest)
 
     let row = sqlx::query(
         r#"
-        SELECT c.id::text as cart_id, c.expires_at, ci.sku_id::text as sku_id, ci.location_id::text as location_id,
-               ci.quantity, ci.fulfillment_type
+        SELECT c.id::text as cart_id, c.customer_id::text as customer_id, c.anonymous_token, c.expires_at,
+               ci.sku_id::text as sku_id, ci.location_id::text as location_id, ci.quantity, ci.fulfillment_type
         FROM cart_items ci
         JOIN carts c ON c.id = ci.cart_id
         WHERE ci.id = $1 AND c.store_id = $2
@@ -211,6 +280,11 @@ pub async fn remove_cart_item(state: &AppState, req: pb::RemoveCartItemRequest)
     let Some(row) = row else {
         return Err(CartError::not_found("cart item not found"));
     };
+    authorize_cart(
+        access,
+        row.get::<Option<String>, _>("customer_id").as_deref(),
+        row.get("anonymous_token"),
+    )?;
 
     let cart_id: String = row.get("cart_id");
     let sku_id: String = row.get("sku_id");
@@ -283,6 +357,7 @@ pub async fn remove_cart_item(state: &AppState, req: pb::RemoveCartItemRequest)
         .execute(&mut *tx)
         .await
         .map_err(CartError::from)?;
+    touch_cart_activity_tx(&mut tx, &cart_id).await?;
 
     tx.commit().await.map_err(CartError::from)?;
 
@@ -294,10 +369,15 @@ pub async fn remove_cart_item(state: &AppState, req: pb::RemoveCartItemRequest)
         total: None,
         status: CartStatus::Active.as_str().to_string(),
         expires_at: chrono_to_timestamp(Some(expires_at)),
+        cart_token: String::new(),
     })
 }
 
-pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest) -> CartResult<pb::Cart> {
+pub async fn update_cart_item(
+    state: &AppState,
+    req: pb::UpdateCartItemRequest,
+    access: &CartAccess,
+) -> CartResult<pb::Cart> {
     if req.quantity <= 0 {
         return Err(CartError::invalid_argument("quantity must be greater than 0"));
     }
@@ -309,8 +389,8 @@ pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest)
     let mut tx = state.db.begin().await.map_err(CartError::from)?;
     let row = sqlx::query(
         r#"
-        SELECT c.id::text as cart_id, c.expires_at, ci.sku_id::text as sku_id, ci.location_id::text as location_id,
-               ci.quantity, ci.fulfillment_type
+        SELECT c.id::text as cart_id, c.customer_id::text as customer_id, c.anonymous_token, c.expires_at,
+               ci.sku_id::text as sku_id, ci.location_id::text as location_id, ci.quantity, ci.fulfillment_type
         FROM cart_items ci
         JOIN carts c ON c.id = ci.cart_id
         WHERE ci.id = $1 AND c.store_id = $2
@@ -327,6 +407,11 @@ pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest)
     let Some(row) = row else {
         return Err(CartError::not_found("cart item not found"));
     };
+    authorize_cart(
+        access,
+        row.get::<Option<String>, _>("customer_id").as_deref(),
+        row.get("anonymous_token"),
+    )?;
 
     let cart_id: String = row.get("cart_id");
     let sku_id: String = row.get("sku_id");
@@ -345,6 +430,7 @@ pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest)
             total: None,
             status: CartStatus::Active.as_str().to_string(),
             expires_at: chrono_to_timestamp(Some(expires_at)),
+            cart_token: String::new(),
         });
     }
 
@@ -354,6 +440,7 @@ pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest)
         .execute(&mut *tx)
         .await
         .map_err(CartError::from)?;
+    touch_cart_activity_tx(&mut tx, &cart_id).await?;
 
     let delta = req.quantity - current_qty;
     if fulfillment_type == "physical" {
@@ -484,17 +571,18 @@ pub async fn update_cart_item(state: &AppState, req: pb::UpdateCartItemRequest)
         total: None,
         status: CartStatus::Active.as_str().to_string(),
         expires_at: chrono_to_timestamp(Some(expires_at)),
+        cart_token: String::new(),
     })
 }
 
-pub async fn get_cart(state: &AppState, req: pb::GetCartRequest) -> CartResult<pb::Cart> {
+pub async fn get_cart(state: &AppState, req: pb::GetCartRequest, access: &CartAccess) -> CartResult<pb::Cart> {
     let store_id = resolve_store_id(state, req.store.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let cart_uuid = CartId::parse(&req.cart_id)?;
 
     let cart_row = sqlx::query(
         r#"
-        SELECT customer_id::text as customer_id, status, expires_at
+        SELECT customer_id::text as customer_id, anonymous_token, status, expires_at
         FROM carts
         WHERE id = $1 AND store_id = $2
         LIMIT 1
@@ -508,6 +596,11 @@ pub async fn get_cart(state: &AppState, req: pb::GetCartRequest) -> CartResult<p
     let Some(cart_row) = cart_row else {
         return Err(CartError::not_found("cart not found"));
     };
+    authorize_cart(
+        access,
+        cart_row.get::<Option<String>, _>("customer_id").as_deref(),
+        cart_row.get("anonymous_token"),
+    )?;
 
     let items = sqlx::query(
         r#"
@@ -575,10 +668,16 @@ pub async fn get_cart(state: &AppState, req: pb::GetCartRequest) -> CartResult<p
         total,
         status: cart_row.get("status"),
         expires_at: chrono_to_timestamp(Some(cart_row.get("expires_at"))),
+        cart_token: String::new(),
     })
 }
 
-pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequest) -> CartResult<pb::Order> {
+pub async fn checkout(
+    state: &AppState,
+    tenant_id: String,
+    req: pb::CheckoutRequest,
+    access: &CartAccess,
+) -> CartResult<pb::Order> {
     let tenant_uuid = parse_uuid(&tenant_id, "tenant_id")?;
     let cart_uuid = CartId::parse(&req.cart_id)?;
     let payment_method = PaymentMethod::from_pb(req.payment_method)?;
@@ -598,7 +697,13 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
     let store_uuid = StoreId::parse(&store_id)?;
 
     let cart_row = sqlx::query(
-        "SELECT customer_id::text as customer_id FROM carts WHERE id = $1 AND store_id = $2 LIMIT 1 FOR UPDATE",
+        r#"
+        SELECT customer_id::text as customer_id, anonymous_token
+        FROM carts
+        WHERE id = $1 AND store_id = $2
+        LIMIT 1
+        FOR UPDATE
+        "#,
     )
     .bind(cart_uuid.as_uuid())
     .bind(store_uuid.as_uuid())
@@ -608,6 +713,30 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
     let Some(cart_row) = cart_row else {
         return Err(CartError::not_found("cart not found"));
     };
+    let cart_customer_id: Option<String> = cart_row.get("customer_id");
+    authorize_cart(access, cart_customer_id.as_deref(), cart_row.get("anonymous_token"))?;
+    let is_guest = cart_customer_id.is_none();
+    let customer_id = match cart_customer_id {
+        Some(customer_id) => customer_id,
+        None => {
+            if req.guest_email.trim().is_empty() {
+                return Err(CartError::invalid_argument(
+                    "guest_email is required for guest checkout",
+                ));
+            }
+            let guest_email =
+                Email::parse(&req.guest_email).map_err(|_| CartError::invalid_argument("guest_email is invalid"))?;
+            let customer_uuid =
+                create_guest_customer_tx(&mut tx, tenant_uuid, store_uuid.as_uuid(), guest_email.as_str()).await?;
+            sqlx::query("UPDATE carts SET customer_id = $1, updated_at = now() WHERE id = $2")
+                .bind(customer_uuid)
+                .bind(cart_uuid.as_uuid())
+                .execute(&mut *tx)
+                .await
+                .map_err(CartError::from)?;
+            customer_uuid.to_string()
+        }
+    };
 
     let items = sqlx::query(
         r#"
@@ -630,6 +759,7 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
 
     let mut total_amount: i64 = 0;
     let mut currency: Option<String> = None;
+    let mut has_physical = false;
 
     for item in &items {
         let cart_item_id: String = item.get("cart_item_id");
@@ -640,6 +770,7 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
         let price_currency: String = item.get("unit_price_currency");
         let fulfillment_type: String = item.get("fulfillment_type");
         let is_physical = fulfillment_type == "physical";
+        has_physical |= is_physical;
 
         if let Some(curr) = &currency {
             if curr != &price_currency {
@@ -745,31 +876,127 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
         total_amount = total_amount.saturating_add(price_amount * (quantity as i64));
     }
 
+    let currency = currency.unwrap_or_else(|| "JPY".to_string());
+    let promotion_code_id: Option<uuid::Uuid> =
+        sqlx::query_scalar("SELECT promotion_code_id FROM cart_promotions WHERE cart_id = $1")
+            .bind(cart_uuid.as_uuid())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(CartError::from)?;
+    // The code row stays locked until commit, so concurrent checkouts cannot exceed usage_limit.
+    let code_discount = match promotion_code_id {
+        Some(promotion_code_id) => {
+            let Some(promotion_code) = crate::promotion::code::lock_redeemable_code(&mut tx, promotion_code_id).await?
+            else {
+                return Err(CartError::failed_precondition("promotion code is no longer available"));
+            };
+            promotion_code.ensure_redeemable(Utc::now())?;
+            if promotion_code.first_order_only && !is_guest {
+                crate::promotion::service::ensure_first_order(
+                    &mut tx,
+                    tenant_uuid,
+                    CustomerId::parse(&customer_id)?.as_uuid(),
+                )
+                .await?;
+            }
+            let discount_amount = promotion_code.discount_for(total_amount, &currency)?;
+            crate::promotion::code::consume_code(&mut tx, promotion_code.id).await?;
+            Some((promotion_code, discount_amount))
+        }
+        None => None,
+    };
+    let discount_amount = code_discount.as_ref().map_or(0, |(_, amount)| *amount);
+    total_amount = total_amount.saturating_sub(discount_amount);
+
+    let (shipping_amount, shipping_carrier_name, free_shipping) = if has_physical {
+        if req.shipping_zone_id.is_empty() || req.shipping_rate_id.is_empty() {
+            return Err(CartError::invalid_argument(
+                "shipping_zone_id and shipping_rate_id are required",
+            ));
+        }
+        let prefecture = req
+            .shipping_address
+            .as_ref()
+            .map(|address| address.prefecture.as_str())
+            .unwrap_or_default();
+        if prefecture.trim().is_empty() {
+            return Err(CartError::invalid_argument("shipping_address.prefecture is required"));
+        }
+        let quote = crate::store_settings::shipping::calculate_shipping_fee(
+            &mut tx,
+            store_uuid.as_uuid(),
+            &req.shipping_zone_id,
+            &req.shipping_rate_id,
+            prefecture,
+            total_amount,
+            &currency,
+        )
+        .await?;
+        let free_shipping =
+            crate::promotion::service::free_shipping_promotion(&mut tx, tenant_uuid, total_amount, &currency).await?;
+        // Guests have no order history yet; their use is flagged on the order instead.
+        if let Some(promotion) = &free_shipping
+            && promotion.first_order_only
+            && !is_guest
+        {
+            crate::promotion::service::ensure_first_order(
+                &mut tx,
+                tenant_uuid,
+                CustomerId::parse(&customer_id)?.as_uuid(),
+            )
+            .await?;
+        }
+        let fee_amount = if free_shipping.is_some() { 0 } else { quote.fee_amount };
+        (fee_amount, quote.carrier_name, free_shipping)
+    } else {
+        (0, None, None)
+    };
+    total_amount = total_amount.saturating_add(shipping_amount);
+
     let status = match payment_method {
         PaymentMethod::BankTransfer => "pending_payment",
         PaymentMethod::Cod => "pending_shipment",
     };
     sqlx::query(
         r#"
-        INSERT INTO orders (id, tenant_id, customer_id, status, total_amount, currency, payment_method)
-        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        INSERT INTO orders (
+            id, tenant_id, customer_id, status, total_amount, currency, payment_method,
+            shipping_amount, shipping_carrier_name
+        )
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
         "#,
     )
     .bind(order_id)
     .bind(tenant_uuid)
-    .bind(
-        cart_row
-            .get::<Option<String>, _>("customer_id")
-            .and_then(|id| parse_uuid(&id, "customer_id").ok()),
-    )
+    .bind(CustomerId::parse(&customer_id)?.as_uuid())
     .bind(status)
     .bind(total_amount)
-    .bind(currency.clone().unwrap_or_else(|| "JPY".to_string()))
+    .bind(&currency)
     .bind(payment_method.as_str())
+    .bind(shipping_amount)
+    .bind(shipping_carrier_name.as_deref())
     .execute(&mut *tx)
     .await
     .map_err(CartError::from)?;
 
+    if let Some(promotion) = &free_shipping {
+        let metadata = if promotion.first_order_only && is_guest {
+            serde_json::json!({ "first_order_only_enforced": true })
+        } else {
+            serde_json::json!({})
+        };
+        crate::promotion::service::record_order_promotion(&mut tx, order_id, promotion.id, metadata).await?;
+    }
+    if let Some((promotion_code, discount_amount)) = &code_discount {
+        let metadata = serde_json::json!({
+            "promotion_code_id": promotion_code.id.to_string(),
+            "discount_amount": discount_amount,
+            "first_order_only_enforced": promotion_code.first_order_only && is_guest,
+        });
+        crate::promotion::service::record_order_promotion(&mut tx, order_id, promotion_code.promotion_id, metadata)
+            .await?;
+    }
+
     for item in &items {
         let cart_item_id: String = item.get("cart_item_id");
         let sku_id: String = item.get("sku_id");
@@ -809,11 +1036,36 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
         .await
         .map_err(CartError::from)?;
 
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                CartAuditAction::Checkout,
+                Some("order"),
+                Some(order_id.to_string()),
+                None,
+                Some(serde_json::json!({
+                    "order_id": order_id.to_string(),
+                    "cart_id": req.cart_id,
+                    "total_amount": total_amount,
+                    "discount_amount": discount_amount,
+                    "item_count": items.len(),
+                })),
+                Some(pb::ActorContext {
+                    actor_id: customer_id.clone(),
+                    actor_type: "customer".to_string(),
+                }),
+            ),
+        )
+        .await?;
+
     tx.commit().await.map_err(CartError::from)?;
 
     Ok(pb::Order {
         id: order_id.to_string(),
-        customer_id: cart_row.get::<Option<String>, _>("customer_id").unwrap_or_default(),
+        customer_id,
         status: match status {
             "pending_payment" => pb::OrderStatus::PendingPayment as i32,
             "pending_shipment" => pb::OrderStatus::PendingShipment as i32,
@@ -821,15 +1073,278 @@ pub async fn checkout(state: &AppState, tenant_id: String, req: pb::CheckoutRequ
         },
         total: Some(pb::Money {
             amount: total_amount,
-            currency: currency.unwrap_or_else(|| "JPY".to_string()),
+            currency: currency.clone(),
         }),
         payment_method: req.payment_method,
         shipping_address: req.shipping_address,
         billing_address: req.billing_address,
         created_at: None,
+        shipping_fee: Some(pb::Money {
+            amount: shipping_amount,
+            currency,
+        }),
+        shipping_carrier_name: shipping_carrier_name.unwrap_or_default(),
     })
 }
 
+async fn create_guest_customer_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_uuid: uuid::Uuid,
+    store_uuid: uuid::Uuid,
+    email: &str,
+) -> CartResult<uuid::Uuid> {
+    let customer_uuid = uuid::Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO customers (id, tenant_id, email, status)
+        VALUES ($1,$2,$3,$4)
+        "#,
+    )
+    .bind(customer_uuid)
+    .bind(tenant_uuid)
+    .bind(email)
+    .bind(GUEST_CUSTOMER_STATUS)
+    .execute(&mut **tx)
+    .await
+    .map_err(CartError::from)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO customer_profiles (customer_id, store_id, email)
+        VALUES ($1,$2,$3)
+        "#,
+    )
+    .bind(customer_uuid)
+    .bind(store_uuid)
+    .bind(email)
+    .execute(&mut **tx)
+    .await
+    .map_err(CartError::from)?;
+
+    Ok(customer_uuid)
+}
+
+pub async fn claim_cart(state: &AppState, req: pb::ClaimCartRequest, access: &CartAccess) -> CartResult<pb::Cart> {
+    let Some(customer_id) = access.customer_id.clone() else {
+        return Err(CartError::unauthenticated("customer authentication is required"));
+    };
+    let customer_uuid = CustomerId::parse(&customer_id)?;
+    let cart_token = uuid::Uuid::parse_str(req.cart_token.trim())
+        .map_err(|_| CartError::invalid_argument("cart_token is invalid"))?;
+    let store_id = resolve_store_id(state, req.store.clone()).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+
+    let customer_exists = sqlx::query(
+        r#"
+        SELECT 1
+        FROM customers c
+        JOIN stores s ON s.tenant_id = c.tenant_id
+        WHERE c.id = $1 AND s.id = $2
+        "#,
+    )
+    .bind(customer_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(&state.db)
+    .await
+    .map_err(CartError::from)?;
+    if customer_exists.is_none() {
+        return Err(CartError::not_found("customer not found"));
+    }
+
+    let row = sqlx::query(
+        r#"
+        UPDATE carts
+        SET customer_id = $1, anonymous_token = NULL, last_activity_at = now(), updated_at = now()
+        WHERE anonymous_token = $2 AND store_id = $3 AND customer_id IS NULL AND status = $4
+        RETURNING id::text as id
+        "#,
+    )
+    .bind(customer_uuid.as_uuid())
+    .bind(cart_token)
+    .bind(store_uuid.as_uuid())
+    .bind(CartStatus::Active.as_str())
+    .fetch_optional(&state.db)
+    .await
+    .map_err(CartError::from)?;
+    let Some(row) = row else {
+        return Err(CartError::not_found("cart not found"));
+    };
+
+    get_cart(
+        state,
+        pb::GetCartRequest {
+            store: req.store,
+            cart_id: row.get("id"),
+        },
+        access,
+    )
+    .await
+}
+
+// Only validates and records the code; used_count is consumed at checkout.
+pub async fn apply_promotion_code(
+    state: &AppState,
+    req: pb::ApplyPromotionCodeRequest,
+    access: &CartAccess,
+) -> CartResult<pb::ApplyPromotionCodeResponse> {
+    let store_id = resolve_store_id(state, req.store.clone()).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let cart_uuid = CartId::parse(&req.cart_id)?;
+    let code = req.code.trim();
+    if code.is_empty() {
+        return Err(CartError::invalid_argument("code is required"));
+    }
+
+    let mut tx = state.db.begin().await.map_err(CartError::from)?;
+    let cart_row = sqlx::query(
+        r#"
+        SELECT customer_id::text as customer_id, anonymous_token, status
+        FROM carts
+        WHERE id = $1 AND store_id = $2
+        LIMIT 1
+        FOR UPDATE
+        "#,
+    )
+    .bind(cart_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(CartError::from)?;
+    let Some(cart_row) = cart_row else {
+        return Err(CartError::not_found("cart not found"));
+    };
+    authorize_cart(
+        access,
+        cart_row.get::<Option<String>, _>("customer_id").as_deref(),
+        cart_row.get("anonymous_token"),
+    )?;
+    if cart_row.get::<String, _>("status") != CartStatus::Active.as_str() {
+        return Err(CartError::failed_precondition("cart is not active"));
+    }
+
+    let applied = sqlx::query("SELECT 1 FROM cart_promotions WHERE cart_id = $1")
+        .bind(cart_uuid.as_uuid())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(CartError::from)?;
+    if applied.is_some() {
+        return Err(CartError::already_exists(
+            "a promotion code is already applied to this cart",
+        ));
+    }
+
+    let Some(promotion_code) =
+        crate::promotion::code::find_redeemable_code(&mut tx, store_uuid.as_uuid(), code).await?
+    else {
+        return Err(CartError::not_found("promotion code not found"));
+    };
+    promotion_code.ensure_redeemable(Utc::now())?;
+
+    let items = sqlx::query(
+        r#"
+        SELECT unit_price_amount, unit_price_currency, quantity
+        FROM cart_items
+        WHERE cart_id = $1 AND status = 'active'
+        "#,
+    )
+    .bind(cart_uuid.as_uuid())
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(CartError::from)?;
+    let mut subtotal: i64 = 0;
+    let mut currency: Option<String> = None;
+    for item in &items {
+        let price_currency: String = item.get("unit_price_currency");
+        if currency.as_ref().is_some_and(|curr| curr != &price_currency) {
+            return Err(CartError::invalid_argument("mixed currency cart is not supported"));
+        }
+        currency = Some(price_currency);
+        let price_amount: i64 = item.get("unit_price_amount");
+        let quantity: i32 = item.get("quantity");
+        subtotal = subtotal.saturating_add(price_amount * (quantity as i64));
+    }
+    let Some(currency) = currency else {
+        return Err(CartError::failed_precondition("cart has no items"));
+    };
+    let discount_amount = promotion_code.discount_for(subtotal, &currency)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO cart_promotions (cart_id, promotion_code_id, promotion_id)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(cart_uuid.as_uuid())
+    .bind(promotion_code.id)
+    .bind(promotion_code.promotion_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(CartError::from)?;
+    touch_cart_activity_tx(&mut tx, &req.cart_id).await?;
+    tx.commit().await.map_err(CartError::from)?;
+
+    Ok(pb::ApplyPromotionCodeResponse {
+        promotion_code_id: promotion_code.id.to_string(),
+        promotion_id: promotion_code.promotion_id.to_string(),
+        discount: Some(pb::Money {
+            amount: discount_amount,
+            currency,
+        }),
+    })
+}
+
+// Drops the cart's code, e.g. once it has expired or hit its usage limit and would fail checkout.
+pub async fn remove_promotion_code(
+    state: &AppState,
+    req: pb::RemovePromotionCodeRequest,
+    access: &CartAccess,
+) -> CartResult<pb::RemovePromotionCodeResponse> {
+    let store_id = resolve_store_id(state, req.store.clone()).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let cart_uuid = CartId::parse(&req.cart_id)?;
+
+    let mut tx = state.db.begin().await.map_err(CartError::from)?;
+    let cart_row = sqlx::query(
+        r#"
+        SELECT customer_id::text as customer_id, anonymous_token, status
+        FROM carts
+        WHERE id = $1 AND store_id = $2
+        LIMIT 1
+        FOR UPDATE
+        "#,
+    )
+    .bind(cart_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(CartError::from)?;
+    let Some(cart_row) = cart_row else {
+        return Err(CartError::not_found("cart not found"));
+    };
+    authorize_cart(
+        access,
+        cart_row.get::<Option<String>, _>("customer_id").as_deref(),
+        cart_row.get("anonymous_token"),
+    )?;
+    if cart_row.get::<String, _>("status") != CartStatus::Active.as_str() {
+        return Err(CartError::failed_precondition("cart is not active"));
+    }
+
+    let removed = sqlx::query("DELETE FROM cart_promotions WHERE cart_id = $1")
+        .bind(cart_uuid.as_uuid())
+        .execute(&mut *tx)
+        .await
+        .map_err(CartError::from)?
+        .rows_affected()
+        > 0;
+    if removed {
+        touch_cart_activity_tx(&mut tx, &req.cart_id).await?;
+    }
+    tx.commit().await.map_err(CartError::from)?;
+
+    Ok(pb::RemovePromotionCodeResponse { removed })
+}
+
 async fn fetch_inventory_stock_tx(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     store_id: uuid::Uuid,
@@ -896,3 +1411,210 @@ async fn insert_inventory_movement_tx(
     .map_err(CartError::from)?;
     Ok(())
 }
+
+async fn touch_cart_activity_tx(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, cart_id: &str) -> CartResult<()> {
+    sqlx::query("UPDATE carts SET last_activity_at = now(), abandoned_at = NULL, updated_at = now() WHERE id = $1")
+        .bind(CartId::parse(cart_id)?.as_uuid())
+        .execute(&mut **tx)
+        .await
+        .map_err(CartError::from)?;
+    Ok(())
+}
+
+pub async fn run_cart_abandonment(state: &AppState, window_hours: i64, batch_size: i64) -> CartResult<usize> {
+    let mut tx = state.db.begin().await.map_err(CartError::from)?;
+    let rows = sqlx::query(
+        r#"
+        SELECT c.id::text as cart_id,
+               c.store_id::text as store_id,
+               s.tenant_id::text as tenant_id,
+               c.customer_id::text as customer_id,
+               items.item_count,
+               items.total_amount,
+               items.currency
+        FROM carts c
+        JOIN stores s ON s.id = c.store_id
+        JOIN LATERAL (
+            SELECT SUM(ci.quantity)::bigint as item_count,
+                   SUM(ci.unit_price_amount * ci.quantity)::bigint as total_amount,
+                   MIN(ci.unit_price_currency) as currency
+            FROM cart_items ci
+            WHERE ci.cart_id = c.id AND ci.status = $1
+        ) items ON items.item_count > 0
+        WHERE c.status = $2
+          AND c.abandoned_at IS NULL
+          AND c.last_activity_at < now() - make_interval(hours => $3)
+          AND c.expires_at > now()
+        ORDER BY c.last_activity_at ASC
+        LIMIT $4
+        FOR UPDATE OF c SKIP LOCKED
+        "#,
+    )
+    .bind(CartItemStatus::Active.as_str())
+    .bind(CartStatus::Active.as_str())
+    .bind(window_hours as i32)
+    .bind(batch_size)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(CartError::from)?;
+
+    let count = rows.len();
+    for row in rows {
+        let cart_id: String = row.get("cart_id");
+        let store_id: String = row.get("store_id");
+        outbox::enqueue_tx(
+            &mut tx,
+            outbox::OutboxEventInput {
+                tenant_id: row.get("tenant_id"),
+                store_id: Some(store_id.clone()),
+                aggregate_type: "cart".to_string(),
+                aggregate_id: cart_id.clone(),
+                event_type: CART_ABANDONED_EVENT_TYPE.to_string(),
+                payload_json: serde_json::json!({
+                    "cart_id": cart_id,
+                    "store_id": store_id,
+                    "customer_id": row.get::<Option<String>, _>("customer_id"),
+                    "item_count": row.get::<i64, _>("item_count"),
+                    "estimated_total": {
+                        "amount": row.get::<i64, _>("total_amount"),
+                        "currency": row.get::<String, _>("currency"),
+                    },
+                }),
+            },
+        )
+        .await?;
+        sqlx::query("UPDATE carts SET abandoned_at = now() WHERE id = $1")
+            .bind(CartId::parse(&cart_id)?.as_uuid())
+            .execute(&mut *tx)
+            .await
+            .map_err(CartError::from)?;
+    }
+
+    tx.commit().await.map_err(CartError::from)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support::{self, TestStore};
+
+    fn access(customer_id: Option<&str>, cart_token: Option<&str>) -> CartAccess {
+        CartAccess {
+            customer_id: customer_id.map(str::to_string),
+            cart_token: cart_token.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn customer_cart_requires_the_owner() {
+        let owner = uuid::Uuid::new_v4().to_string();
+        assert!(authorize_cart(&access(Some(&owner), None), Some(&owner), None).is_ok());
+        assert!(authorize_cart(&access(Some("someone-else"), None), Some(&owner), None).is_err());
+        assert!(authorize_cart(&access(None, None), Some(&owner), None).is_err());
+    }
+
+    #[test]
+    fn anonymous_cart_requires_its_token() {
+        let token = uuid::Uuid::new_v4();
+        let other = uuid::Uuid::new_v4().to_string();
+        assert!(authorize_cart(&access(None, Some(&token.to_string())), None, Some(token)).is_ok());
+        assert!(authorize_cart(&access(None, Some(&other)), None, Some(token)).is_err());
+        assert!(authorize_cart(&access(None, None), None, Some(token)).is_err());
+        assert!(authorize_cart(&access(None, Some("not-a-uuid")), None, Some(token)).is_err());
+    }
+
+    #[test]
+    fn guest_cart_without_a_token_is_rejected() {
+        let owner = uuid::Uuid::new_v4().to_string();
+        assert!(authorize_cart(&access(None, None), None, None).is_err());
+        assert!(authorize_cart(&access(Some(&owner), None), None, None).is_err());
+    }
+
+    // An anonymous cart holding the percent code SPRING10, and the token that opens it.
+    async fn cart_with_code(db: &PgPool) -> (TestStore, uuid::Uuid, CartAccess) {
+        let store = test_support::create_store(db).await;
+        let token = uuid::Uuid::new_v4();
+        let cart_id: uuid::Uuid =
+            sqlx::query_scalar("INSERT INTO carts (store_id, anonymous_token) VALUES ($1, $2) RETURNING id")
+                .bind(store.store_id)
+                .bind(token)
+                .fetch_one(db)
+                .await
+                .unwrap();
+        let promotion_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO promotions (tenant_id, code, discount_type, value_amount, value_currency, status, percent_off)
+            VALUES ($1, 'SPRING', 'percent', 0, 'JPY', 'active', 10)
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let code_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO promotion_codes (promotion_id, store_id, code) VALUES ($1, $2, 'SPRING10') RETURNING id",
+        )
+        .bind(promotion_id)
+        .bind(store.store_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO cart_promotions (cart_id, promotion_code_id, promotion_id) VALUES ($1, $2, $3)")
+            .bind(cart_id)
+            .bind(code_id)
+            .bind(promotion_id)
+            .execute(db)
+            .await
+            .unwrap();
+        (store, cart_id, access(None, Some(&token.to_string())))
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn applying_a_code_to_a_cart_that_has_one_is_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, cart_id, guest) = cart_with_code(&db).await;
+
+        let err = apply_promotion_code(
+            &state,
+            pb::ApplyPromotionCodeRequest {
+                store: store.store_context(),
+                cart_id: cart_id.to_string(),
+                code: "spring10".to_string(),
+            },
+            &guest,
+        )
+        .await
+        .expect_err("second code should be rejected");
+        assert!(matches!(err, CartError::AlreadyExists(_)), "{err:?}");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn removing_the_code_frees_the_cart(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, cart_id, guest) = cart_with_code(&db).await;
+        let req = pb::RemovePromotionCodeRequest {
+            store: store.store_context(),
+            cart_id: cart_id.to_string(),
+        };
+
+        let err = remove_promotion_code(&state, req.clone(), &access(None, None))
+            .await
+            .expect_err("removal needs the cart token");
+        assert!(matches!(err, CartError::PermissionDenied(_)), "{err:?}");
+
+        let first = remove_promotion_code(&state, req.clone(), &guest).await.unwrap();
+        let second = remove_promotion_code(&state, req, &guest).await.unwrap();
+        assert!(first.removed);
+        assert!(!second.removed);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cart_promotions WHERE cart_id = $1")
+            .bind(cart_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
@@ -19,45 +19,27 @@ impl CustomerError {
         match self {
             CustomerError::InvalidArgument(message) => (
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: ErrorCode::InvalidArgument,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
             ),
             CustomerError::NotFound(message) => (
                 StatusCode::NOT_FOUND,
-                Json(ConnectError {
-                    code: ErrorCode::NotFound,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::NotFound, message)),
             ),
             CustomerError::AlreadyExists(message) => (
                 StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::AlreadyExists,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::AlreadyExists, message)),
             ),
             CustomerError::PermissionDenied(message) => (
                 StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: ErrorCode::PermissionDenied,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::PermissionDenied, message)),
             ),
             CustomerError::Unauthenticated(message) => (
                 StatusCode::UNAUTHORIZED,
-                Json(ConnectError {
-                    code: ErrorCode::Unauthenticated,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Unauthenticated, message)),
             ),
             CustomerError::Internal(message) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: ErrorCode::Internal,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, message)),
             ),
         }
     }
@@ -1,16 +1,18 @@
 use chrono::Utc;
+use rs_common::identity::normalize_identity;
 use sqlx::Row;
 
 use crate::{
     AppState,
     customer::error::{CustomerError, CustomerResult},
-    infrastructure::{audit, metafields, outbox},
+    infrastructure::{metafields, outbox},
     pb::pb,
     shared::validation::{Email, Phone},
     shared::{
         audit_action::CustomerAuditAction,
         audit_helpers::{audit_input, to_json_opt},
         ids::{StoreId, TenantId, parse_uuid},
+        pagination::{MAX_PAGE_SIZE, encode_page_token, page_params},
         time::chrono_to_timestamp,
     },
 };
@@ -18,6 +20,7 @@ use crate::{
 const DEFAULT_PROFILE_STATUS: &str = "active";
 const DEFAULT_CUSTOMER_STATUS: &str = "active";
 const METAFIELD_OWNER_TYPE_CUSTOMER: &str = "customer";
+const SEARCH_CUSTOMERS_LIMIT: i64 = 50;
 
 pub async fn list_customers(
     state: &AppState,
@@ -29,60 +32,110 @@ pub async fn list_customers(
     let store_uuid = StoreId::parse(&store_id).map_err(CustomerError::from)?;
     let tenant_uuid = TenantId::parse(&tenant_id).map_err(CustomerError::from)?;
     let q = query.trim();
-    let (limit, offset) = page_params(page);
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE).map_err(CustomerError::from)?;
+    let pattern = (!q.is_empty()).then(|| format!("%{}%", q));
 
-    let rows = if q.is_empty() {
-        sqlx::query(
-            r#"
-            SELECT c.id::text as customer_id,
-                   cp.id::text as profile_id,
-                   cp.store_id::text as store_id,
-                   cp.name, cp.email, cp.phone, cp.status,
-                   c.created_at
-            FROM customers c
-            JOIN customer_profiles cp ON cp.customer_id = c.id
-            WHERE c.tenant_id = $1
-              AND cp.store_id = $2
-            ORDER BY c.created_at DESC
-            LIMIT $3 OFFSET $4
-            "#,
-        )
-        .bind(tenant_uuid.as_uuid())
-        .bind(store_uuid.as_uuid())
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await
-        .map_err(CustomerError::from)?
-    } else {
-        let pattern = format!("%{}%", q);
-        sqlx::query(
-            r#"
-            SELECT c.id::text as customer_id,
+    let mut rows = sqlx::query(
+        r#"
+        SELECT c.id::text as customer_id,
+               c.id as cursor_id,
+               cp.id::text as profile_id,
+               cp.store_id::text as store_id,
+               cp.name, cp.email, cp.phone, cp.status,
+               c.created_at
+        FROM customers c
+        JOIN customer_profiles cp ON cp.customer_id = c.id
+        WHERE c.tenant_id = $1
+          AND cp.store_id = $2
+          AND ($3::text IS NULL OR cp.name ILIKE $3 OR cp.email ILIKE $3 OR cp.phone ILIKE $3)
+          AND ($4::timestamptz IS NULL OR (c.created_at, c.id) < ($4, $5))
+        ORDER BY c.created_at DESC, c.id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(tenant_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .bind(pattern)
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
+    .fetch_all(&state.db)
+    .await
+    .map_err(CustomerError::from)?;
+
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("cursor_id"));
+        }
+    }
+    let customers: Vec<pb::CustomerSummary> = rows
+        .into_iter()
+        .map(|row| pb::CustomerSummary {
+            customer_id: row.get("customer_id"),
+            profile_id: row.get("profile_id"),
+            store_id: row.get("store_id"),
+            name: row.get::<String, _>("name"),
+            email: row.get::<Option<String>, _>("email").unwrap_or_default(),
+            phone: row.get::<Option<String>, _>("phone").unwrap_or_default(),
+            status: row.get("status"),
+            created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
+        })
+        .collect();
+    Ok((customers, pb::PageResult { next_page_token }))
+}
+
+pub async fn search_customers(
+    state: &AppState,
+    store_id: String,
+    tenant_id: String,
+    query: String,
+    status: String,
+) -> CustomerResult<Vec<pb::CustomerSummary>> {
+    let store_uuid = StoreId::parse(&store_id).map_err(CustomerError::from)?;
+    let tenant_uuid = TenantId::parse(&tenant_id).map_err(CustomerError::from)?;
+    let q = query.trim();
+    if q.is_empty() {
+        return Err(CustomerError::InvalidArgument("query is required".to_string()));
+    }
+    let status = status.trim();
+    let pattern = format!("%{}%", q);
+
+    // A customer with several matching identities must only appear once.
+    let rows = sqlx::query(
+        r#"
+        SELECT customer_id, profile_id, store_id, name, email, phone, status, created_at
+        FROM (
+            SELECT DISTINCT ON (cp.customer_id)
+                   c.id::text as customer_id,
                    cp.id::text as profile_id,
                    cp.store_id::text as store_id,
                    cp.name, cp.email, cp.phone, cp.status,
                    c.created_at
             FROM customers c
             JOIN customer_profiles cp ON cp.customer_id = c.id
+            LEFT JOIN customer_identities ci ON ci.customer_id = c.id AND ci.tenant_id = c.tenant_id
             WHERE c.tenant_id = $1
               AND cp.store_id = $2
-              AND (cp.name ILIKE $3 OR cp.email ILIKE $3 OR cp.phone ILIKE $3)
-            ORDER BY c.created_at DESC
-            LIMIT $4 OFFSET $5
-            "#,
-        )
-        .bind(tenant_uuid.as_uuid())
-        .bind(store_uuid.as_uuid())
-        .bind(pattern)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&state.db)
-        .await
-        .map_err(CustomerError::from)?
-    };
+              AND (cp.name ILIKE $3 OR cp.email ILIKE $3 OR cp.phone ILIKE $3 OR ci.identity_value ILIKE $3)
+              AND ($4 = '' OR cp.status = $4)
+            ORDER BY cp.customer_id
+        ) matched
+        ORDER BY created_at DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(tenant_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .bind(pattern)
+    .bind(status)
+    .bind(SEARCH_CUSTOMERS_LIMIT)
+    .fetch_all(&state.db)
+    .await
+    .map_err(CustomerError::from)?;
 
-    let customers: Vec<pb::CustomerSummary> = rows
+    Ok(rows
         .into_iter()
         .map(|row| pb::CustomerSummary {
             customer_id: row.get("customer_id"),
@@ -94,23 +147,7 @@ pub async fn list_customers(
             status: row.get("status"),
             created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
         })
-        .collect();
-
-    let mut next_page_token = String::new();
-    if (customers.len() as i64) == limit {
-        next_page_token = (offset + limit).to_string();
-    }
-    Ok((customers, pb::PageResult { next_page_token }))
-}
-
-fn page_params(page: Option<pb::PageInfo>) -> (i64, i64) {
-    let page = page.unwrap_or(pb::PageInfo {
-        page_size: 50,
-        page_token: String::new(),
-    });
-    let limit = (page.page_size.max(1).min(200)) as i64;
-    let offset = page.page_token.parse::<i64>().unwrap_or(0).max(0);
-    (limit, offset)
+        .collect())
 }
 
 pub async fn get_customer(
@@ -454,44 +491,23 @@ pub async fn create_customer(
         updated_at: chrono_to_timestamp(Some(profile_row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            CustomerAuditAction::Create.into(),
-            Some("customer"),
-            Some(customer.id.clone()),
-            None,
-            to_json_opt(Some(profile.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                CustomerAuditAction::Create,
+                Some("customer"),
+                Some(customer.id.clone()),
+                None,
+                to_json_opt(Some(profile.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
-    outbox::enqueue_tx(
-        &mut tx,
-        outbox::OutboxEventInput {
-            tenant_id: customer.tenant_id.clone(),
-            store_id: Some(store_id.clone()),
-            aggregate_type: "customer".to_string(),
-            aggregate_id: customer.id.clone(),
-            event_type: "customer.profile_upsert".to_string(),
-            payload_json: serde_json::json!({
-                "tenant_id": customer.tenant_id,
-                "source_store_id": store_id,
-                "customer_id": customer.id,
-                "profile": {
-                    "name": profile.name.clone(),
-                    "email": profile.email.clone(),
-                    "phone": profile.phone.clone(),
-                    "status": profile.status.clone(),
-                    "notes": profile.notes.clone(),
-                    "country_code": profile.country_code.clone(),
-                }
-            }),
-        },
-    )
-    .await?;
+    outbox::enqueue_tx(&mut tx, profile_upsert_event(&customer, &store_id, &profile, false)).await?;
 
     for identity in identity_inputs {
         outbox::enqueue_tx(
@@ -529,8 +545,19 @@ pub async fn update_customer(
     customer_id: String,
     mut profile: pb::CustomerProfileInput,
     customer_status: String,
+    force_sync: bool,
     actor: Option<pb::ActorContext>,
 ) -> CustomerResult<(pb::Customer, pb::CustomerProfile)> {
+    // The route already requires CustomersWrite; forcing an overwrite is additionally limited to store staff.
+    if force_sync
+        && !actor
+            .as_ref()
+            .is_some_and(|actor| matches!(actor.actor_type.as_str(), "staff" | "owner"))
+    {
+        return Err(CustomerError::PermissionDenied(
+            "force_sync requires a staff actor".to_string(),
+        ));
+    }
     let store_uuid = StoreId::parse(&store_id).map_err(CustomerError::from)?;
     let tenant_uuid = TenantId::parse(&tenant_id).map_err(CustomerError::from)?;
     let customer_uuid = parse_uuid(&customer_id, "customer_id").map_err(CustomerError::from)?;
@@ -648,42 +675,25 @@ pub async fn update_customer(
         updated_at: chrono_to_timestamp(Some(profile_row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            CustomerAuditAction::Update.into(),
-            Some("customer_profile"),
-            Some(profile.id.clone()),
-            None,
-            to_json_opt(Some(profile.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                CustomerAuditAction::Update,
+                Some("customer_profile"),
+                Some(profile.id.clone()),
+                None,
+                to_json_opt(Some(profile.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     outbox::enqueue_tx(
         &mut tx,
-        outbox::OutboxEventInput {
-            tenant_id: customer.tenant_id.clone(),
-            store_id: Some(store_id.clone()),
-            aggregate_type: "customer".to_string(),
-            aggregate_id: customer.id.clone(),
-            event_type: "customer.profile_upsert".to_string(),
-            payload_json: serde_json::json!({
-                "tenant_id": customer.tenant_id,
-                "source_store_id": store_id,
-                "customer_id": customer.id,
-                "profile": {
-                    "name": profile.name.clone(),
-                    "email": profile.email.clone(),
-                    "phone": profile.phone.clone(),
-                    "status": profile.status.clone(),
-                    "notes": profile.notes.clone(),
-                    "country_code": profile.country_code.clone(),
-                }
-            }),
-        },
+        profile_upsert_event(&customer, &store_id, &profile, force_sync),
     )
     .await?;
 
@@ -758,19 +768,21 @@ pub async fn upsert_customer_identity(
             created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
         };
 
-        audit::record_tx(
-            &mut tx,
-            audit_input(
-                None,
-                CustomerAuditAction::IdentityUpsert.into(),
-                Some("customer_identity"),
-                Some(updated.id.clone()),
-                None,
-                to_json_opt(Some(updated.clone())),
-                actor,
-            ),
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    None,
+                    CustomerAuditAction::IdentityUpsert,
+                    Some("customer_identity"),
+                    Some(updated.id.clone()),
+                    None,
+                    to_json_opt(Some(updated.clone())),
+                    actor,
+                ),
+            )
+            .await?;
 
         outbox::enqueue_tx(
             &mut tx,
@@ -842,19 +854,21 @@ pub async fn upsert_customer_identity(
         created_at: chrono_to_timestamp(Some(Utc::now())),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            CustomerAuditAction::IdentityUpsert.into(),
-            Some("customer_identity"),
-            Some(created.id.clone()),
-            None,
-            to_json_opt(Some(created.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                CustomerAuditAction::IdentityUpsert,
+                Some("customer_identity"),
+                Some(created.id.clone()),
+                None,
+                to_json_opt(Some(created.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     outbox::enqueue_tx(
         &mut tx,
@@ -1016,31 +1030,46 @@ pub async fn upsert_customer_address(
     .map_err(CustomerError::from)?;
     let tenant_id: String = tenant_row.get("tenant_id");
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            CustomerAuditAction::AddressUpsert.into(),
-            Some("customer_address"),
-            Some(updated.id.clone()),
-            None,
-            to_json_opt(Some(updated.clone())),
-            actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                CustomerAuditAction::AddressUpsert,
+                Some("customer_address"),
+                Some(updated.id.clone()),
+                None,
+                to_json_opt(Some(updated.clone())),
+                actor,
+            ),
+        )
+        .await?;
 
     outbox::enqueue_tx(
         &mut tx,
         outbox::OutboxEventInput {
-            tenant_id,
+            tenant_id: tenant_id.clone(),
             store_id: None,
             aggregate_type: "customer".to_string(),
             aggregate_id: updated.customer_id.clone(),
             event_type: "customer.address_upsert".to_string(),
             payload_json: serde_json::json!({
+                "tenant_id": tenant_id,
+                "source_store_id": serde_json::Value::Null,
                 "customer_id": updated.customer_id,
                 "address_id": updated.id.clone(),
+                "address": {
+                    "type": updated.r#type.clone(),
+                    "name": updated.name.clone(),
+                    "postal_code": updated.postal_code.clone(),
+                    "prefecture": updated.prefecture.clone(),
+                    "city": updated.city.clone(),
+                    "line1": updated.line1.clone(),
+                    "line2": updated.line2.clone(),
+                    "phone": updated.phone.clone(),
+                    "country_code": updated.country_code.clone(),
+                }
             }),
         },
     )
@@ -1050,6 +1079,86 @@ pub async fn upsert_customer_address(
     Ok(updated)
 }
 
+pub async fn delete_customer_address(
+    state: &AppState,
+    store_id: String,
+    customer_id: String,
+    address_id: String,
+    actor: Option<pb::ActorContext>,
+) -> CustomerResult<bool> {
+    let store_uuid = StoreId::parse(&store_id).map_err(CustomerError::from)?;
+    let customer_uuid = parse_uuid(&customer_id, "customer_id").map_err(CustomerError::from)?;
+    let address_uuid = parse_uuid(&address_id, "address_id").map_err(CustomerError::from)?;
+
+    let mut tx = state.db.begin().await.map_err(CustomerError::from)?;
+    // The customer must have a profile in the requesting store; addresses themselves are tenant-wide.
+    let row = sqlx::query(
+        r#"
+        SELECT a.id::text as id, a.customer_id::text as customer_id, a.type, a.name,
+               a.postal_code, a.prefecture, a.city, a.line1, a.line2, a.phone, a.country_code,
+               a.created_at, a.updated_at
+        FROM customer_addresses a
+        WHERE a.id = $1 AND a.customer_id = $2
+          AND EXISTS (
+              SELECT 1 FROM customer_profiles cp
+              WHERE cp.customer_id = a.customer_id AND cp.store_id = $3
+          )
+        FOR UPDATE OF a
+        "#,
+    )
+    .bind(address_uuid)
+    .bind(customer_uuid)
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(CustomerError::from)?;
+    let Some(row) = row else {
+        return Err(CustomerError::NotFound("address not found".to_string()));
+    };
+    let before = pb::CustomerAddress {
+        id: row.get("id"),
+        customer_id: row.get("customer_id"),
+        r#type: row.get("type"),
+        name: row.get("name"),
+        postal_code: row.get("postal_code"),
+        prefecture: row.get("prefecture"),
+        city: row.get("city"),
+        line1: row.get("line1"),
+        line2: row.get::<Option<String>, _>("line2").unwrap_or_default(),
+        phone: row.get::<Option<String>, _>("phone").unwrap_or_default(),
+        country_code: row
+            .get::<Option<String>, _>("country_code")
+            .unwrap_or_else(|| "JP".to_string()),
+        created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
+        updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
+    };
+
+    sqlx::query("DELETE FROM customer_addresses WHERE id = $1")
+        .bind(address_uuid)
+        .execute(tx.as_mut())
+        .await
+        .map_err(CustomerError::from)?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id),
+                CustomerAuditAction::AddressDelete,
+                Some("customer_address"),
+                Some(before.id.clone()),
+                to_json_opt(Some(before)),
+                None,
+                actor,
+            ),
+        )
+        .await?;
+
+    tx.commit().await.map_err(CustomerError::from)?;
+    Ok(true)
+}
+
 fn metafield_definition_from_record(record: &metafields::MetafieldDefinitionRecord) -> pb::MetafieldDefinition {
     pb::MetafieldDefinition {
         id: record.id.clone(),
@@ -1274,6 +1383,37 @@ pub async fn upsert_customer_metafield_value(
     Ok(())
 }
 
+/// Builds the `customer.profile_upsert` outbox event. With `force` set, the sync worker overwrites
+/// the profile in every target store instead of only filling empty fields.
+fn profile_upsert_event(
+    customer: &pb::Customer,
+    store_id: &str,
+    profile: &pb::CustomerProfile,
+    force: bool,
+) -> outbox::OutboxEventInput {
+    outbox::OutboxEventInput {
+        tenant_id: customer.tenant_id.clone(),
+        store_id: Some(store_id.to_string()),
+        aggregate_type: "customer".to_string(),
+        aggregate_id: customer.id.clone(),
+        event_type: "customer.profile_upsert".to_string(),
+        payload_json: serde_json::json!({
+            "tenant_id": customer.tenant_id,
+            "source_store_id": store_id,
+            "customer_id": customer.id,
+            "force": force,
+            "profile": {
+                "name": profile.name.clone(),
+                "email": profile.email.clone(),
+                "phone": profile.phone.clone(),
+                "status": profile.status.clone(),
+                "notes": profile.notes.clone(),
+                "country_code": profile.country_code.clone(),
+            }
+        }),
+    }
+}
+
 fn validate_customer_profile(
     profile: &pb::CustomerProfileInput,
     identities: &Vec<pb::CustomerIdentityInput>,
@@ -1313,11 +1453,168 @@ fn normalize_identities(
         .collect()
 }
 
-fn normalize_identity(identity_type: &str, value: &str) -> String {
-    let trimmed = value.trim();
-    match identity_type {
-        "email" => trimmed.to_lowercase(),
-        "phone" => trimmed.chars().filter(|c| c.is_ascii_digit()).collect(),
-        _ => trimmed.to_string(),
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::{audit_action::AuditAction, test_support};
+
+    /// A customer of the store's tenant with a profile in that store.
+    async fn create_customer(db: &PgPool, store: &test_support::TestStore, name: &str, email: &str) -> uuid::Uuid {
+        let customer_id: uuid::Uuid = sqlx::query_scalar("INSERT INTO customers (tenant_id) VALUES ($1) RETURNING id")
+            .bind(store.tenant_id)
+            .fetch_one(db)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO customer_profiles (customer_id, store_id, name, email) VALUES ($1, $2, $3, $4)")
+            .bind(customer_id)
+            .bind(store.store_id)
+            .bind(name)
+            .bind(email)
+            .execute(db)
+            .await
+            .unwrap();
+        customer_id
+    }
+
+    async fn create_address(db: &PgPool, customer_id: uuid::Uuid) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO customer_addresses (customer_id, type, name, postal_code, prefecture, city, line1)
+            VALUES ($1, 'shipping', 'Hanako', '100-0001', 'Tokyo', 'Chiyoda', '1-1')
+            RETURNING id
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn address_is_deleted_only_through_its_customer_and_store(db: PgPool) {
+        let recorder = test_support::RecordingAuditWriter::default();
+        let mut state = test_support::app_state(db.clone());
+        state.audit_writer = std::sync::Arc::new(recorder.clone());
+        let store = test_support::create_store(&db).await;
+        let other_store = test_support::create_store(&db).await;
+        let customer_id = create_customer(&db, &store, "Hanako", "hanako@example.com").await;
+        let other_customer = create_customer(&db, &store, "Taro", "taro@example.com").await;
+        let address_id = create_address(&db, customer_id).await;
+
+        for (store_id, owner) in [(other_store.store_id, customer_id), (store.store_id, other_customer)] {
+            let err = delete_customer_address(
+                &state,
+                store_id.to_string(),
+                owner.to_string(),
+                address_id.to_string(),
+                None,
+            )
+            .await
+            .expect_err("address is outside this customer or store");
+            assert!(matches!(err, CustomerError::NotFound(_)), "{err:?}");
+        }
+        assert!(recorder.events.lock().unwrap().is_empty());
+
+        let deleted = delete_customer_address(
+            &state,
+            store.store_id.to_string(),
+            customer_id.to_string(),
+            address_id.to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(deleted);
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM customer_addresses")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        let events = recorder.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::CustomerAddressDelete);
+        assert_eq!(events[0].target_id.as_deref(), Some(address_id.to_string().as_str()));
+        assert_eq!(
+            events[0].before_json.as_ref().and_then(|before| before.get("city")),
+            Some(&serde_json::json!("Chiyoda"))
+        );
+
+        let err = delete_customer_address(
+            &state,
+            store.store_id.to_string(),
+            customer_id.to_string(),
+            address_id.to_string(),
+            None,
+        )
+        .await
+        .expect_err("address is already gone");
+        assert!(matches!(err, CustomerError::NotFound(_)), "{err:?}");
+    }
+
+    async fn add_identity(db: &PgPool, store: &test_support::TestStore, customer_id: uuid::Uuid, value: &str) {
+        sqlx::query(
+            r#"
+            INSERT INTO customer_identities (customer_id, tenant_id, identity_type, identity_value, source)
+            VALUES ($1, $2, 'line_id', $3, 'admin')
+            "#,
+        )
+        .bind(customer_id)
+        .bind(store.tenant_id)
+        .bind(value)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    async fn search(
+        state: &AppState,
+        store: &test_support::TestStore,
+        query: &str,
+        status: &str,
+    ) -> CustomerResult<Vec<uuid::Uuid>> {
+        let customers = search_customers(
+            state,
+            store.store_id.to_string(),
+            store.tenant_id.to_string(),
+            query.to_string(),
+            status.to_string(),
+        )
+        .await?;
+        Ok(customers
+            .iter()
+            .map(|customer| uuid::Uuid::parse_str(&customer.customer_id).unwrap())
+            .collect())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn search_matches_profiles_and_identities_once_per_customer(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let hanako = create_customer(&db, &store, "Hanako Sato", "hanako@example.com").await;
+        let taro = create_customer(&db, &store, "Taro Suzuki", "taro@example.com").await;
+        add_identity(&db, &store, taro, "sato-fan").await;
+        add_identity(&db, &store, taro, "sato-fan-2").await;
+        let other_store = test_support::create_store(&db).await;
+        create_customer(&db, &other_store, "Jiro Sato", "jiro@example.com").await;
+
+        assert_eq!(search(&state, &store, "HANAKO@", "").await.unwrap(), vec![hanako]);
+        let mut matches = search(&state, &store, "sato", "").await.unwrap();
+        matches.sort();
+        let mut expected = vec![hanako, taro];
+        expected.sort();
+        assert_eq!(matches, expected);
+
+        sqlx::query("UPDATE customer_profiles SET status = 'inactive' WHERE customer_id = $1")
+            .bind(taro)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(search(&state, &store, "sato", "inactive").await.unwrap(), vec![taro]);
+
+        let err = search(&state, &store, "  ", "").await.unwrap_err();
+        assert!(matches!(err, CustomerError::InvalidArgument(_)), "{err:?}");
     }
 }
@@ -1,15 +1,15 @@
-use axum::{Json, http::StatusCode};
-
-use crate::rpc::json::{ConnectError, ErrorCode};
+use crate::pb::pb;
 
 #[derive(Debug)]
 pub enum IdentityError {
     InvalidArgument(String),
+    InvalidFields(String, Vec<pb::FieldViolation>),
     NotFound(String),
     AlreadyExists(String),
     FailedPrecondition(String),
     PermissionDenied(String),
     Unauthenticated(String),
+    ResourceExhausted(String),
     Internal(String),
 }
 
@@ -40,62 +40,12 @@ impl IdentityError {
         IdentityError::Unauthenticated(message.into())
     }
 
-    pub fn internal(message: impl Into<String>) -> Self {
-        IdentityError::Internal(message.into())
+    pub fn resource_exhausted(message: impl Into<String>) -> Self {
+        IdentityError::ResourceExhausted(message.into())
     }
 
-    pub fn into_connect(self) -> (StatusCode, Json<ConnectError>) {
-        match self {
-            IdentityError::InvalidArgument(message) => (
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: ErrorCode::InvalidArgument,
-                    message,
-                }),
-            ),
-            IdentityError::NotFound(message) => (
-                StatusCode::NOT_FOUND,
-                Json(ConnectError {
-                    code: ErrorCode::NotFound,
-                    message,
-                }),
-            ),
-            IdentityError::AlreadyExists(message) => (
-                StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::AlreadyExists,
-                    message,
-                }),
-            ),
-            IdentityError::FailedPrecondition(message) => (
-                StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::FailedPrecondition,
-                    message,
-                }),
-            ),
-            IdentityError::PermissionDenied(message) => (
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: ErrorCode::PermissionDenied,
-                    message,
-                }),
-            ),
-            IdentityError::Unauthenticated(message) => (
-                StatusCode::UNAUTHORIZED,
-                Json(ConnectError {
-                    code: ErrorCode::Unauthenticated,
-                    message,
-                }),
-            ),
-            IdentityError::Internal(message) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: ErrorCode::Internal,
-                    message,
-                }),
-            ),
-        }
+    pub fn internal(message: impl Into<String>) -> Self {
+        IdentityError::Internal(message.into())
     }
 }
 
@@ -104,26 +54,3 @@ impl From<sqlx::Error> for IdentityError {
         IdentityError::Internal(format!("db error: {}", err))
     }
 }
-
-impl From<(StatusCode, Json<ConnectError>)> for IdentityError {
-    fn from(value: (StatusCode, Json<ConnectError>)) -> Self {
-        let (status, Json(err)) = value;
-        match err.code {
-            ErrorCode::InvalidArgument => IdentityError::InvalidArgument(err.message),
-            ErrorCode::NotFound => IdentityError::NotFound(err.message),
-            ErrorCode::AlreadyExists => IdentityError::AlreadyExists(err.message),
-            ErrorCode::FailedPrecondition => IdentityError::FailedPrecondition(err.message),
-            ErrorCode::PermissionDenied => IdentityError::PermissionDenied(err.message),
-            ErrorCode::Unauthenticated => IdentityError::Unauthenticated(err.message),
-            ErrorCode::Internal => IdentityError::Internal(err.message),
-            _ => match status {
-                StatusCode::BAD_REQUEST => IdentityError::InvalidArgument(err.message),
-                StatusCode::NOT_FOUND => IdentityError::NotFound(err.message),
-                StatusCode::CONFLICT => IdentityError::AlreadyExists(err.message),
-                StatusCode::FORBIDDEN => IdentityError::PermissionDenied(err.message),
-                StatusCode::UNAUTHORIZED => IdentityError::Unauthenticated(err.message),
-                _ => IdentityError::Internal(err.message),
-            },
-        }
-    }
-}
@@ -1,11 +1,10 @@
-//! Identity domain boundary (planned).
+//! Identity domain boundary.
 //!
-//! 現時点では `AuthService` / `StoreStaff` / `Permissions` に分散している
-//! 認証・スタッフ・ロール/権限のロジックを、将来的にこのモジュールへ統合する。
-//! いまは `src/rpc/identity.rs` が既存サービスへのファサードになっているため、
-//! ここは空のままにしている。
+//! 認証・スタッフ・ロール/権限のユースケースはすべて `service` に集約している。
+//! `src/rpc/identity.rs` はリクエストの復元・Cookie の付け外し・`IdentityError` の
+//! ConnectError 変換だけを行う薄いハンドラ。
 //!
-//! 統合方針は `docs/architecture/identity.md` を参照。
+//! 詳細は `docs/architecture/identity.md` を参照。
 
 pub mod context;
 pub mod error;
@@ -26,6 +26,7 @@ pub struct StaffAuthRow {
     pub staff_id: String,
     pub password_hash: Option<String>,
     pub role_key: String,
+    pub status: String,
 }
 
 pub struct RoleRow {
@@ -158,7 +159,7 @@ pub trait IdentityRepository {
 
     async fn invite_exists_by_email(&self, store_uuid: &uuid::Uuid, email: &str) -> IdentityResult<bool>;
 
-    async fn fetch_invite_by_token(&self, token: &str) -> IdentityResult<Option<StaffInviteRow>>;
+    async fn fetch_invite_by_token_hash(&self, token_hash: &str) -> IdentityResult<Option<StaffInviteRow>>;
 
     async fn insert_staff_invite_tx<'e, E>(
         &self,
@@ -168,7 +169,7 @@ pub trait IdentityRepository {
         store_uuid: &uuid::Uuid,
         email: &str,
         role_uuid: &uuid::Uuid,
-        token: &str,
+        token_hash: &str,
         created_by: Option<uuid::Uuid>,
         expires_at: chrono::DateTime<chrono::Utc>,
         display_name: Option<&str>,
@@ -180,19 +181,20 @@ pub trait IdentityRepository {
 
     async fn staff_status(&self, store_uuid: &uuid::Uuid, staff_uuid: &uuid::Uuid) -> IdentityResult<Option<String>>;
 
-    async fn fetch_active_staff_by_email(
+    /// Sign-in lookups also return locked staff so the caller can report the lockout.
+    async fn fetch_sign_in_staff_by_email(
         &self,
         store_uuid: &uuid::Uuid,
         email: &str,
     ) -> IdentityResult<Option<StaffAuthRow>>;
 
-    async fn fetch_active_staff_by_login_id(
+    async fn fetch_sign_in_staff_by_login_id(
         &self,
         store_uuid: &uuid::Uuid,
         login_id: &str,
     ) -> IdentityResult<Option<StaffAuthRow>>;
 
-    async fn fetch_active_staff_by_phone(
+    async fn fetch_sign_in_staff_by_phone(
         &self,
         store_uuid: &uuid::Uuid,
         phone: &str,
@@ -632,7 +634,7 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
         Ok(row.is_some())
     }
 
-    async fn fetch_invite_by_token(&self, token: &str) -> IdentityResult<Option<StaffInviteRow>> {
+    async fn fetch_invite_by_token_hash(&self, token_hash: &str) -> IdentityResult<Option<StaffInviteRow>> {
         let row = sqlx::query(
             r#"
             SELECT inv.id::text as invite_id,
@@ -653,7 +655,7 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
               AND ss.status = 'invited'
             "#,
         )
-        .bind(token)
+        .bind(token_hash)
         .fetch_optional(self.db)
         .await
         .map_err(IdentityError::from)?;
@@ -679,7 +681,7 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
         store_uuid: &uuid::Uuid,
         email: &str,
         role_uuid: &uuid::Uuid,
-        token: &str,
+        token_hash: &str,
         created_by: Option<uuid::Uuid>,
         expires_at: chrono::DateTime<chrono::Utc>,
         display_name: Option<&str>,
@@ -713,7 +715,7 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
         .bind(store_uuid)
         .bind(email)
         .bind(role_uuid)
-        .bind(token)
+        .bind(token_hash)
         .bind(created_by)
         .bind(expires_at)
         .execute(&mut *exec)
@@ -754,22 +756,24 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
         Ok(row.map(|row| row.get("status")))
     }
 
-    async fn fetch_active_staff_by_email(
+    async fn fetch_sign_in_staff_by_email(
         &self,
         store_uuid: &uuid::Uuid,
         email: &str,
     ) -> IdentityResult<Option<StaffAuthRow>> {
         let row = sqlx::query(
             r#"
-            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key
+            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key, ss.status
             FROM store_staff ss
             JOIN store_roles sr ON sr.id = ss.role_id
-            WHERE ss.store_id = $1 AND ss.email = $2 AND ss.status = 'active'
+            WHERE ss.store_id = $1 AND ss.email = $2 AND ss.status IN ($3, $4)
             LIMIT 1
             "#,
         )
         .bind(store_uuid)
         .bind(email)
+        .bind(StoreStaffStatus::Active.as_str())
+        .bind(StoreStaffStatus::Locked.as_str())
         .fetch_optional(self.db)
         .await
         .map_err(IdentityError::from)?;
@@ -777,25 +781,28 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
             staff_id: row.get("id"),
             password_hash: row.get("password_hash"),
             role_key: row.get("role_key"),
+            status: row.get("status"),
         }))
     }
 
-    async fn fetch_active_staff_by_login_id(
+    async fn fetch_sign_in_staff_by_login_id(
         &self,
         store_uuid: &uuid::Uuid,
         login_id: &str,
     ) -> IdentityResult<Option<StaffAuthRow>> {
         let row = sqlx::query(
             r#"
-            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key
+            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key, ss.status
             FROM store_staff ss
             JOIN store_roles sr ON sr.id = ss.role_id
-            WHERE ss.store_id = $1 AND ss.login_id = $2 AND ss.status = 'active'
+            WHERE ss.store_id = $1 AND ss.login_id = $2 AND ss.status IN ($3, $4)
             LIMIT 1
             "#,
         )
         .bind(store_uuid)
         .bind(login_id)
+        .bind(StoreStaffStatus::Active.as_str())
+        .bind(StoreStaffStatus::Locked.as_str())
         .fetch_optional(self.db)
         .await
         .map_err(IdentityError::from)?;
@@ -803,25 +810,28 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
             staff_id: row.get("id"),
             password_hash: row.get("password_hash"),
             role_key: row.get("role_key"),
+            status: row.get("status"),
         }))
     }
 
-    async fn fetch_active_staff_by_phone(
+    async fn fetch_sign_in_staff_by_phone(
         &self,
         store_uuid: &uuid::Uuid,
         phone: &str,
     ) -> IdentityResult<Option<StaffAuthRow>> {
         let row = sqlx::query(
             r#"
-            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key
+            SELECT ss.id::text as id, ss.password_hash, sr.key as role_key, ss.status
             FROM store_staff ss
             JOIN store_roles sr ON sr.id = ss.role_id
-            WHERE ss.store_id = $1 AND ss.phone = $2 AND ss.status = 'active'
+            WHERE ss.store_id = $1 AND ss.phone = $2 AND ss.status IN ($3, $4)
             LIMIT 1
             "#,
         )
         .bind(store_uuid)
         .bind(phone)
+        .bind(StoreStaffStatus::Active.as_str())
+        .bind(StoreStaffStatus::Locked.as_str())
         .fetch_optional(self.db)
         .await
         .map_err(IdentityError::from)?;
@@ -829,6 +839,7 @@ impl<'a> IdentityRepository for PgIdentityRepository<'a> {
             staff_id: row.get("id"),
             password_hash: row.get("password_hash"),
             role_key: row.get("role_key"),
+            status: row.get("status"),
         }))
     }
 }
@@ -1067,6 +1078,31 @@ impl<'a> PgIdentityRepository<'a> {
         }))
     }
 
+    pub async fn insert_token_revocation_tx<'e, E>(
+        &self,
+        exec: E,
+        staff_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        reason: &str,
+    ) -> IdentityResult<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO staff_token_revocations (staff_id, store_id, reason)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(staff_uuid)
+        .bind(store_uuid)
+        .bind(reason)
+        .execute(exec)
+        .await
+        .map_err(IdentityError::from)?;
+        Ok(())
+    }
+
     pub async fn update_staff_role_tx<'e, E>(
         &self,
         exec: E,
@@ -1120,4 +1156,78 @@ impl<'a> PgIdentityRepository<'a> {
             name: row.get("name"),
         }))
     }
+
+    pub async fn record_failed_login(&self, staff_uuid: &uuid::Uuid, ip_address: Option<&str>) -> IdentityResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO staff_failed_logins (staff_id, ip_address)
+            VALUES ($1, $2)
+            "#,
+        )
+        .bind(staff_uuid)
+        .bind(ip_address)
+        .execute(self.db)
+        .await
+        .map_err(IdentityError::from)?;
+        Ok(())
+    }
+
+    pub async fn count_recent_failed_logins(
+        &self,
+        staff_uuid: &uuid::Uuid,
+        window_seconds: i64,
+    ) -> IdentityResult<i64> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) as count
+            FROM staff_failed_logins
+            WHERE staff_id = $1
+              AND attempted_at > now() - make_interval(secs => $2)
+            "#,
+        )
+        .bind(staff_uuid)
+        .bind(window_seconds as f64)
+        .fetch_one(self.db)
+        .await
+        .map_err(IdentityError::from)?;
+        Ok(row.get("count"))
+    }
+
+    pub async fn clear_failed_logins_tx<'e, E>(&self, exec: E, staff_uuid: &uuid::Uuid) -> IdentityResult<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query("DELETE FROM staff_failed_logins WHERE staff_id = $1")
+            .bind(staff_uuid)
+            .execute(exec)
+            .await
+            .map_err(IdentityError::from)?;
+        Ok(())
+    }
+
+    pub async fn update_staff_status_tx<'e, E>(
+        &self,
+        exec: E,
+        staff_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        status: StoreStaffStatus,
+    ) -> IdentityResult<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        sqlx::query(
+            r#"
+            UPDATE store_staff
+            SET status = $1, updated_at = now()
+            WHERE id = $2 AND store_id = $3
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(staff_uuid)
+        .bind(store_uuid)
+        .execute(exec)
+        .await
+        .map_err(IdentityError::from)?;
+        Ok(())
+    }
 }
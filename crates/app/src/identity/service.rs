@@ -5,22 +5,25 @@ use argon2::{
 use chrono::{Duration, Utc};
 use jsonwebtoken::{EncodingKey, Header, encode};
 use rand_core::OsRng;
+use rs_common::env::{self, EnvError};
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use sqlx::{Postgres, Row, Transaction};
 
 use crate::{
     AppState,
-    identity::context::{parse_uuid, resolve_store_context, resolve_store_context_without_token_guard},
+    identity::context::parse_uuid,
     identity::error::{IdentityError, IdentityResult},
     identity::repository::{IdentityRepository, PgIdentityRepository},
     identity::status::StoreStaffStatus,
     infrastructure::{audit, email},
     pb::pb,
-    shared::validation::{Email, Phone},
+    shared::validation::{Email, Phone, ValidationBuilder},
     shared::{
         audit_action::IdentityAuditAction,
+        identity::TokenValue,
         ids::{StoreId, TenantId},
+        store_context::{resolve_store_context, resolve_store_context_without_token_guard},
         time::chrono_to_timestamp,
     },
 };
@@ -31,6 +34,33 @@ pub struct IdentityService<'a> {
 
 const ACCESS_TOKEN_TTL_MINUTES: i64 = 5;
 const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+fn staff_lockout_threshold() -> Result<i64, EnvError> {
+    positive_env_i64("STAFF_LOCKOUT_THRESHOLD", 5)
+}
+
+fn staff_lockout_window_seconds() -> Result<i64, EnvError> {
+    positive_env_i64("STAFF_LOCKOUT_WINDOW_SECONDS", 300)
+}
+
+fn positive_env_i64(key: &str, default: i64) -> Result<i64, EnvError> {
+    match env::env_i64(key, default)? {
+        value if value > 0 => Ok(value),
+        value => Err(EnvError::Invalid {
+            name: key.to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Reads the staff lockout settings once at startup so a bad value stops the server instead of
+/// failing every staff sign-in.
+pub fn validate_lockout_config() -> Result<(), EnvError> {
+    staff_lockout_threshold()?;
+    staff_lockout_window_seconds()?;
+    Ok(())
+}
 
 impl<'a> IdentityService<'a> {
     pub fn new(state: &'a AppState) -> Self {
@@ -88,6 +118,13 @@ impl<'a> IdentityService<'a> {
         transfer_owner(self.state, req).await
     }
 
+    pub async fn unlock_staff(
+        &self,
+        req: pb::IdentityUnlockStaffRequest,
+    ) -> IdentityResult<pb::IdentityUnlockStaffResponse> {
+        unlock_staff(self.state, req).await
+    }
+
     pub async fn create_role(
         &self,
         req: pb::IdentityCreateRoleRequest,
@@ -136,6 +173,13 @@ impl<'a> IdentityService<'a> {
     }
 }
 
+/// The explicit `store.store_id` of a request, if any.
+pub fn requested_store_id(store: Option<&pb::StoreContext>) -> Option<String> {
+    store
+        .map(|ctx| ctx.store_id.clone())
+        .filter(|store_id| !store_id.is_empty())
+}
+
 pub struct SignInWithRefresh {
     pub response: pb::IdentitySignInResponse,
     pub refresh_token: String,
@@ -185,9 +229,9 @@ pub async fn sign_in_with_refresh(
         })
     });
 
-    audit::record(
-        state,
-        audit::AuditInput {
+    state
+        .audit_writer
+        .record(audit::AuditInput {
             store_id: Some(resp.store_id.clone()),
             actor_id: Some(resp.staff_id.clone()),
             actor_type: resp.role.clone(),
@@ -200,9 +244,8 @@ pub async fn sign_in_with_refresh(
             before_json: None,
             after_json: None,
             metadata_json,
-        },
-    )
-    .await?;
+        })
+        .await?;
 
     Ok(SignInWithRefresh {
         response: pb::IdentitySignInResponse {
@@ -370,9 +413,9 @@ pub async fn force_sign_out_staff(
             .filter(|v| !v.is_empty())
             .unwrap_or_else(|| "staff".to_string());
 
-        audit::record(
-            state,
-            audit::AuditInput {
+        state
+            .audit_writer
+            .record(audit::AuditInput {
                 store_id: Some(store_id),
                 actor_id,
                 actor_type,
@@ -385,9 +428,8 @@ pub async fn force_sign_out_staff(
                 before_json: None,
                 after_json: None,
                 metadata_json: None,
-            },
-        )
-        .await?;
+            })
+            .await?;
     }
 
     Ok(pb::IdentityForceSignOutStaffResponse { revoked })
@@ -506,30 +548,32 @@ pub async fn update_staff(
         })
         .unwrap_or_else(|| "staff".to_string());
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.clone()),
-            actor_id,
-            actor_type,
-            action: IdentityAuditAction::StaffUpdate.into(),
-            target_type: Some("store_staff".to_string()),
-            target_id: Some(staff.staff_id.clone()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "staff_id": staff.staff_id,
-                "role_id": staff.role_id,
-                "role_key": staff.role_key,
-                "status": staff.status,
-                "display_name": staff.display_name,
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id,
+                actor_type,
+                action: IdentityAuditAction::StaffUpdate.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(staff.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "staff_id": staff.staff_id,
+                    "role_id": staff.role_id,
+                    "role_key": staff.role_key,
+                    "status": staff.status,
+                    "display_name": staff.display_name,
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
     Ok(pb::IdentityUpdateStaffResponse {
@@ -592,7 +636,7 @@ pub async fn invite_staff(
     let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
     let staff_id = uuid::Uuid::new_v4();
     let invite_id = uuid::Uuid::new_v4();
-    let token = uuid::Uuid::new_v4().to_string();
+    let token = TokenValue::generate();
     let expires_at = Utc::now() + Duration::days(7);
 
     repo.insert_staff_invite_tx(
@@ -602,7 +646,7 @@ pub async fn invite_staff(
         &store_uuid.as_uuid(),
         invite_email.as_str(),
         &role_uuid,
-        &token,
+        &token.hash(),
         created_by,
         expires_at,
         if req.display_name.is_empty() {
@@ -619,75 +663,95 @@ pub async fn invite_staff(
         .unwrap_or_else(|| "Store".to_string());
 
     let email_config = email::EmailConfig::from_env();
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.clone()),
-            actor_id,
-            actor_type: req
-                .actor
-                .as_ref()
-                .and_then(|a| {
-                    if a.actor_type.is_empty() {
-                        None
-                    } else {
-                        Some(a.actor_type.clone())
-                    }
-                })
-                .unwrap_or_else(|| "staff".to_string()),
-            action: IdentityAuditAction::StaffInvite.into(),
-            target_type: Some("store_staff_invite".to_string()),
-            target_id: Some(invite_id.to_string()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "invite_id": invite_id.to_string(),
-                "staff_id": staff_id.to_string(),
-                "email": invite_email.as_str(),
-                "role_id": req.role_id,
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id,
+                actor_type: req
+                    .actor
+                    .as_ref()
+                    .and_then(|a| {
+                        if a.actor_type.is_empty() {
+                            None
+                        } else {
+                            Some(a.actor_type.clone())
+                        }
+                    })
+                    .unwrap_or_else(|| "staff".to_string()),
+                action: IdentityAuditAction::StaffInvite.into(),
+                target_type: Some("store_staff_invite".to_string()),
+                target_id: Some(invite_id.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "invite_id": invite_id.to_string(),
+                    "staff_id": staff_id.to_string(),
+                    "email": invite_email.as_str(),
+                    "role_id": req.role_id,
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
 
     email::send_invite_email(
+        &state.email_renderer,
         &email_config,
         invite_email.as_str(),
         &store_name,
         Some(req.display_name.as_str()),
         role_name.as_deref(),
-        &token,
+        token.as_str(),
     )
     .await?;
 
     Ok(pb::IdentityInviteStaffResponse {
         invite_id: invite_id.to_string(),
-        invite_token: token,
+        invite_token: token.into_string(),
         email: invite_email.as_str().to_string(),
         role_id: req.role_id,
         expires_at: chrono_to_timestamp(Some(expires_at)),
     })
 }
 
+pub struct AcceptInviteWithRefresh {
+    pub response: pb::IdentityAcceptInviteResponse,
+    pub refresh_token: String,
+}
+
 pub async fn accept_invite(
     state: &AppState,
     req: pb::IdentityAcceptInviteRequest,
 ) -> IdentityResult<pb::IdentityAcceptInviteResponse> {
+    let result = accept_invite_with_refresh(state, req).await?;
+    Ok(result.response)
+}
+
+/// Activates the invited staff member and signs them in, so the client gets the same
+/// tokens as from SignIn. The invite token is the only credential. The claim, the activation
+/// and the tokens share one transaction, so a failed sign-in leaves the invite usable.
+pub async fn accept_invite_with_refresh(
+    state: &AppState,
+    req: pb::IdentityAcceptInviteRequest,
+) -> IdentityResult<AcceptInviteWithRefresh> {
     if req.token.is_empty() {
         return Err(IdentityError::invalid_argument("token is required"));
     }
     if req.password.is_empty() {
         return Err(IdentityError::invalid_argument("password is required"));
     }
+    state.password_strength.validate("password", &req.password)?;
 
     let repo = PgIdentityRepository::new(&state.db);
     let invite = repo
-        .fetch_invite_by_token(&req.token)
+        .fetch_invite_by_token_hash(&TokenValue::from_submitted(&req.token).hash())
         .await?
         .ok_or_else(|| IdentityError::invalid_argument("invite token is invalid"))?;
 
@@ -695,23 +759,51 @@ pub async fn accept_invite(
         return Err(IdentityError::invalid_argument("invite token has expired"));
     }
 
+    let invite_uuid = parse_uuid(&invite.invite_id, "invite_id")?;
     let store_uuid = StoreId::parse(&invite.store_id)?;
     let staff_uuid = parse_uuid(&invite.staff_id, "staff_id")?;
     let password_hash = hash_password(&req.password)?;
-    if req.display_name.trim().is_empty() {
-        return Err(IdentityError::invalid_argument("display_name is required"));
-    }
-    let display_name = Some(req.display_name.as_str());
+    let display_name = Some(req.display_name.trim()).filter(|name| !name.is_empty());
+    let (_, tenant_id) = resolve_store_context_without_token_guard(
+        state,
+        Some(pb::StoreContext {
+            store_id: invite.store_id.clone(),
+            ..Default::default()
+        }),
+        None,
+    )
+    .await?;
+    let ctx = crate::rpc::request_context::current();
+    let ip_address = ctx.as_ref().and_then(|c| c.ip_address.clone());
+    let user_agent = ctx.as_ref().and_then(|c| c.user_agent.clone());
 
     let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
-    sqlx::query(
+    let accepted_at = Utc::now();
+    // Claiming the invite first makes a concurrent second acceptance fail here.
+    let claimed = sqlx::query(
+        r#"
+        UPDATE store_staff_invites
+        SET accepted_at = $1
+        WHERE id = $2 AND accepted_at IS NULL AND expires_at > $1
+        "#,
+    )
+    .bind(accepted_at)
+    .bind(invite_uuid)
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+    if claimed.rows_affected() != 1 {
+        return Err(IdentityError::invalid_argument("invite token is invalid"));
+    }
+
+    let activated = sqlx::query(
         r#"
         UPDATE store_staff
         SET password_hash = $1,
             status = $2,
             display_name = COALESCE($3, display_name),
             updated_at = now()
-        WHERE id = $4 AND store_id = $5
+        WHERE id = $4 AND store_id = $5 AND status = $6
         "#,
     )
     .bind(password_hash)
@@ -719,163 +811,577 @@ pub async fn accept_invite(
     .bind(display_name)
     .bind(staff_uuid)
     .bind(store_uuid.as_uuid())
+    .bind(StoreStaffStatus::Invited.as_str())
     .execute(tx.as_mut())
     .await
     .map_err(IdentityError::from)?;
+    if activated.rows_affected() != 1 {
+        return Err(IdentityError::invalid_argument("invite token is invalid"));
+    }
 
-    let accepted_at = Utc::now();
-    sqlx::query(
-        r#"
-        UPDATE store_staff_invites
-        SET accepted_at = $1
-        WHERE token = $2 AND accepted_at IS NULL
-        "#,
-    )
-    .bind(accepted_at)
-    .bind(&req.token)
-    .execute(tx.as_mut())
-    .await
-    .map_err(IdentityError::from)?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(invite.store_id.clone()),
+                actor_id: Some(invite.staff_id.clone()),
+                actor_type: invite.role_key.clone(),
+                action: IdentityAuditAction::InviteAccept.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(invite.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "staff_id": invite.staff_id,
+                    "store_id": invite.store_id,
+                    "role_id": invite.role_id,
+                    "role_key": invite.role_key,
+                    "email": invite.email,
+                    "display_name": display_name.map(str::to_string).or(invite.display_name.clone()),
+                    "source": "invite",
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
-    audit::record_tx(
+    let signed_in = issue_staff_tokens_tx(
         &mut tx,
-        audit::AuditInput {
-            store_id: Some(invite.store_id.clone()),
-            actor_id: Some(invite.staff_id.clone()),
-            actor_type: invite.role_key.clone(),
-            action: IdentityAuditAction::InviteAccept.into(),
-            target_type: Some("store_staff".to_string()),
-            target_id: Some(invite.staff_id.clone()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "staff_id": invite.staff_id,
-                "store_id": invite.store_id,
-                "role_id": invite.role_id,
-                "role_key": invite.role_key,
-                "email": invite.email,
-                "display_name": req.display_name,
-                "source": "invite",
-            })),
-            metadata_json: None,
+        jwt_secret(state)?,
+        TokenSubject {
+            store_id: invite.store_id.clone(),
+            tenant_id,
+            staff_id: invite.staff_id.clone(),
+            role: invite.role_key.clone(),
         },
+        ip_address,
+        user_agent,
     )
     .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(invite.store_id.clone()),
+                actor_id: Some(invite.staff_id.clone()),
+                actor_type: invite.role_key.clone(),
+                action: IdentityAuditAction::SignIn.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(invite.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: None,
+                metadata_json: Some(serde_json::json!({ "source": "invite" })),
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
 
-    Ok(pb::IdentityAcceptInviteResponse {
-        staff_id: invite.staff_id,
-        store_id: invite.store_id,
-        store_code: invite.store_code.unwrap_or_default(),
-        email: invite.email,
-        role_key: invite.role_key,
-        accepted_at: chrono_to_timestamp(Some(accepted_at)),
+    Ok(AcceptInviteWithRefresh {
+        response: pb::IdentityAcceptInviteResponse {
+            staff_id: invite.staff_id,
+            store_id: invite.store_id,
+            store_code: invite.store_code.unwrap_or_default(),
+            email: invite.email,
+            role_key: invite.role_key,
+            accepted_at: chrono_to_timestamp(Some(accepted_at)),
+            access_token: signed_in.access_token,
+            tenant_id: signed_in.tenant_id,
+            expires_at: signed_in.expires_at,
+        },
+        refresh_token: signed_in.refresh_token,
     })
 }
 
-pub async fn transfer_owner(
+pub async fn request_password_reset(
     state: &AppState,
-    req: pb::IdentityTransferOwnerRequest,
-) -> IdentityResult<pb::IdentityTransferOwnerResponse> {
-    let (store_id, _tenant_id) = resolve_store_context(state, req.store, req.tenant).await?;
-    require_owner(req.actor.as_ref())?;
-
-    if req.new_owner_staff_id.is_empty() {
-        return Err(IdentityError::invalid_argument("new_owner_staff_id is required"));
-    }
-
+    req: pb::IdentityRequestPasswordResetRequest,
+) -> IdentityResult<pb::IdentityRequestPasswordResetResponse> {
+    let (store_id, _tenant_id) = resolve_store_context_without_token_guard(state, req.store, req.tenant).await?;
     let store_uuid = StoreId::parse(&store_id)?;
-    let new_owner_uuid = parse_uuid(&req.new_owner_staff_id, "new_owner_staff_id")?;
-    let actor_id = req.actor.as_ref().and_then(|a| {
-        if a.actor_id.is_empty() {
-            None
-        } else {
-            Some(a.actor_id.clone())
-        }
-    });
+    let reset_email = Email::parse(&req.email)?;
 
     let repo = PgIdentityRepository::new(&state.db);
-    let current_owner_id = repo.current_owner_id(&store_uuid.as_uuid()).await?;
-    let Some(current_owner_id) = current_owner_id else {
-        return Err(IdentityError::not_found("owner not found"));
-    };
-    if current_owner_id == req.new_owner_staff_id {
-        return Err(IdentityError::invalid_argument("new owner is already the owner"));
-    }
-
-    if let Some(actor_id) = actor_id.as_ref()
-        && actor_id != &current_owner_id
-    {
-        return Err(IdentityError::permission_denied("only owner can transfer ownership"));
-    }
-
-    let target_status = repo.staff_status(&store_uuid.as_uuid(), &new_owner_uuid).await?;
-    let Some(target_status) = target_status else {
-        return Err(IdentityError::not_found("new owner staff not found"));
+    // Unknown emails get the same response so the endpoint cannot be used to enumerate staff.
+    let Some(staff) = repo
+        .fetch_sign_in_staff_by_email(&store_uuid.as_uuid(), reset_email.as_str())
+        .await?
+        .filter(|staff| staff.status == StoreStaffStatus::Active.as_str())
+    else {
+        return Ok(pb::IdentityRequestPasswordResetResponse { accepted: true });
     };
-    let target_status = parse_staff_status_db(&target_status)?;
-    if target_status != StoreStaffStatus::Active {
-        return Err(IdentityError::invalid_argument("new owner must be active"));
-    }
+    let staff_uuid = parse_uuid(&staff.staff_id, "staff_id")?;
 
-    let owner_role_id = repo
-        .role_id_by_key(&store_uuid.as_uuid(), "owner")
-        .await?
-        .ok_or_else(|| IdentityError::not_found("owner role not found"))?;
-    let staff_role_id = repo
-        .role_id_by_key(&store_uuid.as_uuid(), "staff")
-        .await?
-        .ok_or_else(|| IdentityError::not_found("staff role not found"))?;
+    let token = uuid::Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
 
     let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
-    repo.update_staff_role_tx(tx.as_mut(), &new_owner_uuid, &store_uuid.as_uuid(), &owner_role_id)
-        .await?;
-    let current_owner_uuid = parse_uuid(&current_owner_id, "current_owner_id")?;
-    repo.update_staff_role_tx(tx.as_mut(), &current_owner_uuid, &store_uuid.as_uuid(), &staff_role_id)
-        .await?;
-
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.clone()),
-            actor_id,
-            actor_type: req
-                .actor
-                .as_ref()
-                .and_then(|a| {
-                    if a.actor_type.is_empty() {
-                        None
-                    } else {
-                        Some(a.actor_type.clone())
-                    }
-                })
-                .unwrap_or_else(|| "staff".to_string()),
-            action: IdentityAuditAction::OwnerTransfer.into(),
-            target_type: Some("store".to_string()),
-            target_id: Some(store_id),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: Some(serde_json::json!({ "owner_staff_id": current_owner_id })),
-            after_json: Some(serde_json::json!({ "owner_staff_id": req.new_owner_staff_id })),
-            metadata_json: None,
-        },
+    // Only the latest reset link stays valid.
+    sqlx::query("DELETE FROM staff_password_resets WHERE staff_id = $1")
+        .bind(staff_uuid)
+        .execute(tx.as_mut())
+        .await
+        .map_err(IdentityError::from)?;
+    sqlx::query(
+        r#"
+        INSERT INTO staff_password_resets (id, store_id, staff_id, token_hash, expires_at)
+        VALUES ($1,$2,$3,$4,$5)
+        "#,
     )
-    .await?;
-
+    .bind(uuid::Uuid::new_v4())
+    .bind(store_uuid.as_uuid())
+    .bind(staff_uuid)
+    .bind(hash_token(&token))
+    .bind(expires_at)
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
     tx.commit().await.map_err(IdentityError::from)?;
 
-    let new_owner = fetch_staff_summary(state, &store_uuid.as_uuid(), &req.new_owner_staff_id).await?;
-    let previous_owner = fetch_staff_summary(state, &store_uuid.as_uuid(), &current_owner_id).await?;
+    let store_name: String = repo
+        .store_name(&store_uuid.as_uuid())
+        .await?
+        .unwrap_or_else(|| "Store".to_string());
+    let email_config = email::EmailConfig::from_env();
+    if let Err((_, err)) = email::send_password_reset_email(
+        &state.email_renderer,
+        &email_config,
+        reset_email.as_str(),
+        &store_name,
+        &token,
+    )
+    .await
+    {
+        tracing::warn!(store_id = %store_id, error = %err.message, "failed to send password reset email");
+    }
 
-    Ok(pb::IdentityTransferOwnerResponse {
-        transferred: true,
-        new_owner: Some(new_owner),
-        previous_owner: Some(previous_owner),
-    })
+    Ok(pb::IdentityRequestPasswordResetResponse { accepted: true })
+}
+
+pub async fn reset_password(
+    state: &AppState,
+    req: pb::IdentityResetPasswordRequest,
+) -> IdentityResult<pb::IdentityResetPasswordResponse> {
+    let (store_id, _tenant_id) = resolve_store_context_without_token_guard(state, req.store, req.tenant).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    if req.token.is_empty() {
+        return Err(IdentityError::invalid_argument("token is required"));
+    }
+    if req.new_password.is_empty() {
+        return Err(IdentityError::invalid_argument("new_password is required"));
+    }
+    state.password_strength.validate("new_password", &req.new_password)?;
+    let password_hash = hash_password(&req.new_password)?;
+
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
+    // Deleting the row up front makes the token single-use; a concurrent reset with the
+    // same token finds nothing once this transaction commits.
+    let row = sqlx::query(
+        r#"
+        DELETE FROM staff_password_resets
+        WHERE token_hash = $1 AND store_id = $2
+        RETURNING staff_id, expires_at
+        "#,
+    )
+    .bind(hash_token(&req.token))
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+    let Some(row) = row else {
+        return Err(IdentityError::invalid_argument("reset token is invalid"));
+    };
+    let staff_uuid: uuid::Uuid = row.get("staff_id");
+    let expires_at: chrono::DateTime<Utc> = row.get("expires_at");
+    if expires_at <= Utc::now() {
+        // Keep the expired row removed even though the reset is rejected.
+        tx.commit().await.map_err(IdentityError::from)?;
+        return Err(IdentityError::invalid_argument("reset token has expired"));
+    }
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE store_staff
+        SET password_hash = $1, updated_at = now()
+        WHERE id = $2 AND store_id = $3 AND status = $4
+        "#,
+    )
+    .bind(password_hash)
+    .bind(staff_uuid)
+    .bind(store_uuid.as_uuid())
+    .bind(StoreStaffStatus::Active.as_str())
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+    if updated.rows_affected() != 1 {
+        return Err(IdentityError::invalid_argument("reset token is invalid"));
+    }
+
+    // Existing sessions must sign in again with the new password.
+    sqlx::query(
+        r#"
+        UPDATE store_staff_refresh_tokens
+        SET revoked_at = now()
+        WHERE staff_id = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(staff_uuid)
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id: Some(staff_uuid.to_string()),
+                actor_type: "staff".to_string(),
+                action: IdentityAuditAction::PasswordReset.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(staff_uuid.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: None,
+                metadata_json: None,
+            },
+        )
+        .await?;
+
+    tx.commit().await.map_err(IdentityError::from)?;
+
+    Ok(pb::IdentityResetPasswordResponse {
+        staff_id: staff_uuid.to_string(),
+    })
+}
+
+pub async fn change_password(
+    state: &AppState,
+    req: pb::IdentityChangePasswordRequest,
+    actor: Option<pb::ActorContext>,
+    session_id: Option<String>,
+) -> IdentityResult<pb::IdentityChangePasswordResponse> {
+    let (store_id, _tenant_id) = resolve_store_context(state, req.store, req.tenant).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let Some(actor) = actor.filter(|actor| actor.actor_type == "staff" && !actor.actor_id.is_empty()) else {
+        return Err(IdentityError::unauthenticated("staff sign-in is required"));
+    };
+    let staff_uuid = parse_uuid(&actor.actor_id, "actor_id")?;
+    if req.current_password.is_empty() {
+        return Err(IdentityError::invalid_argument("current_password is required"));
+    }
+    if req.new_password.is_empty() {
+        return Err(IdentityError::invalid_argument("new_password is required"));
+    }
+    if req.new_password == req.current_password {
+        return Err(IdentityError::invalid_argument(
+            "new_password must differ from current_password",
+        ));
+    }
+    state.password_strength.validate("new_password", &req.new_password)?;
+    let session_uuid = session_id.and_then(|value| uuid::Uuid::parse_str(&value).ok());
+
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
+    let row = sqlx::query(
+        r#"
+        SELECT password_hash
+        FROM store_staff
+        WHERE id = $1 AND store_id = $2 AND status = $3
+        FOR UPDATE
+        "#,
+    )
+    .bind(staff_uuid)
+    .bind(store_uuid.as_uuid())
+    .bind(StoreStaffStatus::Active.as_str())
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+    let Some(row) = row else {
+        return Err(IdentityError::unauthenticated("staff sign-in is required"));
+    };
+    let verified = row
+        .get::<Option<String>, _>("password_hash")
+        .as_deref()
+        .and_then(|hash| PasswordHash::new(hash).ok())
+        .is_some_and(|parsed_hash| {
+            Argon2::default()
+                .verify_password(req.current_password.as_bytes(), &parsed_hash)
+                .is_ok()
+        });
+    if !verified {
+        // Count the miss towards the lockout threshold like a failed sign-in.
+        let ip_address = crate::rpc::request_context::current().and_then(|ctx| ctx.ip_address);
+        PgIdentityRepository::new(&state.db)
+            .record_failed_login(&staff_uuid, ip_address.as_deref())
+            .await?;
+        return Err(IdentityError::unauthenticated("current password is incorrect"));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE store_staff
+        SET password_hash = $1, updated_at = now()
+        WHERE id = $2 AND store_id = $3
+        "#,
+    )
+    .bind(hash_password(&req.new_password)?)
+    .bind(staff_uuid)
+    .bind(store_uuid.as_uuid())
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+
+    // Other sessions must sign in again; the session making the change stays signed in.
+    sqlx::query(
+        r#"
+        UPDATE store_staff_sessions
+        SET revoked_at = now()
+        WHERE staff_id = $1 AND store_id = $2 AND revoked_at IS NULL AND id IS DISTINCT FROM $3
+        "#,
+    )
+    .bind(staff_uuid)
+    .bind(store_uuid.as_uuid())
+    .bind(session_uuid)
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+    sqlx::query(
+        r#"
+        UPDATE store_staff_refresh_tokens
+        SET revoked_at = now()
+        WHERE staff_id = $1 AND revoked_at IS NULL AND session_id IS DISTINCT FROM $2
+        "#,
+    )
+    .bind(staff_uuid)
+    .bind(session_uuid)
+    .execute(tx.as_mut())
+    .await
+    .map_err(IdentityError::from)?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id: Some(staff_uuid.to_string()),
+                actor_type: "staff".to_string(),
+                action: IdentityAuditAction::PasswordChange.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(staff_uuid.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: None,
+                metadata_json: None,
+            },
+        )
+        .await?;
+
+    tx.commit().await.map_err(IdentityError::from)?;
+
+    Ok(pb::IdentityChangePasswordResponse {
+        staff_id: staff_uuid.to_string(),
+    })
+}
+
+pub async fn transfer_owner(
+    state: &AppState,
+    req: pb::IdentityTransferOwnerRequest,
+) -> IdentityResult<pb::IdentityTransferOwnerResponse> {
+    let (store_id, _tenant_id) = resolve_store_context(state, req.store, req.tenant).await?;
+    require_owner(req.actor.as_ref())?;
+
+    if req.new_owner_staff_id.is_empty() {
+        return Err(IdentityError::invalid_argument("new_owner_staff_id is required"));
+    }
+
+    let store_uuid = StoreId::parse(&store_id)?;
+    let new_owner_uuid = parse_uuid(&req.new_owner_staff_id, "new_owner_staff_id")?;
+    let actor_id = req.actor.as_ref().and_then(|a| {
+        if a.actor_id.is_empty() {
+            None
+        } else {
+            Some(a.actor_id.clone())
+        }
+    });
+
+    let repo = PgIdentityRepository::new(&state.db);
+    let current_owner_id = repo.current_owner_id(&store_uuid.as_uuid()).await?;
+    let Some(current_owner_id) = current_owner_id else {
+        return Err(IdentityError::not_found("owner not found"));
+    };
+    if current_owner_id == req.new_owner_staff_id {
+        return Err(IdentityError::invalid_argument("new owner is already the owner"));
+    }
+
+    if let Some(actor_id) = actor_id.as_ref()
+        && actor_id != &current_owner_id
+    {
+        return Err(IdentityError::permission_denied("only owner can transfer ownership"));
+    }
+
+    let target_status = repo.staff_status(&store_uuid.as_uuid(), &new_owner_uuid).await?;
+    let Some(target_status) = target_status else {
+        return Err(IdentityError::not_found("new owner staff not found"));
+    };
+    let target_status = parse_staff_status_db(&target_status)?;
+    if target_status != StoreStaffStatus::Active {
+        return Err(IdentityError::invalid_argument("new owner must be active"));
+    }
+
+    let owner_role_id = repo
+        .role_id_by_key(&store_uuid.as_uuid(), "owner")
+        .await?
+        .ok_or_else(|| IdentityError::not_found("owner role not found"))?;
+    let staff_role_id = repo
+        .role_id_by_key(&store_uuid.as_uuid(), "staff")
+        .await?
+        .ok_or_else(|| IdentityError::not_found("staff role not found"))?;
+
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
+    repo.update_staff_role_tx(tx.as_mut(), &new_owner_uuid, &store_uuid.as_uuid(), &owner_role_id)
+        .await?;
+    let current_owner_uuid = parse_uuid(&current_owner_id, "current_owner_id")?;
+    repo.update_staff_role_tx(tx.as_mut(), &current_owner_uuid, &store_uuid.as_uuid(), &staff_role_id)
+        .await?;
+    // Access tokens minted before the transfer still say actor_type "owner".
+    repo.insert_token_revocation_tx(
+        tx.as_mut(),
+        &current_owner_uuid,
+        &store_uuid.as_uuid(),
+        "owner_transfer",
+    )
+    .await?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id,
+                actor_type: req
+                    .actor
+                    .as_ref()
+                    .and_then(|a| {
+                        if a.actor_type.is_empty() {
+                            None
+                        } else {
+                            Some(a.actor_type.clone())
+                        }
+                    })
+                    .unwrap_or_else(|| "staff".to_string()),
+                action: IdentityAuditAction::OwnerTransfer.into(),
+                target_type: Some("store".to_string()),
+                target_id: Some(store_id),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: Some(serde_json::json!({ "owner_staff_id": current_owner_id })),
+                after_json: Some(serde_json::json!({ "owner_staff_id": req.new_owner_staff_id })),
+                metadata_json: None,
+            },
+        )
+        .await?;
+
+    tx.commit().await.map_err(IdentityError::from)?;
+
+    let new_owner = fetch_staff_summary(state, &store_uuid.as_uuid(), &req.new_owner_staff_id).await?;
+    let previous_owner = fetch_staff_summary(state, &store_uuid.as_uuid(), &current_owner_id).await?;
+
+    Ok(pb::IdentityTransferOwnerResponse {
+        transferred: true,
+        new_owner: Some(new_owner),
+        previous_owner: Some(previous_owner),
+    })
+}
+
+pub async fn unlock_staff(
+    state: &AppState,
+    req: pb::IdentityUnlockStaffRequest,
+) -> IdentityResult<pb::IdentityUnlockStaffResponse> {
+    let (store_id, _tenant_id) = resolve_store_context(state, req.store, req.tenant).await?;
+    require_owner(req.actor.as_ref())?;
+
+    if req.staff_id.is_empty() {
+        return Err(IdentityError::invalid_argument("staff_id is required"));
+    }
+
+    let store_uuid = StoreId::parse(&store_id)?;
+    let staff_uuid = parse_uuid(&req.staff_id, "staff_id")?;
+
+    let repo = PgIdentityRepository::new(&state.db);
+    let Some(status) = repo.staff_status(&store_uuid.as_uuid(), &staff_uuid).await? else {
+        return Err(IdentityError::not_found("staff not found"));
+    };
+    let status = parse_staff_status_db(&status)?;
+    if status != StoreStaffStatus::Locked {
+        return Err(IdentityError::failed_precondition("staff is not locked"));
+    }
+
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
+    repo.update_staff_status_tx(
+        tx.as_mut(),
+        &staff_uuid,
+        &store_uuid.as_uuid(),
+        StoreStaffStatus::Active,
+    )
+    .await?;
+    repo.clear_failed_logins_tx(tx.as_mut(), &staff_uuid).await?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id: req.actor.as_ref().and_then(|a| {
+                    if a.actor_id.is_empty() {
+                        None
+                    } else {
+                        Some(a.actor_id.clone())
+                    }
+                }),
+                actor_type: req
+                    .actor
+                    .as_ref()
+                    .and_then(|a| {
+                        if a.actor_type.is_empty() {
+                            None
+                        } else {
+                            Some(a.actor_type.clone())
+                        }
+                    })
+                    .unwrap_or_else(|| "staff".to_string()),
+                action: IdentityAuditAction::StaffUnlock.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(req.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: Some(serde_json::json!({ "status": StoreStaffStatus::Locked.as_str() })),
+                after_json: Some(serde_json::json!({ "status": StoreStaffStatus::Active.as_str() })),
+                metadata_json: None,
+            },
+        )
+        .await?;
+
+    tx.commit().await.map_err(IdentityError::from)?;
+
+    let staff = fetch_staff_summary(state, &store_uuid.as_uuid(), &req.staff_id).await?;
+    Ok(pb::IdentityUnlockStaffResponse { staff: Some(staff) })
 }
 
 pub async fn sign_out(
@@ -931,9 +1437,9 @@ pub async fn sign_out(
         })
         .unwrap_or_else(|| "staff".to_string());
 
-    audit::record(
-        state,
-        audit::AuditInput {
+    state
+        .audit_writer
+        .record(audit::AuditInput {
             store_id: Some(store_id),
             actor_id,
             actor_type,
@@ -946,9 +1452,8 @@ pub async fn sign_out(
             before_json: None,
             after_json: None,
             metadata_json: None,
-        },
-    )
-    .await?;
+        })
+        .await?;
 
     Ok(pb::IdentitySignOutResponse { signed_out: true })
 }
@@ -969,19 +1474,23 @@ pub async fn refresh_token(
 
     let (store_id, tenant_id) = resolve_store_context(state, req.store, req.tenant).await?;
     let store_uuid = StoreId::parse(&store_id)?;
-    let refresh_hash = hash_refresh_token(&refresh_token);
+    let refresh_hash = hash_token(&refresh_token);
     let now = Utc::now();
 
+    // The token row stays locked until the rotation commits, so a concurrent replay
+    // of the same token waits and then sees it revoked.
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
     let row = sqlx::query(
         r#"
         SELECT id, staff_id, session_id, expires_at, revoked_at
         FROM store_staff_refresh_tokens
         WHERE token_hash = $1 AND store_id = $2
+        FOR UPDATE
         "#,
     )
     .bind(refresh_hash)
     .bind(store_uuid.as_uuid())
-    .fetch_optional(&state.db)
+    .fetch_optional(tx.as_mut())
     .await
     .map_err(IdentityError::from)?;
 
@@ -1009,7 +1518,7 @@ pub async fn refresh_token(
     )
     .bind(staff_id)
     .bind(store_uuid.as_uuid())
-    .fetch_optional(&state.db)
+    .fetch_optional(tx.as_mut())
     .await
     .map_err(IdentityError::from)?;
 
@@ -1026,8 +1535,7 @@ pub async fn refresh_token(
     let role_key: String = staff_row.get::<Option<String>, _>("role_key").unwrap_or_default();
     let staff_id_str: String = staff_row.get("staff_id");
 
-    let jwt_secret =
-        std::env::var("AUTH_JWT_SECRET").map_err(|_| IdentityError::internal("AUTH_JWT_SECRET is required"))?;
+    let jwt_secret = jwt_secret(state)?;
 
     let exp = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
     let claims = JwtClaims {
@@ -1049,12 +1557,10 @@ pub async fn refresh_token(
 
     let new_refresh_token = uuid::Uuid::new_v4().to_string();
     let new_refresh_id = uuid::Uuid::new_v4();
-    let new_refresh_hash = hash_refresh_token(&new_refresh_token);
+    let new_refresh_hash = hash_token(&new_refresh_token);
     let refresh_expires_at = now + Duration::days(REFRESH_TOKEN_TTL_DAYS);
 
-    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
-
-    sqlx::query(
+    let revoked = sqlx::query(
         r#"
         UPDATE store_staff_refresh_tokens
         SET revoked_at = now(), replaced_by = $1, last_used_at = now()
@@ -1066,6 +1572,9 @@ pub async fn refresh_token(
     .execute(tx.as_mut())
     .await
     .map_err(IdentityError::from)?;
+    if revoked.rows_affected() != 1 {
+        return Err(IdentityError::unauthenticated("refresh token expired"));
+    }
 
     sqlx::query(
         r#"
@@ -1112,6 +1621,27 @@ pub async fn refresh_token(
     })
 }
 
+// Expired refresh tokens (rotated or not) can no longer be used; delete them in batches.
+pub async fn purge_expired_refresh_tokens(state: &AppState, batch_size: i64) -> IdentityResult<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM store_staff_refresh_tokens
+        WHERE id IN (
+            SELECT id
+            FROM store_staff_refresh_tokens
+            WHERE expires_at <= now()
+            ORDER BY expires_at ASC
+            LIMIT $1
+        )
+        "#,
+    )
+    .bind(batch_size)
+    .execute(&state.db)
+    .await
+    .map_err(IdentityError::from)?;
+    Ok(result.rows_affected())
+}
+
 pub async fn create_staff(
     state: &AppState,
     req: pb::IdentityCreateStaffRequest,
@@ -1158,33 +1688,35 @@ pub async fn create_staff(
         })
         .unwrap_or_else(|| "staff".to_string());
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(resp.store_id.clone()),
-            actor_id,
-            actor_type,
-            action: IdentityAuditAction::StaffCreate.into(),
-            target_type: Some("store_staff".to_string()),
-            target_id: Some(resp.staff_id.clone()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "staff_id": resp.staff_id,
-                "store_id": resp.store_id,
-                "role_id": resp.role_id,
-                "role_key": resp.role_key,
-                "email": email,
-                "login_id": login_id,
-                "phone": phone,
-                "display_name": display_name,
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(resp.store_id.clone()),
+                actor_id,
+                actor_type,
+                action: IdentityAuditAction::StaffCreate.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(resp.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "staff_id": resp.staff_id,
+                    "store_id": resp.store_id,
+                    "role_id": resp.role_id,
+                    "role_key": resp.role_key,
+                    "email": email,
+                    "login_id": login_id,
+                    "phone": phone,
+                    "display_name": display_name,
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
     Ok(pb::IdentityCreateStaffResponse {
@@ -1245,28 +1777,30 @@ pub async fn create_role(
             .await
             .map(|(_, tenant_id)| tenant_id)?;
 
-        audit::record_tx(
-            &mut tx,
-            audit::AuditInput {
-                store_id: Some(store_id.clone()),
-                actor_id,
-                actor_type,
-                action: IdentityAuditAction::RoleCreate.into(),
-                target_type: Some("store_role".to_string()),
-                target_id: Some(role.id.clone()),
-                request_id: None,
-                ip_address: None,
-                user_agent: None,
-                before_json: None,
-                after_json: Some(serde_json::json!({
-                    "role_id": role.id,
-                    "key": role.key,
-                    "name": role.name,
-                })),
-                metadata_json: None,
-            },
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit::AuditInput {
+                    store_id: Some(store_id.clone()),
+                    actor_id,
+                    actor_type,
+                    action: IdentityAuditAction::RoleCreate.into(),
+                    target_type: Some("store_role".to_string()),
+                    target_id: Some(role.id.clone()),
+                    request_id: None,
+                    ip_address: None,
+                    user_agent: None,
+                    before_json: None,
+                    after_json: Some(serde_json::json!({
+                        "role_id": role.id,
+                        "key": role.key,
+                        "name": role.name,
+                    })),
+                    metadata_json: None,
+                },
+            )
+            .await?;
     }
 
     tx.commit().await.map_err(IdentityError::from)?;
@@ -1331,26 +1865,28 @@ pub async fn assign_role_to_staff(
         })
         .unwrap_or_else(|| "staff".to_string());
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.clone()),
-            actor_id,
-            actor_type,
-            action: IdentityAuditAction::RoleAssign.into(),
-            target_type: Some("store_staff".to_string()),
-            target_id: Some(req.staff_id.clone()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: None,
-            metadata_json: Some(serde_json::json!({
-                "role_id": req.role_id,
-            })),
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id,
+                actor_type,
+                action: IdentityAuditAction::RoleAssign.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(req.staff_id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: None,
+                metadata_json: Some(serde_json::json!({
+                    "role_id": req.role_id,
+                })),
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
 
@@ -1519,30 +2055,32 @@ pub async fn update_role(
         })
         .unwrap_or_else(|| "staff".to_string());
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.clone()),
-            actor_id,
-            actor_type,
-            action: IdentityAuditAction::RoleUpdate.into(),
-            target_type: Some("store_role".to_string()),
-            target_id: Some(role.id.clone()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "role_id": role.id,
-                "key": role.key,
-                "name": role.name,
-                "description": role.description,
-                "permission_keys": role.permission_keys,
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.clone()),
+                actor_id,
+                actor_type,
+                action: IdentityAuditAction::RoleUpdate.into(),
+                target_type: Some("store_role".to_string()),
+                target_id: Some(role.id.clone()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "role_id": role.id,
+                    "key": role.key,
+                    "name": role.name,
+                    "description": role.description,
+                    "permission_keys": role.permission_keys,
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(IdentityError::from)?;
     Ok(pb::IdentityUpdateRoleResponse {
@@ -1593,28 +2131,30 @@ pub async fn delete_role(
             })
             .unwrap_or_else(|| "staff".to_string());
 
-        audit::record_tx(
-            &mut tx,
-            audit::AuditInput {
-                store_id: Some(store_id.clone()),
-                actor_id,
-                actor_type,
-                action: IdentityAuditAction::RoleDelete.into(),
-                target_type: Some("store_role".to_string()),
-                target_id: Some(row.id.clone()),
-                request_id: None,
-                ip_address: None,
-                user_agent: None,
-                before_json: Some(serde_json::json!({
-                    "role_id": row.id,
-                    "key": row.key,
-                    "name": row.name.unwrap_or_default(),
-                })),
-                after_json: None,
-                metadata_json: None,
-            },
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit::AuditInput {
+                    store_id: Some(store_id.clone()),
+                    actor_id,
+                    actor_type,
+                    action: IdentityAuditAction::RoleDelete.into(),
+                    target_type: Some("store_role".to_string()),
+                    target_id: Some(row.id.clone()),
+                    request_id: None,
+                    ip_address: None,
+                    user_agent: None,
+                    before_json: Some(serde_json::json!({
+                        "role_id": row.id,
+                        "key": row.key,
+                        "name": row.name.unwrap_or_default(),
+                    })),
+                    after_json: None,
+                    metadata_json: None,
+                },
+            )
+            .await?;
 
         tx.commit().await.map_err(IdentityError::from)?;
         return Ok(pb::IdentityDeleteRoleResponse { deleted: true });
@@ -1660,12 +2200,12 @@ async fn sign_in_core(
 
     let repo = PgIdentityRepository::new(&state.db);
     let row = if !email.is_empty() {
-        repo.fetch_active_staff_by_email(&store_uuid.as_uuid(), &email).await?
+        repo.fetch_sign_in_staff_by_email(&store_uuid.as_uuid(), &email).await?
     } else if !login_id.is_empty() {
-        repo.fetch_active_staff_by_login_id(&store_uuid.as_uuid(), &login_id)
+        repo.fetch_sign_in_staff_by_login_id(&store_uuid.as_uuid(), &login_id)
             .await?
     } else if !phone.is_empty() {
-        repo.fetch_active_staff_by_phone(&store_uuid.as_uuid(), &phone).await?
+        repo.fetch_sign_in_staff_by_phone(&store_uuid.as_uuid(), &phone).await?
     } else {
         return Err(IdentityError::invalid_argument(
             "email or login_id or phone is required",
@@ -1673,30 +2213,98 @@ async fn sign_in_core(
     };
 
     let Some(row) = row else {
+        verify_password_hash(&password, None);
         return Err(IdentityError::unauthenticated("invalid credentials"));
     };
 
-    let hash = row
-        .password_hash
-        .ok_or_else(|| IdentityError::unauthenticated("invalid credentials"))?;
-
-    let parsed_hash = PasswordHash::new(&hash).map_err(|_| IdentityError::unauthenticated("invalid credentials"))?;
-
-    Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .map_err(|_| IdentityError::unauthenticated("invalid credentials"))?;
-
     let staff_id: String = row.staff_id;
     let role: String = row.role_key;
-    let jwt_secret =
-        std::env::var("AUTH_JWT_SECRET").map_err(|_| IdentityError::internal("AUTH_JWT_SECRET is required"))?;
-
-    let now = chrono::Utc::now();
-    let exp = now + chrono::Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
     let staff_uuid = parse_uuid(&staff_id, "staff_id").map_err(|_| IdentityError::internal("invalid staff_id"))?;
     let ctx = crate::rpc::request_context::current();
     let ip_address = ctx.as_ref().and_then(|c| c.ip_address.clone());
     let user_agent = ctx.as_ref().and_then(|c| c.user_agent.clone());
+
+    // The lockout is checked before the password so a locked account cannot be used to probe passwords.
+    // The dummy verification keeps a locked answer as slow as a password check.
+    if parse_staff_status_db(&row.status)? == StoreStaffStatus::Locked {
+        verify_password_hash(&password, None);
+        return Err(IdentityError::resource_exhausted("account is locked"));
+    }
+    let lockout_threshold = staff_lockout_threshold().map_err(|err| IdentityError::internal(err.to_string()))?;
+    let lockout_window_seconds =
+        staff_lockout_window_seconds().map_err(|err| IdentityError::internal(err.to_string()))?;
+    let failed_logins = repo
+        .count_recent_failed_logins(&staff_uuid, lockout_window_seconds)
+        .await?;
+    if failed_logins >= lockout_threshold {
+        repo.update_staff_status_tx(&state.db, &staff_uuid, &store_uuid.as_uuid(), StoreStaffStatus::Locked)
+            .await?;
+        verify_password_hash(&password, None);
+        return Err(IdentityError::resource_exhausted("account is locked"));
+    }
+
+    let verified = verify_password_hash(&password, row.password_hash.as_deref());
+    if !verified {
+        repo.record_failed_login(&staff_uuid, ip_address.as_deref()).await?;
+        return Err(IdentityError::unauthenticated("invalid credentials"));
+    }
+    repo.clear_failed_logins_tx(&state.db, &staff_uuid).await?;
+
+    let jwt_secret = jwt_secret(state)?;
+    let mut tx = state.db.begin().await.map_err(IdentityError::from)?;
+    let issued = issue_staff_tokens_tx(
+        &mut tx,
+        jwt_secret,
+        TokenSubject {
+            store_id,
+            tenant_id,
+            staff_id,
+            role,
+        },
+        ip_address,
+        user_agent,
+    )
+    .await?;
+    tx.commit().await.map_err(IdentityError::from)?;
+    Ok(issued)
+}
+
+fn jwt_secret(state: &AppState) -> IdentityResult<&str> {
+    state
+        .auth
+        .jwt_secret
+        .as_deref()
+        .ok_or_else(|| IdentityError::internal("AUTH_JWT_SECRET is required"))
+}
+
+/// The staff member a session and token pair is issued for.
+struct TokenSubject {
+    store_id: String,
+    tenant_id: String,
+    staff_id: String,
+    role: String,
+}
+
+/// Reuses the caller's open session for this client (or starts one) and issues an access token
+/// and a rotated refresh token for it, all inside `tx`.
+async fn issue_staff_tokens_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    jwt_secret: &str,
+    subject: TokenSubject,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+) -> IdentityResult<SignInCoreResult> {
+    let TokenSubject {
+        store_id,
+        tenant_id,
+        staff_id,
+        role,
+    } = subject;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let staff_uuid = parse_uuid(&staff_id, "staff_id").map_err(|_| IdentityError::internal("invalid staff_id"))?;
+
+    let now = chrono::Utc::now();
+    let exp = now + chrono::Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
     let existing_session = sqlx::query(
         r#"
         SELECT id
@@ -1714,7 +2322,7 @@ async fn sign_in_core(
     .bind(staff_uuid)
     .bind(ip_address.as_deref())
     .bind(user_agent.as_deref())
-    .fetch_optional(&state.db)
+    .fetch_optional(tx.as_mut())
     .await
     .map_err(|_| IdentityError::internal("failed to lookup staff session"))?;
 
@@ -1731,7 +2339,7 @@ async fn sign_in_core(
         )
         .bind(exp)
         .bind(session_id)
-        .execute(&state.db)
+        .execute(tx.as_mut())
         .await
         .map_err(|_| IdentityError::internal("failed to update staff session"))?;
         session_id
@@ -1750,7 +2358,7 @@ async fn sign_in_core(
         .bind(ip_address.clone())
         .bind(user_agent.clone())
         .bind(exp)
-        .execute(&state.db)
+        .execute(tx.as_mut())
         .await
         .map_err(|_| IdentityError::internal("failed to create staff session"))?;
         session_id
@@ -1776,9 +2384,9 @@ async fn sign_in_core(
     let refresh_expires_at = now + chrono::Duration::days(REFRESH_TOKEN_TTL_DAYS);
     let refresh_token = uuid::Uuid::new_v4().to_string();
     let refresh_token_id = uuid::Uuid::new_v4();
-    let refresh_hash = hash_refresh_token(&refresh_token);
+    let refresh_hash = hash_token(&refresh_token);
 
-    let _ = sqlx::query(
+    sqlx::query(
         r#"
         UPDATE store_staff_refresh_tokens
         SET revoked_at = now()
@@ -1787,8 +2395,9 @@ async fn sign_in_core(
     )
     .bind(session_id)
     .bind(store_uuid.as_uuid())
-    .execute(&state.db)
-    .await;
+    .execute(tx.as_mut())
+    .await
+    .map_err(|_| IdentityError::internal("failed to revoke refresh tokens"))?;
 
     sqlx::query(
         r#"
@@ -1803,7 +2412,7 @@ async fn sign_in_core(
     .bind(session_id)
     .bind(refresh_hash)
     .bind(refresh_expires_at)
-    .execute(&state.db)
+    .execute(tx.as_mut())
     .await
     .map_err(|_| IdentityError::internal("failed to create refresh token"))?;
 
@@ -1842,25 +2451,28 @@ async fn create_staff_core(
     let (store_id, tenant_id) = resolve_store_context(state, store, tenant).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let _tenant_uuid = TenantId::parse(&tenant_id)?;
-    let email = Email::parse_optional(&email)?
+    let mut validation = ValidationBuilder::new();
+    let email = validation.check("email", Email::parse_optional(&email));
+    let phone = validation.check("phone", Phone::parse_optional(&phone));
+    validation.require("role_id", &role_id);
+    if matches!((&email, &phone), (Some(None), Some(None))) && login_id.is_empty() {
+        validation.violation("login_id", "email or login_id or phone is required");
+    }
+    if password.is_empty() {
+        validation.violation("password", "password is required");
+    } else {
+        validation.check("password", state.password_strength.validate("password", &password));
+    }
+    validation.finish()?;
+    let email = email
+        .flatten()
         .map(|value| value.as_str().to_string())
         .unwrap_or_default();
-    let phone = Phone::parse_optional(&phone)?
+    let phone = phone
+        .flatten()
         .map(|value| value.as_str().to_string())
         .unwrap_or_default();
 
-    if role_id.is_empty() {
-        return Err(IdentityError::invalid_argument("role_id is required"));
-    }
-    if email.is_empty() && login_id.is_empty() && phone.is_empty() {
-        return Err(IdentityError::invalid_argument(
-            "email or login_id or phone is required",
-        ));
-    }
-    if password.is_empty() {
-        return Err(IdentityError::invalid_argument("password is required"));
-    }
-
     let password_hash = hash_password(&password)?;
     let staff_id = uuid::Uuid::new_v4();
 
@@ -1951,6 +2563,30 @@ async fn fetch_staff_summary(
     })
 }
 
+// Accounts without a usable hash (unknown, invited, SSO-only) are verified against this instead,
+// so sign-in takes as long as a wrong password and does not reveal which case applied.
+// Precomputed with `Argon2::default()` so it costs the same as a real staff hash.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$VNId4avHSAdDfV69SDjaaw$AAE+EgvCQzGMs/NiD/Y9KOviZpDj1KMrj3x3s4/Ug10";
+
+fn verify_password_hash(password: &str, hash: Option<&str>) -> bool {
+    let Some((hash, usable)) = verification_hash(hash) else {
+        return false;
+    };
+    let matched = Argon2::default().verify_password(password.as_bytes(), &hash).is_ok();
+    usable && matched
+}
+
+/// The hash a password is checked against, and whether a match may sign the staff in.
+fn verification_hash(hash: Option<&str>) -> Option<(PasswordHash<'_>, bool)> {
+    match hash.and_then(|hash| PasswordHash::new(hash).ok()) {
+        Some(parsed_hash) => Some((parsed_hash, true)),
+        None => PasswordHash::new(DUMMY_PASSWORD_HASH)
+            .ok()
+            .map(|parsed_hash| (parsed_hash, false)),
+    }
+}
+
 fn hash_password(password: &str) -> IdentityResult<String> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
@@ -1960,7 +2596,7 @@ fn hash_password(password: &str) -> IdentityResult<String> {
         .map_err(|_| IdentityError::internal("failed to hash password"))
 }
 
-fn hash_refresh_token(token: &str) -> String {
+fn hash_token(token: &str) -> String {
     let hash = Sha256::digest(token.as_bytes());
     hex::encode(hash)
 }
@@ -2044,9 +2680,558 @@ fn parse_staff_status_optional(value: &str) -> IdentityResult<Option<StoreStaffS
     if value.trim().is_empty() {
         return Ok(None);
     }
-    StoreStaffStatus::try_from(value).map(Some).map_err(IdentityError::invalid_argument)
+    StoreStaffStatus::try_from(value)
+        .map(Some)
+        .map_err(IdentityError::invalid_argument)
 }
 
 fn parse_staff_status_db(value: &str) -> IdentityResult<StoreStaffStatus> {
     StoreStaffStatus::try_from(value).map_err(|_| IdentityError::internal("staff status is invalid"))
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::{
+        audit_action::AuditAction,
+        test_support::{self, TestStore},
+    };
+
+    const PASSWORD: &str = "Correct1pass";
+
+    async fn create_staff(db: &PgPool, store: &TestStore, email: &str) -> uuid::Uuid {
+        let role_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO store_roles (store_id, key, name) VALUES ($1, 'staff', 'Staff') RETURNING id",
+        )
+        .bind(store.store_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO store_staff (store_id, email, password_hash, role_id, status)
+            VALUES ($1, $2, $3, $4, 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(store.store_id)
+        .bind(email)
+        .bind(hash_password(PASSWORD).unwrap())
+        .bind(role_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    async fn try_sign_in(state: &AppState, store: &TestStore, email: &str, password: &str) -> IdentityResult<()> {
+        sign_in(
+            state,
+            pb::IdentitySignInRequest {
+                store: store.store_context(),
+                email: email.to_string(),
+                password: password.to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ())
+    }
+
+    async fn staff_status(db: &PgPool, staff_id: uuid::Uuid) -> String {
+        sqlx::query_scalar("SELECT status FROM store_staff WHERE id = $1")
+            .bind(staff_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    fn unauthenticated_message(result: IdentityResult<()>) -> String {
+        match result {
+            Err(IdentityError::Unauthenticated(message)) => message,
+            other => panic!("expected Unauthenticated, got {other:?}"),
+        }
+    }
+
+    fn assert_locked(result: IdentityResult<()>) {
+        match result {
+            Err(IdentityError::ResourceExhausted(message)) => assert_eq!(message, "account is locked"),
+            other => panic!("expected ResourceExhausted, got {other:?}"),
+        }
+    }
+
+    async fn lock_staff(state: &AppState, store: &TestStore, email: &str) {
+        for _ in 0..5 {
+            unauthenticated_message(try_sign_in(state, store, email, "Wrong1pass").await);
+        }
+        assert_locked(try_sign_in(state, store, email, PASSWORD).await);
+    }
+
+    async fn failed_login_count(db: &PgPool, staff_id: uuid::Uuid) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM staff_failed_logins WHERE staff_id = $1")
+            .bind(staff_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn sign_in_locks_after_the_threshold(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+
+        for _ in 0..5 {
+            unauthenticated_message(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+        }
+        assert_eq!(staff_status(&db, staff_id).await, "active");
+        assert_eq!(failed_login_count(&db, staff_id).await, 5);
+
+        // The correct password no longer helps once the threshold is reached.
+        assert_locked(try_sign_in(&state, &store, "staff@example.com", PASSWORD).await);
+        assert_eq!(staff_status(&db, staff_id).await, "locked");
+        assert_locked(try_sign_in(&state, &store, "staff@example.com", PASSWORD).await);
+        assert_locked(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn failures_outside_the_window_do_not_count(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        sqlx::query(
+            r#"
+            INSERT INTO staff_failed_logins (staff_id, attempted_at)
+            SELECT $1, now() - interval '10 minutes' FROM generate_series(1, 5)
+            "#,
+        )
+        .bind(staff_id)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        for _ in 0..4 {
+            unauthenticated_message(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+        }
+        // Four recent failures are under the threshold of five; the older ones are ignored.
+        unauthenticated_message(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+        assert_eq!(staff_status(&db, staff_id).await, "active");
+    }
+
+    async fn try_unlock_staff(
+        state: &AppState,
+        store: &TestStore,
+        staff_id: uuid::Uuid,
+        actor_type: &str,
+    ) -> IdentityResult<pb::IdentityUnlockStaffResponse> {
+        unlock_staff(
+            state,
+            pb::IdentityUnlockStaffRequest {
+                store: store.store_context(),
+                staff_id: staff_id.to_string(),
+                actor: Some(pb::ActorContext {
+                    actor_id: uuid::Uuid::new_v4().to_string(),
+                    actor_type: actor_type.to_string(),
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn owner_unlocks_a_locked_staff(db: PgPool) {
+        let audit = test_support::RecordingAuditWriter::default();
+        let mut state = test_support::app_state(db.clone());
+        state.audit_writer = std::sync::Arc::new(audit.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        lock_staff(&state, &store, "staff@example.com").await;
+
+        let resp = try_unlock_staff(&state, &store, staff_id, "owner").await.unwrap();
+        assert_eq!(resp.staff.unwrap().status, "active");
+        assert_eq!(staff_status(&db, staff_id).await, "active");
+        assert_eq!(failed_login_count(&db, staff_id).await, 0);
+        let unlocks: Vec<_> = audit
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.action == AuditAction::from(IdentityAuditAction::StaffUnlock))
+            .map(|event| (event.target_id.clone(), event.actor_type.clone()))
+            .collect();
+        assert_eq!(unlocks, vec![(Some(staff_id.to_string()), "owner".to_string())]);
+
+        // The cleared failures mean the next wrong password is an ordinary failure again.
+        unauthenticated_message(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn non_owner_cannot_unlock_staff(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        lock_staff(&state, &store, "staff@example.com").await;
+
+        for actor_type in ["staff", ""] {
+            match try_unlock_staff(&state, &store, staff_id, actor_type).await {
+                Err(IdentityError::PermissionDenied(message)) => assert_eq!(message, "owner role is required"),
+                other => panic!("expected PermissionDenied, got {other:?}"),
+            }
+        }
+        assert_eq!(staff_status(&db, staff_id).await, "locked");
+        assert_eq!(failed_login_count(&db, staff_id).await, 5);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn unlocking_an_active_staff_fails(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+
+        match try_unlock_staff(&state, &store, staff_id, "owner").await {
+            Err(IdentityError::FailedPrecondition(message)) => assert_eq!(message, "staff is not locked"),
+            other => panic!("expected FailedPrecondition, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn dummy_hash_uses_the_default_argon2_cost() {
+        let hash = PasswordHash::new(DUMMY_PASSWORD_HASH).unwrap();
+        let params = argon2::Params::try_from(&hash).unwrap();
+        let default = Argon2::default();
+        assert_eq!(hash.algorithm.as_str(), "argon2id");
+        assert_eq!(
+            (params.m_cost(), params.t_cost(), params.p_cost()),
+            (
+                default.params().m_cost(),
+                default.params().t_cost(),
+                default.params().p_cost()
+            )
+        );
+        assert!(!verify_password_hash("dummy password for timing", None));
+    }
+
+    #[test]
+    fn missing_or_unparseable_hashes_are_verified_against_the_dummy_hash() {
+        for hash in [None, Some(""), Some("not-a-phc-string")] {
+            let (verified_against, usable) = verification_hash(hash).unwrap();
+            assert_eq!(verified_against.to_string(), DUMMY_PASSWORD_HASH, "{hash:?}");
+            assert!(!usable, "{hash:?}");
+        }
+        let staff_hash = hash_password(PASSWORD).unwrap();
+        let (verified_against, usable) = verification_hash(Some(&staff_hash)).unwrap();
+        assert_eq!(verified_against.to_string(), staff_hash);
+        assert!(usable);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn unknown_email_fails_like_a_wrong_password(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        create_staff(&db, &store, "staff@example.com").await;
+
+        let unknown = unauthenticated_message(try_sign_in(&state, &store, "nobody@example.com", PASSWORD).await);
+        let wrong = unauthenticated_message(try_sign_in(&state, &store, "staff@example.com", "Wrong1pass").await);
+        assert_eq!(unknown, "invalid credentials");
+        assert_eq!(unknown, wrong);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn staff_without_a_password_hash_fails_like_a_wrong_password(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "invited@example.com").await;
+        sqlx::query("UPDATE store_staff SET password_hash = NULL WHERE id = $1")
+            .bind(staff_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let message = unauthenticated_message(try_sign_in(&state, &store, "invited@example.com", PASSWORD).await);
+        assert_eq!(message, "invalid credentials");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn create_staff_reports_every_invalid_field(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+
+        let result = super::create_staff(
+            &state,
+            pb::IdentityCreateStaffRequest {
+                store: store.store_context(),
+                email: "not-an-email".to_string(),
+                phone: "0".repeat(33),
+                password: "short".to_string(),
+                ..Default::default()
+            },
+        )
+        .await;
+        let details = match result {
+            Err(IdentityError::InvalidFields(_, details)) => details,
+            other => panic!("expected InvalidFields, got {other:?}"),
+        };
+        let fields: Vec<_> = details.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["email", "phone", "role_id", "password"]);
+        assert_eq!(
+            details[3].message,
+            "password must contain at least 8 characters, an uppercase letter, a digit"
+        );
+        let staff: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM store_staff WHERE store_id = $1")
+            .bind(store.store_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(staff, 0);
+    }
+
+    async fn create_invite(
+        db: &PgPool,
+        store: &TestStore,
+        email: &str,
+        token: &str,
+        expires_in_minutes: i64,
+    ) -> uuid::Uuid {
+        let role_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO store_roles (store_id, key, name) VALUES ($1, 'staff', 'Staff') RETURNING id",
+        )
+        .bind(store.store_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let staff_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO store_staff (store_id, email, role_id, status) VALUES ($1, $2, $3, 'invited') RETURNING id",
+        )
+        .bind(store.store_id)
+        .bind(email)
+        .bind(role_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO store_staff_invites (store_id, email, role_id, token, expires_at)
+            VALUES ($1, $2, $3, $4, now() + make_interval(mins => $5))
+            "#,
+        )
+        .bind(store.store_id)
+        .bind(email)
+        .bind(role_id)
+        .bind(TokenValue::from_submitted(token).hash())
+        .bind(expires_in_minutes as i32)
+        .execute(db)
+        .await
+        .unwrap();
+        staff_id
+    }
+
+    async fn try_accept_invite(state: &AppState, token: &str) -> IdentityResult<AcceptInviteWithRefresh> {
+        accept_invite_with_refresh(
+            state,
+            pb::IdentityAcceptInviteRequest {
+                token: token.to_string(),
+                password: PASSWORD.to_string(),
+                display_name: "Hanako".to_string(),
+            },
+        )
+        .await
+    }
+
+    fn accept_error_message(result: IdentityResult<AcceptInviteWithRefresh>) -> String {
+        invalid_argument_message(result.map(|_| ()))
+    }
+
+    async fn invite_accepted(db: &PgPool, store: &TestStore, email: &str) -> bool {
+        sqlx::query_scalar("SELECT accepted_at IS NOT NULL FROM store_staff_invites WHERE store_id = $1 AND email = $2")
+            .bind(store.store_id)
+            .bind(email)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    async fn refresh_token_count(db: &PgPool, staff_id: uuid::Uuid) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM store_staff_refresh_tokens WHERE staff_id = $1")
+            .bind(staff_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn accepting_an_invite_activates_the_staff_and_signs_in(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_invite(&db, &store, "new@example.com", "invite-token", 60).await;
+
+        let accepted = try_accept_invite(&state, "invite-token").await.unwrap();
+        assert_eq!(accepted.response.staff_id, staff_id.to_string());
+        assert_eq!(accepted.response.tenant_id, store.tenant_id.to_string());
+        assert!(!accepted.response.access_token.is_empty());
+        assert!(!accepted.refresh_token.is_empty());
+        assert_eq!(staff_status(&db, staff_id).await, "active");
+        assert!(invite_accepted(&db, &store, "new@example.com").await);
+        assert_eq!(refresh_token_count(&db, staff_id).await, 1);
+
+        // The chosen password works for an ordinary sign-in afterwards.
+        try_sign_in(&state, &store, "new@example.com", PASSWORD).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn expired_invite_is_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_invite(&db, &store, "new@example.com", "invite-token", -1).await;
+
+        let message = accept_error_message(try_accept_invite(&state, "invite-token").await);
+        assert_eq!(message, "invite token has expired");
+        assert_eq!(staff_status(&db, staff_id).await, "invited");
+        assert!(!invite_accepted(&db, &store, "new@example.com").await);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn invite_can_be_accepted_only_once(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_invite(&db, &store, "new@example.com", "invite-token", 60).await;
+
+        try_accept_invite(&state, "invite-token").await.unwrap();
+        let message = accept_error_message(try_accept_invite(&state, "invite-token").await);
+        assert_eq!(message, "invite token is invalid");
+        assert_eq!(refresh_token_count(&db, staff_id).await, 1);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn wrong_invite_token_is_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_invite(&db, &store, "new@example.com", "invite-token", 60).await;
+
+        let message = accept_error_message(try_accept_invite(&state, "other-token").await);
+        assert_eq!(message, "invite token is invalid");
+        assert_eq!(staff_status(&db, staff_id).await, "invited");
+        assert!(!invite_accepted(&db, &store, "new@example.com").await);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn failed_sign_in_leaves_the_invite_usable(db: PgPool) {
+        let mut state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_invite(&db, &store, "new@example.com", "invite-token", 60).await;
+
+        // Without a signing secret the tokens cannot be issued after the invite is claimed.
+        state.auth.jwt_secret = None;
+        match try_accept_invite(&state, "invite-token").await {
+            Err(IdentityError::Internal(message)) => assert_eq!(message, "AUTH_JWT_SECRET is required"),
+            other => panic!("expected Internal, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(staff_status(&db, staff_id).await, "invited");
+        assert!(!invite_accepted(&db, &store, "new@example.com").await);
+        assert_eq!(refresh_token_count(&db, staff_id).await, 0);
+
+        state.auth.jwt_secret = Some("test-secret".to_string());
+        try_accept_invite(&state, "invite-token").await.unwrap();
+        assert_eq!(staff_status(&db, staff_id).await, "active");
+    }
+
+    async fn create_password_reset(
+        db: &PgPool,
+        store: &TestStore,
+        staff_id: uuid::Uuid,
+        token: &str,
+        expires_in_minutes: i64,
+    ) {
+        sqlx::query(
+            r#"
+            INSERT INTO staff_password_resets (store_id, staff_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, now() + make_interval(mins => $4))
+            "#,
+        )
+        .bind(store.store_id)
+        .bind(staff_id)
+        .bind(hash_token(token))
+        .bind(expires_in_minutes as i32)
+        .execute(db)
+        .await
+        .unwrap();
+    }
+
+    async fn try_reset_password(state: &AppState, store: &TestStore, token: &str) -> IdentityResult<()> {
+        reset_password(
+            state,
+            pb::IdentityResetPasswordRequest {
+                store: store.store_context(),
+                token: token.to_string(),
+                new_password: "Renewed1pass".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|_| ())
+    }
+
+    fn invalid_argument_message(result: IdentityResult<()>) -> String {
+        match result {
+            Err(IdentityError::InvalidArgument(message)) => message,
+            other => panic!("expected InvalidArgument, got {other:?}"),
+        }
+    }
+
+    async fn password_hash(db: &PgPool, staff_id: uuid::Uuid) -> String {
+        sqlx::query_scalar("SELECT password_hash FROM store_staff WHERE id = $1")
+            .bind(staff_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn expired_reset_token_is_rejected_and_removed(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        let before = password_hash(&db, staff_id).await;
+        create_password_reset(&db, &store, staff_id, "expired-token", -1).await;
+
+        let message = invalid_argument_message(try_reset_password(&state, &store, "expired-token").await);
+        assert_eq!(message, "reset token has expired");
+        assert_eq!(password_hash(&db, staff_id).await, before);
+        let message = invalid_argument_message(try_reset_password(&state, &store, "expired-token").await);
+        assert_eq!(message, "reset token is invalid");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn reset_token_works_only_once(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        create_password_reset(&db, &store, staff_id, "reset-token", 60).await;
+
+        try_reset_password(&state, &store, "reset-token").await.unwrap();
+        let after_first = password_hash(&db, staff_id).await;
+        assert!(verify_password_hash("Renewed1pass", Some(&after_first)));
+
+        let message = invalid_argument_message(try_reset_password(&state, &store, "reset-token").await);
+        assert_eq!(message, "reset token is invalid");
+        assert_eq!(password_hash(&db, staff_id).await, after_first);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn reset_token_is_bound_to_its_store(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let other_store = test_support::create_store(&db).await;
+        let staff_id = create_staff(&db, &store, "staff@example.com").await;
+        let before = password_hash(&db, staff_id).await;
+        create_password_reset(&db, &store, staff_id, "reset-token", 60).await;
+
+        let message = invalid_argument_message(try_reset_password(&state, &other_store, "reset-token").await);
+        assert_eq!(message, "reset token is invalid");
+        assert_eq!(password_hash(&db, staff_id).await, before);
+
+        // The attempt from the other store does not consume the token.
+        try_reset_password(&state, &store, "reset-token").await.unwrap();
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt;
 pub enum StoreStaffStatus {
     Active,
     Invited,
+    Locked,
 }
 
 impl StoreStaffStatus {
@@ -11,6 +12,7 @@ impl StoreStaffStatus {
         match self {
             Self::Active => "active",
             Self::Invited => "invited",
+            Self::Locked => "locked",
         }
     }
 }
@@ -29,7 +31,8 @@ impl TryFrom<&str> for StoreStaffStatus {
         match normalized {
             "active" => Ok(Self::Active),
             "invited" => Ok(Self::Invited),
-            _ => Err("status must be active or invited"),
+            "locked" => Ok(Self::Locked),
+            _ => Err("status must be active, invited or locked"),
         }
     }
 }
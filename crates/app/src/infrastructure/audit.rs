@@ -1,13 +1,19 @@
+use async_trait::async_trait;
 use axum::{Json, http::StatusCode};
 use serde_json::Value;
-use sqlx::{Postgres, Transaction};
+use sqlx::{Executor, PgPool, Postgres, Transaction};
 
 use crate::{
-    AppState,
-    rpc::{json::ConnectError, request_context},
-    shared::audit_action::AuditAction,
+    rpc::{
+        json::{ConnectError, ErrorCode},
+        request_context,
+    },
+    shared::audit_action::{AuditAction, AuditActionTrait},
 };
 
+pub type AuditResult = Result<(), (StatusCode, Json<ConnectError>)>;
+
+#[derive(Clone)]
 pub struct AuditInput {
     pub store_id: Option<String>,
     pub actor_id: Option<String>,
@@ -23,84 +29,62 @@ pub struct AuditInput {
     pub metadata_json: Option<Value>,
 }
 
-pub async fn record(state: &AppState, input: AuditInput) -> Result<(), (StatusCode, Json<ConnectError>)> {
-    let mut request_id = input.request_id;
-    let mut ip_address = input.ip_address;
-    let mut user_agent = input.user_agent;
-    let mut store_id = input.store_id;
-    if let Some(ctx) = request_context::current() {
-        if request_id.is_none() {
-            request_id = ctx.request_id;
-        }
-        if ip_address.is_none() {
-            ip_address = ctx.ip_address;
-        }
-        if user_agent.is_none() {
-            user_agent = ctx.user_agent;
-        }
-        if store_id.is_none() {
-            store_id = ctx.store_id;
-        }
+/// Destination for audit events. `AppState.audit_writer` is the single entry point for services.
+#[async_trait]
+pub trait AuditWriter: Send + Sync {
+    async fn record(&self, input: AuditInput) -> AuditResult;
+
+    /// Records inside the caller's transaction. Writers that do not use Postgres may ignore `tx`.
+    async fn record_tx(&self, tx: &mut Transaction<'_, Postgres>, input: AuditInput) -> AuditResult;
+}
+
+/// Writes audit events to the `audit_logs` table.
+pub struct PgAuditWriter {
+    db: PgPool,
+}
+
+impl PgAuditWriter {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
     }
+}
 
-    sqlx::query(
-        r#"
-        INSERT INTO audit_logs (
-            store_id, actor_id, actor_type, action, target_type, target_id,
-            request_id, ip_address, user_agent,
-            before_json, after_json, metadata_json
-        ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
-        "#,
-    )
-    .bind(store_id.and_then(|id| uuid::Uuid::parse_str(&id).ok()))
-    .bind(input.actor_id)
-    .bind(input.actor_type)
-    .bind(input.action.as_str())
-    .bind(input.target_type)
-    .bind(input.target_id)
-    .bind(request_id)
-    .bind(ip_address)
-    .bind(user_agent)
-    .bind(input.before_json)
-    .bind(input.after_json)
-    .bind(input.metadata_json)
-    .execute(&state.db)
-    .await
-    .map_err(|err| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("audit error: {}", err),
-            }),
-        )
-    })?;
-    Ok(())
+#[async_trait]
+impl AuditWriter for PgAuditWriter {
+    async fn record(&self, input: AuditInput) -> AuditResult {
+        insert_audit_log(&self.db, with_request_context(input)).await
+    }
+
+    async fn record_tx(&self, tx: &mut Transaction<'_, Postgres>, input: AuditInput) -> AuditResult {
+        insert_audit_log(tx.as_mut(), with_request_context(input)).await
+    }
 }
 
-pub async fn record_tx(
-    tx: &mut Transaction<'_, Postgres>,
-    input: AuditInput,
-) -> Result<(), (StatusCode, Json<ConnectError>)> {
-    let mut request_id = input.request_id;
-    let mut ip_address = input.ip_address;
-    let mut user_agent = input.user_agent;
-    let mut store_id = input.store_id;
+fn with_request_context(mut input: AuditInput) -> AuditInput {
     if let Some(ctx) = request_context::current() {
-        if request_id.is_none() {
-            request_id = ctx.request_id;
+        if input.metadata_json.is_none() {
+            input.metadata_json = actor_metadata(&ctx);
         }
-        if ip_address.is_none() {
-            ip_address = ctx.ip_address;
+        if input.request_id.is_none() {
+            input.request_id = ctx.request_id;
         }
-        if user_agent.is_none() {
-            user_agent = ctx.user_agent;
+        if input.ip_address.is_none() {
+            input.ip_address = ctx.ip_address;
         }
-        if store_id.is_none() {
-            store_id = ctx.store_id;
+        if input.user_agent.is_none() {
+            input.user_agent = ctx.user_agent;
+        }
+        if input.store_id.is_none() {
+            input.store_id = ctx.store_id;
         }
     }
+    input
+}
 
+async fn insert_audit_log<'e, E>(exec: E, input: AuditInput) -> AuditResult
+where
+    E: Executor<'e, Database = Postgres>,
+{
     sqlx::query(
         r#"
         INSERT INTO audit_logs (
@@ -110,28 +94,35 @@ pub async fn record_tx(
         ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
         "#,
     )
-    .bind(store_id.and_then(|id| uuid::Uuid::parse_str(&id).ok()))
+    .bind(input.store_id.and_then(|id| uuid::Uuid::parse_str(&id).ok()))
     .bind(input.actor_id)
     .bind(input.actor_type)
     .bind(input.action.as_str())
     .bind(input.target_type)
     .bind(input.target_id)
-    .bind(request_id)
-    .bind(ip_address)
-    .bind(user_agent)
+    .bind(input.request_id)
+    .bind(input.ip_address)
+    .bind(input.user_agent)
     .bind(input.before_json)
     .bind(input.after_json)
     .bind(input.metadata_json)
-    .execute(tx.as_mut())
+    .execute(exec)
     .await
     .map_err(|err| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("audit error: {}", err),
-            }),
+            Json(ConnectError::new(ErrorCode::Internal, format!("audit error: {}", err))),
         )
     })?;
     Ok(())
 }
+
+fn actor_metadata(ctx: &request_context::RequestContext) -> Option<Value> {
+    if ctx.role_key.is_none() && ctx.display_name.is_none() {
+        return None;
+    }
+    Some(serde_json::json!({
+        "actor_role_key": ctx.role_key,
+        "actor_display_name": ctx.display_name,
+    }))
+}
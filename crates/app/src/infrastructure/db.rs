@@ -1,15 +1,15 @@
 use axum::{Json, http::StatusCode};
 
-use crate::{AppState, rpc::json::ConnectError};
+use crate::{
+    AppState,
+    rpc::json::{ConnectError, ErrorCode},
+};
 
 pub async fn ping(state: &AppState) -> Result<(), (StatusCode, Json<ConnectError>)> {
     sqlx::query("SELECT 1").execute(&state.db).await.map_err(|err| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("db error: {}", err),
-            }),
+            Json(ConnectError::new(ErrorCode::Internal, format!("db error: {}", err))),
         )
     })?;
     Ok(())
@@ -18,9 +18,6 @@ pub async fn ping(state: &AppState) -> Result<(), (StatusCode, Json<ConnectError
 pub fn error(err: sqlx::Error) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::INTERNAL_SERVER_ERROR,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::Internal,
-            message: format!("db error: {}", err),
-        }),
+        Json(ConnectError::new(ErrorCode::Internal, format!("db error: {}", err))),
     )
 }
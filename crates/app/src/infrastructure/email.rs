@@ -1,15 +1,90 @@
-use lettre::message::{Mailbox, SinglePart};
-use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use handlebars::Handlebars;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::Value;
+use std::{fmt, path::Path};
 
-use crate::rpc::json::ConnectError;
+use crate::rpc::json::{ConnectError, ErrorCode};
 use axum::Json;
 use axum::http::StatusCode;
 
+const EMBEDDED_TEMPLATES: &[(&str, &str)] = &[
+    ("invite", include_str!("../../templates/email/invite.html")),
+    (
+        "password_reset",
+        include_str!("../../templates/email/password_reset.html"),
+    ),
+    (
+        "order_confirmation",
+        include_str!("../../templates/email/order_confirmation.html"),
+    ),
+];
+
+/// A template override that cannot be read or does not compile.
+#[derive(Debug)]
+pub struct EmailTemplateError(String);
+
+impl fmt::Display for EmailTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to load email templates: {}", self.0)
+    }
+}
+
+impl std::error::Error for EmailTemplateError {}
+
+/// Renders the HTML part of outgoing emails from Handlebars templates.
+pub struct EmailRenderer {
+    registry: Handlebars<'static>,
+}
+
+impl EmailRenderer {
+    /// Templates are embedded at build time; `EMAIL_TEMPLATE_DIR/<name>.html` overrides a template when present.
+    /// `main` loads it once at startup so a broken override stops the server.
+    pub fn from_env() -> Result<Self, EmailTemplateError> {
+        let template_dir = std::env::var("EMAIL_TEMPLATE_DIR").ok().filter(|dir| !dir.is_empty());
+        Self::load(template_dir.as_deref().map(Path::new))
+    }
+
+    pub fn load(template_dir: Option<&Path>) -> Result<Self, EmailTemplateError> {
+        let mut registry = Handlebars::new();
+        for (name, embedded) in EMBEDDED_TEMPLATES {
+            let source = match template_dir {
+                Some(dir) => {
+                    let path = dir.join(format!("{name}.html"));
+                    match std::fs::read_to_string(&path) {
+                        Ok(source) => source,
+                        Err(err) if err.kind() == std::io::ErrorKind::NotFound => embedded.to_string(),
+                        Err(err) => return Err(EmailTemplateError(format!("{}: {err}", path.display()))),
+                    }
+                }
+                None => embedded.to_string(),
+            };
+            registry
+                .register_template_string(name, source)
+                .map_err(|err| EmailTemplateError(format!("template {name}: {err}")))?;
+        }
+        Ok(Self { registry })
+    }
+
+    pub fn render(&self, template: &str, context: &Value) -> Result<String, (StatusCode, Json<ConnectError>)> {
+        self.registry.render(template, context).map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(
+                    ErrorCode::Internal,
+                    format!("failed to render email template {template}: {err}"),
+                )),
+            )
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EmailConfig {
     pub smtp_host: String,
     pub smtp_port: u16,
     pub from: String,
+    pub from_name: String,
     pub base_url: String,
 }
 
@@ -21,11 +96,13 @@ impl EmailConfig {
             .and_then(|v| v.parse().ok())
             .unwrap_or(1025);
         let from = std::env::var("SMTP_FROM").unwrap_or_else(|_| "no-reply@local.test".to_string());
+        let from_name = std::env::var("SMTP_FROM_NAME").unwrap_or_default();
         let base_url = std::env::var("BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
         Self {
             smtp_host,
             smtp_port,
             from,
+            from_name,
             base_url,
         }
     }
@@ -37,9 +114,18 @@ impl EmailConfig {
             token
         )
     }
+
+    pub fn password_reset_url(&self, token: &str) -> String {
+        format!(
+            "{}/identity/reset-password?token={}",
+            self.base_url.trim_end_matches('/'),
+            token
+        )
+    }
 }
 
 pub async fn send_invite_email(
+    renderer: &EmailRenderer,
     config: &EmailConfig,
     to_email: &str,
     store_name: &str,
@@ -47,25 +133,6 @@ pub async fn send_invite_email(
     role_name: Option<&str>,
     token: &str,
 ) -> Result<(), (StatusCode, Json<ConnectError>)> {
-    let to = to_email.parse::<Mailbox>().map_err(|_| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "invalid email address".to_string(),
-            }),
-        )
-    })?;
-    let from = config.from.parse::<Mailbox>().map_err(|_| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: "invalid SMTP_FROM".to_string(),
-            }),
-        )
-    })?;
-
     let invite_url = config.invite_url(token);
     let name_line = display_name.filter(|v| !v.is_empty()).unwrap_or("there");
     let role_line = role_name.filter(|v| !v.is_empty()).unwrap_or("staff");
@@ -78,19 +145,76 @@ pub async fn send_invite_email(
         role = role_line,
         url = invite_url
     );
+    let html = renderer.render(
+        "invite",
+        &serde_json::json!({
+            "store_name": store_name,
+            "display_name": name_line,
+            "role_name": role_line,
+            "invite_url": invite_url,
+        }),
+    )?;
+
+    send_email(config, to_email, subject, body, html).await
+}
+
+pub async fn send_password_reset_email(
+    renderer: &EmailRenderer,
+    config: &EmailConfig,
+    to_email: &str,
+    store_name: &str,
+    token: &str,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let reset_url = config.password_reset_url(token);
+    let subject = format!("{}: password reset", store_name);
+    let body = format!(
+        "Hello,\n\nA password reset was requested for your {store} staff account.\n\nReset password: {url}\n\nThis link expires in 1 hour. If you did not request this, you can ignore this email.\n",
+        store = store_name,
+        url = reset_url
+    );
+    let html = renderer.render(
+        "password_reset",
+        &serde_json::json!({
+            "store_name": store_name,
+            "reset_url": reset_url,
+        }),
+    )?;
+
+    send_email(config, to_email, subject, body, html).await
+}
+
+// Sent as multipart/alternative so clients without HTML support fall back to the plain body.
+async fn send_email(
+    config: &EmailConfig,
+    to_email: &str,
+    subject: String,
+    body: String,
+    html: String,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let to = to_email.parse::<Mailbox>().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "invalid email address")),
+        )
+    })?;
+    let from_address = config.from.parse::<Address>().map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ConnectError::new(ErrorCode::Internal, "invalid SMTP_FROM")),
+        )
+    })?;
+    let from_name = Some(config.from_name.clone()).filter(|name| !name.is_empty());
+    let from = Mailbox::new(from_name, from_address);
 
     let email = Message::builder()
         .from(from)
         .to(to)
         .subject(subject)
-        .singlepart(SinglePart::plain(body))
+        .multipart(MultiPart::alternative_plain_html(body, html))
         .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::Internal,
-                    message: "failed to build email".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, "failed to build email")),
             )
         })?;
 
@@ -101,12 +225,114 @@ pub async fn send_invite_email(
     mailer.send(email).await.map_err(|err| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("failed to send email: {err}"),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::Internal,
+                format!("failed to send email: {err}"),
+            )),
         )
     })?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renderer() -> EmailRenderer {
+        EmailRenderer::load(None).unwrap()
+    }
+
+    #[test]
+    fn every_embedded_template_renders() {
+        let renderer = renderer();
+        let invite = renderer
+            .render(
+                "invite",
+                &serde_json::json!({
+                    "store_name": "Tokyo Shop",
+                    "display_name": "Hanako",
+                    "role_name": "staff",
+                    "invite_url": "https://shop.example/identity/accept-invite?token=abc",
+                }),
+            )
+            .unwrap();
+        assert!(invite.contains("Hello Hanako,"));
+        assert!(invite.contains("https://shop.example/identity/accept-invite?token&#x3D;abc"));
+
+        let reset = renderer
+            .render(
+                "password_reset",
+                &serde_json::json!({ "store_name": "Tokyo Shop", "reset_url": "https://shop.example/reset" }),
+            )
+            .unwrap();
+        assert!(reset.contains("<strong>Tokyo Shop</strong> staff account"));
+        assert!(reset.contains("https://shop.example/reset"));
+
+        let order = renderer
+            .render(
+                "order_confirmation",
+                &serde_json::json!({
+                    "store_name": "Tokyo Shop",
+                    "order_id": "ORD-1",
+                    "items": [{ "name": "Mug", "quantity": 2, "amount": "¥2,400" }],
+                    "shipping_amount": "¥500",
+                    "total_amount": "¥2,900",
+                }),
+            )
+            .unwrap();
+        assert!(order.contains("Order number: ORD-1"));
+        assert!(order.contains("Mug &times; 2"));
+        assert!(order.contains("¥2,900"));
+        assert!(!order.contains("View your order"));
+    }
+
+    #[test]
+    fn variables_are_html_escaped() {
+        let html = renderer()
+            .render(
+                "password_reset",
+                &serde_json::json!({
+                    "store_name": "<script>alert(1)</script> & \"Co\"",
+                    "reset_url": "https://shop.example/reset",
+                }),
+            )
+            .unwrap();
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt; &amp; &quot;Co&quot;"));
+    }
+
+    #[test]
+    fn unknown_template_is_an_internal_error() {
+        let (status, Json(err)) = renderer().render("missing", &serde_json::json!({})).unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(err.code, ErrorCode::Internal));
+    }
+
+    #[test]
+    fn broken_override_fails_to_load() {
+        let dir = std::env::temp_dir().join(format!("email-templates-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("invite.html"), "{{#if store_name}}unclosed").unwrap();
+
+        let err = EmailRenderer::load(Some(&dir))
+            .err()
+            .expect("broken template should fail");
+        assert!(err.to_string().contains("template invite"), "{err}");
+
+        // Templates without an override keep the embedded version.
+        std::fs::write(dir.join("invite.html"), "Hi {{display_name}}").unwrap();
+        let renderer = EmailRenderer::load(Some(&dir)).unwrap();
+        let invite = renderer
+            .render("invite", &serde_json::json!({ "display_name": "Hanako" }))
+            .unwrap();
+        assert_eq!(invite, "Hi Hanako");
+        assert!(
+            renderer
+                .render("password_reset", &serde_json::json!({ "store_name": "Tokyo Shop" }))
+                .unwrap()
+                .contains("Tokyo Shop")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,7 +2,10 @@ use axum::{Json, http::StatusCode};
 use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row, postgres::PgRow};
 
-use crate::{infrastructure::db, rpc::json::ConnectError};
+use crate::{
+    infrastructure::db,
+    rpc::json::{ConnectError, ErrorCode},
+};
 
 #[derive(Debug, Clone)]
 pub struct MetafieldDefinitionRecord {
@@ -71,10 +74,7 @@ pub fn normalize_optional_json(value: String) -> Result<String, (StatusCode, Jso
     if serde_json::from_str::<serde_json::Value>(&value).is_err() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "invalid json provided".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "invalid json provided")),
         ));
     }
     Ok(value)
@@ -99,7 +99,7 @@ pub async fn list_definitions(
                created_at,
                updated_at
         FROM metafield_definitions
-        WHERE owner_type = $1
+        WHERE owner_type = $1 AND deleted_at IS NULL
         ORDER BY namespace ASC, key ASC
         "#,
     )
@@ -169,7 +169,7 @@ pub async fn update_definition(
             validations_json = $7::jsonb,
             visibility_json = $8::jsonb,
             updated_at = now()
-        WHERE id = $9 AND owner_type = $10
+        WHERE id = $9 AND owner_type = $10 AND deleted_at IS NULL
         RETURNING id::text as id,
                   owner_type,
                   namespace,
@@ -221,7 +221,7 @@ pub async fn fetch_definition(
                created_at,
                updated_at
         FROM metafield_definitions
-        WHERE id = $1 AND owner_type = $2
+        WHERE id = $1 AND owner_type = $2 AND deleted_at IS NULL
         "#,
     )
     .bind(definition_id)
@@ -233,6 +233,67 @@ pub async fn fetch_definition(
     Ok(row.map(|row| definition_from_row(&row)))
 }
 
+// Values of a soft-deleted definition are kept for historical reference.
+pub async fn soft_delete_definition(
+    pool: &PgPool,
+    owner_type: &str,
+    definition_id: &uuid::Uuid,
+) -> Result<bool, (StatusCode, Json<ConnectError>)> {
+    let result = sqlx::query(
+        r#"
+        UPDATE metafield_definitions
+        SET deleted_at = now(), updated_at = now()
+        WHERE id = $1 AND owner_type = $2 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(definition_id)
+    .bind(owner_type)
+    .execute(pool)
+    .await
+    .map_err(db::error)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Only soft-deleted definitions can be purged; returns the number of removed values.
+pub async fn purge_definition(
+    pool: &PgPool,
+    owner_type: &str,
+    definition_id: &uuid::Uuid,
+) -> Result<Option<u64>, (StatusCode, Json<ConnectError>)> {
+    let mut tx = pool.begin().await.map_err(db::error)?;
+    let definition = sqlx::query(
+        r#"
+        SELECT 1
+        FROM metafield_definitions
+        WHERE id = $1 AND owner_type = $2 AND deleted_at IS NOT NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(definition_id)
+    .bind(owner_type)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if definition.is_none() {
+        return Ok(None);
+    }
+
+    let values = sqlx::query("DELETE FROM metafield_values WHERE definition_id = $1")
+        .bind(definition_id)
+        .execute(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    sqlx::query("DELETE FROM metafield_definitions WHERE id = $1")
+        .bind(definition_id)
+        .execute(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    tx.commit().await.map_err(db::error)?;
+
+    Ok(Some(values.rows_affected()))
+}
+
 pub async fn list_values(
     pool: &PgPool,
     owner_type: &str,
@@ -262,6 +323,7 @@ pub async fn list_values(
         JOIN metafield_definitions d ON d.id = v.definition_id
         WHERE d.owner_type = $1
           AND v.owner_id = $2
+          AND d.deleted_at IS NULL
         ORDER BY d.namespace ASC, d.key ASC
         "#,
     )
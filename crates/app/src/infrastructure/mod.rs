@@ -9,3 +9,4 @@ pub mod metafields;
 pub mod outbox;
 pub mod search;
 pub mod storage;
+pub mod store_cache;
@@ -1,8 +1,53 @@
 use axum::{Json, http::StatusCode};
+use once_cell::sync::Lazy;
+use rs_common::env::EnvError;
 use serde_json::Value;
-use sqlx::{Postgres, Transaction};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
-use crate::{AppState, rpc::json::ConnectError, rpc::request_context};
+use crate::{
+    AppState,
+    rpc::json::{ConnectError, ErrorCode},
+    rpc::request_context,
+};
+
+static SCHEMA_REGISTRY: Lazy<RwLock<SchemaRegistry>> = Lazy::new(|| RwLock::new(SchemaRegistry::default()));
+
+#[derive(Default)]
+struct SchemaRegistry {
+    mode: SchemaValidationMode,
+    validators: HashMap<String, Arc<jsonschema::Validator>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SchemaValidationMode {
+    Off,
+    #[default]
+    Warn,
+    Strict,
+}
+
+impl SchemaValidationMode {
+    /// Reads `OUTBOX_SCHEMA_VALIDATION` (`off` / `warn` / `strict`, default `warn`).
+    pub fn from_env() -> Result<Self, EnvError> {
+        Self::parse(std::env::var("OUTBOX_SCHEMA_VALIDATION").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Result<Self, EnvError> {
+        match value.map(str::trim) {
+            None | Some("") | Some("warn") => Ok(SchemaValidationMode::Warn),
+            Some("off") => Ok(SchemaValidationMode::Off),
+            Some("strict") => Ok(SchemaValidationMode::Strict),
+            Some(other) => Err(EnvError::Invalid {
+                name: "OUTBOX_SCHEMA_VALIDATION".to_string(),
+                value: other.to_string(),
+            }),
+        }
+    }
+}
 
 pub struct OutboxEventInput {
     pub tenant_id: String,
@@ -13,17 +58,45 @@ pub struct OutboxEventInput {
     pub payload_json: Value,
 }
 
+/// Loads the latest schema per event type and the validation mode; `main` calls it once at startup.
+pub async fn load_schemas(db: &PgPool, mode: SchemaValidationMode) -> Result<usize, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT ON (event_type) event_type, json_schema_json
+        FROM outbox_event_schemas
+        ORDER BY event_type, version DESC
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut validators = HashMap::new();
+    for row in rows {
+        let event_type: String = row.get("event_type");
+        let schema: Value = row.get("json_schema_json");
+        match jsonschema::validator_for(&schema) {
+            Ok(validator) => {
+                validators.insert(event_type, Arc::new(validator));
+            }
+            Err(err) => {
+                tracing::warn!(event_type = %event_type, error = %err, "invalid outbox event schema");
+            }
+        }
+    }
+    let count = validators.len();
+    *SCHEMA_REGISTRY.write().expect("outbox schema registry lock") = SchemaRegistry { mode, validators };
+    Ok(count)
+}
+
 pub async fn enqueue(state: &AppState, input: OutboxEventInput) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    validate_payload(&input)?;
     let event_id = uuid::Uuid::new_v4();
     let idempotency_key = build_idempotency_key(&input);
     let store_uuid = input.store_id.and_then(|id| uuid::Uuid::parse_str(&id).ok());
     let tenant_uuid = uuid::Uuid::parse_str(&input.tenant_id).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tenant_id is invalid".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "tenant_id is invalid")),
         )
     })?;
 
@@ -48,10 +121,10 @@ pub async fn enqueue(state: &AppState, input: OutboxEventInput) -> Result<(), (S
     .map_err(|err| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("outbox enqueue failed: {}", err),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::Internal,
+                format!("outbox enqueue failed: {}", err),
+            )),
         )
     })?;
 
@@ -62,16 +135,14 @@ pub async fn enqueue_tx(
     tx: &mut Transaction<'_, Postgres>,
     input: OutboxEventInput,
 ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    validate_payload(&input)?;
     let event_id = uuid::Uuid::new_v4();
     let idempotency_key = build_idempotency_key(&input);
     let store_uuid = input.store_id.and_then(|id| uuid::Uuid::parse_str(&id).ok());
     let tenant_uuid = uuid::Uuid::parse_str(&input.tenant_id).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tenant_id is invalid".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "tenant_id is invalid")),
         )
     })?;
 
@@ -96,16 +167,83 @@ pub async fn enqueue_tx(
     .map_err(|err| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::Internal,
-                message: format!("outbox enqueue failed: {}", err),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::Internal,
+                format!("outbox enqueue failed: {}", err),
+            )),
         )
     })?;
 
     Ok(())
 }
 
+// Search documents are rebuilt asynchronously by the reindex loop; writers only record
+// which product changed, inside their own transaction.
+pub async fn enqueue_search_reindex_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: &str,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let product_uuid = uuid::Uuid::parse_str(product_id).map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "product_id is invalid")),
+        )
+    })?;
+    sqlx::query("INSERT INTO search_reindex_needed (product_id) VALUES ($1)")
+        .bind(product_uuid)
+        .execute(tx.as_mut())
+        .await
+        .map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(
+                    ErrorCode::Internal,
+                    format!("search reindex enqueue failed: {}", err),
+                )),
+            )
+        })?;
+    Ok(())
+}
+
+fn validate_payload(input: &OutboxEventInput) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let (mode, validator) = {
+        let registry = SCHEMA_REGISTRY.read().expect("outbox schema registry lock");
+        (registry.mode, registry.validators.get(&input.event_type).cloned())
+    };
+    if mode == SchemaValidationMode::Off {
+        return Ok(());
+    }
+    let Some(validator) = validator else {
+        return Ok(());
+    };
+    let errors = validator
+        .iter_errors(&input.payload_json)
+        .map(|err| format!("{}: {}", err.instance_path, err))
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    if mode == SchemaValidationMode::Strict {
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ConnectError::new(
+                ErrorCode::Internal,
+                format!(
+                    "outbox payload does not match schema for {}: {}",
+                    input.event_type,
+                    errors.join("; ")
+                ),
+            )),
+        ));
+    }
+    tracing::warn!(
+        event_type = %input.event_type,
+        errors = ?errors,
+        "outbox payload does not match schema"
+    );
+    Ok(())
+}
+
 fn build_idempotency_key(input: &OutboxEventInput) -> String {
     if let Some(ctx) = request_context::current()
         && let Some(request_id) = ctx.request_id
@@ -114,3 +252,26 @@ fn build_idempotency_key(input: &OutboxEventInput) -> String {
     }
     uuid::Uuid::new_v4().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_mode_defaults_to_warn_and_rejects_unknown_values() {
+        assert_eq!(SchemaValidationMode::parse(None), Ok(SchemaValidationMode::Warn));
+        assert_eq!(SchemaValidationMode::parse(Some("")), Ok(SchemaValidationMode::Warn));
+        assert_eq!(SchemaValidationMode::parse(Some("off")), Ok(SchemaValidationMode::Off));
+        assert_eq!(
+            SchemaValidationMode::parse(Some("strict")),
+            Ok(SchemaValidationMode::Strict)
+        );
+        assert_eq!(
+            SchemaValidationMode::parse(Some("Strict")),
+            Err(EnvError::Invalid {
+                name: "OUTBOX_SCHEMA_VALIDATION".to_string(),
+                value: "Strict".to_string(),
+            })
+        );
+    }
+}
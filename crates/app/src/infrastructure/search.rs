@@ -1,6 +1,54 @@
 use axum::{Json, http::StatusCode};
 
-use crate::rpc::json::ConnectError;
+use crate::rpc::json::{ConnectError, ErrorCode};
+
+pub const VALID_RANKING_RULES: &[&str] = &["words", "typo", "proximity", "attribute", "sort", "exactness"];
+pub const SORTABLE_ATTRIBUTES: &[&str] = &["title", "price_amount", "created_at_unix"];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub typo_tolerance_enabled: bool,
+    pub ranking_rules: Vec<String>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            typo_tolerance_enabled: true,
+            ranking_rules: VALID_RANKING_RULES.iter().map(|rule| rule.to_string()).collect(),
+        }
+    }
+}
+
+fn settings_error(err: meilisearch_sdk::errors::Error) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ConnectError::new(
+            ErrorCode::Internal,
+            format!("search settings error: {}", err),
+        )),
+    )
+}
+
+/// Maps a `SearchProductsRequest.sort` value to a Meilisearch sort expression.
+/// An empty value keeps relevance ordering.
+pub fn sort_expression(sort: &str) -> Result<Option<&'static str>, (StatusCode, Json<ConnectError>)> {
+    match sort {
+        "" => Ok(None),
+        "price_asc" => Ok(Some("price_amount:asc")),
+        "price_desc" => Ok(Some("price_amount:desc")),
+        "title_asc" => Ok(Some("title:asc")),
+        "created_desc" => Ok(Some("created_at_unix:desc")),
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "sort must be price_asc|price_desc|title_asc|created_desc",
+            )),
+        )),
+    }
+}
 
 #[derive(Clone)]
 pub struct SearchClient {
@@ -17,30 +65,46 @@ impl SearchClient {
         }
     }
 
+    /// Calls Meilisearch `GET /health`; succeeds only when it reports `available`.
+    pub async fn ping(&self) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        let unavailable = |message: String| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ConnectError::new(ErrorCode::Internal, message)),
+            )
+        };
+        let health = self
+            .client
+            .health()
+            .await
+            .map_err(|err| unavailable(format!("search health error: {}", err)))?;
+        if health.status != "available" {
+            return Err(unavailable(format!("search status: {}", health.status)));
+        }
+        Ok(())
+    }
+
     pub async fn search_products(
         &self,
         query: &str,
         limit: usize,
         tenant_id: &str,
+        sort: Option<&str>,
     ) -> Result<Vec<SearchProduct>, (StatusCode, Json<ConnectError>)> {
         let index = self.client.index(self.index_name.as_str());
         let filter = format!("tenant_id = \"{}\"", tenant_id);
-        let results = index
-            .search()
-            .with_query(query)
-            .with_limit(limit)
-            .with_filter(&filter)
-            .execute::<SearchProduct>()
-            .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: format!("search error: {}", err),
-                    }),
-                )
-            })?;
+        let sort = sort.map(|expr| [expr]);
+        let mut search = index.search();
+        search.with_query(query).with_limit(limit).with_filter(&filter);
+        if let Some(sort) = sort.as_ref() {
+            search.with_sort(sort);
+        }
+        let results = search.execute::<SearchProduct>().await.map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(ErrorCode::Internal, format!("search error: {}", err))),
+            )
+        })?;
         Ok(results.hits.into_iter().map(|hit| hit.result).collect())
     }
 
@@ -49,16 +113,30 @@ impl SearchClient {
         index.add_or_replace(products, Some("id")).await.map_err(|err| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::Internal,
-                    message: format!("search upsert error: {}", err),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::Internal,
+                    format!("search upsert error: {}", err),
+                )),
+            )
+        })?;
+        Ok(())
+    }
+
+    pub async fn delete_product(&self, product_id: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        let index = self.client.index(self.index_name.as_str());
+        index.delete_document(product_id).await.map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(
+                    ErrorCode::Internal,
+                    format!("search delete error: {}", err),
+                )),
             )
         })?;
         Ok(())
     }
 
-    pub async fn ensure_settings(&self) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    pub async fn ensure_settings(&self, config: Option<&SearchConfig>) -> Result<(), (StatusCode, Json<ConnectError>)> {
         let index = self.client.index(self.index_name.as_str());
         index
             .set_filterable_attributes(&[
@@ -70,15 +148,24 @@ impl SearchClient {
                 "category_ids",
             ])
             .await
-            .map_err(|err| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: format!("search settings error: {}", err),
-                    }),
-                )
-            })?;
+            .map_err(settings_error)?;
+        index
+            .set_sortable_attributes(SORTABLE_ATTRIBUTES)
+            .await
+            .map_err(settings_error)?;
+        if let Some(config) = config {
+            index
+                .set_typo_tolerance(&meilisearch_sdk::settings::TypoToleranceSettings {
+                    enabled: Some(config.typo_tolerance_enabled),
+                    ..Default::default()
+                })
+                .await
+                .map_err(settings_error)?;
+            index
+                .set_ranking_rules(&config.ranking_rules)
+                .await
+                .map_err(settings_error)?;
+        }
         Ok(())
     }
 }
@@ -134,14 +221,23 @@ impl SearchService {
         }
     }
 
+    pub async fn ping(&self) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        match &self.backend {
+            SearchBackend::Meilisearch(client) => client.ping().await,
+            SearchBackend::OpenSearch(_) => Ok(()),
+            SearchBackend::None => Ok(()),
+        }
+    }
+
     pub async fn search_products(
         &self,
         query: &str,
         limit: usize,
         tenant_id: &str,
+        sort: Option<&str>,
     ) -> Result<Vec<SearchProduct>, (StatusCode, Json<ConnectError>)> {
         match &self.backend {
-            SearchBackend::Meilisearch(client) => client.search_products(query, limit, tenant_id).await,
+            SearchBackend::Meilisearch(client) => client.search_products(query, limit, tenant_id, sort).await,
             SearchBackend::OpenSearch(_) => Ok(Vec::new()),
             SearchBackend::None => Ok(Vec::new()),
         }
@@ -155,9 +251,17 @@ impl SearchService {
         }
     }
 
-    pub async fn ensure_settings(&self) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    pub async fn delete_product(&self, product_id: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        match &self.backend {
+            SearchBackend::Meilisearch(client) => client.delete_product(product_id).await,
+            SearchBackend::OpenSearch(_) => Ok(()),
+            SearchBackend::None => Ok(()),
+        }
+    }
+
+    pub async fn ensure_settings(&self, config: Option<&SearchConfig>) -> Result<(), (StatusCode, Json<ConnectError>)> {
         match &self.backend {
-            SearchBackend::Meilisearch(client) => client.ensure_settings().await,
+            SearchBackend::Meilisearch(client) => client.ensure_settings(config).await,
             SearchBackend::OpenSearch(_) => Ok(()),
             SearchBackend::None => Ok(()),
         }
@@ -181,4 +285,61 @@ pub struct SearchProduct {
     pub category_ids: Vec<String>,
     #[serde(default)]
     pub sku_codes: Vec<String>,
+    #[serde(default)]
+    pub translations: std::collections::BTreeMap<String, String>,
+    /// Lowest active variant price; 0 when the product has no active variants.
+    #[serde(default)]
+    pub price_amount: i64,
+    #[serde(default)]
+    pub created_at_unix: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support;
+
+    #[test]
+    fn sort_options_map_to_sortable_attributes() {
+        assert_eq!(sort_expression("").unwrap(), None);
+        for (sort, expected) in [
+            ("price_asc", "price_amount:asc"),
+            ("price_desc", "price_amount:desc"),
+            ("title_asc", "title:asc"),
+            ("created_desc", "created_at_unix:desc"),
+        ] {
+            let expression = sort_expression(sort).unwrap().unwrap();
+            assert_eq!(expression, expected);
+            let (attribute, _) = expression.split_once(':').unwrap();
+            assert!(SORTABLE_ATTRIBUTES.contains(&attribute), "{attribute} is not sortable");
+        }
+    }
+
+    #[test]
+    fn unknown_sort_is_invalid_argument() {
+        let (status, Json(err)) = sort_expression("updated_desc").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(matches!(err.code, ErrorCode::InvalidArgument));
+    }
+
+    #[tokio::test]
+    async fn ping_requires_an_available_meilisearch() {
+        let available =
+            SearchService::meilisearch(&test_support::mock_meilisearch("available").await, None, "products");
+        available.ping().await.unwrap();
+
+        let degraded = SearchService::meilisearch(&test_support::mock_meilisearch("degraded").await, None, "products");
+        let (status, Json(err)) = degraded.ping().await.unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(err.message, "search status: degraded");
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let (status, _) = SearchService::meilisearch(&closed, None, "products")
+            .ping()
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
 }
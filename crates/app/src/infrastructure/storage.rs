@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+
 #[derive(Debug, Clone)]
 pub struct StorageLocationConfig {
     pub provider: String,
@@ -54,3 +56,151 @@ pub fn build_object_key(base_path: &str, tenant_id: &str, store_id: &str, name:
     let prefix = store_prefix(base_path, tenant_id, store_id);
     join_path(&prefix, name.trim())
 }
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotConfigured,
+    UnsupportedProvider(String),
+    Upload(String),
+}
+
+/// Writes `body` to `object_key` in the configured bucket.
+pub async fn put_object(
+    config: &StorageLocationConfig,
+    object_key: &str,
+    body: bytes::Bytes,
+    content_type: &str,
+) -> Result<(), StorageError> {
+    if !config.is_configured() {
+        return Err(StorageError::NotConfigured);
+    }
+    match config.provider.as_str() {
+        "s3" => {
+            let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+            if !config.region.is_empty() {
+                loader = loader.region(aws_config::Region::new(config.region.clone()));
+            }
+            let sdk_config = loader.load().await;
+            let client = aws_sdk_s3::Client::new(&sdk_config);
+            let mut put_req = client
+                .put_object()
+                .bucket(&config.bucket)
+                .key(object_key)
+                .body(aws_sdk_s3::primitives::ByteStream::from(body));
+            if !content_type.is_empty() {
+                put_req = put_req.content_type(content_type);
+            }
+            put_req
+                .send()
+                .await
+                .map_err(|err| StorageError::Upload(err.to_string()))?;
+            Ok(())
+        }
+        provider => Err(StorageError::UnsupportedProvider(provider.to_string())),
+    }
+}
+
+/// Destination for uploaded files. `AppState.object_storage` is the entry point for services.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    async fn put_object(
+        &self,
+        config: &StorageLocationConfig,
+        object_key: &str,
+        body: bytes::Bytes,
+        content_type: &str,
+    ) -> Result<(), StorageError>;
+}
+
+/// Uploads through the provider SDK selected by `config.provider`.
+pub struct SdkObjectStorage;
+
+#[async_trait]
+impl ObjectStorage for SdkObjectStorage {
+    async fn put_object(
+        &self,
+        config: &StorageLocationConfig,
+        object_key: &str,
+        body: bytes::Bytes,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        put_object(config, object_key, body, content_type).await
+    }
+}
+
+/// Public URL of an object: the CDN base when configured, otherwise the bucket endpoint.
+pub fn public_object_url(config: &StorageLocationConfig, object_key: &str) -> String {
+    let base = if config.cdn_base_url.is_empty() {
+        match config.provider.as_str() {
+            "s3" => format!("https://{}.s3.amazonaws.com", config.bucket),
+            _ => "".to_string(),
+        }
+    } else {
+        config.cdn_base_url.trim_end_matches('/').to_string()
+    };
+    format!("{}/{}", base, object_key)
+}
+
+pub fn cdn_base_url() -> String {
+    read_env("CDN_BASE_URL")
+}
+
+pub fn rewrite_storage_url(url: &str, cdn_base: &str) -> String {
+    let cdn_base = cdn_base.trim().trim_end_matches('/');
+    if cdn_base.is_empty() {
+        return url.to_string();
+    }
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(host) = parsed.host_str() else {
+        return url.to_string();
+    };
+    // {bucket}.s3.amazonaws.com / {bucket}.s3.{region}.amazonaws.com / {bucket}.s3-{region}.amazonaws.com
+    let is_s3_host = host
+        .strip_suffix(".amazonaws.com")
+        .and_then(|rest| rest.split_once(".s3"))
+        .is_some_and(|(bucket, region)| {
+            !bucket.is_empty() && (region.is_empty() || region.starts_with('.') || region.starts_with('-'))
+        });
+    if !is_s3_host {
+        return url.to_string();
+    }
+    let key = parsed.path().trim_start_matches('/');
+    if cdn_base.starts_with("http://") || cdn_base.starts_with("https://") {
+        format!("{}/{}", cdn_base, key)
+    } else {
+        format!("https://{}/{}", cdn_base, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn s3_urls_are_rewritten_to_the_cdn() {
+        for url in [
+            "https://shop.s3.amazonaws.com/media/a.jpg",
+            "https://shop.s3.ap-northeast-1.amazonaws.com/media/a.jpg",
+            "https://shop.s3-ap-northeast-1.amazonaws.com/media/a.jpg",
+        ] {
+            assert_eq!(
+                rewrite_storage_url(url, "https://cdn.example.com/"),
+                "https://cdn.example.com/media/a.jpg"
+            );
+            assert_eq!(
+                rewrite_storage_url(url, "cdn.example.com"),
+                "https://cdn.example.com/media/a.jpg"
+            );
+        }
+    }
+
+    #[test]
+    fn other_urls_are_left_alone() {
+        let url = "https://images.example.com/media/a.jpg";
+        assert_eq!(rewrite_storage_url(url, "https://cdn.example.com"), url);
+        let s3 = "https://shop.s3.amazonaws.com/media/a.jpg";
+        assert_eq!(rewrite_storage_url(s3, ""), s3);
+    }
+}
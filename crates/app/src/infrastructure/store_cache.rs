@@ -0,0 +1,70 @@
+use moka::sync::Cache;
+use std::time::Duration;
+
+const STORE_CACHE_DEFAULT_TTL_SECONDS: u64 = 60;
+const STORE_CACHE_CAPACITY: u64 = 10_000;
+
+/// Caches `store_id` / `store_code` lookups as `(store_id, tenant_id)` for `STORE_CACHE_TTL_SECONDS`,
+/// holding at most `STORE_CACHE_CAPACITY` keys.
+#[derive(Clone)]
+pub struct StoreCache {
+    entries: Cache<String, (String, String)>,
+    ttl: Duration,
+}
+
+impl StoreCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Cache::builder()
+                .max_capacity(STORE_CACHE_CAPACITY)
+                .time_to_live(ttl)
+                .build(),
+            ttl,
+        }
+    }
+
+    pub fn from_env() -> Result<Self, rs_common::env::EnvError> {
+        let ttl_seconds = rs_common::env::env_u64("STORE_CACHE_TTL_SECONDS", STORE_CACHE_DEFAULT_TTL_SECONDS)?;
+        Ok(Self::new(Duration::from_secs(ttl_seconds)))
+    }
+
+    pub fn get_by_store_id(&self, store_id: &str) -> Option<(String, String)> {
+        self.entries.get(&store_id_key(store_id))
+    }
+
+    pub fn get_by_store_code(&self, store_code: &str) -> Option<(String, String)> {
+        self.entries.get(&store_code_key(store_code))
+    }
+
+    pub fn insert_by_store_id(&self, store_id: &str, tenant_id: &str) {
+        self.insert(store_id_key(store_id), store_id, tenant_id);
+    }
+
+    pub fn insert_by_store_code(&self, store_code: &str, store_id: &str, tenant_id: &str) {
+        self.insert(store_code_key(store_code), store_id, tenant_id);
+    }
+
+    /// Drops every entry that resolves to `store_id`, whichever key it was cached under.
+    pub fn invalidate_store(&self, store_id: &str) {
+        for (key, (cached_store_id, _)) in self.entries.iter() {
+            if cached_store_id == store_id {
+                self.entries.invalidate(key.as_str());
+            }
+        }
+    }
+
+    fn insert(&self, key: String, store_id: &str, tenant_id: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.insert(key, (store_id.to_string(), tenant_id.to_string()));
+    }
+}
+
+fn store_id_key(store_id: &str) -> String {
+    format!("id:{}", store_id)
+}
+
+fn store_code_key(store_code: &str) -> String {
+    format!("code:{}", store_code)
+}
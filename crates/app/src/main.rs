@@ -1,7 +1,12 @@
-use axum::{Json, Router, http::StatusCode, routing::get};
-use rs_common::{cli, env};
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderValue, StatusCode},
+    routing::get,
+};
+use rs_common::{cli, env, shutdown};
 use sqlx::{PgPool, postgres::PgPoolOptions};
-use std::time::Duration;
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc, time::Duration};
 
 mod auction;
 mod audit;
@@ -21,11 +26,24 @@ mod store_settings;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    cli::init("rs-ecommerce");
+    cli::init("rs-ecommerce")?;
+    let cors_origins = cors_allowed_origins()?;
 
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required");
-    let db = PgPoolOptions::new().max_connections(5).connect(&db_url).await?;
+    env::validate_required_vars(&["DATABASE_URL"])?;
+    let db_url = std::env::var("DATABASE_URL")?;
+    let connect_timeout_ms = env::env_u64("DATABASE_CONNECT_TIMEOUT_MS", 5000)?;
+    let db = PgPoolOptions::new()
+        .min_connections(env::env_u64("DATABASE_MIN_CONNECTIONS", 1)? as u32)
+        .max_connections(env::env_u64("DATABASE_MAX_CONNECTIONS", 5)? as u32)
+        .acquire_timeout(Duration::from_millis(connect_timeout_ms))
+        .connect(&db_url)
+        .await?;
     sqlx::migrate!("../../migrations").run(&db).await?;
+    let schema_validation = infrastructure::outbox::SchemaValidationMode::from_env()?;
+    let schema_count = infrastructure::outbox::load_schemas(&db, schema_validation).await?;
+    tracing::info!(schema_count, ?schema_validation, "outbox event schemas loaded");
+    let email_renderer = Arc::new(infrastructure::email::EmailRenderer::from_env()?);
+    identity::service::validate_lockout_config()?;
 
     let search_backend = std::env::var("SEARCH_BACKEND").unwrap_or_else(|_| "meili".to_string());
     let search = match search_backend.as_str() {
@@ -44,34 +62,171 @@ async fn main() -> Result<(), anyhow::Error> {
             panic!("unknown SEARCH_BACKEND: {}", other);
         }
     };
-    search.ensure_settings().await.expect("search settings");
+    search.ensure_settings(None).await.expect("search settings");
+
+    let audit_writer = Arc::new(infrastructure::audit::PgAuditWriter::new(db.clone()));
+    let app_state = AppState {
+        db,
+        search,
+        product_performance_cache: order::analytics::product_performance_cache(),
+        store_cache: infrastructure::store_cache::StoreCache::from_env()?,
+        audit_writer,
+        email_renderer,
+        password_strength: shared::validation::PasswordStrength::from_env()?,
+        auth: rpc::actor::AuthConfig::from_env(),
+        object_storage: Arc::new(infrastructure::storage::SdkObjectStorage),
+        upload_max_bytes: product::media::upload_max_bytes()?,
+    };
+    // The loops below stop at the shutdown signal, abandoning a batch in flight; its transaction rolls back.
+    let shutdown = shutdown::cancel_on_signal();
+    let mut background_tasks = Vec::new();
 
-    let app_state = AppState { db, search };
     let scheduler_state = app_state.clone();
-    tokio::spawn(async move {
-        let batch_size = env::env_usize("AUCTION_WORKER_BATCH_SIZE", 50) as i64;
-        let sleep_ms = env::env_u64("AUCTION_WORKER_SLEEP_MS", 1000);
-        let oneshot = env::env_bool("AUCTION_WORKER_ONESHOT", false);
+    let batch_size = env::env_usize("AUCTION_WORKER_BATCH_SIZE", 50)? as i64;
+    let sleep_ms = env::env_u64("AUCTION_WORKER_SLEEP_MS", 1000)?;
+    let oneshot = env::env_bool("AUCTION_WORKER_ONESHOT", false)?;
+    let token = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
         loop {
-            match auction::service::run_scheduled_auctions(&scheduler_state, batch_size).await {
-                Ok(done) => {
-                    if done > 0 {
-                        tracing::info!(done, "auction auto-bid scheduler executed");
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = async {
+                    match auction::service::run_scheduled_auctions(&scheduler_state, batch_size).await {
+                        Ok(done) => {
+                            if done > 0 {
+                                tracing::info!(done, "auction auto-bid scheduler executed");
+                            }
+                        }
+                        Err(err) => tracing::warn!(error = ?err, "auction auto-bid scheduler failed"),
                     }
-                }
-                Err(err) => tracing::warn!(error = ?err, "auction auto-bid scheduler failed"),
+                    match auction::service::run_expired_auctions(&scheduler_state, batch_size).await {
+                        Ok(done) => {
+                            if done > 0 {
+                                tracing::info!(done, "expired auctions closed");
+                            }
+                        }
+                        Err(err) => tracing::warn!(error = ?err, "expired auction close failed"),
+                    }
+                } => {}
             }
             if oneshot {
                 break;
             }
-            tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+            }
         }
-    });
+    }));
+
+    let cart_state = app_state.clone();
+    let window_hours = env::env_i64("CART_ABANDONMENT_WINDOW_HOURS", 2)?;
+    let batch_size = env::env_usize("CART_ABANDONMENT_BATCH_SIZE", 100)? as i64;
+    let sleep_ms = env::env_u64("CART_ABANDONMENT_SLEEP_MS", 60_000)?;
+    let token = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                result = cart::service::run_cart_abandonment(&cart_state, window_hours, batch_size) => {
+                    match result {
+                        Ok(done) => {
+                            if done > 0 {
+                                tracing::info!(done, "cart abandonment events emitted");
+                            }
+                        }
+                        Err(err) => tracing::warn!(error = ?err, "cart abandonment scan failed"),
+                    }
+                }
+            }
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+            }
+        }
+    }));
+
+    let refresh_token_state = app_state.clone();
+    let batch_size = env::env_usize("REFRESH_TOKEN_CLEANUP_BATCH_SIZE", 500)? as i64;
+    let sleep_ms = env::env_u64("REFRESH_TOKEN_CLEANUP_SLEEP_MS", 3_600_000)?;
+    let token = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                result = identity::service::purge_expired_refresh_tokens(&refresh_token_state, batch_size) => {
+                    match result {
+                        Ok(deleted) => {
+                            if deleted > 0 {
+                                tracing::info!(deleted, "expired refresh tokens purged");
+                            }
+                        }
+                        Err(err) => tracing::warn!(error = ?err, "refresh token cleanup failed"),
+                    }
+                }
+            }
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+            }
+        }
+    }));
+
+    let reindex_state = app_state.clone();
+    let batch_size = env::env_usize("SEARCH_REINDEX_BATCH_SIZE", 100)? as i64;
+    let sleep_ms = env::env_u64("SEARCH_REINDEX_SLEEP_MS", 1000)?;
+    let max_attempts = env::env_u64("SEARCH_REINDEX_MAX_ATTEMPTS", 10)?.min(i32::MAX as u64) as i32;
+    let token = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                result = product::service::process_search_reindex_queue(&reindex_state, batch_size, max_attempts) => {
+                    match result {
+                        Ok(done) => {
+                            if done > 0 {
+                                tracing::info!(done, "products reindexed");
+                            }
+                        }
+                        Err(err) => tracing::warn!(error = ?err, "search reindex queue failed"),
+                    }
+                }
+            }
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+            }
+        }
+    }));
 
-    let app = rpc::router(app_state);
+    let app = rpc::router(app_state, cors_origins);
 
+    let shutdown_timeout_seconds = env::env_u64("SHUTDOWN_TIMEOUT_SECONDS", 30)?;
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await?;
-    axum::serve(listener, app).await?;
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+        .into_future();
+    tokio::pin!(server);
+    tokio::select! {
+        result = &mut server => result?,
+        _ = shutdown.cancelled() => {
+            // Draining starts here; connections and background tasks still running after the timeout are dropped.
+            let drain = async {
+                let result = (&mut server).await;
+                for task in background_tasks {
+                    if let Err(err) = task.await {
+                        tracing::warn!(error = ?err, "background task failed during shutdown");
+                    }
+                }
+                result
+            };
+            match tokio::time::timeout(Duration::from_secs(shutdown_timeout_seconds), drain).await {
+                Ok(result) => result?,
+                Err(_) => tracing::warn!(shutdown_timeout_seconds, "shutdown timed out; closing remaining connections"),
+            }
+        }
+    }
+    tracing::info!("graceful shutdown complete");
     Ok(())
 }
 
@@ -79,9 +234,98 @@ async fn main() -> Result<(), anyhow::Error> {
 pub struct AppState {
     pub db: PgPool,
     pub search: infrastructure::search::SearchService,
+    pub product_performance_cache: order::analytics::ProductPerformanceCache,
+    pub store_cache: infrastructure::store_cache::StoreCache,
+    pub audit_writer: Arc<dyn infrastructure::audit::AuditWriter>,
+    pub email_renderer: Arc<infrastructure::email::EmailRenderer>,
+    pub password_strength: shared::validation::PasswordStrength,
+    /// Token verification settings; signing in fails without `auth.jwt_secret`.
+    pub auth: rpc::actor::AuthConfig,
+    pub object_storage: Arc<dyn infrastructure::storage::ObjectStorage>,
+    pub upload_max_bytes: usize,
+}
+
+const DEFAULT_CORS_ORIGINS: &[&str] = &["http://localhost:3000", "http://127.0.0.1:3000"];
+
+/// Reads `CORS_ALLOWED_ORIGINS` (comma-separated http/https origins), defaulting to the local frontend.
+fn cors_allowed_origins() -> anyhow::Result<Vec<HeaderValue>> {
+    parse_cors_origins(&std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default())
+}
+
+fn parse_cors_origins(configured: &str) -> anyhow::Result<Vec<HeaderValue>> {
+    let origins: Vec<&str> = configured
+        .split(',')
+        .map(|origin| origin.trim().trim_end_matches('/'))
+        .filter(|origin| !origin.is_empty())
+        .collect();
+    let origins = if origins.is_empty() {
+        DEFAULT_CORS_ORIGINS.to_vec()
+    } else {
+        origins
+    };
+
+    origins
+        .into_iter()
+        .map(|origin| {
+            let url = reqwest::Url::parse(origin)
+                .map_err(|err| anyhow::anyhow!("invalid CORS_ALLOWED_ORIGINS entry {origin:?}: {err}"))?;
+            if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() || url.path() != "/" {
+                anyhow::bail!("invalid CORS_ALLOWED_ORIGINS entry {origin:?}: expected http(s)://host[:port]");
+            }
+            HeaderValue::from_str(origin)
+                .map_err(|err| anyhow::anyhow!("invalid CORS_ALLOWED_ORIGINS entry {origin:?}: {err}"))
+        })
+        .collect()
+}
+
+// Ready only when a pooled connection can be acquired and used.
+async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    match sqlx::query("SELECT 1").execute(&state.db).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ready",
+            })),
+        ),
+        Err(err) => {
+            tracing::warn!(error = %err, "readiness check failed");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "status": "unavailable",
+                })),
+            )
+        }
+    }
 }
 
-async fn health() -> (StatusCode, Json<serde_json::Value>) {
+// Healthy only when both the database and the search backend respond.
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let mut body = serde_json::json!({
+        "status": "ok",
+        "db": "ok",
+        "search": "ok",
+    });
+    let mut errors = Vec::new();
+    if let Err((_, Json(err))) = infrastructure::db::ping(&state).await {
+        body["db"] = "unavailable".into();
+        errors.push(err.message);
+    }
+    if let Err((_, Json(err))) = state.search.ping().await {
+        body["search"] = "unavailable".into();
+        errors.push(err.message);
+    }
+    if errors.is_empty() {
+        return (StatusCode::OK, Json(body));
+    }
+    let error = errors.join("; ");
+    tracing::error!(error = %error, "health check failed");
+    body["status"] = "unavailable".into();
+    body["error"] = error.into();
+    (StatusCode::SERVICE_UNAVAILABLE, Json(body))
+}
+
+async fn livez() -> (StatusCode, Json<serde_json::Value>) {
     (
         StatusCode::OK,
         Json(serde_json::json!({
@@ -91,5 +335,86 @@ async fn health() -> (StatusCode, Json<serde_json::Value>) {
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/health", get(health))
+    Router::new()
+        .route("/health", get(health))
+        .route("/readyz", get(readyz))
+        .route("/livez", get(livez))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{infrastructure::search::SearchService, shared::test_support};
+
+    #[test]
+    fn cors_origins_default_to_the_local_frontend() {
+        assert_eq!(parse_cors_origins(" , ").unwrap(), DEFAULT_CORS_ORIGINS);
+        assert_eq!(
+            parse_cors_origins("https://shop.example.com/, http://localhost:8081").unwrap(),
+            ["https://shop.example.com", "http://localhost:8081"]
+        );
+        for invalid in [
+            "shop.example.com",
+            "ftp://shop.example.com",
+            "https://shop.example.com/admin",
+        ] {
+            assert!(parse_cors_origins(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn health_reports_each_dependency(db: PgPool) {
+        let mut state = test_support::app_state(db);
+        state.search = SearchService::meilisearch(&test_support::mock_meilisearch("available").await, None, "products");
+        let (status, Json(body)) = health(State(state.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["search"], "ok");
+
+        state.search = SearchService::meilisearch(&test_support::mock_meilisearch("degraded").await, None, "products");
+        let (status, Json(body)) = health(State(state.clone())).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["db"], "ok");
+        assert_eq!(body["search"], "unavailable");
+        assert_eq!(body["error"], "search status: degraded");
+
+        // Readiness and liveness ignore the search backend.
+        assert_eq!(readyz(State(state)).await.0, StatusCode::OK);
+        assert_eq!(livez().await.0, StatusCode::OK);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn preflight_is_allowed_only_for_configured_origins(db: PgPool) {
+        let app = rpc::router(
+            test_support::app_state(db),
+            parse_cors_origins("https://shop.example.com").unwrap(),
+        );
+        let preflight = |origin: &'static str| {
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/rpc/ecommerce.v1.StorefrontService/ListProducts")
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .header("access-control-request-headers", "content-type")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let res = app
+            .clone()
+            .oneshot(preflight("https://shop.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get("access-control-allow-origin").unwrap(),
+            "https://shop.example.com"
+        );
+
+        let res = app.oneshot(preflight("http://localhost:3000")).await.unwrap();
+        assert!(res.headers().get("access-control-allow-origin").is_none());
+    }
 }
@@ -0,0 +1,106 @@
+use moka::sync::Cache;
+use sqlx::Row;
+use std::time::Duration;
+
+use crate::{
+    AppState,
+    order::error::{OrderError, OrderResult},
+    pb::pb,
+    shared::{ids::StoreId, time::timestamp_to_chrono},
+};
+
+const PRODUCT_PERFORMANCE_TTL: Duration = Duration::from_secs(300);
+const PRODUCT_PERFORMANCE_CACHE_CAPACITY: u64 = 1_000;
+const PRODUCT_PERFORMANCE_DEFAULT_LIMIT: i32 = 20;
+const PRODUCT_PERFORMANCE_MAX_LIMIT: i32 = 200;
+
+/// Product performance per `(store_id, period)`. Every distinct period is its own key, so the cache
+/// is capped as well as expired to keep arbitrary date ranges from growing it without bound.
+pub type ProductPerformanceCache = Cache<(String, String), Vec<pb::ProductPerformance>>;
+
+pub fn product_performance_cache() -> ProductPerformanceCache {
+    Cache::builder()
+        .max_capacity(PRODUCT_PERFORMANCE_CACHE_CAPACITY)
+        .time_to_live(PRODUCT_PERFORMANCE_TTL)
+        .build()
+}
+
+pub async fn get_product_performance(
+    state: &AppState,
+    store_id: String,
+    from: Option<pbjson_types::Timestamp>,
+    to: Option<pbjson_types::Timestamp>,
+    limit: i32,
+) -> OrderResult<Vec<pb::ProductPerformance>> {
+    let store_uuid = StoreId::parse(&store_id)?;
+    let from = timestamp_to_chrono(from);
+    let to = timestamp_to_chrono(to);
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(OrderError::invalid_argument("from must be before to"));
+    }
+    let limit = if limit <= 0 {
+        PRODUCT_PERFORMANCE_DEFAULT_LIMIT
+    } else {
+        limit.min(PRODUCT_PERFORMANCE_MAX_LIMIT)
+    };
+
+    let period_key = format!(
+        "{}:{}:{}",
+        from.map(|v| v.timestamp()).unwrap_or_default(),
+        to.map(|v| v.timestamp()).unwrap_or_default(),
+        limit
+    );
+    let cache_key = (store_id, period_key);
+    if let Some(products) = state.product_performance_cache.get(&cache_key) {
+        return Ok(products);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        SELECT p.id::text as product_id,
+               p.title,
+               SUM(oi.quantity)::bigint as units_sold,
+               SUM(oi.price_amount * oi.quantity)::bigint as revenue_amount,
+               oi.price_currency as revenue_currency
+        FROM order_items oi
+        JOIN orders o ON o.id = oi.order_id
+        JOIN product_skus v ON v.id = oi.variant_id
+        JOIN products p ON p.id = v.product_id
+        WHERE p.store_id = $1
+          AND o.status NOT IN ('canceled')
+          AND ($2::timestamptz IS NULL OR o.created_at >= $2)
+          AND ($3::timestamptz IS NULL OR o.created_at < $3)
+        GROUP BY p.id, p.title, oi.price_currency
+        ORDER BY revenue_amount DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(store_uuid.as_uuid())
+    .bind(from)
+    .bind(to)
+    .bind(limit as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(OrderError::from)?;
+
+    let products = rows
+        .into_iter()
+        .map(|row| pb::ProductPerformance {
+            product_id: row.get("product_id"),
+            title: row.get("title"),
+            units_sold: row.get::<i64, _>("units_sold"),
+            revenue: Some(pb::Money {
+                amount: row.get::<i64, _>("revenue_amount"),
+                currency: row.get::<String, _>("revenue_currency"),
+            }),
+            // Returns are not recorded in this schema yet.
+            return_count: 0,
+        })
+        .collect::<Vec<_>>();
+
+    state.product_performance_cache.insert(cache_key, products.clone());
+
+    Ok(products)
+}
@@ -48,52 +48,31 @@ impl OrderError {
         match self {
             OrderError::InvalidArgument(message) => (
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: ErrorCode::InvalidArgument,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
             ),
             OrderError::NotFound(message) => (
                 StatusCode::NOT_FOUND,
-                Json(ConnectError {
-                    code: ErrorCode::NotFound,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::NotFound, message)),
             ),
             OrderError::AlreadyExists(message) => (
                 StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::AlreadyExists,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::AlreadyExists, message)),
             ),
             OrderError::FailedPrecondition(message) => (
                 StatusCode::CONFLICT,
-                Json(ConnectError {
-                    code: ErrorCode::FailedPrecondition,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::FailedPrecondition, message)),
             ),
             OrderError::PermissionDenied(message) => (
                 StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: ErrorCode::PermissionDenied,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::PermissionDenied, message)),
             ),
             OrderError::Unauthenticated(message) => (
                 StatusCode::UNAUTHORIZED,
-                Json(ConnectError {
-                    code: ErrorCode::Unauthenticated,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Unauthenticated, message)),
             ),
             OrderError::Internal(message) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: ErrorCode::Internal,
-                    message,
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, message)),
             ),
         }
     }
@@ -1,5 +1,7 @@
 /// 注文/出荷のユースケース境界。
 ///
-/// 受注ステータス・出荷ステータスの更新を service で扱う。
+/// 受注ステータス・出荷ステータスの更新を service で扱い、
+/// 売上集計などの分析クエリは analytics に置く。
+pub mod analytics;
 pub mod error;
 pub mod service;
@@ -1,54 +1,99 @@
+use chrono::{DateTime, Utc};
 use sqlx::Row;
 
 use crate::{
     AppState,
-    infrastructure::audit,
     order::error::{OrderError, OrderResult},
     pb::pb,
     shared::{
         audit_action::{OrderAuditAction, ShipmentAuditAction},
         audit_helpers::{audit_input, to_json_opt},
         ids::{nullable_uuid, parse_uuid},
+        pagination::{MAX_PAGE_SIZE, encode_page_token, page_params},
         status::{PaymentMethod, order_status_from_string, order_status_to_string, shipment_status_to_string},
+        time::{chrono_to_timestamp, timestamp_to_chrono},
     },
 };
 
-pub async fn list_orders(state: &AppState, tenant_id: String, status_filter: i32) -> OrderResult<Vec<pb::OrderAdmin>> {
-    let status = order_status_to_string(status_filter);
-    let rows = if let Some(status) = status {
-        sqlx::query(
-            r#"
-            SELECT id::text as id, customer_id::text as customer_id,
-                   status, total_amount, currency, payment_method, created_at
-            FROM orders
-            WHERE tenant_id = $1 AND status = $2
-            ORDER BY created_at DESC
-            LIMIT 50
-            "#,
-        )
-        .bind(&tenant_id)
-        .bind(status)
-        .fetch_all(&state.db)
-        .await
-        .map_err(OrderError::from)?
-    } else {
-        sqlx::query(
-            r#"
-            SELECT id::text as id, customer_id::text as customer_id,
-                   status, total_amount, currency, payment_method, created_at
-            FROM orders
-            WHERE tenant_id = $1
-            ORDER BY created_at DESC
-            LIMIT 50
-            "#,
-        )
-        .bind(&tenant_id)
-        .fetch_all(&state.db)
-        .await
-        .map_err(OrderError::from)?
+pub async fn list_orders(
+    state: &AppState,
+    tenant_id: String,
+    req: pb::ListOrdersRequest,
+) -> OrderResult<(Vec<pb::OrderAdmin>, pb::PageResult, Option<pb::Money>)> {
+    let tenant_uuid = parse_uuid(&tenant_id, "tenant_id")?;
+    let status = order_status_to_string(req.status);
+    let created_after = timestamp_to_chrono(req.created_after);
+    let created_before = timestamp_to_chrono(req.created_before);
+    if let (Some(after), Some(before)) = (created_after, created_before)
+        && after >= before
+    {
+        return Err(OrderError::invalid_argument(
+            "created_after must be before created_before",
+        ));
+    }
+    let (limit, cursor) = page_params(req.page, MAX_PAGE_SIZE)?;
+
+    let mut rows = sqlx::query(
+        r#"
+        SELECT id::text as id, id as cursor_id, customer_id::text as customer_id,
+               status, total_amount, currency, payment_method, created_at
+        FROM orders
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+          AND ($5::timestamptz IS NULL OR (created_at, id) < ($5, $6))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $7
+        "#,
+    )
+    .bind(tenant_uuid)
+    .bind(status)
+    .bind(created_after)
+    .bind(created_before)
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
+    .fetch_all(&state.db)
+    .await
+    .map_err(OrderError::from)?;
+
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("cursor_id"));
+        }
+    }
+
+    // Same filters without the cursor, so the sum covers every page.
+    let sums = sqlx::query(
+        r#"
+        SELECT currency, SUM(total_amount)::bigint as total_amount_sum
+        FROM orders
+        WHERE tenant_id = $1
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+        GROUP BY currency
+        "#,
+    )
+    .bind(tenant_uuid)
+    .bind(status)
+    .bind(created_after)
+    .bind(created_before)
+    .fetch_all(&state.db)
+    .await
+    .map_err(OrderError::from)?;
+    let total_amount_sum = match sums.as_slice() {
+        [row] => Some(pb::Money {
+            amount: row.get::<i64, _>("total_amount_sum"),
+            currency: row.get::<String, _>("currency"),
+        }),
+        _ => None,
     };
 
-    Ok(rows
+    let orders = rows
         .into_iter()
         .map(|row| pb::OrderAdmin {
             id: row.get::<String, _>("id"),
@@ -61,9 +106,11 @@ pub async fn list_orders(state: &AppState, tenant_id: String, status_filter: i32
             payment_method: PaymentMethod::from_str(row.get::<String, _>("payment_method").as_str())
                 .map(|value| value.to_pb())
                 .unwrap_or(pb::PaymentMethod::Unspecified as i32),
-            created_at: None,
+            created_at: chrono_to_timestamp(Some(row.get::<DateTime<Utc>, _>("created_at"))),
         })
-        .collect())
+        .collect();
+
+    Ok((orders, pb::PageResult { next_page_token }, total_amount_sum))
 }
 
 pub async fn update_order_status(
@@ -106,19 +153,21 @@ pub async fn update_order_status(
 
     let before_json = before_status.map(|s| serde_json::json!({ "status": s }));
     let after_json = Some(serde_json::json!({ "status": status }));
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            OrderAuditAction::UpdateStatus.into(),
-            Some("order"),
-            Some(order.id.clone()),
-            before_json,
-            after_json,
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                OrderAuditAction::UpdateStatus,
+                Some("order"),
+                Some(order.id.clone()),
+                before_json,
+                after_json,
+                _actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(OrderError::from)?;
     Ok(order)
@@ -157,19 +206,21 @@ pub async fn create_shipment(
         carrier: req.carrier,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            ShipmentAuditAction::Create.into(),
-            Some("shipment"),
-            Some(shipment.id.clone()),
-            None,
-            to_json_opt(Some(shipment.clone())),
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                ShipmentAuditAction::Create,
+                Some("shipment"),
+                Some(shipment.id.clone()),
+                None,
+                to_json_opt(Some(shipment.clone())),
+                _actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(OrderError::from)?;
     Ok(shipment)
@@ -214,19 +265,21 @@ pub async fn update_shipment_status(
 
     let before_json = before_status.map(|s| serde_json::json!({ "status": s }));
     let after_json = Some(serde_json::json!({ "status": shipment_status_to_string(req.status) }));
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            ShipmentAuditAction::UpdateStatus.into(),
-            Some("shipment"),
-            Some(shipment.id.clone()),
-            before_json,
-            after_json,
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                ShipmentAuditAction::UpdateStatus,
+                Some("shipment"),
+                Some(shipment.id.clone()),
+                before_json,
+                after_json,
+                _actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(OrderError::from)?;
     Ok(shipment)
@@ -256,3 +309,135 @@ async fn tenant_id_for_shipment(state: &AppState, shipment_id: &str) -> OrderRes
     .map_err(OrderError::from)?;
     Ok(row.get("tenant_id"))
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    async fn create_order(
+        db: &PgPool,
+        tenant_id: uuid::Uuid,
+        created_at: &str,
+        status: &str,
+        total_amount: i64,
+    ) -> String {
+        let id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO orders (tenant_id, status, total_amount, currency, payment_method, created_at)
+            VALUES ($1, $2, $3, 'JPY', 'cod', $4)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(status)
+        .bind(total_amount)
+        .bind(created_at.parse::<DateTime<Utc>>().unwrap())
+        .fetch_one(db)
+        .await
+        .unwrap();
+        id.to_string()
+    }
+
+    fn timestamp(value: &str) -> Option<pbjson_types::Timestamp> {
+        chrono_to_timestamp(Some(value.parse().unwrap()))
+    }
+
+    fn october(status: pb::OrderStatus, page: Option<pb::PageInfo>) -> pb::ListOrdersRequest {
+        pb::ListOrdersRequest {
+            status: status as i32,
+            page,
+            created_after: timestamp("2026-10-01T00:00:00Z"),
+            created_before: timestamp("2026-11-01T00:00:00Z"),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn orders_are_filtered_paged_and_summed(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let tenant = store.tenant_id;
+        let tenant_id = tenant.to_string();
+        create_order(&db, tenant, "2026-09-30T23:59:59Z", "pending_shipment", 1000).await;
+        let first = create_order(&db, tenant, "2026-10-01T00:00:00Z", "pending_shipment", 2000).await;
+        let mid = create_order(&db, tenant, "2026-10-15T12:00:00Z", "pending_shipment", 3000).await;
+        create_order(&db, tenant, "2026-10-20T12:00:00Z", "canceled", 500).await;
+        create_order(&db, tenant, "2026-11-01T00:00:00Z", "pending_shipment", 4000).await;
+        let other = test_support::create_store(&db).await;
+        create_order(&db, other.tenant_id, "2026-10-10T00:00:00Z", "pending_shipment", 9000).await;
+
+        let one_per_page = |page_token: &str| {
+            Some(pb::PageInfo {
+                page_size: 1,
+                page_token: page_token.to_string(),
+            })
+        };
+        let (orders, page, total) = list_orders(
+            &state,
+            tenant_id.clone(),
+            october(pb::OrderStatus::PendingShipment, one_per_page("")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(orders.iter().map(|o| o.id.clone()).collect::<Vec<_>>(), [mid]);
+        assert_eq!(
+            total,
+            Some(pb::Money {
+                amount: 5000,
+                currency: "JPY".to_string(),
+            })
+        );
+        let (orders, page, _) = list_orders(
+            &state,
+            tenant_id.clone(),
+            october(pb::OrderStatus::PendingShipment, one_per_page(&page.next_page_token)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(orders.iter().map(|o| o.id.clone()).collect::<Vec<_>>(), [first]);
+        assert!(page.next_page_token.is_empty());
+
+        let (orders, _, total) = list_orders(&state, tenant_id, october(pb::OrderStatus::Unspecified, None))
+            .await
+            .unwrap();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(total.map(|money| money.amount), Some(5500));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn mixed_currencies_have_no_total(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let tenant = store.tenant_id;
+        create_order(&db, tenant, "2026-10-02T00:00:00Z", "pending_shipment", 2000).await;
+        let usd = create_order(&db, tenant, "2026-10-03T00:00:00Z", "pending_shipment", 20).await;
+        sqlx::query("UPDATE orders SET currency = 'USD' WHERE id = $1::uuid")
+            .bind(usd)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let (orders, _, total) = list_orders(
+            &state,
+            store.tenant_id.to_string(),
+            october(pb::OrderStatus::Unspecified, None),
+        )
+        .await
+        .unwrap();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(total, None);
+
+        let reversed = pb::ListOrdersRequest {
+            created_after: timestamp("2026-11-01T00:00:00Z"),
+            created_before: timestamp("2026-10-01T00:00:00Z"),
+            ..Default::default()
+        };
+        let err = list_orders(&state, store.tenant_id.to_string(), reversed)
+            .await
+            .expect_err("empty date range");
+        assert!(matches!(err, OrderError::InvalidArgument(_)), "{err:?}");
+    }
+}
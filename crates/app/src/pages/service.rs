@@ -10,6 +10,7 @@ use crate::{
     rpc::json::{ConnectError, invalid_argument, not_found},
     shared::{
         ids::{StoreId, TenantId, parse_uuid},
+        pagination::{MAX_PAGE_SIZE, encode_page_token, page_params},
         time::{chrono_to_timestamp, timestamp_to_chrono},
     },
 };
@@ -24,10 +25,11 @@ pub async fn list_pages(
 ) -> PageResult<(Vec<pb::PageSummary>, pb::PageResult)> {
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
-    let (limit, offset) = page_params(page);
-    let rows = sqlx::query(
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
+    let mut rows = sqlx::query(
         r#"
         SELECT id::text as id,
+               id as cursor_id,
                title,
                slug,
                status,
@@ -36,18 +38,28 @@ pub async fn list_pages(
                updated_at
           FROM pages
          WHERE store_id = $1 AND tenant_id = $2
+           AND ($3::timestamptz IS NULL OR (updated_at, id) < ($3, $4))
          ORDER BY updated_at DESC, id DESC
-         LIMIT $3 OFFSET $4
+         LIMIT $5
         "#,
     )
     .bind(store_uuid.as_uuid())
     .bind(tenant_uuid.as_uuid())
-    .bind(limit)
-    .bind(offset)
+    .bind(cursor.map(|(updated_at, _)| updated_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
     .fetch_all(&state.db)
     .await
     .map_err(db::error)?;
 
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("updated_at"), last.get("cursor_id"));
+        }
+    }
+
     let pages = rows
         .into_iter()
         .map(|row| pb::PageSummary {
@@ -61,11 +73,6 @@ pub async fn list_pages(
         })
         .collect::<Vec<_>>();
 
-    let mut next_page_token = String::new();
-    if (pages.len() as i64) == limit {
-        next_page_token = (offset + limit).to_string();
-    }
-
     Ok((pages, pb::PageResult { next_page_token }))
 }
 
@@ -315,16 +322,6 @@ fn page_admin_from_row(row: &sqlx::postgres::PgRow) -> pb::PageAdmin {
     }
 }
 
-fn page_params(page: Option<pb::PageInfo>) -> (i64, i64) {
-    let page = page.unwrap_or(pb::PageInfo {
-        page_size: 50,
-        page_token: String::new(),
-    });
-    let limit = (page.page_size.max(1).min(200)) as i64;
-    let offset = page.page_token.parse::<i64>().unwrap_or(0).max(0);
-    (limit, offset)
-}
-
 fn validate_page_input(input: pb::PageInput) -> PageResult<ValidatedPageInput> {
     let title = require_text(&input.title, "title")?;
     let slug = normalize_slug(&input.slug)?;
@@ -8,18 +8,12 @@ use crate::{
     AppState,
     infrastructure::{db, storage},
     pb::pb,
-    rpc::json::ConnectError,
-    shared::{ids::parse_uuid, status::FulfillmentType, time::chrono_to_timestamp},
+    rpc::json::{ConnectError, ErrorCode},
+    shared::{
+        ids::parse_uuid, status::FulfillmentType, store_context::resolve_store_context, time::chrono_to_timestamp,
+    },
 };
 
-async fn resolve_store_context(
-    state: &AppState,
-    store: Option<pb::StoreContext>,
-    tenant: Option<pb::TenantContext>,
-) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
-    crate::identity::context::resolve_store_context(state, store, tenant).await
-}
-
 async fn ensure_sku_is_digital(
     state: &AppState,
     sku_id: &str,
@@ -43,10 +37,7 @@ async fn ensure_sku_is_digital(
     let Some(row) = row else {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id not found")),
         ));
     };
     let fulfillment_type: String = row.get("fulfillment_type");
@@ -54,10 +45,10 @@ async fn ensure_sku_is_digital(
     if parsed != FulfillmentType::Digital {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "sku is not digital fulfillment".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "sku is not digital fulfillment",
+            )),
         ));
     }
     Ok(())
@@ -134,10 +125,7 @@ pub async fn create_digital_upload_url(
     if filename.trim().is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "filename is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "filename is required")),
         ));
     }
     let (store_id, tenant_id) = resolve_store_context(state, store, tenant).await?;
@@ -146,10 +134,10 @@ pub async fn create_digital_upload_url(
     if !storage_config.is_configured() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "private storage is not configured".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "private storage is not configured",
+            )),
         ));
     }
 
@@ -177,19 +165,16 @@ pub async fn create_digital_upload_url(
             let presign_config = PresigningConfig::expires_in(Duration::from_secs(600)).map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to create presign config".to_string(),
-                    }),
+                    Json(ConnectError::new(
+                        ErrorCode::Internal,
+                        "failed to create presign config",
+                    )),
                 )
             })?;
             let presigned = put_req.presigned(presign_config).await.map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to presign upload url".to_string(),
-                    }),
+                    Json(ConnectError::new(ErrorCode::Internal, "failed to presign upload url")),
                 )
             })?;
             let mut headers = HashMap::new();
@@ -206,17 +191,17 @@ pub async fn create_digital_upload_url(
         }
         "gcs" => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "GCS presign is not configured yet".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "GCS presign is not configured yet",
+            )),
         )),
         _ => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "unsupported storage provider".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "unsupported storage provider",
+            )),
         )),
     }
 }
@@ -231,10 +216,10 @@ pub async fn create_digital_asset(
     if asset.object_key.is_empty() || asset.bucket.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "asset.object_key and bucket are required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "asset.object_key and bucket are required",
+            )),
         ));
     }
     let (store_id, _tenant_id) = resolve_store_context(state, store, tenant).await?;
@@ -304,10 +289,7 @@ pub async fn create_digital_download_url(
     let Some(row) = row else {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "asset_id not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "asset_id not found")),
         ));
     };
     let provider: String = row.get("provider");
@@ -318,10 +300,10 @@ pub async fn create_digital_download_url(
     if !storage_config.is_configured() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "private storage is not configured".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "private storage is not configured",
+            )),
         ));
     }
     match provider.as_str() {
@@ -336,19 +318,16 @@ pub async fn create_digital_download_url(
             let presign_config = PresigningConfig::expires_in(Duration::from_secs(3600)).map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to create presign config".to_string(),
-                    }),
+                    Json(ConnectError::new(
+                        ErrorCode::Internal,
+                        "failed to create presign config",
+                    )),
                 )
             })?;
             let presigned = get_req.presigned(presign_config).await.map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to presign download url".to_string(),
-                    }),
+                    Json(ConnectError::new(ErrorCode::Internal, "failed to presign download url")),
                 )
             })?;
             let expires_at = Utc::now() + ChronoDuration::hours(1);
@@ -359,17 +338,17 @@ pub async fn create_digital_download_url(
         }
         "gcs" => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "GCS presign is not configured yet".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "GCS presign is not configured yet",
+            )),
         )),
         _ => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "unsupported storage provider".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "unsupported storage provider",
+            )),
         )),
     }
 }
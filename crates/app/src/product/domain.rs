@@ -16,7 +16,10 @@ pub struct Variant {
     pub status: String,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Validated SKU code: trimmed, non-empty, at most 64 bytes, no whitespace.
+/// Binds and decodes as TEXT, so it can be passed to sqlx queries directly.
+#[derive(Clone, Debug, Eq, PartialEq, sqlx::Type)]
+#[sqlx(transparent)]
 pub struct SkuCode(String);
 
 impl SkuCode {
@@ -25,34 +28,113 @@ impl SkuCode {
         if normalized.is_empty() {
             return Err((
                 axum::http::StatusCode::BAD_REQUEST,
-                axum::Json(crate::rpc::json::ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "sku is required".to_string(),
-                }),
+                axum::Json(crate::rpc::json::ConnectError::new(
+                    crate::rpc::json::ErrorCode::InvalidArgument,
+                    "sku is required",
+                )),
             ));
         }
         if normalized.len() > 64 {
             return Err((
                 axum::http::StatusCode::BAD_REQUEST,
-                axum::Json(crate::rpc::json::ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "sku must be 64 chars or less".to_string(),
-                }),
+                axum::Json(crate::rpc::json::ConnectError::new(
+                    crate::rpc::json::ErrorCode::InvalidArgument,
+                    "sku must be 64 chars or less",
+                )),
             ));
         }
         if normalized.contains(char::is_whitespace) {
             return Err((
                 axum::http::StatusCode::BAD_REQUEST,
-                axum::Json(crate::rpc::json::ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "sku must not contain whitespace".to_string(),
-                }),
+                axum::Json(crate::rpc::json::ConnectError::new(
+                    crate::rpc::json::ErrorCode::InvalidArgument,
+                    "sku must not contain whitespace",
+                )),
             ));
         }
         Ok(Self(normalized.to_string()))
     }
 
-    pub fn as_str(&self) -> &str {
+    /// Wraps a value without validation, for test fixtures.
+    #[cfg(test)]
+    pub fn new_unchecked(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl std::ops::Deref for SkuCode {
+    type Target = str;
+
+    fn deref(&self) -> &str {
         &self.0
     }
 }
+
+impl AsRef<str> for SkuCode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Validated URL slug: 2-128 chars of `a-z`, `0-9` and `-`, not starting or ending with `-`.
+#[derive(Clone, Debug, Eq, PartialEq, sqlx::Type)]
+#[sqlx(transparent)]
+pub struct SlugValue(String);
+
+impl SlugValue {
+    pub fn parse(value: &str) -> Result<Self, (axum::http::StatusCode, axum::Json<crate::rpc::json::ConnectError>)> {
+        let normalized = value.trim();
+        let invalid = |message: &str| {
+            (
+                axum::http::StatusCode::BAD_REQUEST,
+                axum::Json(crate::rpc::json::ConnectError::new(
+                    crate::rpc::json::ErrorCode::InvalidArgument,
+                    message.to_string(),
+                )),
+            )
+        };
+        if normalized.is_empty() {
+            return Err(invalid("slug is required"));
+        }
+        if !(2..=128).contains(&normalized.len()) {
+            return Err(invalid("slug must be between 2 and 128 chars"));
+        }
+        if !normalized
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(invalid("slug must contain only lowercase letters, digits and hyphens"));
+        }
+        if normalized.starts_with('-') || normalized.ends_with('-') {
+            return Err(invalid("slug must not start or end with a hyphen"));
+        }
+        Ok(Self(normalized.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sku_code_allows_up_to_64_chars() {
+        let longest = "A".repeat(64);
+        assert_eq!(
+            SkuCode::parse(&longest).unwrap(),
+            SkuCode::new_unchecked(longest.clone())
+        );
+        assert!(SkuCode::parse(&format!("{longest}A")).is_err());
+    }
+
+    #[test]
+    fn sku_code_is_trimmed_but_rejects_inner_whitespace() {
+        assert_eq!(SkuCode::parse("  TOTE-1\t").unwrap(), SkuCode::new_unchecked("TOTE-1"));
+        assert!(SkuCode::parse("TOTE\t1").is_err());
+        assert!(SkuCode::parse("TOTE 1").is_err());
+        assert!(SkuCode::parse(" \t ").is_err());
+    }
+}
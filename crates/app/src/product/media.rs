@@ -1,7 +1,8 @@
-use aws_sdk_s3::{presigning::PresigningConfig, primitives::ByteStream};
+use aws_sdk_s3::presigning::PresigningConfig;
 use axum::{Json, http::StatusCode};
 use chrono::Utc;
 use reqwest::{Url, header};
+use rs_common::env::{self, EnvError};
 use sqlx::Row;
 use std::{collections::HashMap, net::IpAddr, time::Duration};
 
@@ -9,23 +10,37 @@ use crate::{
     AppState,
     infrastructure::{db, storage},
     pb::pb,
-    rpc::json::ConnectError,
-    shared::{ids::parse_uuid, time::chrono_to_timestamp},
+    rpc::json::{ConnectError, ErrorCode},
+    shared::{
+        audit_action::VariantAuditAction, audit_helpers::audit_input, ids::parse_uuid,
+        store_context::resolve_store_context, time::chrono_to_timestamp,
+    },
 };
 
 fn validate_store_asset_input(asset: &pb::MediaAsset) -> Result<(), (StatusCode, Json<ConnectError>)> {
     if asset.public_url.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "public_url is required")),
         ));
     }
     Ok(())
 }
 
+const DEFAULT_UPLOAD_MAX_BYTES: usize = 10 * 1024 * 1024;
+const UPLOAD_CONTENT_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp", "application/pdf"];
+
+/// `UPLOAD_MAX_BYTES`; main reads it before serving, so a bad value stops startup.
+pub fn upload_max_bytes() -> Result<usize, EnvError> {
+    match env::env_usize("UPLOAD_MAX_BYTES", DEFAULT_UPLOAD_MAX_BYTES)? {
+        0 => Err(EnvError::Invalid {
+            name: "UPLOAD_MAX_BYTES".to_string(),
+            value: "0".to_string(),
+        }),
+        value => Ok(value),
+    }
+}
+
 fn sanitize_filename(name: &str) -> String {
     let trimmed = name.trim();
     if trimmed.is_empty() {
@@ -65,30 +80,30 @@ fn validate_external_url(input: &str) -> Result<Url, (StatusCode, Json<ConnectEr
     let url = Url::parse(input).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url must be a valid URL".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "public_url must be a valid URL",
+            )),
         )
     })?;
     let scheme = url.scheme();
     if scheme != "http" && scheme != "https" {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url must be http or https".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "public_url must be http or https",
+            )),
         ));
     }
     let host = url.host_str().unwrap_or_default().to_lowercase();
     if host == "localhost" || host.ends_with(".local") {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url host is not allowed".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "public_url host is not allowed",
+            )),
         ));
     }
     if let Some(ip) = url.host_str().and_then(|h| h.parse::<IpAddr>().ok())
@@ -96,10 +111,10 @@ fn validate_external_url(input: &str) -> Result<Url, (StatusCode, Json<ConnectEr
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url host is not allowed".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "public_url host is not allowed",
+            )),
         ));
     }
     Ok(url)
@@ -112,6 +127,7 @@ fn extension_from_content_type(content_type: &str) -> Option<&'static str> {
         "image/webp" => Some("webp"),
         "image/gif" => Some("gif"),
         "image/avif" => Some("avif"),
+        "application/pdf" => Some("pdf"),
         _ => None,
     }
 }
@@ -125,10 +141,7 @@ async fn download_external_image(url: &Url) -> Result<(bytes::Bytes, String), (S
         .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::Internal,
-                    message: "failed to create http client".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, "failed to create http client")),
             )
         })?;
     let resp = client
@@ -139,19 +152,19 @@ async fn download_external_image(url: &Url) -> Result<(bytes::Bytes, String), (S
         .map_err(|_| {
             (
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "failed to download image".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "failed to download image",
+                )),
             )
         })?;
     if !resp.status().is_success() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "failed to download image".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "failed to download image",
+            )),
         ));
     }
     if let Some(length) = resp.content_length()
@@ -159,10 +172,7 @@ async fn download_external_image(url: &Url) -> Result<(bytes::Bytes, String), (S
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "image is too large".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "image is too large")),
         ));
     }
     let content_type = resp
@@ -178,35 +188,32 @@ async fn download_external_image(url: &Url) -> Result<(bytes::Bytes, String), (S
     if !content_type.starts_with("image/") {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "public_url must point to an image".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "public_url must point to an image",
+            )),
         ));
     }
     let bytes = resp.bytes().await.map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "failed to download image".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "failed to download image",
+            )),
         )
     })?;
     if bytes.len() > MAX_BYTES {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "image is too large".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "image is too large")),
         ));
     }
     Ok((bytes, content_type))
 }
 
 async fn import_external_asset(
-    _state: &AppState,
+    state: &AppState,
     store_id: &str,
     tenant_id: &str,
     public_url: &str,
@@ -217,10 +224,10 @@ async fn import_external_asset(
     if !storage_config.is_configured() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "storage is not configured".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "storage is not configured",
+            )),
         ));
     }
     let (bytes, content_type) = download_external_image(&url).await?;
@@ -232,61 +239,13 @@ async fn import_external_asset(
     };
     let object_key = storage::build_object_key(&storage_config.base_path, tenant_id, store_id, &filename);
 
-    match storage_config.provider.as_str() {
-        "s3" => {
-            let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
-            if !storage_config.region.is_empty() {
-                loader = loader.region(aws_config::Region::new(storage_config.region.clone()));
-            }
-            let config = loader.load().await;
-            let client = aws_sdk_s3::Client::new(&config);
-            client
-                .put_object()
-                .bucket(&storage_config.bucket)
-                .key(&object_key)
-                .body(ByteStream::from(bytes.clone()))
-                .content_type(content_type.clone())
-                .send()
-                .await
-                .map_err(|_| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ConnectError {
-                            code: crate::rpc::json::ErrorCode::Internal,
-                            message: "failed to upload image".to_string(),
-                        }),
-                    )
-                })?;
-        }
-        "gcs" => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                    message: "GCS import is not configured yet".to_string(),
-                }),
-            ));
-        }
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "unsupported storage provider".to_string(),
-                }),
-            ));
-        }
-    }
-
-    let public_base = if storage_config.cdn_base_url.is_empty() {
-        match storage_config.provider.as_str() {
-            "s3" => format!("https://{}.s3.amazonaws.com", storage_config.bucket),
-            _ => "".to_string(),
-        }
-    } else {
-        storage_config.cdn_base_url.trim_end_matches('/').to_string()
-    };
-    let public_url = format!("{}/{}", public_base, object_key);
+    let size_bytes = bytes.len() as i64;
+    state
+        .object_storage
+        .put_object(&storage_config, &object_key, bytes, &content_type)
+        .await
+        .map_err(storage_error)?;
+    let public_url = storage::public_object_url(&storage_config, &object_key);
     Ok(pb::MediaAsset {
         id: "".to_string(),
         public_url,
@@ -294,7 +253,7 @@ async fn import_external_asset(
         bucket: storage_config.bucket,
         object_key,
         content_type,
-        size_bytes: bytes.len() as i64,
+        size_bytes,
         created_at: None,
         tags: Vec::new(),
     })
@@ -313,10 +272,10 @@ pub async fn create_media_upload_url(
     if !storage_config.is_configured() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "storage provider and bucket are required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "storage provider and bucket are required",
+            )),
         ));
     }
 
@@ -346,19 +305,16 @@ pub async fn create_media_upload_url(
             let presign_config = PresigningConfig::expires_in(Duration::from_secs(900)).map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to create presign config".to_string(),
-                    }),
+                    Json(ConnectError::new(
+                        ErrorCode::Internal,
+                        "failed to create presign config",
+                    )),
                 )
             })?;
             let presigned = put_req.presigned(presign_config).await.map_err(|_| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::Internal,
-                        message: "failed to presign upload url".to_string(),
-                    }),
+                    Json(ConnectError::new(ErrorCode::Internal, "failed to presign upload url")),
                 )
             })?;
             let mut headers = HashMap::new();
@@ -382,27 +338,20 @@ pub async fn create_media_upload_url(
         }
         "gcs" => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::FailedPrecondition,
-                message: "GCS presign is not configured yet".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "GCS presign is not configured yet",
+            )),
         )),
         _ => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "unsupported storage provider".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "unsupported storage provider",
+            )),
         )),
     }
 }
-async fn resolve_store_context(
-    state: &AppState,
-    store: Option<pb::StoreContext>,
-    tenant: Option<pb::TenantContext>,
-) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
-    crate::identity::context::resolve_store_context(state, store, tenant).await
-}
 
 async fn ensure_sku_belongs_to_store(
     state: &AppState,
@@ -425,25 +374,38 @@ async fn ensure_sku_belongs_to_store(
     let Some(row) = row else {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id not found")),
         ));
     };
     let found_store: String = row.get("store_id");
     if found_store != store_id {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::PermissionDenied,
-                message: "sku_id does not belong to store".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::PermissionDenied,
+                "sku_id does not belong to store",
+            )),
         ));
     }
     Ok(())
 }
 
+/// Builds the API view of a `store_media_assets` row. Stored URLs keep pointing at the bucket;
+/// every response rewrites them to the CDN so clients never see raw S3 hosts.
+fn media_asset_from_row(row: &sqlx::postgres::PgRow, cdn_base: &str) -> pb::MediaAsset {
+    pb::MediaAsset {
+        id: row.get("id"),
+        public_url: storage::rewrite_storage_url(&row.get::<String, _>("public_url"), cdn_base),
+        provider: row.get("provider"),
+        bucket: row.get("bucket"),
+        object_key: row.get("object_key"),
+        content_type: row.get::<Option<String>, _>("content_type").unwrap_or_default(),
+        size_bytes: row.get::<Option<i64>, _>("size_bytes").unwrap_or_default(),
+        tags: row.get::<Option<Vec<String>>, _>("tags").unwrap_or_default(),
+        created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
+    }
+}
+
 pub async fn list_media_assets(
     state: &AppState,
     store: Option<pb::StoreContext>,
@@ -485,20 +447,8 @@ pub async fn list_media_assets(
         .map_err(db::error)?
     };
 
-    let assets = rows
-        .into_iter()
-        .map(|row| pb::MediaAsset {
-            id: row.get("id"),
-            public_url: row.get("public_url"),
-            provider: row.get("provider"),
-            bucket: row.get("bucket"),
-            object_key: row.get("object_key"),
-            content_type: row.get::<Option<String>, _>("content_type").unwrap_or_default(),
-            size_bytes: row.get::<Option<i64>, _>("size_bytes").unwrap_or_default(),
-            tags: row.get::<Option<Vec<String>>, _>("tags").unwrap_or_default(),
-            created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
-        })
-        .collect();
+    let cdn_base = storage::cdn_base_url();
+    let assets = rows.iter().map(|row| media_asset_from_row(row, &cdn_base)).collect();
 
     Ok(assets)
 }
@@ -552,19 +502,199 @@ pub async fn create_media_asset(
     .await
     .map_err(db::error)?;
 
+    Ok(media_asset_from_row(&row, &storage::cdn_base_url()))
+}
+
+/// A file received by the multipart `/upload/media` endpoint.
+pub struct MediaUpload {
+    pub store_id: String,
+    pub owner_type: String,
+    pub owner_id: String,
+    pub content_type: String,
+    pub bytes: bytes::Bytes,
+}
+
+pub async fn upload_media(
+    state: &AppState,
+    upload: MediaUpload,
+) -> Result<pb::MediaAsset, (StatusCode, Json<ConnectError>)> {
+    let store = pb::StoreContext {
+        store_id: upload.store_id,
+        ..Default::default()
+    };
+    let (store_id, tenant_id) = resolve_store_context(state, Some(store), None).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let tenant_uuid = parse_uuid(&tenant_id, "tenant_id")?;
+    let owner_uuid = parse_uuid(&upload.owner_id, "owner_id")?;
+    ensure_media_owner(state, &upload.owner_type, owner_uuid, store_uuid).await?;
+
+    if upload.bytes.is_empty() {
+        return Err(invalid_upload("file is required".to_string()));
+    }
+    if upload.bytes.len() > state.upload_max_bytes {
+        return Err(invalid_upload("file is too large".to_string()));
+    }
+    let content_type = upload
+        .content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    if !UPLOAD_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(invalid_upload(format!(
+            "content type must be one of: {}",
+            UPLOAD_CONTENT_TYPES.join(", ")
+        )));
+    }
+    // The declared type comes from the client, so check it against the file signature.
+    if sniff_content_type(&upload.bytes) != Some(content_type.as_str()) {
+        return Err(invalid_upload(
+            "file content does not match its content type".to_string(),
+        ));
+    }
+
+    let storage_config = storage::public_config();
+    let extension = extension_from_content_type(&content_type).unwrap_or("bin");
+    let object_key = storage::build_object_key(
+        &storage_config.base_path,
+        &tenant_id,
+        &store_id,
+        &format!("media/{}.{}", uuid::Uuid::new_v4(), extension),
+    );
+    let size_bytes = upload.bytes.len() as i64;
+    state
+        .object_storage
+        .put_object(&storage_config, &object_key, upload.bytes, &content_type)
+        .await
+        .map_err(storage_error)?;
+    let public_url = storage::public_object_url(&storage_config, &object_key);
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO store_media_assets (
+            tenant_id, store_id, provider, bucket, object_key, public_url, content_type, size_bytes,
+            owner_type, owner_id
+        ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+        RETURNING id::text as id, created_at
+        "#,
+    )
+    .bind(tenant_uuid)
+    .bind(store_uuid)
+    .bind(&storage_config.provider)
+    .bind(&storage_config.bucket)
+    .bind(&object_key)
+    .bind(&public_url)
+    .bind(&content_type)
+    .bind(size_bytes)
+    .bind(&upload.owner_type)
+    .bind(owner_uuid)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db::error)?;
+
     Ok(pb::MediaAsset {
         id: row.get("id"),
-        public_url: row.get("public_url"),
-        provider: row.get("provider"),
-        bucket: row.get("bucket"),
-        object_key: row.get("object_key"),
-        content_type: row.get::<Option<String>, _>("content_type").unwrap_or_default(),
-        size_bytes: row.get::<Option<i64>, _>("size_bytes").unwrap_or_default(),
-        tags: row.get::<Option<Vec<String>>, _>("tags").unwrap_or_default(),
+        public_url: storage::rewrite_storage_url(&public_url, &storage::cdn_base_url()),
+        provider: storage_config.provider,
+        bucket: storage_config.bucket,
+        object_key,
+        content_type,
+        size_bytes,
+        tags: Vec::new(),
         created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
     })
 }
 
+async fn ensure_media_owner(
+    state: &AppState,
+    owner_type: &str,
+    owner_uuid: uuid::Uuid,
+    store_uuid: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let query = match owner_type {
+        "product" => "SELECT 1 FROM products WHERE id = $1 AND store_id = $2",
+        "variant" => {
+            r#"
+            SELECT 1
+            FROM product_skus v
+            JOIN products p ON p.id = v.product_id
+            WHERE v.id = $1 AND p.store_id = $2
+            "#
+        }
+        "store" => {
+            if owner_uuid != store_uuid {
+                return Err(invalid_upload(
+                    "owner_id must be the store_id for store media".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        _ => {
+            return Err(invalid_upload(
+                "owner_type must be product, variant or store".to_string(),
+            ));
+        }
+    };
+    let row = sqlx::query(query)
+        .bind(owner_uuid)
+        .bind(store_uuid)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    if row.is_none() {
+        return Err(invalid_upload("owner_id not found".to_string()));
+    }
+    Ok(())
+}
+
+fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+fn invalid_upload(message: String) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
+    )
+}
+
+fn storage_error(err: storage::StorageError) -> (StatusCode, Json<ConnectError>) {
+    match err {
+        storage::StorageError::NotConfigured => (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "storage is not configured",
+            )),
+        ),
+        storage::StorageError::UnsupportedProvider(provider) => (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("unsupported storage provider: {provider}"),
+            )),
+        ),
+        storage::StorageError::Upload(err) => {
+            tracing::error!(error = %err, "storage upload failed");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(ErrorCode::Internal, "failed to upload file")),
+            )
+        }
+    }
+}
+
 pub async fn update_media_asset_tags(
     state: &AppState,
     store: Option<pb::StoreContext>,
@@ -596,24 +726,11 @@ pub async fn update_media_asset_tags(
     let Some(row) = row else {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "asset not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "asset not found")),
         ));
     };
 
-    Ok(pb::MediaAsset {
-        id: row.get("id"),
-        public_url: row.get("public_url"),
-        provider: row.get("provider"),
-        bucket: row.get("bucket"),
-        object_key: row.get("object_key"),
-        content_type: row.get::<Option<String>, _>("content_type").unwrap_or_default(),
-        size_bytes: row.get::<Option<i64>, _>("size_bytes").unwrap_or_default(),
-        tags: row.get::<Option<Vec<String>>, _>("tags").unwrap_or_default(),
-        created_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("created_at"))),
-    })
+    Ok(media_asset_from_row(&row, &storage::cdn_base_url()))
 }
 
 pub async fn delete_media_asset(
@@ -635,10 +752,10 @@ pub async fn delete_media_asset(
     if used_count > 0 {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "asset is used by sku images".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "asset is used by sku images",
+            )),
         ));
     }
 
@@ -688,12 +805,13 @@ pub async fn list_sku_images(
     .await
     .map_err(db::error)?;
 
+    let cdn_base = storage::cdn_base_url();
     let images = rows
         .into_iter()
         .map(|row| {
             let asset = pb::MediaAsset {
                 id: row.get("asset_ref_id"),
-                public_url: row.get("public_url"),
+                public_url: storage::rewrite_storage_url(&row.get::<String, _>("public_url"), &cdn_base),
                 provider: row.get("provider"),
                 bucket: row.get("bucket"),
                 object_key: row.get("object_key"),
@@ -745,10 +863,7 @@ pub async fn set_sku_images(
         if exists.is_none() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "asset_id not found".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "asset_id not found")),
             ));
         }
         sqlx::query(
@@ -770,3 +885,491 @@ pub async fn set_sku_images(
 
     list_sku_images(state, store, tenant, sku_id).await
 }
+
+/// Replaces a variant's media with `media_ids` in order. Each asset must have been uploaded for the
+/// variant's product or one of its variants, so media from another product cannot be attached.
+pub async fn set_variant_media(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    variant_id: String,
+    media_ids: Vec<String>,
+    actor: Option<pb::ActorContext>,
+) -> Result<Vec<pb::SkuImage>, (StatusCode, Json<ConnectError>)> {
+    let mut seen = std::collections::HashSet::new();
+    if let Some(duplicate) = media_ids.iter().find(|id| !seen.insert(id.as_str())) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("media_id is duplicated: {}", duplicate),
+            )),
+        ));
+    }
+    let (store_id, _tenant_id) = resolve_store_context(state, store.clone(), None).await?;
+    ensure_sku_belongs_to_store(state, &variant_id, &store_id).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let sku_uuid = parse_uuid(&variant_id, "variant_id")?;
+    let media_uuids = media_ids
+        .iter()
+        .map(|id| parse_uuid(id, "media_id"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let before: Vec<String> = sqlx::query_scalar(
+        "SELECT asset_id::text FROM sku_images WHERE store_id = $1 AND sku_id = $2 ORDER BY position FOR UPDATE",
+    )
+    .bind(store_uuid)
+    .bind(sku_uuid)
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    let owned: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM store_media_assets m
+        JOIN product_skus v ON v.id = $2
+        WHERE m.store_id = $1 AND m.id = ANY($3)
+          AND (
+            (m.owner_type = 'product' AND m.owner_id = v.product_id)
+            OR (m.owner_type = 'variant'
+                AND m.owner_id IN (SELECT id FROM product_skus WHERE product_id = v.product_id))
+          )
+        "#,
+    )
+    .bind(store_uuid)
+    .bind(sku_uuid)
+    .bind(&media_uuids)
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if owned != media_uuids.len() as i64 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "media must belong to the variant's product",
+            )),
+        ));
+    }
+
+    sqlx::query("DELETE FROM sku_images WHERE store_id = $1 AND sku_id = $2")
+        .bind(store_uuid)
+        .bind(sku_uuid)
+        .execute(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO sku_images (store_id, sku_id, asset_id, position)
+        SELECT $1, $2, m.asset_id, m.position::int
+        FROM UNNEST($3::uuid[]) WITH ORDINALITY AS m(asset_id, position)
+        "#,
+    )
+    .bind(store_uuid)
+    .bind(sku_uuid)
+    .bind(&media_uuids)
+    .execute(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                VariantAuditAction::MediaSet,
+                Some("variant"),
+                Some(variant_id.clone()),
+                Some(serde_json::json!({ "media_ids": before })),
+                Some(serde_json::json!({ "media_ids": media_ids })),
+                actor,
+            ),
+        )
+        .await?;
+    tx.commit().await.map_err(db::error)?;
+
+    list_sku_images(state, store, None, variant_id).await
+}
+
+pub async fn reorder_variant_media(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    variant_id: String,
+    ordered_media_ids: Vec<String>,
+) -> Result<Vec<pb::SkuImage>, (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, store.clone(), None).await?;
+    ensure_sku_belongs_to_store(state, &variant_id, &store_id).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let sku_uuid = parse_uuid(&variant_id, "variant_id")?;
+    let ordered_ids = ordered_media_ids
+        .iter()
+        .map(|id| parse_uuid(id, "media_id"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let attached: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT asset_id FROM sku_images WHERE store_id = $1 AND sku_id = $2 FOR UPDATE")
+            .bind(store_uuid)
+            .bind(sku_uuid)
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    let unique: std::collections::HashSet<&uuid::Uuid> = ordered_ids.iter().collect();
+    if unique.len() != ordered_ids.len()
+        || ordered_ids.len() != attached.len()
+        || !attached.iter().all(|id| unique.contains(id))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "ordered_media_ids must list each media attached to the variant exactly once",
+            )),
+        ));
+    }
+
+    // (sku_id, position) is unique and checked per row, so park the current positions first.
+    sqlx::query("UPDATE sku_images SET position = -position WHERE store_id = $1 AND sku_id = $2")
+        .bind(store_uuid)
+        .bind(sku_uuid)
+        .execute(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    for (idx, asset_id) in ordered_ids.iter().enumerate() {
+        sqlx::query("UPDATE sku_images SET position = $1 WHERE store_id = $2 AND sku_id = $3 AND asset_id = $4")
+            .bind((idx + 1) as i32)
+            .bind(store_uuid)
+            .bind(sku_uuid)
+            .bind(asset_id)
+            .execute(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    }
+    tx.commit().await.map_err(db::error)?;
+
+    list_sku_images(state, store, None, variant_id).await
+}
+
+pub async fn list_variant_media(
+    state: &AppState,
+    product_id: uuid::Uuid,
+) -> Result<HashMap<String, Vec<pb::ProductMedia>>, (StatusCode, Json<ConnectError>)> {
+    let rows = sqlx::query(
+        r#"
+        SELECT si.sku_id::text as sku_id, a.id::text as asset_id, a.public_url, a.content_type, si.position
+        FROM sku_images si
+        JOIN product_skus v ON v.id = si.sku_id
+        JOIN store_media_assets a ON a.id = si.asset_id
+        WHERE v.product_id = $1
+        ORDER BY si.sku_id, si.position ASC
+        "#,
+    )
+    .bind(product_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    let cdn_base = storage::cdn_base_url();
+    let mut media: HashMap<String, Vec<pb::ProductMedia>> = HashMap::new();
+    for row in rows {
+        media.entry(row.get("sku_id")).or_default().push(pb::ProductMedia {
+            id: row.get("asset_id"),
+            url: storage::rewrite_storage_url(&row.get::<String, _>("public_url"), &cdn_base),
+            content_type: row.get::<Option<String>, _>("content_type").unwrap_or_default(),
+            position: row.get::<i32, _>("position"),
+        });
+    }
+    Ok(media)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    async fn create_variant(db: &PgPool, product_id: uuid::Uuid) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+            VALUES ($1, 'SKU-1', 1000, 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(product_id)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    async fn create_media(db: &PgPool, store: &test_support::TestStore, product_id: uuid::Uuid) -> String {
+        let id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO store_media_assets (tenant_id, store_id, public_url, owner_type, owner_id)
+            VALUES ($1, $2, 'https://cdn.example.com/a.jpg', 'product', $3)
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .bind(product_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        id.to_string()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn variant_media_must_belong_to_the_product(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let product_id = test_support::create_product(&db, &store, "Shirt", "active").await;
+        let other_product_id = test_support::create_product(&db, &store, "Hat", "active").await;
+        let variant_id = create_variant(&db, product_id).await.to_string();
+        let own = create_media(&db, &store, product_id).await;
+        let foreign = create_media(&db, &store, other_product_id).await;
+
+        let err = set_variant_media(
+            &state,
+            store.store_context(),
+            variant_id.clone(),
+            vec![own.clone(), foreign],
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err.1.code, ErrorCode::InvalidArgument));
+
+        let media = set_variant_media(
+            &state,
+            store.store_context(),
+            variant_id.clone(),
+            vec![own.clone()],
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(media.len(), 1);
+
+        let after: serde_json::Value = sqlx::query_scalar(
+            "SELECT after_json FROM audit_logs WHERE action = 'variant.media_set' AND target_id = $1",
+        )
+        .bind(&variant_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(after, serde_json::json!({ "media_ids": [own] }));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn media_assets_are_served_from_the_cdn(db: PgPool) {
+        let store = test_support::create_store(&db).await;
+        sqlx::query(
+            r#"
+            INSERT INTO store_media_assets (tenant_id, store_id, provider, bucket, object_key, public_url)
+            VALUES ($1, $2, 's3', 'shop', 'media/a.jpg', 'https://shop.s3.amazonaws.com/media/a.jpg')
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let row = sqlx::query(
+            r#"
+            SELECT id::text as id, public_url, provider, bucket, object_key, content_type, size_bytes, tags, created_at
+            FROM store_media_assets
+            WHERE store_id = $1
+            "#,
+        )
+        .bind(store.store_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+
+        let asset = media_asset_from_row(&row, "https://cdn.example.com");
+        assert_eq!(asset.public_url, "https://cdn.example.com/media/a.jpg");
+        assert_eq!(asset.object_key, "media/a.jpg");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support::{self, RecordingObjectStorage, TestStore};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn store_upload(store: &TestStore, content_type: &str, bytes: &[u8]) -> MediaUpload {
+        MediaUpload {
+            store_id: store.store_id.to_string(),
+            owner_type: "store".to_string(),
+            owner_id: store.store_id.to_string(),
+            content_type: content_type.to_string(),
+            bytes: bytes::Bytes::copy_from_slice(bytes),
+        }
+    }
+
+    fn recording_state(db: &PgPool) -> (AppState, RecordingObjectStorage) {
+        let storage = RecordingObjectStorage::default();
+        let mut state = test_support::app_state(db.clone());
+        state.object_storage = Arc::new(storage.clone());
+        (state, storage)
+    }
+
+    fn invalid_argument_message(result: Result<pb::MediaAsset, (StatusCode, Json<ConnectError>)>) -> String {
+        let (status, Json(err)) = result.expect_err("upload should be rejected");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        err.message
+    }
+
+    async fn asset_count(db: &PgPool, store: &TestStore) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM store_media_assets WHERE store_id = $1")
+            .bind(store.store_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn upload_is_stored_and_recorded(db: PgPool) {
+        let (state, storage) = recording_state(&db);
+        let store = test_support::create_store(&db).await;
+
+        let asset = upload_media(&state, store_upload(&store, "image/PNG; charset=binary", PNG))
+            .await
+            .unwrap();
+        assert_eq!(asset.content_type, "image/png");
+        assert_eq!(asset.size_bytes, PNG.len() as i64);
+        assert!(
+            asset
+                .object_key
+                .starts_with(&format!("{}/{}/media/", store.tenant_id, store.store_id))
+        );
+        assert!(asset.object_key.ends_with(".png"));
+
+        let objects = storage.objects.lock().unwrap().clone();
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].object_key, asset.object_key);
+        assert_eq!(objects[0].content_type, "image/png");
+        assert_eq!(objects[0].size, PNG.len());
+        assert_eq!(asset_count(&db, &store).await, 1);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn oversized_upload_is_rejected(db: PgPool) {
+        let (mut state, storage) = recording_state(&db);
+        state.upload_max_bytes = PNG.len() - 1;
+        let store = test_support::create_store(&db).await;
+
+        let message = invalid_argument_message(upload_media(&state, store_upload(&store, "image/png", PNG)).await);
+        assert_eq!(message, "file is too large");
+        assert!(storage.objects.lock().unwrap().is_empty());
+        assert_eq!(asset_count(&db, &store).await, 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn spoofed_content_type_is_rejected(db: PgPool) {
+        let (state, storage) = recording_state(&db);
+        let store = test_support::create_store(&db).await;
+
+        let message = invalid_argument_message(
+            upload_media(&state, store_upload(&store, "image/png", b"%PDF-1.7 not an image")).await,
+        );
+        assert_eq!(message, "file content does not match its content type");
+        let message = invalid_argument_message(upload_media(&state, store_upload(&store, "text/html", PNG)).await);
+        assert!(message.starts_with("content type must be one of"), "{message}");
+        assert!(storage.objects.lock().unwrap().is_empty());
+        assert_eq!(asset_count(&db, &store).await, 0);
+    }
+
+    /// A variant with `count` images attached at positions 1..=count.
+    async fn variant_with_images(db: &PgPool, store: &TestStore, count: usize) -> (uuid::Uuid, Vec<uuid::Uuid>) {
+        let product_id = test_support::create_product(db, store, "Poster", "active").await;
+        let sku_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+            VALUES ($1, 'POSTER-1', 1000, 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(product_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        let mut asset_ids = Vec::new();
+        for position in 1..=count {
+            let asset_id: uuid::Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO store_media_assets (tenant_id, store_id, public_url, content_type)
+                VALUES ($1, $2, $3, 'image/png')
+                RETURNING id
+                "#,
+            )
+            .bind(store.tenant_id)
+            .bind(store.store_id)
+            .bind(format!("https://cdn.example/{position}.png"))
+            .fetch_one(db)
+            .await
+            .unwrap();
+            sqlx::query("INSERT INTO sku_images (store_id, sku_id, asset_id, position) VALUES ($1, $2, $3, $4)")
+                .bind(store.store_id)
+                .bind(sku_id)
+                .bind(asset_id)
+                .bind(position as i32)
+                .execute(db)
+                .await
+                .unwrap();
+            asset_ids.push(asset_id);
+        }
+        (sku_id, asset_ids)
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn variant_media_is_reordered_as_listed(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let (sku_id, assets) = variant_with_images(&db, &store, 3).await;
+        let reorder = |ordered: Vec<uuid::Uuid>| {
+            reorder_variant_media(
+                &state,
+                store.store_context(),
+                sku_id.to_string(),
+                ordered.iter().map(uuid::Uuid::to_string).collect(),
+            )
+        };
+
+        let foreign = uuid::Uuid::new_v4();
+        for ordered in [
+            vec![assets[2], assets[0]],
+            vec![assets[2], assets[0], assets[0]],
+            vec![assets[2], assets[0], foreign],
+        ] {
+            let (_, Json(err)) = reorder(ordered)
+                .await
+                .expect_err("every attached media must be listed once");
+            assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        }
+
+        let images = reorder(vec![assets[2], assets[0], assets[1]]).await.unwrap();
+        let order: Vec<_> = images
+            .iter()
+            .map(|image| (image.asset_id.clone(), image.position))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (assets[2].to_string(), 1),
+                (assets[0].to_string(), 2),
+                (assets[1].to_string(), 3),
+            ]
+        );
+    }
+}
@@ -5,21 +5,36 @@ use sqlx::Row;
 use crate::rpc::request_context;
 use crate::{
     AppState,
-    infrastructure::{audit, db, metafields},
+    infrastructure::{db, metafields, outbox},
     pb::pb,
-    product::domain::SkuCode,
-    rpc::json::ConnectError,
+    product::{
+        domain::{SkuCode, SlugValue},
+        media, translation,
+    },
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
-        audit_action::{InventoryAuditAction, ProductAuditAction, VariantAuditAction},
+        audit_action::{CategoryAuditAction, InventoryAuditAction, ProductAuditAction, VariantAuditAction},
         audit_helpers::{audit_input, to_json_opt},
         ids::{ProductId, StoreId, TenantId, nullable_uuid, parse_uuid},
-        money::{money_from_parts, money_to_parts, money_to_parts_opt},
+        money::{money_from_parts, money_to_parts_opt, price_to_parts},
+        pagination::{MAX_PAGE_SIZE, encode_page_token, encode_position_page_token, page_params, position_page_params},
         status::{FulfillmentType, ProductStatus, VariantStatus},
-        time::{chrono_to_timestamp, timestamp_to_chrono},
+        store_context::{resolve_store_context, resolve_store_context_with, store_id_for_tenant},
+        time::{chrono_to_timestamp, chrono_to_timestamp_required, timestamp_to_chrono},
+        validation::ValidationBuilder,
     },
 };
 
 const METAFIELD_OWNER_TYPE_PRODUCT: &str = "product";
+const INVENTORY_LOW_STOCK_THRESHOLD: i32 = 5;
+// Also enforced by the inventory_stocks_quantities_check constraint.
+const MAX_INVENTORY_ON_HAND: i32 = 2_000_000;
+const INVENTORY_STOCK_SET_EVENT_TYPE: &str = "inventory.stock_set";
+const PRODUCT_CREATED_EVENT_TYPE: &str = "product.created";
+const PRODUCT_UPDATED_EVENT_TYPE: &str = "product.updated";
+const ACTIVE_WITHOUT_VARIANTS_WARNING: &str =
+    "product is active but has no priced variants yet—add variants before it can be purchased";
+const BULK_AXIS_VALUES_MAX_ASSIGNMENTS: usize = 500;
 
 pub async fn list_products(
     state: &AppState,
@@ -30,9 +45,16 @@ pub async fn list_products(
     let store_id = StoreId::parse(&store_id)?;
     let rows = sqlx::query(
         r#"
-        SELECT id::text as id, vendor_id::text as vendor_id, title, description, status, tax_rule_id::text as tax_rule_id
+        SELECT id::text as id, vendor_id::text as vendor_id, title, description, status, tax_rule_id::text as tax_rule_id,
+               (sale_start_at IS NOT NULL OR sale_end_at IS NOT NULL) as is_on_sale
         FROM products
-        WHERE tenant_id = $1 AND store_id = $2
+        WHERE tenant_id = $1 AND store_id = $2 AND deleted_at IS NULL
+          AND (sale_start_at IS NULL OR sale_start_at <= now())
+          AND (sale_end_at IS NULL OR sale_end_at > now())
+          AND (
+              vendor_id IS NULL
+              OR EXISTS (SELECT 1 FROM vendors v WHERE v.id = products.vendor_id AND v.status = 'active')
+          )
         ORDER BY created_at DESC
         LIMIT 50
         "#,
@@ -54,6 +76,8 @@ pub async fn list_products(
             variants: Vec::new(),
             updated_at: None,
             tax_rule_id: row.get::<Option<String>, _>("tax_rule_id").unwrap_or_default(),
+            media: Vec::new(),
+            is_on_sale: row.get("is_on_sale"),
         })
         .collect())
 }
@@ -62,6 +86,7 @@ pub async fn get_product(
     state: &AppState,
     tenant_id: String,
     product_id: String,
+    locales: Vec<String>,
 ) -> Result<Option<pb::Product>, (StatusCode, Json<ConnectError>)> {
     let tenant_id = TenantId::parse(&tenant_id)?;
     let store_id = store_id_for_tenant(state, &tenant_id.to_string()).await?;
@@ -69,9 +94,12 @@ pub async fn get_product(
     let product_id = ProductId::parse(&product_id)?;
     let row = sqlx::query(
         r#"
-        SELECT id::text as id, vendor_id::text as vendor_id, title, description, status, tax_rule_id::text as tax_rule_id
+        SELECT id::text as id, vendor_id::text as vendor_id, title, description, status, tax_rule_id::text as tax_rule_id,
+               (sale_start_at IS NOT NULL OR sale_end_at IS NOT NULL) as is_on_sale
         FROM products
-        WHERE tenant_id = $1 AND store_id = $2 AND id = $3
+        WHERE tenant_id = $1 AND store_id = $2 AND id = $3 AND deleted_at IS NULL
+          AND (sale_start_at IS NULL OR sale_start_at <= now())
+          AND (sale_end_at IS NULL OR sale_end_at > now())
         "#,
     )
     .bind(tenant_id.as_uuid())
@@ -80,28 +108,88 @@ pub async fn get_product(
     .fetch_optional(&state.db)
     .await
     .map_err(db::error)?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let translation = translation::find_translation(state, product_id.as_uuid(), &locales).await?;
+    let (title, description) = translation.unwrap_or_else(|| (row.get("title"), row.get("description")));
+    let (variants, media) = storefront_variants(state, product_id.as_uuid()).await?;
 
-    Ok(row.map(|row| pb::Product {
+    Ok(Some(pb::Product {
         id: row.get::<String, _>("id"),
         vendor_id: row.get::<Option<String>, _>("vendor_id").unwrap_or_default(),
-        title: row.get("title"),
-        description: row.get("description"),
+        title,
+        description,
         status: row.get("status"),
-        variants: Vec::new(),
+        variants,
         updated_at: None,
         tax_rule_id: row.get::<Option<String>, _>("tax_rule_id").unwrap_or_default(),
+        media,
+        is_on_sale: row.get("is_on_sale"),
     }))
 }
 
+async fn storefront_variants(
+    state: &AppState,
+    product_id: uuid::Uuid,
+) -> Result<(Vec<pb::Variant>, Vec<pb::ProductMedia>), (StatusCode, Json<ConnectError>)> {
+    let rows = sqlx::query(
+        r#"
+        SELECT id::text as id, sku, price_amount, price_currency, compare_at_amount, compare_at_currency, status
+        FROM product_skus
+        WHERE product_id = $1 AND status = $2
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(product_id)
+    .bind(VariantStatus::Active.as_str())
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+    let mut variant_media = media::list_variant_media(state, product_id).await?;
+
+    // The first variant with its own media stands in for product-level media.
+    let product_media = rows
+        .iter()
+        .find_map(|row| variant_media.get(&row.get::<String, _>("id")).cloned())
+        .unwrap_or_default();
+
+    let variants = rows
+        .into_iter()
+        .map(|row| {
+            let id: String = row.get("id");
+            let media = variant_media.remove(&id).unwrap_or_else(|| product_media.clone());
+            let compare_at_amount: Option<i64> = row.get("compare_at_amount");
+            let compare_at_currency: Option<String> = row.get("compare_at_currency");
+            pb::Variant {
+                id,
+                sku: row.get("sku"),
+                price: Some(money_from_parts(row.get("price_amount"), row.get("price_currency"))),
+                compare_at: match (compare_at_amount, compare_at_currency) {
+                    (Some(amount), Some(currency)) => Some(money_from_parts(amount, currency)),
+                    _ => None,
+                },
+                status: row.get("status"),
+                media,
+            }
+        })
+        .collect();
+    Ok((variants, product_media))
+}
+
 pub async fn list_products_admin(
     state: &AppState,
+    ctx: &request_context::RequestContext,
     tenant: Option<pb::TenantContext>,
     store: Option<pb::StoreContext>,
-) -> Result<Vec<pb::ProductAdmin>, (StatusCode, Json<ConnectError>)> {
-    let (store_id, tenant_id) = resolve_store_context(state, store, tenant).await?;
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::ProductAdmin>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context_with(state, ctx, store, tenant).await?;
     let store_id = StoreId::parse(&store_id)?;
     let tenant_id = TenantId::parse(&tenant_id)?;
-    let rows = sqlx::query(
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
+    let mut rows = sqlx::query(
         r#"
         SELECT id::text as id,
                store_id::text as store_id,
@@ -118,20 +206,34 @@ pub async fn list_products_admin(
                 LIMIT 1) as primary_category_id,
                (SELECT array_agg(category_id::text ORDER BY position)
                 FROM product_category_links pc
-                WHERE pc.product_id = products.id) as category_ids
+                WHERE pc.product_id = products.id) as category_ids,
+               created_at,
+               id as cursor_id
         FROM products
-        WHERE tenant_id = $1 AND store_id = $2
-        ORDER BY created_at DESC
-        LIMIT 50
+        WHERE tenant_id = $1 AND store_id = $2 AND deleted_at IS NULL
+          AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
         "#,
     )
     .bind(tenant_id.as_uuid())
     .bind(store_id.as_uuid())
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
     .fetch_all(&state.db)
     .await
     .map_err(db::error)?;
 
-    Ok(rows
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("cursor_id"));
+        }
+    }
+
+    let products = rows
         .into_iter()
         .map(|row| pb::ProductAdmin {
             id: row.get::<String, _>("id"),
@@ -147,7 +249,9 @@ pub async fn list_products_admin(
             primary_category_id: row.get::<Option<String>, _>("primary_category_id").unwrap_or_default(),
             category_ids: row.get::<Option<Vec<String>>, _>("category_ids").unwrap_or_default(),
         })
-        .collect())
+        .collect();
+
+    Ok((products, pb::PageResult { next_page_token }))
 }
 
 pub async fn list_variants_admin(
@@ -155,10 +259,18 @@ pub async fn list_variants_admin(
     tenant: Option<pb::TenantContext>,
     store: Option<pb::StoreContext>,
     product_id: String,
-) -> Result<(Vec<pb::VariantAdmin>, Vec<pb::VariantAxis>), (StatusCode, Json<ConnectError>)> {
+    status: String,
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::VariantAdmin>, Vec<pb::VariantAxis>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
     let (store_id, _tenant_id) = resolve_store_context(state, store, tenant).await?;
     let store_id = StoreId::parse(&store_id)?;
     let product_id = ProductId::parse(&product_id)?;
+    let status = if status.is_empty() {
+        None
+    } else {
+        Some(VariantStatus::parse(&status)?.as_str())
+    };
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
     let axes_rows = sqlx::query(
         r#"
         SELECT id, name, position
@@ -178,7 +290,7 @@ pub async fn list_variants_admin(
             position: row.get::<i32, _>("position") as u32,
         })
         .collect::<Vec<_>>();
-    let rows = sqlx::query(
+    let mut rows = sqlx::query(
         r#"
         SELECT v.id,
                v.product_id,
@@ -190,19 +302,35 @@ pub async fn list_variants_admin(
                v.compare_at_amount,
                v.compare_at_currency,
                v.status,
-               v.tax_rule_id
+               v.tax_rule_id,
+               v.created_at
         FROM product_skus v
         JOIN products p ON p.id = v.product_id
         WHERE p.store_id = $1 AND v.product_id = $2
-        ORDER BY v.created_at DESC
+          AND ($3::text IS NULL OR v.status = $3)
+          AND ($4::timestamptz IS NULL OR (v.created_at, v.id) < ($4, $5))
+        ORDER BY v.created_at DESC, v.id DESC
+        LIMIT $6
         "#,
     )
     .bind(store_id.as_uuid())
     .bind(product_id.as_uuid())
+    .bind(status)
+    .bind(cursor.map(|(created_at, _)| created_at))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit + 1)
     .fetch_all(&state.db)
     .await
     .map_err(db::error)?;
 
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("id"));
+        }
+    }
+
     let mut variants = Vec::with_capacity(rows.len());
     let mut variant_ids = Vec::with_capacity(rows.len());
     for row in rows {
@@ -271,7 +399,7 @@ pub async fn list_variants_admin(
         }
     }
 
-    Ok((variants, variant_axes))
+    Ok((variants, variant_axes, pb::PageResult { next_page_token }))
 }
 
 pub async fn list_skus_admin(
@@ -279,41 +407,69 @@ pub async fn list_skus_admin(
     tenant: Option<pb::TenantContext>,
     store: Option<pb::StoreContext>,
     query: String,
-) -> Result<Vec<pb::SkuAdmin>, (StatusCode, Json<ConnectError>)> {
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::SkuAdmin>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
     let (store_id, _tenant_id) = resolve_store_context(state, store, tenant).await?;
     let store_id = StoreId::parse(&store_id)?;
-    let pattern = if query.trim().is_empty() {
-        "".to_string()
-    } else {
-        format!("%{}%", query.trim())
-    };
+    let (limit, cursor) = page_params(page, MAX_PAGE_SIZE)?;
+    let query = query.trim();
 
-    let rows = sqlx::query(
+    // Each search branch is a single ILIKE so it can use its own trigram index;
+    // a combined `sku ILIKE $5 OR title ILIKE $5` falls back to scanning every SKU.
+    let sql = if query.is_empty() {
         r#"
-        SELECT v.id::text as id,
-               v.sku,
-               v.jan_code,
-               v.product_id::text as product_id,
-               p.title as product_title,
-               v.fulfillment_type,
-               v.price_amount,
-               v.price_currency,
-               v.status
+        SELECT v.id::text as id, v.id as cursor_id, v.sku, v.jan_code, v.product_id::text as product_id,
+               p.title as product_title, v.fulfillment_type, v.price_amount, v.price_currency,
+               v.status, v.created_at
         FROM product_skus v
         JOIN products p ON p.id = v.product_id
         WHERE p.store_id = $1
-          AND ($2 = '' OR v.sku ILIKE $2 OR p.title ILIKE $2)
-        ORDER BY v.created_at DESC
-        LIMIT 50
-        "#,
-    )
-    .bind(store_id.as_uuid())
-    .bind(pattern)
-    .fetch_all(&state.db)
-    .await
-    .map_err(db::error)?;
+          AND ($2::timestamptz IS NULL OR (v.created_at, v.id) < ($2, $3))
+        ORDER BY v.created_at DESC, v.id DESC
+        LIMIT $4
+        "#
+    } else {
+        r#"
+        WITH matched AS (
+            SELECT v.id
+            FROM product_skus v
+            JOIN products p ON p.id = v.product_id
+            WHERE p.store_id = $1 AND v.sku ILIKE $5
+            UNION
+            SELECT v.id
+            FROM product_skus v
+            JOIN products p ON p.id = v.product_id
+            WHERE p.store_id = $1 AND p.title ILIKE $5
+        )
+        SELECT v.id::text as id, v.id as cursor_id, v.sku, v.jan_code, v.product_id::text as product_id,
+               p.title as product_title, v.fulfillment_type, v.price_amount, v.price_currency,
+               v.status, v.created_at
+        FROM matched m
+        JOIN product_skus v ON v.id = m.id
+        JOIN products p ON p.id = v.product_id
+        WHERE ($2::timestamptz IS NULL OR (v.created_at, v.id) < ($2, $3))
+        ORDER BY v.created_at DESC, v.id DESC
+        LIMIT $4
+        "#
+    };
+    let mut rows_query = sqlx::query(sql)
+        .bind(store_id.as_uuid())
+        .bind(cursor.map(|(created_at, _)| created_at))
+        .bind(cursor.map(|(_, id)| id))
+        .bind(limit + 1);
+    if !query.is_empty() {
+        rows_query = rows_query.bind(format!("%{}%", query));
+    }
+    let mut rows = rows_query.fetch_all(&state.db).await.map_err(db::error)?;
 
-    Ok(rows
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("created_at"), last.get("cursor_id"));
+        }
+    }
+    let skus = rows
         .into_iter()
         .map(|row| pb::SkuAdmin {
             id: row.get("id"),
@@ -328,37 +484,61 @@ pub async fn list_skus_admin(
             status: row.get("status"),
             jan_code: row.get::<Option<String>, _>("jan_code").unwrap_or_default(),
         })
-        .collect())
+        .collect();
+    Ok((skus, pb::PageResult { next_page_token }))
 }
 
 pub async fn create_product(
     state: &AppState,
+    ctx: &request_context::RequestContext,
     req: pb::CreateProductRequest,
     _actor: Option<pb::ActorContext>,
-) -> Result<pb::ProductAdmin, (StatusCode, Json<ConnectError>)> {
-    let (store_id, tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
+) -> Result<(pb::ProductAdmin, Vec<String>), (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context_with(state, ctx, req.store.clone(), req.tenant.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
     let product_id = uuid::Uuid::new_v4();
     let tax_rule_id = nullable_uuid(req.tax_rule_id.clone());
     let sale_start_at = timestamp_to_chrono(req.sale_start_at.clone());
     let sale_end_at = timestamp_to_chrono(req.sale_end_at.clone());
-    let status = ProductStatus::parse(&req.status)?.as_str().to_string();
-    let (primary_category_id, category_ids) =
-        normalize_category_ids(&req.primary_category_id, req.category_ids.clone())?;
+    let mut validation = ValidationBuilder::new();
+    validation.require("title", &req.title);
+    let status = validation.check("status", ProductStatus::parse(&req.status));
+    let categories = validation.check(
+        "primary_category_id",
+        normalize_category_ids(&req.primary_category_id, req.category_ids.clone()),
+    );
     if let (Some(start), Some(end)) = (&sale_start_at, &sale_end_at)
         && start > end
     {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sale_end_at must be later than sale_start_at".to_string(),
-            }),
-        ));
+        validation.violation("sale_end_at", "sale_end_at must be later than sale_start_at");
+    }
+    if req.variant_axes.iter().any(|axis| axis.name.trim().is_empty()) {
+        validation.violation("variant_axes.name", "variant_axes.name is required");
+    }
+    let default_variant = if req.variant_axes.is_empty() {
+        validate_default_variant(&mut validation, req.default_variant.as_ref())
+    } else {
+        None
+    };
+    let (status, (primary_category_id, category_ids)) = validation.finish_with(status.zip(categories))?;
+    let status = status.as_str().to_string();
+    // Without axes the default variant (price > 0) is required above; with axes the variants
+    // are added later, so an active product may have nothing to sell yet.
+    let mut warnings = Vec::new();
+    if status == ProductStatus::Active.as_str() && !req.variant_axes.is_empty() {
+        warnings.push(ACTIVE_WITHOUT_VARIANTS_WARNING.to_string());
     }
+    let vendor_uuid = if req.vendor_id.is_empty() {
+        None
+    } else {
+        Some(parse_uuid(&req.vendor_id, "vendor_id")?)
+    };
     let mut tx = state.db.begin().await.map_err(db::error)?;
     ensure_category_ids_exist(&mut tx, &store_uuid.as_uuid(), &category_ids).await?;
+    if let Some(vendor_uuid) = vendor_uuid {
+        ensure_vendor_in_tenant(&mut tx, &tenant_uuid.as_uuid(), &vendor_uuid).await?;
+    }
     sqlx::query(
         r#"
         INSERT INTO products (
@@ -371,7 +551,7 @@ pub async fn create_product(
     .bind(product_id)
     .bind(tenant_uuid.as_uuid())
     .bind(store_uuid.as_uuid())
-    .bind(crate::shared::ids::nullable_uuid(req.vendor_id.clone()))
+    .bind(vendor_uuid)
     .bind(&req.title)
     .bind(&req.description)
     .bind(&status)
@@ -404,15 +584,6 @@ pub async fn create_product(
     if !req.variant_axes.is_empty() {
         for (idx, axis) in req.variant_axes.iter().enumerate() {
             let name = axis.name.trim();
-            if name.is_empty() {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::InvalidArgument,
-                        message: "variant_axes.name is required".to_string(),
-                    }),
-                ));
-            }
             let position = if axis.position > 0 {
                 axis.position as i32
             } else {
@@ -432,41 +603,7 @@ pub async fn create_product(
             .await
             .map_err(db::error)?;
         }
-    } else {
-        let default_variant = req.default_variant.ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "default_variant is required when variant_axes is empty".to_string(),
-                }),
-            )
-        })?;
-        let default_sku = SkuCode::parse(&default_variant.sku)?;
-        if default_variant.fulfillment_type.trim().is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "default_variant.fulfillment_type is required".to_string(),
-                }),
-            ));
-        }
-        if default_variant.status.trim().is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "default_variant.status is required".to_string(),
-                }),
-            ));
-        }
-        let (price_amount, price_currency) = money_to_parts(default_variant.price.clone())?;
-        let (compare_amount, compare_currency) = money_to_parts_opt(default_variant.compare_at.clone())?;
-        let fulfillment_type = FulfillmentType::parse(&default_variant.fulfillment_type)?
-            .as_str()
-            .to_string();
-        let variant_status = VariantStatus::parse(&default_variant.status)?.as_str().to_string();
+    } else if let Some(default_variant) = default_variant {
         sqlx::query(
             r#"
             INSERT INTO product_skus (
@@ -477,18 +614,14 @@ pub async fn create_product(
         )
         .bind(uuid::Uuid::new_v4())
         .bind(product_id)
-        .bind(default_sku.as_str())
-        .bind(if default_variant.jan_code.is_empty() {
-            None
-        } else {
-            Some(default_variant.jan_code.as_str())
-        })
-        .bind(&fulfillment_type)
-        .bind(price_amount)
-        .bind(&price_currency)
-        .bind(compare_amount)
-        .bind(compare_currency)
-        .bind(&variant_status)
+        .bind(&default_variant.sku)
+        .bind(default_variant.jan_code)
+        .bind(default_variant.fulfillment_type)
+        .bind(default_variant.price_amount)
+        .bind(&default_variant.price_currency)
+        .bind(default_variant.compare_amount)
+        .bind(default_variant.compare_currency)
+        .bind(default_variant.status)
         .bind(tax_rule_id)
         .execute(&mut *tx)
         .await
@@ -510,25 +643,91 @@ pub async fn create_product(
         category_ids: category_ids.clone(),
     };
 
-    audit::record_tx(
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                ProductAuditAction::Create,
+                Some("product"),
+                Some(product.id.clone()),
+                None,
+                to_json_opt(Some(product.clone())),
+                _actor,
+            ),
+        )
+        .await?;
+
+    outbox::enqueue_tx(
         &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            ProductAuditAction::Create.into(),
-            Some("product"),
-            Some(product.id.clone()),
-            None,
-            to_json_opt(Some(product.clone())),
-            _actor,
-        ),
+        product_event_input(tenant_id, &store_id, PRODUCT_CREATED_EVENT_TYPE, &product),
     )
     .await?;
-
+    outbox::enqueue_search_reindex_tx(&mut tx, &product.id).await?;
     tx.commit().await.map_err(db::error)?;
 
-    let _ = reindex_product_by_id(state, &product.id).await;
+    Ok((product, warnings))
+}
+
+struct DefaultVariantFields {
+    sku: SkuCode,
+    jan_code: Option<String>,
+    fulfillment_type: &'static str,
+    status: &'static str,
+    price_amount: i64,
+    price_currency: String,
+    compare_amount: Option<i64>,
+    compare_currency: Option<String>,
+}
 
-    Ok(product)
+fn validate_default_variant(
+    validation: &mut ValidationBuilder,
+    variant: Option<&pb::DefaultVariantInput>,
+) -> Option<DefaultVariantFields> {
+    let Some(variant) = variant else {
+        validation.violation(
+            "default_variant",
+            "default_variant is required when variant_axes is empty",
+        );
+        return None;
+    };
+    let sku = validation.check("default_variant.sku", SkuCode::parse(&variant.sku));
+    let fulfillment_type = if variant.fulfillment_type.trim().is_empty() {
+        validation.violation(
+            "default_variant.fulfillment_type",
+            "default_variant.fulfillment_type is required",
+        );
+        None
+    } else {
+        validation.check(
+            "default_variant.fulfillment_type",
+            FulfillmentType::parse(&variant.fulfillment_type),
+        )
+    };
+    let status = if variant.status.trim().is_empty() {
+        validation.violation("default_variant.status", "default_variant.status is required");
+        None
+    } else {
+        validation.check("default_variant.status", VariantStatus::parse(&variant.status))
+    };
+    let price = validation.check("default_variant.price", price_to_parts(variant.price.clone()));
+    let compare_at = validation.check(
+        "default_variant.compare_at",
+        money_to_parts_opt(variant.compare_at.clone()),
+    );
+    let (price_amount, price_currency) = price?;
+    let (compare_amount, compare_currency) = compare_at?;
+    Some(DefaultVariantFields {
+        sku: sku?,
+        jan_code: (!variant.jan_code.is_empty()).then(|| variant.jan_code.clone()),
+        fulfillment_type: fulfillment_type?.as_str(),
+        status: status?.as_str(),
+        price_amount,
+        price_currency,
+        compare_amount,
+        compare_currency,
+    })
 }
 
 fn normalize_category_ids(
@@ -547,10 +746,10 @@ fn normalize_category_ids(
     if primary.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "primary_category_id is required when category_ids are provided".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "primary_category_id is required when category_ids are provided",
+            )),
         ));
     }
     if ordered.is_empty() {
@@ -564,6 +763,27 @@ fn normalize_category_ids(
     Ok((primary.to_string(), ordered))
 }
 
+// Vendors are tenant-scoped; a vendor id from another tenant must not be attachable.
+async fn ensure_vendor_in_tenant(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    tenant_id: &uuid::Uuid,
+    vendor_id: &uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let row = sqlx::query("SELECT 1 FROM vendors WHERE id = $1 AND tenant_id = $2")
+        .bind(vendor_id)
+        .bind(tenant_id)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    if row.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "vendor_id not found")),
+        ));
+    }
+    Ok(())
+}
+
 async fn ensure_category_ids_exist(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
     store_id: &uuid::Uuid,
@@ -585,10 +805,7 @@ async fn ensure_category_ids_exist(
     if rows.len() != parsed.len() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category_id is invalid".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "category_id is invalid")),
         ));
     }
     Ok(())
@@ -596,10 +813,11 @@ async fn ensure_category_ids_exist(
 
 pub async fn update_product(
     state: &AppState,
+    ctx: &request_context::RequestContext,
     req: pb::UpdateProductRequest,
     _actor: Option<pb::ActorContext>,
-) -> Result<pb::ProductAdmin, (StatusCode, Json<ConnectError>)> {
-    let (store_id, tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
+) -> Result<(pb::ProductAdmin, Vec<String>), (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context_with(state, ctx, req.store.clone(), req.tenant.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
     let product_uuid = ProductId::parse(&req.product_id)?;
@@ -609,23 +827,27 @@ pub async fn update_product(
     let tax_rule_id = nullable_uuid(req.tax_rule_id.clone());
     let sale_start_at = timestamp_to_chrono(req.sale_start_at.clone());
     let sale_end_at = timestamp_to_chrono(req.sale_end_at.clone());
-    let status = ProductStatus::parse(&req.status)?.as_str().to_string();
-    let (primary_category_id, category_ids) =
-        normalize_category_ids(&req.primary_category_id, req.category_ids.clone())?;
+    let mut validation = ValidationBuilder::new();
+    validation.require("title", &req.title);
+    let status = validation.check("status", ProductStatus::parse(&req.status));
+    let categories = validation.check(
+        "primary_category_id",
+        normalize_category_ids(&req.primary_category_id, req.category_ids.clone()),
+    );
     if let (Some(start), Some(end)) = (&sale_start_at, &sale_end_at)
         && start > end
     {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sale_end_at must be later than sale_start_at".to_string(),
-            }),
-        ));
+        validation.violation("sale_end_at", "sale_end_at must be later than sale_start_at");
     }
+    let (status, (primary_category_id, category_ids)) = validation.finish_with(status.zip(categories))?;
+    let status = status.as_str().to_string();
+    let mut warnings = sale_window_warnings(&status, sale_start_at, sale_end_at);
     let mut tx = state.db.begin().await.map_err(db::error)?;
     ensure_category_ids_exist(&mut tx, &store_uuid.as_uuid(), &category_ids).await?;
-    sqlx::query(
+    if status == ProductStatus::Active.as_str() && before.as_ref().is_none_or(|before| before.status != status) {
+        ensure_activatable(&mut tx, &product_uuid.as_uuid(), &mut warnings).await?;
+    }
+    let updated = sqlx::query(
         r#"
         UPDATE products
         SET title = $1,
@@ -635,7 +857,7 @@ pub async fn update_product(
             sale_start_at = $5,
             sale_end_at = $6,
             updated_at = now()
-        WHERE id = $7 AND tenant_id = $8 AND store_id = $9
+        WHERE id = $7 AND tenant_id = $8 AND store_id = $9 AND deleted_at IS NULL
         "#,
     )
     .bind(&req.title)
@@ -650,6 +872,12 @@ pub async fn update_product(
     .execute(tx.as_mut())
     .await
     .map_err(db::error)?;
+    if updated.rows_affected() == 0 {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
+        ));
+    }
 
     sqlx::query("DELETE FROM product_category_links WHERE product_id = $1")
         .bind(product_uuid.as_uuid())
@@ -751,25 +979,130 @@ pub async fn update_product(
         };
     }
 
-    audit::record_tx(
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                ProductAuditAction::Update,
+                Some("product"),
+                Some(product.id.clone()),
+                to_json_opt(before),
+                to_json_opt(Some(after.clone())),
+                _actor,
+            ),
+        )
+        .await?;
+
+    outbox::enqueue_tx(
         &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            ProductAuditAction::Update.into(),
-            Some("product"),
-            Some(product.id.clone()),
-            to_json_opt(before),
-            to_json_opt(Some(after.clone())),
-            _actor,
-        ),
+        product_event_input(tenant_id, &store_id, PRODUCT_UPDATED_EVENT_TYPE, &after),
     )
     .await?;
-
+    outbox::enqueue_search_reindex_tx(&mut tx, &product.id).await?;
     tx.commit().await.map_err(db::error)?;
 
-    let _ = reindex_product_by_id(state, &product.id).await;
+    Ok((product, warnings))
+}
+
+fn product_event_input(
+    tenant_id: String,
+    store_id: &str,
+    event_type: &str,
+    product: &pb::ProductAdmin,
+) -> outbox::OutboxEventInput {
+    outbox::OutboxEventInput {
+        tenant_id,
+        store_id: Some(store_id.to_string()),
+        aggregate_type: "product".to_string(),
+        aggregate_id: product.id.clone(),
+        event_type: event_type.to_string(),
+        payload_json: serde_json::json!({
+            "product_id": product.id,
+            "store_id": store_id,
+            "status": product.status,
+            "title": product.title,
+        }),
+    }
+}
+
+// Activation needs a variant with a positive price. Products with variant axes only get a
+// warning, matching create_product, because their variants may still be on the way.
+// Variant and stock writes are rejected once the product is soft-deleted; FOR SHARE holds off a
+// concurrent DeleteProduct until the write commits.
+async fn ensure_product_not_deleted_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    product_uuid: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let deleted: Option<bool> =
+        sqlx::query_scalar("SELECT deleted_at IS NOT NULL FROM products WHERE id = $1 FOR SHARE")
+            .bind(product_uuid)
+            .fetch_optional(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    match deleted {
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
+        )),
+        Some(true) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::FailedPrecondition, "product is deleted")),
+        )),
+        Some(false) => Ok(()),
+    }
+}
+
+async fn ensure_activatable(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    product_uuid: &uuid::Uuid,
+    warnings: &mut Vec<String>,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let row = sqlx::query(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM product_variant_axes WHERE product_id = $1) as has_axes,
+               EXISTS (SELECT 1 FROM product_skus WHERE product_id = $1 AND price_amount > 0) as has_priced_variant
+        "#,
+    )
+    .bind(product_uuid)
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if row.get::<bool, _>("has_priced_variant") {
+        return Ok(());
+    }
+    if row.get::<bool, _>("has_axes") {
+        warnings.push(ACTIVE_WITHOUT_VARIANTS_WARNING.to_string());
+        return Ok(());
+    }
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(ConnectError::new(
+            ErrorCode::InvalidArgument,
+            "an active product requires a variant with price.amount greater than 0",
+        )),
+    ))
+}
 
-    Ok(product)
+fn sale_window_warnings(
+    status: &str,
+    sale_start_at: Option<chrono::DateTime<Utc>>,
+    sale_end_at: Option<chrono::DateTime<Utc>>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(end) = sale_end_at
+        && status == ProductStatus::Active.as_str()
+        && end < Utc::now()
+    {
+        warnings.push("sale_end_at is in the past—product will not appear on sale".to_string());
+    }
+    if let (Some(start), Some(end)) = (sale_start_at, sale_end_at)
+        && start > end - chrono::Duration::hours(1)
+    {
+        warnings.push("sale window is shorter than 1 hour".to_string());
+    }
+    warnings
 }
 
 pub async fn list_categories_admin(
@@ -825,7 +1158,7 @@ pub async fn list_categories_admin(
         .await
         .map_err(db::error)?
     };
-    Ok(rows.into_iter().map(category_from_row).collect())
+    rows.into_iter().map(category_from_row).collect()
 }
 
 pub async fn create_category(
@@ -841,22 +1174,13 @@ pub async fn create_category(
     if name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category.name is required".to_string(),
-            }),
-        ));
-    }
-    let slug = category.slug.trim();
-    if slug.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category.slug is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "category.name is required",
+            )),
         ));
     }
+    let slug = SlugValue::parse(&category.slug)?;
     let status = if category.status.trim().is_empty() {
         "active".to_string()
     } else {
@@ -877,10 +1201,7 @@ pub async fn create_category(
         if exists.is_none() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "parent_id is invalid".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "parent_id is invalid")),
             ));
         }
     }
@@ -897,6 +1218,7 @@ pub async fn create_category(
         .map_err(db::error)?;
         row.get::<i32, _>("next_pos")
     };
+    ensure_category_slug_available(state, &store_uuid.as_uuid(), &slug, None).await?;
     let row = sqlx::query(
         r#"
         INSERT INTO product_categories (
@@ -918,7 +1240,7 @@ pub async fn create_category(
     .bind(store_uuid.as_uuid())
     .bind(parent_id)
     .bind(name)
-    .bind(slug)
+    .bind(&slug)
     .bind(if category.description.trim().is_empty() {
         None
     } else {
@@ -929,7 +1251,7 @@ pub async fn create_category(
     .fetch_one(&state.db)
     .await
     .map_err(db::error)?;
-    Ok(category_from_row(row))
+    category_from_row(row)
 }
 
 pub async fn update_category(
@@ -945,22 +1267,13 @@ pub async fn update_category(
     if name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category.name is required".to_string(),
-            }),
-        ));
-    }
-    let slug = category.slug.trim();
-    if slug.is_empty() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category.slug is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "category.name is required",
+            )),
         ));
     }
+    let slug = SlugValue::parse(&category.slug)?;
     let status = if category.status.trim().is_empty() {
         "active".to_string()
     } else {
@@ -975,10 +1288,10 @@ pub async fn update_category(
         if parent_id == category_uuid {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "parent_id cannot be same as category_id".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "parent_id cannot be same as category_id",
+                )),
             ));
         }
         let exists = sqlx::query("SELECT id FROM product_categories WHERE store_id = $1 AND id = $2")
@@ -990,10 +1303,7 @@ pub async fn update_category(
         if exists.is_none() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "parent_id is invalid".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "parent_id is invalid")),
             ));
         }
     }
@@ -1002,6 +1312,7 @@ pub async fn update_category(
     } else {
         None
     };
+    ensure_category_slug_available(state, &store_uuid.as_uuid(), &slug, Some(category_uuid)).await?;
     let row = sqlx::query(
         r#"
         UPDATE product_categories
@@ -1026,7 +1337,7 @@ pub async fn update_category(
         "#,
     )
     .bind(name)
-    .bind(slug)
+    .bind(&slug)
     .bind(if category.description.trim().is_empty() {
         None
     } else {
@@ -1040,53 +1351,324 @@ pub async fn update_category(
     .fetch_one(&state.db)
     .await
     .map_err(db::error)?;
-    Ok(category_from_row(row))
+    category_from_row(row)
+}
+
+async fn ensure_category_slug_available(
+    state: &AppState,
+    store_uuid: &uuid::Uuid,
+    slug: &SlugValue,
+    exclude_category_id: Option<uuid::Uuid>,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let exists = sqlx::query(
+        "SELECT 1 FROM product_categories WHERE store_id = $1 AND slug = $2 AND id IS DISTINCT FROM $3 LIMIT 1",
+    )
+    .bind(store_uuid)
+    .bind(slug)
+    .bind(exclude_category_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(db::error)?;
+    if exists.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ConnectError::new(
+                ErrorCode::AlreadyExists,
+                "slug already exists for this store",
+            )),
+        ));
+    }
+    Ok(())
 }
 
 pub async fn delete_category(
     state: &AppState,
     req: pb::DeleteCategoryRequest,
-    _actor: Option<pb::ActorContext>,
-) -> Result<bool, (StatusCode, Json<ConnectError>)> {
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::DeleteCategoryResponse, (StatusCode, Json<ConnectError>)> {
     let (store_id, _tenant_id) = resolve_store_context(state, req.store, None).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let category_uuid = parse_uuid(&req.category_id, "category_id")?;
-    let children = sqlx::query("SELECT 1 FROM product_categories WHERE store_id = $1 AND parent_id = $2 LIMIT 1")
-        .bind(store_uuid.as_uuid())
+    let category = sqlx::query("SELECT 1 FROM product_categories WHERE id = $1 AND store_id = $2")
         .bind(category_uuid)
+        .bind(store_uuid.as_uuid())
         .fetch_optional(&state.db)
         .await
         .map_err(db::error)?;
-    if children.is_some() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category has children".to_string(),
-            }),
-        ));
+    if category.is_none() {
+        return Ok(pb::DeleteCategoryResponse {
+            deleted: false,
+            blocking_product_ids: Vec::new(),
+        });
     }
-    let linked = sqlx::query("SELECT 1 FROM product_category_links WHERE category_id = $1 LIMIT 1")
+    let children = sqlx::query("SELECT 1 FROM product_categories WHERE store_id = $1 AND parent_id = $2 LIMIT 1")
+        .bind(store_uuid.as_uuid())
         .bind(category_uuid)
         .fetch_optional(&state.db)
         .await
         .map_err(db::error)?;
-    if linked.is_some() {
+    if children.is_some() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "category is used by products".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "category has children")),
         ));
     }
+    let linked_product_ids: Vec<String> = sqlx::query_scalar(
+        "SELECT product_id::text FROM product_category_links WHERE category_id = $1 ORDER BY position ASC, product_id ASC",
+    )
+    .bind(category_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+    if !linked_product_ids.is_empty() && !req.force {
+        return Ok(pb::DeleteCategoryResponse {
+            deleted: false,
+            blocking_product_ids: linked_product_ids,
+        });
+    }
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    if !linked_product_ids.is_empty() {
+        let unlinked = sqlx::query(
+            "DELETE FROM product_category_links WHERE category_id = $1 RETURNING product_id, product_id::text as product_id_text, is_primary",
+        )
+        .bind(category_uuid)
+        .fetch_all(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+        // Products that lost their primary category fall back to their next linked category by position.
+        let lost_primary = unlinked
+            .iter()
+            .filter(|row| row.get::<bool, _>("is_primary"))
+            .map(|row| row.get::<uuid::Uuid, _>("product_id"))
+            .collect::<Vec<_>>();
+        if !lost_primary.is_empty() {
+            sqlx::query(
+                r#"
+                UPDATE product_category_links AS l
+                SET is_primary = true
+                FROM (
+                    SELECT DISTINCT ON (product_id) product_id, category_id
+                    FROM product_category_links
+                    WHERE product_id = ANY($1)
+                    ORDER BY product_id, position ASC, created_at ASC
+                ) AS next
+                WHERE l.product_id = next.product_id AND l.category_id = next.category_id
+                "#,
+            )
+            .bind(&lost_primary)
+            .execute(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+        }
+        for row in unlinked.iter() {
+            outbox::enqueue_search_reindex_tx(&mut tx, &row.get::<String, _>("product_id_text")).await?;
+        }
+    }
     let result = sqlx::query("DELETE FROM product_categories WHERE id = $1 AND store_id = $2")
         .bind(category_uuid)
         .bind(store_uuid.as_uuid())
-        .execute(&state.db)
+        .execute(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    let deleted = result.rows_affected() > 0;
+    if deleted && !linked_product_ids.is_empty() {
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    Some(store_id.clone()),
+                    CategoryAuditAction::Delete,
+                    Some("category"),
+                    Some(req.category_id.clone()),
+                    Some(serde_json::json!({ "linked_product_ids": linked_product_ids })),
+                    None,
+                    actor,
+                ),
+            )
+            .await?;
+    }
+    tx.commit().await.map_err(db::error)?;
+
+    Ok(pb::DeleteCategoryResponse {
+        deleted,
+        blocking_product_ids: Vec::new(),
+    })
+}
+
+// Soft delete keeps the rows so past orders can still resolve their variants; `force`
+// removes the product and its catalog data for good, which is only possible while nothing
+// outside the catalog (orders, carts, reservations, auctions) references it.
+pub async fn delete_product(
+    state: &AppState,
+    req: pb::DeleteProductRequest,
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::DeleteProductResponse, (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context(state, req.store, None).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let tenant_uuid = TenantId::parse(&tenant_id)?;
+    let product_uuid = ProductId::parse(&req.product_id)?;
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let product = sqlx::query(
+        r#"
+        SELECT title, status, deleted_at IS NOT NULL as deleted
+        FROM products
+        WHERE id = $1 AND tenant_id = $2 AND store_id = $3
+        FOR UPDATE
+        "#,
+    )
+    .bind(product_uuid.as_uuid())
+    .bind(tenant_uuid.as_uuid())
+    .bind(store_uuid.as_uuid())
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    let Some(product) = product else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
+        ));
+    };
+    if product.get::<bool, _>("deleted") && !req.force {
+        return Ok(pb::DeleteProductResponse {
+            deleted: true,
+            hard_deleted: false,
+        });
+    }
+
+    let in_use: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM order_items oi
+            JOIN orders o ON o.id = oi.order_id
+            JOIN product_skus s ON s.id = oi.variant_id
+            WHERE s.product_id = $1
+              AND o.status IN ('pending_payment', 'pending_shipment', 'shipped')
+        ) OR EXISTS (
+            SELECT 1
+            FROM cart_items ci
+            JOIN carts c ON c.id = ci.cart_id
+            JOIN product_skus s ON s.id = ci.sku_id
+            WHERE s.product_id = $1 AND ci.status = 'active' AND c.status = 'active'
+        )
+        "#,
+    )
+    .bind(product_uuid.as_uuid())
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if in_use {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "product has variants in active orders or carts",
+            )),
+        ));
+    }
+
+    if req.force {
+        let referenced: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM order_items oi JOIN product_skus s ON s.id = oi.variant_id WHERE s.product_id = $1
+            ) OR EXISTS (
+                SELECT 1 FROM cart_items ci JOIN product_skus s ON s.id = ci.sku_id WHERE s.product_id = $1
+            ) OR EXISTS (
+                SELECT 1 FROM inventory_reservations r JOIN product_skus s ON s.id = r.sku_id WHERE s.product_id = $1
+            ) OR EXISTS (
+                SELECT 1
+                FROM inventory_reservation_requests r
+                JOIN product_skus s ON s.id = r.sku_id
+                WHERE s.product_id = $1
+            ) OR EXISTS (
+                SELECT 1
+                FROM auctions a
+                WHERE a.product_id = $1 OR a.sku_id IN (SELECT id FROM product_skus WHERE product_id = $1)
+            )
+            "#,
+        )
+        .bind(product_uuid.as_uuid())
+        .fetch_one(tx.as_mut())
         .await
         .map_err(db::error)?;
-    Ok(result.rows_affected() > 0)
+        if referenced {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::FailedPrecondition,
+                    "product is referenced by orders, carts or auctions; delete it without force instead".to_string(),
+                )),
+            ));
+        }
+        hard_delete_product(&mut tx, product_uuid.as_uuid()).await?;
+    } else {
+        sqlx::query("UPDATE products SET status = $2, deleted_at = now(), updated_at = now() WHERE id = $1")
+            .bind(product_uuid.as_uuid())
+            .bind(ProductStatus::Deleted.as_str())
+            .execute(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    }
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                ProductAuditAction::Delete,
+                Some("product"),
+                Some(req.product_id.clone()),
+                Some(serde_json::json!({
+                    "title": product.get::<String, _>("title"),
+                    "status": product.get::<String, _>("status"),
+                })),
+                Some(serde_json::json!({ "hard_deleted": req.force })),
+                actor,
+            ),
+        )
+        .await?;
+    // The reindex loop removes documents for products that are gone or soft-deleted.
+    outbox::enqueue_search_reindex_tx(&mut tx, &req.product_id).await?;
+    tx.commit().await.map_err(db::error)?;
+
+    Ok(pb::DeleteProductResponse {
+        deleted: true,
+        hard_deleted: req.force,
+    })
+}
+
+async fn hard_delete_product(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    product_id: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    // Children first; variant_axis_values, product_category_links and product_translations
+    // also cascade, but are listed so the set of removed data is explicit.
+    const STATEMENTS: &[&str] = &[
+        "DELETE FROM variant_axis_values WHERE variant_id IN (SELECT id FROM product_skus WHERE product_id = $1)",
+        "DELETE FROM sku_images WHERE sku_id IN (SELECT id FROM product_skus WHERE product_id = $1)",
+        "DELETE FROM store_digital_assets WHERE sku_id IN (SELECT id FROM product_skus WHERE product_id = $1)",
+        "DELETE FROM inventory_movements WHERE sku_id IN (SELECT id FROM product_skus WHERE product_id = $1)",
+        "DELETE FROM inventory_stocks WHERE sku_id IN (SELECT id FROM product_skus WHERE product_id = $1)",
+        "DELETE FROM product_skus WHERE product_id = $1",
+        "DELETE FROM product_variant_axes WHERE product_id = $1",
+        "DELETE FROM product_digital_settings WHERE product_id = $1",
+        "DELETE FROM product_category_links WHERE product_id = $1",
+        "DELETE FROM product_translations WHERE product_id = $1",
+        "DELETE FROM products WHERE id = $1",
+    ];
+    for statement in STATEMENTS {
+        sqlx::query(statement)
+            .bind(product_id)
+            .execute(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    }
+    Ok(())
 }
 
 pub async fn reorder_categories(
@@ -1109,25 +1691,39 @@ pub async fn reorder_categories(
     } else {
         Some(parse_uuid(&req.parent_id, "parent_id")?)
     };
-    let rows = sqlx::query(
-        "SELECT id FROM product_categories WHERE store_id = $1 AND parent_id IS NOT DISTINCT FROM $2 AND id = ANY($3)",
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    // Every submitted id must sit under the requested parent...
+    let submitted_parents: Vec<Option<uuid::Uuid>> =
+        sqlx::query_scalar("SELECT parent_id FROM product_categories WHERE store_id = $1 AND id = ANY($2)")
+            .bind(store_uuid.as_uuid())
+            .bind(&ordered_ids)
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(db::error)?;
+    // ...and together they must be that parent's full set of children, each listed once.
+    let siblings: Vec<uuid::Uuid> = sqlx::query_scalar(
+        "SELECT id FROM product_categories WHERE store_id = $1 AND parent_id IS NOT DISTINCT FROM $2 FOR UPDATE",
     )
     .bind(store_uuid.as_uuid())
     .bind(parent_id)
-    .bind(&ordered_ids)
-    .fetch_all(&state.db)
+    .fetch_all(tx.as_mut())
     .await
     .map_err(db::error)?;
-    if rows.len() != ordered_ids.len() {
+    let unique: std::collections::HashSet<&uuid::Uuid> = ordered_ids.iter().collect();
+    if unique.len() != ordered_ids.len()
+        || submitted_parents.len() != ordered_ids.len()
+        || submitted_parents.iter().any(|parent| *parent != parent_id)
+        || siblings.len() != ordered_ids.len()
+        || !siblings.iter().all(|id| unique.contains(id))
+    {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "ordered_ids contains invalid category".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "ordered_ids must contain exactly all categories under the specified parent",
+            )),
         ));
     }
-    let mut tx = state.db.begin().await.map_err(db::error)?;
     for (idx, category_id) in ordered_ids.iter().enumerate() {
         sqlx::query("UPDATE product_categories SET position = $1, updated_at = now() WHERE id = $2 AND store_id = $3")
             .bind((idx + 1) as i32)
@@ -1160,17 +1756,38 @@ pub async fn reorder_categories(
     .fetch_all(&state.db)
     .await
     .map_err(db::error)?;
-    Ok(rows.into_iter().map(category_from_row).collect())
+    rows.into_iter().map(category_from_row).collect()
 }
 
+/// Products in a category in manual order. Without `page` every product is returned (the reorder
+/// screen needs the whole list); with it the list is paged by `(position, id)`. `total_count` counts
+/// every product matching the filters, not just the page. Soft-deleted products are left out.
 pub async fn list_category_products_admin(
     state: &AppState,
     store: Option<pb::StoreContext>,
     category_id: String,
-) -> Result<Vec<pb::CategoryProductAdmin>, (StatusCode, Json<ConnectError>)> {
+    status: String,
+    page: Option<pb::PageInfo>,
+) -> Result<(Vec<pb::CategoryProductAdmin>, pb::PageResult, i64), (StatusCode, Json<ConnectError>)> {
     let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     let category_uuid = parse_uuid(&category_id, "category_id")?;
+    let status = match status.trim() {
+        "" => None,
+        status => Some(ProductStatus::try_from(status).map_err(|message| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message.to_string())),
+            )
+        })?),
+    };
+    let (limit, cursor) = match page {
+        Some(page) => {
+            let (limit, cursor) = position_page_params(Some(page), MAX_PAGE_SIZE)?;
+            (Some(limit), cursor)
+        }
+        None => (None, None),
+    };
     let exists = sqlx::query("SELECT 1 FROM product_categories WHERE id = $1 AND store_id = $2 LIMIT 1")
         .bind(category_uuid)
         .bind(store_uuid.as_uuid())
@@ -1180,30 +1797,61 @@ pub async fn list_category_products_admin(
     if exists.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "category not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "category not found")),
         ));
     }
-    let rows = sqlx::query(
+    let total_count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM product_category_links pc
+        JOIN products p ON p.id = pc.product_id
+        WHERE pc.category_id = $1 AND p.store_id = $2 AND p.deleted_at IS NULL
+          AND ($3::text IS NULL OR p.status = $3)
+        "#,
+    )
+    .bind(category_uuid)
+    .bind(store_uuid.as_uuid())
+    .bind(status.map(|status| status.as_str()))
+    .fetch_one(&state.db)
+    .await
+    .map_err(db::error)?;
+    let mut rows = sqlx::query(
         r#"
         SELECT p.id::text as product_id,
                p.title,
                p.status,
-               pc.position
+               pc.position,
+               p.id as cursor_id
         FROM product_category_links pc
         JOIN products p ON p.id = pc.product_id
-        WHERE pc.category_id = $1 AND p.store_id = $2
-        ORDER BY pc.position ASC, p.created_at ASC
+        WHERE pc.category_id = $1 AND p.store_id = $2 AND p.deleted_at IS NULL
+          AND ($3::text IS NULL OR p.status = $3)
+          AND ($4::int IS NULL OR (pc.position, p.id) > ($4, $5))
+        ORDER BY pc.position ASC, p.id ASC
+        LIMIT $6
         "#,
     )
     .bind(category_uuid)
     .bind(store_uuid.as_uuid())
+    .bind(status.map(|status| status.as_str()))
+    .bind(cursor.map(|(position, _)| position))
+    .bind(cursor.map(|(_, id)| id))
+    .bind(limit.map(|limit| limit + 1))
     .fetch_all(&state.db)
     .await
     .map_err(db::error)?;
-    Ok(rows.into_iter().map(category_product_from_row).collect())
+
+    let mut next_page_token = String::new();
+    if let Some(limit) = limit
+        && rows.len() > limit as usize
+    {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_position_page_token(last.get("position"), last.get("cursor_id"));
+        }
+    }
+    let products = rows.into_iter().map(category_product_from_row).collect();
+    Ok((products, pb::PageResult { next_page_token }, total_count))
 }
 
 pub async fn reorder_category_products(
@@ -1228,7 +1876,7 @@ pub async fn reorder_category_products(
         SELECT pc.product_id::text as product_id
         FROM product_category_links pc
         JOIN products p ON p.id = pc.product_id
-        WHERE pc.category_id = $1 AND p.store_id = $2
+        WHERE pc.category_id = $1 AND p.store_id = $2 AND p.deleted_at IS NULL
         ORDER BY pc.position ASC
         "#,
     )
@@ -1241,10 +1889,10 @@ pub async fn reorder_category_products(
     if existing_ids.len() != ordered_ids.len() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "ordered_product_ids must include all products in the category".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "ordered_product_ids must include all products in the category",
+            )),
         ));
     }
     let mut sorted_existing = existing_ids.clone();
@@ -1254,10 +1902,10 @@ pub async fn reorder_category_products(
     if sorted_existing != sorted_requested {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "ordered_product_ids contains invalid product".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "ordered_product_ids contains invalid product",
+            )),
         ));
     }
 
@@ -1287,7 +1935,7 @@ pub async fn reorder_category_products(
                pc.position
         FROM product_category_links pc
         JOIN products p ON p.id = pc.product_id
-        WHERE pc.category_id = $1 AND p.store_id = $2
+        WHERE pc.category_id = $1 AND p.store_id = $2 AND p.deleted_at IS NULL
         ORDER BY pc.position ASC, p.created_at ASC
         "#,
     )
@@ -1299,8 +1947,8 @@ pub async fn reorder_category_products(
     Ok(rows.into_iter().map(category_product_from_row).collect())
 }
 
-fn category_from_row(row: sqlx::postgres::PgRow) -> pb::Category {
-    pb::Category {
+fn category_from_row(row: sqlx::postgres::PgRow) -> Result<pb::Category, (StatusCode, Json<ConnectError>)> {
+    Ok(pb::Category {
         id: row.get::<String, _>("id"),
         store_id: row.get::<String, _>("store_id"),
         name: row.get("name"),
@@ -1309,9 +1957,15 @@ fn category_from_row(row: sqlx::postgres::PgRow) -> pb::Category {
         status: row.get("status"),
         parent_id: row.get::<Option<String>, _>("parent_id").unwrap_or_default(),
         position: row.get::<i32, _>("position"),
-        created_at: chrono_to_timestamp(row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")),
-        updated_at: chrono_to_timestamp(row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")),
-    }
+        created_at: Some(chrono_to_timestamp_required(
+            row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at"),
+            "created_at",
+        )?),
+        updated_at: Some(chrono_to_timestamp_required(
+            row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at"),
+            "updated_at",
+        )?),
+    })
 }
 
 fn category_product_from_row(row: sqlx::postgres::PgRow) -> pb::CategoryProductAdmin {
@@ -1357,10 +2011,10 @@ pub async fn create_product_metafield_definition(
     if input.namespace.is_empty() || input.key.is_empty() || input.name.is_empty() || input.value_type.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "definition required fields are missing".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "definition required fields are missing",
+            )),
         ));
     }
 
@@ -1398,19 +2052,19 @@ pub async fn update_product_metafield_definition(
     if definition_id.trim().is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "definition_id is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "definition_id is required",
+            )),
         ));
     }
     if input.namespace.is_empty() || input.key.is_empty() || input.name.is_empty() || input.value_type.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "definition required fields are missing".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "definition required fields are missing",
+            )),
         ));
     }
 
@@ -1442,16 +2096,48 @@ pub async fn update_product_metafield_definition(
     let Some(record) = record else {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "metafield definition not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "metafield definition not found")),
         ));
     };
 
     Ok(metafield_definition_from_record(&record))
 }
 
+pub async fn delete_product_metafield_definition(
+    state: &AppState,
+    definition_id: String,
+) -> Result<bool, (StatusCode, Json<ConnectError>)> {
+    let definition_uuid = parse_uuid(&definition_id, "definition_id")?;
+    metafields::soft_delete_definition(&state.db, METAFIELD_OWNER_TYPE_PRODUCT, &definition_uuid).await
+}
+
+pub async fn purge_product_metafield_definition(
+    state: &AppState,
+    definition_id: String,
+    confirm: bool,
+) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+    if !confirm {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "confirm must be true to purge a metafield definition and its values",
+            )),
+        ));
+    }
+    let definition_uuid = parse_uuid(&definition_id, "definition_id")?;
+    let purged = metafields::purge_definition(&state.db, METAFIELD_OWNER_TYPE_PRODUCT, &definition_uuid).await?;
+    purged.ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "metafield definition must be deleted before it can be purged",
+            )),
+        )
+    })
+}
+
 pub async fn list_product_metafield_values(
     state: &AppState,
     store: Option<pb::StoreContext>,
@@ -1477,10 +2163,7 @@ pub async fn list_product_metafield_values(
     if exists.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "product not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
         ));
     }
 
@@ -1518,20 +2201,17 @@ pub async fn upsert_product_metafield_value(
     if value_json.trim().is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "value_json is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "value_json is required")),
         ));
     }
 
     serde_json::from_str::<serde_json::Value>(&value_json).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "value_json must be valid JSON".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "value_json must be valid JSON",
+            )),
         )
     })?;
 
@@ -1551,10 +2231,7 @@ pub async fn upsert_product_metafield_value(
     if exists.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "product not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
         ));
     }
 
@@ -1563,10 +2240,7 @@ pub async fn upsert_product_metafield_value(
     let Some(definition) = definition else {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::NotFound,
-                message: "metafield definition not found".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::NotFound, "metafield definition not found")),
         ));
     };
     let value_type = definition.value_type.clone();
@@ -1575,10 +2249,10 @@ pub async fn upsert_product_metafield_value(
         let parsed = serde_json::from_str::<serde_json::Value>(&value_json).map_err(|_| {
             (
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "value_json must be a boolean".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "value_json must be a boolean",
+                )),
             )
         })?;
         let valid = if is_list {
@@ -1593,10 +2267,10 @@ pub async fn upsert_product_metafield_value(
         if !valid {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "value_json must be a boolean".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "value_json must be a boolean",
+                )),
             ));
         }
     }
@@ -1625,19 +2299,38 @@ pub async fn create_variant(
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tenant does not match product".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "tenant does not match product",
+            )),
         ));
     }
     let variant_id = uuid::Uuid::new_v4();
-    let (price_amount, price_currency) = money_to_parts(req.price.clone())?;
-    let (compare_amount, compare_currency) = money_to_parts_opt(req.compare_at.clone())?;
-    let fulfillment_type = FulfillmentType::parse(&req.fulfillment_type)?.as_str().to_string();
-    let status = VariantStatus::parse(&req.status)?.as_str().to_string();
-    let sku = SkuCode::parse(&req.sku)?;
+    let mut validation = ValidationBuilder::new();
+    let price = validation.check("price", price_to_parts(req.price.clone()));
+    let compare_at = validation.check("compare_at", money_to_parts_opt(req.compare_at.clone()));
+    let fulfillment_type = validation.check("fulfillment_type", FulfillmentType::parse(&req.fulfillment_type));
+    let status = validation.check("status", VariantStatus::parse(&req.status));
+    let sku = validation.check("sku", SkuCode::parse(&req.sku));
+    let mut axis_value_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    for axis_value in req.axis_values.iter() {
+        let name = axis_value.name.trim().to_lowercase();
+        let value = axis_value.value.trim();
+        if name.is_empty() || value.is_empty() {
+            validation.violation("axis_values", "axis_values.name and axis_values.value are required");
+            break;
+        }
+        if axis_value_map.insert(name, value.to_string()).is_some() {
+            validation.violation("axis_values", "axis_values contains duplicate axis names");
+            break;
+        }
+    }
+    let (((((price_amount, price_currency), (compare_amount, compare_currency)), fulfillment_type), status), sku) =
+        validation.finish_with(price.zip(compare_at).zip(fulfillment_type).zip(status).zip(sku))?;
+    let fulfillment_type = fulfillment_type.as_str().to_string();
+    let status = status.as_str().to_string();
     let mut tx = state.db.begin().await.map_err(db::error)?;
+    ensure_product_not_deleted_tx(&mut tx, parse_uuid(&req.product_id, "product_id")?).await?;
     let axes_rows = sqlx::query(
         r#"
         SELECT id, name, position
@@ -1650,36 +2343,13 @@ pub async fn create_variant(
     .fetch_all(tx.as_mut())
     .await
     .map_err(db::error)?;
-    let mut axis_value_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
-    for axis_value in req.axis_values.iter() {
-        let name = axis_value.name.trim().to_lowercase();
-        let value = axis_value.value.trim();
-        if name.is_empty() || value.is_empty() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "axis_values.name and axis_values.value are required".to_string(),
-                }),
-            ));
-        }
-        if axis_value_map.insert(name, value.to_string()).is_some() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "axis_values contains duplicate axis names".to_string(),
-                }),
-            ));
-        }
-    }
     if axes_rows.is_empty() && !req.axis_values.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "axis_values cannot be set when variant axes are not defined".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "axis_values cannot be set when variant axes are not defined",
+            )),
         ));
     }
     let mut axis_values_for_response = Vec::new();
@@ -1691,10 +2361,10 @@ pub async fn create_variant(
             if value.is_empty() {
                 return Err((
                     StatusCode::BAD_REQUEST,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::InvalidArgument,
-                        message: format!("axis value is required for {}", axis_name),
-                    }),
+                    Json(ConnectError::new(
+                        ErrorCode::InvalidArgument,
+                        format!("axis value is required for {}", axis_name),
+                    )),
                 ));
             }
             axis_values_for_response.push(pb::VariantAxisValue {
@@ -1709,6 +2379,7 @@ pub async fn create_variant(
         .await
         .map_err(db::error)?
         .and_then(|row| row.get::<Option<uuid::Uuid>, _>("tax_rule_id"));
+    ensure_sku_available_tx(&mut tx, &parse_uuid(&store_id, "store_id")?, &sku).await?;
     sqlx::query(
         r#"
         INSERT INTO product_skus (
@@ -1719,7 +2390,7 @@ pub async fn create_variant(
     )
     .bind(variant_id)
     .bind(parse_uuid(&req.product_id, "product_id")?)
-    .bind(sku.as_str())
+    .bind(&sku)
     .bind(if req.jan_code.is_empty() {
         None
     } else {
@@ -1760,7 +2431,7 @@ pub async fn create_variant(
     let variant = pb::VariantAdmin {
         id: variant_id.to_string(),
         product_id: req.product_id,
-        sku: req.sku,
+        sku: sku.into_string(),
         fulfillment_type,
         price: req.price,
         compare_at: req.compare_at,
@@ -1774,27 +2445,59 @@ pub async fn create_variant(
         },
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            VariantAuditAction::Create.into(),
-            Some("variant"),
-            Some(variant.id.clone()),
-            None,
-            to_json_opt(Some(variant.clone())),
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                VariantAuditAction::Create,
+                Some("variant"),
+                Some(variant.id.clone()),
+                None,
+                to_json_opt(Some(variant.clone())),
+                _actor,
+            ),
+        )
+        .await?;
 
+    outbox::enqueue_search_reindex_tx(&mut tx, &variant.product_id).await?;
     tx.commit().await.map_err(db::error)?;
 
-    let _ = reindex_product_by_id(state, &variant.product_id).await;
-
     Ok(variant)
 }
 
+async fn ensure_sku_available_tx(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    store_uuid: &uuid::Uuid,
+    sku: &SkuCode,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let exists = sqlx::query(
+        r#"
+        SELECT 1
+        FROM product_skus v
+        JOIN products p ON p.id = v.product_id
+        WHERE p.store_id = $1 AND v.sku = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(store_uuid)
+    .bind(sku)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if exists.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ConnectError::new(
+                ErrorCode::AlreadyExists,
+                "sku already exists in this store",
+            )),
+        ));
+    }
+    Ok(())
+}
+
 pub async fn update_variant(
     state: &AppState,
     req: pb::UpdateVariantRequest,
@@ -1812,13 +2515,13 @@ pub async fn update_variant(
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tenant does not match variant".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "tenant does not match variant",
+            )),
         ));
     }
-    let (price_amount, price_currency) = money_to_parts(req.price.clone())?;
+    let (price_amount, price_currency) = price_to_parts(req.price.clone())?;
     let (compare_amount, compare_currency) = money_to_parts_opt(req.compare_at.clone())?;
     let fulfillment_type = if req.fulfillment_type.is_empty() {
         None
@@ -1846,29 +2549,29 @@ pub async fn update_variant(
         if name.is_empty() || value.is_empty() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "axis_values.name and axis_values.value are required".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "axis_values.name and axis_values.value are required",
+                )),
             ));
         }
         if axis_value_map.insert(name, value.to_string()).is_some() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "axis_values contains duplicate axis names".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "axis_values contains duplicate axis names",
+                )),
             ));
         }
     }
     if axes_rows.is_empty() && !req.axis_values.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "axis_values cannot be set when variant axes are not defined".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "axis_values cannot be set when variant axes are not defined",
+            )),
         ));
     }
     let mut tx = state.db.begin().await.map_err(db::error)?;
@@ -1905,10 +2608,10 @@ pub async fn update_variant(
             if value.is_empty() {
                 return Err((
                     StatusCode::BAD_REQUEST,
-                    Json(ConnectError {
-                        code: crate::rpc::json::ErrorCode::InvalidArgument,
-                        message: format!("axis value is required for {}", axis_name),
-                    }),
+                    Json(ConnectError::new(
+                        ErrorCode::InvalidArgument,
+                        format!("axis value is required for {}", axis_name),
+                    )),
                 ));
             }
             axis_values_for_response.push(pb::VariantAxisValue {
@@ -2007,32 +2710,191 @@ pub async fn update_variant(
         axis_values: axis_values_for_response,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            VariantAuditAction::Update.into(),
-            Some("variant"),
-            Some(variant.id.clone()),
-            None,
-            to_json_opt(Some(variant.clone())),
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                VariantAuditAction::Update,
+                Some("variant"),
+                Some(variant.id.clone()),
+                None,
+                to_json_opt(Some(variant.clone())),
+                _actor,
+            ),
+        )
+        .await?;
 
+    outbox::enqueue_search_reindex_tx(&mut tx, &variant.product_id).await?;
     tx.commit().await.map_err(db::error)?;
 
-    if let Ok(row) = sqlx::query("SELECT product_id::text as product_id FROM product_skus WHERE id = $1")
-        .bind(parse_uuid(&variant.id, "variant_id")?)
-        .fetch_one(&state.db)
+    Ok(variant)
+}
+
+pub async fn bulk_set_axis_values(
+    state: &AppState,
+    req: pb::BulkSetAxisValuesRequest,
+    actor: Option<pb::ActorContext>,
+) -> Result<i32, (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let tenant_uuid = TenantId::parse(&tenant_id)?;
+    let product_uuid = ProductId::parse(&req.product_id)?;
+    let axis_name = req.axis_name.trim();
+    if axis_name.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "axis_name is required")),
+        ));
+    }
+    if req.assignments.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "assignments is required")),
+        ));
+    }
+    if req.assignments.len() > BULK_AXIS_VALUES_MAX_ASSIGNMENTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!(
+                    "assignments must be at most {} per call",
+                    BULK_AXIS_VALUES_MAX_ASSIGNMENTS
+                ),
+            )),
+        ));
+    }
+    let mut variant_ids = Vec::with_capacity(req.assignments.len());
+    let mut values = Vec::with_capacity(req.assignments.len());
+    let mut seen = std::collections::HashSet::new();
+    for assignment in req.assignments.iter() {
+        let variant_id = parse_uuid(&assignment.variant_id, "variant_id")?;
+        let value = assignment.value.trim();
+        if value.is_empty() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "assignments.value is required",
+                )),
+            ));
+        }
+        if !seen.insert(variant_id) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "assignments contains duplicate variant_id",
+                )),
+            ));
+        }
+        variant_ids.push(variant_id);
+        values.push(value.to_string());
+    }
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let product = sqlx::query("SELECT id FROM products WHERE id = $1 AND tenant_id = $2 AND store_id = $3")
+        .bind(product_uuid.as_uuid())
+        .bind(tenant_uuid.as_uuid())
+        .bind(store_uuid.as_uuid())
+        .fetch_optional(tx.as_mut())
         .await
-    {
-        let product_id: String = row.get("product_id");
-        let _ = reindex_product_by_id(state, &product_id).await;
+        .map_err(db::error)?;
+    if product.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
+        ));
+    }
+    // Axis names are matched case-insensitively, as in create/update variant.
+    let axis_row = sqlx::query(
+        r#"
+        SELECT id, name
+        FROM product_variant_axes
+        WHERE product_id = $1 AND lower(name) = lower($2)
+        "#,
+    )
+    .bind(product_uuid.as_uuid())
+    .bind(axis_name)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    let Some(axis_row) = axis_row else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("axis {} is not defined for product", axis_name),
+            )),
+        ));
+    };
+    let axis_id: uuid::Uuid = axis_row.get("id");
+    let axis_name: String = axis_row.get("name");
+
+    let owned_count = sqlx::query(
+        r#"
+        SELECT COUNT(*) as count
+        FROM product_skus
+        WHERE product_id = $1 AND id = ANY($2)
+        "#,
+    )
+    .bind(product_uuid.as_uuid())
+    .bind(&variant_ids)
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(db::error)?
+    .get::<i64, _>("count");
+    if owned_count != variant_ids.len() as i64 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "assignments contains variants that do not belong to product",
+            )),
+        ));
     }
 
-    Ok(variant)
+    let updated = sqlx::query(
+        r#"
+        INSERT INTO variant_axis_values (id, variant_id, axis_id, value)
+        SELECT gen_random_uuid(), a.variant_id, $1, a.value
+        FROM UNNEST($2::uuid[], $3::text[]) AS a(variant_id, value)
+        ON CONFLICT (variant_id, axis_id) DO UPDATE SET value = EXCLUDED.value
+        "#,
+    )
+    .bind(axis_id)
+    .bind(&variant_ids)
+    .bind(&values)
+    .execute(tx.as_mut())
+    .await
+    .map_err(db::error)?
+    .rows_affected();
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                VariantAuditAction::BulkAxisUpdate,
+                Some("product"),
+                Some(req.product_id.clone()),
+                None,
+                Some(serde_json::json!({
+                    "axis_name": axis_name,
+                    "count": updated,
+                })),
+                actor,
+            ),
+        )
+        .await?;
+
+    outbox::enqueue_search_reindex_tx(&mut tx, &req.product_id).await?;
+    tx.commit().await.map_err(db::error)?;
+
+    Ok(updated as i32)
 }
 
 pub async fn set_inventory(
@@ -2043,22 +2905,43 @@ pub async fn set_inventory(
     if req.location_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "location_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "location_id is required")),
         ));
     }
     if req.sku_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id is required")),
         ));
     }
-    let (store_id, _tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
+    if req.on_hand < 0 || req.reserved < 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "on_hand and reserved must be >= 0",
+            )),
+        ));
+    }
+    if req.on_hand > MAX_INVENTORY_ON_HAND {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("on_hand must be <= {}", MAX_INVENTORY_ON_HAND),
+            )),
+        ));
+    }
+    if req.reserved > req.on_hand {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "reserved must not exceed on_hand",
+            )),
+        ));
+    }
+    let (store_id, tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
     let store_uuid = StoreId::parse(&store_id)?;
     ensure_variant_belongs_to_store(state, &req.sku_id, &store_id).await?;
     ensure_location_belongs_to_store(state, &req.location_id, &store_id).await?;
@@ -2066,6 +2949,12 @@ pub async fn set_inventory(
     let mut tx = state.db.begin().await.map_err(db::error)?;
     let sku_uuid = parse_uuid(&req.sku_id, "sku_id")?;
     let location_uuid = parse_uuid(&req.location_id, "location_id")?;
+    let product_uuid: uuid::Uuid = sqlx::query_scalar("SELECT product_id FROM product_skus WHERE id = $1")
+        .bind(sku_uuid)
+        .fetch_one(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    ensure_product_not_deleted_tx(&mut tx, product_uuid).await?;
     let row = sqlx::query(
         r#"
         SELECT on_hand, reserved
@@ -2138,38 +3027,49 @@ pub async fn set_inventory(
         updated_at: None,
     };
 
-    audit::record_tx(
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                InventoryAuditAction::Set,
+                Some("inventory"),
+                Some(inventory.sku_id.clone()),
+                None,
+                to_json_opt(Some(inventory.clone())),
+                _actor,
+            ),
+        )
+        .await?;
+
+    outbox::enqueue_tx(
         &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            InventoryAuditAction::Set.into(),
-            Some("inventory"),
-            Some(inventory.sku_id.clone()),
-            None,
-            to_json_opt(Some(inventory.clone())),
-            _actor,
-        ),
+        outbox::OutboxEventInput {
+            tenant_id,
+            store_id: Some(store_id.clone()),
+            aggregate_type: "inventory".to_string(),
+            aggregate_id: inventory.sku_id.clone(),
+            event_type: INVENTORY_STOCK_SET_EVENT_TYPE.to_string(),
+            payload_json: serde_json::json!({
+                "variant_id": inventory.sku_id,
+                "location_id": inventory.location_id,
+                "stock": inventory.on_hand,
+                "reserved": inventory.reserved,
+                "available": inventory.available,
+            }),
+        },
     )
     .await?;
 
+    let product_row = sqlx::query("SELECT product_id::text as product_id FROM product_skus WHERE id = $1")
+        .bind(parse_uuid(&inventory.sku_id, "sku_id")?)
+        .fetch_one(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    outbox::enqueue_search_reindex_tx(&mut tx, &product_row.get::<String, _>("product_id")).await?;
     tx.commit().await.map_err(db::error)?;
 
-    if let Ok(row) = sqlx::query(
-        r#"
-        SELECT p.id::text as id
-        FROM products p
-        JOIN product_skus v ON v.product_id = p.id
-        WHERE v.id = $1
-        "#,
-    )
-    .bind(parse_uuid(&inventory.sku_id, "sku_id")?)
-    .fetch_one(&state.db)
-    .await
-    {
-        let product_id: String = row.get("id");
-        let _ = reindex_product_by_id(state, &product_id).await;
-    }
-
     Ok(inventory)
 }
 
@@ -2189,11 +3089,12 @@ pub async fn list_inventory_stocks(
     } else {
         Some(parse_uuid(&req.location_id, "location_id")?)
     };
-    let (limit, offset) = inventory_page_params(req.page);
+    let (limit, cursor) = page_params(req.page, MAX_PAGE_SIZE)?;
 
     let mut builder = sqlx::QueryBuilder::new(
         r#"
-        SELECT sku_id::text as sku_id,
+        SELECT id as cursor_id,
+               sku_id::text as sku_id,
                location_id::text as location_id,
                on_hand,
                reserved,
@@ -2211,18 +3112,31 @@ pub async fn list_inventory_stocks(
         builder.push(" AND location_id = ");
         builder.push_bind(location_uuid);
     }
-    builder.push(" ORDER BY updated_at DESC, sku_id, location_id");
+    if let Some((cursor_updated_at, cursor_id)) = cursor {
+        builder.push(" AND (updated_at, id) < (");
+        builder.push_bind(cursor_updated_at);
+        builder.push(", ");
+        builder.push_bind(cursor_id);
+        builder.push(")");
+    }
+    builder.push(" ORDER BY updated_at DESC, id DESC");
     builder.push(" LIMIT ");
-    builder.push_bind(limit);
-    builder.push(" OFFSET ");
-    builder.push_bind(offset);
+    builder.push_bind(limit + 1);
 
-    let rows = builder
+    let mut rows = builder
         .build()
         .fetch_all(&state.db)
         .await
         .map_err(db::error)?;
 
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("updated_at"), last.get("cursor_id"));
+        }
+    }
+
     let inventories = rows
         .into_iter()
         .map(|row| {
@@ -2239,12 +3153,79 @@ pub async fn list_inventory_stocks(
         })
         .collect::<Vec<_>>();
 
+    Ok((inventories, pb::PageResult { next_page_token }))
+}
+
+pub async fn get_inventory_report(
+    state: &AppState,
+    req: pb::GetInventoryReportRequest,
+) -> Result<(Vec<pb::InventoryReportLine>, pb::PageResult), (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let location_uuid = if req.location_id.is_empty() {
+        None
+    } else {
+        Some(parse_uuid(&req.location_id, "location_id")?)
+    };
+    let (limit, offset) = inventory_page_params(req.page);
+
+    let mut builder = sqlx::QueryBuilder::new(
+        r#"
+        SELECT s.sku_id::text as variant_id,
+               v.sku,
+               p.title as product_title,
+               s.location_id::text as location_id,
+               l.name as location_name,
+               s.on_hand,
+               s.reserved
+        FROM inventory_stocks s
+        JOIN product_skus v ON v.id = s.sku_id
+        JOIN products p ON p.id = v.product_id
+        JOIN store_locations l ON l.id = s.location_id
+        WHERE s.store_id = 
+        "#,
+    );
+    builder.push_bind(store_uuid.as_uuid());
+    if let Some(location_uuid) = location_uuid {
+        builder.push(" AND s.location_id = ");
+        builder.push_bind(location_uuid);
+    }
+    if req.low_stock_only {
+        builder.push(" AND s.on_hand - s.reserved < ");
+        builder.push_bind(INVENTORY_LOW_STOCK_THRESHOLD);
+    }
+    builder.push(" ORDER BY l.name, p.title, v.sku, s.id");
+    builder.push(" LIMIT ");
+    builder.push_bind(limit);
+    builder.push(" OFFSET ");
+    builder.push_bind(offset);
+
+    let rows = builder.build().fetch_all(&state.db).await.map_err(db::error)?;
+
+    let lines = rows
+        .into_iter()
+        .map(|row| {
+            let stock: i32 = row.get("on_hand");
+            let reserved: i32 = row.get("reserved");
+            pb::InventoryReportLine {
+                variant_id: row.get("variant_id"),
+                sku: row.get("sku"),
+                product_title: row.get("product_title"),
+                location_id: row.get("location_id"),
+                location_name: row.get("location_name"),
+                stock,
+                reserved,
+                available: stock - reserved,
+            }
+        })
+        .collect::<Vec<_>>();
+
     let mut next_page_token = String::new();
-    if (inventories.len() as i64) == limit {
+    if (lines.len() as i64) == limit {
         next_page_token = (offset + limit).to_string();
     }
 
-    Ok((inventories, pb::PageResult { next_page_token }))
+    Ok((lines, pb::PageResult { next_page_token }))
 }
 
 pub async fn list_inventory_movements(
@@ -2264,11 +3245,12 @@ pub async fn list_inventory_movements(
         Some(parse_uuid(&req.location_id, "location_id")?)
     };
     let movement_type = req.movement_type.trim();
-    let (limit, offset) = inventory_page_params(req.page);
+    let (limit, cursor) = page_params(req.page, MAX_PAGE_SIZE)?;
 
     let mut builder = sqlx::QueryBuilder::new(
         r#"
         SELECT id::text as id,
+               id as cursor_id,
                sku_id::text as sku_id,
                location_id::text as location_id,
                movement_type,
@@ -2299,18 +3281,31 @@ pub async fn list_inventory_movements(
         builder.push(" AND movement_type = ");
         builder.push_bind(movement_type);
     }
+    if let Some((cursor_occurred_at, cursor_id)) = cursor {
+        builder.push(" AND (occurred_at, id) < (");
+        builder.push_bind(cursor_occurred_at);
+        builder.push(", ");
+        builder.push_bind(cursor_id);
+        builder.push(")");
+    }
     builder.push(" ORDER BY occurred_at DESC, id DESC");
     builder.push(" LIMIT ");
-    builder.push_bind(limit);
-    builder.push(" OFFSET ");
-    builder.push_bind(offset);
+    builder.push_bind(limit + 1);
 
-    let rows = builder
+    let mut rows = builder
         .build()
         .fetch_all(&state.db)
         .await
         .map_err(db::error)?;
 
+    let mut next_page_token = String::new();
+    if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        if let Some(last) = rows.last() {
+            next_page_token = encode_page_token(last.get("occurred_at"), last.get("cursor_id"));
+        }
+    }
+
     let movements = rows
         .into_iter()
         .map(|row| pb::InventoryMovement {
@@ -2331,11 +3326,6 @@ pub async fn list_inventory_movements(
         })
         .collect::<Vec<_>>();
 
-    let mut next_page_token = String::new();
-    if (movements.len() as i64) == limit {
-        next_page_token = (offset + limit).to_string();
-    }
-
     Ok((movements, pb::PageResult { next_page_token }))
 }
 
@@ -2347,28 +3337,19 @@ pub async fn adjust_inventory(
     if req.sku_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id is required")),
         ));
     }
     if req.location_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "location_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "location_id is required")),
         ));
     }
     if req.delta == 0 {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "delta must be non-zero".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "delta must be non-zero")),
         ));
     }
     let (store_id, _tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
@@ -2380,6 +3361,21 @@ pub async fn adjust_inventory(
     let location_uuid = parse_uuid(&req.location_id, "location_id")?;
 
     let mut tx = state.db.begin().await.map_err(db::error)?;
+    // The row is created first so FOR UPDATE always has something to lock; otherwise two first
+    // adjustments of the same SKU/location would both start from zero and one would be lost.
+    sqlx::query(
+        r#"
+        INSERT INTO inventory_stocks (store_id, sku_id, location_id, on_hand, reserved)
+        VALUES ($1,$2,$3,0,0)
+        ON CONFLICT (store_id, sku_id, location_id) DO NOTHING
+        "#,
+    )
+    .bind(store_uuid.as_uuid())
+    .bind(sku_uuid)
+    .bind(location_uuid)
+    .execute(tx.as_mut())
+    .await
+    .map_err(db::error)?;
     let row = sqlx::query(
         r#"
         SELECT on_hand, reserved
@@ -2391,39 +3387,51 @@ pub async fn adjust_inventory(
     .bind(store_uuid.as_uuid())
     .bind(sku_uuid)
     .bind(location_uuid)
-    .fetch_optional(tx.as_mut())
+    .fetch_one(tx.as_mut())
     .await
     .map_err(db::error)?;
 
-    let (on_hand, reserved) = if let Some(row) = row {
-        (row.get::<i32, _>("on_hand"), row.get::<i32, _>("reserved"))
-    } else {
-        (0, 0)
-    };
-    let new_on_hand = on_hand.saturating_add(req.delta);
+    let on_hand: i32 = row.get("on_hand");
+    let reserved: i32 = row.get("reserved");
+    let mut new_on_hand = on_hand.saturating_add(req.delta);
     if new_on_hand < 0 || new_on_hand < reserved {
+        if !req.allow_negative {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "inventory would be negative or below reserved",
+                )),
+            ));
+        }
+        // Reserved units are already promised to carts, so shrinkage cannot take them.
+        new_on_hand = reserved;
+    }
+    if new_on_hand > MAX_INVENTORY_ON_HAND {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "inventory would be negative or below reserved".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("on_hand must be <= {}", MAX_INVENTORY_ON_HAND),
+            )),
         ));
     }
+    let reason = match req.reason.trim() {
+        "" => "manual",
+        reason => reason,
+    };
 
     sqlx::query(
         r#"
-        INSERT INTO inventory_stocks (store_id, sku_id, location_id, on_hand, reserved)
-        VALUES ($1,$2,$3,$4,$5)
-        ON CONFLICT (store_id, sku_id, location_id)
-        DO UPDATE SET on_hand = EXCLUDED.on_hand, updated_at = now()
+        UPDATE inventory_stocks
+        SET on_hand = $4, updated_at = now()
+        WHERE store_id = $1 AND sku_id = $2 AND location_id = $3
         "#,
     )
     .bind(store_uuid.as_uuid())
     .bind(sku_uuid)
     .bind(location_uuid)
     .bind(new_on_hand)
-    .bind(reserved)
     .execute(tx.as_mut())
     .await
     .map_err(db::error)?;
@@ -2442,7 +3450,7 @@ pub async fn adjust_inventory(
             reserved,
             reserved,
             None,
-            Some("manual"),
+            Some(reason),
             None,
             actor_id,
         )
@@ -2469,37 +3477,34 @@ pub async fn transfer_inventory(
     if req.sku_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "sku_id is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "sku_id is required")),
         ));
     }
     if req.from_location_id.is_empty() || req.to_location_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "from_location_id and to_location_id are required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "from_location_id and to_location_id are required",
+            )),
         ));
     }
     if req.from_location_id == req.to_location_id {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "from_location_id and to_location_id must be different".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "from_location_id and to_location_id must be different",
+            )),
         ));
     }
     if req.quantity <= 0 {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "quantity must be positive".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "quantity must be positive",
+            )),
         ));
     }
     let (store_id, _tenant_id) = resolve_store_context(state, req.store.clone(), req.tenant.clone()).await?;
@@ -2536,10 +3541,10 @@ pub async fn transfer_inventory(
     if from_on_hand - from_reserved < req.quantity {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "insufficient available inventory".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "insufficient available inventory",
+            )),
         ));
     }
     let new_from_on_hand = from_on_hand - req.quantity;
@@ -2805,158 +3810,6 @@ async fn tenant_id_for_variant(state: &AppState, variant_id: &str) -> Result<Str
     Ok(row.get("tenant_id"))
 }
 
-async fn store_id_for_tenant(state: &AppState, tenant_id: &str) -> Result<String, (StatusCode, Json<ConnectError>)> {
-    let row = sqlx::query("SELECT id::text as id FROM stores WHERE tenant_id = $1 ORDER BY created_at ASC LIMIT 1")
-        .bind(parse_uuid(tenant_id, "tenant_id")?)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(db::error)?;
-    let Some(row) = row else {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "store not found for tenant".to_string(),
-            }),
-        ));
-    };
-    Ok(row.get("id"))
-}
-
-async fn resolve_store_context(
-    state: &AppState,
-    store: Option<pb::StoreContext>,
-    tenant: Option<pb::TenantContext>,
-) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
-    if let Some(ctx) = request_context::current() {
-        if let Some(auth_store) = ctx.store_id.as_deref()
-            && let Some(store_id) = store.as_ref().and_then(|s| {
-                if s.store_id.is_empty() {
-                    None
-                } else {
-                    Some(s.store_id.as_str())
-                }
-            })
-            && store_id != auth_store
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "store_id does not match token".to_string(),
-                }),
-            ));
-        }
-        if let Some(auth_tenant) = ctx.tenant_id.as_deref()
-            && let Some(tenant_id) = tenant.as_ref().and_then(|t| {
-                if t.tenant_id.is_empty() {
-                    None
-                } else {
-                    Some(t.tenant_id.as_str())
-                }
-            })
-            && tenant_id != auth_tenant
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "tenant_id does not match token".to_string(),
-                }),
-            ));
-        }
-    }
-
-    if let Some(store_id) = store.as_ref().and_then(|s| {
-        if s.store_id.is_empty() {
-            None
-        } else {
-            Some(s.store_id.as_str())
-        }
-    }) {
-        let store_uuid = StoreId::parse(store_id)?;
-        let row = sqlx::query("SELECT tenant_id::text as tenant_id FROM stores WHERE id = $1")
-            .bind(store_uuid.as_uuid())
-            .fetch_one(&state.db)
-            .await
-            .map_err(db::error)?;
-        let tenant_id: String = row.get("tenant_id");
-        return Ok((store_id.to_string(), tenant_id));
-    }
-    if let Some(store_code) = store.as_ref().and_then(|s| {
-        if s.store_code.is_empty() {
-            None
-        } else {
-            Some(s.store_code.as_str())
-        }
-    }) {
-        let row = sqlx::query("SELECT id::text as id, tenant_id::text as tenant_id FROM stores WHERE code = $1")
-            .bind(store_code)
-            .fetch_optional(&state.db)
-            .await
-            .map_err(db::error)?;
-        let Some(row) = row else {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_code not found".to_string(),
-                }),
-            ));
-        };
-        let store_id: String = row.get("id");
-        let tenant_id: String = row.get("tenant_id");
-        if let Some(ctx) = request_context::current()
-            && let Some(auth_store) = ctx.store_id
-            && auth_store != store_id
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "store_code does not match token".to_string(),
-                }),
-            ));
-        }
-        return Ok((store_id, tenant_id));
-    }
-    if let Some(tenant_id) = tenant.and_then(|t| {
-        if t.tenant_id.is_empty() {
-            None
-        } else {
-            Some(t.tenant_id)
-        }
-    }) {
-        let store_id = store_id_for_tenant(state, &tenant_id).await?;
-        return Ok((store_id, tenant_id));
-    }
-    if let Some(ctx) = request_context::current() {
-        if let Some(store_id) = ctx.store_id {
-            let store_uuid = StoreId::parse(&store_id)?;
-            let row = sqlx::query("SELECT tenant_id::text as tenant_id FROM stores WHERE id = $1")
-                .bind(store_uuid.as_uuid())
-                .fetch_optional(&state.db)
-                .await
-                .map_err(db::error)?;
-            if let Some(row) = row {
-                let tenant_id: String = row.get("tenant_id");
-                return Ok((store_id, tenant_id));
-            }
-        }
-        if let Some(tenant_id) = ctx.tenant_id {
-            let store_id = store_id_for_tenant(state, &tenant_id).await?;
-            return Ok((store_id, tenant_id));
-        }
-    }
-    Err((
-        StatusCode::BAD_REQUEST,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::InvalidArgument,
-            message: "store.store_id or tenant.tenant_id is required".to_string(),
-        }),
-    ))
-}
-
 async fn ensure_variant_belongs_to_store(
     state: &AppState,
     variant_id: &str,
@@ -2978,10 +3831,10 @@ async fn ensure_variant_belongs_to_store(
     if owner_store_id != store_id {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "variant does not belong to store".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "variant does not belong to store",
+            )),
         ));
     }
     Ok(())
@@ -3001,29 +3854,131 @@ async fn ensure_location_belongs_to_store(
     if owner_store_id != store_id {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "location does not belong to store".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "location does not belong to store",
+            )),
         ));
     }
     Ok(())
 }
 
-async fn reindex_product_by_id(state: &AppState, product_id: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
+const SEARCH_REINDEX_BACKOFF_BASE_SECONDS: i64 = 5;
+const SEARCH_REINDEX_BACKOFF_MAX_SECONDS: i64 = 3600;
+
+// Delay before the next try after `attempts` failures: 5s, 10s, 20s, ... capped at an hour.
+fn search_reindex_backoff_seconds(attempts: i32) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    SEARCH_REINDEX_BACKOFF_BASE_SECONDS
+        .saturating_mul(1_i64 << exponent)
+        .min(SEARCH_REINDEX_BACKOFF_MAX_SECONDS)
+}
+
+// Drains due rows of search_reindex_needed; several pending rows for one product collapse into a
+// single reindex. Claimed rows stay locked (SKIP LOCKED for other workers) and are deleted only after
+// the search backend accepted the document, in the same transaction. Failures bump `attempts` and
+// push `next_attempt_at` back; rows that reach `max_attempts` are left in place and no longer claimed.
+pub async fn process_search_reindex_queue(
+    state: &AppState,
+    batch_size: i64,
+    max_attempts: i32,
+) -> Result<usize, (StatusCode, Json<ConnectError>)> {
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let rows = sqlx::query(
+        r#"
+        SELECT id, product_id, attempts
+        FROM search_reindex_needed
+        WHERE next_attempt_at <= now() AND attempts < $2
+        ORDER BY next_attempt_at ASC, created_at ASC
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(batch_size)
+    .bind(max_attempts)
+    .fetch_all(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    let mut claimed: std::collections::BTreeMap<uuid::Uuid, (Vec<uuid::Uuid>, i32)> = std::collections::BTreeMap::new();
+    for row in rows {
+        let entry = claimed.entry(row.get("product_id")).or_default();
+        entry.0.push(row.get("id"));
+        entry.1 = entry.1.max(row.get("attempts"));
+    }
+
+    let mut done = 0;
+    for (product_id, (row_ids, attempts)) in &claimed {
+        match reindex_product_by_id(state, &product_id.to_string()).await {
+            Ok(()) => {
+                sqlx::query("DELETE FROM search_reindex_needed WHERE id = ANY($1)")
+                    .bind(row_ids)
+                    .execute(tx.as_mut())
+                    .await
+                    .map_err(db::error)?;
+                done += 1;
+            }
+            Err((_, err)) => {
+                let attempts = attempts + 1;
+                if attempts >= max_attempts {
+                    tracing::error!(
+                        product_id = %product_id,
+                        attempts,
+                        error = %err.message,
+                        "search reindex gave up"
+                    );
+                } else {
+                    tracing::warn!(product_id = %product_id, attempts, error = %err.message, "search reindex failed");
+                }
+                sqlx::query(
+                    r#"
+                    UPDATE search_reindex_needed
+                    SET attempts = $2,
+                        next_attempt_at = now() + make_interval(secs => $3),
+                        last_error = $4
+                    WHERE id = ANY($1)
+                    "#,
+                )
+                .bind(row_ids)
+                .bind(attempts)
+                .bind(search_reindex_backoff_seconds(attempts) as f64)
+                .bind(&err.message)
+                .execute(tx.as_mut())
+                .await
+                .map_err(db::error)?;
+            }
+        }
+    }
+    tx.commit().await.map_err(db::error)?;
+    Ok(done)
+}
+
+pub async fn reindex_product_by_id(state: &AppState, product_id: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
     let row = sqlx::query(
         r#"
         SELECT id::text as id, tenant_id::text as tenant_id, store_id::text as store_id,
                vendor_id::text as vendor_id,
-               title, description, status
+               title, description, status,
+               EXTRACT(EPOCH FROM created_at)::bigint as created_at_unix,
+               COALESCE((
+                   SELECT MIN(price_amount)
+                   FROM product_skus
+                   WHERE product_id = products.id AND status = 'active'
+               ), 0)::bigint as price_amount
         FROM products
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(parse_uuid(product_id, "product_id")?)
-    .fetch_one(&state.db)
+    .fetch_optional(&state.db)
     .await
     .map_err(db::error)?;
+    // Deleted products (soft or hard) drop out of the index.
+    let Some(row) = row else {
+        return state.search.delete_product(product_id).await;
+    };
 
     let category_rows = sqlx::query(
         r#"
@@ -3063,6 +4018,7 @@ async fn reindex_product_by_id(state: &AppState, product_id: &str) -> Result<(),
         .into_iter()
         .map(|row| row.get::<String, _>("sku"))
         .collect::<Vec<_>>();
+    let translations = translation::search_fields(state, parse_uuid(product_id, "product_id")?).await?;
 
     state
         .search
@@ -3077,7 +4033,942 @@ async fn reindex_product_by_id(state: &AppState, product_id: &str) -> Result<(),
             primary_category_id,
             category_ids,
             sku_codes,
+            translations,
+            price_amount: row.get("price_amount"),
+            created_at_unix: row.get("created_at_unix"),
         }])
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    #[test]
+    fn reindex_backoff_doubles_up_to_an_hour() {
+        assert_eq!(search_reindex_backoff_seconds(1), 5);
+        assert_eq!(search_reindex_backoff_seconds(2), 10);
+        assert_eq!(search_reindex_backoff_seconds(4), 40);
+        assert_eq!(search_reindex_backoff_seconds(30), 3600);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn reindex_queue_skips_rows_not_due_or_at_the_cap(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let due = uuid::Uuid::new_v4();
+        let later = uuid::Uuid::new_v4();
+        let exhausted = uuid::Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO search_reindex_needed (product_id, attempts, next_attempt_at)
+            VALUES ($1, 0, now()), ($1, 2, now()), ($2, 1, now() + interval '1 hour'), ($3, 3, now())
+            "#,
+        )
+        .bind(due)
+        .bind(later)
+        .bind(exhausted)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(process_search_reindex_queue(&state, 100, 3).await.unwrap(), 1);
+
+        let remaining: Vec<uuid::Uuid> =
+            sqlx::query_scalar("SELECT product_id FROM search_reindex_needed ORDER BY attempts")
+                .fetch_all(&db)
+                .await
+                .unwrap();
+        assert_eq!(remaining, vec![later, exhausted]);
+        assert_eq!(process_search_reindex_queue(&state, 100, 3).await.unwrap(), 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn deleted_products_reject_updates_variants_and_stock(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let product_id = test_support::create_product(&db, &store, "Retired", "active").await;
+        let sku_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+            VALUES ($1, 'RETIRED-1', 1000, 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(product_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE products SET status = 'deleted', deleted_at = now() WHERE id = $1")
+            .bind(product_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let (_, Json(err)) = update_product(
+            &state,
+            &request_context::RequestContext::default(),
+            pb::UpdateProductRequest {
+                store: store.store_context(),
+                product_id: product_id.to_string(),
+                title: "Back again".to_string(),
+                status: "draft".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .expect_err("deleted product should not be updated");
+        assert!(matches!(err.code, ErrorCode::NotFound), "{err:?}");
+
+        let (_, Json(err)) = create_variant(
+            &state,
+            pb::CreateVariantRequest {
+                product_id: product_id.to_string(),
+                sku: "RETIRED-2".to_string(),
+                fulfillment_type: "physical".to_string(),
+                price: Some(pb::Money {
+                    amount: 1000,
+                    currency: "JPY".to_string(),
+                }),
+                status: "active".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .expect_err("deleted product should not get variants");
+        assert!(matches!(err.code, ErrorCode::FailedPrecondition), "{err:?}");
+
+        let location_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO store_locations (tenant_id, store_id, code, name, status)
+            VALUES ($1, $2, 'main', 'Main', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let (_, Json(err)) = set_inventory(
+            &state,
+            pb::SetInventoryRequest {
+                store: store.store_context(),
+                sku_id: sku_id.to_string(),
+                location_id: location_id.to_string(),
+                on_hand: 5,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .expect_err("deleted product should not get stock");
+        assert!(matches!(err.code, ErrorCode::FailedPrecondition), "{err:?}");
+    }
+
+    async fn category_with_products(db: &PgPool, store: &test_support::TestStore, statuses: &[&str]) -> String {
+        let category_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO product_categories (tenant_id, store_id, name, slug)
+            VALUES ($1, $2, 'Shoes', 'shoes')
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .fetch_one(db)
+        .await
+        .unwrap();
+        for (position, status) in statuses.iter().enumerate() {
+            let product_id = test_support::create_product(db, store, &format!("Product {position}"), status).await;
+            sqlx::query("INSERT INTO product_category_links (product_id, category_id, position) VALUES ($1, $2, $3)")
+                .bind(product_id)
+                .bind(category_id)
+                .bind(position as i32)
+                .execute(db)
+                .await
+                .unwrap();
+        }
+        category_id.to_string()
+    }
+
+    fn page(page_size: i32, page_token: &str) -> Option<pb::PageInfo> {
+        Some(pb::PageInfo {
+            page_size,
+            page_token: page_token.to_string(),
+        })
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn category_products_are_paged_by_position(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let category_id = category_with_products(&db, &store, &["active"; 5]).await;
+
+        let (first, first_page, total) = list_category_products_admin(
+            &state,
+            store.store_context(),
+            category_id.clone(),
+            String::new(),
+            page(2, ""),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].title, "Product 0");
+        assert_eq!(total, 5);
+        assert!(!first_page.next_page_token.is_empty());
+
+        let (second, second_page, _) = list_category_products_admin(
+            &state,
+            store.store_context(),
+            category_id.clone(),
+            String::new(),
+            page(2, &first_page.next_page_token),
+        )
+        .await
+        .unwrap();
+        let (last, last_page, _) = list_category_products_admin(
+            &state,
+            store.store_context(),
+            category_id.clone(),
+            String::new(),
+            page(2, &second_page.next_page_token),
+        )
+        .await
+        .unwrap();
+        assert_eq!(second[0].title, "Product 2");
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].title, "Product 4");
+        assert!(last_page.next_page_token.is_empty());
+
+        let (all, all_page, _) =
+            list_category_products_admin(&state, store.store_context(), category_id, String::new(), None)
+                .await
+                .unwrap();
+        assert_eq!(all.len(), 5);
+        assert!(all_page.next_page_token.is_empty());
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn empty_category_has_no_products(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let category_id = category_with_products(&db, &store, &[]).await;
+
+        let (products, page_result, total) =
+            list_category_products_admin(&state, store.store_context(), category_id, String::new(), page(2, ""))
+                .await
+                .unwrap();
+        assert!(products.is_empty());
+        assert!(page_result.next_page_token.is_empty());
+        assert_eq!(total, 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn category_total_count_follows_the_status_filter(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let category_id = category_with_products(&db, &store, &["active", "draft", "active", "inactive"]).await;
+
+        let (products, _, total) = list_category_products_admin(
+            &state,
+            store.store_context(),
+            category_id.clone(),
+            "active".into(),
+            page(1, ""),
+        )
+        .await
+        .unwrap();
+        assert_eq!(products.len(), 1);
+        assert_eq!(total, 2);
+
+        let err = list_category_products_admin(&state, store.store_context(), category_id, "archived".into(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err.1.code, ErrorCode::InvalidArgument));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn delete_product_records_through_the_audit_writer(db: PgPool) {
+        let recorder = test_support::RecordingAuditWriter::default();
+        let mut state = test_support::app_state(db.clone());
+        state.audit_writer = std::sync::Arc::new(recorder.clone());
+        let store = test_support::create_store(&db).await;
+        let product_id = test_support::create_product(&db, &store, "Shirt", "active").await;
+
+        delete_product(
+            &state,
+            pb::DeleteProductRequest {
+                store: store.store_context(),
+                product_id: product_id.to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let events = recorder.events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].action,
+            crate::shared::audit_action::AuditAction::ProductDelete
+        );
+        assert_eq!(events[0].target_id.as_deref(), Some(product_id.to_string().as_str()));
+        let logged: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs")
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(logged, 0);
+    }
+
+    fn active_product_request(store: &test_support::TestStore, price_amount: i64) -> pb::CreateProductRequest {
+        pb::CreateProductRequest {
+            store: store.store_context(),
+            title: "Tote bag".to_string(),
+            status: "active".to_string(),
+            default_variant: Some(pb::DefaultVariantInput {
+                sku: "TOTE-1".to_string(),
+                fulfillment_type: "physical".to_string(),
+                price: Some(pb::Money {
+                    amount: price_amount,
+                    currency: "JPY".to_string(),
+                }),
+                status: "active".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn active_product_without_a_price_is_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let ctx = request_context::RequestContext::default();
+
+        let (_, Json(err)) = create_product(&state, &ctx, active_product_request(&store, 0), None)
+            .await
+            .expect_err("active product without a price should be rejected");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        assert!(
+            err.details
+                .iter()
+                .any(|violation| violation.field == "default_variant.price")
+        );
+
+        // Activating an unpriced draft through update_product is rejected the same way.
+        let product_id = test_support::create_product(&db, &store, "Draft", "draft").await;
+        let (_, Json(err)) = update_product(
+            &state,
+            &ctx,
+            pb::UpdateProductRequest {
+                store: store.store_context(),
+                product_id: product_id.to_string(),
+                title: "Draft".to_string(),
+                status: "active".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .expect_err("unpriced product should not be activated");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn active_product_with_a_price_is_created(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let ctx = request_context::RequestContext::default();
+
+        let (product, warnings) = create_product(&state, &ctx, active_product_request(&store, 1200), None)
+            .await
+            .unwrap();
+        assert_eq!(product.status, "active");
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let price: i64 = sqlx::query_scalar("SELECT price_amount FROM product_skus WHERE product_id = $1::uuid")
+            .bind(&product.id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(price, 1200);
+    }
+
+    async fn create_sku(db: &PgPool, product_id: uuid::Uuid, sku: &str) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+            VALUES ($1, $2, 1000, 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(product_id)
+        .bind(sku)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    async fn create_location(db: &PgPool, store: &test_support::TestStore, code: &str) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO store_locations (tenant_id, store_id, code, name, status)
+            VALUES ($1, $2, $3, $3, 'active')
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .bind(code)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    /// A store with one SKU and one location and no stock row yet.
+    async fn stock_fixture(db: &PgPool) -> (test_support::TestStore, uuid::Uuid, uuid::Uuid) {
+        let store = test_support::create_store(db).await;
+        let product_id = test_support::create_product(db, &store, "Mug", "active").await;
+        let sku_id = create_sku(db, product_id, "MUG-1").await;
+        let location_id = create_location(db, &store, "main").await;
+        (store, sku_id, location_id)
+    }
+
+    fn adjust_request(
+        store: &test_support::TestStore,
+        sku_id: uuid::Uuid,
+        location_id: uuid::Uuid,
+        delta: i32,
+        allow_negative: bool,
+    ) -> pb::AdjustInventoryRequest {
+        pb::AdjustInventoryRequest {
+            store: store.store_context(),
+            sku_id: sku_id.to_string(),
+            location_id: location_id.to_string(),
+            delta,
+            allow_negative,
+            ..Default::default()
+        }
+    }
+
+    async fn stock_quantities(db: &PgPool, sku_id: uuid::Uuid, location_id: uuid::Uuid) -> Option<(i32, i32)> {
+        sqlx::query_as("SELECT on_hand, reserved FROM inventory_stocks WHERE sku_id = $1 AND location_id = $2")
+            .bind(sku_id)
+            .bind(location_id)
+            .fetch_optional(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn concurrent_first_adjustments_are_all_applied(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, sku_id, location_id) = stock_fixture(&db).await;
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let state = state.clone();
+                let req = adjust_request(&store, sku_id, location_id, 3, false);
+                tokio::spawn(async move { adjust_inventory(&state, req, None).await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(stock_quantities(&db, sku_id, location_id).await, Some((24, 0)));
+        let movements: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM inventory_movements WHERE sku_id = $1")
+            .bind(sku_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(movements, 8);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn negative_delta_below_zero_or_reserved_is_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, sku_id, location_id) = stock_fixture(&db).await;
+
+        // A first adjustment that would go negative leaves no stock row behind.
+        let (_, Json(err)) = adjust_inventory(&state, adjust_request(&store, sku_id, location_id, -1, false), None)
+            .await
+            .expect_err("stock should not go negative");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        assert_eq!(stock_quantities(&db, sku_id, location_id).await, None);
+
+        adjust_inventory(&state, adjust_request(&store, sku_id, location_id, 5, false), None)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE inventory_stocks SET reserved = 2 WHERE sku_id = $1")
+            .bind(sku_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let (_, Json(err)) = adjust_inventory(&state, adjust_request(&store, sku_id, location_id, -4, false), None)
+            .await
+            .expect_err("stock should not drop below reserved");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        assert_eq!(stock_quantities(&db, sku_id, location_id).await, Some((5, 2)));
+
+        // Shrinkage floors on_hand at the reserved units.
+        let inventory = adjust_inventory(&state, adjust_request(&store, sku_id, location_id, -4, true), None)
+            .await
+            .unwrap();
+        assert_eq!((inventory.on_hand, inventory.available), (2, 0));
+        assert_eq!(stock_quantities(&db, sku_id, location_id).await, Some((2, 2)));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn set_inventory_emits_a_stock_set_event(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, sku_id, location_id) = stock_fixture(&db).await;
+
+        let inventory = set_inventory(
+            &state,
+            pb::SetInventoryRequest {
+                store: store.store_context(),
+                sku_id: sku_id.to_string(),
+                location_id: location_id.to_string(),
+                on_hand: 10,
+                reserved: 4,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(inventory.available, 6);
+
+        let events: Vec<(String, Option<uuid::Uuid>, String, serde_json::Value)> = sqlx::query_as(
+            r#"
+            SELECT status, store_id, aggregate_id, payload_json
+            FROM outbox_events
+            WHERE tenant_id = $1 AND event_type = $2
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(INVENTORY_STOCK_SET_EVENT_TYPE)
+        .fetch_all(&db)
+        .await
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![(
+                "pending".to_string(),
+                Some(store.store_id),
+                sku_id.to_string(),
+                serde_json::json!({
+                    "variant_id": sku_id.to_string(),
+                    "location_id": location_id.to_string(),
+                    "stock": 10,
+                    "reserved": 4,
+                    "available": 6,
+                }),
+            )]
+        );
+    }
+
+    fn set_request(
+        store: &test_support::TestStore,
+        sku_id: uuid::Uuid,
+        location_id: uuid::Uuid,
+        on_hand: i32,
+        reserved: i32,
+    ) -> pb::SetInventoryRequest {
+        pb::SetInventoryRequest {
+            store: store.store_context(),
+            sku_id: sku_id.to_string(),
+            location_id: location_id.to_string(),
+            on_hand,
+            reserved,
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn set_inventory_rejects_out_of_range_quantities(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let (store, sku_id, location_id) = stock_fixture(&db).await;
+
+        for (on_hand, reserved) in [(-1, 0), (5, -1), (MAX_INVENTORY_ON_HAND + 1, 0), (3, 4)] {
+            let (_, Json(err)) = set_inventory(
+                &state,
+                set_request(&store, sku_id, location_id, on_hand, reserved),
+                None,
+            )
+            .await
+            .expect_err("quantities should be rejected");
+            assert!(
+                matches!(err.code, ErrorCode::InvalidArgument),
+                "{on_hand}/{reserved}: {err:?}"
+            );
+        }
+        assert_eq!(stock_quantities(&db, sku_id, location_id).await, None);
+
+        set_inventory(
+            &state,
+            set_request(
+                &store,
+                sku_id,
+                location_id,
+                MAX_INVENTORY_ON_HAND,
+                MAX_INVENTORY_ON_HAND,
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            stock_quantities(&db, sku_id, location_id).await,
+            Some((MAX_INVENTORY_ON_HAND, MAX_INVENTORY_ON_HAND))
+        );
+
+        // The check constraint backs the same bounds for writes that bypass the service.
+        let err = sqlx::query("UPDATE inventory_stocks SET reserved = on_hand + 1 WHERE sku_id = $1")
+            .bind(sku_id)
+            .execute(&db)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.as_database_error().and_then(|err| err.constraint()),
+            Some("inventory_stocks_quantities_check")
+        );
+    }
+
+    fn variant_request(product_id: uuid::Uuid, sku: &str) -> pb::CreateVariantRequest {
+        pb::CreateVariantRequest {
+            product_id: product_id.to_string(),
+            sku: sku.to_string(),
+            fulfillment_type: "physical".to_string(),
+            price: Some(pb::Money {
+                amount: 1000,
+                currency: "JPY".to_string(),
+            }),
+            status: "active".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn sku_must_be_unique_within_the_store(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let mug = test_support::create_product(&db, &store, "Mug", "active").await;
+        create_sku(&db, mug, "SHARED-1").await;
+        let cup = test_support::create_product(&db, &store, "Cup", "active").await;
+
+        let (status, Json(err)) = create_variant(&state, variant_request(cup, "SHARED-1"), None)
+            .await
+            .expect_err("sku is already used by another product in the store");
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(matches!(err.code, ErrorCode::AlreadyExists), "{err:?}");
+
+        create_variant(&state, variant_request(cup, "SHARED-2"), None)
+            .await
+            .unwrap();
+
+        // Another store may reuse the code.
+        let other = test_support::create_store(&db).await;
+        let other_mug = test_support::create_product(&db, &other, "Mug", "active").await;
+        create_variant(&state, variant_request(other_mug, "SHARED-1"), None)
+            .await
+            .unwrap();
+    }
+
+    async fn create_category(
+        db: &PgPool,
+        store: &test_support::TestStore,
+        slug: &str,
+        parent_id: Option<uuid::Uuid>,
+    ) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO product_categories (tenant_id, store_id, parent_id, name, slug)
+            VALUES ($1, $2, $3, $4, $4)
+            RETURNING id
+            "#,
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .bind(parent_id)
+        .bind(slug)
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    fn reorder_request(
+        store: &test_support::TestStore,
+        parent_id: Option<uuid::Uuid>,
+        ordered_ids: &[uuid::Uuid],
+    ) -> pb::ReorderCategoriesRequest {
+        pb::ReorderCategoriesRequest {
+            store: store.store_context(),
+            parent_id: parent_id.map(|id| id.to_string()).unwrap_or_default(),
+            ordered_ids: ordered_ids.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn reorder_requires_exactly_the_parents_children(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let shoes = create_category(&db, &store, "shoes", None).await;
+        let bags = create_category(&db, &store, "bags", None).await;
+        let boots = create_category(&db, &store, "boots", Some(shoes)).await;
+
+        for ordered_ids in [vec![bags, shoes, boots], vec![bags], vec![bags, bags]] {
+            let (_, Json(err)) = reorder_categories(&state, reorder_request(&store, None, &ordered_ids), None)
+                .await
+                .expect_err("ordered_ids must be the root categories");
+            assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        }
+        let (_, Json(err)) = reorder_categories(&state, reorder_request(&store, Some(shoes), &[bags]), None)
+            .await
+            .expect_err("bags is not under shoes");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+
+        let roots = reorder_categories(&state, reorder_request(&store, None, &[bags, shoes]), None)
+            .await
+            .unwrap();
+        let order: Vec<_> = roots.iter().map(|category| category.slug.as_str()).collect();
+        assert_eq!(order, ["bags", "shoes"]);
+        let children = reorder_categories(&state, reorder_request(&store, Some(shoes), &[boots]), None)
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 1);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn variants_are_paged_and_filtered_by_status(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let product_id = test_support::create_product(&db, &store, "Mug", "active").await;
+        for n in 1..=5 {
+            create_sku(&db, product_id, &format!("MUG-{n}")).await;
+        }
+        sqlx::query("UPDATE product_skus SET status = 'inactive' WHERE sku = 'MUG-3'")
+            .execute(&db)
+            .await
+            .unwrap();
+        let list = |status: &str, page_info: Option<pb::PageInfo>| {
+            list_variants_admin(
+                &state,
+                None,
+                store.store_context(),
+                product_id.to_string(),
+                status.to_string(),
+                page_info,
+            )
+        };
+
+        let mut skus = Vec::new();
+        let mut page_token = String::new();
+        loop {
+            let (variants, _, page_result) = list("", page(2, &page_token)).await.unwrap();
+            assert!(variants.len() <= 2);
+            skus.extend(variants.into_iter().map(|variant| variant.sku));
+            if page_result.next_page_token.is_empty() {
+                break;
+            }
+            page_token = page_result.next_page_token;
+        }
+        skus.sort();
+        assert_eq!(skus, ["MUG-1", "MUG-2", "MUG-3", "MUG-4", "MUG-5"]);
+
+        let (inactive, _, page_result) = list("inactive", None).await.unwrap();
+        let inactive: Vec<_> = inactive.into_iter().map(|variant| variant.sku).collect();
+        assert_eq!(inactive, ["MUG-3"]);
+        assert!(page_result.next_page_token.is_empty());
+
+        let (_, Json(err)) = list("archived", None).await.expect_err("unknown variant status");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+    }
+
+    async fn create_vendor(db: &PgPool, tenant_id: uuid::Uuid) -> uuid::Uuid {
+        sqlx::query_scalar("INSERT INTO vendors (tenant_id, name, status) VALUES ($1, 'Vendor', 'active') RETURNING id")
+            .bind(tenant_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn vendor_must_belong_to_the_stores_tenant(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let other = test_support::create_store(&db).await;
+        let ctx = request_context::RequestContext::default();
+        let with_vendor = |vendor_id: uuid::Uuid| pb::CreateProductRequest {
+            vendor_id: vendor_id.to_string(),
+            ..active_product_request(&store, 1200)
+        };
+
+        let foreign_vendor = create_vendor(&db, other.tenant_id).await;
+        let (_, Json(err)) = create_product(&state, &ctx, with_vendor(foreign_vendor), None)
+            .await
+            .expect_err("another tenant's vendor should be rejected");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        assert_eq!(err.message, "vendor_id not found");
+        let products: i64 = sqlx::query_scalar("SELECT count(*) FROM products WHERE store_id = $1")
+            .bind(store.store_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(products, 0);
+
+        let vendor = create_vendor(&db, store.tenant_id).await;
+        let (product, _) = create_product(&state, &ctx, with_vendor(vendor), None).await.unwrap();
+        assert_eq!(product.vendor_id, vendor.to_string());
+        let listed = list_products(&state, store.tenant_id.to_string()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+
+        // The storefront hides products once their vendor is no longer active.
+        sqlx::query("UPDATE vendors SET status = 'inactive' WHERE id = $1")
+            .bind(vendor)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert!(
+            list_products(&state, store.tenant_id.to_string())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    async fn product_events(db: &PgPool, store: &test_support::TestStore) -> Vec<(String, String)> {
+        sqlx::query_as(
+            r#"
+            SELECT event_type, aggregate_id
+            FROM outbox_events
+            WHERE store_id = $1 AND aggregate_type = 'product'
+            ORDER BY created_at, event_type
+            "#,
+        )
+        .bind(store.store_id)
+        .fetch_all(db)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn product_writes_enqueue_outbox_events(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let ctx = request_context::RequestContext::default();
+
+        let (product, _) = create_product(&state, &ctx, active_product_request(&store, 1200), None)
+            .await
+            .unwrap();
+        update_product(
+            &state,
+            &ctx,
+            pb::UpdateProductRequest {
+                store: store.store_context(),
+                product_id: product.id.clone(),
+                title: "Tote bag (large)".to_string(),
+                status: "active".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            product_events(&db, &store).await,
+            [
+                ("product.created".to_string(), product.id.clone()),
+                ("product.updated".to_string(), product.id.clone()),
+            ]
+        );
+
+        // A rejected write rolls back without leaving an event behind.
+        let foreign_vendor = create_vendor(&db, test_support::create_store(&db).await.tenant_id).await;
+        let rejected = pb::CreateProductRequest {
+            vendor_id: foreign_vendor.to_string(),
+            ..active_product_request(&store, 1200)
+        };
+        create_product(&state, &ctx, rejected, None)
+            .await
+            .expect_err("another tenant's vendor should be rejected");
+        assert_eq!(product_events(&db, &store).await.len(), 2);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn admin_products_are_paged_newest_first(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        for i in 0..5 {
+            test_support::create_product(&db, &store, &format!("Product {i}"), "draft").await;
+        }
+        let other = test_support::create_store(&db).await;
+        test_support::create_product(&db, &other, "Elsewhere", "draft").await;
+        let ctx = request_context::RequestContext::default();
+        let list = |page: Option<pb::PageInfo>| list_products_admin(&state, &ctx, None, store.store_context(), page);
+        let titles = |products: &[pb::ProductAdmin]| products.iter().map(|p| p.title.clone()).collect::<Vec<_>>();
+
+        let (first, first_page) = list(page(2, "")).await.unwrap();
+        assert_eq!(titles(&first), ["Product 4", "Product 3"]);
+        let (middle, middle_page) = list(page(2, &first_page.next_page_token)).await.unwrap();
+        assert_eq!(titles(&middle), ["Product 2", "Product 1"]);
+        let (last, last_page) = list(page(2, &middle_page.next_page_token)).await.unwrap();
+        assert_eq!(titles(&last), ["Product 0"]);
+        assert!(last_page.next_page_token.is_empty());
+
+        let (_, Json(err)) = list(page(2, "not-a-token")).await.expect_err("invalid page token");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+    }
+
+    async fn create_product_on_sale(
+        db: &PgPool,
+        store: &test_support::TestStore,
+        title: &str,
+        start_days: i64,
+        end_days: i64,
+    ) -> uuid::Uuid {
+        let product_id = test_support::create_product(db, store, title, "active").await;
+        sqlx::query("UPDATE products SET sale_start_at = $2, sale_end_at = $3 WHERE id = $1")
+            .bind(product_id)
+            .bind(Utc::now() + chrono::Duration::days(start_days))
+            .bind(Utc::now() + chrono::Duration::days(end_days))
+            .execute(db)
+            .await
+            .unwrap();
+        product_id
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn storefront_only_shows_products_inside_their_sale_window(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let before = create_product_on_sale(&db, &store, "Before", 1, 2).await;
+        let during = create_product_on_sale(&db, &store, "During", -1, 1).await;
+        let after = create_product_on_sale(&db, &store, "After", -2, -1).await;
+        test_support::create_product(&db, &store, "Always", "active").await;
+
+        let listed = list_products(&state, store.tenant_id.to_string()).await.unwrap();
+        let mut on_sale = listed
+            .iter()
+            .map(|product| (product.title.as_str(), product.is_on_sale))
+            .collect::<Vec<_>>();
+        on_sale.sort();
+        assert_eq!(on_sale, [("Always", false), ("During", true)]);
+
+        let get = |product_id: uuid::Uuid| {
+            get_product(&state, store.tenant_id.to_string(), product_id.to_string(), Vec::new())
+        };
+        assert!(get(before).await.unwrap().is_none());
+        assert!(get(during).await.unwrap().unwrap().is_on_sale);
+        assert!(get(after).await.unwrap().is_none());
+    }
+}
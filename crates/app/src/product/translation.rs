@@ -0,0 +1,285 @@
+use axum::{Json, http::StatusCode};
+use chrono::Utc;
+use sqlx::Row;
+use std::collections::BTreeMap;
+
+use crate::{
+    AppState,
+    infrastructure::{db, outbox},
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+    shared::{
+        audit_action::ProductAuditAction,
+        audit_helpers::{audit_input, to_json_opt},
+        ids::parse_uuid,
+        store_context::resolve_store_context,
+        time::chrono_to_timestamp,
+    },
+};
+
+async fn ensure_product_belongs_to_store(
+    state: &AppState,
+    product_uuid: uuid::Uuid,
+    store_uuid: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let exists = sqlx::query("SELECT 1 FROM products WHERE id = $1 AND store_id = $2")
+        .bind(product_uuid)
+        .bind(store_uuid)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    if exists.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "product not found")),
+        ));
+    }
+    Ok(())
+}
+
+pub fn normalize_locale(locale: &str) -> Result<String, (StatusCode, Json<ConnectError>)> {
+    let normalized = locale.trim().replace('_', "-").to_ascii_lowercase();
+    let valid = (2..=35).contains(&normalized.len())
+        && normalized.split('-').all(|part| {
+            !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if !valid {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "locale is invalid")),
+        ));
+    }
+    Ok(normalized)
+}
+
+pub fn preferred_locales(accept_language: &str) -> Vec<String> {
+    let mut weighted = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            if tag.is_empty() || tag == "*" {
+                return None;
+            }
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                return None;
+            }
+            let locale = normalize_locale(tag).ok()?;
+            Some((locale, quality))
+        })
+        .collect::<Vec<_>>();
+    weighted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut locales = Vec::new();
+    for (locale, _) in weighted {
+        let primary = locale.split('-').next().unwrap_or_default().to_string();
+        for candidate in [locale, primary] {
+            if !locales.contains(&candidate) {
+                locales.push(candidate);
+            }
+        }
+    }
+    locales
+}
+
+pub async fn find_translation(
+    state: &AppState,
+    product_uuid: uuid::Uuid,
+    locales: &[String],
+) -> Result<Option<(String, String)>, (StatusCode, Json<ConnectError>)> {
+    if locales.is_empty() {
+        return Ok(None);
+    }
+    let row = sqlx::query(
+        r#"
+        SELECT title, description
+        FROM product_translations
+        WHERE product_id = $1 AND locale = ANY($2)
+        ORDER BY array_position($2, locale)
+        LIMIT 1
+        "#,
+    )
+    .bind(product_uuid)
+    .bind(locales)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(db::error)?;
+    Ok(row.map(|row| (row.get("title"), row.get("description"))))
+}
+
+pub async fn search_fields(
+    state: &AppState,
+    product_uuid: uuid::Uuid,
+) -> Result<BTreeMap<String, String>, (StatusCode, Json<ConnectError>)> {
+    let rows = sqlx::query(
+        r#"
+        SELECT locale, title, description
+        FROM product_translations
+        WHERE product_id = $1
+        ORDER BY locale ASC
+        "#,
+    )
+    .bind(product_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    let mut fields = BTreeMap::new();
+    for row in rows {
+        let locale: String = row.get("locale");
+        fields.insert(format!("title_{}", locale), row.get("title"));
+        fields.insert(format!("description_{}", locale), row.get("description"));
+    }
+    Ok(fields)
+}
+
+pub async fn list_product_translations(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    product_id: String,
+) -> Result<Vec<pb::ProductTranslation>, (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let product_uuid = parse_uuid(&product_id, "product_id")?;
+    ensure_product_belongs_to_store(state, product_uuid, store_uuid).await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT product_id::text as product_id, locale, title, description, updated_at
+        FROM product_translations
+        WHERE product_id = $1
+        ORDER BY locale ASC
+        "#,
+    )
+    .bind(product_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| pb::ProductTranslation {
+            product_id: row.get("product_id"),
+            locale: row.get("locale"),
+            title: row.get("title"),
+            description: row.get("description"),
+            updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
+        })
+        .collect())
+}
+
+pub async fn upsert_product_translation(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    product_id: String,
+    locale: String,
+    title: String,
+    description: String,
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::ProductTranslation, (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let product_uuid = parse_uuid(&product_id, "product_id")?;
+    let locale = normalize_locale(&locale)?;
+    if title.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "title is required")),
+        ));
+    }
+    ensure_product_belongs_to_store(state, product_uuid, store_uuid).await?;
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let before = sqlx::query(
+        r#"
+        SELECT title, description, updated_at
+        FROM product_translations
+        WHERE product_id = $1 AND locale = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(product_uuid)
+    .bind(&locale)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(db::error)?
+    .map(|row| pb::ProductTranslation {
+        product_id: product_id.clone(),
+        locale: locale.clone(),
+        title: row.get("title"),
+        description: row.get("description"),
+        updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
+    });
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO product_translations (product_id, locale, title, description)
+        VALUES ($1,$2,$3,$4)
+        ON CONFLICT (product_id, locale)
+        DO UPDATE SET title = EXCLUDED.title, description = EXCLUDED.description, updated_at = now()
+        RETURNING updated_at
+        "#,
+    )
+    .bind(product_uuid)
+    .bind(&locale)
+    .bind(&title)
+    .bind(&description)
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+
+    let translation = pb::ProductTranslation {
+        product_id: product_id.clone(),
+        locale,
+        title,
+        description,
+        updated_at: chrono_to_timestamp(Some(row.get::<chrono::DateTime<Utc>, _>("updated_at"))),
+    };
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id),
+                ProductAuditAction::TranslationUpsert,
+                Some("product"),
+                Some(product_id.clone()),
+                to_json_opt(before),
+                to_json_opt(Some(translation.clone())),
+                actor,
+            ),
+        )
+        .await?;
+
+    outbox::enqueue_search_reindex_tx(&mut tx, &product_id).await?;
+    tx.commit().await.map_err(db::error)?;
+
+    Ok(translation)
+}
+
+pub async fn list_supported_locales(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+) -> Result<Vec<String>, (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let rows = sqlx::query(
+        r#"
+        SELECT DISTINCT t.locale
+        FROM product_translations t
+        JOIN products p ON p.id = t.product_id
+        WHERE p.store_id = $1
+        ORDER BY t.locale ASC
+        "#,
+    )
+    .bind(store_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+    Ok(rows.into_iter().map(|row| row.get("locale")).collect())
+}
@@ -0,0 +1,412 @@
+use axum::{Json, http::StatusCode};
+use chrono::{DateTime, Utc};
+use sqlx::Row;
+
+use crate::{
+    AppState,
+    infrastructure::db,
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+    shared::{
+        audit_action::PromotionAuditAction,
+        audit_helpers::{audit_input, to_json_opt},
+        ids::parse_uuid,
+        store_context::resolve_store_context,
+        time::{chrono_to_timestamp, timestamp_to_chrono},
+    },
+};
+
+const PROMOTION_CODE_MAX_LEN: usize = 64;
+const PROMOTION_CODE_STATUS_ACTIVE: &str = "active";
+const PROMOTION_CODE_STATUSES: &[&str] = &["active", "inactive"];
+
+pub async fn create_promotion_code(
+    state: &AppState,
+    req: pb::CreatePromotionCodeRequest,
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::PromotionCode, (StatusCode, Json<ConnectError>)> {
+    let (store_id, tenant_id) = resolve_store_context(state, req.store.clone(), None).await?;
+    let store_uuid = parse_uuid(&store_id, "store_id")?;
+    let tenant_uuid = parse_uuid(&tenant_id, "tenant_id")?;
+    let promotion_uuid = parse_uuid(&req.promotion_id, "promotion_id")?;
+    let code = req.code.trim().to_string();
+    validate_code(&code)?;
+    if req.usage_limit < 0 {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidArgument,
+            "usage_limit must be zero or greater",
+        ));
+    }
+    let status = match req.status.trim() {
+        "" => PROMOTION_CODE_STATUS_ACTIVE,
+        status => status,
+    };
+    if !PROMOTION_CODE_STATUSES.contains(&status) {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidArgument,
+            "status must be active or inactive",
+        ));
+    }
+    let usage_limit = (req.usage_limit > 0).then_some(req.usage_limit);
+    let expires_at = timestamp_to_chrono(req.expires_at.clone());
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    let promotion = sqlx::query("SELECT 1 FROM promotions WHERE id = $1 AND tenant_id = $2")
+        .bind(promotion_uuid)
+        .bind(tenant_uuid)
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(db::error)?;
+    if promotion.is_none() {
+        return Err(error(StatusCode::NOT_FOUND, ErrorCode::NotFound, "promotion not found"));
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO promotion_codes (promotion_id, store_id, code, usage_limit, expires_at, status)
+        VALUES ($1,$2,$3,$4,$5,$6)
+        RETURNING id::text as id, created_at
+        "#,
+    )
+    .bind(promotion_uuid)
+    .bind(store_uuid)
+    .bind(&code)
+    .bind(usage_limit)
+    .bind(expires_at)
+    .bind(status)
+    .fetch_one(tx.as_mut())
+    .await
+    .map_err(|err| match err.as_database_error() {
+        Some(db_err) if db_err.is_unique_violation() => error(
+            StatusCode::CONFLICT,
+            ErrorCode::AlreadyExists,
+            "promotion code already exists in this store",
+        ),
+        _ => db::error(err),
+    })?;
+
+    let promotion_code = pb::PromotionCode {
+        id: row.get("id"),
+        promotion_id: promotion_uuid.to_string(),
+        code,
+        usage_limit: req.usage_limit,
+        used_count: 0,
+        expires_at: req.expires_at,
+        status: status.to_string(),
+        created_at: chrono_to_timestamp(Some(row.get::<DateTime<Utc>, _>("created_at"))),
+    };
+
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id),
+                PromotionAuditAction::CodeCreate,
+                Some("promotion_code"),
+                Some(promotion_code.id.clone()),
+                None,
+                to_json_opt(Some(promotion_code.clone())),
+                actor,
+            ),
+        )
+        .await?;
+
+    tx.commit().await.map_err(db::error)?;
+    Ok(promotion_code)
+}
+
+pub async fn list_promotion_codes(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    promotion_id: String,
+) -> Result<Vec<pb::PromotionCode>, (StatusCode, Json<ConnectError>)> {
+    let (store_id, _tenant_id) = resolve_store_context(state, store, None).await?;
+    let promotion_uuid = if promotion_id.is_empty() {
+        None
+    } else {
+        Some(parse_uuid(&promotion_id, "promotion_id")?)
+    };
+    let rows = sqlx::query(
+        r#"
+        SELECT id::text as id, promotion_id::text as promotion_id, code, usage_limit, used_count,
+               expires_at, status, created_at
+        FROM promotion_codes
+        WHERE store_id = $1 AND ($2::uuid IS NULL OR promotion_id = $2)
+        ORDER BY created_at DESC, id
+        "#,
+    )
+    .bind(parse_uuid(&store_id, "store_id")?)
+    .bind(promotion_uuid)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| pb::PromotionCode {
+            id: row.get("id"),
+            promotion_id: row.get("promotion_id"),
+            code: row.get("code"),
+            usage_limit: row.get::<Option<i32>, _>("usage_limit").unwrap_or(0),
+            used_count: row.get("used_count"),
+            expires_at: chrono_to_timestamp(row.get::<Option<DateTime<Utc>>, _>("expires_at")),
+            status: row.get("status"),
+            created_at: chrono_to_timestamp(Some(row.get::<DateTime<Utc>, _>("created_at"))),
+        })
+        .collect())
+}
+
+/// A promotion code joined with its promotion, as needed to redeem it against a cart.
+pub struct RedeemableCode {
+    pub id: uuid::Uuid,
+    pub promotion_id: uuid::Uuid,
+    pub first_order_only: bool,
+    code_status: String,
+    usage_limit: Option<i32>,
+    used_count: i32,
+    expires_at: Option<DateTime<Utc>>,
+    promotion_status: String,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+    discount_type: String,
+    value_amount: i64,
+    value_currency: String,
+    percent_off: Option<i32>,
+}
+
+impl RedeemableCode {
+    fn from_row(row: sqlx::postgres::PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            promotion_id: row.get("promotion_id"),
+            first_order_only: row.get("first_order_only"),
+            code_status: row.get("code_status"),
+            usage_limit: row.get("usage_limit"),
+            used_count: row.get("used_count"),
+            expires_at: row.get("expires_at"),
+            promotion_status: row.get("promotion_status"),
+            starts_at: row.get("starts_at"),
+            ends_at: row.get("ends_at"),
+            discount_type: row.get("discount_type"),
+            value_amount: row.get("value_amount"),
+            value_currency: row.get("value_currency"),
+            percent_off: row.get("percent_off"),
+        }
+    }
+
+    pub fn ensure_redeemable(&self, now: DateTime<Utc>) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        if self.code_status != PROMOTION_CODE_STATUS_ACTIVE || self.promotion_status != "active" {
+            return Err(failed_precondition("promotion code is not active"));
+        }
+        if self.starts_at.is_some_and(|starts_at| starts_at > now) {
+            return Err(failed_precondition("promotion has not started yet"));
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= now)
+            || self.ends_at.is_some_and(|ends_at| ends_at <= now)
+        {
+            return Err(failed_precondition("promotion code has expired"));
+        }
+        if self.usage_limit.is_some_and(|limit| self.used_count >= limit) {
+            return Err(failed_precondition("promotion code usage limit reached"));
+        }
+        Ok(())
+    }
+
+    // fixed: value is subtracted from the subtotal (capped at the subtotal).
+    // percent: percent_off of the subtotal, rounded down.
+    pub fn discount_for(&self, subtotal: i64, currency: &str) -> Result<i64, (StatusCode, Json<ConnectError>)> {
+        match self.discount_type.as_str() {
+            "fixed" => {
+                if self.value_currency != currency {
+                    return Err(failed_precondition("promotion currency does not match the cart"));
+                }
+                Ok(self.value_amount.clamp(0, subtotal.max(0)))
+            }
+            "percent" => match self.percent_off {
+                Some(percent_off) if (1..=100).contains(&percent_off) => {
+                    Ok(subtotal.max(0).saturating_mul(i64::from(percent_off)) / 100)
+                }
+                _ => Err(failed_precondition("promotion percent_off must be between 1 and 100")),
+            },
+            _ => Err(failed_precondition("this promotion cannot be redeemed with a code")),
+        }
+    }
+}
+
+pub async fn find_redeemable_code(
+    conn: &mut sqlx::PgConnection,
+    store_uuid: uuid::Uuid,
+    code: &str,
+) -> Result<Option<RedeemableCode>, (StatusCode, Json<ConnectError>)> {
+    let row = sqlx::query(
+        r#"
+        SELECT pc.id, pc.promotion_id, pc.status as code_status, pc.usage_limit, pc.used_count,
+               pc.expires_at, p.status as promotion_status, p.starts_at, p.ends_at,
+               p.discount_type, p.value_amount, p.value_currency, p.percent_off, p.first_order_only
+        FROM promotion_codes pc
+        JOIN promotions p ON p.id = pc.promotion_id
+        WHERE pc.store_id = $1 AND lower(pc.code) = lower($2)
+        "#,
+    )
+    .bind(store_uuid)
+    .bind(code)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    Ok(row.map(RedeemableCode::from_row))
+}
+
+/// Loads the code with its row locked so checkout can validate and consume it atomically.
+pub async fn lock_redeemable_code(
+    conn: &mut sqlx::PgConnection,
+    code_id: uuid::Uuid,
+) -> Result<Option<RedeemableCode>, (StatusCode, Json<ConnectError>)> {
+    let row = sqlx::query(
+        r#"
+        SELECT pc.id, pc.promotion_id, pc.status as code_status, pc.usage_limit, pc.used_count,
+               pc.expires_at, p.status as promotion_status, p.starts_at, p.ends_at,
+               p.discount_type, p.value_amount, p.value_currency, p.percent_off, p.first_order_only
+        FROM promotion_codes pc
+        JOIN promotions p ON p.id = pc.promotion_id
+        WHERE pc.id = $1
+        FOR UPDATE OF pc
+        "#,
+    )
+    .bind(code_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    Ok(row.map(RedeemableCode::from_row))
+}
+
+pub async fn consume_code(
+    conn: &mut sqlx::PgConnection,
+    code_id: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let updated = sqlx::query(
+        r#"
+        UPDATE promotion_codes
+        SET used_count = used_count + 1, updated_at = now()
+        WHERE id = $1 AND (usage_limit IS NULL OR used_count < usage_limit)
+        "#,
+    )
+    .bind(code_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    if updated.rows_affected() != 1 {
+        return Err(failed_precondition("promotion code usage limit reached"));
+    }
+    Ok(())
+}
+
+fn validate_code(code: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    if code.is_empty() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidArgument,
+            "code is required",
+        ));
+    }
+    if code.chars().count() > PROMOTION_CODE_MAX_LEN
+        || !code
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidArgument,
+            "code must be up to 64 letters, digits, '-' or '_'",
+        ));
+    }
+    Ok(())
+}
+
+fn failed_precondition(message: &str) -> (StatusCode, Json<ConnectError>) {
+    error(StatusCode::BAD_REQUEST, ErrorCode::FailedPrecondition, message)
+}
+
+fn error(status: StatusCode, code: ErrorCode, message: &str) -> (StatusCode, Json<ConnectError>) {
+    (status, Json(ConnectError::new(code, message.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redeemable(now: DateTime<Utc>) -> RedeemableCode {
+        RedeemableCode {
+            id: uuid::Uuid::new_v4(),
+            promotion_id: uuid::Uuid::new_v4(),
+            first_order_only: false,
+            code_status: "active".to_string(),
+            usage_limit: Some(3),
+            used_count: 0,
+            expires_at: Some(now + chrono::Duration::days(1)),
+            promotion_status: "active".to_string(),
+            starts_at: None,
+            ends_at: None,
+            discount_type: "percent".to_string(),
+            value_amount: 0,
+            value_currency: String::new(),
+            percent_off: Some(10),
+        }
+    }
+
+    fn failed_precondition_message(result: Result<(), (StatusCode, Json<ConnectError>)>) -> String {
+        let (_, Json(err)) = result.expect_err("code should not be redeemable");
+        assert!(matches!(err.code, ErrorCode::FailedPrecondition));
+        err.message
+    }
+
+    #[test]
+    fn code_under_its_limit_is_redeemable() {
+        let now = Utc::now();
+        let mut code = redeemable(now);
+        code.used_count = 2;
+        assert!(code.ensure_redeemable(now).is_ok());
+    }
+
+    #[test]
+    fn code_over_its_limit_is_rejected() {
+        let now = Utc::now();
+        let mut code = redeemable(now);
+        code.used_count = 3;
+        assert_eq!(
+            failed_precondition_message(code.ensure_redeemable(now)),
+            "promotion code usage limit reached"
+        );
+    }
+
+    #[test]
+    fn expired_code_is_rejected() {
+        let now = Utc::now();
+        let mut code = redeemable(now);
+        code.expires_at = Some(now);
+        assert_eq!(
+            failed_precondition_message(code.ensure_redeemable(now)),
+            "promotion code has expired"
+        );
+
+        let mut code = redeemable(now);
+        code.ends_at = Some(now - chrono::Duration::seconds(1));
+        assert_eq!(
+            failed_precondition_message(code.ensure_redeemable(now)),
+            "promotion code has expired"
+        );
+    }
+
+    #[test]
+    fn percent_discount_uses_percent_off() {
+        let code = redeemable(Utc::now());
+        assert_eq!(code.discount_for(1999, "JPY").unwrap(), 199);
+
+        let mut code = redeemable(Utc::now());
+        code.percent_off = None;
+        code.value_amount = 10;
+        assert!(code.discount_for(1999, "JPY").is_err());
+    }
+}
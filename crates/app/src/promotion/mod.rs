@@ -1,4 +1,5 @@
 /// プロモーション（割引）関連の境界。
 ///
-/// 施策の作成・更新は service を通じて行う。
+/// 施策の作成・更新は service を通じて行う。コードによる割引の発行・検証は code が担う。
+pub mod code;
 pub mod service;
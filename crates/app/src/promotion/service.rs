@@ -3,9 +3,9 @@ use sqlx::Row;
 
 use crate::{
     AppState,
-    infrastructure::{audit, db},
+    infrastructure::db,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
         audit_action::PromotionAuditAction,
         audit_helpers::{audit_input, to_json_opt},
@@ -14,21 +14,25 @@ use crate::{
     },
 };
 
+const ABUSED_FIRST_ORDER_DEFAULT_LIMIT: i32 = 50;
+const ABUSED_FIRST_ORDER_MAX_LIMIT: i32 = 200;
+
 pub async fn create_promotion(
     state: &AppState,
     tenant_id: String,
     req: pb::CreatePromotionRequest,
     _actor: Option<pb::ActorContext>,
 ) -> Result<pb::PromotionAdmin, (StatusCode, Json<ConnectError>)> {
-    let (value_amount, value_currency) = money_to_parts(req.value.clone())?;
+    let (value_amount, value_currency, percent_off) =
+        promotion_value(&req.discount_type, req.value.clone(), req.percent_off)?;
     let promotion_id = uuid::Uuid::new_v4();
     let mut tx = state.db.begin().await.map_err(db::error)?;
     sqlx::query(
         r#"
         INSERT INTO promotions (
             id, tenant_id, code, discount_type, value_amount, value_currency,
-            status, starts_at, ends_at
-        ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9)
+            status, starts_at, ends_at, first_order_only, percent_off
+        ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
         "#,
     )
     .bind(promotion_id)
@@ -40,6 +44,8 @@ pub async fn create_promotion(
     .bind(&req.status)
     .bind(timestamp_to_chrono(req.starts_at.clone()))
     .bind(timestamp_to_chrono(req.ends_at.clone()))
+    .bind(req.first_order_only)
+    .bind(percent_off)
     .execute(tx.as_mut())
     .await
     .map_err(db::error)?;
@@ -52,21 +58,25 @@ pub async fn create_promotion(
         status: req.status,
         starts_at: req.starts_at,
         ends_at: req.ends_at,
+        first_order_only: req.first_order_only,
+        percent_off: percent_off.unwrap_or(0),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            PromotionAuditAction::Create.into(),
-            Some("promotion"),
-            Some(promotion.id.clone()),
-            None,
-            to_json_opt(Some(promotion.clone())),
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                PromotionAuditAction::Create,
+                Some("promotion"),
+                Some(promotion.id.clone()),
+                None,
+                to_json_opt(Some(promotion.clone())),
+                _actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(db::error)?;
     Ok(promotion)
@@ -79,14 +89,15 @@ pub async fn update_promotion(
     _actor: Option<pb::ActorContext>,
 ) -> Result<pb::PromotionAdmin, (StatusCode, Json<ConnectError>)> {
     let before = fetch_promotion(state, &tenant_id, &req.promotion_id).await.ok();
-    let (value_amount, value_currency) = money_to_parts(req.value.clone())?;
+    let (value_amount, value_currency, percent_off) =
+        promotion_value(&req.discount_type, req.value.clone(), req.percent_off)?;
     let mut tx = state.db.begin().await.map_err(db::error)?;
     sqlx::query(
         r#"
         UPDATE promotions
         SET code = $1, discount_type = $2, value_amount = $3, value_currency = $4,
-            status = $5, starts_at = $6, ends_at = $7
-        WHERE id = $8 AND tenant_id = $9
+            status = $5, starts_at = $6, ends_at = $7, first_order_only = $8, percent_off = $9
+        WHERE id = $10 AND tenant_id = $11
         "#,
     )
     .bind(&req.code)
@@ -96,6 +107,8 @@ pub async fn update_promotion(
     .bind(&req.status)
     .bind(timestamp_to_chrono(req.starts_at.clone()))
     .bind(timestamp_to_chrono(req.ends_at.clone()))
+    .bind(req.first_order_only)
+    .bind(percent_off)
     .bind(crate::shared::ids::parse_uuid(&req.promotion_id, "promotion_id")?)
     .bind(&tenant_id)
     .execute(tx.as_mut())
@@ -110,21 +123,25 @@ pub async fn update_promotion(
         status: req.status,
         starts_at: req.starts_at,
         ends_at: req.ends_at,
+        first_order_only: req.first_order_only,
+        percent_off: percent_off.unwrap_or(0),
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            None,
-            PromotionAuditAction::Update.into(),
-            Some("promotion"),
-            Some(promotion.id.clone()),
-            to_json_opt(before),
-            to_json_opt(Some(promotion.clone())),
-            _actor,
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                None,
+                PromotionAuditAction::Update,
+                Some("promotion"),
+                Some(promotion.id.clone()),
+                to_json_opt(before),
+                to_json_opt(Some(promotion.clone())),
+                _actor,
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(db::error)?;
     Ok(promotion)
@@ -138,7 +155,7 @@ async fn fetch_promotion(
     let row = sqlx::query(
         r#"
         SELECT id::text as id, code, discount_type, value_amount, value_currency,
-               status, starts_at, ends_at
+               status, starts_at, ends_at, first_order_only, percent_off
         FROM promotions
         WHERE tenant_id = $1 AND id = $2
         "#,
@@ -164,5 +181,196 @@ async fn fetch_promotion(
         ends_at: crate::shared::time::chrono_to_timestamp(
             row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("ends_at"),
         ),
+        first_order_only: row.get("first_order_only"),
+        percent_off: row.get::<Option<i32>, _>("percent_off").unwrap_or(0),
     })
 }
+
+/// Stored `value_amount`, `value_currency` and `percent_off` of a promotion.
+type PromotionValue = (i64, String, Option<i32>);
+
+// percent promotions carry the percentage in percent_off; value is only required for the other types.
+fn promotion_value(
+    discount_type: &str,
+    value: Option<pb::Money>,
+    percent_off: i32,
+) -> Result<PromotionValue, (StatusCode, Json<ConnectError>)> {
+    if discount_type != "percent" {
+        if percent_off != 0 {
+            return Err(invalid_argument("percent_off is only allowed for percent promotions"));
+        }
+        let (value_amount, value_currency) = money_to_parts(value)?;
+        return Ok((value_amount, value_currency, None));
+    }
+    if !(1..=100).contains(&percent_off) {
+        return Err(invalid_argument("percent_off must be between 1 and 100"));
+    }
+    let (value_amount, value_currency) = match value {
+        Some(value) => money_to_parts(Some(value))?,
+        None => (0, String::new()),
+    };
+    Ok((value_amount, value_currency, Some(percent_off)))
+}
+
+fn invalid_argument(message: &str) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ConnectError::new(ErrorCode::InvalidArgument, message.to_string())),
+    )
+}
+
+pub struct AppliedPromotion {
+    pub id: uuid::Uuid,
+    pub first_order_only: bool,
+}
+
+// free_shipping promotions use value_amount as the minimum cart subtotal (0 applies to every cart).
+// Unrestricted promotions are preferred over first-order-only ones.
+pub async fn free_shipping_promotion(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: uuid::Uuid,
+    cart_subtotal: i64,
+    currency: &str,
+) -> Result<Option<AppliedPromotion>, (StatusCode, Json<ConnectError>)> {
+    let row = sqlx::query(
+        r#"
+        SELECT id, first_order_only
+        FROM promotions
+        WHERE tenant_id = $1
+          AND discount_type = 'free_shipping'
+          AND status = 'active'
+          AND (starts_at IS NULL OR starts_at <= now())
+          AND (ends_at IS NULL OR ends_at > now())
+          AND value_currency = $2
+          AND value_amount <= $3
+        ORDER BY first_order_only ASC, value_amount ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(currency)
+    .bind(cart_subtotal)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    Ok(row.map(|row| AppliedPromotion {
+        id: row.get("id"),
+        first_order_only: row.get("first_order_only"),
+    }))
+}
+
+pub async fn ensure_first_order(
+    conn: &mut sqlx::PgConnection,
+    tenant_id: uuid::Uuid,
+    customer_id: uuid::Uuid,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let prior = sqlx::query(
+        r#"
+        SELECT 1
+        FROM orders
+        WHERE customer_id = $1 AND tenant_id = $2 AND status NOT IN ('canceled')
+        LIMIT 1
+        "#,
+    )
+    .bind(customer_id)
+    .bind(tenant_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    if prior.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::FailedPrecondition,
+                "this promotion is for first orders only",
+            )),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn record_order_promotion(
+    conn: &mut sqlx::PgConnection,
+    order_id: uuid::Uuid,
+    promotion_id: uuid::Uuid,
+    metadata: serde_json::Value,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    sqlx::query(
+        r#"
+        INSERT INTO order_promotions (order_id, promotion_id, metadata)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (order_id, promotion_id) DO NOTHING
+        "#,
+    )
+    .bind(order_id)
+    .bind(promotion_id)
+    .bind(metadata)
+    .execute(&mut *conn)
+    .await
+    .map_err(db::error)?;
+    Ok(())
+}
+
+// Guest checkouts skip the first-order check, so flag guest orders whose email already
+// placed an earlier order in the tenant.
+pub async fn list_abused_first_order_promotions(
+    state: &AppState,
+    tenant_id: String,
+    limit: i32,
+) -> Result<Vec<pb::AbusedFirstOrderPromotion>, (StatusCode, Json<ConnectError>)> {
+    let limit = if limit <= 0 {
+        ABUSED_FIRST_ORDER_DEFAULT_LIMIT
+    } else {
+        limit.min(ABUSED_FIRST_ORDER_MAX_LIMIT)
+    };
+    let rows = sqlx::query(
+        r#"
+        SELECT o.id::text as order_id,
+               p.id::text as promotion_id,
+               p.code as promotion_code,
+               c.id::text as customer_id,
+               c.email as customer_email,
+               o.created_at,
+               prior.count as prior_order_count
+        FROM order_promotions op
+        JOIN orders o ON o.id = op.order_id
+        JOIN promotions p ON p.id = op.promotion_id
+        JOIN customers c ON c.id = o.customer_id
+        CROSS JOIN LATERAL (
+            SELECT COUNT(*) as count
+            FROM orders po
+            JOIN customers pc ON pc.id = po.customer_id
+            WHERE po.tenant_id = o.tenant_id
+              AND po.id <> o.id
+              AND po.created_at < o.created_at
+              AND po.status NOT IN ('canceled')
+              AND lower(pc.email) = lower(c.email)
+        ) prior
+        WHERE o.tenant_id = $1
+          AND (op.metadata->>'first_order_only_enforced')::boolean IS TRUE
+          AND prior.count > 0
+        ORDER BY o.created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(crate::shared::ids::parse_uuid(&tenant_id, "tenant_id")?)
+    .bind(limit as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db::error)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| pb::AbusedFirstOrderPromotion {
+            order_id: row.get("order_id"),
+            promotion_id: row.get("promotion_id"),
+            promotion_code: row.get("promotion_code"),
+            customer_id: row.get("customer_id"),
+            customer_email: row.get::<Option<String>, _>("customer_email").unwrap_or_default(),
+            prior_order_count: row.get::<i64, _>("prior_order_count"),
+            ordered_at: crate::shared::time::chrono_to_timestamp(Some(
+                row.get::<chrono::DateTime<chrono::Utc>, _>("created_at"),
+            )),
+        })
+        .collect())
+}
@@ -1,5 +1,6 @@
 use axum::{
     body::Body,
+    extract::State,
     http::{HeaderMap, Request},
     middleware::Next,
     response::Response,
@@ -13,7 +14,7 @@ use std::{
 };
 use tokio::sync::RwLock;
 
-use crate::pb::pb;
+use crate::{AppState, pb::pb};
 
 #[derive(Debug, Clone)]
 pub struct AuthContext {
@@ -22,10 +23,15 @@ pub struct AuthContext {
     pub store_id: Option<String>,
     pub tenant_id: Option<String>,
     pub session_id: Option<String>,
+    /// `iat` of the access token, when it carries one.
+    pub issued_at: Option<i64>,
 }
 
-pub async fn inject_actor(mut req: Request<Body>, next: Next) -> Response {
-    let auth_ctx = auth_from_headers(req.headers()).await;
+pub async fn inject_actor(State(state): State<AppState>, mut req: Request<Body>, next: Next) -> Response {
+    let auth_ctx = auth_from_headers(&state.auth, req.headers()).await;
+    if let Some(ctx) = auth_ctx.as_ref() {
+        record_span_scope(ctx.store_id.as_deref(), ctx.tenant_id.as_deref());
+    }
     let actor = auth_ctx.as_ref().map(|ctx| pb::ActorContext {
         actor_id: ctx.actor_id.clone(),
         actor_type: ctx.actor_type.clone(),
@@ -35,34 +41,87 @@ pub async fn inject_actor(mut req: Request<Body>, next: Next) -> Response {
     next.run(req).await
 }
 
-async fn auth_from_headers(headers: &HeaderMap) -> Option<AuthContext> {
-    if let Some(actor) = auth_from_bearer(headers).await {
-        return Some(actor);
+// Records the resolved scope on the http_request span so logs can be filtered per tenant.
+pub fn record_span_scope(store_id: Option<&str>, tenant_id: Option<&str>) {
+    let span = tracing::Span::current();
+    if let Some(store_id) = store_id {
+        span.record("store_id", store_id);
+    }
+    if let Some(tenant_id) = tenant_id {
+        span.record("tenant_id", tenant_id);
+    }
+}
+
+/// Token verification settings, read from the environment once at startup.
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    /// `AUTH_JWT_SECRET`; verifies HS256 tokens and signs staff access tokens.
+    pub jwt_secret: Option<String>,
+    pub jwks_url: Option<String>,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        Self {
+            jwt_secret: std::env::var("AUTH_JWT_SECRET").ok(),
+            jwks_url: std::env::var("AUTH_JWKS_URL").ok(),
+            issuer: std::env::var("AUTH_JWT_ISSUER").ok(),
+            audience: std::env::var("AUTH_JWT_AUDIENCE").ok(),
+        }
+    }
+
+    // Unsigned identities (raw bearer ids, x-actor-* headers) are only accepted when no verifier is configured.
+    fn allow_unverified_actor(&self) -> bool {
+        self.jwt_secret.is_none() && self.jwks_url.is_none()
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(aud) = &self.audience {
+            validation.set_audience(&[aud]);
+        }
+        validation
+    }
+}
+
+async fn auth_from_headers(config: &AuthConfig, headers: &HeaderMap) -> Option<AuthContext> {
+    // A present but invalid bearer token must not fall through to the header override.
+    if headers.contains_key(axum::http::header::AUTHORIZATION) {
+        return auth_from_bearer(config, headers).await;
+    }
+    if !config.allow_unverified_actor() {
+        return None;
     }
     auth_from_override(headers)
 }
 
-async fn auth_from_bearer(headers: &HeaderMap) -> Option<AuthContext> {
+async fn auth_from_bearer(config: &AuthConfig, headers: &HeaderMap) -> Option<AuthContext> {
     let value = headers.get(axum::http::header::AUTHORIZATION)?;
     let value = value.to_str().ok()?;
     let token = value.strip_prefix("Bearer ")?;
     if token.is_empty() {
         return None;
     }
-    if let Some(ctx) = verify_jwt_rs256(token).await {
+    if let Some(ctx) = verify_jwt_rs256(config, token).await {
         return Some(ctx);
     }
-    if let Some(ctx) = verify_jwt_hs256(token) {
+    if let Some(ctx) = verify_jwt_hs256(config, token) {
         return Some(ctx);
     }
-    // Fallback: treat bearer token as actor_id in dev (when AUTH_JWT_SECRET is not set).
-    if std::env::var("AUTH_JWT_SECRET").is_err() {
+    // Fallback: treat bearer token as actor_id in dev (when no signing key is configured).
+    if config.allow_unverified_actor() {
         return Some(AuthContext {
             actor_id: token.to_string(),
             actor_type: "api".to_string(),
             store_id: None,
             tenant_id: None,
             session_id: None,
+            issued_at: None,
         });
     }
     None
@@ -84,6 +143,7 @@ fn auth_from_override(headers: &HeaderMap) -> Option<AuthContext> {
         store_id: None,
         tenant_id: None,
         session_id: None,
+        issued_at: None,
     })
 }
 
@@ -96,6 +156,7 @@ struct JwtClaims {
     store_id: Option<String>,
     tenant_id: Option<String>,
     jti: Option<String>,
+    iat: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -122,12 +183,12 @@ static JWKS_CACHE: Lazy<RwLock<JwksCache>> = Lazy::new(|| RwLock::new(JwksCache:
 const JWKS_TTL: Duration = Duration::from_secs(300);
 const JWKS_TIMEOUT: Duration = Duration::from_secs(5);
 
-async fn verify_jwt_rs256(token: &str) -> Option<AuthContext> {
-    let jwks_url = std::env::var("AUTH_JWKS_URL").ok()?;
+async fn verify_jwt_rs256(config: &AuthConfig, token: &str) -> Option<AuthContext> {
+    let jwks_url = config.jwks_url.as_deref()?;
     let header = decode_header(token).ok()?;
     let kid = header.kid;
 
-    let key = get_jwk_key(&jwks_url, kid.as_deref()).await?;
+    let key = get_jwk_key(jwks_url, kid.as_deref()).await?;
     if key.kty != "RSA" {
         return None;
     }
@@ -137,39 +198,32 @@ async fn verify_jwt_rs256(token: &str) -> Option<AuthContext> {
         return None;
     }
     let decoding_key = DecodingKey::from_rsa_components(&key.n, &key.e).ok()?;
-    let mut validation = Validation::new(Algorithm::RS256);
-    if let Ok(issuer) = std::env::var("AUTH_JWT_ISSUER") {
-        validation.set_issuer(&[issuer]);
-    }
-    if let Ok(aud) = std::env::var("AUTH_JWT_AUDIENCE") {
-        validation.set_audience(&[aud]);
-    }
-    let data = decode::<JwtClaims>(token, &decoding_key, &validation).ok()?;
+    let data = decode::<JwtClaims>(token, &decoding_key, &config.validation(Algorithm::RS256)).ok()?;
     Some(AuthContext {
         actor_id: data.claims.sub,
         actor_type: data.claims.actor_type.unwrap_or_else(|| "api".to_string()),
         store_id: data.claims.store_id,
         tenant_id: data.claims.tenant_id,
         session_id: data.claims.jti,
+        issued_at: data.claims.iat,
     })
 }
 
-fn verify_jwt_hs256(token: &str) -> Option<AuthContext> {
-    let secret = std::env::var("AUTH_JWT_SECRET").ok()?;
-    let mut validation = Validation::new(Algorithm::HS256);
-    if let Ok(issuer) = std::env::var("AUTH_JWT_ISSUER") {
-        validation.set_issuer(&[issuer]);
-    }
-    if let Ok(aud) = std::env::var("AUTH_JWT_AUDIENCE") {
-        validation.set_audience(&[aud]);
-    }
-    let data = decode::<JwtClaims>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation).ok()?;
+fn verify_jwt_hs256(config: &AuthConfig, token: &str) -> Option<AuthContext> {
+    let secret = config.jwt_secret.as_deref()?;
+    let data = decode::<JwtClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &config.validation(Algorithm::HS256),
+    )
+    .ok()?;
     Some(AuthContext {
         actor_id: data.claims.sub,
         actor_type: data.claims.actor_type.unwrap_or_else(|| "api".to_string()),
         store_id: data.claims.store_id,
         tenant_id: data.claims.tenant_id,
         session_id: data.claims.jti,
+        issued_at: data.claims.iat,
     })
 }
 
@@ -231,3 +285,122 @@ fn allow_single_key_fallback() -> bool {
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+
+    use super::*;
+
+    const SECRET: &str = "test-secret";
+
+    fn secret_config() -> AuthConfig {
+        AuthConfig {
+            jwt_secret: Some(SECRET.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn token(secret: &str, exp_offset_seconds: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = serde_json::json!({
+            "sub": "staff-1",
+            "actor_type": "staff",
+            "store_id": "store-1",
+            "tenant_id": "tenant-1",
+            "jti": "session-1",
+            "iat": now,
+            "exp": now + exp_offset_seconds,
+        });
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn hs256_accepts_a_valid_token() {
+        let ctx = verify_jwt_hs256(&secret_config(), &token(SECRET, 300)).expect("valid token");
+        assert_eq!(ctx.actor_id, "staff-1");
+        assert_eq!(ctx.actor_type, "staff");
+        assert_eq!(ctx.store_id.as_deref(), Some("store-1"));
+        assert_eq!(ctx.tenant_id.as_deref(), Some("tenant-1"));
+        assert_eq!(ctx.session_id.as_deref(), Some("session-1"));
+        assert!(ctx.issued_at.is_some());
+    }
+
+    #[test]
+    fn hs256_rejects_expired_wrong_secret_and_malformed_tokens() {
+        let config = secret_config();
+        assert!(verify_jwt_hs256(&config, &token(SECRET, -3600)).is_none());
+        assert!(verify_jwt_hs256(&config, &token("other-secret", 300)).is_none());
+        assert!(verify_jwt_hs256(&config, "not-a-jwt").is_none());
+        // Without a secret there is nothing to verify against.
+        assert!(verify_jwt_hs256(&AuthConfig::default(), &token(SECRET, 300)).is_none());
+    }
+
+    #[test]
+    fn hs256_checks_issuer_and_audience_when_configured() {
+        let config = AuthConfig {
+            issuer: Some("https://issuer.example".to_string()),
+            ..secret_config()
+        };
+        assert!(verify_jwt_hs256(&config, &token(SECRET, 300)).is_none());
+        let config = AuthConfig {
+            audience: Some("backoffice".to_string()),
+            ..secret_config()
+        };
+        assert!(verify_jwt_hs256(&config, &token(SECRET, 300)).is_none());
+    }
+
+    #[tokio::test]
+    async fn bearer_token_is_verified_when_a_secret_is_set() {
+        let config = secret_config();
+        let valid = headers(&[("authorization", format!("Bearer {}", token(SECRET, 300)))]);
+        assert_eq!(
+            auth_from_headers(&config, &valid).await.map(|ctx| ctx.actor_id),
+            Some("staff-1".to_string())
+        );
+        let expired = headers(&[("authorization", format!("Bearer {}", token(SECRET, -3600)))]);
+        assert!(auth_from_headers(&config, &expired).await.is_none());
+        let forged = headers(&[("authorization", format!("Bearer {}", token("other-secret", 300)))]);
+        assert!(auth_from_headers(&config, &forged).await.is_none());
+        assert!(auth_from_headers(&config, &HeaderMap::new()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn unverified_actors_are_ignored_once_a_verifier_is_configured() {
+        let override_headers = headers(&[
+            ("x-actor-id", "admin-1".to_string()),
+            ("x-actor-type", "owner".to_string()),
+        ]);
+        let raw_bearer = headers(&[("authorization", "Bearer admin-1".to_string())]);
+
+        let dev = AuthConfig::default();
+        assert!(dev.allow_unverified_actor());
+        let ctx = auth_from_headers(&dev, &override_headers).await.expect("dev override");
+        assert_eq!((ctx.actor_id.as_str(), ctx.actor_type.as_str()), ("admin-1", "owner"));
+        assert!(auth_from_headers(&dev, &raw_bearer).await.is_some());
+
+        let jwks = AuthConfig {
+            jwks_url: Some("http://127.0.0.1:9/jwks.json".to_string()),
+            ..Default::default()
+        };
+        for config in [secret_config(), jwks] {
+            assert!(!config.allow_unverified_actor());
+            assert!(auth_from_headers(&config, &override_headers).await.is_none());
+            assert!(auth_from_headers(&config, &raw_bearer).await.is_none());
+        }
+    }
+}
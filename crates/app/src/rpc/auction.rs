@@ -9,7 +9,7 @@ use axum::{
 use crate::{
     AppState, auction,
     pb::pb,
-    rpc::json::{ConnectError, parse_request},
+    rpc::json::{ConnectError, ErrorCode, parse_request},
 };
 
 pub async fn create_auction(
@@ -84,10 +84,7 @@ pub async fn place_bid(
     let amount = req.amount.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "amount is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "amount is required")),
         )
     })?;
     let (auction, bid) =
@@ -108,8 +105,8 @@ pub async fn list_bids(
 ) -> Result<(StatusCode, Json<pb::ListBidsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListBidsRequest>(&headers, body)?;
     let store_id = auction::service::resolve_context(&state, req.store.clone()).await?;
-    let bids = auction::service::list_bids(&state, store_id, req.auction_id).await?;
-    Ok((StatusCode::OK, Json(pb::ListBidsResponse { bids })))
+    let (bids, page) = auction::service::list_bids(&state, store_id, req.auction_id, req.page).await?;
+    Ok((StatusCode::OK, Json(pb::ListBidsResponse { bids, page: Some(page) })))
 }
 
 pub async fn set_auto_bid(
@@ -147,8 +144,14 @@ pub async fn list_auto_bids(
 ) -> Result<(StatusCode, Json<pb::ListAutoBidsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListAutoBidsRequest>(&headers, body)?;
     let store_id = auction::service::resolve_context(&state, req.store.clone()).await?;
-    let auto_bids = auction::service::list_auto_bids(&state, store_id, req.auction_id).await?;
-    Ok((StatusCode::OK, Json(pb::ListAutoBidsResponse { auto_bids })))
+    let (auto_bids, page) = auction::service::list_auto_bids(&state, store_id, req.auction_id, req.page).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::ListAutoBidsResponse {
+            auto_bids,
+            page: Some(page),
+        }),
+    ))
 }
 
 pub async fn close_auction(
@@ -9,7 +9,7 @@ use crate::{
     AppState, audit,
     pb::pb,
     rpc::json::{ConnectError, parse_request, require_store_id},
-    shared::audit_action::AuditAction,
+    shared::audit_action::{AuditAction, AuditActionTrait},
 };
 
 pub async fn list_audit_logs(
@@ -19,26 +19,41 @@ pub async fn list_audit_logs(
 ) -> Result<(StatusCode, Json<pb::ListAuditLogsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListAuditLogsRequest>(&headers, body)?;
     let store_id = require_store_id(req.store.clone())?;
-    let (logs, page) = audit::service::list_audit_logs(&state, store_id, req).await?;
+    let (logs, page, warnings) = audit::service::list_audit_logs(&state, store_id, req).await?;
     Ok((
         StatusCode::OK,
-        Json(pb::ListAuditLogsResponse { logs, page: Some(page) }),
+        Json(pb::ListAuditLogsResponse {
+            logs,
+            page: Some(page),
+            warnings,
+        }),
     ))
 }
 
 pub async fn list_audit_actions(
-    State(_state): State<AppState>,
-    _headers: HeaderMap,
-    _body: Bytes,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListAuditActionsResponse>), (StatusCode, Json<ConnectError>)> {
     use crate::shared::audit_action::ALL_AUDIT_ACTIONS;
 
+    let req = parse_request::<pb::ListAuditActionsRequest>(&headers, body)?;
+    let recorded = match req.store {
+        Some(store) => Some(audit::service::list_recorded_actions(&state, require_store_id(Some(store))?).await?),
+        None => None,
+    };
     let actions = ALL_AUDIT_ACTIONS
         .iter()
+        .filter(|action| {
+            recorded
+                .as_ref()
+                .is_none_or(|recorded| recorded.iter().any(|key| key == action.as_str()))
+        })
         .map(|action| pb::AuditActionItem {
             r#type: audit_action_type(action),
             key: action.as_str().to_string(),
             label: action.label().to_string(),
+            category: action.category().to_string(),
         })
         .collect();
 
@@ -49,8 +64,13 @@ fn audit_action_type(action: &AuditAction) -> i32 {
     match action {
         AuditAction::ProductCreate => pb::AuditActionType::AuditActionProductCreate as i32,
         AuditAction::ProductUpdate => pb::AuditActionType::AuditActionProductUpdate as i32,
+        AuditAction::ProductTranslationUpsert => pb::AuditActionType::AuditActionProductTranslationUpsert as i32,
+        AuditAction::ProductDelete => pb::AuditActionType::AuditActionProductDelete as i32,
+        AuditAction::CategoryDelete => pb::AuditActionType::AuditActionCategoryDelete as i32,
         AuditAction::VariantCreate => pb::AuditActionType::AuditActionVariantCreate as i32,
         AuditAction::VariantUpdate => pb::AuditActionType::AuditActionVariantUpdate as i32,
+        AuditAction::VariantBulkAxisUpdate => pb::AuditActionType::AuditActionVariantBulkAxisUpdate as i32,
+        AuditAction::VariantMediaSet => pb::AuditActionType::AuditActionVariantMediaSet as i32,
         AuditAction::InventorySet => pb::AuditActionType::AuditActionInventorySet as i32,
         AuditAction::StoreSettingsUpdate => pb::AuditActionType::AuditActionStoreSettingsUpdate as i32,
         AuditAction::StoreSettingsInitialize => pb::AuditActionType::AuditActionStoreSettingsInitialize as i32,
@@ -66,12 +86,14 @@ fn audit_action_type(action: &AuditAction) -> i32 {
         AuditAction::TaxRuleDelete => pb::AuditActionType::AuditActionTaxRuleDelete as i32,
         AuditAction::PromotionCreate => pb::AuditActionType::AuditActionPromotionCreate as i32,
         AuditAction::PromotionUpdate => pb::AuditActionType::AuditActionPromotionUpdate as i32,
+        AuditAction::PromotionCodeCreate => pb::AuditActionType::AuditActionPromotionCodeCreate as i32,
         AuditAction::AuctionCreate => pb::AuditActionType::AuditActionAuctionCreate as i32,
         AuditAction::AuctionUpdate => pb::AuditActionType::AuditActionAuctionUpdate as i32,
         AuditAction::AuctionBid => pb::AuditActionType::AuditActionAuctionBid as i32,
         AuditAction::AuctionEnd => pb::AuditActionType::AuditActionAuctionEnd as i32,
         AuditAction::AuctionApprove => pb::AuditActionType::AuditActionAuctionApprove as i32,
         AuditAction::OrderUpdateStatus => pb::AuditActionType::AuditActionOrderUpdateStatus as i32,
+        AuditAction::CartCheckout => pb::AuditActionType::AuditActionCartCheckout as i32,
         AuditAction::ShipmentCreate => pb::AuditActionType::AuditActionShipmentCreate as i32,
         AuditAction::ShipmentUpdateStatus => pb::AuditActionType::AuditActionShipmentUpdateStatus as i32,
         AuditAction::IdentitySignIn => pb::AuditActionType::AuditActionIdentitySignIn as i32,
@@ -85,9 +107,13 @@ fn audit_action_type(action: &AuditAction) -> i32 {
         AuditAction::IdentityRoleUpdate => pb::AuditActionType::AuditActionIdentityRoleUpdate as i32,
         AuditAction::IdentityRoleDelete => pb::AuditActionType::AuditActionIdentityRoleDelete as i32,
         AuditAction::IdentityOwnerTransfer => pb::AuditActionType::AuditActionIdentityOwnerTransfer as i32,
+        AuditAction::IdentityPasswordReset => pb::AuditActionType::AuditActionIdentityPasswordReset as i32,
+        AuditAction::IdentityPasswordChange => pb::AuditActionType::AuditActionIdentityPasswordChange as i32,
+        AuditAction::IdentityStaffUnlock => pb::AuditActionType::AuditActionIdentityStaffUnlock as i32,
         AuditAction::CustomerCreate => pb::AuditActionType::AuditActionCustomerCreate as i32,
         AuditAction::CustomerUpdate => pb::AuditActionType::AuditActionCustomerUpdate as i32,
         AuditAction::CustomerIdentityUpsert => pb::AuditActionType::AuditActionCustomerIdentityUpsert as i32,
         AuditAction::CustomerAddressUpsert => pb::AuditActionType::AuditActionCustomerAddressUpsert as i32,
+        AuditAction::CustomerAddressDelete => pb::AuditActionType::AuditActionCustomerAddressDelete as i32,
     }
 }
@@ -7,13 +7,18 @@ use axum::{
 };
 
 use crate::{
-    AppState,
-    identity::context::resolve_store_context,
-    order, pages,
+    AppState, audit, order, pages,
     pb::pb,
     product, promotion,
-    rpc::json::{ConnectError, parse_request, require_tenant_id},
-    shared::ids::{StoreId, TenantId},
+    rpc::{
+        json::{ConnectError, ErrorCode, parse_request, require_tenant_id},
+        request_context::RequestContext,
+    },
+    shared::{
+        ids::{StoreId, TenantId},
+        store_context::resolve_store_context,
+    },
+    store_settings,
 };
 
 pub async fn get_dashboard_summary(
@@ -26,11 +31,12 @@ pub async fn get_dashboard_summary(
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
 
-    let product_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM products WHERE store_id = $1")
-        .bind(store_uuid.as_uuid())
-        .fetch_one(&state.db)
-        .await
-        .map_err(crate::infrastructure::db::error)?;
+    let product_count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM products WHERE store_id = $1 AND deleted_at IS NULL")
+            .bind(store_uuid.as_uuid())
+            .fetch_one(&state.db)
+            .await
+            .map_err(crate::infrastructure::db::error)?;
 
     let product_active_count: i64 =
         sqlx::query_scalar("SELECT COUNT(*) FROM products WHERE store_id = $1 AND status = 'active'")
@@ -102,18 +108,18 @@ pub async fn get_dashboard_summary(
 pub async fn list_products(
     State(state): State<AppState>,
     Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    Extension(request_ctx): Extension<RequestContext>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListProductsAdminResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListProductsAdminRequest>(&headers, body)?;
-    let products = product::service::list_products_admin(&state, req.tenant, req.store).await?;
+    let (products, page) =
+        product::service::list_products_admin(&state, &request_ctx, req.tenant, req.store, req.page).await?;
     Ok((
         StatusCode::OK,
         Json(pb::ListProductsAdminResponse {
             products,
-            page: Some(pb::PageResult {
-                next_page_token: String::new(),
-            }),
+            page: Some(page),
         }),
     ))
 }
@@ -125,15 +131,14 @@ pub async fn list_variants(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListVariantsAdminResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListVariantsAdminRequest>(&headers, body)?;
-    let (variants, variant_axes) =
-        product::service::list_variants_admin(&state, req.tenant, req.store, req.product_id).await?;
+    let (variants, variant_axes, page) =
+        product::service::list_variants_admin(&state, req.tenant, req.store, req.product_id, req.status, req.page)
+            .await?;
     Ok((
         StatusCode::OK,
         Json(pb::ListVariantsAdminResponse {
             variants,
-            page: Some(pb::PageResult {
-                next_page_token: String::new(),
-            }),
+            page: Some(page),
             variant_axes,
         }),
     ))
@@ -146,45 +151,48 @@ pub async fn list_skus(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListSkusAdminResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListSkusAdminRequest>(&headers, body)?;
-    let skus = product::service::list_skus_admin(&state, req.tenant, req.store, req.query).await?;
+    let (skus, page) = product::service::list_skus_admin(&state, req.tenant, req.store, req.query, req.page).await?;
     Ok((
         StatusCode::OK,
-        Json(pb::ListSkusAdminResponse {
-            skus,
-            page: Some(pb::PageResult {
-                next_page_token: String::new(),
-            }),
-        }),
+        Json(pb::ListSkusAdminResponse { skus, page: Some(page) }),
     ))
 }
 
 pub async fn create_product(
     State(state): State<AppState>,
     Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    Extension(request_ctx): Extension<RequestContext>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::CreateProductResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::CreateProductRequest>(&headers, body)?;
     let actor = req.actor.clone().or(actor_ctx);
-    let product = product::service::create_product(&state, req, actor).await?;
+    let (product, warnings) = product::service::create_product(&state, &request_ctx, req, actor).await?;
     Ok((
         StatusCode::OK,
-        Json(pb::CreateProductResponse { product: Some(product) }),
+        Json(pb::CreateProductResponse {
+            product: Some(product),
+            warnings,
+        }),
     ))
 }
 
 pub async fn update_product(
     State(state): State<AppState>,
     Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    Extension(request_ctx): Extension<RequestContext>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpdateProductResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpdateProductRequest>(&headers, body)?;
     let actor = req.actor.clone().or(actor_ctx);
-    let product = product::service::update_product(&state, req, actor).await?;
+    let (product, warnings) = product::service::update_product(&state, &request_ctx, req, actor).await?;
     Ok((
         StatusCode::OK,
-        Json(pb::UpdateProductResponse { product: Some(product) }),
+        Json(pb::UpdateProductResponse {
+            product: Some(product),
+            warnings,
+        }),
     ))
 }
 
@@ -233,6 +241,18 @@ pub async fn update_category(
     ))
 }
 
+pub async fn delete_product(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::DeleteProductResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::DeleteProductRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let resp = product::service::delete_product(&state, req, actor).await?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
 pub async fn delete_category(
     State(state): State<AppState>,
     Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -241,8 +261,8 @@ pub async fn delete_category(
 ) -> Result<(StatusCode, Json<pb::DeleteCategoryResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::DeleteCategoryRequest>(&headers, body)?;
     let actor = req.actor.clone().or(actor_ctx);
-    let deleted = product::service::delete_category(&state, req, actor).await?;
-    Ok((StatusCode::OK, Json(pb::DeleteCategoryResponse { deleted })))
+    let resp = product::service::delete_category(&state, req, actor).await?;
+    Ok((StatusCode::OK, Json(resp)))
 }
 
 pub async fn reorder_categories(
@@ -264,8 +284,17 @@ pub async fn list_category_products(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListCategoryProductsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListCategoryProductsRequest>(&headers, body)?;
-    let products = product::service::list_category_products_admin(&state, req.store, req.category_id).await?;
-    Ok((StatusCode::OK, Json(pb::ListCategoryProductsResponse { products })))
+    let (products, page, total_count) =
+        product::service::list_category_products_admin(&state, req.store, req.category_id, req.status, req.page)
+            .await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::ListCategoryProductsResponse {
+            products,
+            page: Some(page),
+            total_count,
+        }),
+    ))
 }
 
 pub async fn reorder_category_products(
@@ -310,6 +339,18 @@ pub async fn update_variant(
     ))
 }
 
+pub async fn bulk_set_axis_values(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::BulkSetAxisValuesResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::BulkSetAxisValuesRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let updated_count = product::service::bulk_set_axis_values(&state, req, actor).await?;
+    Ok((StatusCode::OK, Json(pb::BulkSetAxisValuesResponse { updated_count })))
+}
+
 pub async fn list_media_assets(
     State(state): State<AppState>,
     Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -412,6 +453,30 @@ pub async fn set_sku_images(
     Ok((StatusCode::OK, Json(pb::SetSkuImagesResponse { images })))
 }
 
+pub async fn set_variant_media(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::SetVariantMediaResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::SetVariantMediaRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let media = product::media::set_variant_media(&state, req.store, req.variant_id, req.media_ids, actor).await?;
+    Ok((StatusCode::OK, Json(pb::SetVariantMediaResponse { media })))
+}
+
+pub async fn reorder_variant_media(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ReorderVariantMediaResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ReorderVariantMediaRequest>(&headers, body)?;
+    let _actor = req.actor.clone().or(actor_ctx);
+    let media = product::media::reorder_variant_media(&state, req.store, req.variant_id, req.ordered_media_ids).await?;
+    Ok((StatusCode::OK, Json(pb::ReorderVariantMediaResponse { media })))
+}
+
 pub async fn list_digital_assets(
     State(state): State<AppState>,
     Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -515,6 +580,17 @@ pub async fn list_inventory_movements(
     Ok((StatusCode::OK, Json(pb::ListInventoryMovementsResponse { movements, page: Some(page) })))
 }
 
+pub async fn get_inventory_report(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::GetInventoryReportResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::GetInventoryReportRequest>(&headers, body)?;
+    let (lines, page) = product::service::get_inventory_report(&state, req).await?;
+    Ok((StatusCode::OK, Json(pb::GetInventoryReportResponse { lines, page: Some(page) })))
+}
+
 pub async fn adjust_inventory(
     State(state): State<AppState>,
     Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -546,21 +622,46 @@ pub async fn list_orders(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListOrdersResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListOrdersRequest>(&headers, body)?;
-    let tenant_id = require_tenant_id(req.tenant)?;
-    let orders = order::service::list_orders(&state, tenant_id, req.status)
+    let tenant_id = require_tenant_id(req.tenant.clone())?;
+    let (orders, page, total_amount_sum) = order::service::list_orders(&state, tenant_id, req)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((
         StatusCode::OK,
         Json(pb::ListOrdersResponse {
             orders,
-            page: Some(pb::PageResult {
-                next_page_token: String::new(),
-            }),
+            page: Some(page),
+            total_amount_sum,
         }),
     ))
 }
 
+pub async fn get_product_performance(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::GetProductPerformanceResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::GetProductPerformanceRequest>(&headers, body)?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, None).await?;
+    let products = order::analytics::get_product_performance(&state, store_id, req.from, req.to, req.limit)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(pb::GetProductPerformanceResponse { products })))
+}
+
+pub async fn get_store_activity(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::GetStoreActivityResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::GetStoreActivityRequest>(&headers, body)?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, None).await?;
+    let events = audit::service::get_store_activity(&state, store_id, req.limit).await?;
+    Ok((StatusCode::OK, Json(pb::GetStoreActivityResponse { events })))
+}
+
 pub async fn update_order_status(
     State(state): State<AppState>,
     Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -653,6 +754,49 @@ pub async fn update_promotion(
     ))
 }
 
+pub async fn list_abused_first_order_promotions(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ListAbusedFirstOrderPromotionsResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ListAbusedFirstOrderPromotionsRequest>(&headers, body)?;
+    let tenant_id = require_tenant_id(req.tenant.clone())?;
+    let orders = promotion::service::list_abused_first_order_promotions(&state, tenant_id, req.limit).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::ListAbusedFirstOrderPromotionsResponse { orders }),
+    ))
+}
+
+pub async fn create_promotion_code(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::CreatePromotionCodeResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::CreatePromotionCodeRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let promotion_code = promotion::code::create_promotion_code(&state, req, actor).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::CreatePromotionCodeResponse {
+            promotion_code: Some(promotion_code),
+        }),
+    ))
+}
+
+pub async fn list_promotion_codes(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ListPromotionCodesResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ListPromotionCodesRequest>(&headers, body)?;
+    let promotion_codes = promotion::code::list_promotion_codes(&state, req.store, req.promotion_id).await?;
+    Ok((StatusCode::OK, Json(pb::ListPromotionCodesResponse { promotion_codes })))
+}
+
 pub async fn list_pages(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -770,14 +914,100 @@ pub async fn update_product_metafield_definition(
     ))
 }
 
+pub async fn get_search_settings(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::GetSearchSettingsResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::GetSearchSettingsRequest>(&headers, body)?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
+    let settings = store_settings::search::get_search_settings(&state, store_id).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::GetSearchSettingsResponse {
+            settings: Some(settings),
+        }),
+    ))
+}
+
+pub async fn update_search_settings(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::UpdateSearchSettingsResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::UpdateSearchSettingsRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
+    let settings = store_settings::search::update_search_settings(
+        &state,
+        store_id,
+        tenant_id,
+        req.settings.unwrap_or_default(),
+        actor,
+    )
+    .await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::UpdateSearchSettingsResponse {
+            settings: Some(settings),
+        }),
+    ))
+}
+
+pub async fn list_valid_ranking_rules(
+    State(_state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ListValidRankingRulesResponse>), (StatusCode, Json<ConnectError>)> {
+    let _req = parse_request::<pb::ListValidRankingRulesRequest>(&headers, body)?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::ListValidRankingRulesResponse {
+            ranking_rules: store_settings::search::list_valid_ranking_rules(),
+        }),
+    ))
+}
+
+pub async fn delete_product_metafield_definition(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::DeleteProductMetafieldDefinitionResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::DeleteProductMetafieldDefinitionRequest>(&headers, body)?;
+    let _actor = req.actor.clone().or(actor_ctx);
+    let deleted = product::service::delete_product_metafield_definition(&state, req.definition_id).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::DeleteProductMetafieldDefinitionResponse { deleted }),
+    ))
+}
+
+pub async fn purge_metafield_definition(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::PurgeMetafieldDefinitionResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::PurgeMetafieldDefinitionRequest>(&headers, body)?;
+    let _actor = req.actor.clone().or(actor_ctx);
+    let deleted_value_count =
+        product::service::purge_product_metafield_definition(&state, req.definition_id, req.confirm).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::PurgeMetafieldDefinitionResponse {
+            deleted_value_count: deleted_value_count as i64,
+        }),
+    ))
+}
+
 fn require_page_input(page: Option<pb::PageInput>) -> Result<pb::PageInput, (StatusCode, Json<ConnectError>)> {
     page.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "page is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "page is required")),
         )
     })
 }
@@ -812,3 +1042,51 @@ pub async fn upsert_product_metafield_value(
     .await?;
     Ok((StatusCode::OK, Json(pb::UpsertProductMetafieldValueResponse {})))
 }
+
+pub async fn list_product_translations(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ListProductTranslationsResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ListProductTranslationsRequest>(&headers, body)?;
+    let translations = product::translation::list_product_translations(&state, req.store, req.product_id).await?;
+    Ok((StatusCode::OK, Json(pb::ListProductTranslationsResponse { translations })))
+}
+
+pub async fn upsert_product_translation(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::UpsertProductTranslationResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::UpsertProductTranslationRequest>(&headers, body)?;
+    let actor = req.actor.clone().or(actor_ctx);
+    let translation = product::translation::upsert_product_translation(
+        &state,
+        req.store,
+        req.product_id,
+        req.locale,
+        req.title,
+        req.description,
+        actor,
+    )
+    .await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::UpsertProductTranslationResponse {
+            translation: Some(translation),
+        }),
+    ))
+}
+
+pub async fn list_supported_locales(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ListSupportedLocalesResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ListSupportedLocalesRequest>(&headers, body)?;
+    let locales = product::translation::list_supported_locales(&state, req.store).await?;
+    Ok((StatusCode::OK, Json(pb::ListSupportedLocalesResponse { locales })))
+}
@@ -8,9 +8,9 @@ use axum::{
 
 use crate::{
     AppState, customer,
-    identity::context::resolve_store_context,
     pb::pb,
-    rpc::json::{ConnectError, parse_request},
+    rpc::json::{ConnectError, ErrorCode, parse_request},
+    shared::store_context::resolve_store_context,
 };
 
 pub async fn list_customers(
@@ -33,6 +33,20 @@ pub async fn list_customers(
     ))
 }
 
+pub async fn search_customers(
+    State(state): State<AppState>,
+    Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::SearchCustomersResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::SearchCustomersRequest>(&headers, body)?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
+    let customers = customer::service::search_customers(&state, store_id, tenant_id, req.query, req.status)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(pb::SearchCustomersResponse { customers })))
+}
+
 pub async fn get_customer(
     State(state): State<AppState>,
     Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -67,10 +81,7 @@ pub async fn create_customer(
     let profile = req.profile.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "profile is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "profile is required")),
         )
     })?;
     let actor = req.actor.or(actor_ctx);
@@ -99,10 +110,7 @@ pub async fn update_customer(
     let profile = req.profile.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "profile is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "profile is required")),
         )
     })?;
     let actor = req.actor.or(actor_ctx);
@@ -113,6 +121,7 @@ pub async fn update_customer(
         req.customer_id,
         profile,
         req.customer_status,
+        req.force_sync,
         actor,
     )
     .await
@@ -138,10 +147,7 @@ pub async fn upsert_customer_identity(
     let identity = req.identity.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "identity is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "identity is required")),
         )
     })?;
     let identity = customer::service::upsert_customer_identity(&state, tenant_id, req.customer_id, identity, actor)
@@ -167,10 +173,7 @@ pub async fn upsert_customer_address(
     let address = req.address.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "address is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "address is required")),
         )
     })?;
     let address = customer::service::upsert_customer_address(&state, req.customer_id, address, actor)
@@ -182,6 +185,21 @@ pub async fn upsert_customer_address(
     ))
 }
 
+pub async fn delete_customer_address(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::DeleteCustomerAddressResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::DeleteCustomerAddressRequest>(&headers, body)?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
+    let actor = req.actor.or(actor_ctx);
+    let deleted = customer::service::delete_customer_address(&state, store_id, req.customer_id, req.address_id, actor)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(pb::DeleteCustomerAddressResponse { deleted })))
+}
+
 pub async fn list_customer_metafield_definitions(
     State(state): State<AppState>,
     Extension(_actor_ctx): Extension<Option<pb::ActorContext>>,
@@ -211,10 +229,7 @@ pub async fn create_customer_metafield_definition(
     let input = req.definition.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "definition is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "definition is required")),
         )
     })?;
     let definition = customer::service::create_customer_metafield_definition(&state, input)
@@ -240,10 +255,7 @@ pub async fn update_customer_metafield_definition(
     let input = req.definition.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "definition is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "definition is required")),
         )
     })?;
     let definition = customer::service::update_customer_metafield_definition(&state, req.definition_id, input)
@@ -8,10 +8,11 @@ use axum::{
 };
 
 use crate::{
-    AppState, identity,
+    AppState,
+    identity::{self, error::IdentityError},
     pb::pb,
     rpc::actor::AuthContext,
-    rpc::json::{ConnectError, parse_request},
+    rpc::json::{ConnectError, ErrorCode, parse_request},
 };
 
 const REFRESH_COOKIE_PREFIX: &str = "refresh_token_";
@@ -45,17 +46,7 @@ pub async fn sign_out(
     let req = parse_request::<pb::IdentitySignOutRequest>(&headers, body)?;
     let store_id_from_auth = auth_ctx.as_ref().and_then(|ctx| ctx.store_id.clone());
     let session_id = auth_ctx.and_then(|ctx| ctx.session_id);
-    let store_id = req
-        .store
-        .as_ref()
-        .and_then(|s| {
-            if s.store_id.is_empty() {
-                None
-            } else {
-                Some(s.store_id.clone())
-            }
-        })
-        .or(store_id_from_auth);
+    let store_id = identity::service::requested_store_id(req.store.as_ref()).or(store_id_from_auth);
     let resp = identity::service::sign_out(&state, req, actor_ctx, session_id)
         .await
         .map_err(|err| err.into_connect())?;
@@ -76,25 +67,8 @@ pub async fn refresh_token(
     body: Bytes,
 ) -> Result<Response, (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::IdentityRefreshTokenRequest>(&headers, body)?;
-    let store_id = req
-        .store
-        .as_ref()
-        .and_then(|s| {
-            if s.store_id.is_empty() {
-                None
-            } else {
-                Some(s.store_id.clone())
-            }
-        })
-        .ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_id is required".to_string(),
-                }),
-            )
-        })?;
+    let store_id = identity::service::requested_store_id(req.store.as_ref())
+        .ok_or_else(|| IdentityError::invalid_argument("store_id is required").into_connect())?;
     let cookie_name = refresh_cookie_name(&store_id);
     let refresh_token = extract_cookie(&headers, &cookie_name).unwrap_or_default();
     let result = identity::service::refresh_token(&state, req, refresh_token)
@@ -327,9 +301,7 @@ pub async fn invite_staff(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::IdentityInviteStaffResponse>), (StatusCode, Json<ConnectError>)> {
     let mut req = parse_request::<pb::IdentityInviteStaffRequest>(&headers, body)?;
-    if req.actor.is_none() {
-        req.actor = actor_ctx;
-    }
+    req.actor = actor_ctx;
     let resp = identity::service::invite_staff(&state, req)
         .await
         .map_err(|err| err.into_connect())?;
@@ -340,9 +312,54 @@ pub async fn accept_invite(
     State(state): State<AppState>,
     headers: HeaderMap,
     body: Bytes,
-) -> Result<(StatusCode, Json<pb::IdentityAcceptInviteResponse>), (StatusCode, Json<ConnectError>)> {
+) -> Result<Response, (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::IdentityAcceptInviteRequest>(&headers, body)?;
-    let resp = identity::service::accept_invite(&state, req)
+    let result = identity::service::accept_invite_with_refresh(&state, req)
+        .await
+        .map_err(|err| err.into_connect())?;
+    let cookie_name = refresh_cookie_name(&result.response.store_id);
+    let mut response = Json(result.response).into_response();
+    response.headers_mut().insert(
+        axum::http::header::SET_COOKIE,
+        build_refresh_cookie(&cookie_name, &result.refresh_token, REFRESH_TOKEN_MAX_AGE),
+    );
+    Ok(response)
+}
+
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::IdentityRequestPasswordResetResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::IdentityRequestPasswordResetRequest>(&headers, body)?;
+    let resp = identity::service::request_password_reset(&state, req)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::IdentityResetPasswordResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::IdentityResetPasswordRequest>(&headers, body)?;
+    let resp = identity::service::reset_password(&state, req)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::IdentityChangePasswordResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::IdentityChangePasswordRequest>(&headers, body)?;
+    let session_id = auth_ctx.and_then(|ctx| ctx.session_id);
+    let resp = identity::service::change_password(&state, req, actor_ctx, session_id)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(resp)))
@@ -355,11 +372,258 @@ pub async fn transfer_owner(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::IdentityTransferOwnerResponse>), (StatusCode, Json<ConnectError>)> {
     let mut req = parse_request::<pb::IdentityTransferOwnerRequest>(&headers, body)?;
-    if req.actor.is_none() {
-        req.actor = actor_ctx;
-    }
+    req.actor = actor_ctx;
     let resp = identity::service::transfer_owner(&state, req)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(resp)))
 }
+
+pub async fn unlock_staff(
+    State(state): State<AppState>,
+    Extension(actor_ctx): Extension<Option<pb::ActorContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::IdentityUnlockStaffResponse>), (StatusCode, Json<ConnectError>)> {
+    let mut req = parse_request::<pb::IdentityUnlockStaffRequest>(&headers, body)?;
+    // The owner check reads `req.actor`, so it must come from the verified token, never the body.
+    req.actor = actor_ctx;
+    let resp = identity::service::unlock_staff(&state, req)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
+// IdentityError と ConnectError の相互変換は rpc 層に置き、identity ドメインを Connect の型から切り離す。
+impl IdentityError {
+    pub fn into_connect(self) -> (StatusCode, Json<ConnectError>) {
+        match self {
+            IdentityError::InvalidArgument(message) => (
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
+            ),
+            IdentityError::InvalidFields(message, details) => (
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(ErrorCode::InvalidArgument, message).with_details(details)),
+            ),
+            IdentityError::NotFound(message) => (
+                StatusCode::NOT_FOUND,
+                Json(ConnectError::new(ErrorCode::NotFound, message)),
+            ),
+            IdentityError::AlreadyExists(message) => (
+                StatusCode::CONFLICT,
+                Json(ConnectError::new(ErrorCode::AlreadyExists, message)),
+            ),
+            IdentityError::FailedPrecondition(message) => (
+                StatusCode::CONFLICT,
+                Json(ConnectError::new(ErrorCode::FailedPrecondition, message)),
+            ),
+            IdentityError::PermissionDenied(message) => (
+                StatusCode::FORBIDDEN,
+                Json(ConnectError::new(ErrorCode::PermissionDenied, message)),
+            ),
+            IdentityError::Unauthenticated(message) => (
+                StatusCode::UNAUTHORIZED,
+                Json(ConnectError::new(ErrorCode::Unauthenticated, message)),
+            ),
+            IdentityError::ResourceExhausted(message) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ConnectError::new(ErrorCode::ResourceExhausted, message)),
+            ),
+            IdentityError::Internal(message) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(ErrorCode::Internal, message)),
+            ),
+        }
+    }
+}
+
+impl From<(StatusCode, Json<ConnectError>)> for IdentityError {
+    fn from(value: (StatusCode, Json<ConnectError>)) -> Self {
+        let (status, Json(err)) = value;
+        match err.code {
+            ErrorCode::InvalidArgument if !err.details.is_empty() => {
+                IdentityError::InvalidFields(err.message, err.details)
+            }
+            ErrorCode::InvalidArgument => IdentityError::InvalidArgument(err.message),
+            ErrorCode::NotFound => IdentityError::NotFound(err.message),
+            ErrorCode::AlreadyExists => IdentityError::AlreadyExists(err.message),
+            ErrorCode::FailedPrecondition => IdentityError::FailedPrecondition(err.message),
+            ErrorCode::PermissionDenied => IdentityError::PermissionDenied(err.message),
+            ErrorCode::Unauthenticated => IdentityError::Unauthenticated(err.message),
+            ErrorCode::ResourceExhausted => IdentityError::ResourceExhausted(err.message),
+            ErrorCode::Internal => IdentityError::Internal(err.message),
+            _ => match status {
+                StatusCode::BAD_REQUEST => IdentityError::InvalidArgument(err.message),
+                StatusCode::NOT_FOUND => IdentityError::NotFound(err.message),
+                StatusCode::CONFLICT => IdentityError::AlreadyExists(err.message),
+                StatusCode::FORBIDDEN => IdentityError::PermissionDenied(err.message),
+                StatusCode::UNAUTHORIZED => IdentityError::Unauthenticated(err.message),
+                _ => IdentityError::Internal(err.message),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support::{self, TestStore};
+
+    fn json_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        headers
+    }
+
+    async fn create_staff_with_role(db: &PgPool, store: &TestStore, role_key: &str) -> uuid::Uuid {
+        let role_id: uuid::Uuid =
+            sqlx::query_scalar("INSERT INTO store_roles (store_id, key, name) VALUES ($1, $2, $2) RETURNING id")
+                .bind(store.store_id)
+                .bind(role_key)
+                .fetch_one(db)
+                .await
+                .unwrap();
+        sqlx::query_scalar("INSERT INTO store_staff (store_id, role_id, status) VALUES ($1, $2, 'active') RETURNING id")
+            .bind(store.store_id)
+            .bind(role_id)
+            .fetch_one(db)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn unlock_staff_ignores_an_owner_actor_in_the_body(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id: uuid::Uuid =
+            sqlx::query_scalar("INSERT INTO store_staff (store_id, status) VALUES ($1, 'locked') RETURNING id")
+                .bind(store.store_id)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+        let body = serde_json::json!({
+            "store": { "store_id": store.store_id.to_string() },
+            "staff_id": staff_id.to_string(),
+            "actor": { "actor_id": staff_id.to_string(), "actor_type": "owner" },
+        });
+        let token_actor = pb::ActorContext {
+            actor_id: uuid::Uuid::new_v4().to_string(),
+            actor_type: "staff".to_string(),
+        };
+
+        let (status, Json(err)) = unlock_staff(
+            State(state),
+            Extension(Some(token_actor)),
+            json_headers(),
+            Bytes::from(body.to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(err.message, "owner role is required");
+        let status: String = sqlx::query_scalar("SELECT status FROM store_staff WHERE id = $1")
+            .bind(staff_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(status, "locked");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn transfer_owner_ignores_an_owner_actor_in_the_body(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let owner_id = create_staff_with_role(&db, &store, "owner").await;
+        let staff_id = create_staff_with_role(&db, &store, "staff").await;
+        // A staff member claims to be the owner in the body and names themselves the new owner.
+        let body = serde_json::json!({
+            "store": { "store_id": store.store_id.to_string() },
+            "new_owner_staff_id": staff_id.to_string(),
+            "actor": { "actor_id": owner_id.to_string(), "actor_type": "owner" },
+        });
+        let token_actor = pb::ActorContext {
+            actor_id: staff_id.to_string(),
+            actor_type: "staff".to_string(),
+        };
+
+        let (status, Json(err)) = transfer_owner(
+            State(state),
+            Extension(Some(token_actor)),
+            json_headers(),
+            Bytes::from(body.to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(matches!(err.code, ErrorCode::PermissionDenied), "{err:?}");
+        assert_eq!(err.message, "owner role is required");
+        let owner_role: String = sqlx::query_scalar(
+            "SELECT r.key FROM store_staff s JOIN store_roles r ON r.id = s.role_id WHERE s.id = $1",
+        )
+        .bind(owner_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        assert_eq!(owner_role, "owner");
+    }
+
+    async fn try_sign_in(
+        state: &AppState,
+        store: &TestStore,
+        email: &str,
+        password: &str,
+    ) -> (StatusCode, ConnectError) {
+        let body = serde_json::json!({
+            "store": { "store_id": store.store_id.to_string() },
+            "email": email,
+            "password": password,
+        });
+        let Err((status, Json(err))) =
+            sign_in(State(state.clone()), json_headers(), Bytes::from(body.to_string())).await
+        else {
+            panic!("sign-in as {email} succeeded");
+        };
+        (status, err)
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn locked_sign_in_is_resource_exhausted_for_any_password(db: PgPool) {
+        use argon2::{Argon2, PasswordHasher, password_hash::SaltString};
+
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id = create_staff_with_role(&db, &store, "staff").await;
+        let password_hash = Argon2::default()
+            .hash_password(b"Correct1pass", &SaltString::generate(&mut rand_core::OsRng))
+            .unwrap()
+            .to_string();
+        sqlx::query("UPDATE store_staff SET email = 'staff@example.com', password_hash = $2 WHERE id = $1")
+            .bind(staff_id)
+            .bind(password_hash)
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO staff_failed_logins (staff_id) SELECT $1 FROM generate_series(1, 5)")
+            .bind(staff_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        // Reaching the threshold and an already locked account give the same answer, right password or not.
+        for password in ["Correct1pass", "Wrong1pass", "Correct1pass"] {
+            let (status, err) = try_sign_in(&state, &store, "staff@example.com", password).await;
+            assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+            assert!(matches!(err.code, ErrorCode::ResourceExhausted), "{err:?}");
+            assert_eq!(err.message, "account is locked");
+        }
+        let (status, err) = try_sign_in(&state, &store, "nobody@example.com", "Correct1pass").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(err.message, "invalid credentials");
+    }
+}
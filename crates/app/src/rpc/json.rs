@@ -17,6 +17,7 @@ pub enum ErrorCode {
     PermissionDenied,
     Unauthenticated,
     FailedPrecondition,
+    ResourceExhausted,
     Internal,
     UnsupportedMediaType,
 }
@@ -25,26 +26,43 @@ pub enum ErrorCode {
 pub struct ConnectError {
     pub code: ErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<pb::FieldViolation>,
+}
+
+impl ConnectError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with_details(mut self, details: Vec<pb::FieldViolation>) -> Self {
+        self.details = details;
+        self
+    }
 }
 
 pub fn parse_json_body(headers: &HeaderMap, body: Bytes) -> Result<Value, (StatusCode, Json<ConnectError>)> {
     if !is_json_content_type(headers) {
         return Err((
             StatusCode::UNSUPPORTED_MEDIA_TYPE,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::UnsupportedMediaType,
-                message: "content-type must be application/json".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::UnsupportedMediaType,
+                "content-type must be application/json",
+            )),
         ));
     }
 
     serde_json::from_slice::<Value>(&body).map_err(|err| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: format!("invalid json: {}", err),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("invalid json: {}", err),
+            )),
         )
     })
 }
@@ -57,10 +75,10 @@ pub fn parse_request<T: DeserializeOwned>(
     serde_json::from_value::<T>(value).map_err(|err| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: format!("invalid request: {}", err),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("invalid request: {}", err),
+            )),
         )
     })
 }
@@ -76,10 +94,10 @@ pub fn require_tenant_id(tenant: Option<pb::TenantContext>) -> Result<String, (S
         Some(id) => Ok(id),
         None => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tenant.tenant_id is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "tenant.tenant_id is required",
+            )),
         )),
     }
 }
@@ -89,10 +107,10 @@ pub fn require_store_id(store: Option<pb::StoreContext>) -> Result<String, (Stat
         Some(id) => Ok(id),
         None => Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "store.store_id is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "store.store_id is required",
+            )),
         )),
     }
 }
@@ -100,20 +118,14 @@ pub fn require_store_id(store: Option<pb::StoreContext>) -> Result<String, (Stat
 pub fn invalid_argument(message: impl Into<String>) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::BAD_REQUEST,
-        Json(ConnectError {
-            code: ErrorCode::InvalidArgument,
-            message: message.into(),
-        }),
+        Json(ConnectError::new(ErrorCode::InvalidArgument, message.into())),
     )
 }
 
 pub fn not_found(message: impl Into<String>) -> (StatusCode, Json<ConnectError>) {
     (
         StatusCode::NOT_FOUND,
-        Json(ConnectError {
-            code: ErrorCode::NotFound,
-            message: message.into(),
-        }),
+        Json(ConnectError::new(ErrorCode::NotFound, message.into())),
     )
 }
 
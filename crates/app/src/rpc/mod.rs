@@ -1,6 +1,6 @@
 use axum::{
     Router,
-    extract::State,
+    extract::{DefaultBodyLimit, State},
     http::{HeaderValue, Method, Request},
     middleware,
     routing::post,
@@ -23,10 +23,11 @@ mod session;
 mod setup;
 mod store_settings;
 mod storefront;
+mod upload;
 
-pub fn router(state: AppState) -> Router<()> {
+pub fn router(state: AppState, allowed_origins: Vec<HeaderValue>) -> Router<()> {
     let cors = CorsLayer::new()
-        .allow_methods([Method::POST, Method::OPTIONS])
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
@@ -34,14 +35,12 @@ pub fn router(state: AppState) -> Router<()> {
             axum::http::header::HeaderName::from_static("x-actor-type"),
             axum::http::header::HeaderName::from_static("x-request-id"),
             axum::http::header::HeaderName::from_static("x-refresh-attempt"),
+            axum::http::header::HeaderName::from_static("x-cart-token"),
             axum::http::header::HeaderName::from_static("connect-protocol-version"),
             axum::http::header::HeaderName::from_static("connect-timeout-ms"),
         ])
         .allow_credentials(true)
-        .allow_origin([
-            HeaderValue::from_static("http://localhost:3000"),
-            HeaderValue::from_static("http://127.0.0.1:3000"),
-        ]);
+        .allow_origin(allowed_origins);
 
     Router::new()
         .route(
@@ -88,6 +87,18 @@ pub fn router(state: AppState) -> Router<()> {
             "/rpc/ecommerce.v1.StorefrontService/Checkout",
             post(storefront::checkout),
         )
+        .route(
+            "/rpc/ecommerce.v1.StorefrontService/ClaimCart",
+            post(storefront::claim_cart),
+        )
+        .route(
+            "/rpc/ecommerce.v1.StorefrontService/ApplyPromotionCode",
+            post(storefront::apply_promotion_code),
+        )
+        .route(
+            "/rpc/ecommerce.v1.StorefrontService/RemovePromotionCode",
+            post(storefront::remove_promotion_code),
+        )
         .route(
             "/rpc/ecommerce.v1.StorefrontService/GetOrder",
             post(storefront::get_order),
@@ -119,6 +130,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/DeleteProduct",
+            post(backoffice::delete_product).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListCategories",
             post(backoffice::list_categories).route_layer(middleware::from_fn_with_state(
@@ -245,6 +265,33 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/GetSearchSettings",
+            post(backoffice::get_search_settings).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/UpdateSearchSettings",
+            post(backoffice::update_search_settings).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ListValidRankingRules",
+            post(backoffice::list_valid_ranking_rules).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.AuctionService/ListAuctions",
             post(auction::list_auctions).route_layer(middleware::from_fn_with_state(
@@ -353,6 +400,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/BulkSetAxisValues",
+            post(backoffice::bulk_set_axis_values).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListMediaAssets",
             post(backoffice::list_media_assets).route_layer(middleware::from_fn_with_state(
@@ -371,6 +427,18 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/upload/media",
+            post(upload::upload_media)
+                // Leave room for the multipart boundaries and text fields around the file.
+                .layer(DefaultBodyLimit::max(state.upload_max_bytes.saturating_add(64 * 1024)))
+                .route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    |State(state), req, next| {
+                        permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                    },
+                )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/CreateMediaUploadUrl",
             post(backoffice::create_media_upload_url).route_layer(middleware::from_fn_with_state(
@@ -416,6 +484,24 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/SetVariantMedia",
+            post(backoffice::set_variant_media).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ReorderVariantMedia",
+            post(backoffice::reorder_variant_media).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListDigitalAssets",
             post(backoffice::list_digital_assets).route_layer(middleware::from_fn_with_state(
@@ -497,6 +583,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/GetInventoryReport",
+            post(backoffice::get_inventory_report).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListOrders",
             post(backoffice::list_orders).route_layer(middleware::from_fn_with_state(
@@ -506,6 +601,24 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/GetStoreActivity",
+            post(backoffice::get_store_activity).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::AuditRead)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/GetProductPerformance",
+            post(backoffice::get_product_performance).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::OrdersRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/UpdateOrderStatus",
             post(backoffice::update_order_status).route_layer(middleware::from_fn_with_state(
@@ -551,6 +664,33 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/CreatePromotionCode",
+            post(backoffice::create_promotion_code).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::PromotionsWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ListPromotionCodes",
+            post(backoffice::list_promotion_codes).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::PromotionsRead)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ListAbusedFirstOrderPromotions",
+            post(backoffice::list_abused_first_order_promotions).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::OrdersRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListProductMetafieldDefinitions",
             post(backoffice::list_product_metafield_definitions).route_layer(middleware::from_fn_with_state(
@@ -578,6 +718,24 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/DeleteProductMetafieldDefinition",
+            post(backoffice::delete_product_metafield_definition).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/PurgeMetafieldDefinition",
+            post(backoffice::purge_metafield_definition).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsWrite)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.BackofficeService/ListProductMetafieldValues",
             post(backoffice::list_product_metafield_values).route_layer(middleware::from_fn_with_state(
@@ -596,6 +754,33 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ListProductTranslations",
+            post(backoffice::list_product_translations).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogRead)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/UpsertProductTranslation",
+            post(backoffice::upsert_product_translation).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogWrite)
+                },
+            )),
+        )
+        .route(
+            "/rpc/ecommerce.v1.BackofficeService/ListSupportedLocales",
+            post(backoffice::list_supported_locales).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CatalogRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.CustomerService/ListCustomers",
             post(customer::list_customers).route_layer(middleware::from_fn_with_state(
@@ -605,6 +790,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.CustomerService/SearchCustomers",
+            post(customer::search_customers).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CustomersRead)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.CustomerService/GetCustomer",
             post(customer::get_customer).route_layer(middleware::from_fn_with_state(
@@ -650,6 +844,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.CustomerService/DeleteCustomerAddress",
+            post(customer::delete_customer_address).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::CustomersWrite)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.CustomerService/ListCustomerMetafieldDefinitions",
             post(customer::list_customer_metafield_definitions).route_layer(middleware::from_fn_with_state(
@@ -856,6 +1059,15 @@ pub fn router(state: AppState) -> Router<()> {
             "/rpc/ecommerce.v1.SetupService/ValidateStoreCode",
             post(setup::validate_store_code),
         )
+        .route(
+            "/rpc/ecommerce.v1.SetupService/ValidateStoreSetup",
+            post(setup::validate_store_setup).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::SettingsRead)
+                },
+            )),
+        )
         // IdentityService is the single entry point for staff auth/roles.
         .route("/rpc/ecommerce.v1.IdentityService/SignIn", post(identity::sign_in))
         .route("/rpc/ecommerce.v1.IdentityService/SignOut", post(identity::sign_out))
@@ -979,6 +1191,18 @@ pub fn router(state: AppState) -> Router<()> {
             "/rpc/ecommerce.v1.IdentityService/AcceptInvite",
             post(identity::accept_invite),
         )
+        .route(
+            "/rpc/ecommerce.v1.IdentityService/RequestPasswordReset",
+            post(identity::request_password_reset),
+        )
+        .route(
+            "/rpc/ecommerce.v1.IdentityService/ResetPassword",
+            post(identity::reset_password),
+        )
+        .route(
+            "/rpc/ecommerce.v1.IdentityService/ChangePassword",
+            post(identity::change_password),
+        )
         .route(
             "/rpc/ecommerce.v1.IdentityService/TransferOwner",
             post(identity::transfer_owner).route_layer(middleware::from_fn_with_state(
@@ -988,6 +1212,15 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
+        .route(
+            "/rpc/ecommerce.v1.IdentityService/UnlockStaff",
+            post(identity::unlock_staff).route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                |State(state), req, next| {
+                    permissions::require_permission_key(state, req, next, permissions::PermissionKey::StaffManage)
+                },
+            )),
+        )
         .route(
             "/rpc/ecommerce.v1.AuditService/ListAuditLogs",
             post(audit::list_audit_logs).route_layer(middleware::from_fn_with_state(
@@ -1006,12 +1239,16 @@ pub fn router(state: AppState) -> Router<()> {
                 },
             )),
         )
-        .layer(middleware::from_fn(request_context::inject_request_context))
+        .merge(crate::router())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            request_context::inject_request_context,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             session::require_active_staff_session,
         ))
-        .layer(middleware::from_fn(actor::inject_actor))
+        .layer(middleware::from_fn_with_state(state.clone(), actor::inject_actor))
         .layer(TraceLayer::new_for_http().make_span_with(|req: &Request<_>| {
             let request_id = req
                 .headers()
@@ -1023,7 +1260,9 @@ pub fn router(state: AppState) -> Router<()> {
                 method = %req.method(),
                 uri = %req.uri(),
                 request_id = %request_id,
-                trace_id = tracing::field::Empty
+                trace_id = tracing::field::Empty,
+                store_id = tracing::field::Empty,
+                tenant_id = tracing::field::Empty
             )
         }))
         .layer(cors)
@@ -6,7 +6,11 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
-use crate::{AppState, rpc::actor::AuthContext, rpc::json::ConnectError};
+use crate::{
+    AppState,
+    rpc::actor::AuthContext,
+    rpc::json::{ConnectError, ErrorCode},
+};
 
 pub async fn require_permission_with_state(
     state: AppState,
@@ -69,15 +73,12 @@ pub async fn require_permission_with_state(
 
 fn error_response(status: StatusCode, message: &str) -> Response {
     let code = match status {
-        StatusCode::UNAUTHORIZED => crate::rpc::json::ErrorCode::Unauthenticated,
-        StatusCode::FORBIDDEN => crate::rpc::json::ErrorCode::PermissionDenied,
-        StatusCode::INTERNAL_SERVER_ERROR => crate::rpc::json::ErrorCode::Internal,
-        _ => crate::rpc::json::ErrorCode::PermissionDenied,
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthenticated,
+        StatusCode::FORBIDDEN => ErrorCode::PermissionDenied,
+        StatusCode::INTERNAL_SERVER_ERROR => ErrorCode::Internal,
+        _ => ErrorCode::PermissionDenied,
     };
-    let body = Json(ConnectError {
-        code,
-        message: message.to_string(),
-    });
+    let body = Json(ConnectError::new(code, message.to_string()));
     (status, body).into_response()
 }
 
@@ -87,6 +88,7 @@ pub enum PermissionKey {
     CatalogWrite,
     OrdersRead,
     OrdersWrite,
+    PromotionsRead,
     PromotionsWrite,
     AuctionRead,
     AuctionWrite,
@@ -107,6 +109,7 @@ impl PermissionKey {
             PermissionKey::CatalogWrite => "catalog.write",
             PermissionKey::OrdersRead => "orders.read",
             PermissionKey::OrdersWrite => "orders.write",
+            PermissionKey::PromotionsRead => "promotions.read",
             PermissionKey::PromotionsWrite => "promotions.write",
             PermissionKey::AuctionRead => "auction.read",
             PermissionKey::AuctionWrite => "auction.write",
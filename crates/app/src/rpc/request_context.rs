@@ -1,14 +1,27 @@
 use axum::http::HeaderValue;
 use axum::{
     body::Body,
+    extract::{ConnectInfo, State},
     http::{HeaderMap, Request},
     middleware::Next,
     response::Response,
 };
 use opentelemetry::trace::{TraceContextExt, TraceId};
+use sqlx::Row;
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Instant,
+};
 use tracing::info;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use crate::{
+    AppState,
+    rpc::actor::{AuthContext, record_span_scope},
+};
+
+/// Per-request metadata. Handlers read it with `Extension<RequestContext>` and pass it to services
+/// explicitly; `current()` is the task-local fallback for code that has not been migrated yet.
 #[derive(Clone, Default)]
 pub struct RequestContext {
     pub request_id: Option<String>,
@@ -16,27 +29,49 @@ pub struct RequestContext {
     pub user_agent: Option<String>,
     pub store_id: Option<String>,
     pub tenant_id: Option<String>,
+    pub role_key: Option<String>,
+    pub display_name: Option<String>,
+}
+
+impl RequestContext {
+    /// Context of a staff token scoped to `store_id` / `tenant_id`, for service tests that run
+    /// without the middleware.
+    #[cfg(test)]
+    pub fn test_context(store_id: impl ToString, tenant_id: impl ToString) -> Self {
+        Self {
+            store_id: Some(store_id.to_string()),
+            tenant_id: Some(tenant_id.to_string()),
+            ..Default::default()
+        }
+    }
 }
 
 tokio::task_local! {
     static REQUEST_CONTEXT: RequestContext;
 }
 
-pub async fn inject_request_context(req: Request<Body>, next: Next) -> Response {
-    let auth_ctx = req
-        .extensions()
-        .get::<Option<crate::rpc::actor::AuthContext>>()
-        .and_then(|v| v.clone());
-    let ctx = RequestContext {
+pub async fn inject_request_context(State(state): State<AppState>, mut req: Request<Body>, next: Next) -> Response {
+    let started_at = Instant::now();
+    let auth_ctx = req.extensions().get::<Option<AuthContext>>().and_then(|v| v.clone());
+    let peer_addr = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let mut ctx = RequestContext {
         request_id: extract_request_id(req.headers()),
-        ip_address: extract_ip_address(req.headers()),
+        ip_address: extract_ip_address(req.headers(), peer_addr),
         user_agent: extract_user_agent(req.headers()),
         store_id: auth_ctx.as_ref().and_then(|ctx| ctx.store_id.clone()),
         tenant_id: auth_ctx.as_ref().and_then(|ctx| ctx.tenant_id.clone()),
+        role_key: None,
+        display_name: None,
     };
+    if let Some(auth) = auth_ctx.as_ref() {
+        enrich_staff_context(&state, auth, &mut ctx).await;
+    }
+    record_span_scope(ctx.store_id.as_deref(), ctx.tenant_id.as_deref());
+    req.extensions_mut().insert(ctx.clone());
     let method = req.method().clone();
     let path = req.uri().path().to_string();
     let request_id = ctx.request_id.clone();
+    let role_key = ctx.role_key.clone();
     // Attach trace_id to the current span if available (OpenTelemetry).
     if let Some(trace_id) = current_trace_id().or_else(|| request_id.clone()) {
         tracing::Span::current().record("trace_id", &trace_id);
@@ -45,6 +80,7 @@ pub async fn inject_request_context(req: Request<Body>, next: Next) -> Response
         .scope(ctx.clone(), async move {
             info!(
                 request_id = request_id.as_deref().unwrap_or(""),
+                role_key = role_key.as_deref().unwrap_or(""),
                 method = %method,
                 path = %path,
                 "request start"
@@ -55,6 +91,7 @@ pub async fn inject_request_context(req: Request<Body>, next: Next) -> Response
     info!(
         request_id = ctx.request_id.as_deref().unwrap_or(""),
         status = %res.status(),
+        elapsed_ms = started_at.elapsed().as_millis() as u64,
         "request end"
     );
     attach_request_id(&mut res, ctx.request_id);
@@ -72,6 +109,45 @@ pub fn current() -> Option<RequestContext> {
     REQUEST_CONTEXT.try_with(|ctx| ctx.clone()).ok()
 }
 
+async fn enrich_staff_context(state: &AppState, auth: &AuthContext, ctx: &mut RequestContext) {
+    if auth.session_id.is_none() {
+        return;
+    }
+    let Some(store_uuid) = ctx.store_id.as_deref().and_then(|id| uuid::Uuid::parse_str(id).ok()) else {
+        return;
+    };
+    let Ok(staff_uuid) = uuid::Uuid::parse_str(&auth.actor_id) else {
+        return;
+    };
+    let row = sqlx::query(
+        r#"
+        SELECT st.tenant_id::text as tenant_id, r.key as role_key, s.display_name
+        FROM store_staff s
+        JOIN stores st ON st.id = s.store_id
+        LEFT JOIN store_roles r ON r.id = s.role_id
+        WHERE s.id = $1 AND s.store_id = $2
+        "#,
+    )
+    .bind(staff_uuid)
+    .bind(store_uuid)
+    .fetch_optional(&state.db)
+    .await;
+    let row = match row {
+        Ok(Some(row)) => row,
+        Ok(None) => return,
+        Err(err) => {
+            tracing::warn!(error = %err, "failed to load staff context");
+            return;
+        }
+    };
+    let tenant_id: String = row.get("tenant_id");
+    if ctx.tenant_id.is_none() {
+        ctx.tenant_id = Some(tenant_id);
+    }
+    ctx.role_key = row.get("role_key");
+    ctx.display_name = row.get("display_name");
+}
+
 fn extract_request_id(headers: &HeaderMap) -> Option<String> {
     if let Some(value) = headers.get("x-request-id")
         && let Ok(value) = value.to_str()
@@ -82,23 +158,50 @@ fn extract_request_id(headers: &HeaderMap) -> Option<String> {
     Some(uuid::Uuid::new_v4().to_string())
 }
 
-fn extract_ip_address(headers: &HeaderMap) -> Option<String> {
+// Proxies append to X-Forwarded-For, so the client is the first public address in the list;
+// private hops (load balancers, sidecars) are skipped. Falls back to X-Real-IP, then the TCP peer.
+fn extract_ip_address(headers: &HeaderMap, peer_addr: Option<SocketAddr>) -> Option<String> {
     if let Some(value) = headers.get("x-forwarded-for")
         && let Ok(value) = value.to_str()
-        && let Some(first) = value.split(',').next()
+        && let Some(ip) = value.split(',').filter_map(parse_ip).find(is_public_ip)
     {
-        let first = first.trim();
-        if !first.is_empty() {
-            return Some(first.to_string());
-        }
+        return Some(ip.to_string());
     }
     if let Some(value) = headers.get("x-real-ip")
         && let Ok(value) = value.to_str()
-        && !value.is_empty()
+        && let Some(ip) = parse_ip(value)
     {
-        return Some(value.to_string());
+        return Some(ip.to_string());
+    }
+    peer_addr.map(|addr| addr.ip().to_canonical().to_string())
+}
+
+fn parse_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim();
+    value
+        .parse::<IpAddr>()
+        .or_else(|_| value.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()
+        .map(|ip| ip.to_canonical())
+}
+
+fn is_public_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let shared = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || shared)
+        }
+        IpAddr::V6(ip) => {
+            let unique_local = (ip.segments()[0] & 0xfe00) == 0xfc00;
+            let link_local = (ip.segments()[0] & 0xffc0) == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
     }
-    None
 }
 
 fn extract_user_agent(headers: &HeaderMap) -> Option<String> {
@@ -118,3 +221,60 @@ fn current_trace_id() -> Option<String> {
         Some(trace_id.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_map(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn peer() -> Option<SocketAddr> {
+        Some("192.0.2.10:443".parse().unwrap())
+    }
+
+    #[test]
+    fn first_public_forwarded_address_wins() {
+        let headers = header_map(&[("x-forwarded-for", "1.2.3.4, 10.0.0.1")]);
+        assert_eq!(extract_ip_address(&headers, peer()).as_deref(), Some("1.2.3.4"));
+
+        let headers = header_map(&[("x-forwarded-for", "10.0.0.1, 100.64.0.2, 5.6.7.8:1234")]);
+        assert_eq!(extract_ip_address(&headers, peer()).as_deref(), Some("5.6.7.8"));
+    }
+
+    #[test]
+    fn private_only_forwarded_for_falls_back() {
+        let forwarded = ("x-forwarded-for", "10.0.0.1, 192.168.1.5, 127.0.0.1, fd00::1");
+        let headers_with_real_ip = header_map(&[forwarded, ("x-real-ip", "8.8.8.8")]);
+        assert_eq!(
+            extract_ip_address(&headers_with_real_ip, peer()).as_deref(),
+            Some("8.8.8.8")
+        );
+
+        assert_eq!(
+            extract_ip_address(&header_map(&[forwarded]), peer()).as_deref(),
+            Some("192.0.2.10")
+        );
+    }
+
+    #[test]
+    fn malformed_headers_are_ignored() {
+        let headers = header_map(&[("x-forwarded-for", "unknown, not-an-ip"), ("x-real-ip", "999.1.1.1")]);
+        assert_eq!(extract_ip_address(&headers, peer()).as_deref(), Some("192.0.2.10"));
+        assert_eq!(extract_ip_address(&headers, None), None);
+    }
+
+    #[test]
+    fn ipv4_mapped_peer_is_canonical() {
+        let peer = Some("[::ffff:203.0.113.7]:8080".parse().unwrap());
+        assert_eq!(
+            extract_ip_address(&HeaderMap::new(), peer).as_deref(),
+            Some("203.0.113.7")
+        );
+    }
+}
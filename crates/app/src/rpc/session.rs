@@ -7,7 +7,11 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
-use crate::{AppState, rpc::actor::AuthContext, rpc::json::ConnectError};
+use crate::{
+    AppState,
+    rpc::actor::AuthContext,
+    rpc::json::{ConnectError, ErrorCode},
+};
 use sqlx::Row;
 
 pub async fn require_active_staff_session(
@@ -21,51 +25,42 @@ pub async fn require_active_staff_session(
         return next.run(req).await;
     };
 
-    let Some(session_id) = auth.session_id.clone() else {
-        return next.run(req).await;
+    let session = match auth.session_id.as_deref() {
+        None => None,
+        Some(session_id) => {
+            let Some(store_id) = auth.store_id.as_deref() else {
+                return error_response(StatusCode::UNAUTHORIZED, "unauthenticated");
+            };
+            match (uuid::Uuid::parse_str(session_id), uuid::Uuid::parse_str(store_id)) {
+                (Ok(session_uuid), Ok(store_uuid)) => Some((session_uuid, store_uuid)),
+                _ => return error_response(StatusCode::UNAUTHORIZED, "invalid session"),
+            }
+        }
     };
-
-    let Some(store_id) = auth.store_id.clone() else {
-        return error_response(StatusCode::UNAUTHORIZED, "unauthenticated");
+    let Ok(staff_uuid) = uuid::Uuid::parse_str(&auth.actor_id) else {
+        if session.is_some() {
+            return error_response(StatusCode::UNAUTHORIZED, "invalid session");
+        }
+        return next.run(req).await;
     };
 
-    let session_uuid = match uuid::Uuid::parse_str(&session_id) {
-        Ok(id) => id,
-        Err(_) => return error_response(StatusCode::UNAUTHORIZED, "invalid session"),
+    let check = match check_staff_token(&state, staff_uuid, auth.issued_at, session).await {
+        Ok(check) => check,
+        Err(err) => {
+            tracing::error!(error = %err, "failed to check staff session");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "internal error");
+        }
     };
-    let staff_uuid = match uuid::Uuid::parse_str(&auth.actor_id) {
-        Ok(id) => id,
-        Err(_) => return error_response(StatusCode::UNAUTHORIZED, "invalid session"),
-    };
-    let store_uuid = match uuid::Uuid::parse_str(&store_id) {
-        Ok(id) => id,
-        Err(_) => return error_response(StatusCode::UNAUTHORIZED, "invalid session"),
-    };
-
-    let row = sqlx::query(
-        r#"
-        SELECT revoked_at, expires_at
-        FROM store_staff_sessions
-        WHERE id = $1 AND store_id = $2 AND staff_id = $3
-        "#,
-    )
-    .bind(session_uuid)
-    .bind(store_uuid)
-    .bind(staff_uuid)
-    .fetch_optional(&state.db)
-    .await;
-
-    let Some(row) = row.ok().flatten() else {
+    if check.token_revoked {
         return error_response(StatusCode::UNAUTHORIZED, "unauthenticated");
+    }
+    let Some((session_uuid, _)) = session else {
+        return next.run(req).await;
     };
-
-    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
-    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
-
-    if revoked_at.is_some() {
+    if !check.session_found || check.session_revoked_at.is_some() {
         return error_response(StatusCode::UNAUTHORIZED, "unauthenticated");
     }
-    if let Some(expires_at) = expires_at
+    if let Some(expires_at) = check.session_expires_at
         && expires_at <= chrono::Utc::now()
     {
         return error_response(StatusCode::UNAUTHORIZED, "unauthenticated");
@@ -85,15 +80,98 @@ pub async fn require_active_staff_session(
     next.run(req).await
 }
 
+struct StaffTokenCheck {
+    token_revoked: bool,
+    session_found: bool,
+    session_revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    session_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+// Token revocations and the session are read in one round trip. `iat` has one-second resolution, so a
+// token counts as revoked when `iat <= revoked_at` truncated to the second: one issued in the same second
+// as a revocation is rejected and the staff member signs in again. Tokens without iat are rejected by any
+// revocation. `to_timestamp(iat) <= revoked_at` is the same test and can use the (staff_id, revoked_at) index.
+async fn check_staff_token(
+    state: &AppState,
+    staff_uuid: uuid::Uuid,
+    issued_at: Option<i64>,
+    session: Option<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<StaffTokenCheck, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            EXISTS (
+                SELECT 1
+                FROM staff_token_revocations r
+                WHERE r.staff_id = $1 AND ($2::bigint IS NULL OR r.revoked_at >= to_timestamp($2))
+            ) AS token_revoked,
+            s.id IS NOT NULL AS session_found,
+            s.revoked_at,
+            s.expires_at
+        FROM (SELECT 1) AS request
+        LEFT JOIN store_staff_sessions s ON s.id = $3 AND s.store_id = $4 AND s.staff_id = $1
+        "#,
+    )
+    .bind(staff_uuid)
+    .bind(issued_at)
+    .bind(session.map(|(session_uuid, _)| session_uuid))
+    .bind(session.map(|(_, store_uuid)| store_uuid))
+    .fetch_one(&state.db)
+    .await?;
+    Ok(StaffTokenCheck {
+        token_revoked: row.get("token_revoked"),
+        session_found: row.get("session_found"),
+        session_revoked_at: row.get("revoked_at"),
+        session_expires_at: row.get("expires_at"),
+    })
+}
+
 fn error_response(status: StatusCode, message: &str) -> Response {
     let code = match status {
-        StatusCode::UNAUTHORIZED => crate::rpc::json::ErrorCode::Unauthenticated,
-        StatusCode::INTERNAL_SERVER_ERROR => crate::rpc::json::ErrorCode::Internal,
-        _ => crate::rpc::json::ErrorCode::PermissionDenied,
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthenticated,
+        StatusCode::INTERNAL_SERVER_ERROR => ErrorCode::Internal,
+        _ => ErrorCode::PermissionDenied,
     };
-    let body = Json(ConnectError {
-        code,
-        message: message.to_string(),
-    });
+    let body = Json(ConnectError::new(code, message.to_string()));
     (status, body).into_response()
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn tokens_issued_up_to_the_revocation_second_are_revoked(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let staff_id: uuid::Uuid = sqlx::query_scalar("INSERT INTO store_staff (store_id) VALUES ($1) RETURNING id")
+            .bind(store.store_id)
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        let revoked_at: chrono::DateTime<chrono::Utc> = "2026-10-16T00:00:10.700Z".parse().unwrap();
+        sqlx::query(
+            "INSERT INTO staff_token_revocations (staff_id, store_id, reason, revoked_at) VALUES ($1, $2, 'test', $3)",
+        )
+        .bind(staff_id)
+        .bind(store.store_id)
+        .bind(revoked_at)
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let second = revoked_at.timestamp();
+        for (iat, expected) in [
+            (Some(second - 1), true),
+            (Some(second), true),
+            (Some(second + 1), false),
+            (None, true),
+        ] {
+            let check = check_staff_token(&state, staff_id, iat, None).await.unwrap();
+            assert_eq!(check.token_revoked, expected, "iat {iat:?}");
+        }
+    }
+}
@@ -36,3 +36,15 @@ pub async fn validate_store_code(
     let resp = setup::service::validate_store_code(&state, req).await?;
     Ok((StatusCode::OK, Json(resp)))
 }
+
+pub async fn validate_store_setup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ValidateStoreSetupResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ValidateStoreSetupRequest>(&headers, body)?;
+    let (store_id, _tenant_id) =
+        crate::shared::store_context::resolve_store_context(&state, req.store, req.tenant).await?;
+    let resp = setup::service::validate_store_setup(&state, &store_id).await?;
+    Ok((StatusCode::OK, Json(resp)))
+}
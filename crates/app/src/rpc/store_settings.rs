@@ -9,7 +9,8 @@ use axum::{
 use crate::{
     AppState,
     pb::pb,
-    rpc::json::{ConnectError, parse_request},
+    rpc::json::{ConnectError, ErrorCode, parse_request},
+    shared::store_context::resolve_store_context,
     store_settings,
 };
 
@@ -20,7 +21,7 @@ pub async fn get_store_settings(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::GetStoreSettingsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::GetStoreSettingsRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let settings = store_settings::service::get_store_settings(&state, store_id, _tenant_id).await?;
     Ok((
         StatusCode::OK,
@@ -37,15 +38,12 @@ pub async fn update_store_settings(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpdateStoreSettingsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpdateStoreSettingsRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let settings = req.settings.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "settings is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "settings is required")),
         )
     })?;
     let settings = store_settings::service::update_store_settings(&state, store_id, tenant_id, settings, actor).await?;
@@ -64,15 +62,12 @@ pub async fn initialize_store_settings(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::InitializeStoreSettingsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::InitializeStoreSettingsRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let settings = req.settings.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "settings is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "settings is required")),
         )
     })?;
     let mall = req.mall.unwrap_or(pb::MallSettings {
@@ -98,7 +93,7 @@ pub async fn get_mall_settings(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::GetMallSettingsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::GetMallSettingsRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let mall = store_settings::service::get_mall_settings(&state, store_id, _tenant_id).await?;
     Ok((StatusCode::OK, Json(pb::GetMallSettingsResponse { mall: Some(mall) })))
 }
@@ -110,7 +105,7 @@ pub async fn update_mall_settings(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpdateMallSettingsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpdateMallSettingsRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let mall = req.mall.unwrap_or(pb::MallSettings {
         enabled: false,
@@ -131,7 +126,7 @@ pub async fn list_store_locations(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListStoreLocationsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListStoreLocationsRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let locations = store_settings::service::list_store_locations(&state, store_id).await?;
     Ok((StatusCode::OK, Json(pb::ListStoreLocationsResponse { locations })))
 }
@@ -143,15 +138,12 @@ pub async fn upsert_store_location(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpsertStoreLocationResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpsertStoreLocationRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let location = req.location.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "location is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "location is required")),
         )
     })?;
     let location = store_settings::service::upsert_store_location(&state, store_id, tenant_id, location, actor).await?;
@@ -170,7 +162,7 @@ pub async fn delete_store_location(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::DeleteStoreLocationResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::DeleteStoreLocationRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let deleted =
         store_settings::service::delete_store_location(&state, store_id, tenant_id, req.location_id, actor).await?;
@@ -184,7 +176,7 @@ pub async fn list_shipping_zones(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListShippingZonesResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListShippingZonesRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let zones = store_settings::service::list_shipping_zones(&state, store_id).await?;
     Ok((StatusCode::OK, Json(pb::ListShippingZonesResponse { zones })))
 }
@@ -196,15 +188,12 @@ pub async fn upsert_shipping_zone(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpsertShippingZoneResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpsertShippingZoneRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let zone = req.zone.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "zone is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "zone is required")),
         )
     })?;
     let zone = store_settings::service::upsert_shipping_zone(&state, store_id, tenant_id, zone, actor).await?;
@@ -221,7 +210,7 @@ pub async fn delete_shipping_zone(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::DeleteShippingZoneResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::DeleteShippingZoneRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let deleted =
         store_settings::service::delete_shipping_zone(&state, store_id, tenant_id, req.zone_id, actor).await?;
@@ -235,7 +224,7 @@ pub async fn list_shipping_rates(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListShippingRatesResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListShippingRatesRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let rates = store_settings::service::list_shipping_rates(&state, store_id, req.zone_id).await?;
     Ok((StatusCode::OK, Json(pb::ListShippingRatesResponse { rates })))
 }
@@ -247,15 +236,12 @@ pub async fn upsert_shipping_rate(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpsertShippingRateResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpsertShippingRateRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let rate = req.rate.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "rate is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "rate is required")),
         )
     })?;
     let rate = store_settings::service::upsert_shipping_rate(&state, store_id, tenant_id, rate, actor).await?;
@@ -272,7 +258,7 @@ pub async fn delete_shipping_rate(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::DeleteShippingRateResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::DeleteShippingRateRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let deleted =
         store_settings::service::delete_shipping_rate(&state, store_id, tenant_id, req.rate_id, actor).await?;
@@ -286,7 +272,7 @@ pub async fn list_tax_rules(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::ListTaxRulesResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::ListTaxRulesRequest>(&headers, body)?;
-    let (store_id, _tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, _tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let rules = store_settings::service::list_tax_rules(&state, store_id).await?;
     Ok((StatusCode::OK, Json(pb::ListTaxRulesResponse { rules })))
 }
@@ -298,15 +284,12 @@ pub async fn upsert_tax_rule(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpsertTaxRuleResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpsertTaxRuleRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let rule = req.rule.ok_or_else(|| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "rule is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "rule is required")),
         )
     })?;
     let rule = store_settings::service::upsert_tax_rule(&state, store_id, tenant_id, rule, actor).await?;
@@ -320,7 +303,7 @@ pub async fn delete_tax_rule(
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::DeleteTaxRuleResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::DeleteTaxRuleRequest>(&headers, body)?;
-    let (store_id, tenant_id) = store_settings::service::resolve_store_context(&state, req.store, req.tenant).await?;
+    let (store_id, tenant_id) = resolve_store_context(&state, req.store, req.tenant).await?;
     let actor = req.actor.or(actor_ctx);
     let deleted = store_settings::service::delete_tax_rule(&state, store_id, tenant_id, req.rule_id, actor).await?;
     Ok((StatusCode::OK, Json(pb::DeleteTaxRuleResponse { deleted })))
@@ -1,18 +1,21 @@
 use axum::{
     Json,
     body::Bytes,
-    extract::State,
-    http::{HeaderMap, StatusCode},
+    extract::{Extension, State},
+    http::{HeaderMap, StatusCode, header},
 };
 
 use crate::{
     AppState, cart,
-    identity::context::resolve_store_context_without_token_guard,
-    infrastructure::search::SearchProduct,
+    infrastructure::search::{self, SearchProduct},
     pages,
     pb::pb,
     product,
-    rpc::json::{ConnectError, parse_request, require_tenant_id},
+    rpc::{
+        actor::AuthContext,
+        json::{ConnectError, parse_request, require_tenant_id},
+    },
+    shared::store_context::resolve_store_context_without_token_guard,
 };
 
 pub async fn list_products(
@@ -39,9 +42,14 @@ pub async fn get_product(
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::GetProductResponse>), (StatusCode, Json<ConnectError>)> {
+    let locales = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(product::translation::preferred_locales)
+        .unwrap_or_default();
     let req = parse_request::<pb::GetProductRequest>(&headers, body)?;
     let tenant_id = require_tenant_id(req.tenant)?;
-    let product = product::service::get_product(&state, tenant_id, req.product_id).await?;
+    let product = product::service::get_product(&state, tenant_id, req.product_id, locales).await?;
     Ok((StatusCode::OK, Json(pb::GetProductResponse { product })))
 }
 
@@ -52,7 +60,8 @@ pub async fn search_products(
 ) -> Result<(StatusCode, Json<pb::SearchProductsResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::SearchProductsRequest>(&headers, body)?;
     let tenant_id = require_tenant_id(req.tenant)?;
-    let hits = state.search.search_products(&req.query, 50, &tenant_id).await?;
+    let sort = search::sort_expression(&req.sort)?;
+    let hits = state.search.search_products(&req.query, 50, &tenant_id, sort).await?;
     let products = hits_to_products(hits, tenant_id);
     Ok((
         StatusCode::OK,
@@ -87,6 +96,8 @@ fn hits_to_products(hits: Vec<SearchProduct>, tenant_id: String) -> Vec<pb::Prod
             variants: Vec::new(),
             updated_at: None,
             tax_rule_id: String::new(),
+            media: Vec::new(),
+            is_on_sale: false,
         })
         .filter(|p| !tenant_id.is_empty() && !p.id.is_empty())
         .collect()
@@ -94,11 +105,13 @@ fn hits_to_products(hits: Vec<SearchProduct>, tenant_id: String) -> Vec<pb::Prod
 
 pub async fn create_cart(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::CreateCartResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::CreateCartRequest>(&headers, body)?;
-    let cart = cart::service::create_cart(&state, req)
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::create_cart(&state, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::CreateCartResponse { cart: Some(cart) })))
@@ -106,11 +119,13 @@ pub async fn create_cart(
 
 pub async fn add_cart_item(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::AddCartItemResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::AddCartItemRequest>(&headers, body)?;
-    let cart = cart::service::add_cart_item(&state, req)
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::add_cart_item(&state, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::AddCartItemResponse { cart: Some(cart) })))
@@ -118,11 +133,13 @@ pub async fn add_cart_item(
 
 pub async fn update_cart_item(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::UpdateCartItemResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::UpdateCartItemRequest>(&headers, body)?;
-    let cart = cart::service::update_cart_item(&state, req)
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::update_cart_item(&state, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::UpdateCartItemResponse { cart: Some(cart) })))
@@ -130,11 +147,13 @@ pub async fn update_cart_item(
 
 pub async fn remove_cart_item(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::RemoveCartItemResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::RemoveCartItemRequest>(&headers, body)?;
-    let cart = cart::service::remove_cart_item(&state, req)
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::remove_cart_item(&state, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::RemoveCartItemResponse { cart: Some(cart) })))
@@ -142,11 +161,13 @@ pub async fn remove_cart_item(
 
 pub async fn get_cart(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::GetCartResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::GetCartRequest>(&headers, body)?;
-    let cart = cart::service::get_cart(&state, req)
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::get_cart(&state, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::GetCartResponse { cart: Some(cart) })))
@@ -154,17 +175,61 @@ pub async fn get_cart(
 
 pub async fn checkout(
     State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<(StatusCode, Json<pb::CheckoutResponse>), (StatusCode, Json<ConnectError>)> {
     let req = parse_request::<pb::CheckoutRequest>(&headers, body)?;
     let tenant_id = require_tenant_id(req.tenant.clone())?;
-    let order = cart::service::checkout(&state, tenant_id, req)
+    let access = cart_access(auth_ctx, &headers);
+    let order = cart::service::checkout(&state, tenant_id, req, &access)
         .await
         .map_err(|err| err.into_connect())?;
     Ok((StatusCode::OK, Json(pb::CheckoutResponse { order: Some(order) })))
 }
 
+pub async fn claim_cart(
+    State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ClaimCartResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ClaimCartRequest>(&headers, body)?;
+    let access = cart_access(auth_ctx, &headers);
+    let cart = cart::service::claim_cart(&state, req, &access)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(pb::ClaimCartResponse { cart: Some(cart) })))
+}
+
+pub async fn apply_promotion_code(
+    State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::ApplyPromotionCodeResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::ApplyPromotionCodeRequest>(&headers, body)?;
+    let access = cart_access(auth_ctx, &headers);
+    let resp = cart::service::apply_promotion_code(&state, req, &access)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
+pub async fn remove_promotion_code(
+    State(state): State<AppState>,
+    Extension(auth_ctx): Extension<Option<AuthContext>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<pb::RemovePromotionCodeResponse>), (StatusCode, Json<ConnectError>)> {
+    let req = parse_request::<pb::RemovePromotionCodeRequest>(&headers, body)?;
+    let access = cart_access(auth_ctx, &headers);
+    let resp = cart::service::remove_promotion_code(&state, req, &access)
+        .await
+        .map_err(|err| err.into_connect())?;
+    Ok((StatusCode::OK, Json(resp)))
+}
+
 pub async fn get_order(
     State(_state): State<AppState>,
     headers: HeaderMap,
@@ -173,3 +238,16 @@ pub async fn get_order(
     let _req = parse_request::<pb::GetOrderRequest>(&headers, body)?;
     Ok((StatusCode::OK, Json(pb::GetOrderResponse { order: None })))
 }
+
+fn cart_access(auth_ctx: Option<AuthContext>, headers: &HeaderMap) -> cart::service::CartAccess {
+    cart::service::CartAccess {
+        customer_id: auth_ctx
+            .filter(|ctx| ctx.actor_type == "customer")
+            .map(|ctx| ctx.actor_id),
+        cart_token: headers
+            .get("x-cart-token")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty()),
+    }
+}
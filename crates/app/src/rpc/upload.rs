@@ -0,0 +1,53 @@
+use axum::{
+    Json,
+    extract::{Multipart, State, multipart::MultipartError},
+    http::StatusCode,
+};
+
+use crate::{
+    AppState,
+    pb::pb,
+    product::{self, media::MediaUpload},
+    rpc::json::{ConnectError, ErrorCode},
+};
+
+/// `POST /upload/media`: multipart counterpart of CreateMediaAsset for clients sending the file itself.
+pub async fn upload_media(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<pb::CreateMediaAssetResponse>), (StatusCode, Json<ConnectError>)> {
+    let mut upload = MediaUpload {
+        store_id: String::new(),
+        owner_type: String::new(),
+        owner_id: String::new(),
+        content_type: String::new(),
+        bytes: Default::default(),
+    };
+    while let Some(field) = multipart.next_field().await.map_err(multipart_error)? {
+        match field.name().unwrap_or("") {
+            "store_id" => upload.store_id = field.text().await.map_err(multipart_error)?,
+            "owner_type" => upload.owner_type = field.text().await.map_err(multipart_error)?,
+            "owner_id" => upload.owner_id = field.text().await.map_err(multipart_error)?,
+            "file" => {
+                upload.content_type = field.content_type().unwrap_or("").to_string();
+                upload.bytes = field.bytes().await.map_err(multipart_error)?;
+            }
+            _ => {}
+        }
+    }
+    let asset = product::media::upload_media(&state, upload).await?;
+    Ok((
+        StatusCode::OK,
+        Json(pb::CreateMediaAssetResponse { asset: Some(asset) }),
+    ))
+}
+
+fn multipart_error(err: MultipartError) -> (StatusCode, Json<ConnectError>) {
+    let status = err.status();
+    let code = if status == StatusCode::PAYLOAD_TOO_LARGE {
+        ErrorCode::ResourceExhausted
+    } else {
+        ErrorCode::InvalidArgument
+    };
+    (status, Json(ConnectError::new(code, err.body_text())))
+}
@@ -3,9 +3,10 @@ use crate::{
     infrastructure::audit,
     infrastructure::db,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::audit_action::{IdentityAuditAction, MallSettingsAuditAction, StoreSettingsAuditAction},
-    shared::validation::StoreCode,
+    shared::ids::parse_uuid,
+    shared::validation::{Email, StoreCode},
     store_settings::{
         repository::{PgStoreSettingsRepository, StoreSettingsRepository},
         service::{default_mall_settings, default_store_settings},
@@ -17,6 +18,7 @@ use argon2::{
 };
 use axum::{Json, http::StatusCode};
 use rand_core::OsRng;
+use sqlx::Row;
 
 pub async fn initialize_store(
     state: &AppState,
@@ -26,31 +28,29 @@ pub async fn initialize_store(
     if req.store_name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "store_name is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "store_name is required")),
         ));
     }
     if req.owner_email.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "owner_email is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "owner_email is required")),
         ));
     }
     if req.owner_password.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "owner_password is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "owner_password is required",
+            )),
         ));
     }
+    state
+        .password_strength
+        .validate("owner_password", &req.owner_password)?;
     let store_code = StoreCode::parse(&req.store_code)?;
+    let owner_email = Email::parse(&req.owner_email)?;
 
     let mut tx = state.db.begin().await.map_err(db::error)?;
 
@@ -62,10 +62,7 @@ pub async fn initialize_store(
     if existing.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::AlreadyExists,
-                message: "store already exists".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::AlreadyExists, "store already exists")),
         ));
     }
 
@@ -77,10 +74,7 @@ pub async fn initialize_store(
     if existing_code.is_some() {
         return Err((
             StatusCode::CONFLICT,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::AlreadyExists,
-                message: "store_code already exists".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::AlreadyExists, "store_code already exists")),
         ));
     }
 
@@ -192,7 +186,7 @@ pub async fn initialize_store(
     )
     .bind(owner_staff_id)
     .bind(store_id)
-    .bind(&req.owner_email)
+    .bind(owner_email.as_str())
     .bind(Option::<String>::None)
     .bind(Option::<String>::None)
     .bind(password_hash)
@@ -221,79 +215,86 @@ pub async fn initialize_store(
         })
         .unwrap_or_else(|| "system".to_string());
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.to_string()),
-            actor_id: actor_id.clone(),
-            actor_type: actor_type.clone(),
-            action: StoreSettingsAuditAction::Initialize.into(),
-            target_type: Some("store_settings".to_string()),
-            target_id: Some(store_id.to_string()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "store_id": store_id.to_string(),
-                "store_name": req.store_name,
-                "store_code": store_code.as_str(),
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.to_string()),
+                actor_id: actor_id.clone(),
+                actor_type: actor_type.clone(),
+                action: StoreSettingsAuditAction::Initialize.into(),
+                target_type: Some("store_settings".to_string()),
+                target_id: Some(store_id.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "store_id": store_id.to_string(),
+                    "store_name": req.store_name,
+                    "store_code": store_code.as_str(),
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.to_string()),
-            actor_id: actor_id.clone(),
-            actor_type: actor_type.clone(),
-            action: MallSettingsAuditAction::Initialize.into(),
-            target_type: Some("mall_settings".to_string()),
-            target_id: Some(store_id.to_string()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "store_id": store_id.to_string(),
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.to_string()),
+                actor_id: actor_id.clone(),
+                actor_type: actor_type.clone(),
+                action: MallSettingsAuditAction::Initialize.into(),
+                target_type: Some("mall_settings".to_string()),
+                target_id: Some(store_id.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "store_id": store_id.to_string(),
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
-    audit::record_tx(
-        &mut tx,
-        audit::AuditInput {
-            store_id: Some(store_id.to_string()),
-            actor_id,
-            actor_type,
-            action: IdentityAuditAction::StaffCreate.into(),
-            target_type: Some("store_staff".to_string()),
-            target_id: Some(owner_staff_id.to_string()),
-            request_id: None,
-            ip_address: None,
-            user_agent: None,
-            before_json: None,
-            after_json: Some(serde_json::json!({
-                "staff_id": owner_staff_id.to_string(),
-                "store_id": store_id.to_string(),
-                "role_id": owner_role_id.to_string(),
-                "role_key": "owner",
-                "email": req.owner_email,
-            })),
-            metadata_json: None,
-        },
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit::AuditInput {
+                store_id: Some(store_id.to_string()),
+                actor_id,
+                actor_type,
+                action: IdentityAuditAction::StaffCreate.into(),
+                target_type: Some("store_staff".to_string()),
+                target_id: Some(owner_staff_id.to_string()),
+                request_id: None,
+                ip_address: None,
+                user_agent: None,
+                before_json: None,
+                after_json: Some(serde_json::json!({
+                    "staff_id": owner_staff_id.to_string(),
+                    "store_id": store_id.to_string(),
+                    "role_id": owner_role_id.to_string(),
+                    "role_key": "owner",
+                    "email": owner_email.as_str(),
+                })),
+                metadata_json: None,
+            },
+        )
+        .await?;
 
     tx.commit().await.map_err(db::error)?;
+    state.store_cache.invalidate_store(&store_id.to_string());
 
     // Ensure search settings exist (safe to call repeatedly).
-    let _ = state.search.ensure_settings().await;
+    let _ = state.search.ensure_settings(None).await;
 
     Ok(pb::InitializeStoreResponse {
         tenant_id: tenant_id.to_string(),
@@ -336,6 +337,95 @@ pub async fn validate_store_code(
     })
 }
 
+pub async fn validate_store_setup(
+    state: &AppState,
+    store_id: &str,
+) -> Result<pb::ValidateStoreSetupResponse, (StatusCode, Json<ConnectError>)> {
+    let store_uuid = parse_uuid(store_id, "store_id")?;
+    let row = sqlx::query(
+        r#"
+        SELECT
+            EXISTS (
+                SELECT 1
+                FROM products p
+                JOIN product_skus v ON v.product_id = p.id AND v.status = 'active'
+                WHERE p.store_id = $1 AND p.status = 'active'
+            ) as has_active_product,
+            EXISTS (
+                SELECT 1 FROM store_locations WHERE store_id = $1 AND status = 'active'
+            ) as has_active_location,
+            EXISTS (
+                SELECT 1
+                FROM shipping_zones z
+                JOIN shipping_rates r ON r.zone_id = z.id
+                WHERE z.store_id = $1
+            ) as has_shipping_rate,
+            EXISTS (SELECT 1 FROM tax_rules WHERE store_id = $1) as has_tax_rule,
+            COALESCE(btrim(ps.contact_email), '') <> '' as has_contact_email,
+            COALESCE(btrim(ps.legal_name), '') <> '' as has_legal_name,
+            COALESCE(ps.https_enabled, false) as https_enabled
+        FROM stores s
+        LEFT JOIN store_profile_settings ps ON ps.store_id = s.id
+        WHERE s.id = $1
+        "#,
+    )
+    .bind(store_uuid)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(db::error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ConnectError::new(ErrorCode::NotFound, "store not found")),
+        )
+    })?;
+
+    let checks = vec![
+        setup_check(
+            "active_product",
+            row.get("has_active_product"),
+            "at least one active product with an active variant is required",
+        ),
+        setup_check(
+            "active_location",
+            row.get("has_active_location"),
+            "at least one active store location is required",
+        ),
+        setup_check(
+            "shipping_rate",
+            row.get("has_shipping_rate"),
+            "at least one shipping zone with a rate is required",
+        ),
+        setup_check("tax_rule", row.get("has_tax_rule"), "at least one tax rule is required"),
+        setup_check(
+            "contact_email",
+            row.get("has_contact_email"),
+            "store_settings.contact_email is required",
+        ),
+        setup_check(
+            "legal_name",
+            row.get("has_legal_name"),
+            "store_settings.legal_name is required",
+        ),
+        setup_check("https_enabled", row.get("https_enabled"), "https must be enabled"),
+    ];
+    let ready = checks.iter().all(|check| check.passed);
+
+    Ok(pb::ValidateStoreSetupResponse { checks, ready })
+}
+
+fn setup_check(check_name: &str, passed: bool, failure_message: &str) -> pb::SetupCheckResult {
+    pb::SetupCheckResult {
+        check_name: check_name.to_string(),
+        passed,
+        message: if passed {
+            "ok".to_string()
+        } else {
+            failure_message.to_string()
+        },
+    }
+}
+
 fn hash_password(password: &str) -> Result<String, (StatusCode, Json<ConnectError>)> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
@@ -345,10 +435,7 @@ fn hash_password(password: &str) -> Result<String, (StatusCode, Json<ConnectErro
         .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::Internal,
-                    message: "failed to hash password".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::Internal, "failed to hash password")),
             )
         })
 }
@@ -2,8 +2,13 @@
 pub enum AuditAction {
     ProductCreate,
     ProductUpdate,
+    ProductTranslationUpsert,
+    ProductDelete,
+    CategoryDelete,
     VariantCreate,
     VariantUpdate,
+    VariantBulkAxisUpdate,
+    VariantMediaSet,
     InventorySet,
     StoreSettingsUpdate,
     StoreSettingsInitialize,
@@ -19,12 +24,14 @@ pub enum AuditAction {
     TaxRuleDelete,
     PromotionCreate,
     PromotionUpdate,
+    PromotionCodeCreate,
     AuctionUpdate,
     AuctionCreate,
     AuctionBid,
     AuctionEnd,
     AuctionApprove,
     OrderUpdateStatus,
+    CartCheckout,
     ShipmentCreate,
     ShipmentUpdateStatus,
     IdentitySignIn,
@@ -38,66 +45,28 @@ pub enum AuditAction {
     IdentityRoleUpdate,
     IdentityRoleDelete,
     IdentityOwnerTransfer,
+    IdentityPasswordReset,
+    IdentityPasswordChange,
+    IdentityStaffUnlock,
     CustomerCreate,
     CustomerUpdate,
     CustomerIdentityUpsert,
     CustomerAddressUpsert,
+    CustomerAddressDelete,
 }
 
 impl AuditAction {
-    pub fn as_str(self) -> &'static str {
-        match self {
-            AuditAction::ProductCreate => "product.create",
-            AuditAction::ProductUpdate => "product.update",
-            AuditAction::VariantCreate => "variant.create",
-            AuditAction::VariantUpdate => "variant.update",
-            AuditAction::InventorySet => "inventory.set",
-            AuditAction::StoreSettingsUpdate => "store_settings.update",
-            AuditAction::StoreSettingsInitialize => "store_settings.initialize",
-            AuditAction::MallSettingsInitialize => "mall_settings.initialize",
-            AuditAction::MallSettingsUpdate => "mall_settings.update",
-            AuditAction::StoreLocationUpsert => "store_location.upsert",
-            AuditAction::StoreLocationDelete => "store_location.delete",
-            AuditAction::ShippingZoneUpsert => "shipping_zone.upsert",
-            AuditAction::ShippingZoneDelete => "shipping_zone.delete",
-            AuditAction::ShippingRateUpsert => "shipping_rate.upsert",
-            AuditAction::ShippingRateDelete => "shipping_rate.delete",
-            AuditAction::TaxRuleUpsert => "tax_rule.upsert",
-            AuditAction::TaxRuleDelete => "tax_rule.delete",
-            AuditAction::PromotionCreate => "promotion.create",
-            AuditAction::PromotionUpdate => "promotion.update",
-            AuditAction::AuctionUpdate => "auction.update",
-            AuditAction::AuctionCreate => "auction.create",
-            AuditAction::AuctionBid => "auction.bid",
-            AuditAction::AuctionEnd => "auction.end",
-            AuditAction::AuctionApprove => "auction.approve",
-            AuditAction::OrderUpdateStatus => "order.update_status",
-            AuditAction::ShipmentCreate => "shipment.create",
-            AuditAction::ShipmentUpdateStatus => "shipment.update_status",
-            AuditAction::IdentitySignIn => "identity.sign_in",
-            AuditAction::IdentitySignOut => "identity.sign_out",
-            AuditAction::IdentityStaffCreate => "identity.staff_create",
-            AuditAction::IdentityStaffUpdate => "identity.staff_update",
-            AuditAction::IdentityStaffInvite => "identity.staff_invite",
-            AuditAction::IdentityInviteAccept => "identity.invite_accept",
-            AuditAction::IdentityRoleCreate => "identity.role_create",
-            AuditAction::IdentityRoleAssign => "identity.role_assign",
-            AuditAction::IdentityRoleUpdate => "identity.role_update",
-            AuditAction::IdentityRoleDelete => "identity.role_delete",
-            AuditAction::IdentityOwnerTransfer => "identity.owner_transfer",
-            AuditAction::CustomerCreate => "customer.create",
-            AuditAction::CustomerUpdate => "customer.update",
-            AuditAction::CustomerIdentityUpsert => "customer.identity_upsert",
-            AuditAction::CustomerAddressUpsert => "customer.address_upsert",
-        }
-    }
-
     pub fn label(self) -> &'static str {
         match self {
             AuditAction::ProductCreate => "Product created",
             AuditAction::ProductUpdate => "Product updated",
+            AuditAction::ProductTranslationUpsert => "Product translation saved",
+            AuditAction::ProductDelete => "Product deleted",
+            AuditAction::CategoryDelete => "Category deleted",
             AuditAction::VariantCreate => "Variant created",
             AuditAction::VariantUpdate => "Variant updated",
+            AuditAction::VariantBulkAxisUpdate => "Variant axis values updated",
+            AuditAction::VariantMediaSet => "Variant media updated",
             AuditAction::InventorySet => "Inventory set",
             AuditAction::StoreSettingsUpdate => "Store settings updated",
             AuditAction::StoreSettingsInitialize => "Store settings initialized",
@@ -113,12 +82,14 @@ impl AuditAction {
             AuditAction::TaxRuleDelete => "Tax rule deleted",
             AuditAction::PromotionCreate => "Promotion created",
             AuditAction::PromotionUpdate => "Promotion updated",
+            AuditAction::PromotionCodeCreate => "Promotion code created",
             AuditAction::AuctionUpdate => "Auction updated",
             AuditAction::AuctionCreate => "Auction created",
             AuditAction::AuctionBid => "Auction bid placed",
             AuditAction::AuctionEnd => "Auction ended",
             AuditAction::AuctionApprove => "Auction approved",
             AuditAction::OrderUpdateStatus => "Order status updated",
+            AuditAction::CartCheckout => "Order created from cart",
             AuditAction::ShipmentCreate => "Shipment created",
             AuditAction::ShipmentUpdateStatus => "Shipment status updated",
             AuditAction::IdentitySignIn => "Signed in",
@@ -132,10 +103,14 @@ impl AuditAction {
             AuditAction::IdentityRoleUpdate => "Role updated",
             AuditAction::IdentityRoleDelete => "Role deleted",
             AuditAction::IdentityOwnerTransfer => "Owner transferred",
+            AuditAction::IdentityPasswordReset => "Password reset",
+            AuditAction::IdentityPasswordChange => "Password changed",
+            AuditAction::IdentityStaffUnlock => "Staff unlocked",
             AuditAction::CustomerCreate => "Customer created",
             AuditAction::CustomerUpdate => "Customer updated",
             AuditAction::CustomerIdentityUpsert => "Customer identity saved",
             AuditAction::CustomerAddressUpsert => "Customer address saved",
+            AuditAction::CustomerAddressDelete => "Customer address deleted",
         }
     }
 }
@@ -143,8 +118,13 @@ impl AuditAction {
 pub const ALL_AUDIT_ACTIONS: &[AuditAction] = &[
     AuditAction::ProductCreate,
     AuditAction::ProductUpdate,
+    AuditAction::ProductTranslationUpsert,
+    AuditAction::ProductDelete,
+    AuditAction::CategoryDelete,
     AuditAction::VariantCreate,
     AuditAction::VariantUpdate,
+    AuditAction::VariantBulkAxisUpdate,
+    AuditAction::VariantMediaSet,
     AuditAction::InventorySet,
     AuditAction::StoreSettingsUpdate,
     AuditAction::StoreSettingsInitialize,
@@ -160,12 +140,14 @@ pub const ALL_AUDIT_ACTIONS: &[AuditAction] = &[
     AuditAction::TaxRuleDelete,
     AuditAction::PromotionCreate,
     AuditAction::PromotionUpdate,
+    AuditAction::PromotionCodeCreate,
     AuditAction::AuctionUpdate,
     AuditAction::AuctionCreate,
     AuditAction::AuctionBid,
     AuditAction::AuctionEnd,
     AuditAction::AuctionApprove,
     AuditAction::OrderUpdateStatus,
+    AuditAction::CartCheckout,
     AuditAction::ShipmentCreate,
     AuditAction::ShipmentUpdateStatus,
     AuditAction::IdentitySignIn,
@@ -179,22 +161,35 @@ pub const ALL_AUDIT_ACTIONS: &[AuditAction] = &[
     AuditAction::IdentityRoleUpdate,
     AuditAction::IdentityRoleDelete,
     AuditAction::IdentityOwnerTransfer,
+    AuditAction::IdentityPasswordReset,
+    AuditAction::IdentityPasswordChange,
+    AuditAction::IdentityStaffUnlock,
     AuditAction::CustomerCreate,
     AuditAction::CustomerUpdate,
     AuditAction::CustomerIdentityUpsert,
     AuditAction::CustomerAddressUpsert,
+    AuditAction::CustomerAddressDelete,
 ];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProductAuditAction {
     Create,
     Update,
+    TranslationUpsert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CategoryAuditAction {
+    Delete,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VariantAuditAction {
     Create,
     Update,
+    BulkAxisUpdate,
+    MediaSet,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -242,6 +237,7 @@ pub enum TaxRuleAuditAction {
 pub enum PromotionAuditAction {
     Create,
     Update,
+    CodeCreate,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -258,6 +254,11 @@ pub enum OrderAuditAction {
     UpdateStatus,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CartAuditAction {
+    Checkout,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShipmentAuditAction {
     Create,
@@ -277,6 +278,9 @@ pub enum IdentityAuditAction {
     RoleUpdate,
     RoleDelete,
     OwnerTransfer,
+    PasswordReset,
+    PasswordChange,
+    StaffUnlock,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -285,6 +289,7 @@ pub enum CustomerAuditAction {
     Update,
     IdentityUpsert,
     AddressUpsert,
+    AddressDelete,
 }
 
 impl From<ProductAuditAction> for AuditAction {
@@ -292,6 +297,16 @@ impl From<ProductAuditAction> for AuditAction {
         match action {
             ProductAuditAction::Create => AuditAction::ProductCreate,
             ProductAuditAction::Update => AuditAction::ProductUpdate,
+            ProductAuditAction::TranslationUpsert => AuditAction::ProductTranslationUpsert,
+            ProductAuditAction::Delete => AuditAction::ProductDelete,
+        }
+    }
+}
+
+impl From<CategoryAuditAction> for AuditAction {
+    fn from(action: CategoryAuditAction) -> Self {
+        match action {
+            CategoryAuditAction::Delete => AuditAction::CategoryDelete,
         }
     }
 }
@@ -301,6 +316,8 @@ impl From<VariantAuditAction> for AuditAction {
         match action {
             VariantAuditAction::Create => AuditAction::VariantCreate,
             VariantAuditAction::Update => AuditAction::VariantUpdate,
+            VariantAuditAction::BulkAxisUpdate => AuditAction::VariantBulkAxisUpdate,
+            VariantAuditAction::MediaSet => AuditAction::VariantMediaSet,
         }
     }
 }
@@ -372,6 +389,7 @@ impl From<PromotionAuditAction> for AuditAction {
         match action {
             PromotionAuditAction::Create => AuditAction::PromotionCreate,
             PromotionAuditAction::Update => AuditAction::PromotionUpdate,
+            PromotionAuditAction::CodeCreate => AuditAction::PromotionCodeCreate,
         }
     }
 }
@@ -396,6 +414,14 @@ impl From<OrderAuditAction> for AuditAction {
     }
 }
 
+impl From<CartAuditAction> for AuditAction {
+    fn from(action: CartAuditAction) -> Self {
+        match action {
+            CartAuditAction::Checkout => AuditAction::CartCheckout,
+        }
+    }
+}
+
 impl From<ShipmentAuditAction> for AuditAction {
     fn from(action: ShipmentAuditAction) -> Self {
         match action {
@@ -419,6 +445,9 @@ impl From<IdentityAuditAction> for AuditAction {
             IdentityAuditAction::RoleUpdate => AuditAction::IdentityRoleUpdate,
             IdentityAuditAction::RoleDelete => AuditAction::IdentityRoleDelete,
             IdentityAuditAction::OwnerTransfer => AuditAction::IdentityOwnerTransfer,
+            IdentityAuditAction::PasswordReset => AuditAction::IdentityPasswordReset,
+            IdentityAuditAction::PasswordChange => AuditAction::IdentityPasswordChange,
+            IdentityAuditAction::StaffUnlock => AuditAction::IdentityStaffUnlock,
         }
     }
 }
@@ -430,6 +459,183 @@ impl From<CustomerAuditAction> for AuditAction {
             CustomerAuditAction::Update => AuditAction::CustomerUpdate,
             CustomerAuditAction::IdentityUpsert => AuditAction::CustomerIdentityUpsert,
             CustomerAuditAction::AddressUpsert => AuditAction::CustomerAddressUpsert,
+            CustomerAuditAction::AddressDelete => AuditAction::CustomerAddressDelete,
         }
     }
 }
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+pub trait AuditActionTrait: sealed::Sealed + Copy + Into<AuditAction> {
+    fn as_str(&self) -> &'static str {
+        Into::<AuditAction>::into(*self).as_str()
+    }
+
+    fn category(&self) -> &'static str {
+        Into::<AuditAction>::into(*self).category()
+    }
+}
+
+impl sealed::Sealed for AuditAction {}
+impl AuditActionTrait for AuditAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ProductCreate => "product.create",
+            AuditAction::ProductUpdate => "product.update",
+            AuditAction::ProductTranslationUpsert => "product.translation_upsert",
+            AuditAction::ProductDelete => "product.delete",
+            AuditAction::CategoryDelete => "category.delete",
+            AuditAction::VariantCreate => "variant.create",
+            AuditAction::VariantUpdate => "variant.update",
+            AuditAction::VariantBulkAxisUpdate => "variant.bulk_axis_update",
+            AuditAction::VariantMediaSet => "variant.media_set",
+            AuditAction::InventorySet => "inventory.set",
+            AuditAction::StoreSettingsUpdate => "store_settings.update",
+            AuditAction::StoreSettingsInitialize => "store_settings.initialize",
+            AuditAction::MallSettingsInitialize => "mall_settings.initialize",
+            AuditAction::MallSettingsUpdate => "mall_settings.update",
+            AuditAction::StoreLocationUpsert => "store_location.upsert",
+            AuditAction::StoreLocationDelete => "store_location.delete",
+            AuditAction::ShippingZoneUpsert => "shipping_zone.upsert",
+            AuditAction::ShippingZoneDelete => "shipping_zone.delete",
+            AuditAction::ShippingRateUpsert => "shipping_rate.upsert",
+            AuditAction::ShippingRateDelete => "shipping_rate.delete",
+            AuditAction::TaxRuleUpsert => "tax_rule.upsert",
+            AuditAction::TaxRuleDelete => "tax_rule.delete",
+            AuditAction::PromotionCreate => "promotion.create",
+            AuditAction::PromotionUpdate => "promotion.update",
+            AuditAction::PromotionCodeCreate => "promotion.code_create",
+            AuditAction::AuctionUpdate => "auction.update",
+            AuditAction::AuctionCreate => "auction.create",
+            AuditAction::AuctionBid => "auction.bid",
+            AuditAction::AuctionEnd => "auction.end",
+            AuditAction::AuctionApprove => "auction.approve",
+            AuditAction::OrderUpdateStatus => "order.update_status",
+            AuditAction::CartCheckout => "order.created",
+            AuditAction::ShipmentCreate => "shipment.create",
+            AuditAction::ShipmentUpdateStatus => "shipment.update_status",
+            AuditAction::IdentitySignIn => "identity.sign_in",
+            AuditAction::IdentitySignOut => "identity.sign_out",
+            AuditAction::IdentityStaffCreate => "identity.staff_create",
+            AuditAction::IdentityStaffUpdate => "identity.staff_update",
+            AuditAction::IdentityStaffInvite => "identity.staff_invite",
+            AuditAction::IdentityInviteAccept => "identity.invite_accept",
+            AuditAction::IdentityRoleCreate => "identity.role_create",
+            AuditAction::IdentityRoleAssign => "identity.role_assign",
+            AuditAction::IdentityRoleUpdate => "identity.role_update",
+            AuditAction::IdentityRoleDelete => "identity.role_delete",
+            AuditAction::IdentityOwnerTransfer => "identity.owner_transfer",
+            AuditAction::IdentityPasswordReset => "identity.password_reset",
+            AuditAction::IdentityPasswordChange => "identity.password_change",
+            AuditAction::IdentityStaffUnlock => "identity.staff_unlock",
+            AuditAction::CustomerCreate => "customer.create",
+            AuditAction::CustomerUpdate => "customer.update",
+            AuditAction::CustomerIdentityUpsert => "customer.identity_upsert",
+            AuditAction::CustomerAddressUpsert => "customer.address_upsert",
+            AuditAction::CustomerAddressDelete => "customer.address_delete",
+        }
+    }
+
+    fn category(&self) -> &'static str {
+        match self {
+            AuditAction::ProductCreate
+            | AuditAction::ProductUpdate
+            | AuditAction::ProductTranslationUpsert
+            | AuditAction::ProductDelete => "product",
+            AuditAction::CategoryDelete => "category",
+            AuditAction::VariantCreate
+            | AuditAction::VariantUpdate
+            | AuditAction::VariantBulkAxisUpdate
+            | AuditAction::VariantMediaSet => "variant",
+            AuditAction::InventorySet => "inventory",
+            AuditAction::StoreSettingsUpdate | AuditAction::StoreSettingsInitialize => "store_settings",
+            AuditAction::MallSettingsInitialize | AuditAction::MallSettingsUpdate => "mall_settings",
+            AuditAction::StoreLocationUpsert | AuditAction::StoreLocationDelete => "store_location",
+            AuditAction::ShippingZoneUpsert | AuditAction::ShippingZoneDelete => "shipping_zone",
+            AuditAction::ShippingRateUpsert | AuditAction::ShippingRateDelete => "shipping_rate",
+            AuditAction::TaxRuleUpsert | AuditAction::TaxRuleDelete => "tax_rule",
+            AuditAction::PromotionCreate | AuditAction::PromotionUpdate | AuditAction::PromotionCodeCreate => {
+                "promotion"
+            }
+            AuditAction::AuctionUpdate
+            | AuditAction::AuctionCreate
+            | AuditAction::AuctionBid
+            | AuditAction::AuctionEnd
+            | AuditAction::AuctionApprove => "auction",
+            AuditAction::OrderUpdateStatus | AuditAction::CartCheckout => "order",
+            AuditAction::ShipmentCreate | AuditAction::ShipmentUpdateStatus => "shipment",
+            AuditAction::IdentitySignIn
+            | AuditAction::IdentitySignOut
+            | AuditAction::IdentityStaffCreate
+            | AuditAction::IdentityStaffUpdate
+            | AuditAction::IdentityStaffInvite
+            | AuditAction::IdentityInviteAccept
+            | AuditAction::IdentityRoleCreate
+            | AuditAction::IdentityRoleAssign
+            | AuditAction::IdentityRoleUpdate
+            | AuditAction::IdentityRoleDelete
+            | AuditAction::IdentityOwnerTransfer
+            | AuditAction::IdentityPasswordReset
+            | AuditAction::IdentityPasswordChange
+            | AuditAction::IdentityStaffUnlock => "identity",
+            AuditAction::CustomerCreate
+            | AuditAction::CustomerUpdate
+            | AuditAction::CustomerIdentityUpsert
+            | AuditAction::CustomerAddressUpsert
+            | AuditAction::CustomerAddressDelete => "customer",
+        }
+    }
+}
+
+impl sealed::Sealed for ProductAuditAction {}
+impl AuditActionTrait for ProductAuditAction {}
+
+impl sealed::Sealed for CategoryAuditAction {}
+impl AuditActionTrait for CategoryAuditAction {}
+
+impl sealed::Sealed for VariantAuditAction {}
+impl AuditActionTrait for VariantAuditAction {}
+
+impl sealed::Sealed for InventoryAuditAction {}
+impl AuditActionTrait for InventoryAuditAction {}
+
+impl sealed::Sealed for StoreSettingsAuditAction {}
+impl AuditActionTrait for StoreSettingsAuditAction {}
+
+impl sealed::Sealed for MallSettingsAuditAction {}
+impl AuditActionTrait for MallSettingsAuditAction {}
+
+impl sealed::Sealed for StoreLocationAuditAction {}
+impl AuditActionTrait for StoreLocationAuditAction {}
+
+impl sealed::Sealed for ShippingZoneAuditAction {}
+impl AuditActionTrait for ShippingZoneAuditAction {}
+
+impl sealed::Sealed for ShippingRateAuditAction {}
+impl AuditActionTrait for ShippingRateAuditAction {}
+
+impl sealed::Sealed for TaxRuleAuditAction {}
+impl AuditActionTrait for TaxRuleAuditAction {}
+
+impl sealed::Sealed for PromotionAuditAction {}
+impl AuditActionTrait for PromotionAuditAction {}
+
+impl sealed::Sealed for AuctionAuditAction {}
+impl AuditActionTrait for AuctionAuditAction {}
+
+impl sealed::Sealed for OrderAuditAction {}
+impl AuditActionTrait for OrderAuditAction {}
+
+impl sealed::Sealed for CartAuditAction {}
+impl AuditActionTrait for CartAuditAction {}
+
+impl sealed::Sealed for ShipmentAuditAction {}
+impl AuditActionTrait for ShipmentAuditAction {}
+
+impl sealed::Sealed for IdentityAuditAction {}
+impl AuditActionTrait for IdentityAuditAction {}
+
+impl sealed::Sealed for CustomerAuditAction {}
+impl AuditActionTrait for CustomerAuditAction {}
@@ -1,8 +1,8 @@
-use crate::{infrastructure::audit, pb::pb, shared::audit_action::AuditAction};
+use crate::{infrastructure::audit, pb::pb, shared::audit_action::AuditActionTrait};
 
 pub fn audit_input(
     store_id: Option<String>,
-    action: AuditAction,
+    action: impl AuditActionTrait,
     target_type: Option<&str>,
     target_id: Option<String>,
     before_json: Option<serde_json::Value>,
@@ -14,7 +14,7 @@ pub fn audit_input(
         store_id,
         actor_id,
         actor_type,
-        action,
+        action: action.into(),
         target_type: target_type.map(|v| v.to_string()),
         target_id,
         request_id: None,
@@ -0,0 +1,80 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+const TOKEN_BYTES: usize = 32;
+
+/// Opaque bearer token handed to a recipient once (e.g. staff invites).
+/// Only `hash()` is stored, so lookups compare hashes and the plaintext never reaches the DB.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenValue(String);
+
+impl TokenValue {
+    /// 32 random bytes from the OS RNG, URL-safe base64 without padding (43 chars).
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    pub fn from_submitted(value: &str) -> Self {
+        Self(value.trim().to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Hex-encoded SHA-256 of the token.
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.0.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[test]
+    fn generated_tokens_are_43_url_safe_chars() {
+        let token = TokenValue::generate();
+        assert_eq!(token.as_str().len(), 43);
+        assert!(
+            token
+                .as_str()
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{token:?}"
+        );
+        assert_ne!(token, TokenValue::generate());
+    }
+
+    #[test]
+    fn submitted_tokens_hash_like_the_issued_one() {
+        let token = TokenValue::generate();
+        let hash = token.hash();
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, token.as_str());
+        let submitted = TokenValue::from_submitted(&format!(" {}\n", token.as_str()));
+        assert_eq!(submitted.hash(), hash);
+        assert_ne!(TokenValue::generate().hash(), hash);
+    }
+
+    // The invite token migration hashes existing plaintext tokens in SQL; both sides must agree.
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn hash_matches_the_migration_hash(db: PgPool) {
+        let token = TokenValue::generate();
+        let hashed: String = sqlx::query_scalar("SELECT encode(sha256(convert_to($1, 'UTF8')), 'hex')")
+            .bind(token.as_str())
+            .fetch_one(&db)
+            .await
+            .unwrap();
+        assert_eq!(hashed, token.hash());
+    }
+}
@@ -1,7 +1,7 @@
 use axum::{Json, http::StatusCode};
 use std::fmt;
 
-use crate::rpc::json::ConnectError;
+use crate::rpc::json::{ConnectError, ErrorCode};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub struct TenantId(uuid::Uuid);
@@ -195,10 +195,10 @@ pub fn parse_uuid(id: &str, field: &str) -> Result<uuid::Uuid, (StatusCode, Json
     uuid::Uuid::parse_str(id).map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: format!("{} must be a valid UUID", field),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("{} must be a valid UUID", field),
+            )),
         )
     })
 }
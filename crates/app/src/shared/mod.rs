@@ -1,7 +1,12 @@
 pub mod audit_action;
 pub mod audit_helpers;
+pub mod identity;
 pub mod ids;
 pub mod money;
+pub mod pagination;
 pub mod status;
+pub mod store_context;
+#[cfg(test)]
+pub mod test_support;
 pub mod time;
 pub mod validation;
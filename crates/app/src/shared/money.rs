@@ -1,27 +1,90 @@
 use axum::{Json, http::StatusCode};
 
-use crate::{pb::pb, rpc::json::ConnectError};
+use crate::{
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+};
+
+/// Active ISO 4217 currency codes, sorted for binary search.
+const ISO_4217_CODES: &[&str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN", "BAM", "BBD", "BDT", "BGN", "BHD", "BIF",
+    "BMD", "BND", "BOB", "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD", "CDF", "CHF", "CLP", "CNY", "COP", "CRC",
+    "CUP", "CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR", "FJD", "FKP", "GBP", "GEL", "GHS",
+    "GIP", "GMD", "GNF", "GTQ", "GYD", "HKD", "HNL", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR", "ISK", "JMD",
+    "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW", "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL",
+    "LYD", "MAD", "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK", "MXN", "MYR", "MZN", "NAD",
+    "NGN", "NIO", "NOK", "NPR", "NZD", "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON", "RSD",
+    "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP", "SLE", "SOS", "SRD", "SSP", "STN", "SVC", "SYP",
+    "SZL", "THB", "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX", "USD", "UYU", "UZS", "VES",
+    "VND", "VUV", "WST", "XAF", "XCD", "XOF", "XPF", "YER", "ZAR", "ZMW", "ZWL",
+];
+
+/// An upper-case ISO 4217 currency code.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CurrencyCode(String);
+
+impl CurrencyCode {
+    pub fn parse(value: &str, field: &str) -> Result<Self, (StatusCode, Json<ConnectError>)> {
+        let code = value.trim().to_ascii_uppercase();
+        if ISO_4217_CODES.binary_search(&code.as_str()).is_err() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("{field} is not a valid ISO 4217 currency code"),
+                )),
+            ));
+        }
+        Ok(Self(code))
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
 
 pub fn money_to_parts(money: Option<pb::Money>) -> Result<(i64, String), (StatusCode, Json<ConnectError>)> {
     let Some(money) = money else {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "money is required".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "money is required")),
         ));
     };
     if money.currency.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "money.currency is required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "money.currency is required",
+            )),
         ));
     }
-    Ok((money.amount, money.currency))
+    if money.amount < 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "money.amount must be non-negative",
+            )),
+        ));
+    }
+    let currency = CurrencyCode::parse(&money.currency, "money.currency")?;
+    Ok((money.amount, currency.into_string()))
+}
+
+// Product and variant prices must be strictly positive; compare_at may still be 0.
+pub fn price_to_parts(money: Option<pb::Money>) -> Result<(i64, String), (StatusCode, Json<ConnectError>)> {
+    let (amount, currency) = money_to_parts(money)?;
+    if amount == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "price.amount must be greater than 0",
+            )),
+        ));
+    }
+    Ok((amount, currency))
 }
 
 pub fn money_to_parts_opt(
@@ -33,15 +96,99 @@ pub fn money_to_parts_opt(
     if money.currency.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "compare_at.currency is required when compare_at is set".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "compare_at.currency is required when compare_at is set",
+            )),
         ));
     }
-    Ok((Some(money.amount), Some(money.currency)))
+    if money.amount < 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "compare_at.amount must be non-negative",
+            )),
+        ));
+    }
+    let currency = CurrencyCode::parse(&money.currency, "compare_at.currency")?;
+    Ok((Some(money.amount), Some(currency.into_string())))
 }
 
 pub fn money_from_parts(amount: i64, currency: String) -> pb::Money {
     pb::Money { amount, currency }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(amount: i64, currency: &str) -> Option<pb::Money> {
+        Some(money_from_parts(amount, currency.to_string()))
+    }
+
+    fn invalid_message<T: std::fmt::Debug>(result: Result<T, (StatusCode, Json<ConnectError>)>) -> String {
+        let (status, Json(err)) = result.expect_err("money should be rejected");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(matches!(err.code, ErrorCode::InvalidArgument));
+        err.message
+    }
+
+    #[test]
+    fn currency_codes_are_sorted_for_binary_search() {
+        assert!(ISO_4217_CODES.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn missing_money_is_invalid_argument() {
+        assert_eq!(invalid_message(money_to_parts(None)), "money is required");
+        assert_eq!(
+            invalid_message(money_to_parts(money(100, ""))),
+            "money.currency is required"
+        );
+    }
+
+    #[test]
+    fn currency_is_normalized_and_checked_against_iso_4217() {
+        assert_eq!(money_to_parts(money(100, " jpy ")).unwrap(), (100, "JPY".to_string()));
+        assert_eq!(
+            invalid_message(money_to_parts(money(100, "XYZ"))),
+            "money.currency is not a valid ISO 4217 currency code"
+        );
+        assert_eq!(
+            invalid_message(money_to_parts_opt(money(100, "YEN"))),
+            "compare_at.currency is not a valid ISO 4217 currency code"
+        );
+    }
+
+    #[test]
+    fn negative_amounts_are_rejected() {
+        assert_eq!(
+            invalid_message(money_to_parts(money(-1, "JPY"))),
+            "money.amount must be non-negative"
+        );
+        assert_eq!(
+            invalid_message(money_to_parts_opt(money(-1, "JPY"))),
+            "compare_at.amount must be non-negative"
+        );
+        assert_eq!(money_to_parts(money(0, "JPY")).unwrap(), (0, "JPY".to_string()));
+        assert_eq!(
+            money_to_parts_opt(money(0, "JPY")).unwrap(),
+            (Some(0), Some("JPY".to_string()))
+        );
+        assert_eq!(money_to_parts_opt(None).unwrap(), (None, None));
+    }
+
+    #[test]
+    fn prices_must_be_positive() {
+        assert_eq!(
+            invalid_message(price_to_parts(money(0, "JPY"))),
+            "price.amount must be greater than 0"
+        );
+        assert_eq!(
+            invalid_message(price_to_parts(money(-500, "JPY"))),
+            "money.amount must be non-negative"
+        );
+        assert_eq!(price_to_parts(money(1, "JPY")).unwrap(), (1, "JPY".to_string()));
+    }
+}
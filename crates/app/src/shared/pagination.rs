@@ -0,0 +1,168 @@
+use axum::{Json, http::StatusCode};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Last row of the previous page; list queries order by `(created_at, id) DESC` and resume after it.
+pub type PageCursor = (DateTime<Utc>, uuid::Uuid);
+
+#[derive(Serialize, Deserialize)]
+struct PageTokenBody {
+    created_at: String,
+    id: String,
+}
+
+/// Opaque page token: base64url JSON of the last row's `created_at` and `id`.
+pub fn encode_page_token(created_at: DateTime<Utc>, id: uuid::Uuid) -> String {
+    let body = PageTokenBody {
+        created_at: created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        id: id.to_string(),
+    };
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(&body).unwrap_or_default())
+}
+
+pub fn decode_page_token(token: &str) -> Result<PageCursor, (StatusCode, Json<ConnectError>)> {
+    let decoded = URL_SAFE_NO_PAD.decode(token).ok();
+    let body = decoded.and_then(|bytes| serde_json::from_slice::<PageTokenBody>(&bytes).ok());
+    let cursor = body.and_then(|body| {
+        let created_at = DateTime::parse_from_rfc3339(&body.created_at).ok()?.with_timezone(&Utc);
+        let id = uuid::Uuid::parse_str(&body.id).ok()?;
+        Some((created_at, id))
+    });
+    cursor.ok_or_else(invalid_page_token)
+}
+
+/// Last row of the previous page for lists kept in manual order, resumed after `(position, id)`.
+pub type PositionCursor = (i32, uuid::Uuid);
+
+#[derive(Serialize, Deserialize)]
+struct PositionTokenBody {
+    position: i32,
+    id: String,
+}
+
+/// Opaque page token: base64url JSON of the last row's `position` and `id`.
+pub fn encode_position_page_token(position: i32, id: uuid::Uuid) -> String {
+    let body = PositionTokenBody {
+        position,
+        id: id.to_string(),
+    };
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(&body).unwrap_or_default())
+}
+
+/// Page size (default 50, capped at `max_page_size`) and the decoded position cursor, if any.
+pub fn position_page_params(
+    page: Option<pb::PageInfo>,
+    max_page_size: i64,
+) -> Result<(i64, Option<PositionCursor>), (StatusCode, Json<ConnectError>)> {
+    let page = page.unwrap_or_default();
+    let limit = page_limit(&page, DEFAULT_PAGE_SIZE, max_page_size);
+    if page.page_token.is_empty() {
+        return Ok((limit, None));
+    }
+    let decoded = URL_SAFE_NO_PAD.decode(&page.page_token).ok();
+    let body = decoded.and_then(|bytes| serde_json::from_slice::<PositionTokenBody>(&bytes).ok());
+    let cursor = body.and_then(|body| Some((body.position, uuid::Uuid::parse_str(&body.id).ok()?)));
+    Ok((limit, Some(cursor.ok_or_else(invalid_page_token)?)))
+}
+
+/// Page size (default 50, capped at `max_page_size`) and the decoded cursor, if any.
+pub fn page_params(
+    page: Option<pb::PageInfo>,
+    max_page_size: i64,
+) -> Result<(i64, Option<PageCursor>), (StatusCode, Json<ConnectError>)> {
+    page_params_with_default(page, DEFAULT_PAGE_SIZE, max_page_size)
+}
+
+pub fn page_params_with_default(
+    page: Option<pb::PageInfo>,
+    default_page_size: i64,
+    max_page_size: i64,
+) -> Result<(i64, Option<PageCursor>), (StatusCode, Json<ConnectError>)> {
+    let page = page.unwrap_or_default();
+    let limit = page_limit(&page, default_page_size, max_page_size);
+    if page.page_token.is_empty() {
+        return Ok((limit, None));
+    }
+    Ok((limit, Some(decode_page_token(&page.page_token)?)))
+}
+
+fn page_limit(page: &pb::PageInfo, default_page_size: i64, max_page_size: i64) -> i64 {
+    if page.page_size <= 0 {
+        default_page_size
+    } else {
+        (page.page_size as i64).min(max_page_size)
+    }
+}
+
+fn invalid_page_token() -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ConnectError::new(ErrorCode::InvalidArgument, "page_token is invalid")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(page_size: i32, page_token: &str) -> Option<pb::PageInfo> {
+        Some(pb::PageInfo {
+            page_size,
+            page_token: page_token.to_string(),
+        })
+    }
+
+    fn assert_invalid(result: Result<impl std::fmt::Debug, (StatusCode, Json<ConnectError>)>) {
+        let err = result.expect_err("token should be rejected");
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+        assert!(matches!(err.1.code, ErrorCode::InvalidArgument));
+    }
+
+    #[test]
+    fn page_token_round_trips() {
+        let created_at = DateTime::from_timestamp_micros(1_792_108_800_123_456).unwrap();
+        let id = uuid::Uuid::new_v4();
+        let token = encode_page_token(created_at, id);
+        assert_eq!(decode_page_token(&token).unwrap(), (created_at, id));
+        assert_eq!(
+            page_params(page(10, &token), MAX_PAGE_SIZE).unwrap(),
+            (10, Some((created_at, id)))
+        );
+    }
+
+    #[test]
+    fn tampered_page_token_is_invalid_argument() {
+        let token = encode_page_token(Utc::now(), uuid::Uuid::new_v4());
+        let mut tampered = token.clone();
+        tampered.replace_range(..4, "AAAA");
+        assert_invalid(decode_page_token(&tampered));
+        assert_invalid(decode_page_token("not a token"));
+        assert_invalid(decode_page_token(
+            &URL_SAFE_NO_PAD.encode(r#"{"created_at":"yesterday","id":"1"}"#),
+        ));
+        assert_invalid(page_params(page(10, &format!("{token}x")), MAX_PAGE_SIZE));
+    }
+
+    #[test]
+    fn page_size_defaults_and_is_capped() {
+        assert_eq!(page_params(None, MAX_PAGE_SIZE).unwrap(), (DEFAULT_PAGE_SIZE, None));
+        assert_eq!(
+            page_params(page(0, ""), MAX_PAGE_SIZE).unwrap(),
+            (DEFAULT_PAGE_SIZE, None)
+        );
+        assert_eq!(
+            page_params(page(500, ""), MAX_PAGE_SIZE).unwrap(),
+            (MAX_PAGE_SIZE, None)
+        );
+        assert_eq!(page_params_with_default(page(0, ""), 20, 100).unwrap(), (20, None));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::pb::pb;
-use crate::rpc::json::ConnectError;
+use crate::rpc::json::{ConnectError, ErrorCode};
 use axum::{Json, http::StatusCode};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -7,20 +7,20 @@ pub enum ProductStatus {
     Active,
     Inactive,
     Draft,
+    /// Set by DeleteProduct's soft delete together with `deleted_at`; never accepted as a write.
+    Deleted,
 }
 
 impl ProductStatus {
     pub fn parse(value: &str) -> Result<Self, (StatusCode, Json<ConnectError>)> {
-        match value {
-            "active" => Ok(ProductStatus::Active),
-            "inactive" => Ok(ProductStatus::Inactive),
-            "draft" => Ok(ProductStatus::Draft),
+        match Self::try_from(value) {
+            Ok(status) if status != ProductStatus::Deleted => Ok(status),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "product.status must be active|inactive|draft".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "product.status must be active|inactive|draft",
+                )),
             )),
         }
     }
@@ -30,6 +30,21 @@ impl ProductStatus {
             ProductStatus::Active => "active",
             ProductStatus::Inactive => "inactive",
             ProductStatus::Draft => "draft",
+            ProductStatus::Deleted => "deleted",
+        }
+    }
+}
+
+impl TryFrom<&str> for ProductStatus {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "active" => Ok(ProductStatus::Active),
+            "inactive" => Ok(ProductStatus::Inactive),
+            "draft" => Ok(ProductStatus::Draft),
+            "deleted" => Ok(ProductStatus::Deleted),
+            _ => Err("status must be active, inactive, draft or deleted"),
         }
     }
 }
@@ -47,10 +62,10 @@ impl VariantStatus {
             "inactive" => Ok(VariantStatus::Inactive),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "variant.status must be active|inactive".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "variant.status must be active|inactive",
+                )),
             )),
         }
     }
@@ -76,10 +91,10 @@ impl FulfillmentType {
             "digital" => Ok(FulfillmentType::Digital),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "fulfillment_type must be physical|digital".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "fulfillment_type must be physical|digital",
+                )),
             )),
         }
     }
@@ -105,10 +120,10 @@ impl CartStatus {
             "ordered" => Ok(CartStatus::Ordered),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "cart.status must be active|ordered".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "cart.status must be active|ordered",
+                )),
             )),
         }
     }
@@ -134,10 +149,10 @@ impl CartItemStatus {
             "removed" => Ok(CartItemStatus::Removed),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "cart_item.status must be active|removed".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "cart_item.status must be active|removed",
+                )),
             )),
         }
     }
@@ -205,10 +220,10 @@ impl PaymentMethod {
             Some(pb::PaymentMethod::Cod) => Ok(PaymentMethod::Cod),
             _ => Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "payment_method is required".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "payment_method is required",
+                )),
             )),
         }
     }
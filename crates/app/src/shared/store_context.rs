@@ -0,0 +1,302 @@
+use axum::{Json, http::StatusCode};
+use sqlx::Row;
+
+use crate::{
+    AppState,
+    infrastructure::db,
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+    rpc::request_context::{self, RequestContext},
+    shared::ids::{StoreId, TenantId},
+};
+
+/// Resolves `(store_id, tenant_id)` for a request, guarded against the caller's token.
+/// Uses the task-local request context; handlers that extract a `RequestContext` should
+/// call [`resolve_store_context_with`] instead.
+pub async fn resolve_store_context(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    tenant: Option<pb::TenantContext>,
+) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
+    let ctx = request_context::current().unwrap_or_default();
+    resolve_store_context_with(state, &ctx, store, tenant).await
+}
+
+/// Resolution order: `store.store_id`, `store.store_code`, the token's store, `tenant.tenant_id`
+/// (its first store), then the token's tenant. An explicit store or tenant that differs from the
+/// token is rejected with PermissionDenied.
+pub async fn resolve_store_context_with(
+    state: &AppState,
+    ctx: &RequestContext,
+    store: Option<pb::StoreContext>,
+    tenant: Option<pb::TenantContext>,
+) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
+    let requested_store_id = store.as_ref().map(|s| s.store_id.as_str()).filter(|id| !id.is_empty());
+    let requested_store_code = store
+        .as_ref()
+        .map(|s| s.store_code.as_str())
+        .filter(|code| !code.is_empty());
+    let requested_tenant_id = tenant
+        .as_ref()
+        .map(|t| t.tenant_id.as_str())
+        .filter(|id| !id.is_empty());
+
+    if let (Some(auth_store), Some(store_id)) = (ctx.store_id.as_deref(), requested_store_id)
+        && store_id != auth_store
+    {
+        return Err(permission_denied("store_id does not match token"));
+    }
+    if let (Some(auth_tenant), Some(tenant_id)) = (ctx.tenant_id.as_deref(), requested_tenant_id)
+        && tenant_id != auth_tenant
+    {
+        return Err(permission_denied("tenant_id does not match token"));
+    }
+
+    if let Some(store_id) = requested_store_id {
+        if ctx.store_id.as_deref() == Some(store_id)
+            && let Some(tenant_id) = ctx.tenant_id.clone()
+        {
+            return Ok((store_id.to_string(), tenant_id));
+        }
+        let Some(tenant_id) = tenant_id_for_store(state, store_id).await? else {
+            return Err(invalid_argument("store_id not found"));
+        };
+        return Ok((store_id.to_string(), tenant_id));
+    }
+    if let Some(store_code) = requested_store_code {
+        let Some((store_id, tenant_id)) = store_by_code(state, store_code).await? else {
+            return Err(invalid_argument("store_code not found"));
+        };
+        if let Some(auth_store) = ctx.store_id.as_deref()
+            && auth_store != store_id
+        {
+            return Err(permission_denied("store_code does not match token"));
+        }
+        return Ok((store_id, tenant_id));
+    }
+    if let Some(store_id) = ctx.store_id.clone() {
+        if let Some(tenant_id) = ctx.tenant_id.clone() {
+            return Ok((store_id, tenant_id));
+        }
+        if let Some(tenant_id) = tenant_id_for_store(state, &store_id).await? {
+            return Ok((store_id, tenant_id));
+        }
+    }
+    if let Some(tenant_id) = requested_tenant_id
+        .map(str::to_string)
+        .or_else(|| ctx.tenant_id.clone())
+    {
+        let store_id = store_id_for_tenant(state, &tenant_id).await?;
+        return Ok((store_id, tenant_id));
+    }
+    Err(invalid_argument("store.store_id or tenant.tenant_id is required"))
+}
+
+/// Same lookups without the token checks, for storefront and sign-in paths that run before
+/// (or without) a staff token.
+pub async fn resolve_store_context_without_token_guard(
+    state: &AppState,
+    store: Option<pb::StoreContext>,
+    tenant: Option<pb::TenantContext>,
+) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
+    resolve_store_context_with(state, &RequestContext::default(), store, tenant).await
+}
+
+/// The tenant's first store by creation time.
+pub async fn store_id_for_tenant(
+    state: &AppState,
+    tenant_id: &str,
+) -> Result<String, (StatusCode, Json<ConnectError>)> {
+    let tenant_uuid = TenantId::parse(tenant_id)?;
+    let row = sqlx::query("SELECT id::text as id FROM stores WHERE tenant_id = $1 ORDER BY created_at ASC LIMIT 1")
+        .bind(tenant_uuid.as_uuid())
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    let Some(row) = row else {
+        return Err(invalid_argument("tenant_id not found"));
+    };
+    Ok(row.get("id"))
+}
+
+async fn tenant_id_for_store(
+    state: &AppState,
+    store_id: &str,
+) -> Result<Option<String>, (StatusCode, Json<ConnectError>)> {
+    let store_uuid = StoreId::parse(store_id)?;
+    if let Some((_, tenant_id)) = state.store_cache.get_by_store_id(store_id) {
+        return Ok(Some(tenant_id));
+    }
+    let row = sqlx::query("SELECT tenant_id::text as tenant_id FROM stores WHERE id = $1")
+        .bind(store_uuid.as_uuid())
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    let tenant_id = row.map(|row| row.get::<String, _>("tenant_id"));
+    if let Some(tenant_id) = tenant_id.as_deref() {
+        state.store_cache.insert_by_store_id(store_id, tenant_id);
+    }
+    Ok(tenant_id)
+}
+
+async fn store_by_code(
+    state: &AppState,
+    store_code: &str,
+) -> Result<Option<(String, String)>, (StatusCode, Json<ConnectError>)> {
+    if let Some(cached) = state.store_cache.get_by_store_code(store_code) {
+        return Ok(Some(cached));
+    }
+    let row = sqlx::query("SELECT id::text as id, tenant_id::text as tenant_id FROM stores WHERE code = $1")
+        .bind(store_code)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let store_id: String = row.get("id");
+    let tenant_id: String = row.get("tenant_id");
+    state
+        .store_cache
+        .insert_by_store_code(store_code, &store_id, &tenant_id);
+    Ok(Some((store_id, tenant_id)))
+}
+
+fn invalid_argument(message: &str) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ConnectError::new(ErrorCode::InvalidArgument, message.to_string())),
+    )
+}
+
+fn permission_denied(message: &str) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ConnectError::new(ErrorCode::PermissionDenied, message.to_string())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn store_code_lookups_hit_the_database_once(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        sqlx::query("UPDATE stores SET code = 'test-store' WHERE id = $1")
+            .bind(store.store_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        let by_code = || {
+            Some(pb::StoreContext {
+                store_id: String::new(),
+                store_code: "test-store".to_string(),
+            })
+        };
+        let expected = (store.store_id.to_string(), store.tenant_id.to_string());
+
+        assert_eq!(resolve_store_context(&state, by_code(), None).await.unwrap(), expected);
+        // Renaming the code behind the cache's back shows the second call never reached the database.
+        sqlx::query("UPDATE stores SET code = 'renamed' WHERE id = $1")
+            .bind(store.store_id)
+            .execute(&db)
+            .await
+            .unwrap();
+        assert_eq!(resolve_store_context(&state, by_code(), None).await.unwrap(), expected);
+
+        state.store_cache.invalidate_store(&store.store_id.to_string());
+        assert!(resolve_store_context(&state, by_code(), None).await.is_err());
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn token_scope_picks_and_guards_the_store(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let other = test_support::create_store(&db).await;
+        let ctx = RequestContext::test_context(store.store_id, store.tenant_id);
+        let expected = (store.store_id.to_string(), store.tenant_id.to_string());
+
+        assert_eq!(
+            resolve_store_context_with(&state, &ctx, None, None).await.unwrap(),
+            expected
+        );
+        assert_eq!(
+            resolve_store_context_with(&state, &ctx, store.store_context(), None)
+                .await
+                .unwrap(),
+            expected
+        );
+        let (_, Json(err)) = resolve_store_context_with(&state, &ctx, other.store_context(), None)
+            .await
+            .expect_err("another store is outside the token");
+        assert!(matches!(err.code, ErrorCode::PermissionDenied), "{err:?}");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn explicit_store_or_tenant_resolves_without_a_token(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let expected = (store.store_id.to_string(), store.tenant_id.to_string());
+        let tenant = || {
+            Some(pb::TenantContext {
+                tenant_id: store.tenant_id.to_string(),
+            })
+        };
+
+        assert_eq!(
+            resolve_store_context(&state, store.store_context(), None)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(resolve_store_context(&state, None, tenant()).await.unwrap(), expected);
+
+        let unknown = Some(pb::StoreContext {
+            store_id: uuid::Uuid::new_v4().to_string(),
+            store_code: String::new(),
+        });
+        let (_, Json(err)) = resolve_store_context(&state, unknown, None)
+            .await
+            .expect_err("unknown store_id");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        let (_, Json(err)) = resolve_store_context(&state, None, None)
+            .await
+            .expect_err("nothing to resolve from");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn token_tenant_guards_the_requested_tenant(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let other = test_support::create_store(&db).await;
+        let ctx = RequestContext {
+            tenant_id: Some(store.tenant_id.to_string()),
+            ..Default::default()
+        };
+
+        // A tenant-scoped token without a store falls back to the tenant's first store.
+        assert_eq!(
+            resolve_store_context_with(&state, &ctx, None, None).await.unwrap(),
+            (store.store_id.to_string(), store.tenant_id.to_string())
+        );
+        let other_tenant = Some(pb::TenantContext {
+            tenant_id: other.tenant_id.to_string(),
+        });
+        let (_, Json(err)) = resolve_store_context_with(&state, &ctx, None, other_tenant.clone())
+            .await
+            .expect_err("another tenant is outside the token");
+        assert!(matches!(err.code, ErrorCode::PermissionDenied), "{err:?}");
+        assert_eq!(
+            resolve_store_context_without_token_guard(&state, None, other_tenant)
+                .await
+                .unwrap(),
+            (other.store_id.to_string(), other.tenant_id.to_string())
+        );
+    }
+}
@@ -0,0 +1,607 @@
+//! Fixtures shared by the `#[sqlx::test]` cases.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{Json, http::StatusCode};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::{
+    AppState,
+    infrastructure::{
+        self,
+        audit::{AuditInput, AuditResult, AuditWriter},
+        storage::{ObjectStorage, StorageError, StorageLocationConfig},
+    },
+    order,
+    pb::pb,
+    rpc::{
+        self,
+        json::{ConnectError, ErrorCode},
+    },
+    shared::validation::PasswordStrength,
+    store_settings::repository::{
+        MallSettingsRecord, PrefectureRecord, ShippingRateRecord, ShippingZoneRecord, StoreLocationRecord,
+        StoreSettingsRecord, StoreSettingsRepository, TaxRuleRecord,
+    },
+};
+
+pub struct TestStore {
+    pub tenant_id: uuid::Uuid,
+    pub store_id: uuid::Uuid,
+}
+
+impl TestStore {
+    pub fn store_context(&self) -> Option<pb::StoreContext> {
+        Some(pb::StoreContext {
+            store_id: self.store_id.to_string(),
+            store_code: String::new(),
+        })
+    }
+}
+
+/// App state over the test database with search disabled.
+pub fn app_state(db: PgPool) -> AppState {
+    AppState {
+        search: infrastructure::search::SearchService::none(),
+        product_performance_cache: order::analytics::product_performance_cache(),
+        store_cache: infrastructure::store_cache::StoreCache::new(Duration::from_secs(60)),
+        audit_writer: Arc::new(infrastructure::audit::PgAuditWriter::new(db.clone())),
+        email_renderer: Arc::new(infrastructure::email::EmailRenderer::load(None).expect("embedded email templates")),
+        password_strength: PasswordStrength::default(),
+        auth: rpc::actor::AuthConfig {
+            jwt_secret: Some("test-secret".to_string()),
+            ..Default::default()
+        },
+        object_storage: Arc::new(RecordingObjectStorage::default()),
+        upload_max_bytes: 1024 * 1024,
+        db,
+    }
+}
+
+/// Builds an `AppState` whose pool never connects, for tests that run entirely against mocks.
+pub fn app_state_without_db() -> AppState {
+    app_state(PgPool::connect_lazy("postgres://localhost/unused").expect("lazy pool"))
+}
+
+pub async fn create_store(db: &PgPool) -> TestStore {
+    let tenant_id: uuid::Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO tenants (name, type, default_currency, status)
+        VALUES ('Test tenant', 'single_brand', 'JPY', 'active')
+        RETURNING id
+        "#,
+    )
+    .fetch_one(db)
+    .await
+    .expect("insert tenant");
+    let store_id: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO stores (tenant_id, name) VALUES ($1, 'Test store') RETURNING id")
+            .bind(tenant_id)
+            .fetch_one(db)
+            .await
+            .expect("insert store");
+    TestStore { tenant_id, store_id }
+}
+
+pub async fn create_product(db: &PgPool, store: &TestStore, title: &str, status: &str) -> uuid::Uuid {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO products (tenant_id, store_id, title, description, status)
+        VALUES ($1, $2, $3, '', $4)
+        RETURNING id
+        "#,
+    )
+    .bind(store.tenant_id)
+    .bind(store.store_id)
+    .bind(title)
+    .bind(status)
+    .fetch_one(db)
+    .await
+    .expect("insert product")
+}
+
+/// Serves Meilisearch's `GET /health` on a local port, reporting `status`. Returns the base URL.
+pub async fn mock_meilisearch(status: &'static str) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind mock meilisearch");
+    let addr = listener.local_addr().expect("mock meilisearch address");
+    let app = axum::Router::new().route(
+        "/health",
+        axum::routing::get(move || async move { axum::Json(serde_json::json!({ "status": status })) }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{addr}")
+}
+
+/// Keeps every audit event in memory instead of writing `audit_logs`.
+#[derive(Clone, Default)]
+pub struct RecordingAuditWriter {
+    pub events: Arc<Mutex<Vec<AuditInput>>>,
+}
+
+#[async_trait]
+impl AuditWriter for RecordingAuditWriter {
+    async fn record(&self, input: AuditInput) -> AuditResult {
+        self.events.lock().unwrap().push(input);
+        Ok(())
+    }
+
+    async fn record_tx(&self, _tx: &mut Transaction<'_, Postgres>, input: AuditInput) -> AuditResult {
+        self.record(input).await
+    }
+}
+
+/// An uploaded object as seen by [`RecordingObjectStorage`].
+#[derive(Clone, Debug)]
+pub struct StoredObject {
+    pub object_key: String,
+    pub content_type: String,
+    pub size: usize,
+}
+
+/// Keeps uploads in memory instead of sending them to a bucket.
+#[derive(Clone, Default)]
+pub struct RecordingObjectStorage {
+    pub objects: Arc<Mutex<Vec<StoredObject>>>,
+}
+
+#[async_trait]
+impl ObjectStorage for RecordingObjectStorage {
+    async fn put_object(
+        &self,
+        _config: &StorageLocationConfig,
+        object_key: &str,
+        body: bytes::Bytes,
+        content_type: &str,
+    ) -> Result<(), StorageError> {
+        self.objects.lock().unwrap().push(StoredObject {
+            object_key: object_key.to_string(),
+            content_type: content_type.to_string(),
+            size: body.len(),
+        });
+        Ok(())
+    }
+}
+
+/// Serves store settings from memory and records settings writes instead of storing them.
+/// Location, shipping and tax methods return an `Internal` error.
+#[derive(Default)]
+pub struct MockStoreSettingsRepository {
+    pub store_settings: Option<StoreSettingsRecord>,
+    pub tenant_settings: Option<StoreSettingsRecord>,
+    pub store_name: Option<String>,
+    pub saved_settings: Mutex<Vec<pb::StoreSettings>>,
+    pub saved_mall_settings: Mutex<Vec<pb::MallSettings>>,
+}
+
+impl MockStoreSettingsRepository {
+    fn saved_mall_record(&self) -> Option<MallSettingsRecord> {
+        self.saved_mall_settings
+            .lock()
+            .unwrap()
+            .last()
+            .map(|mall| MallSettingsRecord {
+                enabled: mall.enabled,
+                commission_rate: mall.commission_rate,
+                vendor_approval_required: mall.vendor_approval_required,
+            })
+    }
+}
+
+fn unsupported(method: &str) -> (StatusCode, Json<ConnectError>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ConnectError::new(
+            ErrorCode::Internal,
+            format!("MockStoreSettingsRepository does not support {method}"),
+        )),
+    )
+}
+
+#[async_trait]
+impl StoreSettingsRepository for MockStoreSettingsRepository {
+    async fn fetch_store_settings_by_store(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Option<StoreSettingsRecord>, (StatusCode, Json<ConnectError>)> {
+        Ok(self.store_settings.clone())
+    }
+
+    async fn fetch_store_settings_by_tenant(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+    ) -> Result<Option<StoreSettingsRecord>, (StatusCode, Json<ConnectError>)> {
+        Ok(self.tenant_settings.clone())
+    }
+
+    async fn fetch_store_name(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Option<String>, (StatusCode, Json<ConnectError>)> {
+        Ok(self.store_name.clone())
+    }
+
+    async fn upsert_store_settings(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        _cod_fee_amount: i64,
+        _cod_fee_currency: String,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.saved_settings.lock().unwrap().push(settings.clone());
+        Ok(())
+    }
+
+    async fn upsert_store_settings_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        cod_fee_amount: i64,
+        cod_fee_currency: String,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.upsert_store_settings(tenant_uuid, store_uuid, settings, cod_fee_amount, cod_fee_currency)
+            .await
+    }
+
+    async fn insert_store_settings_if_absent(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        _cod_fee_amount: i64,
+        _cod_fee_currency: String,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.saved_settings.lock().unwrap().push(settings.clone());
+        Ok(())
+    }
+
+    async fn insert_store_settings_if_absent_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        cod_fee_amount: i64,
+        cod_fee_currency: String,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.insert_store_settings_if_absent(tenant_uuid, store_uuid, settings, cod_fee_amount, cod_fee_currency)
+            .await
+    }
+
+    async fn upsert_mall_settings(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        mall: &pb::MallSettings,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.saved_mall_settings.lock().unwrap().push(mall.clone());
+        Ok(())
+    }
+
+    async fn upsert_mall_settings_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        mall: &pb::MallSettings,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.upsert_mall_settings(tenant_uuid, store_uuid, mall).await
+    }
+
+    async fn save_store_settings(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        audit_writer: &dyn AuditWriter,
+        audit: AuditInput,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.saved_settings.lock().unwrap().push(settings.clone());
+        audit_writer.record(audit).await
+    }
+
+    async fn initialize_settings(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        settings: &pb::StoreSettings,
+        mall: &pb::MallSettings,
+        audit_writer: &dyn AuditWriter,
+        audits: Vec<AuditInput>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        self.saved_settings.lock().unwrap().push(settings.clone());
+        self.saved_mall_settings.lock().unwrap().push(mall.clone());
+        for audit in audits {
+            audit_writer.record(audit).await?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_mall_settings_by_store(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Option<MallSettingsRecord>, (StatusCode, Json<ConnectError>)> {
+        Ok(self.saved_mall_record())
+    }
+
+    async fn fetch_mall_settings_by_tenant(
+        &self,
+        _tenant_uuid: &uuid::Uuid,
+    ) -> Result<Option<MallSettingsRecord>, (StatusCode, Json<ConnectError>)> {
+        Ok(self.saved_mall_record())
+    }
+
+    async fn list_store_locations(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Vec<StoreLocationRecord>, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("list_store_locations"))
+    }
+
+    async fn insert_store_location(
+        &self,
+        _location_id: &uuid::Uuid,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _location: &pb::StoreLocation,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_store_location"))
+    }
+
+    async fn insert_store_location_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _location_id: &uuid::Uuid,
+        _tenant_uuid: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _location: &pb::StoreLocation,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_store_location_tx"))
+    }
+
+    async fn update_store_location(
+        &self,
+        _location_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _location: &pb::StoreLocation,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_store_location"))
+    }
+
+    async fn update_store_location_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _location_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _location: &pb::StoreLocation,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_store_location_tx"))
+    }
+
+    async fn delete_store_location(
+        &self,
+        _location_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_store_location"))
+    }
+
+    async fn delete_store_location_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _location_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_store_location_tx"))
+    }
+
+    async fn list_shipping_zones(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Vec<ShippingZoneRecord>, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("list_shipping_zones"))
+    }
+
+    async fn list_zone_prefectures(
+        &self,
+        _zone_uuid: &uuid::Uuid,
+    ) -> Result<Vec<PrefectureRecord>, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("list_zone_prefectures"))
+    }
+
+    async fn insert_shipping_zone_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _zone_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _tenant_uuid: &uuid::Uuid,
+        _zone: &pb::ShippingZone,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_shipping_zone_tx"))
+    }
+
+    async fn update_shipping_zone_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _zone_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _zone: &pb::ShippingZone,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_shipping_zone_tx"))
+    }
+
+    async fn delete_zone_prefectures_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _zone_id: &uuid::Uuid,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_zone_prefectures_tx"))
+    }
+
+    async fn insert_zone_prefecture_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _zone_id: &uuid::Uuid,
+        _prefecture: &pb::Prefecture,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_zone_prefecture_tx"))
+    }
+
+    async fn delete_shipping_zone_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _zone_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_shipping_zone_tx"))
+    }
+
+    async fn list_shipping_rates(
+        &self,
+        _store_uuid: &uuid::Uuid,
+        _zone_uuid: &uuid::Uuid,
+    ) -> Result<Vec<ShippingRateRecord>, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("list_shipping_rates"))
+    }
+
+    async fn insert_shipping_rate(
+        &self,
+        _rate_id: &uuid::Uuid,
+        _zone_uuid: &uuid::Uuid,
+        _rate: &pb::ShippingRate,
+        _fee_amount: i64,
+        _fee_currency: &str,
+        _min: Option<i64>,
+        _max: Option<i64>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_shipping_rate"))
+    }
+
+    async fn insert_shipping_rate_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _rate_id: &uuid::Uuid,
+        _zone_uuid: &uuid::Uuid,
+        _rate: &pb::ShippingRate,
+        _fee_amount: i64,
+        _fee_currency: &str,
+        _min: Option<i64>,
+        _max: Option<i64>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_shipping_rate_tx"))
+    }
+
+    async fn update_shipping_rate(
+        &self,
+        _rate_id: &uuid::Uuid,
+        _rate: &pb::ShippingRate,
+        _fee_amount: i64,
+        _fee_currency: &str,
+        _min: Option<i64>,
+        _max: Option<i64>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_shipping_rate"))
+    }
+
+    async fn update_shipping_rate_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _rate_id: &uuid::Uuid,
+        _rate: &pb::ShippingRate,
+        _fee_amount: i64,
+        _fee_currency: &str,
+        _min: Option<i64>,
+        _max: Option<i64>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_shipping_rate_tx"))
+    }
+
+    async fn delete_shipping_rate(
+        &self,
+        _store_uuid: &uuid::Uuid,
+        _rate_id: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_shipping_rate"))
+    }
+
+    async fn delete_shipping_rate_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _store_uuid: &uuid::Uuid,
+        _rate_id: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_shipping_rate_tx"))
+    }
+
+    async fn list_tax_rules(
+        &self,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<Vec<TaxRuleRecord>, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("list_tax_rules"))
+    }
+
+    async fn insert_tax_rule(
+        &self,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _tenant_uuid: &uuid::Uuid,
+        _rule: &pb::TaxRule,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_tax_rule"))
+    }
+
+    async fn insert_tax_rule_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _tenant_uuid: &uuid::Uuid,
+        _rule: &pb::TaxRule,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("insert_tax_rule_tx"))
+    }
+
+    async fn update_tax_rule(
+        &self,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _rule: &pb::TaxRule,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_tax_rule"))
+    }
+
+    async fn update_tax_rule_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+        _rule: &pb::TaxRule,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("update_tax_rule_tx"))
+    }
+
+    async fn delete_tax_rule(
+        &self,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_tax_rule"))
+    }
+
+    async fn delete_tax_rule_tx(
+        &self,
+        _exec: &mut Transaction<'_, Postgres>,
+        _rule_id: &uuid::Uuid,
+        _store_uuid: &uuid::Uuid,
+    ) -> Result<u64, (StatusCode, Json<ConnectError>)> {
+        Err(unsupported("delete_tax_rule_tx"))
+    }
+}
@@ -1,5 +1,8 @@
+use axum::{Json, http::StatusCode};
 use chrono::{DateTime, Utc};
 
+use crate::rpc::json::{ConnectError, ErrorCode};
+
 pub fn timestamp_to_chrono(ts: Option<pbjson_types::Timestamp>) -> Option<DateTime<Utc>> {
     let ts = ts?;
     let nanos = u32::try_from(ts.nanos).ok()?;
@@ -15,3 +18,33 @@ pub fn chrono_to_timestamp(dt: Option<DateTime<Utc>>) -> Option<pbjson_types::Ti
         nanos: 0,
     })
 }
+
+/// Like `timestamp_to_chrono`, but rejects a missing or out-of-range request field with `InvalidArgument`.
+pub fn timestamp_to_chrono_required(
+    ts: Option<pbjson_types::Timestamp>,
+    field: &str,
+) -> Result<DateTime<Utc>, (StatusCode, Json<ConnectError>)> {
+    let invalid = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
+        )
+    };
+    if ts.is_none() {
+        return Err(invalid(format!("{} is required", field)));
+    }
+    timestamp_to_chrono(ts).ok_or_else(|| invalid(format!("{} is invalid", field)))
+}
+
+/// Like `chrono_to_timestamp`, but treats a missing value as a bad row and returns `Internal`.
+pub fn chrono_to_timestamp_required(
+    dt: Option<DateTime<Utc>>,
+    field: &str,
+) -> Result<pbjson_types::Timestamp, (StatusCode, Json<ConnectError>)> {
+    chrono_to_timestamp(dt).ok_or_else(|| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ConnectError::new(ErrorCode::Internal, format!("{} is missing", field))),
+        )
+    })
+}
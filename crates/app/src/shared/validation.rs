@@ -1,6 +1,13 @@
 use axum::{Json, http::StatusCode};
+use rs_common::{
+    env::{self, EnvError},
+    identity::NormalizationRule,
+};
 
-use crate::rpc::json::ConnectError;
+use crate::{
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StoreCode(String);
@@ -17,19 +24,16 @@ impl StoreCode {
         if normalized.is_empty() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_code is required".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "store_code is required")),
             ));
         }
         if normalized.len() > 64 {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_code must be 64 chars or less".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "store_code must be 64 chars or less",
+                )),
             ));
         }
         if !normalized
@@ -38,10 +42,10 @@ impl StoreCode {
         {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_code must be alphanumeric or -_. only".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "store_code must be alphanumeric or -_. only",
+                )),
             ));
         }
         Ok(Self(normalized.to_string()))
@@ -58,22 +62,17 @@ impl Email {
         if normalized.is_empty() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "email is required".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "email is required")),
             ));
         }
         if !normalized.contains('@') {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "email is invalid".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "email is invalid")),
             ));
         }
-        Ok(Self(normalized.to_string()))
+        // Stored and matched in lowercase, the same key as the "email" customer identity.
+        Ok(Self(NormalizationRule::Email.apply(normalized)))
     }
 
     pub fn parse_optional(value: &str) -> Result<Option<Self>, (StatusCode, Json<ConnectError>)> {
@@ -95,19 +94,16 @@ impl Phone {
         if normalized.is_empty() {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "phone is required".to_string(),
-                }),
+                Json(ConnectError::new(ErrorCode::InvalidArgument, "phone is required")),
             ));
         }
         if normalized.len() > 32 {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "phone must be 32 chars or less".to_string(),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "phone must be 32 chars or less",
+                )),
             ));
         }
         Ok(Self(normalized.to_string()))
@@ -125,3 +121,232 @@ impl Phone {
         &self.0
     }
 }
+
+const PASSWORD_MIN_LEN: usize = 8;
+
+/// Password policy for staff and owner passwords; `main` reads it once at startup.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PasswordStrength {
+    require_special: bool,
+}
+
+impl PasswordStrength {
+    pub fn new(require_special: bool) -> Self {
+        Self { require_special }
+    }
+
+    /// Special characters are only required when `STAFF_PASSWORD_REQUIRE_SPECIAL=true`; a value
+    /// other than `true` / `false` is an error.
+    pub fn from_env() -> Result<Self, EnvError> {
+        Ok(Self::new(env::env_bool("STAFF_PASSWORD_REQUIRE_SPECIAL", false)?))
+    }
+
+    /// Validates a new password for `field`.
+    pub fn validate(&self, field: &str, password: &str) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        let missing = password_strength_violations(password, self.require_special);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                format!("{} must contain {}", field, missing.join(", ")),
+            )),
+        ))
+    }
+}
+
+/// Lists every unmet requirement so the caller can report them all at once.
+pub fn password_strength_violations(password: &str, require_special: bool) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    if password.chars().count() < PASSWORD_MIN_LEN {
+        missing.push("at least 8 characters");
+    }
+    if !password.chars().any(|c| c.is_uppercase()) {
+        missing.push("an uppercase letter");
+    }
+    if !password.chars().any(|c| c.is_ascii_digit()) {
+        missing.push("a digit");
+    }
+    if require_special && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+        missing.push("a special character");
+    }
+    missing
+}
+
+/// Accumulates field violations so a request reports every invalid field in one error.
+#[derive(Debug, Default)]
+pub struct ValidationBuilder {
+    violations: Vec<pb::FieldViolation>,
+}
+
+impl ValidationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn violation(&mut self, field: &str, message: impl Into<String>) -> &mut Self {
+        self.violations.push(pb::FieldViolation {
+            field: field.to_string(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn require(&mut self, field: &str, value: &str) -> &mut Self {
+        if value.trim().is_empty() {
+            self.violation(field, format!("{} is required", field));
+        }
+        self
+    }
+
+    /// Records the error of a pure validator (no I/O) under `field`; returns the value when it passed.
+    pub fn check<T>(&mut self, field: &str, result: Result<T, (StatusCode, Json<ConnectError>)>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err((_, Json(err))) => {
+                if err.details.is_empty() {
+                    self.violation(field, err.message);
+                } else {
+                    self.violations.extend(err.details);
+                }
+                None
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        if self.violations.is_empty() {
+            return Ok(());
+        }
+        let message = self
+            .violations
+            .iter()
+            .map(|violation| violation.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        Err((
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, message).with_details(self.violations)),
+        ))
+    }
+
+    /// Finishes validation and returns the values collected by `check`; a missing value without a
+    /// recorded violation is reported as Internal instead of panicking.
+    pub fn finish_with<T>(self, value: Option<T>) -> Result<T, (StatusCode, Json<ConnectError>)> {
+        self.finish()?;
+        value.ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ConnectError::new(ErrorCode::Internal, "validated value is missing")),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn password_meeting_every_rule_passes() {
+        assert!(password_strength_violations("Correct1pass", false).is_empty());
+        assert!(password_strength_violations("Correct1pass!", true).is_empty());
+        assert!(
+            PasswordStrength::new(true)
+                .validate("password", "Correct1pass!")
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn each_failing_rule_is_reported() {
+        assert_eq!(
+            password_strength_violations("Short1A", false),
+            vec!["at least 8 characters"]
+        );
+        assert_eq!(
+            password_strength_violations("lowercase1", false),
+            vec!["an uppercase letter"]
+        );
+        assert_eq!(password_strength_violations("NoDigitsHere", false), vec!["a digit"]);
+        assert_eq!(
+            password_strength_violations("Correct1pass", true),
+            vec!["a special character"]
+        );
+        // Whitespace does not count as a special character.
+        assert_eq!(
+            password_strength_violations("Correct1 pass", true),
+            vec!["a special character"]
+        );
+    }
+
+    #[test]
+    fn all_failing_rules_are_reported_together() {
+        assert_eq!(
+            password_strength_violations("abc", true),
+            vec![
+                "at least 8 characters",
+                "an uppercase letter",
+                "a digit",
+                "a special character"
+            ]
+        );
+        let (status, Json(err)) = PasswordStrength::default().validate("new_password", "abc").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            err.message,
+            "new_password must contain at least 8 characters, an uppercase letter, a digit"
+        );
+    }
+
+    #[test]
+    fn builder_reports_every_violation_at_once() {
+        let mut validation = ValidationBuilder::new();
+        validation.require("name", " ");
+        let email = validation.check("email", Email::parse_optional("not-an-email"));
+        let phone = validation.check("phone", Phone::parse_optional(""));
+        assert!(email.is_none());
+        assert_eq!(phone, Some(None));
+
+        let (status, Json(err)) = validation.finish().unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(matches!(err.code, ErrorCode::InvalidArgument));
+        assert_eq!(err.message, "name is required; email is invalid");
+        let fields: Vec<_> = err.details.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["name", "email"]);
+    }
+
+    #[test]
+    fn finish_with_a_missing_value_and_no_violations_is_internal() {
+        let (status, Json(err)) = ValidationBuilder::new().finish_with(None::<Email>).unwrap_err();
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(err.code, ErrorCode::Internal));
+
+        let mut validation = ValidationBuilder::new();
+        let email = validation.check("email", Email::parse("Staff@Example.com"));
+        assert_eq!(validation.finish_with(email).unwrap().as_str(), "staff@example.com");
+    }
+
+    #[test]
+    fn length_counts_characters_not_bytes() {
+        assert!(password_strength_violations("Pässwö1d", false).is_empty());
+        assert_eq!(
+            password_strength_violations("Pässw1é", false),
+            vec!["at least 8 characters"]
+        );
+    }
+
+    #[test]
+    fn email_is_trimmed_and_lowercased() {
+        let email = Email::parse("  User@Example.COM ").unwrap();
+        assert_eq!(email.as_str(), "user@example.com");
+        assert_eq!(email.as_str(), Email::parse("user@example.com").unwrap().as_str());
+        assert_eq!(
+            Email::parse_optional(" ÄBC@Example.com").unwrap().unwrap().as_str(),
+            "äbc@example.com"
+        );
+        assert!(Email::parse_optional("  ").unwrap().is_none());
+    }
+}
@@ -2,9 +2,8 @@ use axum::{Json, http::StatusCode};
 
 use crate::{
     AppState,
-    infrastructure::audit,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
         audit_action::StoreLocationAuditAction,
         ids::{StoreId, TenantId, parse_uuid},
@@ -71,19 +70,21 @@ pub async fn upsert_store_location(
         status: location.status,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            StoreLocationAuditAction::Upsert.into(),
-            Some("store_location"),
-            Some(updated.id.clone()),
-            None,
-            to_json_opt(Some(updated.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                StoreLocationAuditAction::Upsert,
+                Some("store_location"),
+                Some(updated.id.clone()),
+                None,
+                to_json_opt(Some(updated.clone())),
+                actor.clone(),
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(updated)
@@ -109,19 +110,21 @@ pub async fn delete_store_location(
         .await?;
     let deleted = rows > 0;
     if deleted {
-        audit::record_tx(
-            &mut tx,
-            audit_input(
-                Some(store_id.clone()),
-                StoreLocationAuditAction::Delete.into(),
-                Some("store_location"),
-                Some(location_id),
-                None,
-                None,
-                actor.clone(),
-            ),
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    Some(store_id.clone()),
+                    StoreLocationAuditAction::Delete,
+                    Some("store_location"),
+                    Some(location_id),
+                    None,
+                    None,
+                    actor.clone(),
+                ),
+            )
+            .await?;
     }
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(deleted)
@@ -131,10 +134,10 @@ pub fn validate_store_location(location: &pb::StoreLocation) -> Result<(), (Stat
     if location.code.is_empty() || location.name.is_empty() || location.status.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "location code/name/status are required".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "location code/name/status are required",
+            )),
         ));
     }
     Ok(())
@@ -1,5 +1,6 @@
 pub mod locations;
 pub mod repository;
+pub mod search;
 pub mod service;
 pub mod shipping;
 pub mod tax;
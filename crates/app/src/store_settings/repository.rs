@@ -1,7 +1,16 @@
+use async_trait::async_trait;
 use axum::{Json, http::StatusCode};
+use rust_decimal::{Decimal, prelude::FromPrimitive};
 use sqlx::{Postgres, Row, Transaction};
 
-use crate::{infrastructure::db, rpc::json::ConnectError};
+use crate::{
+    infrastructure::{
+        audit::{AuditInput, AuditWriter},
+        db,
+    },
+    rpc::json::{ConnectError, ErrorCode},
+    shared::money::money_to_parts,
+};
 
 pub struct PgStoreSettingsRepository<'a> {
     db: &'a sqlx::PgPool,
@@ -124,23 +133,44 @@ pub struct ShippingRateRecord {
     pub max_subtotal_amount: Option<i64>,
     pub fee_amount: i64,
     pub fee_currency: String,
+    pub carrier_name: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct TaxRuleRecord {
     pub id: String,
     pub name: String,
-    pub rate: f64,
+    pub rate: Decimal,
     pub applies_to: String,
 }
 
-#[derive(Debug, Clone)]
-pub struct StoreLookupRecord {
-    pub store_id: String,
-    pub tenant_id: String,
+const TAX_RATE_SCALE: u32 = 4;
+
+/// The API carries tax rates as doubles; they are stored exactly as `numeric(5,4)`.
+pub fn tax_rate_from_f64(rate: f64) -> Result<Decimal, (StatusCode, Json<ConnectError>)> {
+    Decimal::from_f64(rate)
+        .map(|rate| rate.normalize())
+        .filter(|rate| (Decimal::ZERO..=Decimal::ONE).contains(rate) && rate.scale() <= TAX_RATE_SCALE)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    "tax rule rate must be between 0 and 1 with at most 4 decimal places",
+                )),
+            )
+        })
 }
 
-pub trait StoreSettingsRepository {
+/// Tax on `amount` (in the smallest currency unit) at `rate`, rounded to a whole unit.
+#[cfg_attr(not(test), expect(dead_code, reason = "checkout does not apply tax rules yet"))]
+pub fn tax_amount(amount: i64, rate: Decimal) -> Decimal {
+    (Decimal::from(amount) * rate).round_dp(0)
+}
+
+/// Storage for store and mall settings. Services take `&dyn StoreSettingsRepository` so the backend can be swapped.
+#[async_trait]
+pub trait StoreSettingsRepository: Send + Sync {
     async fn fetch_store_settings_by_store(
         &self,
         store_uuid: &uuid::Uuid,
@@ -206,6 +236,28 @@ pub trait StoreSettingsRepository {
         mall: &crate::pb::pb::MallSettings,
     ) -> Result<(), (StatusCode, Json<ConnectError>)>;
 
+    /// Upserts the store settings and records `audit` in the same transaction.
+    async fn save_store_settings(
+        &self,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &crate::pb::pb::StoreSettings,
+        audit_writer: &dyn AuditWriter,
+        audit: AuditInput,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)>;
+
+    /// Inserts the store settings unless the store has them, upserts the mall settings and records
+    /// `audits`, all in one transaction.
+    async fn initialize_settings(
+        &self,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &crate::pb::pb::StoreSettings,
+        mall: &crate::pb::pb::MallSettings,
+        audit_writer: &dyn AuditWriter,
+        audits: Vec<AuditInput>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)>;
+
     async fn fetch_mall_settings_by_store(
         &self,
         store_uuid: &uuid::Uuid,
@@ -417,23 +469,9 @@ pub trait StoreSettingsRepository {
         rule_id: &uuid::Uuid,
         store_uuid: &uuid::Uuid,
     ) -> Result<u64, (StatusCode, Json<ConnectError>)>;
-
-    async fn tenant_id_by_store_id(
-        &self,
-        store_uuid: &uuid::Uuid,
-    ) -> Result<Option<String>, (StatusCode, Json<ConnectError>)>;
-
-    async fn store_by_code(
-        &self,
-        store_code: &str,
-    ) -> Result<Option<StoreLookupRecord>, (StatusCode, Json<ConnectError>)>;
-
-    async fn first_store_by_tenant(
-        &self,
-        tenant_uuid: &uuid::Uuid,
-    ) -> Result<Option<String>, (StatusCode, Json<ConnectError>)>;
 }
 
+#[async_trait]
 impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
     async fn fetch_store_settings_by_store(
         &self,
@@ -1013,6 +1051,59 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         Ok(())
     }
 
+    async fn save_store_settings(
+        &self,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &crate::pb::pb::StoreSettings,
+        audit_writer: &dyn AuditWriter,
+        audit: AuditInput,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        let (cod_fee_amount, cod_fee_currency) = money_to_parts(payment(settings).cod_fee)?;
+        let mut tx = self.db.begin().await.map_err(db::error)?;
+        self.upsert_store_settings_tx(
+            &mut tx,
+            tenant_uuid,
+            store_uuid,
+            settings,
+            cod_fee_amount,
+            cod_fee_currency,
+        )
+        .await?;
+        audit_writer.record_tx(&mut tx, audit).await?;
+        tx.commit().await.map_err(db::error)?;
+        Ok(())
+    }
+
+    async fn initialize_settings(
+        &self,
+        tenant_uuid: &uuid::Uuid,
+        store_uuid: &uuid::Uuid,
+        settings: &crate::pb::pb::StoreSettings,
+        mall: &crate::pb::pb::MallSettings,
+        audit_writer: &dyn AuditWriter,
+        audits: Vec<AuditInput>,
+    ) -> Result<(), (StatusCode, Json<ConnectError>)> {
+        let (cod_fee_amount, cod_fee_currency) = money_to_parts(payment(settings).cod_fee)?;
+        let mut tx = self.db.begin().await.map_err(db::error)?;
+        self.insert_store_settings_if_absent_tx(
+            &mut tx,
+            tenant_uuid,
+            store_uuid,
+            settings,
+            cod_fee_amount,
+            cod_fee_currency,
+        )
+        .await?;
+        self.upsert_mall_settings_tx(&mut tx, tenant_uuid, store_uuid, mall)
+            .await?;
+        for audit in audits {
+            audit_writer.record_tx(&mut tx, audit).await?;
+        }
+        tx.commit().await.map_err(db::error)?;
+        Ok(())
+    }
+
     async fn fetch_mall_settings_by_store(
         &self,
         store_uuid: &uuid::Uuid,
@@ -1371,7 +1462,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             r#"
             SELECT r.id::text as id, r.zone_id::text as zone_id, r.name,
                    r.min_subtotal_amount, r.max_subtotal_amount,
-                   r.fee_amount, r.fee_currency
+                   r.fee_amount, r.fee_currency, r.carrier_name
             FROM shipping_rates r
             JOIN shipping_zones z ON z.id = r.zone_id
             WHERE z.store_id = $1 AND r.zone_id = $2
@@ -1393,6 +1484,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
                 max_subtotal_amount: row.get("max_subtotal_amount"),
                 fee_amount: row.get("fee_amount"),
                 fee_currency: row.get("fee_currency"),
+                carrier_name: row.get("carrier_name"),
             })
             .collect())
     }
@@ -1411,8 +1503,8 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             r#"
             INSERT INTO shipping_rates (
                 id, zone_id, name, min_subtotal_amount, max_subtotal_amount,
-                fee_amount, fee_currency
-            ) VALUES ($1,$2,$3,$4,$5,$6,$7)
+                fee_amount, fee_currency, carrier_name
+            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
             "#,
         )
         .bind(rate_id)
@@ -1422,6 +1514,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(max)
         .bind(fee_amount)
         .bind(fee_currency)
+        .bind(&rate.carrier_name)
         .execute(self.db)
         .await
         .map_err(db::error)?;
@@ -1443,8 +1536,8 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             r#"
             INSERT INTO shipping_rates (
                 id, zone_id, name, min_subtotal_amount, max_subtotal_amount,
-                fee_amount, fee_currency
-            ) VALUES ($1,$2,$3,$4,$5,$6,$7)
+                fee_amount, fee_currency, carrier_name
+            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8)
             "#,
         )
         .bind(rate_id)
@@ -1454,6 +1547,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(max)
         .bind(fee_amount)
         .bind(fee_currency)
+        .bind(&rate.carrier_name)
         .execute(exec.as_mut())
         .await
         .map_err(db::error)?;
@@ -1473,8 +1567,8 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             r#"
             UPDATE shipping_rates
             SET name = $1, min_subtotal_amount = $2, max_subtotal_amount = $3,
-                fee_amount = $4, fee_currency = $5, updated_at = now()
-            WHERE id = $6
+                fee_amount = $4, fee_currency = $5, carrier_name = $6, updated_at = now()
+            WHERE id = $7
             "#,
         )
         .bind(&rate.name)
@@ -1482,6 +1576,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(max)
         .bind(fee_amount)
         .bind(fee_currency)
+        .bind(&rate.carrier_name)
         .bind(rate_id)
         .execute(self.db)
         .await
@@ -1503,8 +1598,8 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             r#"
             UPDATE shipping_rates
             SET name = $1, min_subtotal_amount = $2, max_subtotal_amount = $3,
-                fee_amount = $4, fee_currency = $5, updated_at = now()
-            WHERE id = $6
+                fee_amount = $4, fee_currency = $5, carrier_name = $6, updated_at = now()
+            WHERE id = $7
             "#,
         )
         .bind(&rate.name)
@@ -1512,6 +1607,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(max)
         .bind(fee_amount)
         .bind(fee_currency)
+        .bind(&rate.carrier_name)
         .bind(rate_id)
         .execute(exec.as_mut())
         .await
@@ -1581,7 +1677,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             .map(|row| TaxRuleRecord {
                 id: row.get("id"),
                 name: row.get("name"),
-                rate: row.get::<f64, _>("rate"),
+                rate: row.get::<Decimal, _>("rate"),
                 applies_to: row.get("applies_to"),
             })
             .collect())
@@ -1604,7 +1700,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(store_uuid)
         .bind(tenant_uuid)
         .bind(&rule.name)
-        .bind(rule.rate)
+        .bind(tax_rate_from_f64(rule.rate)?)
         .bind(&rule.applies_to)
         .execute(self.db)
         .await
@@ -1630,7 +1726,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
         .bind(store_uuid)
         .bind(tenant_uuid)
         .bind(&rule.name)
-        .bind(rule.rate)
+        .bind(tax_rate_from_f64(rule.rate)?)
         .bind(&rule.applies_to)
         .execute(exec.as_mut())
         .await
@@ -1652,7 +1748,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             "#,
         )
         .bind(&rule.name)
-        .bind(rule.rate)
+        .bind(tax_rate_from_f64(rule.rate)?)
         .bind(&rule.applies_to)
         .bind(rule_id)
         .bind(store_uuid)
@@ -1677,7 +1773,7 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             "#,
         )
         .bind(&rule.name)
-        .bind(rule.rate)
+        .bind(tax_rate_from_f64(rule.rate)?)
         .bind(&rule.applies_to)
         .bind(rule_id)
         .bind(store_uuid)
@@ -1715,45 +1811,6 @@ impl<'a> StoreSettingsRepository for PgStoreSettingsRepository<'a> {
             .map_err(db::error)?;
         Ok(res.rows_affected())
     }
-
-    async fn tenant_id_by_store_id(
-        &self,
-        store_uuid: &uuid::Uuid,
-    ) -> Result<Option<String>, (StatusCode, Json<ConnectError>)> {
-        let row = sqlx::query("SELECT tenant_id::text as tenant_id FROM stores WHERE id = $1")
-            .bind(store_uuid)
-            .fetch_optional(self.db)
-            .await
-            .map_err(db::error)?;
-        Ok(row.map(|row| row.get("tenant_id")))
-    }
-
-    async fn store_by_code(
-        &self,
-        store_code: &str,
-    ) -> Result<Option<StoreLookupRecord>, (StatusCode, Json<ConnectError>)> {
-        let row = sqlx::query("SELECT id::text as id, tenant_id::text as tenant_id FROM stores WHERE code = $1")
-            .bind(store_code)
-            .fetch_optional(self.db)
-            .await
-            .map_err(db::error)?;
-        Ok(row.map(|row| StoreLookupRecord {
-            store_id: row.get("id"),
-            tenant_id: row.get("tenant_id"),
-        }))
-    }
-
-    async fn first_store_by_tenant(
-        &self,
-        tenant_uuid: &uuid::Uuid,
-    ) -> Result<Option<String>, (StatusCode, Json<ConnectError>)> {
-        let row = sqlx::query("SELECT id::text as id FROM stores WHERE tenant_id = $1 ORDER BY created_at ASC LIMIT 1")
-            .bind(tenant_uuid)
-            .fetch_optional(self.db)
-            .await
-            .map_err(db::error)?;
-        Ok(row.map(|row| row.get("id")))
-    }
 }
 
 impl<'a> PgStoreSettingsRepository<'a> {
@@ -1761,3 +1818,37 @@ impl<'a> PgStoreSettingsRepository<'a> {
         Self { db }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn api_rates_give_tax_within_one_unit(price in 0i64..=100_000_000, basis_points in 0i64..=10_000) {
+            let rate = tax_rate_from_f64(basis_points as f64 / 10_000.0).unwrap();
+            prop_assert_eq!(rate, Decimal::new(basis_points, TAX_RATE_SCALE));
+
+            // The exact tax is price * basis_points / 10_000, so it lies between these whole units.
+            let floor = price * basis_points / 10_000;
+            let tax = tax_amount(price, rate);
+            prop_assert!(tax == Decimal::from(floor) || tax == Decimal::from(floor + 1), "tax {tax} above {floor}");
+            // Prices in whole multiples of 10_000 have an exact tax, with no binary rounding error.
+            prop_assert_eq!(tax_amount(price * 10_000, rate), Decimal::from(price * basis_points));
+        }
+
+        #[test]
+        fn rates_outside_zero_to_one_or_finer_than_four_places_are_rejected(
+            basis_points in 0i64..10_000,
+            extra_digit in 1i64..=9,
+            above_one in 1i64..=1_000_000,
+        ) {
+            let finer = (basis_points * 10 + extra_digit) as f64 / 100_000.0;
+            prop_assert!(tax_rate_from_f64(finer).is_err(), "{finer}");
+            prop_assert!(tax_rate_from_f64(1.0 + above_one as f64 / 10_000.0).is_err());
+            prop_assert!(tax_rate_from_f64(-(above_one as f64) / 10_000.0).is_err());
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use axum::{Json, http::StatusCode};
+use sqlx::Row;
+
+use crate::{
+    AppState,
+    infrastructure::{
+        db,
+        search::{SearchConfig, VALID_RANKING_RULES},
+    },
+    pb::pb,
+    rpc::json::{ConnectError, ErrorCode},
+    shared::{
+        audit_action::StoreSettingsAuditAction,
+        audit_helpers::{audit_input, to_json_opt},
+        ids::{StoreId, TenantId},
+    },
+};
+
+pub fn list_valid_ranking_rules() -> Vec<String> {
+    VALID_RANKING_RULES.iter().map(|rule| rule.to_string()).collect()
+}
+
+pub async fn get_search_settings(
+    state: &AppState,
+    store_id: String,
+) -> Result<pb::SearchSettings, (StatusCode, Json<ConnectError>)> {
+    let store_uuid = StoreId::parse(&store_id)?;
+    let row = sqlx::query("SELECT search_settings_json FROM store_catalog_settings WHERE store_id = $1")
+        .bind(store_uuid.as_uuid())
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db::error)?;
+    let config = row
+        .and_then(|row| row.get::<Option<serde_json::Value>, _>("search_settings_json"))
+        .and_then(|value| serde_json::from_value::<SearchConfig>(value).ok())
+        .unwrap_or_default();
+    Ok(search_settings_to_pb(config))
+}
+
+pub async fn update_search_settings(
+    state: &AppState,
+    store_id: String,
+    tenant_id: String,
+    settings: pb::SearchSettings,
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::SearchSettings, (StatusCode, Json<ConnectError>)> {
+    validate_ranking_rules(&settings.ranking_rules)?;
+    let store_uuid = StoreId::parse(&store_id)?;
+    let tenant_uuid = TenantId::parse(&tenant_id)?;
+    let before = get_search_settings(state, store_id.clone()).await?;
+    let config = SearchConfig {
+        typo_tolerance_enabled: settings.typo_tolerance_enabled,
+        ranking_rules: settings.ranking_rules,
+    };
+    let config_json = serde_json::to_value(&config).map_err(|err| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ConnectError::new(
+                ErrorCode::Internal,
+                format!("failed to encode search settings: {}", err),
+            )),
+        )
+    })?;
+
+    let mut tx = state.db.begin().await.map_err(db::error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO store_catalog_settings (store_id, tenant_id, search_settings_json)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (store_id)
+        DO UPDATE SET search_settings_json = EXCLUDED.search_settings_json, updated_at = now()
+        "#,
+    )
+    .bind(store_uuid.as_uuid())
+    .bind(tenant_uuid.as_uuid())
+    .bind(config_json)
+    .execute(tx.as_mut())
+    .await
+    .map_err(db::error)?;
+
+    let updated = search_settings_to_pb(config.clone());
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                StoreSettingsAuditAction::Update,
+                Some("search_settings"),
+                Some(store_id),
+                to_json_opt(Some(before)),
+                to_json_opt(Some(updated.clone())),
+                actor,
+            ),
+        )
+        .await?;
+    tx.commit().await.map_err(db::error)?;
+
+    // The product index is shared by every store, so the latest update applies index-wide.
+    state.search.ensure_settings(Some(&config)).await?;
+    Ok(updated)
+}
+
+fn validate_ranking_rules(rules: &[String]) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    let invalid = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, message)),
+        )
+    };
+    if rules.is_empty() {
+        return Err(invalid("ranking_rules is required".to_string()));
+    }
+    for (idx, rule) in rules.iter().enumerate() {
+        if !VALID_RANKING_RULES.contains(&rule.as_str()) {
+            return Err(invalid(format!("invalid ranking rule: {}", rule)));
+        }
+        if rules[..idx].contains(rule) {
+            return Err(invalid(format!("duplicate ranking rule: {}", rule)));
+        }
+    }
+    Ok(())
+}
+
+fn search_settings_to_pb(config: SearchConfig) -> pb::SearchSettings {
+    pb::SearchSettings {
+        typo_tolerance_enabled: config.typo_tolerance_enabled,
+        ranking_rules: config.ranking_rules,
+    }
+}
@@ -1,16 +1,15 @@
 use axum::{Json, http::StatusCode};
 
-use crate::rpc::request_context;
 use crate::{
     AppState,
-    infrastructure::{audit, db},
+    infrastructure::db,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
         audit_action::{MallSettingsAuditAction, StoreSettingsAuditAction},
         audit_helpers::{audit_input, to_json_opt},
         ids::{StoreId, TenantId},
-        money::{money_from_parts, money_to_parts},
+        money::money_from_parts,
     },
     store_settings::{
         locations,
@@ -186,9 +185,16 @@ pub async fn get_store_settings(
     state: &AppState,
     store_id: String,
     tenant_id: String,
+) -> Result<pb::StoreSettings, (StatusCode, Json<ConnectError>)> {
+    get_store_settings_with_repo(&PgStoreSettingsRepository::new(&state.db), store_id, tenant_id).await
+}
+
+pub async fn get_store_settings_with_repo(
+    repo: &dyn StoreSettingsRepository,
+    store_id: String,
+    tenant_id: String,
 ) -> Result<pb::StoreSettings, (StatusCode, Json<ConnectError>)> {
     let store_uuid = StoreId::parse(&store_id)?;
-    let repo = PgStoreSettingsRepository::new(&state.db);
     let row = repo.fetch_store_settings_by_store(&store_uuid.as_uuid()).await?;
 
     if let Some(row) = row {
@@ -344,7 +350,19 @@ pub async fn update_store_settings(
     settings: pb::StoreSettings,
     actor: Option<pb::ActorContext>,
 ) -> Result<pb::StoreSettings, (StatusCode, Json<ConnectError>)> {
-    let before = get_store_settings(state, store_id.clone(), tenant_id.clone())
+    let repo = PgStoreSettingsRepository::new(&state.db);
+    update_store_settings_with_repo(state, &repo, store_id, tenant_id, settings, actor).await
+}
+
+pub async fn update_store_settings_with_repo(
+    state: &AppState,
+    repo: &dyn StoreSettingsRepository,
+    store_id: String,
+    tenant_id: String,
+    settings: pb::StoreSettings,
+    actor: Option<pb::ActorContext>,
+) -> Result<pb::StoreSettings, (StatusCode, Json<ConnectError>)> {
+    let before = get_store_settings_with_repo(repo, store_id.clone(), tenant_id.clone())
         .await
         .ok();
     let merged_settings = if let Some(existing) = before.clone() {
@@ -355,34 +373,24 @@ pub async fn update_store_settings(
     validate_store_settings_for_update(before.as_ref(), &merged_settings)?;
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
-    let payment = merged_settings.payment.clone().unwrap_or_default();
-    let (cod_fee_amount, cod_fee_currency) = money_to_parts(payment.cod_fee.clone())?;
-    let repo = PgStoreSettingsRepository::new(&state.db);
-    let mut tx = state.db.begin().await.map_err(db::error)?;
-    repo.upsert_store_settings_tx(
-        &mut tx,
+    let audit = audit_input(
+        Some(store_id.clone()),
+        StoreSettingsAuditAction::Update,
+        Some("store_settings"),
+        Some(store_id.clone()),
+        to_json_opt(before),
+        to_json_opt(Some(merged_settings.clone())),
+        actor,
+    );
+    repo.save_store_settings(
         &tenant_uuid.as_uuid(),
         &store_uuid.as_uuid(),
         &merged_settings,
-        cod_fee_amount,
-        cod_fee_currency,
+        state.audit_writer.as_ref(),
+        audit,
     )
     .await?;
-
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            StoreSettingsAuditAction::Update.into(),
-            Some("store_settings"),
-            Some(store_id.clone()),
-            to_json_opt(before),
-            to_json_opt(Some(merged_settings.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
-    tx.commit().await.map_err(db::error)?;
+    state.store_cache.invalidate_store(&store_id);
 
     Ok(merged_settings)
 }
@@ -540,56 +548,53 @@ pub async fn initialize_store_settings(
     settings: pb::StoreSettings,
     mall: pb::MallSettings,
     actor: Option<pb::ActorContext>,
+) -> Result<(pb::StoreSettings, pb::MallSettings), (StatusCode, Json<ConnectError>)> {
+    let repo = PgStoreSettingsRepository::new(&state.db);
+    initialize_store_settings_with_repo(state, &repo, store_id, tenant_id, settings, mall, actor).await
+}
+
+pub async fn initialize_store_settings_with_repo(
+    state: &AppState,
+    repo: &dyn StoreSettingsRepository,
+    store_id: String,
+    tenant_id: String,
+    settings: pb::StoreSettings,
+    mall: pb::MallSettings,
+    actor: Option<pb::ActorContext>,
 ) -> Result<(pb::StoreSettings, pb::MallSettings), (StatusCode, Json<ConnectError>)> {
     validate_store_settings(&settings)?;
     validate_mall_settings(&mall)?;
     let store_uuid = StoreId::parse(&store_id)?;
     let tenant_uuid = TenantId::parse(&tenant_id)?;
-    let payment = settings.payment.clone().unwrap_or_default();
-    let (cod_fee_amount, cod_fee_currency) = money_to_parts(payment.cod_fee.clone())?;
-    let repo = PgStoreSettingsRepository::new(&state.db);
-    let mut tx = state.db.begin().await.map_err(db::error)?;
-    repo.insert_store_settings_if_absent_tx(
-        &mut tx,
-        &tenant_uuid.as_uuid(),
-        &store_uuid.as_uuid(),
-        &settings,
-        cod_fee_amount,
-        cod_fee_currency,
-    )
-    .await?;
-
-    audit::record_tx(
-        &mut tx,
+    let audits = vec![
         audit_input(
             Some(store_id.clone()),
-            StoreSettingsAuditAction::Initialize.into(),
+            StoreSettingsAuditAction::Initialize,
             Some("store_settings"),
             Some(store_id.clone()),
             None,
             to_json_opt(Some(settings.clone())),
             actor.clone(),
         ),
-    )
-    .await?;
-
-    repo.upsert_mall_settings_tx(&mut tx, &tenant_uuid.as_uuid(), &store_uuid.as_uuid(), &mall)
-        .await?;
-
-    audit::record_tx(
-        &mut tx,
         audit_input(
             Some(store_id.clone()),
-            MallSettingsAuditAction::Initialize.into(),
+            MallSettingsAuditAction::Initialize,
             Some("mall_settings"),
             Some(store_id.clone()),
             None,
             to_json_opt(Some(mall.clone())),
-            actor.clone(),
+            actor,
         ),
+    ];
+    repo.initialize_settings(
+        &tenant_uuid.as_uuid(),
+        &store_uuid.as_uuid(),
+        &settings,
+        &mall,
+        state.audit_writer.as_ref(),
+        audits,
     )
     .await?;
-    tx.commit().await.map_err(db::error)?;
 
     Ok((settings, mall))
 }
@@ -664,19 +669,21 @@ pub async fn update_mall_settings(
     repo.upsert_mall_settings_tx(&mut tx, &tenant_uuid.as_uuid(), &store_uuid.as_uuid(), &mall)
         .await?;
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            MallSettingsAuditAction::Update.into(),
-            Some("mall_settings"),
-            Some(store_id.clone()),
-            to_json_opt(before),
-            to_json_opt(Some(mall.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                MallSettingsAuditAction::Update,
+                Some("mall_settings"),
+                Some(store_id.clone()),
+                to_json_opt(before),
+                to_json_opt(Some(mall.clone())),
+                actor.clone(),
+            ),
+        )
+        .await?;
     tx.commit().await.map_err(db::error)?;
 
     Ok(mall)
@@ -764,153 +771,6 @@ pub async fn delete_tax_rule(
     tax::delete_tax_rule(state, store_id, tenant_id, rule_id, actor).await
 }
 
-pub async fn resolve_store_context(
-    state: &AppState,
-    store: Option<pb::StoreContext>,
-    tenant: Option<pb::TenantContext>,
-) -> Result<(String, String), (StatusCode, Json<ConnectError>)> {
-    if let Some(ctx) = request_context::current() {
-        if let Some(auth_store) = ctx.store_id.as_deref()
-            && let Some(store_id) = store.as_ref().and_then(|s| {
-                if s.store_id.is_empty() {
-                    None
-                } else {
-                    Some(s.store_id.as_str())
-                }
-            })
-            && store_id != auth_store
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "store_id does not match token".to_string(),
-                }),
-            ));
-        }
-        if let Some(auth_tenant) = ctx.tenant_id.as_deref()
-            && let Some(tenant_id) = tenant.as_ref().and_then(|t| {
-                if t.tenant_id.is_empty() {
-                    None
-                } else {
-                    Some(t.tenant_id.as_str())
-                }
-            })
-            && tenant_id != auth_tenant
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "tenant_id does not match token".to_string(),
-                }),
-            ));
-        }
-    }
-
-    if let Some(store_id) = store.as_ref().and_then(|s| {
-        if s.store_id.is_empty() {
-            None
-        } else {
-            Some(s.store_id.as_str())
-        }
-    }) {
-        let store_uuid = StoreId::parse(store_id)?;
-        let repo = PgStoreSettingsRepository::new(&state.db);
-        let tenant_id = repo.tenant_id_by_store_id(&store_uuid.as_uuid()).await?;
-        let Some(tenant_id) = tenant_id else {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_id not found".to_string(),
-                }),
-            ));
-        };
-        return Ok((store_id.to_string(), tenant_id));
-    }
-    if let Some(store_code) = store.as_ref().and_then(|s| {
-        if s.store_code.is_empty() {
-            None
-        } else {
-            Some(s.store_code.as_str())
-        }
-    }) {
-        let repo = PgStoreSettingsRepository::new(&state.db);
-        let row = repo.store_by_code(store_code).await?;
-        let Some(row) = row else {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "store_code not found".to_string(),
-                }),
-            ));
-        };
-        let store_id = row.store_id;
-        let tenant_id = row.tenant_id;
-        if let Some(ctx) = request_context::current()
-            && let Some(auth_store) = ctx.store_id
-            && auth_store != store_id
-        {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::PermissionDenied,
-                    message: "store_code does not match token".to_string(),
-                }),
-            ));
-        }
-        return Ok((store_id, tenant_id));
-    }
-    if let Some(tenant_id) = tenant.and_then(|t| {
-        if t.tenant_id.is_empty() {
-            None
-        } else {
-            Some(t.tenant_id)
-        }
-    }) {
-        let tenant_uuid = TenantId::parse(&tenant_id)?;
-        let repo = PgStoreSettingsRepository::new(&state.db);
-        let store_id = repo.first_store_by_tenant(&tenant_uuid.as_uuid()).await?;
-        let Some(store_id) = store_id else {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: "tenant_id not found".to_string(),
-                }),
-            ));
-        };
-        return Ok((store_id, tenant_id));
-    }
-    if let Some(ctx) = request_context::current() {
-        if let Some(store_id) = ctx.store_id {
-            let store_uuid = StoreId::parse(&store_id)?;
-            let repo = PgStoreSettingsRepository::new(&state.db);
-            let tenant_id = repo.tenant_id_by_store_id(&store_uuid.as_uuid()).await?;
-            if let Some(tenant_id) = tenant_id {
-                return Ok((store_id, tenant_id));
-            }
-        }
-        if let Some(tenant_id) = ctx.tenant_id {
-            let tenant_uuid = TenantId::parse(&tenant_id)?;
-            let repo = PgStoreSettingsRepository::new(&state.db);
-            let store_id = repo.first_store_by_tenant(&tenant_uuid.as_uuid()).await?;
-            if let Some(store_id) = store_id {
-                return Ok((store_id, tenant_id));
-            }
-        }
-    }
-    Err((
-        StatusCode::BAD_REQUEST,
-        Json(ConnectError {
-            code: crate::rpc::json::ErrorCode::InvalidArgument,
-            message: "store.store_id or tenant.tenant_id is required".to_string(),
-        }),
-    ))
-}
-
 pub fn validate_store_settings(settings: &pb::StoreSettings) -> Result<(), (StatusCode, Json<ConnectError>)> {
     let profile = settings.profile.as_ref();
     let contact = settings.contact.as_ref();
@@ -934,10 +794,10 @@ pub fn validate_store_settings(settings: &pb::StoreSettings) -> Result<(), (Stat
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "store settings required fields are missing".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "store settings required fields are missing",
+            )),
         ));
     }
     Ok(())
@@ -984,11 +844,173 @@ pub fn validate_mall_settings(mall: &pb::MallSettings) -> Result<(), (StatusCode
     if !(0.0..=1.0).contains(&mall.commission_rate) {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "commission_rate must be between 0 and 1".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "commission_rate must be between 0 and 1",
+            )),
         ));
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{self, MockStoreSettingsRepository};
+
+    fn record(store_name: &str) -> StoreSettingsRecord {
+        StoreSettingsRecord {
+            store_name: store_name.to_string(),
+            legal_name: format!("{store_name} K.K."),
+            contact_email: "shop@example.com".to_string(),
+            contact_phone: "03-0000-0000".to_string(),
+            address_prefecture: "Tokyo".to_string(),
+            address_city: "Shibuya".to_string(),
+            address_line1: "1-1-1".to_string(),
+            address_line2: None,
+            legal_notice: "Notice".to_string(),
+            default_language: "ja".to_string(),
+            primary_domain: None,
+            subdomain: None,
+            https_enabled: true,
+            currency: "JPY".to_string(),
+            tax_mode: "exclusive".to_string(),
+            tax_rounding: "round".to_string(),
+            order_initial_status: "pending_payment".to_string(),
+            cod_enabled: true,
+            cod_fee_amount: Some(300),
+            cod_fee_currency: Some("JPY".to_string()),
+            bank_transfer_enabled: false,
+            bank_name: String::new(),
+            bank_branch: String::new(),
+            bank_account_type: String::new(),
+            bank_account_number: String::new(),
+            bank_account_name: String::new(),
+            theme: "default".to_string(),
+            brand_color: "#111827".to_string(),
+            logo_url: None,
+            favicon_url: None,
+            time_zone: "Asia/Tokyo".to_string(),
+            sku_code_regex: None,
+        }
+    }
+
+    fn ids() -> (String, String) {
+        (uuid::Uuid::new_v4().to_string(), uuid::Uuid::new_v4().to_string())
+    }
+
+    fn store_name(settings: &pb::StoreSettings) -> &str {
+        settings
+            .profile
+            .as_ref()
+            .map(|p| p.store_name.as_str())
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn settings_fall_back_from_store_to_tenant_to_defaults() {
+        let (store_id, tenant_id) = ids();
+        let get = |repo: MockStoreSettingsRepository| {
+            let (store_id, tenant_id) = (store_id.clone(), tenant_id.clone());
+            async move { get_store_settings_with_repo(&repo, store_id, tenant_id).await.unwrap() }
+        };
+
+        let settings = get(MockStoreSettingsRepository {
+            store_settings: Some(record("Store row")),
+            tenant_settings: Some(record("Tenant row")),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(store_name(&settings), "Store row");
+        assert_eq!(
+            settings.payment.and_then(|p| p.cod_fee),
+            Some(money_from_parts(300, "JPY".to_string()))
+        );
+
+        let settings = get(MockStoreSettingsRepository {
+            tenant_settings: Some(record("Tenant row")),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(store_name(&settings), "Tenant row");
+
+        let settings = get(MockStoreSettingsRepository {
+            store_name: Some("Named store".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(settings, default_store_settings("Named store".to_string()));
+        let settings = get(MockStoreSettingsRepository::default()).await;
+        assert_eq!(store_name(&settings), "Store");
+    }
+
+    #[tokio::test]
+    async fn update_merges_over_existing_settings() {
+        let audit = test_support::RecordingAuditWriter::default();
+        let mut state = test_support::app_state_without_db();
+        state.audit_writer = std::sync::Arc::new(audit.clone());
+        let repo = MockStoreSettingsRepository {
+            store_settings: Some(record("Tokyo Shop")),
+            ..Default::default()
+        };
+        let (store_id, tenant_id) = ids();
+        let incoming = pb::StoreSettings {
+            contact: Some(pb::StoreContact {
+                contact_email: "new@example.com".to_string(),
+                contact_phone: String::new(),
+            }),
+            ..Default::default()
+        };
+
+        let updated = update_store_settings_with_repo(&state, &repo, store_id, tenant_id, incoming, None)
+            .await
+            .unwrap();
+        let contact = updated.contact.clone().unwrap_or_default();
+        assert_eq!(contact.contact_email, "new@example.com");
+        assert_eq!(contact.contact_phone, "03-0000-0000");
+        assert_eq!(store_name(&updated), "Tokyo Shop");
+        assert_eq!(*repo.saved_settings.lock().unwrap(), vec![updated]);
+        assert_eq!(audit.events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn initialize_requires_complete_settings() {
+        let audit = test_support::RecordingAuditWriter::default();
+        let mut state = test_support::app_state_without_db();
+        state.audit_writer = std::sync::Arc::new(audit.clone());
+        let repo = MockStoreSettingsRepository::default();
+        let (store_id, tenant_id) = ids();
+
+        let incomplete = default_store_settings("Tokyo Shop".to_string());
+        let (_, Json(err)) = initialize_store_settings_with_repo(
+            &state,
+            &repo,
+            store_id.clone(),
+            tenant_id.clone(),
+            incomplete,
+            default_mall_settings(),
+            None,
+        )
+        .await
+        .expect_err("contact and address are empty");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+        assert!(repo.saved_settings.lock().unwrap().is_empty());
+        assert!(audit.events.lock().unwrap().is_empty());
+
+        let settings = store_settings_from_record(record("Tokyo Shop"));
+        let mall = pb::MallSettings {
+            enabled: true,
+            commission_rate: 0.1,
+            vendor_approval_required: false,
+        };
+        let (settings, mall) =
+            initialize_store_settings_with_repo(&state, &repo, store_id, tenant_id, settings, mall, None)
+                .await
+                .unwrap();
+        assert_eq!(store_name(&settings), "Tokyo Shop");
+        assert!(mall.enabled);
+        assert_eq!(*repo.saved_settings.lock().unwrap(), vec![settings]);
+        assert_eq!(*repo.saved_mall_settings.lock().unwrap(), vec![mall]);
+        assert_eq!(audit.events.lock().unwrap().len(), 2);
+    }
+}
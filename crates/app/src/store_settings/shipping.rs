@@ -1,10 +1,10 @@
 use axum::{Json, http::StatusCode};
+use sqlx::Row;
 
 use crate::{
     AppState,
-    infrastructure::audit,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
         audit_action::{ShippingRateAuditAction, ShippingZoneAuditAction},
         ids::{StoreId, TenantId, parse_uuid},
@@ -81,19 +81,21 @@ pub async fn upsert_shipping_zone(
         prefectures: zone.prefectures,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            ShippingZoneAuditAction::Upsert.into(),
-            Some("shipping_zone"),
-            Some(updated.id.clone()),
-            None,
-            to_json_opt(Some(updated.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                ShippingZoneAuditAction::Upsert,
+                Some("shipping_zone"),
+                Some(updated.id.clone()),
+                None,
+                to_json_opt(Some(updated.clone())),
+                actor.clone(),
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(updated)
@@ -117,19 +119,21 @@ pub async fn delete_shipping_zone(
         .await?;
     let deleted = rows > 0;
     if deleted {
-        audit::record_tx(
-            &mut tx,
-            audit_input(
-                Some(store_id.clone()),
-                ShippingZoneAuditAction::Delete.into(),
-                Some("shipping_zone"),
-                Some(zone_id),
-                None,
-                None,
-                actor.clone(),
-            ),
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    Some(store_id.clone()),
+                    ShippingZoneAuditAction::Delete,
+                    Some("shipping_zone"),
+                    Some(zone_id),
+                    None,
+                    None,
+                    actor.clone(),
+                ),
+            )
+            .await?;
     }
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(deleted)
@@ -160,6 +164,7 @@ pub async fn list_shipping_rates(
                 .zip(Some(row.fee_currency.clone()))
                 .map(|(amount, currency)| money_from_parts(amount, currency)),
             fee: Some(money_from_parts(row.fee_amount, row.fee_currency)),
+            carrier_name: row.carrier_name,
         })
         .collect())
 }
@@ -182,8 +187,13 @@ pub async fn upsert_shipping_rate(
     let max = rate.max_subtotal.clone().map(|m| m.amount);
     let repo = PgStoreSettingsRepository::new(&state.db);
     let mut tx = state.db.begin().await.map_err(crate::infrastructure::db::error)?;
-    if rate.id.is_empty() {
-        let zone_uuid = parse_uuid(&rate.zone_id, "zone_id")?;
+    let zone_uuid = if rate.id.is_empty() {
+        Some(parse_uuid(&rate.zone_id, "zone_id")?)
+    } else {
+        None
+    };
+    ensure_no_overlapping_rate(&mut tx, zone_uuid, rate_id, &rate.carrier_name, min, max).await?;
+    if let Some(zone_uuid) = zone_uuid {
         repo.insert_shipping_rate_tx(
             &mut tx,
             &rate_id,
@@ -208,26 +218,87 @@ pub async fn upsert_shipping_rate(
         min_subtotal: rate.min_subtotal,
         max_subtotal: rate.max_subtotal,
         fee: rate.fee,
+        carrier_name: rate.carrier_name,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            ShippingRateAuditAction::Upsert.into(),
-            Some("shipping_rate"),
-            Some(updated.id.clone()),
-            None,
-            to_json_opt(Some(updated.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                ShippingRateAuditAction::Upsert,
+                Some("shipping_rate"),
+                Some(updated.id.clone()),
+                None,
+                to_json_opt(Some(updated.clone())),
+                actor.clone(),
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(updated)
 }
 
+// Rates of the same carrier in a zone must not cover the same subtotal; bounds are inclusive
+// (see calculate_shipping_fee), so adjacent ranges such as ..=999 and 1000.. are fine. Rates of
+// different carriers may overlap because the customer picks one at checkout.
+async fn ensure_no_overlapping_rate(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    zone_uuid: Option<uuid::Uuid>,
+    rate_id: uuid::Uuid,
+    carrier_name: &str,
+    min: Option<i64>,
+    max: Option<i64>,
+) -> Result<(), (StatusCode, Json<ConnectError>)> {
+    // Locking the zone serializes concurrent upserts into it. Updates keep their current zone.
+    let zone = sqlx::query(
+        r#"
+        SELECT id FROM shipping_zones
+        WHERE id = COALESCE($1, (SELECT zone_id FROM shipping_rates WHERE id = $2))
+        FOR UPDATE
+        "#,
+    )
+    .bind(zone_uuid)
+    .bind(rate_id)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(crate::infrastructure::db::error)?;
+    let Some(zone) = zone else {
+        return Ok(());
+    };
+    let overlapping = sqlx::query(
+        r#"
+        SELECT 1 FROM shipping_rates
+        WHERE zone_id = $1 AND id <> $2 AND carrier_name = $3
+          AND NOT (COALESCE(max_subtotal_amount, $6) < COALESCE($4, $5)
+                   OR COALESCE(min_subtotal_amount, $5) > COALESCE($7, $6))
+        LIMIT 1
+        "#,
+    )
+    .bind(zone.get::<uuid::Uuid, _>("id"))
+    .bind(rate_id)
+    .bind(carrier_name)
+    .bind(min)
+    .bind(i64::MIN)
+    .bind(i64::MAX)
+    .bind(max)
+    .fetch_optional(tx.as_mut())
+    .await
+    .map_err(crate::infrastructure::db::error)?;
+    if overlapping.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ConnectError::new(
+                ErrorCode::AlreadyExists,
+                "shipping rate overlaps with existing rate",
+            )),
+        ));
+    }
+    Ok(())
+}
+
 pub async fn delete_shipping_rate(
     state: &AppState,
     store_id: String,
@@ -244,34 +315,101 @@ pub async fn delete_shipping_rate(
         .await?;
     let deleted = rows > 0;
     if deleted {
-        audit::record_tx(
-            &mut tx,
-            audit_input(
-                Some(store_id.clone()),
-                ShippingRateAuditAction::Delete.into(),
-                Some("shipping_rate"),
-                Some(rate_id),
-                None,
-                None,
-                actor.clone(),
-            ),
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    Some(store_id.clone()),
+                    ShippingRateAuditAction::Delete,
+                    Some("shipping_rate"),
+                    Some(rate_id),
+                    None,
+                    None,
+                    actor.clone(),
+                ),
+            )
+            .await?;
     }
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(deleted)
 }
 
+#[derive(Debug, Clone)]
+pub struct ShippingQuote {
+    pub fee_amount: i64,
+    pub carrier_name: Option<String>,
+}
+
+pub async fn calculate_shipping_fee(
+    conn: &mut sqlx::PgConnection,
+    store_uuid: uuid::Uuid,
+    zone_id: &str,
+    rate_id: &str,
+    prefecture: &str,
+    cart_subtotal: i64,
+    currency: &str,
+) -> Result<ShippingQuote, (StatusCode, Json<ConnectError>)> {
+    let invalid = |message: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ConnectError::new(ErrorCode::InvalidArgument, message.to_string())),
+        )
+    };
+    let rate_uuid = parse_uuid(rate_id, "shipping_rate_id")?;
+    let zone_uuid = parse_uuid(zone_id, "shipping_zone_id")?;
+    let row = sqlx::query(
+        r#"
+        SELECT r.min_subtotal_amount, r.max_subtotal_amount, r.fee_amount, r.fee_currency, r.carrier_name,
+               EXISTS (
+                   SELECT 1 FROM shipping_zone_prefectures zp
+                   WHERE zp.zone_id = z.id
+                     AND (zp.prefecture_code = $4 OR zp.prefecture_name = $4)
+               ) as covers_prefecture
+        FROM shipping_rates r
+        JOIN shipping_zones z ON z.id = r.zone_id
+        WHERE r.id = $1 AND r.zone_id = $2 AND z.store_id = $3
+        "#,
+    )
+    .bind(rate_uuid)
+    .bind(zone_uuid)
+    .bind(store_uuid)
+    .bind(prefecture.trim())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(crate::infrastructure::db::error)?;
+    let Some(row) = row else {
+        return Err(invalid("shipping_rate_id not found"));
+    };
+    if !row.get::<bool, _>("covers_prefecture") {
+        return Err(invalid("shipping_rate_id is invalid for your address"));
+    }
+    let fee_currency: String = row.get("fee_currency");
+    if fee_currency != currency {
+        return Err(invalid("shipping_rate_id currency does not match the cart"));
+    }
+    let min: Option<i64> = row.get("min_subtotal_amount");
+    let max: Option<i64> = row.get("max_subtotal_amount");
+    if min.is_some_and(|min| cart_subtotal < min) || max.is_some_and(|max| cart_subtotal > max) {
+        return Err(invalid("shipping_rate_id does not apply to the cart subtotal"));
+    }
+    let carrier_name: String = row.get("carrier_name");
+    Ok(ShippingQuote {
+        fee_amount: row.get("fee_amount"),
+        carrier_name: Some(carrier_name).filter(|name| !name.is_empty()),
+    })
+}
+
 pub fn validate_shipping_rate(rate: &pb::ShippingRate) -> Result<(), (StatusCode, Json<ConnectError>)> {
     if let (Some(min), Some(max)) = (&rate.min_subtotal, &rate.max_subtotal)
         && min.amount > max.amount
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "min_subtotal must be <= max_subtotal".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "min_subtotal must be <= max_subtotal",
+            )),
         ));
     }
     if let Some(fee) = &rate.fee
@@ -279,10 +417,7 @@ pub fn validate_shipping_rate(rate: &pb::ShippingRate) -> Result<(), (StatusCode
     {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "fee must be >= 0".to_string(),
-            }),
+            Json(ConnectError::new(ErrorCode::InvalidArgument, "fee must be >= 0")),
         ));
     }
     Ok(())
@@ -293,10 +428,10 @@ pub fn validate_shipping_zone(zone: &pb::ShippingZone) -> Result<(), (StatusCode
         if !is_valid_prefecture_code(&pref.code) {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ConnectError {
-                    code: crate::rpc::json::ErrorCode::InvalidArgument,
-                    message: format!("invalid prefecture code: {}", pref.code),
-                }),
+                Json(ConnectError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("invalid prefecture code: {}", pref.code),
+                )),
             ));
         }
     }
@@ -313,3 +448,74 @@ fn is_valid_prefecture_code(code: &str) -> bool {
         Err(_) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::shared::test_support;
+
+    fn yen(amount: i64) -> Option<pb::Money> {
+        Some(money_from_parts(amount, "JPY".to_string()))
+    }
+
+    fn rate(zone_id: uuid::Uuid, carrier_name: &str, min: Option<i64>, max: Option<i64>) -> pb::ShippingRate {
+        pb::ShippingRate {
+            zone_id: zone_id.to_string(),
+            name: format!("{carrier_name} {min:?}-{max:?}"),
+            min_subtotal: min.and_then(yen),
+            max_subtotal: max.and_then(yen),
+            fee: yen(500),
+            carrier_name: carrier_name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    async fn overlapping_rates_of_one_carrier_are_rejected(db: PgPool) {
+        let state = test_support::app_state(db.clone());
+        let store = test_support::create_store(&db).await;
+        let zone_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO shipping_zones (tenant_id, store_id, name) VALUES ($1, $2, 'Japan') RETURNING id",
+        )
+        .bind(store.tenant_id)
+        .bind(store.store_id)
+        .fetch_one(&db)
+        .await
+        .unwrap();
+        let upsert = |rate: pb::ShippingRate| {
+            upsert_shipping_rate(
+                &state,
+                store.store_id.to_string(),
+                store.tenant_id.to_string(),
+                rate,
+                None,
+            )
+        };
+
+        let (_, Json(err)) = upsert(rate(zone_id, "yamato", Some(1000), Some(999)))
+            .await
+            .expect_err("min above max");
+        assert!(matches!(err.code, ErrorCode::InvalidArgument), "{err:?}");
+
+        let small = upsert(rate(zone_id, "yamato", None, Some(999))).await.unwrap();
+        // Inclusive bounds: 1000.. starts right after ..=999.
+        upsert(rate(zone_id, "yamato", Some(1000), None)).await.unwrap();
+        let (status, Json(err)) = upsert(rate(zone_id, "yamato", Some(500), Some(1500)))
+            .await
+            .expect_err("range overlaps both rates");
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(matches!(err.code, ErrorCode::AlreadyExists), "{err:?}");
+        upsert(rate(zone_id, "sagawa", Some(500), Some(1500))).await.unwrap();
+
+        // An update is checked against the other rates of its zone, not against itself.
+        let mut widened = rate(zone_id, "yamato", None, Some(1000));
+        widened.id = small.id.clone();
+        let (_, Json(err)) = upsert(widened).await.expect_err("widened range overlaps 1000..");
+        assert!(matches!(err.code, ErrorCode::AlreadyExists), "{err:?}");
+        let mut renamed = rate(zone_id, "yamato", None, Some(999));
+        renamed.id = small.id;
+        upsert(renamed).await.unwrap();
+    }
+}
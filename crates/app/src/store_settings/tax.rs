@@ -1,15 +1,15 @@
 use axum::{Json, http::StatusCode};
+use rust_decimal::prelude::ToPrimitive;
 
 use crate::{
     AppState,
-    infrastructure::audit,
     pb::pb,
-    rpc::json::ConnectError,
+    rpc::json::{ConnectError, ErrorCode},
     shared::{
         audit_action::TaxRuleAuditAction,
         ids::{StoreId, TenantId, parse_uuid},
     },
-    store_settings::repository::{PgStoreSettingsRepository, StoreSettingsRepository},
+    store_settings::repository::{PgStoreSettingsRepository, StoreSettingsRepository, tax_rate_from_f64},
 };
 
 use crate::shared::audit_helpers::{audit_input, to_json_opt};
@@ -26,7 +26,7 @@ pub async fn list_tax_rules(
         .map(|row| pb::TaxRule {
             id: row.id,
             name: row.name,
-            rate: row.rate,
+            rate: row.rate.to_f64().unwrap_or_default(),
             applies_to: row.applies_to,
         })
         .collect())
@@ -65,19 +65,21 @@ pub async fn upsert_tax_rule(
         applies_to: rule.applies_to,
     };
 
-    audit::record_tx(
-        &mut tx,
-        audit_input(
-            Some(store_id.clone()),
-            TaxRuleAuditAction::Upsert.into(),
-            Some("tax_rule"),
-            Some(updated.id.clone()),
-            None,
-            to_json_opt(Some(updated.clone())),
-            actor.clone(),
-        ),
-    )
-    .await?;
+    state
+        .audit_writer
+        .record_tx(
+            &mut tx,
+            audit_input(
+                Some(store_id.clone()),
+                TaxRuleAuditAction::Upsert,
+                Some("tax_rule"),
+                Some(updated.id.clone()),
+                None,
+                to_json_opt(Some(updated.clone())),
+                actor.clone(),
+            ),
+        )
+        .await?;
 
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(updated)
@@ -99,19 +101,21 @@ pub async fn delete_tax_rule(
         .await?;
     let deleted = rows > 0;
     if deleted {
-        audit::record_tx(
-            &mut tx,
-            audit_input(
-                Some(store_id.clone()),
-                TaxRuleAuditAction::Delete.into(),
-                Some("tax_rule"),
-                Some(rule_id),
-                None,
-                None,
-                actor.clone(),
-            ),
-        )
-        .await?;
+        state
+            .audit_writer
+            .record_tx(
+                &mut tx,
+                audit_input(
+                    Some(store_id.clone()),
+                    TaxRuleAuditAction::Delete,
+                    Some("tax_rule"),
+                    Some(rule_id),
+                    None,
+                    None,
+                    actor.clone(),
+                ),
+            )
+            .await?;
     }
     tx.commit().await.map_err(crate::infrastructure::db::error)?;
     Ok(deleted)
@@ -121,20 +125,12 @@ pub fn validate_tax_rule(rule: &pb::TaxRule) -> Result<(), (StatusCode, Json<Con
     if rule.name.is_empty() || rule.applies_to.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tax rule name/applies_to are required".to_string(),
-            }),
-        ));
-    }
-    if !(0.0..=1.0).contains(&rule.rate) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ConnectError {
-                code: crate::rpc::json::ErrorCode::InvalidArgument,
-                message: "tax rule rate must be between 0 and 1".to_string(),
-            }),
+            Json(ConnectError::new(
+                ErrorCode::InvalidArgument,
+                "tax rule name/applies_to are required",
+            )),
         ));
     }
+    tax_rate_from_f64(rule.rate)?;
     Ok(())
 }
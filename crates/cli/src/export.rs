@@ -0,0 +1,322 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::Parser;
+use futures_util::TryStreamExt;
+use sqlx::{PgPool, Postgres, QueryBuilder, postgres::PgPoolOptions, types::Uuid};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::Instant,
+};
+
+// Above this many line rows the export switches to COPY ... TO STDOUT.
+const COPY_THRESHOLD: i64 = 100_000;
+
+const CSV_HEADER: &[&str] = &[
+    "order_id",
+    "created_at",
+    "status",
+    "customer_id",
+    "customer_name",
+    "product_title",
+    "sku",
+    "quantity",
+    "unit_price",
+    "currency",
+    "line_total",
+    "tax_amount",
+    "discount_amount",
+    "shipping_amount",
+    "order_total",
+];
+
+#[derive(Parser, Debug)]
+pub struct ExportOrdersArgs {
+    /// PostgreSQL connection string
+    #[arg(long, env = "DATABASE_URL")]
+    db_url: String,
+    /// Store to export
+    #[arg(long, env = "EXPORT_STORE_ID")]
+    store_id: String,
+    /// Include orders created at or after this RFC3339 timestamp
+    #[arg(long)]
+    from: Option<String>,
+    /// Include orders created before this RFC3339 timestamp
+    #[arg(long)]
+    to: Option<String>,
+    /// Filter by order status
+    #[arg(long)]
+    status: Option<String>,
+    /// Output CSV file
+    #[arg(long)]
+    output: PathBuf,
+    /// Rows fetched per batch
+    #[arg(long, env = "EXPORT_BATCH_SIZE", default_value_t = 1000)]
+    batch_size: usize,
+}
+
+#[derive(Debug)]
+struct ExportFilters {
+    store_id: Uuid,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    status: Option<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OrderLineRow {
+    order_id: Uuid,
+    line_id: Uuid,
+    created_at: DateTime<Utc>,
+    status: String,
+    customer_id: Option<String>,
+    customer_name: String,
+    product_title: String,
+    sku: String,
+    quantity: i32,
+    unit_price: i64,
+    currency: String,
+    line_total: i64,
+    order_total: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CountRow {
+    count: i64,
+}
+
+pub async fn run_export_orders(args: ExportOrdersArgs) -> Result<()> {
+    let filters = ExportFilters {
+        store_id: Uuid::parse_str(&args.store_id).context("--store-id must be a uuid")?,
+        from: args.from.as_deref().map(parse_timestamp).transpose()?,
+        to: args.to.as_deref().map(parse_timestamp).transpose()?,
+        status: args.status.filter(|v| !v.is_empty()).map(parse_status).transpose()?,
+    };
+    if let (Some(from), Some(to)) = (filters.from, filters.to)
+        && from > to
+    {
+        bail!("--from must be before --to");
+    }
+    let batch_size = args.batch_size.max(1) as i64;
+
+    let db = PgPoolOptions::new().max_connections(2).connect(&args.db_url).await?;
+    let file = File::create(&args.output).with_context(|| format!("failed to create {}", args.output.display()))?;
+    let mut out = BufWriter::new(file);
+
+    let started_at = Instant::now();
+    let mut builder = QueryBuilder::<Postgres>::new(format!("SELECT COUNT(*) as count {} WHERE ", ORDER_LINES_FROM));
+    apply_filters(&mut builder, &filters);
+    let count = builder.build_query_as::<CountRow>().fetch_one(&db).await?.count;
+
+    tracing::info!(count, batch_size, filters = ?filters, "order export started");
+
+    let total = if count > COPY_THRESHOLD {
+        export_with_copy(&db, &filters, &mut out).await?
+    } else {
+        export_with_cursor(&db, &filters, batch_size, &mut out).await?
+    };
+    out.flush()?;
+
+    tracing::info!(
+        total,
+        elapsed_ms = started_at.elapsed().as_millis(),
+        "order export completed"
+    );
+    Ok(())
+}
+
+async fn export_with_cursor(
+    db: &PgPool,
+    filters: &ExportFilters,
+    batch_size: i64,
+    out: &mut impl Write,
+) -> Result<usize> {
+    write_csv_row(out, CSV_HEADER.iter().map(|v| v.to_string()))?;
+
+    let mut cursor: Option<(DateTime<Utc>, Uuid, Uuid)> = None;
+    let mut total = 0usize;
+    loop {
+        let mut builder = QueryBuilder::<Postgres>::new(format!("{} {} WHERE ", ORDER_LINES_COLUMNS, ORDER_LINES_FROM));
+        apply_filters(&mut builder, filters);
+        if let Some((created_at, order_id, line_id)) = cursor {
+            builder.push(" AND (o.created_at, o.id, oi.id) > (");
+            builder.push_bind(created_at);
+            builder.push(", ");
+            builder.push_bind(order_id);
+            builder.push(", ");
+            builder.push_bind(line_id);
+            builder.push(")");
+        }
+        builder.push(" ORDER BY o.created_at ASC, o.id ASC, oi.id ASC LIMIT ");
+        builder.push_bind(batch_size);
+
+        let rows = builder.build_query_as::<OrderLineRow>().fetch_all(db).await?;
+        if rows.is_empty() {
+            break;
+        }
+
+        let rows_len = rows.len();
+        for row in &rows {
+            write_csv_row(
+                out,
+                [
+                    row.order_id.to_string(),
+                    row.created_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                    row.status.clone(),
+                    row.customer_id.clone().unwrap_or_default(),
+                    row.customer_name.clone(),
+                    row.product_title.clone(),
+                    row.sku.clone(),
+                    row.quantity.to_string(),
+                    row.unit_price.to_string(),
+                    row.currency.clone(),
+                    row.line_total.to_string(),
+                    // Tax, discount and shipping are not stored per order yet.
+                    "0".to_string(),
+                    "0".to_string(),
+                    "0".to_string(),
+                    row.order_total.to_string(),
+                ],
+            )?;
+        }
+        if let Some(last) = rows.last() {
+            cursor = Some((last.created_at, last.order_id, last.line_id));
+        }
+        total += rows_len;
+        tracing::info!(processed = total, batch_size = rows_len, "order export batch completed");
+        if (rows_len as i64) < batch_size {
+            break;
+        }
+    }
+    Ok(total)
+}
+
+async fn export_with_copy(db: &PgPool, filters: &ExportFilters, out: &mut impl Write) -> Result<usize> {
+    // COPY does not accept bind parameters, so filters are inlined from already-validated values.
+    let mut conditions = vec![format!("p.store_id = '{}'", filters.store_id)];
+    if let Some(from) = filters.from {
+        conditions.push(format!(
+            "o.created_at >= '{}'",
+            from.to_rfc3339_opts(SecondsFormat::Micros, true)
+        ));
+    }
+    if let Some(to) = filters.to {
+        conditions.push(format!(
+            "o.created_at < '{}'",
+            to.to_rfc3339_opts(SecondsFormat::Micros, true)
+        ));
+    }
+    if let Some(status) = &filters.status {
+        conditions.push(format!("o.status = '{}'", status));
+    }
+    let statement = format!(
+        r#"
+        COPY (
+            SELECT o.id as order_id,
+                   to_char(o.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') as created_at,
+                   o.status,
+                   o.customer_id,
+                   COALESCE(cp.name, c.name, '') as customer_name,
+                   p.title as product_title,
+                   v.sku,
+                   oi.quantity,
+                   oi.price_amount as unit_price,
+                   oi.price_currency as currency,
+                   oi.price_amount * oi.quantity as line_total,
+                   0 as tax_amount,
+                   0 as discount_amount,
+                   0 as shipping_amount,
+                   o.total_amount as order_total
+            {}
+            WHERE {}
+            ORDER BY o.created_at ASC, o.id ASC, oi.id ASC
+        ) TO STDOUT WITH CSV HEADER
+        "#,
+        ORDER_LINES_FROM,
+        conditions.join(" AND ")
+    );
+
+    let mut conn = db.acquire().await?;
+    let mut stream = conn.copy_out_raw(&statement).await?;
+    let mut lines = 0usize;
+    while let Some(chunk) = stream.try_next().await? {
+        lines += chunk.iter().filter(|b| **b == b'\n').count();
+        out.write_all(&chunk)?;
+    }
+    // Subtract the header line.
+    Ok(lines.saturating_sub(1))
+}
+
+const ORDER_LINES_FROM: &str = r#"
+    FROM orders o
+    JOIN order_items oi ON oi.order_id = o.id
+    JOIN product_skus v ON v.id = oi.variant_id
+    JOIN products p ON p.id = v.product_id
+    LEFT JOIN customers c ON c.id = o.customer_id
+    LEFT JOIN customer_profiles cp ON cp.customer_id = o.customer_id AND cp.store_id = p.store_id
+"#;
+
+const ORDER_LINES_COLUMNS: &str = r#"
+    SELECT o.id as order_id,
+           oi.id as line_id,
+           o.created_at,
+           o.status,
+           o.customer_id::text as customer_id,
+           COALESCE(cp.name, c.name, '') as customer_name,
+           p.title as product_title,
+           v.sku,
+           oi.quantity,
+           oi.price_amount as unit_price,
+           oi.price_currency as currency,
+           (oi.price_amount * oi.quantity)::bigint as line_total,
+           o.total_amount as order_total
+"#;
+
+fn apply_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, filters: &'a ExportFilters) {
+    let mut separated = builder.separated(" AND ");
+    separated.push("p.store_id = ");
+    separated.push_bind_unseparated(filters.store_id);
+    if let Some(from) = filters.from {
+        separated.push("o.created_at >= ");
+        separated.push_bind_unseparated(from);
+    }
+    if let Some(to) = filters.to {
+        separated.push("o.created_at < ");
+        separated.push_bind_unseparated(to);
+    }
+    if let Some(status) = &filters.status {
+        separated.push("o.status = ");
+        separated.push_bind_unseparated(status);
+    }
+}
+
+pub(crate) fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|v| v.with_timezone(&Utc))
+        .with_context(|| format!("invalid RFC3339 timestamp: {}", value))
+}
+
+fn parse_status(value: String) -> Result<String> {
+    if !value.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+        bail!("invalid status: {}", value);
+    }
+    Ok(value)
+}
+
+pub(crate) fn write_csv_row(out: &mut impl Write, fields: impl IntoIterator<Item = String>) -> Result<()> {
+    let line = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    out.write_all(line.as_bytes())?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use sqlx::{Postgres, QueryBuilder, postgres::PgPoolOptions, types::Uuid};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::Instant,
+};
+
+use crate::export::write_csv_row;
+
+// Matches the low_stock_only threshold of BackofficeService/GetInventoryReport.
+const LOW_STOCK_THRESHOLD: i32 = 5;
+
+const CSV_HEADER: &[&str] = &[
+    "variant_id",
+    "sku",
+    "product_title",
+    "location_id",
+    "location_name",
+    "stock",
+    "reserved",
+    "available",
+];
+
+#[derive(Parser, Debug)]
+pub struct InventoryReportArgs {
+    /// PostgreSQL connection string
+    #[arg(long, env = "DATABASE_URL")]
+    db_url: String,
+    /// Store to report
+    #[arg(long)]
+    store_id: String,
+    /// Restrict the report to a single location
+    #[arg(long)]
+    location_id: Option<String>,
+    /// Only include lines with available < 5
+    #[arg(long, default_value_t = false)]
+    low_stock_only: bool,
+    /// Output CSV file
+    #[arg(long)]
+    output: PathBuf,
+    /// Rows fetched per batch
+    #[arg(long, env = "EXPORT_BATCH_SIZE", default_value_t = 1000)]
+    batch_size: usize,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct InventoryLineRow {
+    variant_id: Uuid,
+    sku: String,
+    product_title: String,
+    location_id: Uuid,
+    location_name: String,
+    stock: i32,
+    reserved: i32,
+}
+
+pub async fn run_inventory_report(args: InventoryReportArgs) -> Result<()> {
+    let store_id = Uuid::parse_str(&args.store_id).context("--store-id must be a uuid")?;
+    let location_id = args
+        .location_id
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .map(Uuid::parse_str)
+        .transpose()
+        .context("--location-id must be a uuid")?;
+    let batch_size = args.batch_size.max(1) as i64;
+
+    let db = PgPoolOptions::new().max_connections(2).connect(&args.db_url).await?;
+    let file = File::create(&args.output).with_context(|| format!("failed to create {}", args.output.display()))?;
+    let mut out = BufWriter::new(file);
+    write_csv_row(&mut out, CSV_HEADER.iter().map(|v| v.to_string()))?;
+
+    let started_at = Instant::now();
+    tracing::info!(
+        store_id = %store_id,
+        location_id = ?location_id,
+        low_stock_only = args.low_stock_only,
+        batch_size,
+        "inventory report started"
+    );
+
+    let mut cursor: Option<(Uuid, Uuid)> = None;
+    let mut total = 0usize;
+    loop {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            r#"
+            SELECT s.sku_id as variant_id,
+                   v.sku,
+                   p.title as product_title,
+                   s.location_id,
+                   l.name as location_name,
+                   s.on_hand as stock,
+                   s.reserved
+            FROM inventory_stocks s
+            JOIN product_skus v ON v.id = s.sku_id
+            JOIN products p ON p.id = v.product_id
+            JOIN store_locations l ON l.id = s.location_id
+            WHERE s.store_id = "#,
+        );
+        builder.push_bind(store_id);
+        if let Some(location_id) = location_id {
+            builder.push(" AND s.location_id = ");
+            builder.push_bind(location_id);
+        }
+        if args.low_stock_only {
+            builder.push(" AND s.on_hand - s.reserved < ");
+            builder.push_bind(LOW_STOCK_THRESHOLD);
+        }
+        if let Some((location_id, sku_id)) = cursor {
+            builder.push(" AND (s.location_id, s.sku_id) > (");
+            builder.push_bind(location_id);
+            builder.push(", ");
+            builder.push_bind(sku_id);
+            builder.push(")");
+        }
+        builder.push(" ORDER BY s.location_id ASC, s.sku_id ASC LIMIT ");
+        builder.push_bind(batch_size);
+
+        let rows = builder.build_query_as::<InventoryLineRow>().fetch_all(&db).await?;
+        if rows.is_empty() {
+            break;
+        }
+
+        let rows_len = rows.len();
+        for row in &rows {
+            write_csv_row(
+                &mut out,
+                [
+                    row.variant_id.to_string(),
+                    row.sku.clone(),
+                    row.product_title.clone(),
+                    row.location_id.to_string(),
+                    row.location_name.clone(),
+                    row.stock.to_string(),
+                    row.reserved.to_string(),
+                    (row.stock - row.reserved).to_string(),
+                ],
+            )?;
+        }
+        if let Some(last) = rows.last() {
+            cursor = Some((last.location_id, last.variant_id));
+        }
+        total += rows_len;
+        tracing::info!(
+            processed = total,
+            batch_size = rows_len,
+            "inventory report batch completed"
+        );
+        if (rows_len as i64) < batch_size {
+            break;
+        }
+    }
+    out.flush()?;
+
+    tracing::info!(
+        total,
+        elapsed_ms = started_at.elapsed().as_millis(),
+        "inventory report completed"
+    );
+    Ok(())
+}
@@ -1,9 +1,17 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Datelike, Utc};
 use clap::{Parser, Subcommand};
 use meilisearch_sdk::client::Client;
 use serde::{Deserialize, Serialize};
 use sqlx::{Postgres, QueryBuilder, postgres::PgPoolOptions};
-use std::time::{Duration, Instant};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+mod export;
+mod inventory;
+mod outbox;
 
 #[derive(Parser, Debug)]
 #[command(name = "rs-ecommerce", version, about = "rs-ecommerce operational CLI")]
@@ -19,6 +27,21 @@ enum Commands {
         #[command(subcommand)]
         command: SearchCommands,
     },
+    /// Export related commands
+    Export {
+        #[command(subcommand)]
+        command: ExportCommands,
+    },
+    /// Inventory related commands
+    Inventory {
+        #[command(subcommand)]
+        command: InventoryCommands,
+    },
+    /// Outbox related commands
+    Outbox {
+        #[command(subcommand)]
+        command: OutboxCommands,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -27,6 +50,27 @@ enum SearchCommands {
     Reindex(ReindexArgs),
 }
 
+#[derive(Subcommand, Debug)]
+enum ExportCommands {
+    /// Export order lines as CSV for accounting systems
+    Orders(export::ExportOrdersArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum InventoryCommands {
+    /// Write an inventory snapshot per location as CSV
+    Report(inventory::InventoryReportArgs),
+}
+
+#[derive(Subcommand, Debug)]
+enum OutboxCommands {
+    /// Inspect and replay permanently failed outbox events
+    Dlq {
+        #[command(subcommand)]
+        command: outbox::DlqCommands,
+    },
+}
+
 #[derive(Parser, Debug)]
 struct ReindexArgs {
     /// PostgreSQL connection string
@@ -65,6 +109,12 @@ struct ReindexArgs {
     /// Filter by product_id
     #[arg(long, env = "REINDEX_PRODUCT_ID")]
     product_id: Option<String>,
+    /// Only products created at or after this RFC3339 timestamp
+    #[arg(long, env = "REINDEX_CREATED_AFTER")]
+    created_after: Option<String>,
+    /// Only products updated at or after this RFC3339 timestamp
+    #[arg(long, env = "REINDEX_UPDATED_AFTER")]
+    updated_after: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -74,6 +124,8 @@ struct ReindexFilters {
     vendor_id: Option<String>,
     status: Option<String>,
     product_id: Option<String>,
+    created_after: Option<DateTime<Utc>>,
+    updated_after: Option<DateTime<Utc>>,
 }
 
 impl ReindexFilters {
@@ -83,6 +135,8 @@ impl ReindexFilters {
             && self.vendor_id.is_none()
             && self.status.is_none()
             && self.product_id.is_none()
+            && self.created_after.is_none()
+            && self.updated_after.is_none()
     }
 }
 
@@ -103,6 +157,12 @@ struct SearchProduct {
     category_ids: Vec<String>,
     #[serde(default)]
     sku_codes: Vec<String>,
+    #[serde(default)]
+    translations: BTreeMap<String, String>,
+    #[serde(default)]
+    price_amount: i64,
+    #[serde(default)]
+    created_at_unix: i64,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -117,6 +177,10 @@ struct SearchRow {
     primary_category_id: String,
     category_ids: Vec<String>,
     sku_codes: Vec<String>,
+    translations: sqlx::types::Json<BTreeMap<String, String>>,
+    price_amount: i64,
+    created_at_unix: i64,
+    created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -126,7 +190,7 @@ struct CountRow {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    rs_common::cli::init("rs-ecommerce-cli");
+    rs_common::cli::init("rs-ecommerce-cli")?;
 
     let cli = Cli::parse();
 
@@ -134,6 +198,22 @@ async fn main() -> Result<()> {
         Commands::Search {
             command: SearchCommands::Reindex(args),
         } => run_reindex(args).await,
+        Commands::Export {
+            command: ExportCommands::Orders(args),
+        } => export::run_export_orders(args).await,
+        Commands::Inventory {
+            command: InventoryCommands::Report(args),
+        } => inventory::run_inventory_report(args).await,
+        Commands::Outbox {
+            command: OutboxCommands::Dlq {
+                command: outbox::DlqCommands::List(args),
+            },
+        } => outbox::run_dlq_list(args).await,
+        Commands::Outbox {
+            command: OutboxCommands::Dlq {
+                command: outbox::DlqCommands::Replay(args),
+            },
+        } => outbox::run_dlq_replay(args).await,
     }
 }
 
@@ -144,6 +224,8 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
         vendor_id: args.vendor_id.filter(|v| !v.is_empty()),
         status: args.status.filter(|v| !v.is_empty()),
         product_id: args.product_id.filter(|v| !v.is_empty()),
+        created_after: parse_reindex_timestamp(args.created_after.as_deref(), "--created-after")?,
+        updated_after: parse_reindex_timestamp(args.updated_after.as_deref(), "--updated-after")?,
     };
 
     let batch_size = args.batch_size as i64;
@@ -159,9 +241,10 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
     );
 
     if args.count_only {
-        let mut builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) as count FROM products p");
+        let mut builder =
+            QueryBuilder::<Postgres>::new("SELECT COUNT(*) as count FROM products p WHERE p.deleted_at IS NULL");
         if !filters.is_empty() {
-            builder.push(" WHERE ");
+            builder.push(" AND ");
             apply_filters(&mut builder, &filters);
         }
         let row = builder.build_query_as::<CountRow>().fetch_one(&db).await?;
@@ -185,8 +268,12 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
             "category_ids",
         ])
         .await?;
+    index
+        .set_sortable_attributes(&["title", "price_amount", "created_at_unix"])
+        .await?;
 
-    let mut offset = 0i64;
+    // Keyset cursor: (created_at, id) of the last product in the previous batch.
+    let mut cursor: Option<(DateTime<Utc>, String)> = None;
     let mut total = 0usize;
     let started_at = Instant::now();
     loop {
@@ -214,18 +301,32 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
                        SELECT array_agg(sku ORDER BY created_at)
                        FROM product_skus
                        WHERE product_id = p.id
-                   ), ARRAY[]::text[]) as sku_codes
+                   ), ARRAY[]::text[]) as sku_codes,
+                   COALESCE((
+                       SELECT jsonb_object_agg(kv.key, kv.value)
+                       FROM product_translations t
+                       CROSS JOIN LATERAL (VALUES
+                           ('title_' || t.locale, t.title),
+                           ('description_' || t.locale, t.description)
+                       ) AS kv(key, value)
+                       WHERE t.product_id = p.id
+                   ), '{}'::jsonb) as translations,
+                   COALESCE((
+                       SELECT MIN(price_amount)
+                       FROM product_skus
+                       WHERE product_id = p.id AND status = 'active'
+                   ), 0)::bigint as price_amount,
+                   EXTRACT(EPOCH FROM p.created_at)::bigint as created_at_unix,
+                   p.created_at
             FROM products p
+            WHERE p.deleted_at IS NULL
             "#,
         );
         if !filters.is_empty() {
-            builder.push(" WHERE ");
+            builder.push(" AND ");
             apply_filters(&mut builder, &filters);
         }
-        builder.push(" ORDER BY p.created_at ASC LIMIT ");
-        builder.push_bind(batch_size);
-        builder.push(" OFFSET ");
-        builder.push_bind(offset);
+        push_page(&mut builder, cursor.as_ref(), batch_size);
 
         let rows = builder.build_query_as::<SearchRow>().fetch_all(&db).await?;
 
@@ -234,6 +335,7 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
         }
 
         let rows_len = rows.len();
+        cursor = rows.last().map(|row| (row.created_at, row.id.clone()));
         let mut docs = Vec::with_capacity(rows_len);
         for row in rows {
             docs.push(SearchProduct {
@@ -247,6 +349,9 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
                 primary_category_id: row.primary_category_id,
                 category_ids: row.category_ids,
                 sku_codes: row.sku_codes,
+                translations: row.translations.0,
+                price_amount: row.price_amount,
+                created_at_unix: row.created_at_unix,
             });
         }
 
@@ -254,11 +359,9 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
             index.add_or_replace(&docs, Some("id")).await?;
         }
         total += rows_len;
-        offset += batch_size;
         tracing::info!(
             processed = total,
             batch_size = rows_len,
-            offset,
             dry_run = args.dry_run,
             "search reindex batch completed"
         );
@@ -274,26 +377,121 @@ async fn run_reindex(args: ReindexArgs) -> Result<()> {
     Ok(())
 }
 
+// Keyset pagination: resumes after the (created_at, id) of the previous batch's last row,
+// so each batch is an index range scan instead of an ever-growing OFFSET.
+fn push_page(builder: &mut QueryBuilder<'_, Postgres>, cursor: Option<&(DateTime<Utc>, String)>, batch_size: i64) {
+    if let Some((created_at, id)) = cursor {
+        builder.push(" AND (p.created_at, p.id) > (");
+        builder.push_bind(*created_at);
+        builder.push(", ");
+        builder.push_bind(id.clone());
+        builder.push("::uuid)");
+    }
+    builder.push(" ORDER BY p.created_at ASC, p.id ASC LIMIT ");
+    builder.push_bind(batch_size);
+}
+
 fn apply_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, filters: &'a ReindexFilters) {
     let mut separated = builder.separated(" AND ");
     if let Some(tenant_id) = &filters.tenant_id {
         separated.push("p.tenant_id::text = ");
-        separated.push_bind(tenant_id);
+        separated.push_bind_unseparated(tenant_id);
     }
     if let Some(store_id) = &filters.store_id {
         separated.push("p.store_id::text = ");
-        separated.push_bind(store_id);
+        separated.push_bind_unseparated(store_id);
     }
     if let Some(vendor_id) = &filters.vendor_id {
         separated.push("p.vendor_id::text = ");
-        separated.push_bind(vendor_id);
+        separated.push_bind_unseparated(vendor_id);
     }
     if let Some(status) = &filters.status {
         separated.push("p.status = ");
-        separated.push_bind(status);
+        separated.push_bind_unseparated(status);
     }
     if let Some(product_id) = &filters.product_id {
         separated.push("p.id::text = ");
-        separated.push_bind(product_id);
+        separated.push_bind_unseparated(product_id);
+    }
+    if let Some(created_after) = filters.created_after {
+        separated.push("p.created_at >= ");
+        separated.push_bind_unseparated(created_after);
+    }
+    if let Some(updated_after) = filters.updated_after {
+        separated.push("p.updated_at >= ");
+        separated.push_bind_unseparated(updated_after);
+    }
+}
+
+fn parse_reindex_timestamp(value: Option<&str>, flag: &str) -> Result<Option<DateTime<Utc>>> {
+    let Some(value) = value.filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let parsed = export::parse_timestamp(value)?;
+    if parsed.year() < 2000 {
+        bail!("{flag} must not be before 2000-01-01");
+    }
+    if parsed > Utc::now() {
+        bail!("{flag} must not be in the future");
+    }
+    Ok(Some(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter_sql(filters: &ReindexFilters) -> String {
+        let mut builder = QueryBuilder::<Postgres>::new("");
+        apply_filters(&mut builder, filters);
+        builder.sql().to_string()
+    }
+
+    #[test]
+    fn date_filters_are_bound_as_lower_bounds() {
+        let filters = ReindexFilters {
+            store_id: Some("store".to_string()),
+            created_after: parse_reindex_timestamp(Some("2026-01-01T00:00:00+09:00"), "--created-after").unwrap(),
+            updated_after: parse_reindex_timestamp(Some("2026-02-01T00:00:00Z"), "--updated-after").unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(filters.created_after.unwrap().to_rfc3339(), "2025-12-31T15:00:00+00:00");
+        assert_eq!(
+            filter_sql(&filters),
+            "p.store_id::text = $1 AND p.created_at >= $2 AND p.updated_at >= $3"
+        );
+    }
+
+    #[test]
+    fn reindex_timestamps_are_validated() {
+        assert_eq!(parse_reindex_timestamp(None, "--created-after").unwrap(), None);
+        assert_eq!(parse_reindex_timestamp(Some(""), "--created-after").unwrap(), None);
+        assert!(parse_reindex_timestamp(Some("2026-01-01"), "--created-after").is_err());
+
+        let err = parse_reindex_timestamp(Some("1999-12-31T23:59:59Z"), "--updated-after").unwrap_err();
+        assert_eq!(err.to_string(), "--updated-after must not be before 2000-01-01");
+        let tomorrow = (Utc::now() + chrono::Duration::days(1)).to_rfc3339();
+        let err = parse_reindex_timestamp(Some(&tomorrow), "--created-after").unwrap_err();
+        assert_eq!(err.to_string(), "--created-after must not be in the future");
+    }
+
+    #[test]
+    fn pages_resume_after_the_previous_batch() {
+        let mut first = QueryBuilder::<Postgres>::new("WHERE p.deleted_at IS NULL");
+        push_page(&mut first, None, 500);
+        assert_eq!(
+            first.sql(),
+            "WHERE p.deleted_at IS NULL ORDER BY p.created_at ASC, p.id ASC LIMIT $1"
+        );
+
+        let cursor = (Utc::now(), "00000000-0000-0000-0000-000000000001".to_string());
+        let mut next = QueryBuilder::<Postgres>::new("WHERE p.deleted_at IS NULL");
+        push_page(&mut next, Some(&cursor), 500);
+        assert_eq!(
+            next.sql(),
+            "WHERE p.deleted_at IS NULL AND (p.created_at, p.id) > ($1, $2::uuid) \
+             ORDER BY p.created_at ASC, p.id ASC LIMIT $3"
+        );
+        assert!(!next.sql().contains("OFFSET"));
     }
 }
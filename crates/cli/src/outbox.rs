@@ -0,0 +1,129 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::{Parser, Subcommand};
+use sqlx::{Postgres, QueryBuilder, postgres::PgPoolOptions, types::Uuid};
+
+#[derive(Subcommand, Debug)]
+pub enum DlqCommands {
+    /// List events in the outbox dead letter queue
+    List(DlqListArgs),
+    /// Move a dead-lettered event back to outbox_events as pending
+    Replay(DlqReplayArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct DlqListArgs {
+    /// PostgreSQL connection string
+    #[arg(long, env = "DATABASE_URL")]
+    db_url: String,
+    /// Filter by tenant_id
+    #[arg(long)]
+    tenant_id: Option<String>,
+    /// Filter by event_type
+    #[arg(long)]
+    event_type: Option<String>,
+    /// Maximum number of events to print
+    #[arg(long, default_value_t = 100)]
+    limit: i64,
+}
+
+#[derive(Parser, Debug)]
+pub struct DlqReplayArgs {
+    /// PostgreSQL connection string
+    #[arg(long, env = "DATABASE_URL")]
+    db_url: String,
+    /// Dead-lettered event id
+    event_id: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct DeadLetterRow {
+    id: Uuid,
+    tenant_id: Uuid,
+    event_type: String,
+    aggregate_type: String,
+    aggregate_id: String,
+    retry_count: i32,
+    failed_at: DateTime<Utc>,
+    failure_reason: Option<String>,
+}
+
+pub async fn run_dlq_list(args: DlqListArgs) -> Result<()> {
+    let tenant_id = args
+        .tenant_id
+        .as_deref()
+        .filter(|v| !v.is_empty())
+        .map(Uuid::parse_str)
+        .transpose()
+        .context("--tenant-id must be a uuid")?;
+    let event_type = args.event_type.filter(|v| !v.is_empty());
+
+    let db = PgPoolOptions::new().max_connections(1).connect(&args.db_url).await?;
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT id, tenant_id, event_type, aggregate_type, aggregate_id, retry_count, failed_at, failure_reason
+        FROM dead_letter_outbox_events
+        WHERE 1 = 1"#,
+    );
+    if let Some(tenant_id) = tenant_id {
+        builder.push(" AND tenant_id = ");
+        builder.push_bind(tenant_id);
+    }
+    if let Some(event_type) = event_type {
+        builder.push(" AND event_type = ");
+        builder.push_bind(event_type);
+    }
+    builder.push(" ORDER BY failed_at DESC, id DESC LIMIT ");
+    builder.push_bind(args.limit.max(1));
+
+    let rows = builder.build_query_as::<DeadLetterRow>().fetch_all(&db).await?;
+    println!("id\ttenant_id\tevent_type\taggregate\tretry_count\tfailed_at\tfailure_reason");
+    for row in &rows {
+        println!(
+            "{}\t{}\t{}\t{}:{}\t{}\t{}\t{}",
+            row.id,
+            row.tenant_id,
+            row.event_type,
+            row.aggregate_type,
+            row.aggregate_id,
+            row.retry_count,
+            row.failed_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            row.failure_reason.as_deref().unwrap_or(""),
+        );
+    }
+    tracing::info!(count = rows.len(), "dead letter events listed");
+    Ok(())
+}
+
+pub async fn run_dlq_replay(args: DlqReplayArgs) -> Result<()> {
+    let event_id = Uuid::parse_str(&args.event_id).context("event id must be a uuid")?;
+
+    let db = PgPoolOptions::new().max_connections(1).connect(&args.db_url).await?;
+    let mut tx = db.begin().await?;
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO outbox_events
+            (id, tenant_id, store_id, aggregate_type, aggregate_id, event_type, payload_json, status,
+             idempotency_key, created_at, retry_count)
+        SELECT id, tenant_id, store_id, aggregate_type, aggregate_id, event_type, payload_json, 'pending',
+               idempotency_key, created_at, 0
+        FROM dead_letter_outbox_events
+        WHERE id = $1
+        "#,
+    )
+    .bind(event_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        bail!("event {event_id} is not in the dead letter queue");
+    }
+    sqlx::query("DELETE FROM dead_letter_outbox_events WHERE id = $1")
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    tracing::info!(event_id = %event_id, "dead letter event replayed");
+    Ok(())
+}
@@ -1,3 +1,3 @@
-pub fn init(service_name: &str) {
-    crate::telemetry::init_tracing(service_name);
+pub fn init(service_name: &str) -> Result<(), crate::env::EnvError> {
+    crate::telemetry::init_tracing(service_name)
 }
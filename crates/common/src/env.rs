@@ -1,18 +1,121 @@
-pub fn env_usize(key: &str, default: usize) -> usize {
-    std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+use std::{fmt, str::FromStr};
+
+/// A configuration variable that is missing or cannot be parsed into the expected type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EnvError {
+    Missing { name: String },
+    Invalid { name: String, value: String },
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::Missing { name } => write!(f, "{name} is required"),
+            EnvError::Invalid { name, value } => write!(f, "{name} has an invalid value: {value:?}"),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+pub fn env_usize(key: &str, default: usize) -> Result<usize, EnvError> {
+    env_parse(key, default)
+}
+
+pub fn env_i64(key: &str, default: i64) -> Result<i64, EnvError> {
+    env_parse(key, default)
+}
+
+pub fn env_u64(key: &str, default: u64) -> Result<u64, EnvError> {
+    env_parse(key, default)
+}
+
+pub fn env_bool(key: &str, default: bool) -> Result<bool, EnvError> {
+    env_parse(key, default)
 }
 
-pub fn env_i64(key: &str, default: i64) -> i64 {
-    std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+pub fn require_usize(key: &str) -> Result<usize, EnvError> {
+    let value = raw(key).ok_or_else(|| EnvError::Missing { name: key.to_string() })?;
+    parse(key, &value)
 }
 
-pub fn env_u64(key: &str, default: u64) -> u64 {
-    std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+/// Fails on the first variable that is unset or empty, so a worker stops before its main loop
+/// instead of on first use.
+pub fn validate_required_vars(keys: &[&str]) -> Result<(), EnvError> {
+    match keys.iter().find(|key| raw(key).is_none()) {
+        Some(key) => Err(EnvError::Missing { name: key.to_string() }),
+        None => Ok(()),
+    }
 }
 
-pub fn env_bool(key: &str, default: bool) -> bool {
-    std::env::var(key)
-        .ok()
-        .and_then(|v| v.parse::<bool>().ok())
-        .unwrap_or(default)
+// Unset and empty values fall back to the default; anything else must parse.
+fn env_parse<T: FromStr>(key: &str, default: T) -> Result<T, EnvError> {
+    match raw(key) {
+        Some(value) => parse(key, &value),
+        None => Ok(default),
+    }
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, EnvError> {
+    value.trim().parse().map_err(|_| EnvError::Invalid {
+        name: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn raw(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test owns its variable names, so tests running in parallel never touch the same key.
+    fn set(key: &str, value: &str) {
+        // SAFETY: no other thread reads or writes these test-only variables.
+        unsafe { std::env::set_var(key, value) };
+    }
+
+    #[test]
+    fn unset_and_empty_values_use_the_default() {
+        assert_eq!(env_usize("RS_COMMON_TEST_UNSET", 7), Ok(7));
+        set("RS_COMMON_TEST_EMPTY", "  ");
+        assert_eq!(env_i64("RS_COMMON_TEST_EMPTY", -3), Ok(-3));
+    }
+
+    #[test]
+    fn values_are_trimmed_and_parsed() {
+        set("RS_COMMON_TEST_U64", " 42 ");
+        set("RS_COMMON_TEST_BOOL", "true");
+        assert_eq!(env_u64("RS_COMMON_TEST_U64", 0), Ok(42));
+        assert_eq!(env_bool("RS_COMMON_TEST_BOOL", false), Ok(true));
+    }
+
+    #[test]
+    fn unparsable_values_are_rejected() {
+        set("RS_COMMON_TEST_INVALID", "ten");
+        assert_eq!(
+            env_usize("RS_COMMON_TEST_INVALID", 10),
+            Err(EnvError::Invalid {
+                name: "RS_COMMON_TEST_INVALID".to_string(),
+                value: "ten".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn required_values_must_be_set() {
+        set("RS_COMMON_TEST_REQUIRED", "3");
+        assert_eq!(require_usize("RS_COMMON_TEST_REQUIRED"), Ok(3));
+        let missing = EnvError::Missing {
+            name: "RS_COMMON_TEST_MISSING".to_string(),
+        };
+        assert_eq!(require_usize("RS_COMMON_TEST_MISSING"), Err(missing.clone()));
+        assert_eq!(
+            validate_required_vars(&["RS_COMMON_TEST_REQUIRED", "RS_COMMON_TEST_MISSING"]),
+            Err(missing)
+        );
+        assert_eq!(validate_required_vars(&["RS_COMMON_TEST_REQUIRED"]), Ok(()));
+    }
 }
@@ -0,0 +1,64 @@
+/// How a customer identity value is canonicalized before it is stored or matched.
+/// Shared by the API and the customer sync worker so both produce the same key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NormalizationRule {
+    /// Trimmed and lowercased.
+    Email,
+    /// ASCII digits only.
+    Phone,
+    /// Trimmed and lowercased.
+    LineId,
+    /// Trimmed, lowercased, leading `@` removed.
+    Twitter,
+    /// Trimmed only.
+    Raw,
+}
+
+impl NormalizationRule {
+    pub fn for_type(identity_type: &str) -> Self {
+        match identity_type {
+            "email" => NormalizationRule::Email,
+            "phone" => NormalizationRule::Phone,
+            "line_id" => NormalizationRule::LineId,
+            "twitter" => NormalizationRule::Twitter,
+            _ => NormalizationRule::Raw,
+        }
+    }
+
+    pub fn apply(self, value: &str) -> String {
+        let trimmed = value.trim();
+        match self {
+            NormalizationRule::Email | NormalizationRule::LineId => trimmed.to_lowercase(),
+            NormalizationRule::Phone => trimmed.chars().filter(|c| c.is_ascii_digit()).collect(),
+            NormalizationRule::Twitter => trimmed.trim_start_matches('@').trim_start().to_lowercase(),
+            NormalizationRule::Raw => trimmed.to_string(),
+        }
+    }
+}
+
+pub fn normalize_identity(identity_type: &str, value: &str) -> String {
+    NormalizationRule::for_type(identity_type).apply(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_type_uses_its_rule() {
+        assert_eq!(
+            normalize_identity("email", "  Hanako@Example.COM "),
+            "hanako@example.com"
+        );
+        assert_eq!(normalize_identity("phone", "+81 (90) 1234-5678"), "819012345678");
+        assert_eq!(normalize_identity("line_id", " Hanako_Line "), "hanako_line");
+        assert_eq!(normalize_identity("twitter", " @Hanako_JP"), "hanako_jp");
+    }
+
+    #[test]
+    fn unknown_types_are_only_trimmed() {
+        assert_eq!(NormalizationRule::for_type("loyalty_card"), NormalizationRule::Raw);
+        assert_eq!(normalize_identity("loyalty_card", "  AbC-123 "), "AbC-123");
+        assert_eq!(normalize_identity("Email", " Mixed@Case "), "Mixed@Case");
+    }
+}
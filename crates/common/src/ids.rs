@@ -0,0 +1,8 @@
+use anyhow::Context;
+use uuid::Uuid;
+
+/// Parses an id taken from an outbox payload. The error names the field and the offending
+/// value so the event's `last_error` says what was malformed.
+pub fn parse_uuid(value: &str, field: &str) -> anyhow::Result<Uuid> {
+    Uuid::parse_str(value).with_context(|| format!("{field} is not a valid uuid: {value:?}"))
+}
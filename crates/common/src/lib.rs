@@ -1,3 +1,6 @@
 pub mod cli;
 pub mod env;
+pub mod identity;
+pub mod ids;
+pub mod shutdown;
 pub mod telemetry;
@@ -1,17 +1,44 @@
+use std::fmt;
+
 use opentelemetry::KeyValue;
 use opentelemetry::trace::TracerProvider as _;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{Resource, trace as sdktrace};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::{JsonFields, Writer};
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::env::EnvError;
 
-pub fn init_tracing(service_name: &str) {
-    let registry = tracing_subscriber::registry().with(tracing_subscriber::EnvFilter::from_default_env());
+const DEFAULT_OTEL_ENDPOINT: &str = "http://localhost:4317";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LogFormat {
+    Json,
+    Pretty,
+}
 
-    let use_json = std::env::var("LOG_FORMAT").ok().as_deref() == Some("json");
+/// Installs the global subscriber.
+///
+/// - `RS_LOG_LEVEL` replaces `RUST_LOG` when set; both take `EnvFilter` directives.
+/// - `RS_LOG_FORMAT` (`json` or `pretty`, falling back to `LOG_FORMAT`) picks the output format.
+/// - `RS_OTEL_ENDPOINT` (or `ENABLE_OTEL=true` with `OTEL_EXPORTER_OTLP_ENDPOINT`) enables the
+///   OTLP exporter; JSON is then the default format.
+pub fn init_tracing(service_name: &str) -> Result<(), EnvError> {
+    let filter = env_filter()?;
+    let otel_endpoint = otel_endpoint();
+    let format = match log_format()? {
+        Some(format) => format,
+        None if otel_endpoint.is_some() => LogFormat::Json,
+        None => LogFormat::Pretty,
+    };
 
-    if std::env::var("ENABLE_OTEL").ok().as_deref() == Some("true") {
-        let endpoint =
-            std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or_else(|_| "http://localhost:4317".to_string());
+    let otel_layer = otel_endpoint.map(|endpoint| {
         let provider = opentelemetry_otlp::new_pipeline()
             .tracing()
             .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
@@ -25,15 +52,203 @@ pub fn init_tracing(service_name: &str) {
             .expect("otel tracer provider");
         let tracer = provider.tracer(service_name.to_string());
         opentelemetry::global::set_tracer_provider(provider);
-        let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
-        // tracing-opentelemetry 0.26 expects the JSON formatter.
-        registry
-            .with(tracing_subscriber::fmt::layer().json())
-            .with(otel_layer)
-            .init();
-    } else if use_json {
-        registry.with(tracing_subscriber::fmt::layer().json()).init();
-    } else {
-        registry.with(tracing_subscriber::fmt::layer()).init();
+        tracing_opentelemetry::layer().with_tracer(tracer)
+    });
+    let json_layer = (format == LogFormat::Json).then(|| {
+        tracing_subscriber::fmt::layer()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonLines {
+                service: service_name.to_string(),
+            })
+    });
+    let pretty_layer = (format == LogFormat::Pretty).then(tracing_subscriber::fmt::layer);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(json_layer)
+        .with(pretty_layer)
+        .with(otel_layer)
+        .init();
+    Ok(())
+}
+
+fn env_filter() -> Result<EnvFilter, EnvError> {
+    match non_empty_var("RS_LOG_LEVEL") {
+        Some(directives) => EnvFilter::try_new(&directives).map_err(|_| EnvError::Invalid {
+            name: "RS_LOG_LEVEL".to_string(),
+            value: directives,
+        }),
+        None => Ok(EnvFilter::from_default_env()),
+    }
+}
+
+fn log_format() -> Result<Option<LogFormat>, EnvError> {
+    let (name, value) = match non_empty_var("RS_LOG_FORMAT") {
+        Some(value) => ("RS_LOG_FORMAT", value),
+        None => match non_empty_var("LOG_FORMAT") {
+            Some(value) => ("LOG_FORMAT", value),
+            None => return Ok(None),
+        },
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "json" => Ok(Some(LogFormat::Json)),
+        "pretty" | "text" => Ok(Some(LogFormat::Pretty)),
+        _ => Err(EnvError::Invalid {
+            name: name.to_string(),
+            value,
+        }),
+    }
+}
+
+fn otel_endpoint() -> Option<String> {
+    if let Some(endpoint) = non_empty_var("RS_OTEL_ENDPOINT") {
+        return Some(endpoint);
+    }
+    (std::env::var("ENABLE_OTEL").ok().as_deref() == Some("true"))
+        .then(|| non_empty_var("OTEL_EXPORTER_OTLP_ENDPOINT").unwrap_or_else(|| DEFAULT_OTEL_ENDPOINT.to_string()))
+}
+
+fn non_empty_var(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// One JSON object per line: the fields of the enclosing spans (`request_id`, `trace_id`,
+/// `store_id`, ...), the event's own fields, then `ts`, `level`, `service`, `target` and `msg`,
+/// which always hold the logger's values.
+struct JsonLines {
+    service: String,
+}
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut ts = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut ts))?;
+
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+
+        // Spans are visited root first so the innermost span wins on duplicate names.
+        let mut line = Map::new();
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(fields) = extensions.get::<FormattedFields<N>>() else {
+                    continue;
+                };
+                if let Ok(Value::Object(fields)) = serde_json::from_str::<Value>(&fields.fields) {
+                    line.extend(fields);
+                }
+            }
+        }
+        let msg = visitor.fields.remove("message").unwrap_or(Value::String(String::new()));
+        line.extend(visitor.fields);
+
+        // The fixed keys go in last so a span or event field with the same name cannot replace them.
+        let metadata = event.metadata();
+        line.insert("ts".to_string(), Value::String(ts));
+        line.insert(
+            "level".to_string(),
+            Value::String(metadata.level().as_str().to_string()),
+        );
+        line.insert("service".to_string(), Value::String(self.service.clone()));
+        line.insert("target".to_string(), Value::String(metadata.target().to_string()));
+        line.insert("msg".to_string(), msg);
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+#[derive(Default)]
+struct JsonVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.fields
+            .insert(field.name().to_string(), Value::String(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields
+            .insert(field.name().to_string(), Value::String(value.to_string()));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn json_lines_carry_the_service_over_span_and_event_fields() {
+        let captured = Captured::default();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .fmt_fields(JsonFields::new())
+                .event_format(JsonLines {
+                    service: "rs-ecommerce".to_string(),
+                })
+                .with_writer(captured.clone()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "req-1", service = "spoofed");
+            let _entered = span.enter();
+            tracing::info!(level = "spoofed", msg = "spoofed", store_id = "store-1", "order placed");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["service"], "rs-ecommerce");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["msg"], "order placed");
+        assert_eq!(line["request_id"], "req-1");
+        assert_eq!(line["store_id"], "store-1");
     }
 }
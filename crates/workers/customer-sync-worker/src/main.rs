@@ -1,10 +1,10 @@
 use std::time::Duration;
 
 use anyhow::Result;
-use rs_common::{env, telemetry};
+use rs_common::{env, identity::normalize_identity, ids::parse_uuid, shutdown, telemetry};
 use serde::Deserialize;
 use sqlx::{PgPool, Row, postgres::PgPoolOptions};
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 #[derive(Debug)]
 struct OutboxEvent {
@@ -21,6 +21,8 @@ struct ProfilePayload {
     source_store_id: Option<String>,
     customer_id: String,
     profile: ProfileData,
+    #[serde(default)]
+    force: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,33 +50,86 @@ struct IdentityData {
     source: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct AddressPayload {
+    tenant_id: String,
+    source_store_id: Option<String>,
+    customer_id: String,
+    address_id: String,
+    address: AddressData,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressData {
+    #[serde(rename = "type")]
+    address_type: String,
+    name: String,
+    postal_code: String,
+    prefecture: String,
+    city: String,
+    line1: String,
+    line2: String,
+    phone: String,
+    country_code: String,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    telemetry::init_tracing("customer-sync-worker");
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required");
-    let pool = PgPoolOptions::new().max_connections(5).connect(&db_url).await?;
+    telemetry::init_tracing("customer-sync-worker")?;
+    env::validate_required_vars(&["DATABASE_URL"])?;
+    let db_url = std::env::var("DATABASE_URL")?;
+    let connect_timeout_ms = env::env_u64("DATABASE_CONNECT_TIMEOUT_MS", 5000)?;
+    let pool = PgPoolOptions::new()
+        .min_connections(env::env_u64("DATABASE_MIN_CONNECTIONS", 1)? as u32)
+        .max_connections(env::env_u64("DATABASE_MAX_CONNECTIONS", 5)? as u32)
+        .acquire_timeout(Duration::from_millis(connect_timeout_ms))
+        .connect(&db_url)
+        .await?;
+
+    let max_schema_wait_seconds = env::env_u64("MAX_SCHEMA_WAIT_SECONDS", 300)?;
+    let schema_wait_sleep_ms = env::env_u64("SCHEMA_WAIT_SLEEP_MS", 1000)?;
+    wait_for_schema(&pool, max_schema_wait_seconds, schema_wait_sleep_ms).await?;
 
-    wait_for_schema(&pool).await?;
+    let batch_size = env::env_usize("CUSTOMER_SYNC_BATCH_SIZE", 100)? as i64;
+    let sleep_ms = env::env_u64("CUSTOMER_SYNC_WORKER_SLEEP_MS", 1000)?;
+    let oneshot = env::env_bool("CUSTOMER_SYNC_WORKER_ONESHOT", false)?;
+    let max_retries = env::env_i64("CUSTOMER_SYNC_MAX_RETRIES", 5)? as i32;
+    let dlq_age_seconds = env::env_i64("OUTBOX_DLQ_AGE_SECONDS", 3600)?;
+    let retention_days = env::env_i64("OUTBOX_RETENTION_DAYS", 30)? as i32;
+    let cleanup_batch_size = env::env_usize("OUTBOX_CLEANUP_BATCH_SIZE", 1000)? as i64;
 
-    let batch_size = env::env_usize("CUSTOMER_SYNC_BATCH_SIZE", 100) as i64;
-    let sleep_ms = env::env_u64("CUSTOMER_SYNC_WORKER_SLEEP_MS", 1000);
-    let oneshot = env::env_bool("CUSTOMER_SYNC_WORKER_ONESHOT", false);
+    let shutdown = shutdown::cancel_on_signal();
 
     loop {
-        let processed = process_outbox_batch(&pool, batch_size).await?;
+        let processed = process_outbox_batch(&pool, batch_size, max_retries).await?;
         info!(processed, "customer sync batch processed");
-        if oneshot {
+        let dead_lettered = move_failed_to_dead_letter(&pool, batch_size, dlq_age_seconds).await?;
+        if dead_lettered > 0 {
+            warn!(dead_lettered, "failed outbox events moved to dead letter queue");
+        }
+        let purged = purge_published_events(&pool, cleanup_batch_size, retention_days).await?;
+        if purged > 0 {
+            info!(purged, "published outbox events purged");
+        }
+        if oneshot || shutdown.is_cancelled() {
             break;
         }
-        tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+        }
     }
 
+    info!("graceful shutdown complete");
     Ok(())
 }
 
-async fn wait_for_schema(pool: &PgPool) -> Result<()> {
-    let mut attempts = 0;
-    loop {
+/// Polls every `sleep_ms` until migrations have created the worker's tables, giving up after
+/// `max_wait_seconds` (`MAX_SCHEMA_WAIT_SECONDS` / `SCHEMA_WAIT_SLEEP_MS`).
+async fn wait_for_schema(pool: &PgPool, max_wait_seconds: u64, sleep_ms: u64) -> Result<()> {
+    let sleep_ms = sleep_ms.max(1);
+    let max_attempts = (max_wait_seconds * 1000).div_ceil(sleep_ms).max(1);
+    for attempt in 1..=max_attempts {
         let row = sqlx::query("SELECT to_regclass('public.outbox_events')::text as outbox_table")
             .fetch_one(pool)
             .await?;
@@ -82,15 +137,19 @@ async fn wait_for_schema(pool: &PgPool) -> Result<()> {
         if exists.is_some() {
             return Ok(());
         }
-        attempts += 1;
-        if attempts % 10 == 0 {
-            info!(attempts, "waiting for migrations to create outbox tables");
+        if attempt % 10 == 0 {
+            info!(attempts = attempt, "waiting for migrations to create outbox tables");
         }
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
     }
+    error!(
+        attempts = max_attempts,
+        max_wait_seconds, "timed out waiting for migrations to create outbox tables"
+    );
+    anyhow::bail!("outbox tables not found after waiting {max_wait_seconds}s; check that migrations have run")
 }
 
-async fn process_outbox_batch(pool: &PgPool, batch_size: i64) -> Result<usize> {
+async fn process_outbox_batch(pool: &PgPool, batch_size: i64, max_retries: i32) -> Result<usize> {
     let mut tx = pool.begin().await?;
     let rows = sqlx::query(
         r#"
@@ -98,7 +157,11 @@ async fn process_outbox_batch(pool: &PgPool, batch_size: i64) -> Result<usize> {
             SELECT id
             FROM outbox_events
             WHERE status = 'pending'
-              AND event_type IN ('customer.profile_upsert', 'customer.identity_upsert', 'customer.address_upsert')
+              AND (next_retry_at IS NULL OR next_retry_at <= now())
+              AND event_type IN (
+                  'customer.profile_upsert', 'customer.identity_upsert', 'customer.address_upsert',
+                  'product.created', 'product.updated'
+              )
             ORDER BY created_at ASC
             LIMIT $1
             FOR UPDATE SKIP LOCKED
@@ -134,10 +197,26 @@ async fn process_outbox_batch(pool: &PgPool, batch_size: i64) -> Result<usize> {
             }
             Err(err) => {
                 warn!(error = %err, event_id = %event.id, "customer sync event failed");
-                sqlx::query("UPDATE outbox_events SET status = 'failed' WHERE id = $1")
-                    .bind(event.id)
-                    .execute(pool)
-                    .await?;
+                // Back off 2^retry_count seconds and retry; only give up once max_retries is reached.
+                sqlx::query(
+                    r#"
+                    UPDATE outbox_events
+                    SET retry_count = retry_count + 1,
+                        status = CASE WHEN retry_count + 1 >= $2 THEN 'failed' ELSE 'pending' END,
+                        next_retry_at = CASE
+                            WHEN retry_count + 1 >= $2 THEN NULL
+                            ELSE now() + make_interval(secs => power(2, retry_count))
+                        END,
+                        failed_at = CASE WHEN retry_count + 1 >= $2 THEN now() ELSE NULL END,
+                        last_error = $3
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(event.id)
+                .bind(max_retries)
+                .bind(err.to_string())
+                .execute(pool)
+                .await?;
             }
         }
     }
@@ -145,6 +224,94 @@ async fn process_outbox_batch(pool: &PgPool, batch_size: i64) -> Result<usize> {
     Ok(processed)
 }
 
+async fn move_failed_to_dead_letter(pool: &PgPool, batch_size: i64, age_seconds: i64) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let ids: Vec<uuid::Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM outbox_events
+        WHERE status = 'failed'
+          AND COALESCE(failed_at, created_at) <= now() - make_interval(secs => $1)
+        ORDER BY created_at ASC
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(age_seconds as f64)
+    .bind(batch_size)
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO dead_letter_outbox_events
+            (id, tenant_id, store_id, aggregate_type, aggregate_id, event_type, payload_json, status,
+             idempotency_key, created_at, published_at, retry_count, next_retry_at, failed_at, failure_reason)
+        SELECT id, tenant_id, store_id, aggregate_type, aggregate_id, event_type, payload_json, status,
+               idempotency_key, created_at, published_at, retry_count, next_retry_at,
+               COALESCE(failed_at, now()), last_error
+        FROM outbox_events
+        WHERE id = ANY($1)
+        "#,
+    )
+    .bind(&ids)
+    .execute(&mut *tx)
+    .await?;
+    // processed_events references outbox_events; a replay re-applies the event to every store,
+    // which the non-destructive merge makes safe.
+    sqlx::query("DELETE FROM processed_events WHERE event_id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+    let moved = sqlx::query("DELETE FROM outbox_events WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    Ok(moved)
+}
+
+// One batch per loop iteration keeps the transaction (and its row locks) short.
+async fn purge_published_events(pool: &PgPool, batch_size: i64, retention_days: i32) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    let ids: Vec<uuid::Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM outbox_events
+        WHERE status = 'published'
+          AND published_at < now() - make_interval(days => $1)
+        ORDER BY published_at ASC
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(retention_days)
+    .bind(batch_size)
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    sqlx::query("DELETE FROM processed_events WHERE event_id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+    let purged = sqlx::query("DELETE FROM outbox_events WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    Ok(purged)
+}
+
 async fn handle_event(pool: &PgPool, event: &OutboxEvent) -> Result<()> {
     match event.event_type.as_str() {
         "customer.profile_upsert" => {
@@ -156,26 +323,73 @@ async fn handle_event(pool: &PgPool, event: &OutboxEvent) -> Result<()> {
             apply_identity_sync(pool, event.id, payload).await?;
         }
         "customer.address_upsert" => {
-            // Currently not fanned-out across stores.
+            let payload: AddressPayload = serde_json::from_value(event.payload.clone())?;
+            apply_address_sync(pool, event.id, payload).await?;
+        }
+        // No cross-store product sync yet; consume the events so they show up in the logs.
+        "product.created" | "product.updated" => {
+            info!(
+                event_id = %event.id,
+                event_type = %event.event_type,
+                tenant_id = %event.tenant_id,
+                store_id = ?event.store_id,
+                product_id = event.payload.get("product_id").and_then(|v| v.as_str()).unwrap_or_default(),
+                "product event received"
+            );
         }
         _ => {}
     }
     Ok(())
 }
 
+/// Default merge rule: only fields that are empty in the target store are filled.
+const PROFILE_UPSERT_MERGE_SQL: &str = r#"
+    INSERT INTO customer_profiles (id, customer_id, store_id, name, email, phone, status, notes)
+    VALUES (gen_random_uuid(), $1,$2,$3,$4,$5,$6,$7)
+    ON CONFLICT (customer_id, store_id)
+    DO UPDATE SET
+        name = CASE WHEN customer_profiles.name = '' THEN EXCLUDED.name ELSE customer_profiles.name END,
+        email = CASE WHEN customer_profiles.email IS NULL OR customer_profiles.email = '' THEN EXCLUDED.email ELSE customer_profiles.email END,
+        phone = CASE WHEN customer_profiles.phone IS NULL OR customer_profiles.phone = '' THEN EXCLUDED.phone ELSE customer_profiles.phone END,
+        status = CASE WHEN customer_profiles.status = '' THEN EXCLUDED.status ELSE customer_profiles.status END,
+        updated_at = now()
+"#;
+
+/// Forced sync (`force: true` in the payload): the source store's profile overwrites the target.
+const PROFILE_UPSERT_FORCE_SQL: &str = r#"
+    INSERT INTO customer_profiles (id, customer_id, store_id, name, email, phone, status, notes)
+    VALUES (gen_random_uuid(), $1,$2,$3,$4,$5,$6,$7)
+    ON CONFLICT (customer_id, store_id)
+    DO UPDATE SET
+        name = EXCLUDED.name,
+        email = EXCLUDED.email,
+        phone = EXCLUDED.phone,
+        status = EXCLUDED.status,
+        notes = EXCLUDED.notes,
+        updated_at = now()
+"#;
+
 async fn apply_profile_sync(pool: &PgPool, event_id: uuid::Uuid, payload: ProfilePayload) -> Result<()> {
-    let tenant_id = uuid::Uuid::parse_str(&payload.tenant_id)?;
-    let customer_id = uuid::Uuid::parse_str(&payload.customer_id)?;
-    let source_store_id = payload.source_store_id.and_then(|s| uuid::Uuid::parse_str(&s).ok());
+    let tenant_id = parse_uuid(&payload.tenant_id, "tenant_id")?;
+    let customer_id = parse_uuid(&payload.customer_id, "customer_id")?;
+    let source_store_id = payload
+        .source_store_id
+        .map(|s| parse_uuid(&s, "source_store_id"))
+        .transpose()?;
     let name = payload.profile.name.clone();
     let email = payload.profile.email.clone();
     let phone = payload.profile.phone.clone();
     let status = payload.profile.status.clone();
     let notes = payload.profile.notes.clone();
+    let upsert_sql = if payload.force {
+        PROFILE_UPSERT_FORCE_SQL
+    } else {
+        PROFILE_UPSERT_MERGE_SQL
+    };
 
     let target_rows = sqlx::query(
         r#"
-        SELECT store_id::text as store_id
+        SELECT store_id
         FROM store_sync_settings
         WHERE tenant_id = $1 AND customer_sync_enabled = true
         "#,
@@ -185,8 +399,7 @@ async fn apply_profile_sync(pool: &PgPool, event_id: uuid::Uuid, payload: Profil
     .await?;
 
     for row in target_rows {
-        let store_id_str: String = row.get("store_id");
-        let store_id = uuid::Uuid::parse_str(&store_id_str)?;
+        let store_id: uuid::Uuid = row.get("store_id");
         if Some(store_id) == source_store_id {
             continue;
         }
@@ -206,26 +419,95 @@ async fn apply_profile_sync(pool: &PgPool, event_id: uuid::Uuid, payload: Profil
         .execute(pool)
         .await?;
 
+        sqlx::query(upsert_sql)
+            .bind(customer_id)
+            .bind(store_id)
+            .bind(&name)
+            .bind(if email.is_empty() { None } else { Some(email.clone()) })
+            .bind(if phone.is_empty() { None } else { Some(phone.clone()) })
+            .bind(if status.is_empty() { "active" } else { status.as_str() })
+            .bind(if notes.is_empty() { None } else { Some(notes.clone()) })
+            .execute(pool)
+            .await?;
+
+        mark_processed(pool, tenant_id, event_id, store_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn apply_address_sync(pool: &PgPool, event_id: uuid::Uuid, payload: AddressPayload) -> Result<()> {
+    let tenant_id = parse_uuid(&payload.tenant_id, "tenant_id")?;
+    let customer_id = parse_uuid(&payload.customer_id, "customer_id")?;
+    let address_id = parse_uuid(&payload.address_id, "address_id")?;
+    let source_store_id = payload
+        .source_store_id
+        .map(|s| parse_uuid(&s, "source_store_id"))
+        .transpose()?;
+    let address = payload.address;
+
+    let target_rows = sqlx::query(
+        r#"
+        SELECT store_id
+        FROM store_sync_settings
+        WHERE tenant_id = $1 AND customer_sync_enabled = true
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+
+    for row in target_rows {
+        let store_id: uuid::Uuid = row.get("store_id");
+        if Some(store_id) == source_store_id {
+            continue;
+        }
+        if already_processed(pool, tenant_id, event_id, store_id).await? {
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO customers (id, tenant_id, status)
+            VALUES ($1,$2,'active')
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(customer_id)
+        .bind(tenant_id)
+        .execute(pool)
+        .await?;
+
+        // Same merge rule as profiles: only fill fields that are still empty locally.
         sqlx::query(
             r#"
-            INSERT INTO customer_profiles (id, customer_id, store_id, name, email, phone, status, notes)
-            VALUES (gen_random_uuid(), $1,$2,$3,$4,$5,$6,$7)
-            ON CONFLICT (customer_id, store_id)
+            INSERT INTO customer_addresses
+                (id, customer_id, type, name, postal_code, prefecture, city, line1, line2, phone, country_code)
+            VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+            ON CONFLICT (id)
             DO UPDATE SET
-                name = CASE WHEN customer_profiles.name = '' THEN EXCLUDED.name ELSE customer_profiles.name END,
-                email = CASE WHEN customer_profiles.email IS NULL OR customer_profiles.email = '' THEN EXCLUDED.email ELSE customer_profiles.email END,
-                phone = CASE WHEN customer_profiles.phone IS NULL OR customer_profiles.phone = '' THEN EXCLUDED.phone ELSE customer_profiles.phone END,
-                status = CASE WHEN customer_profiles.status = '' THEN EXCLUDED.status ELSE customer_profiles.status END,
+                name = CASE WHEN customer_addresses.name = '' THEN EXCLUDED.name ELSE customer_addresses.name END,
+                postal_code = CASE WHEN customer_addresses.postal_code = '' THEN EXCLUDED.postal_code ELSE customer_addresses.postal_code END,
+                prefecture = CASE WHEN customer_addresses.prefecture = '' THEN EXCLUDED.prefecture ELSE customer_addresses.prefecture END,
+                city = CASE WHEN customer_addresses.city = '' THEN EXCLUDED.city ELSE customer_addresses.city END,
+                line1 = CASE WHEN customer_addresses.line1 = '' THEN EXCLUDED.line1 ELSE customer_addresses.line1 END,
+                line2 = CASE WHEN customer_addresses.line2 IS NULL OR customer_addresses.line2 = '' THEN EXCLUDED.line2 ELSE customer_addresses.line2 END,
+                phone = CASE WHEN customer_addresses.phone IS NULL OR customer_addresses.phone = '' THEN EXCLUDED.phone ELSE customer_addresses.phone END,
                 updated_at = now()
+            WHERE customer_addresses.customer_id = EXCLUDED.customer_id
             "#,
         )
+        .bind(address_id)
         .bind(customer_id)
-        .bind(store_id)
-        .bind(&name)
-        .bind(if email.is_empty() { None } else { Some(email.clone()) })
-        .bind(if phone.is_empty() { None } else { Some(phone.clone()) })
-        .bind(if status.is_empty() { "active" } else { status.as_str() })
-        .bind(if notes.is_empty() { None } else { Some(notes.clone()) })
+        .bind(&address.address_type)
+        .bind(&address.name)
+        .bind(&address.postal_code)
+        .bind(&address.prefecture)
+        .bind(&address.city)
+        .bind(&address.line1)
+        .bind(if address.line2.is_empty() { None } else { Some(address.line2.clone()) })
+        .bind(if address.phone.is_empty() { None } else { Some(address.phone.clone()) })
+        .bind(if address.country_code.is_empty() { "JP" } else { address.country_code.as_str() })
         .execute(pool)
         .await?;
 
@@ -236,10 +518,41 @@ async fn apply_profile_sync(pool: &PgPool, event_id: uuid::Uuid, payload: Profil
 }
 
 async fn apply_identity_sync(pool: &PgPool, event_id: uuid::Uuid, payload: IdentityPayload) -> Result<()> {
-    let tenant_id = uuid::Uuid::parse_str(&payload.tenant_id)?;
-    let customer_id = uuid::Uuid::parse_str(&payload.customer_id)?;
+    let tenant_id = parse_uuid(&payload.tenant_id, "tenant_id")?;
+    let customer_id = parse_uuid(&payload.customer_id, "customer_id")?;
+    let source_store_id = payload
+        .source_store_id
+        .map(|s| parse_uuid(&s, "source_store_id"))
+        .transpose()?;
     let identity_value = normalize_identity(&payload.identity.identity_type, &payload.identity.identity_value);
 
+    let target_rows = sqlx::query(
+        r#"
+        SELECT store_id
+        FROM store_sync_settings
+        WHERE tenant_id = $1 AND customer_sync_enabled = true
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut pending_store_ids = Vec::new();
+    for row in target_rows {
+        let store_id: uuid::Uuid = row.get("store_id");
+        if Some(store_id) == source_store_id {
+            continue;
+        }
+        if already_processed(pool, tenant_id, event_id, store_id).await? {
+            continue;
+        }
+        pending_store_ids.push(store_id);
+    }
+    if pending_store_ids.is_empty() {
+        return Ok(());
+    }
+
+    // customer_identities is tenant-level, so one upsert covers every pending store.
     sqlx::query(
         r#"
         INSERT INTO customer_identities
@@ -264,8 +577,8 @@ async fn apply_identity_sync(pool: &PgPool, event_id: uuid::Uuid, payload: Ident
     .execute(pool)
     .await?;
 
-    if let Some(source_store_id) = payload.source_store_id.and_then(|s| uuid::Uuid::parse_str(&s).ok()) {
-        mark_processed(pool, tenant_id, event_id, source_store_id).await?;
+    for store_id in pending_store_ids {
+        mark_processed(pool, tenant_id, event_id, store_id).await?;
     }
     Ok(())
 }
@@ -306,11 +619,508 @@ async fn mark_processed(
     Ok(())
 }
 
-fn normalize_identity(identity_type: &str, value: &str) -> String {
-    let trimmed = value.trim();
-    match identity_type {
-        "email" => trimmed.to_lowercase(),
-        "phone" => trimmed.chars().filter(|c| c.is_ascii_digit()).collect(),
-        _ => trimmed.to_string(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_tenant(pool: &PgPool) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO tenants (name, type, default_currency, status)
+            VALUES ('Test tenant', 'multi_brand', 'JPY', 'active')
+            RETURNING id
+            "#,
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// A store of `tenant_id` with customer sync enabled.
+    async fn create_synced_store(pool: &PgPool, tenant_id: uuid::Uuid) -> uuid::Uuid {
+        let store_id: uuid::Uuid =
+            sqlx::query_scalar("INSERT INTO stores (tenant_id, name) VALUES ($1, 'Test store') RETURNING id")
+                .bind(tenant_id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        sqlx::query("INSERT INTO store_sync_settings (store_id, tenant_id) VALUES ($1, $2)")
+            .bind(store_id)
+            .bind(tenant_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        store_id
+    }
+
+    async fn create_customer(pool: &PgPool, tenant_id: uuid::Uuid) -> uuid::Uuid {
+        sqlx::query_scalar("INSERT INTO customers (tenant_id) VALUES ($1) RETURNING id")
+            .bind(tenant_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn receipt_stores(pool: &PgPool, event_id: uuid::Uuid) -> Vec<uuid::Uuid> {
+        sqlx::query_scalar("SELECT store_id FROM processed_events WHERE event_id = $1 ORDER BY store_id")
+            .bind(event_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn insert_event(
+        pool: &PgPool,
+        tenant_id: uuid::Uuid,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload_json, idempotency_key)
+            VALUES ($1, 'customer', 'customer', $2, $3, gen_random_uuid()::text)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(event_type)
+        .bind(payload)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn event_status(pool: &PgPool, event_id: uuid::Uuid) -> (String, i32, Option<String>) {
+        sqlx::query_as("SELECT status, retry_count, last_error FROM outbox_events WHERE id = $1")
+            .bind(event_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn make_due(pool: &PgPool, event_id: uuid::Uuid) {
+        sqlx::query("UPDATE outbox_events SET next_retry_at = now() WHERE id = $1")
+            .bind(event_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn failing_event_backs_off_then_fails_at_max_retries(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let event_id = insert_event(
+            &pool,
+            tenant_id,
+            "customer.profile_upsert",
+            serde_json::json!({ "tenant_id": "not-a-uuid" }),
+        )
+        .await;
+
+        assert_eq!(process_outbox_batch(&pool, 10, 3).await.unwrap(), 0);
+        let (status, retry_count, last_error) = event_status(&pool, event_id).await;
+        assert_eq!((status.as_str(), retry_count), ("pending", 1));
+        assert!(last_error.is_some());
+        // The backoff keeps the event out of the next batch until it is due.
+        process_outbox_batch(&pool, 10, 3).await.unwrap();
+        assert_eq!(event_status(&pool, event_id).await.1, 1);
+
+        make_due(&pool, event_id).await;
+        process_outbox_batch(&pool, 10, 3).await.unwrap();
+        assert_eq!(event_status(&pool, event_id).await.0, "pending");
+        make_due(&pool, event_id).await;
+        process_outbox_batch(&pool, 10, 3).await.unwrap();
+        let (status, retry_count, _) = event_status(&pool, event_id).await;
+        assert_eq!((status.as_str(), retry_count), ("failed", 3));
+        let failed: (bool, bool) =
+            sqlx::query_as("SELECT failed_at IS NOT NULL, next_retry_at IS NULL FROM outbox_events WHERE id = $1")
+                .bind(event_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(failed, (true, true));
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn address_sync_fills_only_empty_fields(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let source_store = create_synced_store(&pool, tenant_id).await;
+        let target_store = create_synced_store(&pool, tenant_id).await;
+        let customer_id = create_customer(&pool, tenant_id).await;
+        let address_id: uuid::Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO customer_addresses (customer_id, type, name, postal_code, prefecture, city, line1, phone)
+            VALUES ($1, 'shipping', 'Local Name', '', 'Tokyo', 'Local City', '', '090-0000-0000')
+            RETURNING id
+            "#,
+        )
+        .bind(customer_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let event_id = insert_event(
+            &pool,
+            tenant_id,
+            "customer.address_upsert",
+            serde_json::json!({
+                "tenant_id": tenant_id.to_string(),
+                "source_store_id": source_store.to_string(),
+                "customer_id": customer_id.to_string(),
+                "address_id": address_id.to_string(),
+                "address": {
+                    "type": "shipping",
+                    "name": "Synced Name",
+                    "postal_code": "100-0001",
+                    "prefecture": "Osaka",
+                    "city": "Synced City",
+                    "line1": "1-1 Chiyoda",
+                    "line2": "Room 101",
+                    "phone": "080-1111-1111",
+                    "country_code": "JP",
+                },
+            }),
+        )
+        .await;
+
+        assert_eq!(process_outbox_batch(&pool, 10, 5).await.unwrap(), 1);
+
+        let address: (String, String, String, String, String, Option<String>, Option<String>) = sqlx::query_as(
+            r#"
+            SELECT name, postal_code, prefecture, city, line1, line2, phone
+            FROM customer_addresses
+            WHERE id = $1
+            "#,
+        )
+        .bind(address_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            address,
+            (
+                "Local Name".to_string(),
+                "100-0001".to_string(),
+                "Tokyo".to_string(),
+                "Local City".to_string(),
+                "1-1 Chiyoda".to_string(),
+                Some("Room 101".to_string()),
+                Some("090-0000-0000".to_string()),
+            )
+        );
+        assert_eq!(receipt_stores(&pool, event_id).await, vec![target_store]);
+        assert_eq!(event_status(&pool, event_id).await.0, "published");
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn schema_wait_gives_up_when_the_table_is_missing(pool: PgPool) {
+        wait_for_schema(&pool, 0, 1).await.unwrap();
+
+        sqlx::query("DROP TABLE outbox_events CASCADE")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = wait_for_schema(&pool, 0, 1).await.unwrap_err();
+        assert!(err.to_string().contains("not found after waiting 0s"), "{err}");
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn identity_sync_records_a_receipt_per_target_store(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let mut stores = vec![
+            create_synced_store(&pool, tenant_id).await,
+            create_synced_store(&pool, tenant_id).await,
+        ];
+        stores.sort();
+        let customer_id = create_customer(&pool, tenant_id).await;
+        let payload = serde_json::json!({
+            "tenant_id": tenant_id.to_string(),
+            "source_store_id": null,
+            "customer_id": customer_id.to_string(),
+            "identity": {
+                "identity_type": "email",
+                "identity_value": " Buyer@Example.com ",
+                "verified": true,
+                "source": "",
+            },
+        });
+        let event_id = insert_event(&pool, tenant_id, "customer.identity_upsert", payload.clone()).await;
+
+        assert_eq!(process_outbox_batch(&pool, 10, 5).await.unwrap(), 1);
+        assert_eq!(receipt_stores(&pool, event_id).await, stores);
+        let identity: (String, bool, String) =
+            sqlx::query_as("SELECT identity_value, verified, source FROM customer_identities WHERE customer_id = $1")
+                .bind(customer_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(identity, ("buyer@example.com".to_string(), true, "admin".to_string()));
+
+        // A replay of the same event finds every receipt and leaves the identity alone.
+        sqlx::query("UPDATE customer_identities SET verified = false WHERE customer_id = $1")
+            .bind(customer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        apply_identity_sync(&pool, event_id, serde_json::from_value(payload).unwrap())
+            .await
+            .unwrap();
+        let verified: bool = sqlx::query_scalar("SELECT verified FROM customer_identities WHERE customer_id = $1")
+            .bind(customer_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!verified);
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn old_failed_events_move_to_the_dead_letter_table_intact(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let store_id = create_synced_store(&pool, tenant_id).await;
+        let payload = serde_json::json!({ "customer_id": "c1" });
+        let old = insert_event(&pool, tenant_id, "customer.profile_upsert", payload.clone()).await;
+        let recent = insert_event(&pool, tenant_id, "customer.profile_upsert", payload.clone()).await;
+        sqlx::query(
+            r#"
+            UPDATE outbox_events
+            SET status = 'failed', retry_count = 5, last_error = 'boom',
+                failed_at = CASE WHEN id = $1 THEN now() - interval '2 hours' ELSE now() END
+            WHERE id = ANY($2)
+            "#,
+        )
+        .bind(old)
+        .bind(vec![old, recent])
+        .execute(&pool)
+        .await
+        .unwrap();
+        mark_processed(&pool, tenant_id, old, store_id).await.unwrap();
+
+        assert_eq!(move_failed_to_dead_letter(&pool, 10, 3600).await.unwrap(), 1);
+
+        let dead: (uuid::Uuid, String, serde_json::Value, String, i32, Option<String>, bool) = sqlx::query_as(
+            r#"
+            SELECT tenant_id, event_type, payload_json, status, retry_count, failure_reason,
+                   failed_at < now() - interval '1 hour'
+            FROM dead_letter_outbox_events
+            WHERE id = $1
+            "#,
+        )
+        .bind(old)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            dead,
+            (
+                tenant_id,
+                "customer.profile_upsert".to_string(),
+                payload,
+                "failed".to_string(),
+                5,
+                Some("boom".to_string()),
+                true,
+            )
+        );
+        let outbox: Vec<uuid::Uuid> = sqlx::query_scalar("SELECT id FROM outbox_events")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(outbox, vec![recent]);
+        assert!(receipt_stores(&pool, old).await.is_empty());
+    }
+
+    fn profile_event(
+        tenant_id: uuid::Uuid,
+        source_store: uuid::Uuid,
+        customer_id: uuid::Uuid,
+        force: bool,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "tenant_id": tenant_id.to_string(),
+            "source_store_id": source_store.to_string(),
+            "customer_id": customer_id.to_string(),
+            "profile": {
+                "name": "Synced Name",
+                "email": "synced@example.com",
+                "phone": "080-1111-1111",
+                "status": "active",
+                "notes": "synced notes",
+            },
+            "force": force,
+        })
+    }
+
+    async fn profile(
+        pool: &PgPool,
+        customer_id: uuid::Uuid,
+        store_id: uuid::Uuid,
+    ) -> (String, Option<String>, Option<String>, String, Option<String>) {
+        sqlx::query_as(
+            "SELECT name, email, phone, status, notes FROM customer_profiles WHERE customer_id = $1 AND store_id = $2",
+        )
+        .bind(customer_id)
+        .bind(store_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn malformed_uuid_in_a_valid_payload_fails_the_event(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let store_id = create_synced_store(&pool, tenant_id).await;
+        let customer_id = create_customer(&pool, tenant_id).await;
+        // Every field deserializes; only parse_uuid can reject the customer_id.
+        let mut payload = profile_event(tenant_id, store_id, customer_id, false);
+        payload["customer_id"] = serde_json::json!("not-a-uuid");
+        let event_id = insert_event(&pool, tenant_id, "customer.profile_upsert", payload.clone()).await;
+        let expected_error = r#"customer_id is not a valid uuid: "not-a-uuid""#;
+
+        let event = OutboxEvent {
+            id: event_id,
+            tenant_id,
+            store_id: None,
+            event_type: "customer.profile_upsert".to_string(),
+            payload,
+        };
+        let err = handle_event(&pool, &event).await.unwrap_err();
+        assert_eq!(err.to_string(), expected_error);
+
+        assert_eq!(process_outbox_batch(&pool, 10, 1).await.unwrap(), 0);
+        let (status, retry_count, last_error) = event_status(&pool, event_id).await;
+        assert_eq!((status.as_str(), retry_count), ("failed", 1));
+        assert_eq!(last_error.as_deref(), Some(expected_error));
+        assert!(receipt_stores(&pool, event_id).await.is_empty());
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn forced_profile_sync_overwrites_filled_fields(pool: PgPool) {
+        let tenant_id = create_tenant(&pool).await;
+        let source_store = create_synced_store(&pool, tenant_id).await;
+        let target_store = create_synced_store(&pool, tenant_id).await;
+        let customer_id = create_customer(&pool, tenant_id).await;
+        sqlx::query(
+            r#"
+            INSERT INTO customer_profiles (customer_id, store_id, name, email, status, notes)
+            VALUES ($1, $2, 'Local Name', 'local@example.com', 'inactive', 'local notes')
+            "#,
+        )
+        .bind(customer_id)
+        .bind(target_store)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let merge = insert_event(
+            &pool,
+            tenant_id,
+            "customer.profile_upsert",
+            profile_event(tenant_id, source_store, customer_id, false),
+        )
+        .await;
+        assert_eq!(process_outbox_batch(&pool, 10, 5).await.unwrap(), 1);
+        assert_eq!(event_status(&pool, merge).await.0, "published");
+        assert_eq!(
+            profile(&pool, customer_id, target_store).await,
+            (
+                "Local Name".to_string(),
+                Some("local@example.com".to_string()),
+                Some("080-1111-1111".to_string()),
+                "inactive".to_string(),
+                Some("local notes".to_string()),
+            )
+        );
+
+        let forced = insert_event(
+            &pool,
+            tenant_id,
+            "customer.profile_upsert",
+            profile_event(tenant_id, source_store, customer_id, true),
+        )
+        .await;
+        assert_eq!(process_outbox_batch(&pool, 10, 5).await.unwrap(), 1);
+        assert_eq!(event_status(&pool, forced).await.0, "published");
+        assert_eq!(
+            profile(&pool, customer_id, target_store).await,
+            (
+                "Synced Name".to_string(),
+                Some("synced@example.com".to_string()),
+                Some("080-1111-1111".to_string()),
+                "active".to_string(),
+                Some("synced notes".to_string()),
+            )
+        );
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn purge_removes_only_events_published_before_the_retention_window(pool: PgPool) {
+        const EXPIRED: i64 = 2_000;
+        const BATCH_SIZE: i64 = 500;
+        let tenant_id = create_tenant(&pool).await;
+        let store_id = create_synced_store(&pool, tenant_id).await;
+        let expired: Vec<uuid::Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO outbox_events
+                (tenant_id, aggregate_type, aggregate_id, event_type, payload_json, idempotency_key,
+                 status, published_at)
+            SELECT $1, 'product', 'product', 'product.updated', '{}'::jsonb, gen_random_uuid()::text,
+                   'published', now() - interval '31 days' - n * interval '1 second'
+            FROM generate_series(1, $2) n
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(EXPIRED)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let payload = serde_json::json!({});
+        let retained = insert_event(&pool, tenant_id, "product.updated", payload.clone()).await;
+        let pending = insert_event(&pool, tenant_id, "product.updated", payload).await;
+        sqlx::query(
+            "UPDATE outbox_events SET status = 'published', published_at = now() - interval '29 days' WHERE id = $1",
+        )
+        .bind(retained)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE outbox_events SET created_at = now() - interval '60 days' WHERE id = $1")
+            .bind(pending)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO processed_events (tenant_id, store_id, event_id) SELECT $1, $2, unnest($3::uuid[])")
+            .bind(tenant_id)
+            .bind(store_id)
+            .bind(&expired)
+            .execute(&pool)
+            .await
+            .unwrap();
+        mark_processed(&pool, tenant_id, retained, store_id).await.unwrap();
+
+        let mut purged = 0;
+        loop {
+            let batch = purge_published_events(&pool, BATCH_SIZE, 30).await.unwrap();
+            assert!(batch <= BATCH_SIZE as u64, "purged {batch} rows in one batch");
+            if batch == 0 {
+                break;
+            }
+            purged += batch;
+        }
+        assert_eq!(purged, EXPIRED as u64);
+
+        let mut remaining: Vec<uuid::Uuid> = sqlx::query_scalar("SELECT id FROM outbox_events")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        remaining.sort();
+        let mut expected = vec![retained, pending];
+        expected.sort();
+        assert_eq!(remaining, expected);
+        let receipts: Vec<uuid::Uuid> = sqlx::query_scalar("SELECT event_id FROM processed_events")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(receipts, vec![retained]);
+        assert_eq!(receipt_stores(&pool, retained).await, vec![store_id]);
     }
 }
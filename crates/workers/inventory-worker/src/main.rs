@@ -1,9 +1,20 @@
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
-use rs_common::{env, telemetry};
+use rs_common::{env, shutdown, telemetry};
 use sqlx::{PgPool, Row, postgres::PgPoolOptions};
-use tracing::info;
+use tracing::{error, info, warn};
+
+use crate::{metrics::WorkerMetrics, outbox::InventoryOutboxWorker};
+
+mod metrics;
+mod outbox;
+
+const SERIALIZATION_FAILURE: &str = "40001";
+const SERIALIZATION_RETRY_BASE_MS: u64 = 20;
 
 #[derive(Debug)]
 struct ReservationRequest {
@@ -16,41 +27,83 @@ struct ReservationRequest {
     quantity: i32,
 }
 
+enum RequestOutcome {
+    Reserved,
+    Failed,
+    Requeued,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    telemetry::init_tracing("inventory-worker");
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL is required");
-    let pool = PgPoolOptions::new().max_connections(5).connect(&db_url).await?;
+    telemetry::init_tracing("inventory-worker")?;
+    env::validate_required_vars(&["DATABASE_URL"])?;
+    let db_url = std::env::var("DATABASE_URL")?;
+    let connect_timeout_ms = env::env_u64("DATABASE_CONNECT_TIMEOUT_MS", 5000)?;
+    let pool = PgPoolOptions::new()
+        .min_connections(env::env_u64("DATABASE_MIN_CONNECTIONS", 1)? as u32)
+        .max_connections(env::env_u64("DATABASE_MAX_CONNECTIONS", 5)? as u32)
+        .acquire_timeout(Duration::from_millis(connect_timeout_ms))
+        .connect(&db_url)
+        .await?;
+
+    let max_schema_wait_seconds = env::env_u64("MAX_SCHEMA_WAIT_SECONDS", 300)?;
+    let schema_wait_sleep_ms = env::env_u64("SCHEMA_WAIT_SLEEP_MS", 1000)?;
+    wait_for_schema(&pool, max_schema_wait_seconds, schema_wait_sleep_ms).await?;
 
-    wait_for_schema(&pool).await?;
+    let batch_size = env::env_usize("INVENTORY_WORKER_BATCH_SIZE", 50)? as i64;
+    let ttl_seconds = env::env_i64("INVENTORY_RESERVATION_TTL_SECONDS", 900)?;
+    let sleep_ms = env::env_u64("INVENTORY_WORKER_SLEEP_MS", 500)?;
+    let oneshot = env::env_bool("INVENTORY_WORKER_ONESHOT", false)?;
+    let retry_max = env::env_u64("INVENTORY_SERIALIZATION_RETRY_MAX", 3)? as u32;
+    let max_retries = env::env_i64("INVENTORY_MAX_RETRIES", 3)? as i32;
+    let cleanup_age_days = env::env_i64("INVENTORY_CLEANUP_AGE_DAYS", 30)? as i32;
+    let metrics_port = env::env_u64("METRICS_PORT", 9090)? as u16;
 
-    let batch_size = env::env_usize("INVENTORY_WORKER_BATCH_SIZE", 50) as i64;
-    let ttl_seconds = env::env_i64("INVENTORY_RESERVATION_TTL_SECONDS", 900);
-    let sleep_ms = env::env_u64("INVENTORY_WORKER_SLEEP_MS", 500);
-    let oneshot = env::env_bool("INVENTORY_WORKER_ONESHOT", false);
+    let shutdown = shutdown::cancel_on_signal();
+    let outbox_worker = InventoryOutboxWorker::new(pool.clone(), batch_size, max_retries);
+
+    let metrics = Arc::new(WorkerMetrics::new()?);
+    let server_metrics = metrics.clone();
+    tokio::spawn(async move {
+        if let Err(err) = metrics::serve(server_metrics, metrics_port).await {
+            warn!(error = %err, "inventory worker metrics server stopped");
+        }
+    });
 
     loop {
-        let (hot_done, hot_failed) = process_queue_batch(&pool, batch_size, ttl_seconds, true).await?;
-        let (normal_done, normal_failed) = process_queue_batch(&pool, batch_size, ttl_seconds, false).await?;
-        let released = release_expired_reservations(&pool, batch_size).await?;
+        let (hot_done, hot_failed) =
+            process_queue_batch(&pool, &metrics, batch_size, ttl_seconds, retry_max, max_retries, true).await?;
+        let (normal_done, normal_failed) =
+            process_queue_batch(&pool, &metrics, batch_size, ttl_seconds, retry_max, max_retries, false).await?;
+        let released = release_expired_reservations(&pool, &metrics, batch_size).await?;
+        let purged = purge_failed_requests(&pool, batch_size, cleanup_age_days).await?;
+        let stock_events = outbox_worker.process_batch().await?;
 
         info!(
             hot_done,
-            hot_failed, normal_done, normal_failed, released, "inventory worker batch completed"
+            hot_failed, normal_done, normal_failed, released, purged, stock_events, "inventory worker batch completed"
         );
 
-        if oneshot {
+        // Batches run to completion; shutdown is only observed between them.
+        if oneshot || shutdown.is_cancelled() {
             break;
         }
-        tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {}
+        }
     }
 
+    info!("graceful shutdown complete");
     Ok(())
 }
 
-async fn wait_for_schema(pool: &PgPool) -> Result<()> {
-    let mut attempts = 0;
-    loop {
+/// Polls every `sleep_ms` until migrations have created the worker's tables, giving up after
+/// `max_wait_seconds` (`MAX_SCHEMA_WAIT_SECONDS` / `SCHEMA_WAIT_SLEEP_MS`).
+async fn wait_for_schema(pool: &PgPool, max_wait_seconds: u64, sleep_ms: u64) -> Result<()> {
+    let sleep_ms = sleep_ms.max(1);
+    let max_attempts = (max_wait_seconds * 1000).div_ceil(sleep_ms).max(1);
+    for attempt in 1..=max_attempts {
         let row = sqlx::query("SELECT to_regclass('public.inventory_reservation_requests')::text as req_table")
             .fetch_one(pool)
             .await?;
@@ -58,18 +111,33 @@ async fn wait_for_schema(pool: &PgPool) -> Result<()> {
         if exists.is_some() {
             return Ok(());
         }
-        attempts += 1;
-        if attempts % 10 == 0 {
+        if attempt % 10 == 0 {
             info!(
-                attempts,
+                attempts = attempt,
                 "waiting for migrations to create inventory reservation tables"
             );
         }
-        tokio::time::sleep(Duration::from_secs(1)).await;
+        tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
     }
+    error!(
+        attempts = max_attempts,
+        max_wait_seconds, "timed out waiting for migrations to create inventory reservation tables"
+    );
+    anyhow::bail!(
+        "inventory reservation tables not found after waiting {max_wait_seconds}s; check that migrations have run"
+    )
 }
 
-async fn process_queue_batch(pool: &PgPool, batch_size: i64, ttl_seconds: i64, is_hot: bool) -> Result<(usize, usize)> {
+async fn process_queue_batch(
+    pool: &PgPool,
+    metrics: &WorkerMetrics,
+    batch_size: i64,
+    ttl_seconds: i64,
+    retry_max: u32,
+    max_retries: i32,
+    is_hot: bool,
+) -> Result<(usize, usize)> {
+    let started_at = Instant::now();
     let mut tx = pool.begin().await?;
     let mut done = 0usize;
     let mut failed = 0usize;
@@ -79,6 +147,7 @@ async fn process_queue_batch(pool: &PgPool, batch_size: i64, ttl_seconds: i64, i
             SELECT id
             FROM inventory_reservation_requests
             WHERE status = 'queued' AND is_hot = $1
+              AND (next_retry_at IS NULL OR next_retry_at <= now())
             ORDER BY created_at ASC
             LIMIT $2
             FOR UPDATE SKIP LOCKED
@@ -106,17 +175,137 @@ async fn process_queue_batch(pool: &PgPool, batch_size: i64, ttl_seconds: i64, i
             location_id: row.get("location_id"),
             quantity: row.get("quantity"),
         };
-        if process_request(pool, request, ttl_seconds).await? {
-            done += 1;
-        } else {
-            failed += 1;
+        match process_request(pool, &request, ttl_seconds, retry_max, max_retries).await? {
+            RequestOutcome::Reserved => done += 1,
+            RequestOutcome::Failed => failed += 1,
+            RequestOutcome::Requeued => {}
         }
     }
 
+    metrics.record_batch(is_hot, done, failed, started_at.elapsed().as_secs_f64());
     Ok((done, failed))
 }
 
-async fn process_request(pool: &PgPool, request: ReservationRequest, ttl_seconds: i64) -> Result<bool> {
+// Serialization failures (40001) under contention are retried in-process; other database
+// errors re-queue the request with backoff. Insufficient stock fails immediately.
+async fn process_request(
+    pool: &PgPool,
+    request: &ReservationRequest,
+    ttl_seconds: i64,
+    retry_max: u32,
+    max_retries: i32,
+) -> Result<RequestOutcome> {
+    let mut attempt = 0u32;
+    loop {
+        match try_process_request(pool, request, ttl_seconds).await {
+            Ok(true) => return Ok(RequestOutcome::Reserved),
+            Ok(false) => return Ok(RequestOutcome::Failed),
+            Err(err) if is_serialization_failure(&err) => {
+                if attempt >= retry_max {
+                    warn!(
+                        request_id = %request.id,
+                        attempts = attempt + 1,
+                        "reservation request failed after serialization retries"
+                    );
+                    mark_request_failed(pool, request.id).await?;
+                    return Ok(RequestOutcome::Failed);
+                }
+                let delay = retry_delay(attempt);
+                tracing::debug!(
+                    request_id = %request.id,
+                    attempt = attempt + 1,
+                    delay_ms = delay.as_millis() as u64,
+                    "serialization failure; retrying reservation request"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) if err.downcast_ref::<sqlx::Error>().is_some() => {
+                warn!(request_id = %request.id, error = %err, "reservation request errored");
+                return requeue_request(pool, request.id, max_retries).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// Same backoff as outbox retries: next_retry_at = now() + 2^retry_count seconds.
+async fn requeue_request(pool: &PgPool, request_id: uuid::Uuid, max_retries: i32) -> Result<RequestOutcome> {
+    let row = sqlx::query(
+        r#"
+        UPDATE inventory_reservation_requests
+        SET retry_count = retry_count + 1,
+            status = CASE WHEN retry_count + 1 >= $2 THEN 'failed' ELSE 'queued' END,
+            next_retry_at = CASE
+                WHEN retry_count + 1 >= $2 THEN NULL
+                ELSE now() + make_interval(secs => power(2, retry_count))
+            END,
+            updated_at = now()
+        WHERE id = $1
+        RETURNING status
+        "#,
+    )
+    .bind(request_id)
+    .bind(max_retries)
+    .fetch_one(pool)
+    .await?;
+    let status: String = row.get("status");
+    if status == "failed" {
+        Ok(RequestOutcome::Failed)
+    } else {
+        Ok(RequestOutcome::Requeued)
+    }
+}
+
+async fn purge_failed_requests(pool: &PgPool, batch_size: i64, age_days: i32) -> Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM inventory_reservation_requests
+        WHERE id IN (
+            SELECT id
+            FROM inventory_reservation_requests
+            WHERE status = 'failed' AND updated_at < now() - make_interval(days => $1)
+            ORDER BY updated_at ASC
+            LIMIT $2
+        )
+        "#,
+    )
+    .bind(age_days)
+    .bind(batch_size)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+fn is_serialization_failure(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<sqlx::Error>(),
+        Some(sqlx::Error::Database(db_err)) if db_err.code().as_deref() == Some(SERIALIZATION_FAILURE)
+    )
+}
+
+// Exponential backoff with full jitter: base * 2^attempt plus up to the same amount again.
+fn retry_delay(attempt: u32) -> Duration {
+    let backoff = SERIALIZATION_RETRY_BASE_MS.saturating_mul(1 << attempt.min(10));
+    let jitter = (uuid::Uuid::new_v4().as_u128() % (backoff as u128 + 1)) as u64;
+    Duration::from_millis(backoff + jitter)
+}
+
+async fn mark_request_failed(pool: &PgPool, request_id: uuid::Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE inventory_reservation_requests
+        SET status = 'failed', updated_at = now()
+        WHERE id = $1
+        "#,
+    )
+    .bind(request_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn try_process_request(pool: &PgPool, request: &ReservationRequest, ttl_seconds: i64) -> Result<bool> {
     let Some(location_id) = request.location_id else {
         let mut tx = pool.begin().await?;
         sqlx::query(
@@ -254,7 +443,7 @@ async fn process_request(pool: &PgPool, request: ReservationRequest, ttl_seconds
     Ok(true)
 }
 
-async fn release_expired_reservations(pool: &PgPool, batch_size: i64) -> Result<usize> {
+async fn release_expired_reservations(pool: &PgPool, metrics: &WorkerMetrics, batch_size: i64) -> Result<usize> {
     let mut tx = pool.begin().await?;
     let rows = sqlx::query(
         r#"
@@ -341,6 +530,7 @@ async fn release_expired_reservations(pool: &PgPool, batch_size: i64) -> Result<
     }
 
     tx.commit().await?;
+    metrics.record_released(released);
     Ok(released)
 }
 
@@ -383,3 +573,102 @@ async fn insert_inventory_movement_tx(
     .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A queued reservation request for a fresh store, product, SKU and cart.
+    async fn insert_request(pool: &PgPool, status: &str, updated_days_ago: i32) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            WITH tenant AS (
+                INSERT INTO tenants (name, type, default_currency, status)
+                VALUES ('Test tenant', 'single_brand', 'JPY', 'active')
+                RETURNING id
+            ), store AS (
+                INSERT INTO stores (tenant_id, name) SELECT id, 'Test store' FROM tenant RETURNING id, tenant_id
+            ), product AS (
+                INSERT INTO products (tenant_id, store_id, title, description, status)
+                SELECT tenant_id, id, 'Mug', '', 'active' FROM store
+                RETURNING id
+            ), sku AS (
+                INSERT INTO product_skus (product_id, sku, price_amount, price_currency, status)
+                SELECT id, 'MUG-1', 1000, 'JPY', 'active' FROM product
+                RETURNING id
+            ), cart AS (
+                INSERT INTO carts (store_id) SELECT id FROM store RETURNING id, store_id
+            )
+            INSERT INTO inventory_reservation_requests (store_id, cart_id, sku_id, quantity, status, updated_at)
+            SELECT cart.store_id, cart.id, sku.id, 1, $1, now() - make_interval(days => $2)
+            FROM cart, sku
+            RETURNING id
+            "#,
+        )
+        .bind(status)
+        .bind(updated_days_ago)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn request_state(pool: &PgPool, request_id: uuid::Uuid) -> (String, i32, bool) {
+        sqlx::query_as(
+            "SELECT status, retry_count, next_retry_at IS NOT NULL FROM inventory_reservation_requests WHERE id = $1",
+        )
+        .bind(request_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn requeue_backs_off_until_max_retries(pool: PgPool) {
+        let request_id = insert_request(&pool, "processing", 0).await;
+
+        assert!(matches!(
+            requeue_request(&pool, request_id, 3).await.unwrap(),
+            RequestOutcome::Requeued
+        ));
+        assert_eq!(request_state(&pool, request_id).await, ("queued".to_string(), 1, true));
+        assert!(matches!(
+            requeue_request(&pool, request_id, 3).await.unwrap(),
+            RequestOutcome::Requeued
+        ));
+        assert!(matches!(
+            requeue_request(&pool, request_id, 3).await.unwrap(),
+            RequestOutcome::Failed
+        ));
+        assert_eq!(request_state(&pool, request_id).await, ("failed".to_string(), 3, false));
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn purge_removes_only_old_failed_requests(pool: PgPool) {
+        let old_failed = insert_request(&pool, "failed", 31).await;
+        let recent_failed = insert_request(&pool, "failed", 29).await;
+        let old_queued = insert_request(&pool, "queued", 31).await;
+
+        assert_eq!(purge_failed_requests(&pool, 100, 30).await.unwrap(), 1);
+        let remaining: Vec<uuid::Uuid> =
+            sqlx::query_scalar("SELECT id FROM inventory_reservation_requests WHERE id = ANY($1)")
+                .bind(vec![old_failed, recent_failed, old_queued])
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert!(!remaining.contains(&old_failed));
+        assert!(remaining.contains(&recent_failed));
+        assert!(remaining.contains(&old_queued));
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn schema_wait_gives_up_when_the_table_is_missing(pool: PgPool) {
+        wait_for_schema(&pool, 0, 1).await.unwrap();
+
+        sqlx::query("DROP TABLE inventory_reservation_requests CASCADE")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = wait_for_schema(&pool, 0, 1).await.unwrap_err();
+        assert!(err.to_string().contains("not found after waiting 0s"), "{err}");
+    }
+}
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{Router, extract::State, http::StatusCode, http::header, response::IntoResponse, routing::get};
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+use tracing::info;
+
+pub struct WorkerMetrics {
+    registry: Registry,
+    reservations_done: IntCounterVec,
+    reservations_failed: IntCounterVec,
+    reservations_released: IntCounter,
+    batch_duration: Histogram,
+}
+
+impl WorkerMetrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+        let reservations_done = IntCounterVec::new(
+            Opts::new(
+                "inventory_reservations_done_total",
+                "Reservation requests processed successfully",
+            ),
+            &["priority"],
+        )?;
+        let reservations_failed = IntCounterVec::new(
+            Opts::new(
+                "inventory_reservations_failed_total",
+                "Reservation requests marked failed",
+            ),
+            &["priority"],
+        )?;
+        let reservations_released = IntCounter::new(
+            "inventory_reservations_released_total",
+            "Expired reservations released back to stock",
+        )?;
+        let batch_duration = Histogram::with_opts(HistogramOpts::new(
+            "inventory_batch_duration_seconds",
+            "Duration of a single reservation queue batch",
+        ))?;
+        registry.register(Box::new(reservations_done.clone()))?;
+        registry.register(Box::new(reservations_failed.clone()))?;
+        registry.register(Box::new(reservations_released.clone()))?;
+        registry.register(Box::new(batch_duration.clone()))?;
+        Ok(Self {
+            registry,
+            reservations_done,
+            reservations_failed,
+            reservations_released,
+            batch_duration,
+        })
+    }
+
+    pub fn record_batch(&self, is_hot: bool, done: usize, failed: usize, elapsed_secs: f64) {
+        let priority = if is_hot { "hot" } else { "normal" };
+        self.reservations_done
+            .with_label_values(&[priority])
+            .inc_by(done as u64);
+        self.reservations_failed
+            .with_label_values(&[priority])
+            .inc_by(failed as u64);
+        self.batch_duration.observe(elapsed_secs);
+    }
+
+    pub fn record_released(&self, released: usize) {
+        self.reservations_released.inc_by(released as u64);
+    }
+
+    fn render(&self) -> Result<String> {
+        let mut buf = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+        Ok(String::from_utf8(buf)?)
+    }
+}
+
+pub async fn serve(metrics: Arc<WorkerMetrics>, port: u16) -> Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(metrics);
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    info!(port, "inventory worker metrics listening");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn metrics_handler(State(metrics): State<Arc<WorkerMetrics>>) -> impl IntoResponse {
+    match metrics.render() {
+        Ok(body) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, TextEncoder::new().format_type().to_string())],
+            body,
+        ),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/plain".to_string())],
+            err.to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_batches_are_rendered() {
+        let metrics = WorkerMetrics::new().unwrap();
+        metrics.record_batch(true, 3, 1, 0.25);
+        metrics.record_batch(false, 2, 0, 0.5);
+        metrics.record_batch(true, 4, 0, 1.5);
+        metrics.record_released(5);
+
+        let body = metrics.render().unwrap();
+        assert!(
+            body.contains(r#"inventory_reservations_done_total{priority="hot"} 7"#),
+            "{body}"
+        );
+        assert!(
+            body.contains(r#"inventory_reservations_done_total{priority="normal"} 2"#),
+            "{body}"
+        );
+        assert!(
+            body.contains(r#"inventory_reservations_failed_total{priority="hot"} 1"#),
+            "{body}"
+        );
+        assert!(
+            body.contains(r#"inventory_reservations_failed_total{priority="normal"} 0"#),
+            "{body}"
+        );
+        assert!(body.contains("inventory_reservations_released_total 5"), "{body}");
+        assert!(
+            body.contains("# TYPE inventory_batch_duration_seconds histogram"),
+            "{body}"
+        );
+        assert!(body.contains("inventory_batch_duration_seconds_count 3"), "{body}");
+        assert!(body.contains("inventory_batch_duration_seconds_sum 2.25"), "{body}");
+        assert!(
+            body.contains(r#"inventory_batch_duration_seconds_bucket{le="1"} 2"#),
+            "{body}"
+        );
+    }
+}
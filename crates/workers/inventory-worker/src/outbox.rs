@@ -0,0 +1,221 @@
+use anyhow::Result;
+use rs_common::ids::parse_uuid;
+use serde::Deserialize;
+use sqlx::{PgPool, Row};
+use tracing::warn;
+
+const STOCK_SET_EVENT_TYPE: &str = "inventory.stock_set";
+
+#[derive(Debug, Deserialize)]
+struct StockSetPayload {
+    variant_id: String,
+    location_id: String,
+    available: i32,
+}
+
+/// Consumes `inventory.stock_set` outbox events. For now it only warns when available stock
+/// falls below the row's optional `reorder_point`.
+pub struct InventoryOutboxWorker {
+    pool: PgPool,
+    batch_size: i64,
+    max_retries: i32,
+}
+
+impl InventoryOutboxWorker {
+    pub fn new(pool: PgPool, batch_size: i64, max_retries: i32) -> Self {
+        Self {
+            pool,
+            batch_size,
+            max_retries,
+        }
+    }
+
+    /// Claims due events with `FOR UPDATE SKIP LOCKED` and handles them inside that transaction.
+    /// The rows stay `pending` while locked, so a worker that dies mid-batch releases them on
+    /// rollback instead of leaving them stuck in `processing`.
+    pub async fn process_batch(&self) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+        let rows = sqlx::query(
+            r#"
+            SELECT id, store_id, payload_json
+            FROM outbox_events
+            WHERE status = 'pending'
+              AND (next_retry_at IS NULL OR next_retry_at <= now())
+              AND event_type = $2
+            ORDER BY created_at ASC
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(self.batch_size)
+        .bind(STOCK_SET_EVENT_TYPE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut processed = 0usize;
+        for row in rows {
+            let event_id: uuid::Uuid = row.get("id");
+            let store_id: Option<uuid::Uuid> = row.get("store_id");
+            let payload: serde_json::Value = row.get("payload_json");
+            // Handlers read through the pool, so their errors cannot abort the claiming transaction.
+            match self.handle_stock_set(store_id, payload).await {
+                Ok(()) => {
+                    processed += 1;
+                    sqlx::query("UPDATE outbox_events SET status = 'published', published_at = now() WHERE id = $1")
+                        .bind(event_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                Err(err) => {
+                    warn!(error = %err, event_id = %event_id, "inventory outbox event failed");
+                    // Same backoff as the customer sync worker: 2^retry_count seconds until max_retries.
+                    sqlx::query(
+                        r#"
+                        UPDATE outbox_events
+                        SET retry_count = retry_count + 1,
+                            status = CASE WHEN retry_count + 1 >= $2 THEN 'failed' ELSE 'pending' END,
+                            next_retry_at = CASE
+                                WHEN retry_count + 1 >= $2 THEN NULL
+                                ELSE now() + make_interval(secs => power(2, retry_count))
+                            END,
+                            failed_at = CASE WHEN retry_count + 1 >= $2 THEN now() ELSE NULL END,
+                            last_error = $3
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(event_id)
+                    .bind(self.max_retries)
+                    .bind(err.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(processed)
+    }
+
+    async fn handle_stock_set(&self, store_id: Option<uuid::Uuid>, payload: serde_json::Value) -> Result<()> {
+        let payload: StockSetPayload = serde_json::from_value(payload)?;
+        let sku_id = parse_uuid(&payload.variant_id, "variant_id")?;
+        let location_id = parse_uuid(&payload.location_id, "location_id")?;
+        let reorder_point: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT reorder_point
+            FROM inventory_stocks
+            WHERE store_id = $1 AND sku_id = $2 AND location_id = $3
+            "#,
+        )
+        .bind(store_id)
+        .bind(sku_id)
+        .bind(location_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+        if let Some(reorder_point) = reorder_point
+            && payload.available < reorder_point
+        {
+            warn!(
+                store_id = ?store_id,
+                sku_id = %sku_id,
+                location_id = %location_id,
+                available = payload.available,
+                reorder_point,
+                "inventory below reorder point"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn insert_event(pool: &PgPool, tenant_id: uuid::Uuid, payload: serde_json::Value) -> uuid::Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO outbox_events (tenant_id, aggregate_type, aggregate_id, event_type, payload_json, idempotency_key)
+            VALUES ($1, 'inventory', 'sku', $2, $3, gen_random_uuid()::text)
+            RETURNING id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(STOCK_SET_EVENT_TYPE)
+        .bind(payload)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn event_state(pool: &PgPool, event_id: uuid::Uuid) -> (String, i32) {
+        sqlx::query_as("SELECT status, retry_count FROM outbox_events WHERE id = $1")
+            .bind(event_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn batch_publishes_valid_events_and_retries_bad_ones(pool: PgPool) {
+        let tenant_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (name, type, default_currency, status) VALUES ('t', 'single_brand', 'JPY', 'active') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let valid = insert_event(
+            &pool,
+            tenant_id,
+            serde_json::json!({
+                "variant_id": uuid::Uuid::new_v4().to_string(),
+                "location_id": uuid::Uuid::new_v4().to_string(),
+                "available": 3,
+            }),
+        )
+        .await;
+        let invalid = insert_event(&pool, tenant_id, serde_json::json!({ "variant_id": "not-a-uuid" })).await;
+
+        let worker = InventoryOutboxWorker::new(pool.clone(), 10, 2);
+        assert_eq!(worker.process_batch().await.unwrap(), 1);
+        assert_eq!(event_state(&pool, valid).await, ("published".to_string(), 0));
+        assert_eq!(event_state(&pool, invalid).await, ("pending".to_string(), 1));
+
+        // Not due yet because of the backoff, so the next batch leaves it alone.
+        assert_eq!(worker.process_batch().await.unwrap(), 0);
+        sqlx::query("UPDATE outbox_events SET next_retry_at = now() WHERE id = $1")
+            .bind(invalid)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(worker.process_batch().await.unwrap(), 0);
+        assert_eq!(event_state(&pool, invalid).await, ("failed".to_string(), 2));
+    }
+
+    #[sqlx::test(migrations = "../../../migrations")]
+    async fn events_stay_pending_while_a_batch_holds_them(pool: PgPool) {
+        let tenant_id: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (name, type, default_currency, status) VALUES ('t', 'single_brand', 'JPY', 'active') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let event_id = insert_event(&pool, tenant_id, serde_json::json!({})).await;
+
+        // Another batch holds the row: it is skipped but stays 'pending', so a rollback hands it straight back.
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SELECT id FROM outbox_events WHERE id = $1 FOR UPDATE SKIP LOCKED")
+            .bind(event_id)
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        let worker = InventoryOutboxWorker::new(pool.clone(), 10, 5);
+        assert_eq!(worker.process_batch().await.unwrap(), 0);
+        assert_eq!(event_state(&pool, event_id).await, ("pending".to_string(), 0));
+        drop(tx);
+
+        worker.process_batch().await.unwrap();
+        assert_eq!(event_state(&pool, event_id).await, ("pending".to_string(), 1));
+    }
+}
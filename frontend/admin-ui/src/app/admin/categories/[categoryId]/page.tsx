@@ -111,7 +111,15 @@ export default function CategoryDetailPage() {
     }
     setIsSubmitting(true);
     try {
-      await deleteCategory({ categoryId });
+      const resp = await deleteCategory({ categoryId });
+      if (!resp.deleted) {
+        push({
+          variant: "error",
+          title: "Delete failed",
+          description: "The category is still linked to products.",
+        });
+        return;
+      }
       push({
         variant: "success",
         title: "Category deleted",
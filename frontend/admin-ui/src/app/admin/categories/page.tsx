@@ -75,7 +75,15 @@ export default function CategoriesPage() {
   async function handleDelete(category: Category) {
     setIsSubmitting(true);
     try {
-      await deleteCategory({ categoryId: category.id });
+      const resp = await deleteCategory({ categoryId: category.id });
+      if (!resp.deleted) {
+        push({
+          variant: "error",
+          title: "Delete failed",
+          description: `${category.name} is still linked to products.`,
+        });
+        return;
+      }
       push({
         variant: "success",
         title: "Category deleted",
@@ -0,0 +1,69 @@
+CREATE TABLE IF NOT EXISTS outbox_event_schemas (
+    event_type text NOT NULL,
+    version int NOT NULL,
+    json_schema_json jsonb NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (event_type, version)
+);
+
+INSERT INTO outbox_event_schemas (event_type, version, json_schema_json) VALUES
+(
+    'customer.profile_upsert',
+    1,
+    '{
+        "type": "object",
+        "required": ["tenant_id", "customer_id", "profile"],
+        "properties": {
+            "tenant_id": {"type": "string"},
+            "source_store_id": {"type": ["string", "null"]},
+            "customer_id": {"type": "string"},
+            "profile": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "email": {"type": "string"},
+                    "phone": {"type": "string"},
+                    "status": {"type": "string"},
+                    "notes": {"type": "string"},
+                    "country_code": {"type": "string"}
+                }
+            }
+        }
+    }'::jsonb
+),
+(
+    'customer.identity_upsert',
+    1,
+    '{
+        "type": "object",
+        "required": ["tenant_id", "customer_id", "identity"],
+        "properties": {
+            "tenant_id": {"type": "string"},
+            "source_store_id": {"type": ["string", "null"]},
+            "customer_id": {"type": "string"},
+            "identity": {
+                "type": "object",
+                "required": ["identity_type", "identity_value"],
+                "properties": {
+                    "identity_type": {"type": "string"},
+                    "identity_value": {"type": "string"},
+                    "verified": {"type": "boolean"},
+                    "source": {"type": "string"}
+                }
+            }
+        }
+    }'::jsonb
+),
+(
+    'customer.address_upsert',
+    1,
+    '{
+        "type": "object",
+        "required": ["customer_id", "address_id"],
+        "properties": {
+            "customer_id": {"type": "string"},
+            "address_id": {"type": "string"}
+        }
+    }'::jsonb
+)
+ON CONFLICT (event_type, version) DO NOTHING;
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS product_translations (
+    product_id uuid NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    locale text NOT NULL,
+    title text NOT NULL,
+    description text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (product_id, locale)
+);
+
+CREATE INDEX IF NOT EXISTS product_translations_locale_idx
+    ON product_translations (locale);
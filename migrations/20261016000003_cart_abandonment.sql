@@ -0,0 +1,32 @@
+ALTER TABLE carts
+    ADD COLUMN IF NOT EXISTS last_activity_at timestamptz NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS abandoned_at timestamptz;
+
+CREATE INDEX IF NOT EXISTS carts_abandonment_idx
+    ON carts (last_activity_at)
+    WHERE status = 'active' AND abandoned_at IS NULL;
+
+INSERT INTO outbox_event_schemas (event_type, version, json_schema_json) VALUES
+(
+    'cart.abandoned',
+    1,
+    '{
+        "type": "object",
+        "required": ["cart_id", "store_id", "customer_id", "item_count", "estimated_total"],
+        "properties": {
+            "cart_id": {"type": "string"},
+            "store_id": {"type": "string"},
+            "customer_id": {"type": ["string", "null"]},
+            "item_count": {"type": "integer", "minimum": 1},
+            "estimated_total": {
+                "type": "object",
+                "required": ["amount", "currency"],
+                "properties": {
+                    "amount": {"type": "integer"},
+                    "currency": {"type": "string"}
+                }
+            }
+        }
+    }'::jsonb
+)
+ON CONFLICT (event_type, version) DO NOTHING;
@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS audit_logs_store_created_id_idx
+    ON audit_logs (store_id, created_at DESC, id DESC);
@@ -0,0 +1,6 @@
+ALTER TABLE carts
+    ADD COLUMN IF NOT EXISTS anonymous_token uuid;
+
+CREATE UNIQUE INDEX IF NOT EXISTS carts_anonymous_token_idx
+    ON carts (anonymous_token)
+    WHERE anonymous_token IS NOT NULL;
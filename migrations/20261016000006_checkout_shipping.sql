@@ -0,0 +1,6 @@
+ALTER TABLE shipping_rates
+    ADD COLUMN IF NOT EXISTS carrier_name text NOT NULL DEFAULT '';
+
+ALTER TABLE orders
+    ADD COLUMN IF NOT EXISTS shipping_amount bigint NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS shipping_carrier_name text;
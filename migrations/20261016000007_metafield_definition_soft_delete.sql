@@ -0,0 +1,10 @@
+ALTER TABLE metafield_definitions
+    ADD COLUMN IF NOT EXISTS deleted_at timestamptz;
+
+-- Soft-deleted definitions must not block re-creating the same namespace/key.
+ALTER TABLE metafield_definitions
+    DROP CONSTRAINT IF EXISTS metafield_definitions_owner_type_namespace_key_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS metafield_definitions_active_key_idx
+    ON metafield_definitions (owner_type, namespace, key)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE store_catalog_settings
+    ADD COLUMN IF NOT EXISTS search_settings_json jsonb NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,14 @@
+ALTER TABLE promotions
+    ADD COLUMN IF NOT EXISTS first_order_only boolean NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS order_promotions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    order_id uuid NOT NULL REFERENCES orders(id) ON DELETE CASCADE,
+    promotion_id uuid NOT NULL REFERENCES promotions(id),
+    metadata jsonb NOT NULL DEFAULT '{}'::jsonb,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    UNIQUE (order_id, promotion_id)
+);
+
+CREATE INDEX IF NOT EXISTS order_promotions_promotion_idx
+    ON order_promotions (promotion_id, created_at);
@@ -0,0 +1,5 @@
+-- Supports ListAuditLogs metadata_query (jsonb containment).
+CREATE INDEX IF NOT EXISTS audit_logs_metadata_json_gin_idx
+    ON audit_logs USING GIN (metadata_json jsonb_path_ops);
+CREATE INDEX IF NOT EXISTS audit_logs_after_json_gin_idx
+    ON audit_logs USING GIN (after_json jsonb_path_ops);
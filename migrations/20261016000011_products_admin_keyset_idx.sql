@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS products_store_created_id_idx
+    ON products (store_id, created_at DESC, id DESC);
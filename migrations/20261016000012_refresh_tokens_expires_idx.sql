@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS store_staff_refresh_tokens_expires_idx
+    ON store_staff_refresh_tokens (expires_at);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS staff_password_resets (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    store_id uuid NOT NULL REFERENCES stores(id),
+    staff_id uuid NOT NULL REFERENCES store_staff(id) ON DELETE CASCADE,
+    token_hash text NOT NULL,
+    expires_at timestamptz NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS staff_password_resets_token_hash_key
+    ON staff_password_resets (token_hash);
+CREATE INDEX IF NOT EXISTS staff_password_resets_staff_idx
+    ON staff_password_resets (staff_id);
@@ -0,0 +1,3 @@
+ALTER TABLE outbox_events
+    ADD COLUMN IF NOT EXISTS retry_count integer NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_retry_at timestamptz;
@@ -0,0 +1,24 @@
+ALTER TABLE outbox_events
+    ADD COLUMN IF NOT EXISTS failed_at timestamptz,
+    ADD COLUMN IF NOT EXISTS last_error text;
+
+CREATE TABLE IF NOT EXISTS dead_letter_outbox_events (
+    id uuid PRIMARY KEY,
+    tenant_id uuid NOT NULL REFERENCES tenants(id),
+    store_id uuid REFERENCES stores(id),
+    aggregate_type text NOT NULL,
+    aggregate_id text NOT NULL,
+    event_type text NOT NULL,
+    payload_json jsonb NOT NULL,
+    status text NOT NULL,
+    idempotency_key text NOT NULL,
+    created_at timestamptz NOT NULL,
+    published_at timestamptz,
+    retry_count integer NOT NULL DEFAULT 0,
+    next_retry_at timestamptz,
+    failed_at timestamptz NOT NULL,
+    failure_reason text
+);
+
+CREATE INDEX IF NOT EXISTS dead_letter_outbox_events_tenant_idx
+    ON dead_letter_outbox_events (tenant_id, event_type, failed_at DESC);
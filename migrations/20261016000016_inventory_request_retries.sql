@@ -0,0 +1,7 @@
+ALTER TABLE inventory_reservation_requests
+    ADD COLUMN IF NOT EXISTS retry_count integer NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_retry_at timestamptz;
+
+CREATE INDEX IF NOT EXISTS inventory_reservation_requests_failed_idx
+    ON inventory_reservation_requests (updated_at)
+    WHERE status = 'failed';
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS search_reindex_needed (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_id uuid NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS search_reindex_needed_created_idx
+    ON search_reindex_needed (created_at);
+CREATE INDEX IF NOT EXISTS search_reindex_needed_product_idx
+    ON search_reindex_needed (product_id);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS staff_failed_logins (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    staff_id uuid NOT NULL REFERENCES store_staff(id) ON DELETE CASCADE,
+    attempted_at timestamptz NOT NULL DEFAULT now(),
+    ip_address text
+);
+
+CREATE INDEX IF NOT EXISTS staff_failed_logins_staff_idx
+    ON staff_failed_logins (staff_id, attempted_at DESC);
@@ -0,0 +1,27 @@
+INSERT INTO outbox_event_schemas (event_type, version, json_schema_json) VALUES
+(
+    'customer.profile_upsert',
+    2,
+    '{
+        "type": "object",
+        "required": ["tenant_id", "customer_id", "profile"],
+        "properties": {
+            "tenant_id": {"type": "string"},
+            "source_store_id": {"type": ["string", "null"]},
+            "customer_id": {"type": "string"},
+            "force": {"type": "boolean"},
+            "profile": {
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "email": {"type": "string"},
+                    "phone": {"type": "string"},
+                    "status": {"type": "string"},
+                    "notes": {"type": "string"},
+                    "country_code": {"type": "string"}
+                }
+            }
+        }
+    }'::jsonb
+)
+ON CONFLICT (event_type, version) DO NOTHING;
@@ -0,0 +1,32 @@
+-- A customer can hold at most one bid per amount on an auction; this rejects duplicate auto-bids
+-- produced by concurrent auto-bid evaluation.
+-- Existing duplicates keep their earliest bid, and auctions pointing at a removed bid are moved to it.
+CREATE TEMP TABLE auction_bid_duplicates ON COMMIT DROP AS
+SELECT id, keep_id
+FROM (
+    SELECT id,
+           first_value(id) OVER (
+               PARTITION BY auction_id, customer_id, amount
+               ORDER BY created_at, id
+           ) AS keep_id
+    FROM auction_bids
+    WHERE customer_id IS NOT NULL
+) ranked
+WHERE id <> keep_id;
+
+UPDATE auctions a
+SET current_bid_id = d.keep_id
+FROM auction_bid_duplicates d
+WHERE a.current_bid_id = d.id;
+
+UPDATE auctions a
+SET winning_bid_id = d.keep_id
+FROM auction_bid_duplicates d
+WHERE a.winning_bid_id = d.id;
+
+DELETE FROM auction_bids b
+USING auction_bid_duplicates d
+WHERE b.id = d.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS auction_bids_auction_customer_amount_unique
+    ON auction_bids (auction_id, customer_id, amount);
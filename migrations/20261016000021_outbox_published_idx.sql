@@ -0,0 +1,2 @@
+CREATE INDEX IF NOT EXISTS outbox_status_published_idx
+    ON outbox_events (status, published_at);
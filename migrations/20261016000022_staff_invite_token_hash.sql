@@ -0,0 +1,4 @@
+-- Invite tokens are now stored as hex SHA-256 hashes. Hashing the existing plaintext tokens
+-- the same way keeps outstanding invite links valid.
+UPDATE store_staff_invites
+SET token = encode(sha256(convert_to(token, 'UTF8')), 'hex');
@@ -0,0 +1,6 @@
+-- NOT VALID enforces the bounds on every new write without failing on historical rows;
+-- run VALIDATE CONSTRAINT once existing data has been checked.
+ALTER TABLE inventory_stocks
+    ADD CONSTRAINT inventory_stocks_quantities_check
+    CHECK (on_hand >= 0 AND reserved >= 0 AND reserved <= on_hand AND on_hand <= 2000000)
+    NOT VALID;
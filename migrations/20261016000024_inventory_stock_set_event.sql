@@ -0,0 +1,21 @@
+-- Optional per-row reorder point; the inventory worker warns when available stock drops below it.
+ALTER TABLE inventory_stocks
+    ADD COLUMN IF NOT EXISTS reorder_point integer;
+
+INSERT INTO outbox_event_schemas (event_type, version, json_schema_json) VALUES
+(
+    'inventory.stock_set',
+    1,
+    '{
+        "type": "object",
+        "required": ["variant_id", "location_id", "stock", "reserved", "available"],
+        "properties": {
+            "variant_id": {"type": "string"},
+            "location_id": {"type": "string"},
+            "stock": {"type": "integer", "minimum": 0},
+            "reserved": {"type": "integer", "minimum": 0},
+            "available": {"type": "integer"}
+        }
+    }'::jsonb
+)
+ON CONFLICT (event_type, version) DO NOTHING;
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS promotion_codes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    promotion_id uuid NOT NULL REFERENCES promotions(id) ON DELETE CASCADE,
+    store_id uuid NOT NULL REFERENCES stores(id),
+    code text NOT NULL,
+    usage_limit integer,
+    used_count integer NOT NULL DEFAULT 0,
+    expires_at timestamptz,
+    status text NOT NULL DEFAULT 'active',
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    CONSTRAINT promotion_codes_usage_check CHECK (
+        used_count >= 0 AND (usage_limit IS NULL OR (usage_limit > 0 AND used_count <= usage_limit))
+    )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS promotion_codes_store_code_unique
+    ON promotion_codes (store_id, lower(code));
+
+CREATE INDEX IF NOT EXISTS promotion_codes_promotion_idx
+    ON promotion_codes (promotion_id, created_at);
+
+-- One code per cart; used_count is only incremented when the cart checks out.
+CREATE TABLE IF NOT EXISTS cart_promotions (
+    cart_id uuid PRIMARY KEY REFERENCES carts(id) ON DELETE CASCADE,
+    promotion_code_id uuid NOT NULL REFERENCES promotion_codes(id),
+    promotion_id uuid NOT NULL REFERENCES promotions(id),
+    created_at timestamptz NOT NULL DEFAULT now()
+);
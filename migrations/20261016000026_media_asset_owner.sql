@@ -0,0 +1,16 @@
+-- Uploads through /upload/media record what the file belongs to; older assets keep NULL.
+ALTER TABLE store_media_assets
+    ADD COLUMN IF NOT EXISTS owner_type text,
+    ADD COLUMN IF NOT EXISTS owner_id uuid;
+
+ALTER TABLE store_media_assets
+    DROP CONSTRAINT IF EXISTS store_media_assets_owner_check;
+ALTER TABLE store_media_assets
+    ADD CONSTRAINT store_media_assets_owner_check CHECK (
+        (owner_type IS NULL AND owner_id IS NULL)
+        OR (owner_type IN ('product', 'variant', 'store') AND owner_id IS NOT NULL)
+    );
+
+CREATE INDEX IF NOT EXISTS store_media_assets_owner_idx
+    ON store_media_assets (store_id, owner_type, owner_id)
+    WHERE owner_id IS NOT NULL;
@@ -0,0 +1,8 @@
+-- Admin SKU search matches substrings of sku or product title; trigram indexes let ILIKE '%q%' avoid a scan.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX IF NOT EXISTS product_skus_sku_trgm_idx
+    ON product_skus USING gin (sku gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS products_title_trgm_idx
+    ON products USING gin (title gin_trgm_ops);
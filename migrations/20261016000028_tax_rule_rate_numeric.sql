@@ -0,0 +1,3 @@
+-- Tax rates are exact decimals (0.1000 = 10%); the app reads them as rust_decimal::Decimal.
+ALTER TABLE tax_rules
+    ALTER COLUMN rate TYPE numeric(5,4) USING round(rate, 4);
@@ -0,0 +1,6 @@
+-- ListAuditLogs filtered by actor pages by (created_at, id); ListAuditActions reads distinct actions per store.
+CREATE INDEX IF NOT EXISTS audit_logs_store_actor_created_id_idx
+    ON audit_logs (store_id, actor_id, created_at DESC, id DESC);
+
+CREATE INDEX IF NOT EXISTS audit_logs_store_action_idx
+    ON audit_logs (store_id, action);
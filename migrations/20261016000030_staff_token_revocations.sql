@@ -0,0 +1,11 @@
+-- Access tokens issued to a staff member before revoked_at are rejected, whether or not they carry a session id.
+CREATE TABLE IF NOT EXISTS staff_token_revocations (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    staff_id uuid NOT NULL REFERENCES store_staff(id) ON DELETE CASCADE,
+    store_id uuid NOT NULL REFERENCES stores(id),
+    reason text NOT NULL,
+    revoked_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS staff_token_revocations_staff_idx
+    ON staff_token_revocations (staff_id, revoked_at DESC);
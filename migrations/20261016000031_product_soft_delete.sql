@@ -0,0 +1,4 @@
+-- DeleteProduct soft-deletes by default: status = 'deleted' plus deleted_at, so order
+-- history keeps resolving the product's variants.
+ALTER TABLE products
+    ADD COLUMN IF NOT EXISTS deleted_at timestamptz;
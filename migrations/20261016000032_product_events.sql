@@ -0,0 +1,30 @@
+INSERT INTO outbox_event_schemas (event_type, version, json_schema_json) VALUES
+(
+    'product.created',
+    1,
+    '{
+        "type": "object",
+        "required": ["product_id", "store_id", "status", "title"],
+        "properties": {
+            "product_id": {"type": "string"},
+            "store_id": {"type": "string"},
+            "status": {"type": "string"},
+            "title": {"type": "string"}
+        }
+    }'::jsonb
+),
+(
+    'product.updated',
+    1,
+    '{
+        "type": "object",
+        "required": ["product_id", "store_id", "status", "title"],
+        "properties": {
+            "product_id": {"type": "string"},
+            "store_id": {"type": "string"},
+            "status": {"type": "string"},
+            "title": {"type": "string"}
+        }
+    }'::jsonb
+)
+ON CONFLICT (event_type, version) DO NOTHING;
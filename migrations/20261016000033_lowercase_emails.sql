@@ -0,0 +1,33 @@
+-- Email::parse now lowercases addresses, so stored values must match that form for lookups.
+-- Staff and open invites are unique per store; rows whose lowercased email would collide with
+-- another row are left unchanged and need to be merged by hand.
+UPDATE store_staff AS ss
+SET email = lower(ss.email), updated_at = now()
+WHERE ss.email IS NOT NULL
+  AND ss.email <> lower(ss.email)
+  AND NOT EXISTS (
+      SELECT 1 FROM store_staff other
+      WHERE other.store_id = ss.store_id
+        AND other.id <> ss.id
+        AND lower(other.email) = lower(ss.email)
+  );
+
+UPDATE store_staff_invites AS inv
+SET email = lower(inv.email)
+WHERE inv.email <> lower(inv.email)
+  AND NOT EXISTS (
+      SELECT 1 FROM store_staff_invites other
+      WHERE other.store_id = inv.store_id
+        AND other.id <> inv.id
+        AND other.accepted_at IS NULL
+        AND inv.accepted_at IS NULL
+        AND lower(other.email) = lower(inv.email)
+  );
+
+UPDATE customer_profiles
+SET email = lower(email), updated_at = now()
+WHERE email IS NOT NULL AND email <> lower(email);
+
+UPDATE customers
+SET email = lower(email), updated_at = now()
+WHERE email IS NOT NULL AND email <> lower(email);
@@ -0,0 +1,12 @@
+-- Percent promotions keep their percentage in percent_off instead of value_amount.
+ALTER TABLE promotions
+    ADD COLUMN IF NOT EXISTS percent_off integer;
+
+UPDATE promotions
+SET percent_off = value_amount::integer
+WHERE discount_type = 'percent'
+  AND percent_off IS NULL
+  AND value_amount BETWEEN 1 AND 100;
+
+ALTER TABLE promotions
+    ADD CONSTRAINT promotions_percent_off_check CHECK (percent_off IS NULL OR percent_off BETWEEN 1 AND 100);
@@ -0,0 +1,8 @@
+-- Failed reindexes back off instead of being re-queued at once; rows that hit the attempt cap stay for inspection.
+ALTER TABLE search_reindex_needed
+    ADD COLUMN IF NOT EXISTS attempts integer NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS next_attempt_at timestamptz NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS last_error text;
+
+CREATE INDEX IF NOT EXISTS search_reindex_needed_next_attempt_idx
+    ON search_reindex_needed (next_attempt_at, created_at);
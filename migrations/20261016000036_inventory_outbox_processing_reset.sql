@@ -0,0 +1,5 @@
+-- The inventory worker no longer marks claimed events as 'processing'; release rows a crashed worker left behind.
+UPDATE outbox_events
+SET status = 'pending'
+WHERE status = 'processing'
+  AND event_type = 'inventory.stock_set';